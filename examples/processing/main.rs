@@ -0,0 +1,61 @@
+//! Ports of classic Processing examples, run in a window.
+//!
+//! ```text
+//! cargo run --release --example processing -- <name> [--auto]
+//! ```
+//!
+//! Without a name the available ports are listed. `--auto` replaces the mouse with the
+//! scripted path used by the golden render tests.
+
+mod sketches;
+
+use minifb::{MouseMode, Window, WindowOptions};
+use simple_sketch::canvas::Canvas;
+use simple_sketch::geom::Point;
+
+use sketches::{Input, HEIGHT, PORTS, WIDTH};
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let auto = args.iter().any(|arg| arg == "--auto");
+    let Some(name) = args.iter().find(|arg| !arg.starts_with("--")) else {
+        println!("available ports:");
+        for port in PORTS {
+            println!("  {}", port.name);
+        }
+        return Ok(());
+    };
+    let port = PORTS
+        .iter()
+        .find(|port| port.name == name)
+        .ok_or_else(|| format!("unknown port: {name}"))?;
+
+    let mut window = Window::new(port.name, WIDTH, HEIGHT, WindowOptions::default()).map_err(|e| e.to_string())?;
+    window.set_target_fps(60);
+
+    let mut canvas = Canvas::new(WIDTH, HEIGHT);
+    let mut example = (port.create)(&mut canvas);
+    let mut mouse = Point::new(0.0, 0.0);
+    let mut frame = 0;
+
+    while window.is_open() && !window.is_key_down(minifb::Key::Escape) {
+        let input = if auto {
+            sketches::scripted_input(frame)
+        } else {
+            // Processing keeps reporting the last position while the cursor is outside the window
+            if let Some((x, y)) = window.get_mouse_pos(MouseMode::Discard) {
+                mouse = Point::new(x, y);
+            }
+            Input { frame, mouse }
+        };
+
+        example.draw(&mut canvas, &input);
+        canvas.flush();
+        window
+            .update_with_buffer(canvas.pixel_buffer.get_buffer(), WIDTH, HEIGHT)
+            .map_err(|e| e.to_string())?;
+
+        frame += 1;
+    }
+    Ok(())
+}
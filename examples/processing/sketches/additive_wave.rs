@@ -0,0 +1,58 @@
+//! Additive Wave by Daniel Shiffman: several waves of random amplitude and period added together.
+
+use std::f32::consts::TAU;
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input, Random};
+
+const X_SPACING: usize = 8;
+const MAX_WAVES: usize = 4;
+
+struct AdditiveWave {
+    theta: f32,
+    amplitude: [f32; MAX_WAVES],
+    dx: [f32; MAX_WAVES],
+    y_values: Vec<f32>,
+}
+
+pub fn create(canvas: &mut Canvas) -> Box<dyn Example> {
+    let mut random = Random::new(4);
+    let mut amplitude = [0.0; MAX_WAVES];
+    let mut dx = [0.0; MAX_WAVES];
+    for i in 0..MAX_WAVES {
+        amplitude[i] = random.range(10.0, 30.0);
+        let period = random.range(100.0, 300.0);
+        dx[i] = (TAU / period) * X_SPACING as f32;
+    }
+    let w = canvas.width + 16;
+    Box::new(AdditiveWave { theta: 0.0, amplitude, dx, y_values: vec![0.0; w / X_SPACING] })
+}
+
+impl Example for AdditiveWave {
+    fn draw(&mut self, canvas: &mut Canvas, _input: &Input) {
+        canvas.background(gray(0));
+
+        // Calculate the wave
+        self.theta += 0.02;
+        self.y_values.iter_mut().for_each(|y| *y = 0.0);
+        for j in 0..MAX_WAVES {
+            let mut x = self.theta;
+            for y in self.y_values.iter_mut() {
+                // Every other wave is cosine instead of sine
+                *y += if j % 2 == 0 { x.sin() } else { x.cos() } * self.amplitude[j];
+                x += self.dx[j];
+            }
+        }
+
+        // Render it
+        canvas.set_stroke(None);
+        canvas.set_fill(Some(Color::new(255, 255, 255, 50)));
+        for (x, y) in self.y_values.iter().enumerate() {
+            let center = Point::new((x * X_SPACING) as f32, canvas.height as f32 / 2.0 + y);
+            canvas.ellipse(center, 16.0, 16.0);
+        }
+    }
+}
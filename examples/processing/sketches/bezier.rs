@@ -0,0 +1,30 @@
+//! Bezier: a fan of curves whose first end point follows the mouse.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input};
+
+struct Bezier;
+
+pub fn create(_canvas: &mut Canvas) -> Box<dyn Example> {
+    Box::new(Bezier)
+}
+
+impl Example for Bezier {
+    fn draw(&mut self, canvas: &mut Canvas, input: &Input) {
+        canvas.background(gray(0));
+        canvas.set_stroke(Some(gray(255)));
+        canvas.set_fill(None);
+
+        for i in (0..200).step_by(20) {
+            let i = i as f32;
+            canvas.bezier(
+                Point::new(input.mouse.x - i / 2.0, 40.0 + i),
+                Point::new(410.0, 20.0),
+                Point::new(440.0, 300.0),
+                Point::new(240.0 - i / 16.0, 300.0 + i / 8.0),
+            );
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Bouncy Bubbles, based on code from Keith Peters: balls that fall, bounce and push each other apart.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input, Random};
+
+const NUM_BALLS: usize = 12;
+const SPRING: f32 = 0.05;
+const GRAVITY: f32 = 0.03;
+const FRICTION: f32 = -0.9;
+
+struct Ball {
+    position: Point,
+    velocity: Point,
+    diameter: f32,
+}
+
+struct BouncyBubbles {
+    balls: Vec<Ball>,
+}
+
+pub fn create(canvas: &mut Canvas) -> Box<dyn Example> {
+    let mut random = Random::new(12);
+    let balls = (0..NUM_BALLS)
+        .map(|_| Ball {
+            position: Point::new(random.range(0.0, canvas.width as f32), random.range(0.0, canvas.height as f32)),
+            velocity: Point::new(0.0, 0.0),
+            diameter: random.range(30.0, 70.0),
+        })
+        .collect();
+    Box::new(BouncyBubbles { balls })
+}
+
+impl Example for BouncyBubbles {
+    fn draw(&mut self, canvas: &mut Canvas, _input: &Input) {
+        canvas.background(gray(0));
+        canvas.set_stroke(None);
+        canvas.set_fill(Some(Color::new(255, 255, 255, 204)));
+
+        self.collide();
+        let (width, height) = (canvas.width as f32, canvas.height as f32);
+        for ball in &mut self.balls {
+            ball.step(width, height);
+            canvas.ellipse(ball.position, ball.diameter, ball.diameter);
+        }
+    }
+}
+
+impl BouncyBubbles {
+    fn collide(&mut self) {
+        for i in 0..self.balls.len() {
+            for j in i + 1..self.balls.len() {
+                let delta = self.balls[j].position - self.balls[i].position;
+                let distance = delta.length();
+                let min_dist = self.balls[j].diameter / 2.0 + self.balls[i].diameter / 2.0;
+                if distance < min_dist {
+                    let angle = delta.y.atan2(delta.x);
+                    let target = self.balls[i].position + Point::new(angle.cos(), angle.sin()) * min_dist;
+                    let acceleration = (target - self.balls[j].position) * SPRING;
+                    self.balls[i].velocity = self.balls[i].velocity - acceleration;
+                    self.balls[j].velocity = self.balls[j].velocity + acceleration;
+                }
+            }
+        }
+    }
+}
+
+impl Ball {
+    fn step(&mut self, width: f32, height: f32) {
+        self.velocity.y += GRAVITY;
+        self.position = self.position + self.velocity;
+        let radius = self.diameter / 2.0;
+        if self.position.x + radius > width {
+            self.position.x = width - radius;
+            self.velocity.x *= FRICTION;
+        } else if self.position.x - radius < 0.0 {
+            self.position.x = radius;
+            self.velocity.x *= FRICTION;
+        }
+        if self.position.y + radius > height {
+            self.position.y = height - radius;
+            self.velocity.y *= FRICTION;
+        } else if self.position.y - radius < 0.0 {
+            self.position.y = radius;
+            self.velocity.y *= FRICTION;
+        }
+    }
+}
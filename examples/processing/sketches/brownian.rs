@@ -0,0 +1,42 @@
+//! Brownian motion: a random walk drawn as a fading trail of its recent steps.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input, Random};
+
+const NUM: usize = 2000;
+const RANGE: f32 = 6.0;
+
+struct Brownian {
+    trail: Vec<Point>,
+    random: Random,
+}
+
+pub fn create(canvas: &mut Canvas) -> Box<dyn Example> {
+    let center = Point::new(canvas.width as f32 / 2.0, canvas.height as f32 / 2.0);
+    Box::new(Brownian { trail: vec![center; NUM], random: Random::new(2000) })
+}
+
+impl Example for Brownian {
+    fn draw(&mut self, canvas: &mut Canvas, _input: &Input) {
+        canvas.background(gray(51));
+
+        // Shift all elements one place to the left and add a new step at the end
+        self.trail.rotate_left(1);
+        let last = self.trail[NUM - 2];
+        let step = Point::new(self.random.range(-RANGE, RANGE), self.random.range(-RANGE, RANGE));
+        let next = last + step;
+        self.trail[NUM - 1] = Point::new(
+            next.x.clamp(0.0, canvas.width as f32),
+            next.y.clamp(0.0, canvas.height as f32),
+        );
+
+        // Older steps are darker
+        for (j, segment) in self.trail.windows(2).enumerate() {
+            let value = (j + 1) as f32 / NUM as f32 * 204.0 + 51.0;
+            canvas.set_stroke(Some(gray(value as u8)));
+            canvas.line(segment[0], segment[1]);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Clock: an analog clock face. The original reads the system clock; this port advances
+//! one second per frame so that its output is reproducible.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input};
+
+// The time shown on the first frame, in seconds since midnight
+const START_TIME: u32 = 10 * 3600 + 8 * 60;
+
+struct Clock;
+
+pub fn create(_canvas: &mut Canvas) -> Box<dyn Example> {
+    Box::new(Clock)
+}
+
+impl Example for Clock {
+    fn draw(&mut self, canvas: &mut Canvas, input: &Input) {
+        let center = Point::new(canvas.width as f32 / 2.0, canvas.height as f32 / 2.0);
+        let radius = canvas.width.min(canvas.height) as f32 / 2.0;
+        let seconds_radius = radius * 0.72;
+        let minutes_radius = radius * 0.60;
+        let hours_radius = radius * 0.50;
+        let clock_diameter = radius * 1.8;
+
+        canvas.background(gray(0));
+
+        // Draw the clock background
+        canvas.set_fill(Some(gray(80)));
+        canvas.set_stroke(None);
+        canvas.ellipse(center, clock_diameter, clock_diameter);
+
+        // Angles for sin() and cos() start at 3 o'clock; subtract a quarter turn to make them start at the top
+        let time = START_TIME + input.frame;
+        let (h, m, s) = ((time / 3600) % 24, (time / 60) % 60, time % 60);
+        let s_angle = s as f32 / 60.0 * TAU - FRAC_PI_2;
+        let m_angle = (m as f32 + s as f32 / 60.0) / 60.0 * TAU - FRAC_PI_2;
+        let h_angle = (h as f32 + m as f32 / 60.0) / 24.0 * TAU * 2.0 - FRAC_PI_2;
+        let hand = |angle: f32, length: f32| center + Point::new(angle.cos(), angle.sin()) * length;
+
+        // Draw the hands of the clock
+        canvas.set_stroke(Some(gray(255)));
+        canvas.set_stroke_weight(1.0);
+        canvas.line(center, hand(s_angle, seconds_radius));
+        canvas.set_stroke_weight(2.0);
+        canvas.line(center, hand(m_angle, minutes_radius));
+        canvas.set_stroke_weight(4.0);
+        canvas.line(center, hand(h_angle, hours_radius));
+
+        // Draw the minute ticks
+        canvas.set_stroke_weight(2.0);
+        for a in (0..360).step_by(6) {
+            canvas.point(hand((a as f32).to_radians(), seconds_radius));
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! Distance 2D: a grid of dots that grow with their distance from the cursor.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input};
+
+struct Distance2d;
+
+pub fn create(_canvas: &mut Canvas) -> Box<dyn Example> {
+    Box::new(Distance2d)
+}
+
+impl Example for Distance2d {
+    fn draw(&mut self, canvas: &mut Canvas, input: &Input) {
+        canvas.background(gray(0));
+        canvas.set_stroke(None);
+        canvas.set_fill(Some(gray(255)));
+
+        let max_distance = Point::new(canvas.width as f32, canvas.height as f32).length();
+        for i in (0..=canvas.width).step_by(20) {
+            for j in (0..=canvas.height).step_by(20) {
+                let center = Point::new(i as f32, j as f32);
+                let size = input.mouse.distance(&center) / max_distance * 66.0;
+                canvas.ellipse(center, size, size);
+            }
+        }
+    }
+}
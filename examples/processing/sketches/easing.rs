@@ -0,0 +1,27 @@
+//! Easing: a circle that moves a fraction of the remaining distance to the cursor each frame.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input};
+
+const EASING: f32 = 0.05;
+
+struct Easing {
+    position: Point,
+}
+
+pub fn create(_canvas: &mut Canvas) -> Box<dyn Example> {
+    Box::new(Easing { position: Point::new(0.0, 0.0) })
+}
+
+impl Example for Easing {
+    fn draw(&mut self, canvas: &mut Canvas, input: &Input) {
+        canvas.background(gray(51));
+        canvas.set_stroke(None);
+        canvas.set_fill(Some(gray(255)));
+
+        self.position = self.position + (input.mouse - self.position) * EASING;
+        canvas.ellipse(self.position, 66.0, 66.0);
+    }
+}
@@ -0,0 +1,35 @@
+//! Follow 1, based on code from Keith Peters: a line segment pushed and pulled by the cursor.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input};
+
+const SEGMENT_LENGTH: f32 = 50.0;
+
+struct Follow {
+    position: Point,
+}
+
+pub fn create(_canvas: &mut Canvas) -> Box<dyn Example> {
+    Box::new(Follow { position: Point::new(100.0, 100.0) })
+}
+
+impl Example for Follow {
+    fn draw(&mut self, canvas: &mut Canvas, input: &Input) {
+        canvas.background(gray(0));
+        canvas.set_stroke_weight(20.0);
+        canvas.set_stroke(Some(Color::new(255, 255, 255, 160)));
+
+        let delta = input.mouse - self.position;
+        let angle = delta.y.atan2(delta.x);
+        self.position = input.mouse - Point::new(angle.cos(), angle.sin()) * SEGMENT_LENGTH;
+
+        canvas.push();
+        canvas.translate(self.position);
+        canvas.rotate(angle);
+        canvas.line(Point::new(0.0, 0.0), Point::new(SEGMENT_LENGTH, 0.0));
+        canvas.pop();
+    }
+}
@@ -0,0 +1,46 @@
+//! Follow 3, based on code from Keith Peters: a chain of segments dragged along by the cursor.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input};
+
+const SEGMENTS: usize = 20;
+const SEGMENT_LENGTH: f32 = 18.0;
+
+struct FollowChain {
+    joints: [Point; SEGMENTS],
+}
+
+pub fn create(_canvas: &mut Canvas) -> Box<dyn Example> {
+    Box::new(FollowChain { joints: [Point::new(0.0, 0.0); SEGMENTS] })
+}
+
+impl Example for FollowChain {
+    fn draw(&mut self, canvas: &mut Canvas, input: &Input) {
+        canvas.background(gray(0));
+        canvas.set_stroke_weight(9.0);
+        canvas.set_stroke(Some(Color::new(255, 255, 255, 100)));
+
+        self.drag_segment(canvas, 0, input.mouse);
+        for i in 0..SEGMENTS - 1 {
+            let target = self.joints[i];
+            self.drag_segment(canvas, i + 1, target);
+        }
+    }
+}
+
+impl FollowChain {
+    fn drag_segment(&mut self, canvas: &mut Canvas, i: usize, target: Point) {
+        let delta = target - self.joints[i];
+        let angle = delta.y.atan2(delta.x);
+        self.joints[i] = target - Point::new(angle.cos(), angle.sin()) * SEGMENT_LENGTH;
+
+        canvas.push();
+        canvas.translate(self.joints[i]);
+        canvas.rotate(angle);
+        canvas.line(Point::new(0.0, 0.0), Point::new(SEGMENT_LENGTH, 0.0));
+        canvas.pop();
+    }
+}
@@ -0,0 +1,67 @@
+//! Game of Life by Joan Soler-Adillon: Conway's cellular automaton on a wrapping grid.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input, Random};
+
+const CELL_SIZE: usize = 10;
+const PROBABILITY_OF_ALIVE_AT_START: f32 = 0.15;
+// The original steps every 100 ms
+const FRAMES_PER_GENERATION: u32 = 6;
+
+struct GameOfLife {
+    columns: usize,
+    rows: usize,
+    cells: Vec<bool>,
+}
+
+pub fn create(canvas: &mut Canvas) -> Box<dyn Example> {
+    let (columns, rows) = (canvas.width / CELL_SIZE, canvas.height / CELL_SIZE);
+    let mut random = Random::new(15);
+    let cells = (0..columns * rows)
+        .map(|_| random.range(0.0, 1.0) < PROBABILITY_OF_ALIVE_AT_START)
+        .collect();
+    Box::new(GameOfLife { columns, rows, cells })
+}
+
+impl Example for GameOfLife {
+    fn draw(&mut self, canvas: &mut Canvas, input: &Input) {
+        if input.frame > 0 && input.frame.is_multiple_of(FRAMES_PER_GENERATION) {
+            self.iterate();
+        }
+
+        canvas.background(gray(0));
+        canvas.set_stroke(Some(gray(48)));
+        for y in 0..self.rows {
+            for x in 0..self.columns {
+                let alive = self.cells[y * self.columns + x];
+                canvas.set_fill(Some(if alive { Color::new(0, 200, 0, 255) } else { gray(0) }));
+                let top_left = Point::new((x * CELL_SIZE) as f32, (y * CELL_SIZE) as f32);
+                canvas.rectangle(top_left, CELL_SIZE as f32, CELL_SIZE as f32);
+            }
+        }
+    }
+}
+
+impl GameOfLife {
+    fn iterate(&mut self) {
+        let previous = self.cells.clone();
+        for y in 0..self.rows {
+            for x in 0..self.columns {
+                let mut neighbours = 0;
+                for dy in [self.rows - 1, 0, 1] {
+                    for dx in [self.columns - 1, 0, 1] {
+                        if (dx, dy) != (0, 0) {
+                            let (nx, ny) = ((x + dx) % self.columns, (y + dy) % self.rows);
+                            neighbours += previous[ny * self.columns + nx] as u32;
+                        }
+                    }
+                }
+                let cell = &mut self.cells[y * self.columns + x];
+                *cell = matches!((*cell, neighbours), (true, 2) | (_, 3));
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Koch Curve by Daniel Shiffman: a snowflake that gains one level of detail every second.
+
+use std::f32::consts::PI;
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::geom::{Line, Point};
+
+use super::{gray, Example, Input};
+
+const MAX_GENERATIONS: u32 = 5;
+
+struct Koch;
+
+pub fn create(_canvas: &mut Canvas) -> Box<dyn Example> {
+    Box::new(Koch)
+}
+
+impl Example for Koch {
+    fn draw(&mut self, canvas: &mut Canvas, input: &Input) {
+        canvas.background(gray(0));
+        canvas.set_stroke(Some(gray(255)));
+
+        let (width, height) = (canvas.width as f32, canvas.height as f32);
+        // An equilateral triangle centered on the canvas, small enough for the snowflake to fit
+        let size = height * 0.75;
+        let top = height / 2.0 - size * 3f32.sqrt() / 6.0;
+        let a = Point::new(width / 2.0 - size / 2.0, top);
+        let b = Point::new(width / 2.0 + size / 2.0, top);
+        let c = Point::new(width / 2.0, top + size * (PI / 3.0).sin());
+
+        let mut lines = vec![Line::new(a, b), Line::new(b, c), Line::new(c, a)];
+        for _ in 0..(input.frame / 60) % (MAX_GENERATIONS + 1) {
+            lines = lines.iter().flat_map(generate).collect();
+        }
+        for line in &lines {
+            canvas.line(line.start, line.end);
+        }
+    }
+}
+
+// Replaces the middle third of a line with two sides of an equilateral triangle
+fn generate(line: &Line) -> [Line; 4] {
+    let a = line.start;
+    let e = line.end;
+    let third = (e - a) * (1.0 / 3.0);
+    let b = a + third;
+    let d = a + third * 2.0;
+    let (sin, cos) = (-PI / 3.0).sin_cos();
+    let c = b + Point::new(third.x * cos - third.y * sin, third.x * sin + third.y * cos);
+    [Line::new(a, b), Line::new(b, c), Line::new(c, d), Line::new(d, e)]
+}
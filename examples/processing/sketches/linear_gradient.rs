@@ -0,0 +1,54 @@
+//! Linear Gradient: gradients along the x and y axes drawn line by line with interpolated colors.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input};
+
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+}
+
+struct LinearGradient;
+
+pub fn create(_canvas: &mut Canvas) -> Box<dyn Example> {
+    Box::new(LinearGradient)
+}
+
+impl Example for LinearGradient {
+    fn draw(&mut self, canvas: &mut Canvas, _input: &Input) {
+        let (b1, b2) = (gray(255), gray(0));
+        let (c1, c2) = (Color::new(204, 102, 0, 255), Color::new(0, 102, 153, 255));
+        let (width, height) = (canvas.width as f32, canvas.height as f32);
+
+        // Background
+        set_gradient(canvas, 0.0, 0.0, width / 2.0, height, b1, b2, Axis::X);
+        set_gradient(canvas, width / 2.0, 0.0, width / 2.0, height, b2, b1, Axis::X);
+        // Foreground
+        set_gradient(canvas, 50.0, 90.0, 540.0, 80.0, c1, c2, Axis::Y);
+        set_gradient(canvas, 50.0, 190.0, 540.0, 80.0, c2, c1, Axis::X);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn set_gradient(canvas: &mut Canvas, x: f32, y: f32, w: f32, h: f32, c1: Color, c2: Color, axis: Axis) {
+    match axis {
+        Axis::Y => {
+            for i in 0..h as u32 {
+                let inter = i as f32 / h;
+                canvas.set_stroke(Some(c1.lerp(&c2, inter)));
+                canvas.line(Point::new(x, y + i as f32), Point::new(x + w, y + i as f32));
+            }
+        }
+        Axis::X => {
+            for i in 0..w as u32 {
+                let inter = i as f32 / w;
+                canvas.set_stroke(Some(c1.lerp(&c2, inter)));
+                canvas.line(Point::new(x + i as f32, y), Point::new(x + i as f32, y + h));
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! The Mandelbrot Set by Daniel Shiffman: every pixel is shaded by how quickly its point escapes.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+
+use super::{gray, map, Example, Input};
+
+const MAX_ITERATIONS: u32 = 100;
+
+struct Mandelbrot {
+    pixels: Vec<Color>,
+}
+
+pub fn create(canvas: &mut Canvas) -> Box<dyn Example> {
+    let (width, height) = (canvas.width, canvas.height);
+
+    // Show the area from -w/2 to w/2 on the real axis, keeping the aspect ratio
+    let w = 4.0;
+    let h = w * height as f32 / width as f32;
+    let (x_min, y_min) = (-w / 2.0, -h / 2.0);
+    let (dx, dy) = (w / width as f32, h / height as f32);
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for j in 0..height {
+        for i in 0..width {
+            let (x0, y0) = (x_min + i as f32 * dx, y_min + j as f32 * dy);
+            let (mut a, mut b) = (x0, y0);
+            let mut n = 0;
+            while n < MAX_ITERATIONS && a * a + b * b <= 16.0 {
+                (a, b) = (a * a - b * b + x0, 2.0 * a * b + y0);
+                n += 1;
+            }
+
+            // Points that never escape are black, the rest brighten with their escape time
+            let brightness = if n == MAX_ITERATIONS {
+                0.0
+            } else {
+                map((n as f32 / MAX_ITERATIONS as f32).sqrt(), 0.0, 1.0, 0.0, 255.0)
+            };
+            pixels.push(gray(brightness as u8));
+        }
+    }
+    Box::new(Mandelbrot { pixels })
+}
+
+impl Example for Mandelbrot {
+    fn draw(&mut self, canvas: &mut Canvas, _input: &Input) {
+        for (i, color) in self.pixels.iter().enumerate() {
+            let (x, y) = (i % canvas.width, i / canvas.width);
+            canvas.pixel_buffer.set_pixel(x as i32, y as i32, *color);
+        }
+    }
+}
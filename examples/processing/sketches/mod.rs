@@ -0,0 +1,107 @@
+//! Ports of classic examples from the Processing website.
+//!
+//! Each port keeps the structure of the original sketch: the `setup` code moves into the
+//! constructor and `draw` is called once per frame. Helpers that Processing provides as
+//! built-ins but the framework doesn't are defined here.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::geom::Point;
+
+mod additive_wave;
+mod bezier;
+mod bouncy_bubbles;
+mod brownian;
+mod clock;
+mod distance_2d;
+mod easing;
+mod follow;
+mod follow_chain;
+mod game_of_life;
+mod koch;
+mod linear_gradient;
+mod mandelbrot;
+mod radial_gradient;
+mod recursion;
+mod regular_polygon;
+mod sine_wave;
+mod star;
+mod tree;
+mod wolfram_ca;
+
+pub const WIDTH: usize = 640;
+pub const HEIGHT: usize = 360;
+
+/// The per-frame state Processing exposes as globals.
+pub struct Input {
+    pub frame: u32,
+    pub mouse: Point,
+}
+
+pub trait Example {
+    fn draw(&mut self, canvas: &mut Canvas, input: &Input);
+}
+
+pub struct Port {
+    pub name: &'static str,
+    pub create: fn(&mut Canvas) -> Box<dyn Example>,
+}
+
+pub const PORTS: &[Port] = &[
+    Port { name: "additive_wave", create: additive_wave::create },
+    Port { name: "bezier", create: bezier::create },
+    Port { name: "bouncy_bubbles", create: bouncy_bubbles::create },
+    Port { name: "brownian", create: brownian::create },
+    Port { name: "clock", create: clock::create },
+    Port { name: "distance_2d", create: distance_2d::create },
+    Port { name: "easing", create: easing::create },
+    Port { name: "follow", create: follow::create },
+    Port { name: "follow_chain", create: follow_chain::create },
+    Port { name: "game_of_life", create: game_of_life::create },
+    Port { name: "koch", create: koch::create },
+    Port { name: "linear_gradient", create: linear_gradient::create },
+    Port { name: "mandelbrot", create: mandelbrot::create },
+    Port { name: "radial_gradient", create: radial_gradient::create },
+    Port { name: "recursion", create: recursion::create },
+    Port { name: "regular_polygon", create: regular_polygon::create },
+    Port { name: "sine_wave", create: sine_wave::create },
+    Port { name: "star", create: star::create },
+    Port { name: "tree", create: tree::create },
+    Port { name: "wolfram_ca", create: wolfram_ca::create },
+];
+
+/// A mouse that traces a Lissajous figure, for running the ports without a user.
+pub fn scripted_input(frame: u32) -> Input {
+    let t = frame as f32;
+    let mouse = Point::new(
+        WIDTH as f32 * (0.5 + 0.4 * (t * 0.05).sin()),
+        HEIGHT as f32 * (0.5 + 0.4 * (t * 0.07).sin()),
+    );
+    Input { frame, mouse }
+}
+
+pub fn gray(value: u8) -> Color {
+    Color::new(value, value, value, 255)
+}
+
+/// Processing's `map()`: re-maps `value` from one range to another.
+pub fn map(value: f32, start1: f32, stop1: f32, start2: f32, stop2: f32) -> f32 {
+    start2 + (stop2 - start2) * ((value - start1) / (stop1 - start1))
+}
+
+/// Small deterministic generator standing in for Processing's `random()`.
+pub struct Random(u32);
+
+impl Random {
+    pub fn new(seed: u32) -> Self {
+        Random(seed.max(1))
+    }
+
+    /// A random value in `low..high`.
+    pub fn range(&mut self, low: f32, high: f32) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        low + (high - low) * (self.0 as f32 / u32::MAX as f32)
+    }
+}
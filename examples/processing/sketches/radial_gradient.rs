@@ -0,0 +1,39 @@
+//! Radial Gradient: circles of gradually changing hue, drawn largest first.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input, Random};
+
+// The original runs at one frame per second
+const FRAMES_PER_UPDATE: u32 = 60;
+
+struct RadialGradient {
+    random: Random,
+}
+
+pub fn create(_canvas: &mut Canvas) -> Box<dyn Example> {
+    Box::new(RadialGradient { random: Random::new(360) })
+}
+
+impl Example for RadialGradient {
+    fn draw(&mut self, canvas: &mut Canvas, input: &Input) {
+        if !input.frame.is_multiple_of(FRAMES_PER_UPDATE) {
+            return;
+        }
+
+        canvas.background(gray(0));
+        canvas.set_stroke(None);
+        let dim = canvas.width / 2;
+        for x in (0..=canvas.width).step_by(dim) {
+            let center = Point::new(x as f32, canvas.height as f32 / 2.0);
+            let mut hue = self.random.range(0.0, 360.0);
+            for radius in (1..=dim / 2).rev() {
+                canvas.set_fill(Some(Color::hsv_to_rgb(hue, 0.9, 0.9)));
+                canvas.ellipse(center, radius as f32 * 2.0, radius as f32 * 2.0);
+                hue = (hue + 1.0) % 360.0;
+            }
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Recursion: a circle that draws two smaller copies of itself inside.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input};
+
+struct Recursion;
+
+pub fn create(_canvas: &mut Canvas) -> Box<dyn Example> {
+    Box::new(Recursion)
+}
+
+impl Example for Recursion {
+    fn draw(&mut self, canvas: &mut Canvas, _input: &Input) {
+        canvas.background(gray(255));
+        canvas.set_stroke(None);
+        draw_circle(canvas, canvas.width as f32 / 2.0, 280.0, 6);
+    }
+}
+
+fn draw_circle(canvas: &mut Canvas, x: f32, radius: f32, level: u32) {
+    let tt = 126.0 * level as f32 / 4.0;
+    canvas.set_fill(Some(gray(tt as u8)));
+    canvas.ellipse(Point::new(x, canvas.height as f32 / 2.0), radius * 2.0, radius * 2.0);
+    if level > 1 {
+        draw_circle(canvas, x - radius / 2.0, radius / 2.0, level - 1);
+        draw_circle(canvas, x + radius / 2.0, radius / 2.0, level - 1);
+    }
+}
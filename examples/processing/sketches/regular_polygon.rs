@@ -0,0 +1,45 @@
+//! Regular Polygon: a triangle, an icosagon and a heptagon, each spinning at its own speed.
+
+use std::f32::consts::TAU;
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::geom::{Path, Point};
+
+use super::{gray, Example, Input};
+
+struct RegularPolygon;
+
+pub fn create(_canvas: &mut Canvas) -> Box<dyn Example> {
+    Box::new(RegularPolygon)
+}
+
+impl Example for RegularPolygon {
+    fn draw(&mut self, canvas: &mut Canvas, input: &Input) {
+        canvas.background(gray(102));
+        canvas.set_fill(Some(gray(255)));
+        canvas.set_stroke(Some(Color::new(0, 0, 0, 255)));
+
+        let (width, height) = (canvas.width as f32, canvas.height as f32);
+        let frame = input.frame as f32;
+        for (x, speed, radius, sides) in [(0.2, 200.0, 82.0, 3), (0.5, 50.0, 80.0, 20), (0.8, -100.0, 70.0, 7)] {
+            canvas.push();
+            canvas.translate(Point::new(width * x, height * 0.5));
+            canvas.rotate(frame / speed);
+            canvas.draw_path(&polygon(radius, sides));
+            canvas.pop();
+        }
+    }
+}
+
+fn polygon(radius: f32, sides: u32) -> Path {
+    let angle = TAU / sides as f32;
+    let mut path = Path::new();
+    path.move_to(Point::new(radius, 0.0));
+    for i in 1..sides {
+        let a = i as f32 * angle;
+        path.line_to(Point::new(a.cos(), a.sin()) * radius);
+    }
+    path.close();
+    path
+}
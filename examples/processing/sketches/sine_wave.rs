@@ -0,0 +1,43 @@
+//! Sine Wave by Daniel Shiffman: a simple sine wave rendered as a row of dots.
+
+use std::f32::consts::TAU;
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input};
+
+const X_SPACING: usize = 16;
+const AMPLITUDE: f32 = 75.0;
+const PERIOD: f32 = 500.0;
+
+struct SineWave {
+    theta: f32,
+    y_values: Vec<f32>,
+}
+
+pub fn create(canvas: &mut Canvas) -> Box<dyn Example> {
+    let w = canvas.width + 16;
+    Box::new(SineWave { theta: 0.0, y_values: vec![0.0; w / X_SPACING] })
+}
+
+impl Example for SineWave {
+    fn draw(&mut self, canvas: &mut Canvas, _input: &Input) {
+        canvas.background(gray(0));
+
+        self.theta += 0.02;
+        let dx = (TAU / PERIOD) * X_SPACING as f32;
+        let mut x = self.theta;
+        for y in self.y_values.iter_mut() {
+            *y = x.sin() * AMPLITUDE;
+            x += dx;
+        }
+
+        canvas.set_stroke(None);
+        canvas.set_fill(Some(gray(255)));
+        for (x, y) in self.y_values.iter().enumerate() {
+            let center = Point::new((x * X_SPACING) as f32, canvas.height as f32 / 2.0 + y);
+            canvas.ellipse(center, 16.0, 16.0);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Star: three rotating stars with different numbers of points, built as closed paths.
+
+use std::f32::consts::TAU;
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::geom::{Path, Point};
+
+use super::{gray, Example, Input};
+
+struct Star;
+
+pub fn create(_canvas: &mut Canvas) -> Box<dyn Example> {
+    Box::new(Star)
+}
+
+impl Example for Star {
+    fn draw(&mut self, canvas: &mut Canvas, input: &Input) {
+        canvas.background(gray(102));
+        canvas.set_fill(Some(gray(255)));
+        canvas.set_stroke(Some(Color::new(0, 0, 0, 255)));
+
+        let (width, height) = (canvas.width as f32, canvas.height as f32);
+        let frame = input.frame as f32;
+        let stars = [(0.2, 200.0, 5.0, 70.0, 3), (0.5, 400.0, 80.0, 100.0, 40), (0.8, -100.0, 30.0, 70.0, 5)];
+        for (x, speed, radius1, radius2, points) in stars {
+            canvas.push();
+            canvas.translate(Point::new(width * x, height * 0.5));
+            canvas.rotate(frame / speed);
+            canvas.draw_path(&star(radius1, radius2, points));
+            canvas.pop();
+        }
+    }
+}
+
+fn star(radius1: f32, radius2: f32, points: u32) -> Path {
+    let angle = TAU / points as f32;
+    let half_angle = angle / 2.0;
+    let mut path = Path::new();
+    for i in 0..points {
+        let a = i as f32 * angle;
+        let outer = Point::new(a.cos(), a.sin()) * radius2;
+        let inner = Point::new((a + half_angle).cos(), (a + half_angle).sin()) * radius1;
+        if i == 0 {
+            path.move_to(outer);
+        } else {
+            path.line_to(outer);
+        }
+        path.line_to(inner);
+    }
+    path.close();
+    path
+}
@@ -0,0 +1,45 @@
+//! Recursive Tree by Daniel Shiffman: a branching tree whose angle follows the mouse.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::geom::Point;
+
+use super::{gray, map, Example, Input};
+
+struct Tree;
+
+pub fn create(_canvas: &mut Canvas) -> Box<dyn Example> {
+    Box::new(Tree)
+}
+
+impl Example for Tree {
+    fn draw(&mut self, canvas: &mut Canvas, input: &Input) {
+        canvas.background(gray(0));
+        canvas.set_stroke(Some(gray(255)));
+
+        // The angle between branches ranges from 0 to 90 degrees with the mouse position
+        let theta = map(input.mouse.x, 0.0, canvas.width as f32, 0.0, 90.0).to_radians();
+
+        // Start the tree from the bottom of the screen with a 120 pixel trunk
+        canvas.push();
+        canvas.translate(Point::new(canvas.width as f32 / 2.0, canvas.height as f32));
+        canvas.line(Point::new(0.0, 0.0), Point::new(0.0, -120.0));
+        canvas.translate(Point::new(0.0, -120.0));
+        branch(canvas, 120.0, theta);
+        canvas.pop();
+    }
+}
+
+fn branch(canvas: &mut Canvas, h: f32, theta: f32) {
+    // Each branch is two thirds the size of the previous one
+    let h = h * 0.66;
+    if h > 2.0 {
+        for angle in [theta, -theta] {
+            canvas.push();
+            canvas.rotate(angle);
+            canvas.line(Point::new(0.0, 0.0), Point::new(0.0, -h));
+            canvas.translate(Point::new(0.0, -h));
+            branch(canvas, h, theta);
+            canvas.pop();
+        }
+    }
+}
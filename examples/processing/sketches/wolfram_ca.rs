@@ -0,0 +1,71 @@
+//! Wolfram Cellular Automata by Daniel Shiffman: a one-dimensional automaton drawn one
+//! generation per row, restarting with a new rule when it reaches the bottom.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::geom::Point;
+
+use super::{gray, Example, Input, Random};
+
+const CELL_SIZE: usize = 10;
+
+struct WolframCa {
+    cells: Vec<bool>,
+    generation: usize,
+    ruleset: u8,
+    random: Random,
+}
+
+pub fn create(canvas: &mut Canvas) -> Box<dyn Example> {
+    let mut ca = WolframCa {
+        cells: Vec::new(),
+        generation: 0,
+        ruleset: 90,
+        random: Random::new(90),
+    };
+    ca.restart(canvas.width / CELL_SIZE);
+    canvas.background(gray(255));
+    Box::new(ca)
+}
+
+impl Example for WolframCa {
+    fn draw(&mut self, canvas: &mut Canvas, _input: &Input) {
+        canvas.set_stroke(None);
+        for (i, &alive) in self.cells.iter().enumerate() {
+            canvas.set_fill(Some(gray(if alive { 0 } else { 255 })));
+            let top_left = Point::new((i * CELL_SIZE) as f32, (self.generation * CELL_SIZE) as f32);
+            canvas.rectangle(top_left, CELL_SIZE as f32, CELL_SIZE as f32);
+        }
+
+        if self.generation + 1 < canvas.height / CELL_SIZE {
+            self.generate();
+        } else {
+            // Pick a new random rule and start over
+            self.ruleset = self.random.range(0.0, 256.0) as u8;
+            self.restart(self.cells.len());
+            canvas.background(gray(255));
+        }
+    }
+}
+
+impl WolframCa {
+    fn restart(&mut self, count: usize) {
+        self.cells = vec![false; count];
+        self.cells[count / 2] = true;
+        self.generation = 0;
+    }
+
+    fn generate(&mut self) {
+        let n = self.cells.len();
+        let next = (0..n)
+            .map(|i| {
+                // The left, middle and right neighbours form the bit index into the rule
+                let left = self.cells[(i + n - 1) % n] as u8;
+                let middle = self.cells[i] as u8;
+                let right = self.cells[(i + 1) % n] as u8;
+                self.ruleset >> (left << 2 | middle << 1 | right) & 1 == 1
+            })
+            .collect();
+        self.cells = next;
+        self.generation += 1;
+    }
+}
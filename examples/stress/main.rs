@@ -0,0 +1,121 @@
+//! Rasterizer performance harness.
+//!
+//! Renders every stress scene headlessly for a number of frames and writes the frame times
+//! to a JSON file, so results can be compared across commits:
+//!
+//! ```text
+//! cargo run --release --example stress -- [--frames N] [--out results.json] [scene ...]
+//! ```
+
+mod scenes;
+
+use std::fs;
+use std::process::Command;
+use std::time::Instant;
+
+use simple_sketch::canvas::Canvas;
+
+const WIDTH: usize = 1280;
+const HEIGHT: usize = 720;
+
+struct Options {
+    frames: u32,
+    out: String,
+    scenes: Vec<String>,
+}
+
+struct SceneResult {
+    name: &'static str,
+    frame_ms: Vec<f64>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        frames: 10,
+        out: "stress-results.json".to_string(),
+        scenes: Vec::new(),
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--frames" => {
+                let value = args.next().ok_or("--frames needs a value")?;
+                options.frames = value.parse().map_err(|_| format!("invalid frame count: {value}"))?;
+            }
+            "--out" => options.out = args.next().ok_or("--out needs a path")?,
+            name => options.scenes.push(name.to_string()),
+        }
+    }
+    Ok(options)
+}
+
+fn main() -> Result<(), String> {
+    let options = parse_args()?;
+    let mut results = Vec::new();
+
+    for scene in scenes::SCENES {
+        if !options.scenes.is_empty() && !options.scenes.iter().any(|s| s == scene.name) {
+            continue;
+        }
+
+        let mut canvas = Canvas::new(WIDTH, HEIGHT);
+        let mut frame_ms = Vec::with_capacity(options.frames as usize);
+        for frame in 0..options.frames {
+            let start = Instant::now();
+            (scene.draw)(&mut canvas, frame);
+            canvas.flush();
+            frame_ms.push(start.elapsed().as_secs_f64() * 1000.0);
+        }
+
+        let result = SceneResult { name: scene.name, frame_ms };
+        let (mean, min, max) = stats(&result.frame_ms);
+        println!("{:<20} mean {:>8.2} ms   min {:>8.2} ms   max {:>8.2} ms", result.name, mean, min, max);
+        results.push(result);
+    }
+
+    fs::write(&options.out, to_json(&results)).map_err(|e| e.to_string())?;
+    println!("wrote {}", options.out);
+    Ok(())
+}
+
+fn stats(frame_ms: &[f64]) -> (f64, f64, f64) {
+    let mean = frame_ms.iter().sum::<f64>() / frame_ms.len().max(1) as f64;
+    let min = frame_ms.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = frame_ms.iter().copied().fold(0.0, f64::max);
+    (mean, min, max)
+}
+
+// The current commit, if run from a git checkout, so results can be matched to code
+fn git_commit() -> Option<String> {
+    let output = Command::new("git").args(["rev-parse", "HEAD"]).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn to_json(results: &[SceneResult]) -> String {
+    let commit = git_commit().map_or("null".to_string(), |c| format!("\"{c}\""));
+    let scenes: Vec<String> = results
+        .iter()
+        .map(|result| {
+            let (mean, min, max) = stats(&result.frame_ms);
+            let frames: Vec<String> = result.frame_ms.iter().map(|ms| format!("{ms:.4}")).collect();
+            format!(
+                "    {{\"name\": \"{}\", \"frames\": {}, \"mean_ms\": {:.4}, \"min_ms\": {:.4}, \"max_ms\": {:.4}, \"frame_ms\": [{}]}}",
+                result.name,
+                result.frame_ms.len(),
+                mean,
+                min,
+                max,
+                frames.join(", ")
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\n  \"commit\": {},\n  \"width\": {},\n  \"height\": {},\n  \"scenes\": [\n{}\n  ]\n}}\n",
+        commit,
+        WIDTH,
+        HEIGHT,
+        scenes.join(",\n")
+    )
+}
@@ -0,0 +1,81 @@
+//! Stress scenes exercising the rasterizer with large amounts of geometry.
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::geom::{Path, Point};
+
+pub struct Scene {
+    pub name: &'static str,
+    pub draw: fn(&mut Canvas, u32),
+}
+
+pub const SCENES: &[Scene] = &[
+    Scene { name: "lines_10k", draw: lines_10k },
+    Scene { name: "circles_5k", draw: circles_5k },
+    Scene { name: "fullscreen_polygon", draw: fullscreen_polygon },
+];
+
+// Small deterministic generator so every run draws exactly the same geometry
+struct Random(u32);
+
+impl Random {
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32
+    }
+
+    fn point(&mut self, canvas: &Canvas) -> Point {
+        Point::new(self.next() * canvas.width as f32, self.next() * canvas.height as f32)
+    }
+}
+
+fn lines_10k(canvas: &mut Canvas, frame: u32) {
+    let mut random = Random(0x2545_F491 ^ frame);
+    canvas.background(Color::new(0, 0, 0, 255));
+    for i in 0..10_000 {
+        canvas.set_stroke(Some(Color::hsv_to_rgb((i % 360) as f32, 1.0, 1.0)));
+        let start = random.point(canvas);
+        let end = random.point(canvas);
+        canvas.line(start, end);
+    }
+}
+
+fn circles_5k(canvas: &mut Canvas, frame: u32) {
+    let mut random = Random(0x9E37_79B9 ^ frame);
+    canvas.background(Color::new(0, 0, 0, 255));
+    canvas.set_stroke_weight(1.0);
+    for i in 0..5_000 {
+        let color = Color::hsv_to_rgb((i % 360) as f32, 0.8, 1.0);
+        canvas.set_fill(Some(color.with_alpha(128)));
+        canvas.set_stroke(Some(color));
+        let center = random.point(canvas);
+        let diameter = 4.0 + random.next() * 16.0;
+        canvas.ellipse(center, diameter, diameter);
+    }
+}
+
+fn fullscreen_polygon(canvas: &mut Canvas, frame: u32) {
+    let center = Point::new(canvas.width as f32 / 2.0, canvas.height as f32 / 2.0);
+    let radius = canvas.width.max(canvas.height) as f32 / 2.0;
+    let points = 64;
+
+    let mut path = Path::new();
+    for i in 0..points {
+        let angle = (i as f32 / points as f32 + frame as f32 * 0.001) * std::f32::consts::TAU;
+        let r = if i % 2 == 0 { radius } else { radius * 0.8 };
+        let vertex = center + Point::new(r * angle.cos(), r * angle.sin());
+        if i == 0 {
+            path.move_to(vertex);
+        } else {
+            path.line_to(vertex);
+        }
+    }
+    path.close();
+
+    canvas.background(Color::new(0, 0, 0, 255));
+    canvas.set_fill(Some(Color::new(40, 120, 220, 255)));
+    canvas.set_stroke(Some(Color::new(255, 255, 255, 255)));
+    canvas.draw_path(&path);
+}
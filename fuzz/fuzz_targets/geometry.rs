@@ -0,0 +1,98 @@
+//! Feeds arbitrary, possibly degenerate, geometry into the `geom` kernels.
+//!
+//! ```text
+//! cargo +nightly fuzz run geometry -- -timeout=5
+//! ```
+//!
+//! Besides checking for panics and timeouts, a few invariants are asserted for inputs within
+//! `MAX_COORDINATE`; beyond that f32 precision makes them meaningless.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use simple_sketch::geom::{CatmullRom, CubicBezier, Line, Path, Point, Polygon, QuadBezier, Rect, Transform};
+
+const MAX_VERTICES: usize = 16;
+const TOLERANCE: f32 = 0.25;
+const MAX_COORDINATE: f32 = 1e6;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    polygon_a: Vec<(f32, f32)>,
+    polygon_b: Vec<(f32, f32)>,
+    lines: [(f32, f32, f32, f32); 2],
+    curve: [(f32, f32); 4],
+    probe: (f32, f32),
+    t: f32,
+    tension: f32,
+}
+
+fn polygon(points: &[(f32, f32)]) -> Polygon {
+    Polygon::new(points.iter().take(MAX_VERTICES).map(|&(x, y)| Point::new(x, y)).collect())
+}
+
+fn in_range(points: &[Point]) -> bool {
+    points.iter().all(|p| p.x.abs() <= MAX_COORDINATE && p.y.abs() <= MAX_COORDINATE)
+}
+
+// Containment with some slack for rounding in the bounds computation
+fn roughly_contains(rect: &Rect, point: &Point) -> bool {
+    let slack = 1e-3 * (1.0 + rect.width().abs().max(rect.height().abs()));
+    point.x >= rect.min.x - slack
+        && point.x <= rect.max.x + slack
+        && point.y >= rect.min.y - slack
+        && point.y <= rect.max.y + slack
+}
+
+fuzz_target!(|input: Input| {
+    let probe = Point::new(input.probe.0, input.probe.1);
+
+    // Polygons and boolean operations
+    let a = polygon(&input.polygon_a);
+    let b = polygon(&input.polygon_b);
+    let _ = (a.signed_area(), a.perimeter(), a.centroid(), a.winding(), a.is_convex());
+    let _ = (a.contains_point(&probe), a.distance_to_boundary(&probe), a.bounding_box());
+    for result in [a.union(&b), a.intersection(&b), a.difference(&b), a.xor(&b)] {
+        for polygon in &result {
+            assert!(polygon.vertices.len() >= 3);
+        }
+    }
+
+    // Lines
+    let [l1, l2] = input.lines.map(|(x1, y1, x2, y2)| Line::new(Point::new(x1, y1), Point::new(x2, y2)));
+    let _ = (l1.length(), l1.midpoint(), l1.slope(), l1.intersects(&l2), l1.intersection_point(&l2));
+    let _ = l1.closest_point(&probe);
+
+    // Curves
+    let [p0, p1, p2, p3] = input.curve.map(|(x, y)| Point::new(x, y));
+    let cubic = CubicBezier::new(p0, p1, p2, p3);
+    let quad = QuadBezier::new(p0, p1, p3);
+    let points = cubic.flatten(TOLERANCE);
+    assert!(points.len() >= 2);
+    let _ = (quad.flatten(TOLERANCE), quad.length(), quad.bounding_box(), quad.split(input.t));
+    let _ = (cubic.split(input.t), cubic.tangent_at(input.t), cubic.point_at_length(input.t));
+    let length = cubic.length();
+    let t = cubic.t_at_length(input.t.abs() * length);
+    if in_range(&[p0, p1, p2, p3]) && input.t.is_finite() {
+        assert!((0.0..=1.0).contains(&t));
+        let bounds = cubic.bounding_box();
+        for point in &points {
+            assert!(roughly_contains(&bounds, point));
+        }
+    }
+
+    let spline = CatmullRom::new(input.polygon_a.iter().take(MAX_VERTICES).map(|&(x, y)| Point::new(x, y)).collect(), input.tension);
+    let _ = (spline.point_at(input.t), spline.to_polyline(TOLERANCE));
+
+    // Paths and transforms
+    let mut path = Path::new();
+    path.move_to(p0).cubic_to(p1, p2, p3).quad_to(p1, p0).line_to(probe).close();
+    let _ = (path.length(), path.point_at_length(input.t), path.bounding_box(), path.flatten(TOLERANCE));
+    let transform = Transform::rotate(input.t).then(&Transform::scale(input.tension, input.t));
+    if let Some(inverse) = transform.inverse() {
+        let _ = inverse.transform_point(probe);
+    }
+    let _ = path.transform(&transform);
+});
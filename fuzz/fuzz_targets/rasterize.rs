@@ -0,0 +1,133 @@
+//! Feeds arbitrary drawing calls, including NaN, infinite, zero-sized and huge
+//! coordinates, into a small canvas.
+//!
+//! ```text
+//! cargo +nightly fuzz run rasterize -- -timeout=5
+//! ```
+//!
+//! Any panic, a pixel buffer that changes size, or a call that exceeds the timeout is a bug.
+//! The inputs of bugs found so far are kept in `fuzz/regressions/rasterize`, named after
+//! what they draw, and run once each with
+//!
+//! ```text
+//! cargo +nightly fuzz run rasterize fuzz/regressions/rasterize -- -runs=0 -timeout=5
+//! ```
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::geom::{Path, Point, Transform};
+use simple_sketch::instance::Instance;
+use simple_sketch::shape::{ArcMode, Ellipse};
+
+const MAX_OPS: usize = 32;
+
+#[derive(Arbitrary, Debug)]
+enum PathOp {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    QuadTo(f32, f32, f32, f32),
+    CubicTo(f32, f32, f32, f32, f32, f32),
+    Close,
+}
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Background(u32),
+    Fill(Option<u32>),
+    Stroke(Option<u32>),
+    StrokeWeight(f32),
+    Line(f32, f32, f32, f32),
+    Ellipse(f32, f32, f32, f32),
+    Circle(f32, f32, f32),
+    Triangle([f32; 6]),
+    Star(f32, f32, u8, f32, f32, f32),
+    RegularPolygon(f32, f32, u8, f32, f32),
+    Arc(f32, f32, f32, f32, f32, u8),
+    Capsule([f32; 4], f32),
+    Rectangle(f32, f32, f32, f32),
+    RoundedRect(f32, f32, f32, f32, [f32; 4]),
+    Bezier([f32; 8]),
+    Path(Vec<PathOp>),
+    Depth(f32, f32, f32, f32, f32),
+    Instanced(f32, f32, Vec<[f32; 6]>),
+    FixedPoint(bool),
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    width: u8,
+    height: u8,
+    ops: Vec<Op>,
+}
+
+fn point(x: f32, y: f32) -> Point {
+    Point::new(x, y)
+}
+
+fn run(canvas: &mut Canvas, op: &Op) {
+    match op {
+        Op::Background(color) => canvas.background(Color(*color)),
+        Op::Fill(color) => canvas.set_fill(color.map(Color)),
+        Op::Stroke(color) => canvas.set_stroke(color.map(Color)),
+        Op::StrokeWeight(weight) => canvas.set_stroke_weight(*weight),
+        Op::Line(x1, y1, x2, y2) => canvas.line(point(*x1, *y1), point(*x2, *y2)),
+        Op::Ellipse(x, y, w, h) => canvas.ellipse(point(*x, *y), *w, *h),
+        Op::Circle(x, y, d) => canvas.circle(point(*x, *y), *d),
+        Op::Arc(x, y, d, start, end, mode) => {
+            let mode = [ArcMode::Open, ArcMode::Chord, ArcMode::Pie][*mode as usize % 3];
+            canvas.arc(point(*x, *y), *d, *start, *end, mode);
+        }
+        Op::Star(x, y, points, inner, outer, rotation) => canvas.star(point(*x, *y), *points as usize, *inner, *outer, *rotation),
+        Op::RegularPolygon(x, y, sides, radius, rotation) => canvas.regular_polygon(point(*x, *y), *sides as usize, *radius, *rotation),
+        Op::Triangle(c) => canvas.triangle(point(c[0], c[1]), point(c[2], c[3]), point(c[4], c[5])),
+        Op::Capsule(c, radius) => canvas.capsule(point(c[0], c[1]), point(c[2], c[3]), *radius),
+        Op::Rectangle(x, y, w, h) => canvas.rectangle(point(*x, *y), *w, *h),
+        Op::RoundedRect(x, y, w, h, radii) => canvas.rounded_rect(point(*x, *y), *w, *h, *radii),
+        Op::Bezier(c) => canvas.bezier(point(c[0], c[1]), point(c[2], c[3]), point(c[4], c[5]), point(c[6], c[7])),
+        Op::Path(ops) => {
+            let mut path = Path::new();
+            for op in ops.iter().take(MAX_OPS) {
+                match *op {
+                    PathOp::MoveTo(x, y) => path.move_to(point(x, y)),
+                    PathOp::LineTo(x, y) => path.line_to(point(x, y)),
+                    PathOp::QuadTo(cx, cy, x, y) => path.quad_to(point(cx, cy), point(x, y)),
+                    PathOp::CubicTo(c1x, c1y, c2x, c2y, x, y) => path.cubic_to(point(c1x, c1y), point(c2x, c2y), point(x, y)),
+                    PathOp::Close => path.close(),
+                };
+            }
+            canvas.draw_path(&path);
+        }
+        Op::Depth(depth, x, y, w, h) => canvas.with_depth(*depth, |c| c.ellipse(point(*x, *y), *w, *h)),
+        Op::Instanced(w, h, transforms) => {
+            let shape = Ellipse { center: point(0.0, 0.0), width: *w, height: *h };
+            let instances: Vec<Instance> = transforms
+                .iter()
+                .take(MAX_OPS)
+                .map(|m| Instance {
+                    transform: Transform { m11: m[0], m12: m[1], m21: m[2], m22: m[3], tx: m[4], ty: m[5] },
+                    color: Color::new(255, 255, 255, 255),
+                })
+                .collect();
+            canvas.draw_instanced(&shape, &instances);
+        }
+        Op::FixedPoint(enabled) => canvas.set_fixed_point(*enabled),
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let width = input.width as usize + 1;
+    let height = input.height as usize + 1;
+    let mut canvas = Canvas::new(width, height);
+
+    for op in input.ops.iter().take(MAX_OPS) {
+        run(&mut canvas, op);
+    }
+    canvas.flush();
+
+    assert_eq!(canvas.pixel_buffer.get_buffer().len(), width * height);
+});
@@ -0,0 +1,1350 @@
+//! Running sketches in a window.
+//!
+//! A sketch implements `Application`, with `draw` called once per frame, optional callbacks
+//! for input and `Settings` for its window, and is handed to an `ApplicationRunner`, which
+//! owns the window and the canvas:
+//!
+//! ```no_run
+//! # use simple_sketch::app::{Application, ApplicationRunner, Context, Settings};
+//! # use simple_sketch::canvas::Canvas;
+//! # fn main() -> Result<(), simple_sketch::error::SketchError> {
+//! struct Dots;
+//!
+//! impl Application for Dots {
+//!     fn settings(&self) -> Settings {
+//!         Settings::new().size(800, 600).title("Dots")
+//!     }
+//!
+//!     fn draw(&mut self, canvas: &mut Canvas, ctx: &Context) {
+//!         canvas.circle(ctx.mouse().position(), 10.0);
+//!     }
+//! }
+//!
+//! ApplicationRunner::new(Dots)?.run()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Sketches that implement `Default` can also be started with `run::<Dots>()`. Sketches with
+//! expensive simulations can use `run_threaded` instead of `run` to keep the window responsive.
+//!
+//! Without a display, `ApplicationRunner::headless` renders frames without opening a window,
+//! for example to save a batch of outputs from `run_frames`.
+
+use std::cell::{Cell, RefCell, RefMut};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "audio")]
+use crate::audio::{AudioClip, AudioFrame, AudioInput, AudioPlayback};
+use crate::backend::{Backend, FrameRegion, InputState};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::backend::MinifbBackend;
+use crate::canvas::Canvas;
+use crate::clipboard;
+use crate::color::Color;
+use crate::cursor::{CursorStyle, SoftwareCursor};
+use crate::error::SketchError;
+use crate::frame_history::{FrameHistory, HistoricFrame};
+use crate::geom::Point;
+use crate::history::History;
+use crate::input::{DragEvent, Key, KeyEvent, KeyInput, Keyboard, Mouse, MouseEvent, MouseInput, WheelEvent};
+use crate::pixelbuffer::PixelBuffer;
+use crate::png::PngColor;
+use crate::profiler::{FrameProfiler, Stage};
+use crate::recorder::{FrameRecorder, VideoRecorder};
+use crate::terminal::TerminalBackend;
+use crate::tiled::TiledRenderer;
+use crate::tween::Tweens;
+
+/// Most `Application::update` calls made in one frame in `UpdateMode::Fixed`. When a frame
+/// takes longer than this many steps, the simulation slows down instead of falling further behind.
+const MAX_UPDATES_PER_FRAME: u32 = 8;
+
+/// The slowest speed the Down arrow slows a sketch down to.
+const MIN_TIME_SCALE: f32 = 1.0 / 16.0;
+
+/// How far in seconds a playing soundtrack can get ahead of or behind the sketch's clock
+/// before it is restarted at the clock's position.
+#[cfg(feature = "audio")]
+const MAX_SOUNDTRACK_DRIFT: f32 = 0.1;
+
+/// How often an `ApplicationRunner` calls `Application::update`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpdateMode {
+    /// Once per frame, with the measured frame time.
+    PerFrame,
+    /// At a fixed number of updates per second, each with a `dt` of one step, independent of
+    /// the frame rate. A frame runs as many updates as are due, possibly none, and
+    /// `Context::interpolation` tells `draw` how far it is between the last two updates.
+    Fixed(f32),
+}
+
+/// The window and canvas configuration of a sketch, built by chaining setters on
+/// `Settings::new()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) title: String,
+    pub(crate) frame_rate: f32,
+    pub(crate) resizable: bool,
+    pub(crate) vsync: bool,
+    pub(crate) position: Option<(isize, isize)>,
+    pub(crate) pixel_density: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            width: 640,
+            height: 360,
+            title: "Simple Sketch".to_string(),
+            frame_rate: 60.0,
+            resizable: true,
+            vsync: false,
+            position: None,
+            pixel_density: 1.0,
+        }
+    }
+}
+
+impl Settings {
+    /// A 640 by 360 resizable window titled "Simple Sketch", running at 60 frames per second.
+    pub fn new() -> Self {
+        Settings::default()
+    }
+
+    /// Sets the size of the canvas, and of the window showing it, in pixels.
+    pub fn size(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Sets the number of frames per second the runner aims for. Headless runners advance
+    /// their clock by one over this per frame.
+    pub fn frame_rate(mut self, frames_per_second: f32) -> Self {
+        self.frame_rate = frames_per_second;
+        self
+    }
+
+    /// Sets whether the user can resize the window. The canvas follows the window's size.
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Asks for frames to be synchronized with the display's refresh. minifb windows can't do
+    /// that, so they keep limiting themselves to the frame rate.
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+
+    /// Sets the position of the window's top left corner on the screen, in pixels. By default
+    /// the system places the window.
+    pub fn position(mut self, x: isize, y: isize) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    /// Sets the number of canvas pixels per window pixel along each axis, like Processing's
+    /// `pixelDensity()`. The size, drawing and mouse coordinates stay in window pixels, while the
+    /// canvas renders at the higher resolution, so `pixel_density(display_density())` gives
+    /// sharp output on high-DPI displays. Defaults to 1.
+    pub fn pixel_density(mut self, pixel_density: f32) -> Self {
+        self.pixel_density = pixel_density;
+        self
+    }
+}
+
+/// The scale factor of the display, as the number of physical pixels per logical pixel, for
+/// `Settings::pixel_density`. minifb can't query the monitor, so this reads the scale factor
+/// that desktop environments pass to applications in `GDK_SCALE`, `QT_SCALE_FACTOR` or
+/// `SIMPLE_SKETCH_DENSITY`, which overrides the others. Retina displays on macOS report 2.
+/// Falls back to 1.
+pub fn display_density() -> f32 {
+    let from_env = ["SIMPLE_SKETCH_DENSITY", "GDK_SCALE", "QT_SCALE_FACTOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok()?.trim().parse::<f32>().ok())
+        .find(|density| density.is_finite() && *density > 0.0);
+    match from_env {
+        Some(density) => density,
+        None if cfg!(target_os = "macos") => 2.0,
+        None => 1.0,
+    }
+}
+
+/// A sketch run by an `ApplicationRunner`. Only `draw` is required.
+#[allow(unused_variables)]
+pub trait Application {
+    /// The window and canvas configuration, read once when the runner is created.
+    fn settings(&self) -> Settings {
+        Settings::default()
+    }
+
+    /// Called once before the first frame.
+    fn setup(&mut self, canvas: &mut Canvas) {}
+
+    /// Called every frame before `draw`, after the input callbacks. `dt` is the time since
+    /// the previous frame in seconds, or 0 in the first frame, so that moving things by their
+    /// speed times `dt` animates them at the same speed whatever the frame rate.
+    /// See `UpdateMode` for calling it at a fixed rate instead.
+    fn update(&mut self, ctx: &Context, dt: f32) {}
+
+    /// Draws a frame. The canvas keeps its contents between frames, so sketches usually start
+    /// by drawing a background.
+    fn draw(&mut self, canvas: &mut Canvas, ctx: &Context);
+
+    /// Called when a mouse button is pressed.
+    fn mouse_pressed(&mut self, event: &MouseEvent) {}
+
+    /// Called when a mouse button is released.
+    fn mouse_released(&mut self, event: &MouseEvent) {}
+
+    /// Called after `mouse_released` if the mouse didn't move while the button was down.
+    fn mouse_clicked(&mut self, event: &MouseEvent) {}
+
+    /// Called when the mouse moves with no button held down.
+    fn mouse_moved(&mut self, event: &MouseEvent) {}
+
+    /// Called when the mouse moves with a button held down.
+    fn mouse_dragged(&mut self, event: &DragEvent) {}
+
+    /// Called when the mouse wheel turns or the touchpad scrolls.
+    fn mouse_wheel(&mut self, event: &WheelEvent) {}
+
+    /// Called when a key is pressed. Holding the key down doesn't repeat the call.
+    fn key_pressed(&mut self, event: &KeyEvent) {}
+
+    /// Called when a key is released.
+    fn key_released(&mut self, event: &KeyEvent) {}
+
+    /// Called with the path of each file dropped onto the sketch, before the next `update`.
+    /// Files come from backends that support drag and drop, and from
+    /// `ApplicationRunner::drop_file`, for example for paths given on the command line, since
+    /// the default minifb backend can't receive drops.
+    fn on_file_dropped(&mut self, path: &Path) {}
+
+    /// Called when the window was resized, after the canvas was resized to the new size in
+    /// window pixels and before the next `update`.
+    fn on_resize(&mut self, width: usize, height: usize) {}
+
+    /// Called once when the sketch stops: when its window is closed or Escape is pressed, when
+    /// the runner fails, or when a headless runner is finished. This is the place to flush
+    /// recordings, save state and close devices.
+    fn exit(&mut self) {}
+}
+
+/// The state of the running sketch, passed to `Application::update` and `Application::draw`.
+#[derive(Debug, Clone)]
+pub struct Context {
+    mouse: Mouse,
+    keyboard: Keyboard,
+    frame_count: u64,
+    elapsed: Duration,
+    interpolation: f32,
+    paused: bool,
+    time_scale: f32,
+    /// Files to save the current frame to once it's drawn.
+    saves: RefCell<Vec<PathBuf>>,
+    /// Whether to copy the current frame to the clipboard once it's drawn.
+    copy_to_clipboard: Cell<bool>,
+    /// Whether drawn frames are written to the frame recorder.
+    recording: Cell<bool>,
+    cursor_hidden: Cell<bool>,
+    cursor_style: Cell<CursorStyle>,
+    software_cursor: RefCell<Option<SoftwareCursor>>,
+    tweens: RefCell<Tweens>,
+    history: RefCell<History>,
+    /// The stage timings of the last frames, or None when the sketch isn't profiled.
+    profiler: Option<FrameProfiler>,
+    #[cfg(feature = "audio")]
+    audio: AudioFrame,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Context {
+            mouse: Mouse::default(),
+            keyboard: Keyboard::default(),
+            frame_count: 0,
+            elapsed: Duration::ZERO,
+            interpolation: 0.0,
+            paused: false,
+            time_scale: 1.0,
+            saves: RefCell::default(),
+            copy_to_clipboard: Cell::default(),
+            recording: Cell::default(),
+            cursor_hidden: Cell::default(),
+            cursor_style: Cell::default(),
+            software_cursor: RefCell::default(),
+            tweens: RefCell::default(),
+            history: RefCell::default(),
+            profiler: None,
+            #[cfg(feature = "audio")]
+            audio: AudioFrame::default(),
+        }
+    }
+}
+
+impl Context {
+    /// The number of frames drawn before the current one, so 0 in the first frame.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// The time since the sketch started running, in whole milliseconds, like Processing's `millis()`.
+    /// Like `seconds_since_start`, this is the sketch's own clock.
+    pub fn millis(&self) -> u64 {
+        self.elapsed.as_millis() as u64
+    }
+
+    /// The time since the sketch started running, in seconds. It is sampled once at the start
+    /// of each frame, so it stays the same during `update` and `draw`. This is the sketch's own
+    /// clock, which stands still while the sketch is paused and runs slower in slow motion.
+    pub fn seconds_since_start(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    /// Whether the sketch is paused. A paused sketch gets input callbacks but no `update` or
+    /// `draw` calls, except for single steps, during which this stays true.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// How fast the sketch's clock runs compared to real time, 1 at normal speed.
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// How far the current frame is from the last update towards the next one, from 0 to 1.
+    /// In `UpdateMode::Fixed`, drawing objects at their previous state interpolated towards
+    /// their current state by this factor keeps motion smooth when the update rate and the
+    /// frame rate differ. Always 1 in `UpdateMode::PerFrame`.
+    pub fn interpolation(&self) -> f32 {
+        self.interpolation
+    }
+
+    /// Saves the current frame as a PNG file once it's drawn, like Processing's `saveFrame()`.
+    /// A run of `#` characters in `pattern` is replaced by the frame count, padded with zeros
+    /// to the same number of digits, so `"out/frame-####.png"` saves the first frame as
+    /// `out/frame-0000.png`. Missing directories are created.
+    pub fn save_frame(&self, pattern: &str) {
+        self.saves.borrow_mut().push(frame_path(pattern, self.frame_count));
+    }
+
+    /// Starts writing every drawn frame, from the current one on, to the runner's frame
+    /// recorder. See `ApplicationRunner::set_recording_directory`.
+    pub fn start_recording(&self) {
+        self.recording.set(true);
+    }
+
+    /// Stops writing frames to the frame recorder, from the current one on.
+    pub fn stop_recording(&self) {
+        self.recording.set(false);
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.get()
+    }
+
+    /// Copies the current frame to the clipboard as an image once it's drawn.
+    pub fn copy_canvas_to_clipboard(&self) {
+        self.copy_to_clipboard.set(true);
+    }
+
+    /// Reads the text on the system clipboard, for example a seed pasted into the sketch.
+    pub fn clipboard_text(&self) -> Result<String, SketchError> {
+        clipboard::text()
+    }
+
+    /// Puts text on the system clipboard.
+    pub fn set_clipboard_text(&self, text: &str) -> Result<(), SketchError> {
+        clipboard::set_text(text)
+    }
+
+    /// Hides the system cursor while it is over the window, for example for fullscreen pieces.
+    pub fn hide_cursor(&self) {
+        self.cursor_hidden.set(true);
+    }
+
+    /// Shows the system cursor again after `hide_cursor`.
+    pub fn show_cursor(&self) {
+        self.cursor_hidden.set(false);
+    }
+
+    /// Sets which of the system's standard cursors is shown over the window.
+    pub fn set_cursor(&self, style: CursorStyle) {
+        self.cursor_style.set(style);
+    }
+
+    /// Replaces the system cursor with a cursor drawn on top of every frame, or goes back to
+    /// the system cursor with None. The software cursor isn't part of the canvas, so it doesn't
+    /// show up in saved frames and doesn't leave trails on sketches without a background.
+    pub fn set_software_cursor(&self, cursor: Option<SoftwareCursor>) {
+        *self.software_cursor.borrow_mut() = cursor;
+    }
+
+    // Whether the system cursor is shown, and its style
+    fn window_cursor(&self) -> (bool, CursorStyle) {
+        (!self.cursor_hidden.get() && self.software_cursor.borrow().is_none(), self.cursor_style.get())
+    }
+
+    /// The sketch's named tweens, which the runner moves forward by the sketch's clock before
+    /// every update. Don't hold on to them while calling this again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::app::Context;
+    /// # use simple_sketch::easing::Easing;
+    /// # use simple_sketch::tween::Tween;
+    /// # struct Sketch;
+    /// # impl Sketch {
+    /// fn update(&mut self, ctx: &Context, _dt: f32) {
+    ///     if ctx.frame_count() == 0 {
+    ///         ctx.tweens().start("fade", Tween::between(0.0, 255.0, 2.0, Easing::SineInOut).delay(1.0));
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn tweens(&self) -> RefMut<'_, Tweens> {
+        self.tweens.borrow_mut()
+    }
+
+    /// The sketch's drawing history, for sketches that let their user draw and undo. See
+    /// `History`. Don't hold on to it while calling this again.
+    pub fn history(&self) -> RefMut<'_, History> {
+        self.history.borrow_mut()
+    }
+
+    /// Takes back the last edit of the drawing history. Returns false if there is none.
+    pub fn undo(&self) -> bool {
+        self.history.borrow_mut().undo()
+    }
+
+    /// Puts back the last edit of the drawing history that was undone. Returns false if there
+    /// is none.
+    pub fn redo(&self) -> bool {
+        self.history.borrow_mut().redo()
+    }
+
+    /// How long the last frames spent updating, drawing and being shown, or None unless
+    /// profiling was turned on with `ApplicationRunner::set_profiling`. The frame being drawn
+    /// isn't included until it's complete.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::app::Context;
+    /// # use simple_sketch::profiler::Stage;
+    /// # fn draw(ctx: &Context) {
+    /// if let Some(profiler) = ctx.profiler() {
+    ///     println!("{:.1} fps, {:?} drawing", profiler.fps(), profiler.average(Stage::Draw));
+    /// }
+    /// # }
+    /// ```
+    pub fn profiler(&self) -> Option<&FrameProfiler> {
+        self.profiler.as_ref()
+    }
+
+    /// The loudness and spectrum of the audio input, analyzed before the updates of the
+    /// current frame. Silent, with no bins, without an input. See
+    /// `ApplicationRunner::set_audio_input`.
+    #[cfg(feature = "audio")]
+    pub fn audio(&self) -> &AudioFrame {
+        &self.audio
+    }
+
+    /// The mouse position and buttons in the current frame.
+    pub fn mouse(&self) -> &Mouse {
+        &self.mouse
+    }
+
+    /// Checks if a key is held down in the current frame.
+    pub fn is_key_down(&self, key: Key) -> bool {
+        self.keyboard.is_down(key)
+    }
+
+    /// All keys held down in the current frame, in no particular order.
+    pub fn keys_down(&self) -> &[Key] {
+        self.keyboard.down()
+    }
+}
+
+/// Creates a sketch with `Default` and runs it in a window until the window is closed or Escape
+/// is pressed, so that a `main` function can be a single line:
+///
+/// ```no_run
+/// # use simple_sketch::app;
+/// # use simple_sketch::app::{Application, Context};
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::error::SketchError;
+/// # #[derive(Default)]
+/// # struct MySketch;
+/// # impl Application for MySketch {
+/// #     fn draw(&mut self, _: &mut Canvas, _: &Context) {}
+/// # }
+/// fn main() -> Result<(), SketchError> {
+///     app::run::<MySketch>()
+/// }
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run<A: Application + Default>() -> Result<(), SketchError> {
+    ApplicationRunner::new(A::default())?.run()
+}
+
+/// Like `run`, but shows the sketch in the terminal it was started from, with a
+/// `TerminalBackend`, rather than in a window.
+pub fn run_in_terminal<A: Application + Default>() -> Result<(), SketchError> {
+    let app = A::default();
+    let backend = TerminalBackend::new(&app.settings())?;
+    ApplicationRunner::with_backend(app, backend).run()
+}
+
+/// Owns the window and canvas of a sketch and runs its frame loop.
+pub struct ApplicationRunner<A> {
+    /// None for headless runners.
+    backend: Option<Box<dyn Backend>>,
+    /// The visibility and style last set on the window's cursor.
+    window_cursor: Option<(bool, CursorStyle)>,
+    /// The frame last shown in the window.
+    presented: PresentedFrame,
+    frames: FrameLoop<A>,
+    exited: bool,
+}
+
+// Everything but the backend, which updates and draws the sketch. It is kept apart from the
+// backend so that `run_threaded` can move it to another thread.
+struct FrameLoop<A> {
+    app: A,
+    canvas: Canvas,
+    context: Context,
+    update_mode: UpdateMode,
+    /// Time in seconds not yet simulated by fixed-rate updates.
+    accumulator: f32,
+    screenshot_key: Option<Key>,
+    recorder: FrameRecorder,
+    record_key: Option<Key>,
+    video: Option<VideoRecorder>,
+    #[cfg(feature = "audio")]
+    audio_input: Option<AudioInput>,
+    #[cfg(feature = "audio")]
+    soundtrack: Option<AudioClip>,
+    /// The soundtrack playing along with the window.
+    #[cfg(feature = "audio")]
+    playback: Option<AudioPlayback>,
+    /// Whether Space, Right, Down and Up control playback.
+    playback_keys: bool,
+    /// Whether Ctrl+Z and Ctrl+Y undo and redo the drawing history.
+    undo_keys: bool,
+    /// Whether to update and draw the next frame while paused.
+    step_requested: bool,
+    /// Whether the profiler's timings are drawn over the frames shown in the window.
+    profiler_overlay: bool,
+    /// The last drawn frames, or None when they aren't kept.
+    frame_history: Option<FrameHistory>,
+    /// Files to pass to `Application::on_file_dropped` in the next frame.
+    dropped_files: Vec<PathBuf>,
+    /// Frames per second, for single steps and the clock of headless runners.
+    frame_rate: f32,
+    /// The number of times `frame` was called, including paused frames.
+    ticks: u64,
+    /// The real time since the sketch started at the last call to `frame`.
+    clock: Duration,
+}
+
+// A frame drawn on the background thread of `run_threaded`, waiting to be shown.
+#[derive(Default)]
+struct CompletedFrame {
+    /// The pixels of the canvas, with the profiler overlay and the software cursor drawn over them.
+    pixels: Vec<u32>,
+    width: usize,
+    height: usize,
+    cursor: (bool, CursorStyle),
+    /// Whether the frame wasn't shown yet.
+    fresh: bool,
+}
+
+// A copy of the frame last shown in the window, to only present what changed since.
+#[derive(Default)]
+struct PresentedFrame {
+    pixels: Vec<u32>,
+    width: usize,
+    height: usize,
+}
+
+impl PresentedFrame {
+    // Shows a frame through `backend`: in full after a resize, only the region that changed
+    // otherwise, and not at all when it didn't change, which only processes the window's events.
+    fn present(&mut self, backend: &mut dyn Backend, pixels: &[u32], width: usize, height: usize) -> Result<(), SketchError> {
+        if (width, height) != (self.width, self.height) || pixels.len() != self.pixels.len() || width == 0 {
+            self.pixels.clear();
+            self.pixels.extend_from_slice(pixels);
+            (self.width, self.height) = (width, height);
+            return backend.present(pixels, width, height);
+        }
+
+        let rows = self.pixels.chunks(width).zip(pixels.chunks(width));
+        let changed_rows: Vec<usize> = rows.enumerate().filter(|(_, (old, new))| old != new).map(|(y, _)| y).collect();
+        let (Some(&first_row), Some(&last_row)) = (changed_rows.first(), changed_rows.last()) else {
+            return backend.poll();
+        };
+        let (mut first_column, mut last_column) = (width, 0);
+        for &y in &changed_rows {
+            let (old, new) = (&self.pixels[y * width..(y + 1) * width], &pixels[y * width..(y + 1) * width]);
+            let differs = |x: &usize| old[*x] != new[*x];
+            first_column = first_column.min((0..width).find(differs).unwrap_or(width));
+            last_column = last_column.max((0..width).rev().find(differs).unwrap_or(0));
+        }
+        let region = (first_row * width)..((last_row + 1) * width);
+        self.pixels[region.clone()].copy_from_slice(&pixels[region]);
+        let changed = FrameRegion { x: first_column, y: first_row, width: last_column + 1 - first_column, height: last_row + 1 - first_row };
+        backend.present_changed(pixels, width, height, changed)
+    }
+}
+
+impl<A: Application> ApplicationRunner<A> {
+    /// Opens a minifb window configured by the sketch's `Application::settings`. On wasm32,
+    /// sketches are shown with `web::run` instead.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new(app: A) -> Result<Self, SketchError> {
+        let backend = MinifbBackend::new(&app.settings())?;
+        Ok(ApplicationRunner::with_backend(app, backend))
+    }
+
+    /// Creates a runner that shows the sketch through another windowing backend than minifb.
+    pub fn with_backend(app: A, backend: impl Backend + 'static) -> Self {
+        let mut runner = ApplicationRunner::headless(app);
+        runner.backend = Some(Box::new(backend));
+        runner
+    }
+
+    /// Creates a runner with a canvas of the size in the sketch's `Application::settings` but
+    /// no window, for rendering with `run_frames` on machines without a display. Headless
+    /// sketches get no input, and their clock advances by exactly one frame's duration per
+    /// frame, however long frames take to render, so that their output doesn't depend on the
+    /// speed of the machine.
+    pub fn headless(app: A) -> Self {
+        let settings = app.settings();
+        ApplicationRunner {
+            backend: None,
+            window_cursor: None,
+            presented: PresentedFrame::default(),
+            frames: FrameLoop {
+                app,
+                canvas: Canvas::with_pixel_density(settings.width, settings.height, settings.pixel_density),
+                context: Context::default(),
+                update_mode: UpdateMode::PerFrame,
+                accumulator: 0.0,
+                screenshot_key: Some(Key::S),
+                recorder: FrameRecorder::default(),
+                record_key: Some(Key::R),
+                video: None,
+                #[cfg(feature = "audio")]
+                audio_input: None,
+                #[cfg(feature = "audio")]
+                soundtrack: None,
+                #[cfg(feature = "audio")]
+                playback: None,
+                playback_keys: true,
+                undo_keys: true,
+                step_requested: false,
+                profiler_overlay: false,
+                frame_history: None,
+                dropped_files: Vec::new(),
+                frame_rate: settings.frame_rate,
+                ticks: 0,
+                clock: Duration::ZERO,
+            },
+            exited: false,
+        }
+    }
+
+    /// Sets the key that saves the current frame as `screenshot-####.png` in the working
+    /// directory, numbered by frame count. Defaults to S, None turns screenshots off.
+    pub fn set_screenshot_key(&mut self, key: Option<Key>) {
+        self.frames.screenshot_key = key;
+    }
+
+    /// Sets the directory the frame recorder writes `frame_000001.png`, `frame_000002.png` and
+    /// so on to, while recording. Defaults to `frames` in the working directory.
+    pub fn set_recording_directory(&mut self, directory: impl Into<PathBuf>) {
+        self.frames.recorder.set_directory(directory);
+    }
+
+    /// Starts writing every drawn frame to the frame recorder. Paused frames aren't written,
+    /// so the sequence holds exactly the frames the sketch drew.
+    pub fn start_recording(&mut self) {
+        self.frames.context.start_recording();
+    }
+
+    pub fn stop_recording(&mut self) {
+        self.frames.context.stop_recording();
+    }
+
+    /// The frame recorder, for example to check how many frames it wrote.
+    pub fn recorder(&self) -> &FrameRecorder {
+        &self.frames.recorder
+    }
+
+    /// Sets the key that starts and stops recording. Defaults to R, None turns the key off.
+    pub fn set_record_key(&mut self, key: Option<Key>) {
+        self.frames.record_key = key;
+    }
+
+    /// Starts recording every drawn frame to an MP4 or WebM video at the sketch's frame rate,
+    /// through ffmpeg. See `VideoRecorder`. The recording ends with `stop_video`, or when the
+    /// sketch stops. The canvas can't be resized during the recording.
+    pub fn start_video(&mut self, path: impl Into<PathBuf>) -> Result<(), SketchError> {
+        self.stop_video()?;
+        let buffer = &self.frames.canvas.pixel_buffer;
+        self.frames.video = Some(VideoRecorder::start(path, buffer.width, buffer.height, self.frames.frame_rate)?);
+        Ok(())
+    }
+
+    /// Ends the video recording, if there is one, and waits for the video to be written.
+    pub fn stop_video(&mut self) -> Result<(), SketchError> {
+        self.frames.video.take().map_or(Ok(()), VideoRecorder::finish)
+    }
+
+    /// Sets the audio input analyzed into `Context::audio` before every frame, or stops
+    /// analyzing audio with None.
+    #[cfg(feature = "audio")]
+    pub fn set_audio_input(&mut self, input: Option<AudioInput>) {
+        self.frames.audio_input = input;
+        if self.frames.audio_input.is_none() {
+            self.frames.context.audio = AudioFrame::default();
+        }
+    }
+
+    /// Sets a clip that plays along with the sketch's clock, from its start at the start of the
+    /// sketch, and that `Context::audio` analyzes at the clock's position instead of the audio
+    /// input. It stays silent while the sketch is paused or runs at another speed, and in
+    /// headless runners. None removes it.
+    #[cfg(feature = "audio")]
+    pub fn set_soundtrack(&mut self, clip: Option<AudioClip>) {
+        self.frames.playback = None;
+        self.frames.soundtrack = clip;
+        if self.frames.soundtrack.is_none() {
+            self.frames.context.audio = AudioFrame::default();
+        }
+    }
+
+    /// Drops a file onto the sketch, as if it was dragged onto the window. The sketch's
+    /// `Application::on_file_dropped` is called with it at the start of the next frame.
+    pub fn drop_file(&mut self, path: impl Into<PathBuf>) {
+        self.frames.dropped_files.push(path.into());
+    }
+
+    /// Turns the built-in playback controls on or off. They are on by default: Space pauses
+    /// and resumes the sketch, Right arrow draws a single frame while it is paused, and Down
+    /// and Up arrow halve and double its speed, down to 1/16 and back up to normal speed.
+    /// With a frame history, Left arrow pauses the sketch and steps back a frame, Right arrow
+    /// steps forward again until it reaches the live frame, and Shift with either jumps a
+    /// second. The keys still reach `Application::key_pressed`.
+    pub fn set_playback_keys(&mut self, enabled: bool) {
+        self.frames.playback_keys = enabled;
+    }
+
+    /// Turns profiling on or off. While on, the time every drawn frame spends in `update`, in
+    /// `draw` and its lines, fills and strokes, and in being shown is measured, and the timings
+    /// of the last frames can be read from `profiler` and `Context::profiler`. Off by default.
+    /// Turning it off discards the timings. Profiling isn't available in the browser, where it
+    /// stays off.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        let enabled = enabled && !cfg!(target_arch = "wasm32");
+        if enabled != self.frames.context.profiler.is_some() {
+            self.frames.context.profiler = enabled.then(FrameProfiler::default);
+            self.frames.canvas.set_draw_timing(enabled);
+        }
+    }
+
+    /// The stage timings of the last frames, or None unless profiling is on. See `set_profiling`.
+    pub fn profiler(&self) -> Option<&FrameProfiler> {
+        self.frames.context.profiler.as_ref()
+    }
+
+    /// Turns the profiler overlay on or off. While on, the frame rate and the average time of
+    /// every stage of the last frames are drawn in the top left corner of the window with the
+    /// debug font. The overlay isn't part of saved or recorded frames. Turning it on also turns
+    /// on profiling. Off by default.
+    pub fn set_profiler_overlay(&mut self, enabled: bool) {
+        if enabled {
+            self.set_profiling(true);
+        }
+        self.frames.profiler_overlay = enabled;
+    }
+
+    /// Turns the built-in undo keys on or off. They are on by default: Ctrl+Z undoes the last
+    /// edit of `Context::history`, and Ctrl+Y or Ctrl+Shift+Z redoes it. Cmd works in place of
+    /// Ctrl. The keys still reach `Application::key_pressed`.
+    pub fn set_undo_keys(&mut self, enabled: bool) {
+        self.frames.undo_keys = enabled;
+    }
+
+    /// Keeps the frames drawn in the last `seconds` at the sketch's frame rate, to step back
+    /// through them while the sketch is paused, or stops keeping them with None. Off by
+    /// default. See `FrameHistory` and `set_playback_keys`.
+    pub fn set_frame_history(&mut self, seconds: Option<f32>) {
+        let Some(seconds) = seconds else {
+            self.frames.frame_history = None;
+            return;
+        };
+        let capacity = (seconds * self.frames.frame_rate).ceil().max(1.0) as usize;
+        match &mut self.frames.frame_history {
+            Some(history) => history.set_capacity(capacity),
+            None => self.frames.frame_history = Some(FrameHistory::new(capacity)),
+        }
+    }
+
+    /// The last drawn frames, or None unless they are kept. See `set_frame_history`.
+    pub fn frame_history(&self) -> Option<&FrameHistory> {
+        self.frames.frame_history.as_ref()
+    }
+
+    /// The last drawn frames, for example to step through them from code.
+    pub fn frame_history_mut(&mut self) -> Option<&mut FrameHistory> {
+        self.frames.frame_history.as_mut()
+    }
+
+    /// Pauses or resumes the sketch. See `Context::is_paused`.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.frames.context.paused = paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.frames.context.paused
+    }
+
+    /// Updates and draws the next frame even though the sketch is paused, advancing its clock
+    /// by one frame at the frame rate.
+    pub fn step(&mut self) {
+        self.frames.step_requested = true;
+    }
+
+    /// Sets how fast the sketch's clock runs compared to real time, for example 0.25 for slow
+    /// motion. `dt` and the fixed update rate are scaled with it.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.frames.context.time_scale = time_scale.max(0.0);
+    }
+
+    /// Sets how often `Application::update` is called. Defaults to once per frame.
+    pub fn set_update_mode(&mut self, mode: UpdateMode) {
+        self.frames.update_mode = mode;
+        self.frames.accumulator = 0.0;
+    }
+
+    /// The sketch being run.
+    pub fn app(&self) -> &A {
+        &self.frames.app
+    }
+
+    /// The canvas the sketch draws on.
+    pub fn canvas(&self) -> &Canvas {
+        &self.frames.canvas
+    }
+
+    /// Renders the sketch's current frame at `scale` times its canvas size to a PNG file, in
+    /// tiles, for prints larger than a window. `Application::draw` is called once per tile with
+    /// the current context, so it must draw the same picture every time. See `TiledRenderer`.
+    pub fn render_tiled(&mut self, path: impl AsRef<Path>, scale: f32) -> Result<(), SketchError> {
+        let FrameLoop { app, canvas, context, .. } = &mut self.frames;
+        TiledRenderer::new(canvas.width, canvas.height).scale(scale).render_png(path, |canvas| app.draw(canvas, context))
+    }
+
+    /// Renders the sketch's current frame again for export, at `scale` times its canvas size
+    /// with `samples` by `samples` supersamples per pixel, and returns the pixels. The preview in
+    /// the window isn't affected. Like `render_tiled`, `Application::draw` may be called
+    /// several times and must draw the same picture every time.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simple_sketch::app::{Application, ApplicationRunner};
+    /// # use simple_sketch::error::SketchError;
+    /// # fn archive<A: Application>(runner: &mut ApplicationRunner<A>) -> Result<(), SketchError> {
+    /// runner.render_high_quality(4.0, 4)?.save_png("archive.png")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn render_high_quality(&mut self, scale: f32, samples: usize) -> Result<PixelBuffer, SketchError> {
+        let FrameLoop { app, canvas, context, .. } = &mut self.frames;
+        TiledRenderer::new(canvas.width, canvas.height).scale(scale).samples(samples).render(|canvas| app.draw(canvas, context))
+    }
+
+    /// Runs frames until the window is closed or Escape is pressed, then calls
+    /// `Application::exit`. Fails for headless runners.
+    pub fn run(&mut self) -> Result<(), SketchError> {
+        if self.backend.is_none() {
+            return Err(SketchError::NoWindow);
+        }
+        let start = Instant::now();
+        let mut result = Ok(true);
+        while let Ok(true) = result {
+            result = self.show_frame(start.elapsed());
+        }
+        let exited = self.exit();
+        result.and(exited)
+    }
+
+    // Reads the window's input, then updates, draws and shows a frame, with `elapsed` as the
+    // real time since the sketch started. Returns false, without drawing, once the window is
+    // closed or Escape is pressed.
+    pub(crate) fn show_frame(&mut self, elapsed: Duration) -> Result<bool, SketchError> {
+        let Some(backend) = &mut self.backend else {
+            return Err(SketchError::NoWindow);
+        };
+        if !backend.is_open() {
+            return Ok(false);
+        }
+        let input = backend.input();
+        if input.keys.contains(&Key::Escape) {
+            return Ok(false);
+        }
+        let drawn = self.frames.frame(elapsed, vec![input]);
+        self.frames.play_soundtrack()?;
+        self.frames.save_frames(drawn)?;
+        set_cursor(backend.as_mut(), &mut self.window_cursor, self.frames.context.window_cursor());
+        let (width, height) = (self.frames.canvas.pixel_buffer.width, self.frames.canvas.pixel_buffer.height);
+        let profiling = drawn && self.frames.context.profiler.is_some();
+        let mut presenting = None;
+        self.frames.with_overlays_drawn(|pixels| {
+            let started = profiling.then(Instant::now);
+            let presented = self.presented.present(backend.as_mut(), pixels, width, height);
+            presenting = started.map(|started| started.elapsed());
+            presented
+        })?;
+        if let (Some(profiler), Some(time)) = (&mut self.frames.context.profiler, presenting) {
+            profiler.add(Stage::Present, time);
+        }
+        Ok(true)
+    }
+
+    /// Like `run`, but updates and draws the sketch on a background thread at the frame rate
+    /// of its `Settings`, while the window keeps responding and shows the latest completed
+    /// frame. An expensive `update` then slows down the sketch, but not the window. Input is
+    /// passed to the sketch's callbacks on the background thread, in the order it happened.
+    pub fn run_threaded(&mut self) -> Result<(), SketchError>
+    where
+        A: Send,
+    {
+        let Some(backend) = &mut self.backend else {
+            return Err(SketchError::NoWindow);
+        };
+        let frames = &mut self.frames;
+        let latest = Mutex::new(CompletedFrame::default());
+        let stop = AtomicBool::new(false);
+        let (send_input, inputs) = mpsc::channel();
+
+        let result = thread::scope(|scope| {
+            let worker = scope.spawn(|| frames.run_worker(inputs, &latest, &stop));
+            let mut shown = CompletedFrame::default();
+            let mut result = Ok(());
+            while result.is_ok() && !worker.is_finished() && backend.is_open() {
+                let input = backend.input();
+                if input.keys.contains(&Key::Escape) {
+                    break;
+                }
+                // The worker stops reading once it has finished
+                let _ = send_input.send(input);
+                {
+                    let mut latest = latest.lock().unwrap_or_else(PoisonError::into_inner);
+                    if latest.fresh {
+                        // Trade the shown frame's buffer for the new one, for the worker to reuse
+                        mem::swap(&mut *latest, &mut shown);
+                        latest.fresh = false;
+                    }
+                }
+                result = if shown.pixels.is_empty() {
+                    backend.poll()
+                } else {
+                    set_cursor(backend.as_mut(), &mut self.window_cursor, shown.cursor);
+                    self.presented.present(backend.as_mut(), &shown.pixels, shown.width, shown.height)
+                };
+            }
+            stop.store(true, Ordering::Relaxed);
+            match worker.join() {
+                Ok(worker_result) => result.and(worker_result),
+                Err(panic) => std::panic::resume_unwind(panic),
+            }
+        });
+        let exited = self.exit();
+        result.and(exited)
+    }
+
+    /// Calls `Application::exit`, unless `run` already did, and returns the sketch. This is
+    /// how headless runners end.
+    pub fn finish(mut self) -> A {
+        if !self.exited {
+            self.frames.app.exit();
+        }
+        self.frames.app
+    }
+
+    /// Renders `frames` frames without showing them, calling `on_frame` with the canvas and the
+    /// frame count after each one. The sketch's clock advances by one frame's duration per frame.
+    /// Calling it again continues where the previous call stopped. Fails if a frame can't be
+    /// saved.
+    pub fn run_frames(&mut self, frames: u64, mut on_frame: impl FnMut(&Canvas, u64)) -> Result<(), SketchError> {
+        let frame_loop = &mut self.frames;
+        for _ in 0..frames {
+            let frame = frame_loop.context.frame_count;
+            let elapsed = Duration::from_secs_f64(frame_loop.ticks as f64 / frame_loop.frame_rate as f64);
+            let drawn = frame_loop.frame(elapsed, Vec::new());
+            frame_loop.save_frames(drawn)?;
+            on_frame(&frame_loop.canvas, frame);
+        }
+        Ok(())
+    }
+
+    // Ends the video recording and the soundtrack and calls `Application::exit`
+    pub(crate) fn exit(&mut self) -> Result<(), SketchError> {
+        #[cfg(feature = "audio")]
+        {
+            self.frames.playback = None;
+        }
+        let video = self.stop_video();
+        self.frames.app.exit();
+        self.exited = true;
+        video
+    }
+}
+
+impl<A: Application> FrameLoop<A> {
+    // Handles the inputs sampled since the previous frame, in order, and updates and draws the
+    // sketch unless it is paused, with `elapsed` as the real time since it started. Returns
+    // whether the sketch was drawn.
+    fn frame(&mut self, elapsed: Duration, inputs: Vec<InputState>) -> bool {
+        if self.ticks == 0 {
+            self.app.setup(&mut self.canvas);
+        }
+        let real_dt = elapsed.saturating_sub(self.clock);
+        self.clock = elapsed;
+        self.ticks += 1;
+        for input in inputs {
+            self.handle_input(input);
+        }
+        for path in mem::take(&mut self.dropped_files) {
+            self.app.on_file_dropped(&path);
+        }
+        if self.context.paused && !mem::take(&mut self.step_requested) {
+            return false;
+        }
+
+        let dt = if self.context.frame_count == 0 {
+            Duration::ZERO
+        } else if self.context.paused {
+            Duration::from_secs_f64(1.0 / self.frame_rate as f64)
+        } else {
+            real_dt
+        };
+        let dt = dt.mul_f32(self.context.time_scale);
+        self.context.elapsed += dt;
+        self.context.tweens.get_mut().advance(dt.as_secs_f32());
+        #[cfg(feature = "audio")]
+        if let Some(clip) = &self.soundtrack {
+            self.context.audio = clip.analyze_at(self.context.elapsed.as_secs_f32());
+        } else if let Some(input) = &self.audio_input {
+            self.context.audio = input.analyze();
+        }
+        if let Some(profiler) = &mut self.context.profiler {
+            profiler.start_frame();
+        }
+        let started = self.context.profiler.is_some().then(Instant::now);
+        self.run_updates(dt.as_secs_f32());
+        let updated = started.map(|_| Instant::now());
+        self.canvas.restore_drawn_frame();
+        self.app.draw(&mut self.canvas, &self.context);
+        self.canvas.flush();
+        self.canvas.composite_frame();
+        if let (Some(profiler), Some(started), Some(updated)) = (&mut self.context.profiler, started, updated) {
+            profiler.add(Stage::Update, updated - started);
+            profiler.add(Stage::Draw, updated.elapsed());
+            profiler.add_all(&self.canvas.take_draw_times());
+        }
+        if let Some(history) = &mut self.frame_history {
+            history.push(&self.canvas.pixel_buffer, self.context.frame_count, self.context.seconds_since_start());
+        }
+        self.context.frame_count += 1;
+        true
+    }
+
+    // Starts the soundtrack at the clock's position while the sketch runs at normal speed, and
+    // stops it otherwise. The playback is restarted when it drifts away from the clock, for
+    // example after single steps.
+    fn play_soundtrack(&mut self) -> Result<(), SketchError> {
+        #[cfg(feature = "audio")]
+        if let Some(clip) = &self.soundtrack {
+            let position = self.context.elapsed.as_secs_f32();
+            let audible = !self.context.paused && self.context.time_scale == 1.0 && position < clip.duration();
+            let in_sync = self.playback.as_ref().is_some_and(|playback| (playback.position() - position).abs() < MAX_SOUNDTRACK_DRIFT);
+            if !audible || !in_sync {
+                self.playback = None;
+            }
+            if audible && self.playback.is_none() {
+                self.playback = Some(clip.play_from(position)?);
+            }
+        }
+        Ok(())
+    }
+
+    // The frame loop of `run_threaded`'s background thread. Draws frames at the frame rate, or
+    // as fast as it can if that's slower, and hands each one over through `latest` until `stop`
+    // is set.
+    fn run_worker(&mut self, inputs: Receiver<InputState>, latest: &Mutex<CompletedFrame>, stop: &AtomicBool) -> Result<(), SketchError> {
+        let start = Instant::now();
+        let frame_time = Duration::from_secs_f64(1.0 / self.frame_rate as f64);
+        let mut completed = CompletedFrame::default();
+        while !stop.load(Ordering::Relaxed) {
+            let frame_start = Instant::now();
+            let drawn = self.frame(start.elapsed(), inputs.try_iter().collect());
+            self.play_soundtrack()?;
+            self.save_frames(drawn)?;
+
+            completed.width = self.canvas.pixel_buffer.width;
+            completed.height = self.canvas.pixel_buffer.height;
+            completed.cursor = self.context.window_cursor();
+            self.with_overlays_drawn(|pixels| {
+                completed.pixels.clear();
+                completed.pixels.extend_from_slice(pixels);
+            });
+            completed.fresh = true;
+            // A frame that wasn't shown yet is dropped, and its buffer reused
+            mem::swap(&mut *latest.lock().unwrap_or_else(PoisonError::into_inner), &mut completed);
+
+            if let Some(rest) = frame_time.checked_sub(frame_start.elapsed()) {
+                thread::sleep(rest);
+            }
+        }
+        Ok(())
+    }
+
+    // Saves the frame to the files, and copies it to the clipboard, as requested while it was
+    // drawn, and records it if it was `drawn` during a recording.
+    fn save_frames(&mut self, drawn: bool) -> Result<(), SketchError> {
+        if self.context.recording.get() {
+            self.recorder.start();
+        } else {
+            self.recorder.stop();
+        }
+        if drawn {
+            self.recorder.record(&self.canvas)?;
+            if let Some(video) = &mut self.video {
+                video.write_frame(&self.canvas)?;
+            }
+        }
+        // While stepping back through the frame history, the frame shown is the one saved
+        let shown = self.frame_history.as_ref().and_then(FrameHistory::shown).map_or(&self.canvas.pixel_buffer, HistoricFrame::pixels);
+        for path in self.context.saves.take() {
+            save_png(shown, &path)
+                .map_err(|e| io::Error::new(e.kind(), format!("could not save frame to {}: {e}", path.display())))?;
+        }
+        if self.context.copy_to_clipboard.take() {
+            let mut png = Vec::new();
+            write_frame_png(shown, &mut png)?;
+            clipboard::set_png(&png)?;
+        }
+        Ok(())
+    }
+
+    // Calls `show` with the pixels of the canvas, or of the frame stepped back to in the frame
+    // history, with the profiler overlay and the software cursor drawn over them while they
+    // are shown.
+    fn with_overlays_drawn<R>(&mut self, show: impl FnOnce(&[u32]) -> R) -> R {
+        let software_cursor = self.context.software_cursor.borrow();
+        let profiler = self.context.profiler.as_ref().filter(|_| self.profiler_overlay);
+        let buffer = &self.canvas.pixel_buffer;
+        let historic = self.frame_history.as_ref().and_then(FrameHistory::shown);
+        let historic = historic.filter(|shown| (shown.pixels().width, shown.pixels().height) == (buffer.width, buffer.height));
+        if software_cursor.is_none() && profiler.is_none() && historic.is_none() {
+            return show(buffer.get_buffer());
+        }
+        let frame = buffer.get_buffer().to_vec();
+        if let Some(historic) = historic {
+            self.canvas.pixel_buffer.get_buffer_mut().copy_from_slice(historic.pixels().get_buffer());
+            let seconds_ago = self.context.seconds_since_start() - historic.time();
+            let label = format!("frame {} ({seconds_ago:.2} s ago)", historic.frame_count());
+            self.canvas.push();
+            self.canvas.set_fill(Some(Color::new(255, 255, 255, 255)));
+            self.canvas.debug_text(&label, Point::new(4.0, self.canvas.height as f32 - 12.0));
+            self.canvas.pop();
+            self.canvas.take_draw_times();
+        }
+        if let Some(profiler) = profiler {
+            profiler.draw(&mut self.canvas, Point::new(4.0, 4.0));
+            // The overlay isn't part of the next frame's drawing
+            self.canvas.take_draw_times();
+        }
+        if let Some(cursor) = software_cursor.as_ref() {
+            self.canvas.draw_cursor(cursor, self.context.mouse.position());
+        }
+        let result = show(self.canvas.pixel_buffer.get_buffer());
+        self.canvas.pixel_buffer.get_buffer_mut().copy_from_slice(&frame);
+        result
+    }
+
+    fn run_updates(&mut self, dt: f32) {
+        let UpdateMode::Fixed(rate) = self.update_mode else {
+            self.context.interpolation = 1.0;
+            self.app.update(&self.context, dt);
+            return;
+        };
+
+        let step = 1.0 / rate;
+        // Start with one update so that the first frame draws an updated state
+        self.accumulator += if self.context.frame_count == 0 { step } else { dt };
+        let mut updates = 0;
+        while self.accumulator >= step && updates < MAX_UPDATES_PER_FRAME {
+            self.app.update(&self.context, step);
+            self.accumulator -= step;
+            updates += 1;
+        }
+        if updates == MAX_UPDATES_PER_FRAME {
+            self.accumulator %= step;
+        }
+        self.context.interpolation = self.accumulator / step;
+    }
+
+    fn handle_input(&mut self, input: InputState) {
+        self.dropped_files.extend(input.dropped_files);
+        self.handle_resize(input.size);
+        self.handle_mouse(input.mouse_position, input.mouse_pressed, input.wheel);
+        self.handle_keys(input.keys);
+    }
+
+    fn handle_resize(&mut self, (width, height): (usize, usize)) {
+        // Minimized windows report a size of zero, keep the canvas as it is until they're restored
+        if width == 0 || height == 0 || (width, height) == (self.canvas.width, self.canvas.height) {
+            return;
+        }
+        self.canvas.resize(width, height);
+        self.app.on_resize(width, height);
+    }
+
+    fn handle_mouse(&mut self, position: Option<Point>, pressed: [bool; 3], wheel: Option<Point>) {
+        for input in self.context.mouse.update(position, pressed, wheel) {
+            match input {
+                MouseInput::Pressed(event) => self.app.mouse_pressed(&event),
+                MouseInput::Released(event) => self.app.mouse_released(&event),
+                MouseInput::Clicked(event) => self.app.mouse_clicked(&event),
+                MouseInput::Moved(event) => self.app.mouse_moved(&event),
+                MouseInput::Dragged(event) => self.app.mouse_dragged(&event),
+                MouseInput::Wheel(event) => self.app.mouse_wheel(&event),
+            }
+        }
+    }
+
+    fn handle_keys(&mut self, keys: Vec<Key>) {
+        for input in self.context.keyboard.update(keys) {
+            match input {
+                KeyInput::Pressed(event) => {
+                    if Some(event.key) == self.screenshot_key {
+                        let shown = self.frame_history.as_ref().and_then(FrameHistory::shown);
+                        let frame = shown.map_or(self.context.frame_count, HistoricFrame::frame_count);
+                        self.context.saves.borrow_mut().push(frame_path("screenshot-####.png", frame));
+                    }
+                    if Some(event.key) == self.record_key {
+                        self.context.recording.set(!self.context.recording.get());
+                    }
+                    if self.playback_keys {
+                        self.handle_playback_key(event.key);
+                    }
+                    if self.undo_keys {
+                        self.handle_undo_key(event.key);
+                    }
+                    self.app.key_pressed(&event);
+                }
+                KeyInput::Released(event) => self.app.key_released(&event),
+            }
+        }
+    }
+
+    fn handle_undo_key(&mut self, key: Key) {
+        let keyboard = &self.context.keyboard;
+        let command = [Key::LeftCtrl, Key::RightCtrl, Key::LeftSuper, Key::RightSuper].into_iter().any(|key| keyboard.is_down(key));
+        let shift = keyboard.is_down(Key::LeftShift) || keyboard.is_down(Key::RightShift);
+        match key {
+            Key::Z if command && shift => self.context.redo(),
+            Key::Z if command => self.context.undo(),
+            Key::Y if command => self.context.redo(),
+            _ => false,
+        };
+    }
+
+    fn handle_playback_key(&mut self, key: Key) {
+        let keyboard = &self.context.keyboard;
+        let shift = keyboard.is_down(Key::LeftShift) || keyboard.is_down(Key::RightShift);
+        let context = &mut self.context;
+        let history = self.frame_history.as_mut();
+        let scrubbing = history.as_ref().is_some_and(|history| history.is_scrubbing());
+        match key {
+            Key::Space => context.paused = !context.paused,
+            Key::Left => {
+                if let Some(history) = history {
+                    context.paused = true;
+                    scrub(history, -1, shift);
+                }
+            }
+            Key::Right if context.paused && scrubbing => scrub(history.expect("scrubbing needs a frame history"), 1, shift),
+            Key::Right if context.paused => self.step_requested = true,
+            Key::Down => context.time_scale = (context.time_scale / 2.0).max(MIN_TIME_SCALE),
+            Key::Up => context.time_scale = (context.time_scale * 2.0).min(context.time_scale.max(1.0)),
+            _ => {}
+        }
+    }
+}
+
+// Steps the frame history by a frame in `direction`, or by a second with Shift held.
+fn scrub(history: &mut FrameHistory, direction: isize, shift: bool) {
+    if shift {
+        history.jump(direction as f32);
+    } else {
+        history.step(direction);
+    }
+}
+
+// Sets the visibility and style of the window's cursor, unless they are already `applied`.
+fn set_cursor(backend: &mut dyn Backend, applied: &mut Option<(bool, CursorStyle)>, cursor: (bool, CursorStyle)) {
+    if *applied != Some(cursor) {
+        backend.set_cursor(cursor.0, cursor.1);
+        *applied = Some(cursor);
+    }
+}
+
+// Replaces the first run of `#` in `pattern` with the zero-padded frame count.
+fn frame_path(pattern: &str, frame: u64) -> PathBuf {
+    let Some(start) = pattern.find('#') else {
+        return PathBuf::from(pattern);
+    };
+    let digits = pattern[start..].find(|c| c != '#').unwrap_or(pattern.len() - start);
+    let end = start + digits;
+    PathBuf::from(format!("{}{frame:0digits$}{}", &pattern[..start], &pattern[end..]))
+}
+
+// Saves a frame as an opaque PNG file.
+pub(crate) fn save_png(pixels: &PixelBuffer, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    write_frame_png(pixels, BufWriter::new(File::create(path)?))
+}
+
+// Writes a frame as an opaque PNG, the way it looks in the window.
+fn write_frame_png(pixels: &PixelBuffer, out: impl Write) -> io::Result<()> {
+    pixels.write_png(out, PngColor::Rgb)
+}
@@ -0,0 +1,184 @@
+//! Loading images, fonts and palettes by name, keeping them in memory after the first load and
+//! optionally reloading them when their files change, so textures can be edited while the
+//! sketch runs.
+//!
+//! Files are watched by comparing their modification times, checked at most every
+//! `CHECK_INTERVAL` for each asset, which costs little even when assets are looked up every
+//! frame.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::error::SketchError;
+use crate::font::{Font, FontError};
+use crate::image::Image;
+use crate::palette::Palette;
+
+/// How long an asset goes without checking its file for changes during hot reloading.
+const CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Images, fonts and palettes loaded from the files in a directory, by their path relative to
+/// it. Each file is loaded the first time it is asked for and kept until `clear`.
+///
+/// With hot reloading on, asking for an asset reloads it if its file changed since it was
+/// loaded. If the new file can't be loaded, for example because it is still being written,
+/// the previous version is kept and loading is tried again on the next change check.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simple_sketch::assets::Assets;
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::error::SketchError;
+/// # use simple_sketch::geom::Point;
+/// # fn draw(canvas: &mut Canvas) -> Result<(), SketchError> {
+/// let mut assets = Assets::new("assets");
+/// assets.set_hot_reload(true);
+///
+/// // In draw: the texture updates as soon as it is saved from the paint program
+/// canvas.image(assets.image("textures/brick.png")?, Point::new(0.0, 0.0));
+/// canvas.set_fill(Some(assets.palette("palettes/sweetie-16.hex")?.get(3)));
+/// # Ok(())
+/// # }
+/// ```
+pub struct Assets {
+    root: PathBuf,
+    hot_reload: bool,
+    images: HashMap<String, Entry<Image>>,
+    fonts: HashMap<String, Entry<Font>>,
+    palettes: HashMap<String, Entry<Palette>>,
+}
+
+// A loaded asset and what is needed to tell when its file changes
+struct Entry<T> {
+    path: PathBuf,
+    /// The modification time of the file the value was loaded from, if the system has one.
+    modified: Option<SystemTime>,
+    checked: Instant,
+    value: T,
+}
+
+impl<T> Entry<T> {
+    fn load(path: PathBuf, load: fn(&Path) -> Result<T, SketchError>) -> Result<Self, SketchError> {
+        let modified = modified(&path);
+        let value = load(&path)?;
+        Ok(Entry { path, modified, checked: Instant::now(), value })
+    }
+
+    // Reloads the value if the file changed. Returns whether it was reloaded.
+    fn reload_if_changed(&mut self, load: fn(&Path) -> Result<T, SketchError>) -> bool {
+        self.checked = Instant::now();
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return false;
+        }
+        match load(&self.path) {
+            Ok(value) => {
+                self.value = value;
+                self.modified = modified;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl Assets {
+    /// Creates an empty set of assets loaded from the files under `root`.
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Assets {
+            root: root.as_ref().to_path_buf(),
+            hot_reload: false,
+            images: HashMap::new(),
+            fonts: HashMap::new(),
+            palettes: HashMap::new(),
+        }
+    }
+
+    /// The directory asset names are relative to.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Turns reloading assets whose files changed on or off. Off by default.
+    pub fn set_hot_reload(&mut self, enabled: bool) {
+        self.hot_reload = enabled;
+    }
+
+    pub fn hot_reload(&self) -> bool {
+        self.hot_reload
+    }
+
+    /// The image in the PNG or JPEG file at `name`, relative to the root. See `Image::load`.
+    pub fn image(&mut self, name: &str) -> Result<&Image, SketchError> {
+        get(&mut self.images, &self.root, name, self.hot_reload, |path| Image::load(path))
+    }
+
+    /// The TrueType font in the file at `name`, relative to the root, for `Canvas::set_font`.
+    pub fn font(&mut self, name: &str) -> Result<&Font, SketchError> {
+        get(&mut self.fonts, &self.root, name, self.hot_reload, load_font)
+    }
+
+    /// The palette in the `.gpl` or `.hex` file at `name`, relative to the root. See
+    /// `Palette::parse`.
+    pub fn palette(&mut self, name: &str) -> Result<&Palette, SketchError> {
+        get(&mut self.palettes, &self.root, name, self.hot_reload, |path| Palette::load(path))
+    }
+
+    /// Reloads every loaded asset whose file changed, whether hot reloading is on or not, and
+    /// returns their names. Assets that fail to reload keep their previous version.
+    pub fn reload_changed(&mut self) -> Vec<String> {
+        let mut reloaded = Vec::new();
+        reload_changed(&mut self.images, |path| Image::load(path), &mut reloaded);
+        reload_changed(&mut self.fonts, load_font, &mut reloaded);
+        reload_changed(&mut self.palettes, |path| Palette::load(path), &mut reloaded);
+        reloaded
+    }
+
+    /// Forgets all loaded assets, so they are loaded again when next asked for.
+    pub fn clear(&mut self) {
+        self.images.clear();
+        self.fonts.clear();
+        self.palettes.clear();
+    }
+}
+
+// Looks up `name`, loading it on first use and, with hot reloading, when its file changed
+fn get<'a, T>(
+    entries: &'a mut HashMap<String, Entry<T>>,
+    root: &Path,
+    name: &str,
+    hot_reload: bool,
+    load: fn(&Path) -> Result<T, SketchError>,
+) -> Result<&'a T, SketchError> {
+    if !entries.contains_key(name) {
+        let entry = Entry::load(root.join(name), load)?;
+        entries.insert(name.to_string(), entry);
+    }
+    let entry = entries.get_mut(name).expect("the entry was just inserted");
+    if hot_reload && entry.checked.elapsed() >= CHECK_INTERVAL {
+        entry.reload_if_changed(load);
+    }
+    Ok(&entry.value)
+}
+
+fn reload_changed<T>(entries: &mut HashMap<String, Entry<T>>, load: fn(&Path) -> Result<T, SketchError>, reloaded: &mut Vec<String>) {
+    for (name, entry) in entries {
+        if entry.reload_if_changed(load) {
+            reloaded.push(name.clone());
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn load_font(path: &Path) -> Result<Font, SketchError> {
+    Font::load(path).map_err(|error| match error {
+        FontError::Io(e) => SketchError::from(std::io::Error::new(e.kind(), format!("could not load {}: {e}", path.display()))),
+        error => error.into(),
+    })
+}
@@ -0,0 +1,525 @@
+//! Audio input and soundtracks for audio-reactive sketches, behind the `audio` feature.
+//!
+//! Live sound is captured by an `ffmpeg` process, which has to be installed, from PulseAudio
+//! on Linux, AVFoundation on macOS and DirectShow on Windows. Samples from other sources, such
+//! as a cpal stream, can be pushed through an `AudioSender` instead.
+//!
+//! Capturing goes through ffmpeg rather than a native audio library like cpal so that the
+//! feature adds no dependencies, the way the `serial` feature goes through `stty`: cpal needs
+//! the ALSA development files to build on Linux, while ffmpeg is needed anyway to decode and
+//! play `AudioClip`s.
+//!
+//! Handed to `ApplicationRunner::set_audio_input`, the input is analyzed before every frame
+//! into the loudness and spectrum of the latest samples, which `Context::audio` returns:
+//!
+//! ```no_run
+//! # use simple_sketch::app::{Application, ApplicationRunner, Context};
+//! # use simple_sketch::audio::AudioInput;
+//! # use simple_sketch::canvas::Canvas;
+//! # use simple_sketch::error::SketchError;
+//! # use simple_sketch::geom::Point;
+//! # struct Visualizer;
+//! # impl Application for Visualizer {
+//! #     fn draw(&mut self, _: &mut Canvas, _: &Context) {}
+//! # }
+//! # fn main() -> Result<(), SketchError> {
+//! let mut runner = ApplicationRunner::new(Visualizer)?;
+//! runner.set_audio_input(Some(AudioInput::start()?));
+//! runner.run()?;
+//!
+//! # Ok(())
+//! # }
+//! # fn draw(canvas: &mut Canvas, ctx: &Context) {
+//! // In draw
+//! let audio = ctx.audio();
+//! canvas.circle(Point::new(320.0, 180.0), 20.0 + 200.0 * audio.rms);
+//! let bass = audio.band(20.0, 250.0);
+//! # }
+//! ```
+//!
+//! Music visualizations can play an `AudioClip` instead, analyzed at the position the
+//! sketch's clock is at.
+
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Instant;
+
+use crate::error::SketchError;
+
+/// The sample rate ffmpeg captures at, in samples per second.
+const CAPTURE_RATE: u32 = 44_100;
+
+/// The FFT size inputs start with: 1024 samples, about 23 milliseconds at 44.1 kHz, in 512
+/// bins of about 43 Hz.
+const DEFAULT_FFT_SIZE: usize = 1024;
+
+// The ffmpeg input format and default device of each platform
+#[cfg(target_os = "macos")]
+const CAPTURE: (&str, &str) = ("avfoundation", ":0");
+#[cfg(target_os = "windows")]
+const CAPTURE: (&str, &str) = ("dshow", "audio=default");
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const CAPTURE: (&str, &str) = ("pulse", "default");
+
+/// The loudness and spectrum of the latest audio, as analyzed before a frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioFrame {
+    /// The root mean square amplitude, from 0 for silence to about 0.7 for a full scale sine.
+    pub rms: f32,
+    /// The largest absolute sample, from 0 to 1.
+    pub peak: f32,
+    /// The magnitudes of the frequencies from 0 up to half the sample rate, in bins of equal
+    /// width. A full scale sine at the frequency of a bin gives it a magnitude of about 1.
+    pub bins: Vec<f32>,
+    /// The sample rate of the analyzed audio, in samples per second. 0 without audio.
+    pub sample_rate: u32,
+}
+
+impl AudioFrame {
+    // Analyzes a window of samples, whose length is a power of two
+    fn analyze(window: &mut [f32], sample_rate: u32) -> Self {
+        let rms = (window.iter().map(|sample| sample * sample).sum::<f32>() / window.len() as f32).sqrt();
+        let peak = window.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        AudioFrame { rms, peak, bins: spectrum(window), sample_rate }
+    }
+
+    /// The frequency in the middle of bin `index`, in Hz.
+    pub fn bin_frequency(&self, index: usize) -> f32 {
+        index as f32 * self.bin_width()
+    }
+
+    /// The width of every bin, in Hz.
+    pub fn bin_width(&self) -> f32 {
+        if self.bins.is_empty() {
+            0.0
+        } else {
+            self.sample_rate as f32 / (2 * self.bins.len()) as f32
+        }
+    }
+
+    /// The average magnitude of the bins from `low` to `high` Hz, for example 20 to 250 Hz
+    /// for the bass. 0 for a band without bins.
+    pub fn band(&self, low: f32, high: f32) -> f32 {
+        let width = self.bin_width();
+        if width == 0.0 {
+            return 0.0;
+        }
+        let first = (low / width).round().max(0.0) as usize;
+        let last = ((high / width).round().max(0.0) as usize).min(self.bins.len() - 1);
+        if first > last {
+            return 0.0;
+        }
+        self.bins[first..=last].iter().sum::<f32>() / (last - first + 1) as f32
+    }
+}
+
+/// A source of audio samples, analyzed into an `AudioFrame` once per frame.
+///
+/// Only the latest samples are kept, as many as the FFT size, so the analysis always shows
+/// the sound of the moment.
+#[derive(Debug)]
+pub struct AudioInput {
+    buffer: Arc<Mutex<Buffer>>,
+    sample_rate: u32,
+    /// The ffmpeg process capturing the sound, if any.
+    process: Option<Child>,
+}
+
+impl AudioInput {
+    /// Starts capturing mono sound from the system's default recording device through ffmpeg.
+    /// On Windows, where DirectShow has no default device, use `start_device` with the name
+    /// of a device from `ffmpeg -list_devices true -f dshow -i dummy`.
+    pub fn start() -> Result<Self, SketchError> {
+        AudioInput::start_device(CAPTURE.1)
+    }
+
+    /// Starts capturing from a device, named the way ffmpeg's input format of the platform
+    /// names it, for example `audio=Microphone (USB Audio)` on Windows or `:1` on macOS.
+    /// ffmpeg reports devices it can't open on the standard error output.
+    pub fn start_device(device: &str) -> Result<Self, SketchError> {
+        let rate = CAPTURE_RATE.to_string();
+        let process = Command::new("ffmpeg")
+            .args(["-loglevel", "error", "-f", CAPTURE.0, "-i", device])
+            .args(["-ac", "1", "-ar", &rate, "-f", "f32le", "-"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn();
+        let mut process = match process {
+            Ok(process) => process,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Err(SketchError::Audio("ffmpeg not found, install it to capture audio".to_string()));
+            }
+            Err(error) => return Err(error.into()),
+        };
+        let mut input = AudioInput::new(CAPTURE_RATE);
+        if let Some(mut output) = process.stdout.take() {
+            let sender = input.sender();
+            thread::spawn(move || {
+                let mut bytes = [0; 4096];
+                // A sample can be split between reads
+                let mut filled = 0;
+                while let Ok(read) = output.read(&mut bytes[filled..]) {
+                    if read == 0 {
+                        break;
+                    }
+                    filled += read;
+                    let whole = filled - filled % 4;
+                    let samples: Vec<f32> = bytes[..whole].chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+                    sender.push(&samples);
+                    bytes.copy_within(whole..filled, 0);
+                    filled -= whole;
+                }
+            });
+        }
+        input.process = Some(process);
+        Ok(input)
+    }
+
+    /// Creates an input without a capture of its own, for samples at `sample_rate` pushed
+    /// through its `sender`.
+    pub fn new(sample_rate: u32) -> Self {
+        let buffer = Buffer { samples: VecDeque::with_capacity(DEFAULT_FFT_SIZE), capacity: DEFAULT_FFT_SIZE };
+        AudioInput { buffer: Arc::new(Mutex::new(buffer)), sample_rate, process: None }
+    }
+
+    /// A handle for pushing samples into the input from any thread, for example from the
+    /// callback of an audio library.
+    pub fn sender(&self) -> AudioSender {
+        AudioSender { buffer: Arc::clone(&self.buffer) }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn fft_size(&self) -> usize {
+        self.buffer().capacity
+    }
+
+    /// Sets how many of the latest samples are analyzed, rounded up to a power of two, at
+    /// least 16. Larger sizes give narrower bins but react slower. Defaults to 1024.
+    pub fn set_fft_size(&mut self, size: usize) {
+        self.buffer().capacity = size.max(16).next_power_of_two();
+    }
+
+    /// Analyzes the latest samples. Until there are as many as the FFT size, the missing
+    /// samples count as silence.
+    pub fn analyze(&self) -> AudioFrame {
+        let mut window = {
+            let buffer = self.buffer();
+            let mut window = vec![0.0; buffer.capacity - buffer.samples.len().min(buffer.capacity)];
+            window.extend(buffer.samples.iter().skip(buffer.samples.len().saturating_sub(buffer.capacity)));
+            window
+        };
+        AudioFrame::analyze(&mut window, self.sample_rate)
+    }
+
+    fn buffer(&self) -> MutexGuard<'_, Buffer> {
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for AudioInput {
+    fn drop(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            let _ = process.kill();
+            let _ = process.wait();
+        }
+    }
+}
+
+/// A sound loaded into memory, such as the soundtrack of a music visualization, analyzed at
+/// any position in it.
+///
+/// Handed to `ApplicationRunner::set_soundtrack`, the clip plays along with the sketch's clock
+/// and `Context::audio` returns the analysis of the position it's at. Headless runners don't
+/// play it, but analyze it the same way, so frames exported with `run_frames` match the sound
+/// when they are put together into a video with it.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simple_sketch::app::{Application, ApplicationRunner, Context};
+/// # use simple_sketch::audio::AudioClip;
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::error::SketchError;
+/// # struct Visualizer;
+/// # impl Application for Visualizer {
+/// #     fn draw(&mut self, _: &mut Canvas, _: &Context) {}
+/// # }
+/// # fn main() -> Result<(), SketchError> {
+/// let mut runner = ApplicationRunner::new(Visualizer)?;
+/// runner.set_soundtrack(Some(AudioClip::load("assets/song.ogg")?));
+/// runner.run()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioClip {
+    /// The file the clip was loaded from, which is what gets played.
+    path: Option<PathBuf>,
+    /// The sound mixed down to mono.
+    samples: Vec<f32>,
+    sample_rate: u32,
+    fft_size: usize,
+}
+
+impl AudioClip {
+    /// Loads a sound file. WAV files are read directly; other formats, such as OGG and MP3, are
+    /// decoded through ffmpeg, which has to be installed for them.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SketchError> {
+        let path = path.as_ref();
+        let is_wav = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("wav"));
+        let (samples, sample_rate) = if is_wav { decode_wav(&fs::read(path)?)? } else { (decode_with_ffmpeg(path)?, CAPTURE_RATE) };
+        Ok(AudioClip { path: Some(path.to_path_buf()), samples, sample_rate, fft_size: DEFAULT_FFT_SIZE })
+    }
+
+    /// Creates a clip from mono samples from -1 to 1 at `sample_rate`, for example generated
+    /// ones. Without a file, the clip can be analyzed but not played.
+    pub fn from_samples(samples: Vec<f32>, sample_rate: u32) -> Self {
+        AudioClip { path: None, samples, sample_rate, fft_size: DEFAULT_FFT_SIZE }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// The sound mixed down to mono.
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// The length of the clip in seconds.
+    pub fn duration(&self) -> f32 {
+        if self.sample_rate == 0 {
+            0.0
+        } else {
+            self.samples.len() as f32 / self.sample_rate as f32
+        }
+    }
+
+    pub fn fft_size(&self) -> usize {
+        self.fft_size
+    }
+
+    /// Sets how many samples are analyzed, like `AudioInput::set_fft_size`.
+    pub fn set_fft_size(&mut self, size: usize) {
+        self.fft_size = size.max(16).next_power_of_two();
+    }
+
+    /// Analyzes the samples up to `seconds` into the clip, as much as the FFT size, which is
+    /// what was heard last at that position. Before the start and after the end is silence.
+    pub fn analyze_at(&self, seconds: f32) -> AudioFrame {
+        let end = (seconds.max(0.0) as f64 * self.sample_rate as f64).round() as usize;
+        let start = end as isize - self.fft_size as isize;
+        let mut window: Vec<f32> = (start..end as isize)
+            .map(|i| usize::try_from(i).ok().and_then(|i| self.samples.get(i)).copied().unwrap_or(0.0))
+            .collect();
+        AudioFrame::analyze(&mut window, self.sample_rate)
+    }
+
+    /// Starts playing the clip's file from `seconds` into it through `ffplay`, which comes with
+    /// ffmpeg. The sound plays until the end of the clip, or until the playback is stopped
+    /// or dropped.
+    pub fn play_from(&self, seconds: f32) -> Result<AudioPlayback, SketchError> {
+        let Some(path) = &self.path else {
+            return Err(SketchError::Audio("clips without a file can't be played".to_string()));
+        };
+        let process = Command::new("ffplay")
+            .args(["-nodisp", "-autoexit", "-loglevel", "error", "-ss", &seconds.max(0.0).to_string()])
+            .arg(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::inherit())
+            .spawn();
+        match process {
+            Ok(process) => Ok(AudioPlayback { process, from: seconds.max(0.0), started: Instant::now() }),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                Err(SketchError::Audio("ffplay not found, install ffmpeg to play audio".to_string()))
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+/// A clip playing through ffplay, started by `AudioClip::play_from`.
+#[derive(Debug)]
+pub struct AudioPlayback {
+    process: Child,
+    from: f32,
+    started: Instant,
+}
+
+impl AudioPlayback {
+    /// Where in the clip the sound should be by now, in seconds.
+    pub fn position(&self) -> f32 {
+        self.from + self.started.elapsed().as_secs_f32()
+    }
+
+    /// Stops the sound, like dropping the playback does.
+    pub fn stop(self) {}
+}
+
+impl Drop for AudioPlayback {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Pushes samples into an `AudioInput`. See `AudioInput::sender`.
+#[derive(Debug, Clone)]
+pub struct AudioSender {
+    buffer: Arc<Mutex<Buffer>>,
+}
+
+impl AudioSender {
+    /// Adds mono samples from -1 to 1 after the ones pushed before.
+    pub fn push(&self, samples: &[f32]) {
+        let mut buffer = self.buffer.lock().unwrap_or_else(PoisonError::into_inner);
+        let skipped = samples.len().saturating_sub(buffer.capacity);
+        buffer.samples.extend(&samples[skipped..]);
+        let excess = buffer.samples.len().saturating_sub(buffer.capacity);
+        buffer.samples.drain(..excess);
+    }
+}
+
+// The latest samples, at most as many as the FFT size
+#[derive(Debug)]
+struct Buffer {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+// The samples of a PCM or floating point WAV file, mixed down to mono, and its sample rate
+fn decode_wav(bytes: &[u8]) -> Result<(Vec<f32>, u32), SketchError> {
+    let invalid = |reason: &str| SketchError::Audio(format!("invalid WAV file: {reason}"));
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(invalid("not a RIFF WAVE file"));
+    }
+    let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+    let u32_at = |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+    let mut format = None;
+    let mut at = 12;
+    while at + 8 <= bytes.len() {
+        let (id, size) = (&bytes[at..at + 4], u32_at(at + 4) as usize);
+        let body = at + 8;
+        let end = body.saturating_add(size).min(bytes.len());
+        if id == b"fmt " && size >= 16 && end - body >= 16 {
+            let mut tag = u16_at(body);
+            // WAVE_FORMAT_EXTENSIBLE keeps the actual format at the start of its subformat
+            if tag == 0xFFFE && end - body >= 26 {
+                tag = u16_at(body + 24);
+            }
+            format = Some((tag, u16_at(body + 2) as usize, u32_at(body + 4), u16_at(body + 14) as usize));
+        } else if id == b"data" {
+            let Some((tag, channels, sample_rate, bits)) = format else {
+                return Err(invalid("the data comes before the format"));
+            };
+            let data = &bytes[body..end];
+            let width = bits / 8;
+            if channels == 0 || width == 0 {
+                return Err(invalid("no channels"));
+            }
+            let decode: fn(&[u8]) -> f32 = match (tag, bits) {
+                (1, 8) => |b| (b[0] as f32 - 128.0) / 128.0,
+                (1, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+                (1, 24) => |b| i32::from_le_bytes([0, b[0], b[1], b[2]]) as f32 / 2_147_483_648.0,
+                (1, 32) => |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+                (3, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+                (3, 64) => |b| f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32,
+                _ => return Err(SketchError::Audio(format!("unsupported WAV format {tag} with {bits} bits per sample"))),
+            };
+            let samples = data
+                .chunks_exact(width * channels)
+                .map(|frame| frame.chunks_exact(width).map(decode).sum::<f32>() / channels as f32)
+                .collect();
+            return Ok((samples, sample_rate));
+        }
+        // Chunks are padded to an even size
+        at = body.saturating_add(size + size % 2);
+    }
+    Err(invalid("no data"))
+}
+
+// Decodes a sound file into mono samples at the capture rate with ffmpeg
+fn decode_with_ffmpeg(path: &Path) -> Result<Vec<f32>, SketchError> {
+    let rate = CAPTURE_RATE.to_string();
+    let output = Command::new("ffmpeg")
+        .args(["-loglevel", "error", "-i"])
+        .arg(path)
+        .args(["-ac", "1", "-ar", &rate, "-f", "f32le", "-"])
+        .stdin(Stdio::null())
+        .output();
+    let output = match output {
+        Ok(output) => output,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            return Err(SketchError::Audio("ffmpeg not found, install it to load audio other than WAV".to_string()));
+        }
+        Err(error) => return Err(error.into()),
+    };
+    if !output.status.success() {
+        let errors = String::from_utf8_lossy(&output.stderr);
+        return Err(SketchError::Audio(format!("ffmpeg failed ({}): {}", output.status, errors.trim())));
+    }
+    Ok(output.stdout.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+}
+
+// The magnitudes of the first half of the FFT of samples windowed with a Hann window,
+// scaled so that a full scale sine peaks at about 1. The length must be a power of two.
+fn spectrum(samples: &mut [f32]) -> Vec<f32> {
+    let size = samples.len();
+    for (i, sample) in samples.iter_mut().enumerate() {
+        *sample *= 0.5 - 0.5 * (2.0 * PI * i as f32 / size as f32).cos();
+    }
+    let re = samples;
+    let mut im = vec![0.0; size];
+    fft(re, &mut im);
+    // The window sums to half the size, and a sine is split between the positive and
+    // negative frequencies
+    let scale = 4.0 / size as f32;
+    (0..size / 2).map(|i| (re[i] * re[i] + im[i] * im[i]).sqrt() * scale).collect()
+}
+
+// An in-place iterative radix-2 FFT
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let size = re.len();
+    let bits = size.trailing_zeros();
+    if size < 2 {
+        return;
+    }
+    for i in 0..size {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut length = 2;
+    while length <= size {
+        let angle = -2.0 * PI / length as f32;
+        for start in (0..size).step_by(length) {
+            for k in 0..length / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + length / 2);
+                let (tr, ti) = (re[b] * cos - im[b] * sin, re[b] * sin + im[b] * cos);
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        length *= 2;
+    }
+}
@@ -0,0 +1,96 @@
+//! Axes for charts and plots: a line from one value to another with evenly spaced ticks and
+//! labels. See `Canvas::axis` and `Canvas::grid`.
+
+use crate::geom::Point;
+
+/// The number of ticks `Axis::new` aims for.
+const TARGET_TICKS: f32 = 6.0;
+
+/// An axis drawn from `start` to `end`, with the value `min` at `start` and `max` at `end`.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::axis::Axis;
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::geom::Point;
+/// # fn draw(canvas: &mut Canvas, readings: Vec<(f32, f32)>) {
+/// // A chart of temperatures over a day
+/// let x_axis = Axis::new(Point::new(40.0, 300.0), Point::new(600.0, 300.0), 0.0, 24.0);
+/// let y_axis = Axis::new(Point::new(40.0, 300.0), Point::new(40.0, 20.0), -10.0, 35.0);
+/// canvas.axis(&x_axis);
+/// canvas.axis(&y_axis);
+/// for (hour, temperature) in readings {
+///     canvas.circle(Point::new(x_axis.position(hour).x, y_axis.position(temperature).y), 4.0);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Axis {
+    pub start: Point,
+    pub end: Point,
+    pub min: f32,
+    pub max: f32,
+    /// The difference in value between ticks, counted from 0. No ticks are drawn for 0.
+    pub tick_spacing: f32,
+    /// How long ticks are. Ticks point down from horizontal axes and left from vertical ones,
+    /// and to the other side with a negative length.
+    pub tick_length: f32,
+    /// Whether the ticks are labeled with their values, in the current font.
+    pub labels: bool,
+}
+
+impl Axis {
+    /// Creates an axis with ticks at round values, about six of them, and labels.
+    pub fn new(start: Point, end: Point, min: f32, max: f32) -> Self {
+        Axis { start, end, min, max, tick_spacing: nice_spacing((max - min).abs() / TARGET_TICKS), tick_length: 6.0, labels: true }
+    }
+
+    /// Where `value` lies on the axis. Values outside `min` to `max` lie beyond its ends.
+    pub fn position(&self, value: f32) -> Point {
+        let t = if self.max == self.min { 0.0 } else { (value - self.min) / (self.max - self.min) };
+        self.start.lerp(&self.end, t)
+    }
+
+    /// The values of the ticks from `min` to `max`.
+    pub fn ticks(&self) -> impl Iterator<Item = f32> {
+        let (low, high) = (self.min.min(self.max), self.min.max(self.max));
+        let spacing = self.tick_spacing.abs();
+        let (first, last) = if spacing > 0.0 && low.is_finite() && high.is_finite() {
+            // A little slack keeps ticks on the ends despite rounding
+            let slack = spacing * 1e-4;
+            (((low - slack) / spacing).ceil() as i64, ((high + slack) / spacing).floor() as i64)
+        } else {
+            (1, 0)
+        };
+        (first..=last).map(move |i| i as f32 * spacing)
+    }
+
+    /// The label of a tick: its value with as many decimals as the tick spacing needs.
+    pub fn label(&self, value: f32) -> String {
+        let decimals = if self.tick_spacing.abs() > 0.0 { (-self.tick_spacing.abs().log10().floor()).max(0.0) as usize } else { 0 };
+        // Avoid labeling zero as "-0"
+        let value = if value.abs() < self.tick_spacing.abs() * 1e-4 { 0.0 } else { value };
+        format!("{value:.decimals$}")
+    }
+
+    /// The unit vector that ticks point along.
+    pub(crate) fn tick_direction(&self) -> Point {
+        let normal = (self.end - self.start).normalize().perpendicular();
+        if normal.y < 0.0 || (normal.y == 0.0 && normal.x > 0.0) {
+            normal * -1.0
+        } else {
+            normal
+        }
+    }
+}
+
+// The smallest of 1, 2 and 5 times a power of ten that is at least `spacing`
+fn nice_spacing(spacing: f32) -> f32 {
+    if spacing <= 0.0 || !spacing.is_finite() {
+        return 0.0;
+    }
+    let power = 10f32.powf(spacing.log10().floor());
+    [1.0, 2.0, 5.0, 10.0].into_iter().map(|factor| factor * power).find(|&nice| nice >= spacing * 0.9999).unwrap_or(10.0 * power)
+}
@@ -0,0 +1,169 @@
+//! Windowing backends: what opens the window, shows frames in it and reads its input.
+//!
+//! `ApplicationRunner::new` uses `MinifbBackend`. Other windowing libraries, like winit with
+//! softbuffer or SDL2, can be plugged in by implementing `Backend` and passing it to
+//! `ApplicationRunner::with_backend`. `TerminalBackend` shows sketches in a terminal instead.
+
+use std::path::PathBuf;
+
+#[cfg(not(target_arch = "wasm32"))]
+use minifb::{CursorStyle as WindowCursor, MouseMode, ScaleMode, Window, WindowOptions};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::app::Settings;
+use crate::cursor::CursorStyle;
+use crate::error::SketchError;
+use crate::geom::Point;
+use crate::input::Key;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::input::MouseButton;
+
+/// A window that shows the frames of a sketch and reports its input.
+///
+/// The runner calls `input` once per frame, then `present` or `present_changed` with the
+/// finished frame, or `poll` while it waits for one or when the frame didn't change.
+pub trait Backend {
+    /// Whether the window is still open. The sketch stops once it is closed.
+    fn is_open(&self) -> bool;
+
+    /// The state of the window's size and input devices right now, and the files dropped onto
+    /// it since the previous call.
+    fn input(&mut self) -> InputState;
+
+    /// Shows a frame of `width` by `height` pixels in `0RGB` format, row by row, and processes
+    /// the window's events. The frame may be larger than the window when the pixel density
+    /// isn't 1, in which case it is scaled down to fit.
+    fn present(&mut self, pixels: &[u32], width: usize, height: usize) -> Result<(), SketchError>;
+
+    /// Shows a frame that only differs from the previously presented one within `changed`.
+    /// Backends that can upload part of a frame override it; the default presents the whole
+    /// frame. The runner calls `poll` instead when nothing changed at all, so backends must
+    /// keep showing the last frame until they are given another.
+    fn present_changed(&mut self, pixels: &[u32], width: usize, height: usize, changed: FrameRegion) -> Result<(), SketchError> {
+        let _ = changed;
+        self.present(pixels, width, height)
+    }
+
+    /// Processes the window's events without showing a new frame.
+    fn poll(&mut self) -> Result<(), SketchError>;
+
+    /// Shows or hides the system cursor over the window and sets its style.
+    fn set_cursor(&mut self, visible: bool, style: CursorStyle);
+}
+
+/// A rectangle of pixels in a frame, from `(x, y)` up to but not including
+/// `(x + width, y + height)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameRegion {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// The size of a window and the state of its input devices, sampled once per frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputState {
+    /// The size of the window in window pixels. A size of zero, as reported by minimized
+    /// windows, leaves the canvas as it is.
+    pub size: (usize, usize),
+    /// The mouse position in window pixels, or None when it is outside the window.
+    pub mouse_position: Option<Point>,
+    /// Whether each mouse button is held down, indexed by `MouseButton::index`.
+    pub mouse_pressed: [bool; 3],
+    /// How far the mouse wheel turned since the previous sample, if it did.
+    pub wheel: Option<Point>,
+    /// The keys held down, in no particular order.
+    pub keys: Vec<Key>,
+    /// The files dropped onto the window since the previous sample. Always empty for backends
+    /// without drag and drop.
+    pub dropped_files: Vec<PathBuf>,
+}
+
+/// The default backend, a minifb window.
+///
+/// minifb doesn't report resize events or dropped files, so resizes are detected by
+/// comparing the window size between frames, and no files are ever dropped. Not available on
+/// wasm32, where `web::WebBackend` shows sketches instead.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct MinifbBackend {
+    window: Window,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl MinifbBackend {
+    /// Opens a window configured by `settings`.
+    pub fn new(settings: &Settings) -> Result<Self, SketchError> {
+        if settings.width == 0 || settings.height == 0 {
+            return Err(SketchError::InvalidSize { width: settings.width, height: settings.height });
+        }
+        let options = WindowOptions {
+            resize: settings.resizable,
+            // Until the canvas catches up with a new window size, show it unscaled. Denser
+            // canvases are scaled down to the window, which shows them at native resolution
+            // on high-DPI displays.
+            scale_mode: if settings.pixel_density == 1.0 { ScaleMode::UpperLeft } else { ScaleMode::AspectRatioStretch },
+            ..WindowOptions::default()
+        };
+        let mut window = Window::new(&settings.title, settings.width, settings.height, options).map_err(|e| SketchError::WindowCreation(e.to_string()))?;
+        window.set_target_fps(settings.frame_rate.round() as usize);
+        if let Some((x, y)) = settings.position {
+            window.set_position(x, y);
+        }
+        Ok(MinifbBackend { window })
+    }
+
+    /// The minifb window, for features this crate doesn't wrap.
+    pub fn window(&mut self) -> &mut Window {
+        &mut self.window
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Backend for MinifbBackend {
+    fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+
+    fn input(&mut self) -> InputState {
+        let window = &self.window;
+        let mouse_pressed = MouseButton::ALL.map(|button| {
+            window.get_mouse_down(match button {
+                MouseButton::Left => minifb::MouseButton::Left,
+                MouseButton::Middle => minifb::MouseButton::Middle,
+                MouseButton::Right => minifb::MouseButton::Right,
+            })
+        });
+        InputState {
+            size: window.get_size(),
+            mouse_position: window.get_mouse_pos(MouseMode::Discard).map(|(x, y)| Point::new(x, y)),
+            mouse_pressed,
+            wheel: window.get_scroll_wheel().map(|(x, y)| Point::new(x, y)),
+            keys: window.get_keys(),
+            dropped_files: Vec::new(),
+        }
+    }
+
+    fn present(&mut self, pixels: &[u32], width: usize, height: usize) -> Result<(), SketchError> {
+        self.window.update_with_buffer(pixels, width, height).map_err(|e| SketchError::WindowUpdate(e.to_string()))
+    }
+
+    fn poll(&mut self) -> Result<(), SketchError> {
+        self.window.update();
+        Ok(())
+    }
+
+    fn set_cursor(&mut self, visible: bool, style: CursorStyle) {
+        self.window.set_cursor_visibility(visible);
+        self.window.set_cursor_style(match style {
+            CursorStyle::Arrow => WindowCursor::Arrow,
+            CursorStyle::Text => WindowCursor::Ibeam,
+            CursorStyle::Crosshair => WindowCursor::Crosshair,
+            CursorStyle::OpenHand => WindowCursor::OpenHand,
+            CursorStyle::ClosedHand => WindowCursor::ClosedHand,
+            CursorStyle::ResizeHorizontal => WindowCursor::ResizeLeftRight,
+            CursorStyle::ResizeVertical => WindowCursor::ResizeUpDown,
+            CursorStyle::Move => WindowCursor::ResizeAll,
+        });
+    }
+}
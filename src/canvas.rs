@@ -1,7 +1,43 @@
-use crate::pixelbuffer::PixelBuffer;
+use crate::pixelbuffer::{ImageFormat, PixelBuffer};
 use crate::color::Color;
-use crate::geom::Point;
-use crate::shape::{Shape, Ellipse, Rectangle};
+use crate::geom::{Point, Line, Rect, OrientedRect, CubicBezier, Path, Polyline, Transform};
+use crate::shape::{Shape, Arc, ArcMode, Capsule, Circle, Ellipse, FillOutline, Polygon, Rectangle, RegularPolygon, RoundedRect, Outline, Star, Transformed, Triangle};
+use crate::command::{DrawCommand, Style, DepthEntry, RecordedCommand};
+use crate::instance::{self, Instance, CoverageMask};
+use crate::pick::{PickEntry, ShapeId};
+use crate::mask_cache::{MaskCache, ShapeKey};
+use crate::raster::{self, FillCoverage, Precision, StrokeCoverage};
+use crate::renderer::Renderer;
+use crate::cursor::SoftwareCursor;
+use crate::error::SketchError;
+use crate::pdf::{self, Page};
+use crate::image::{self, Image, ImageFilter, Pattern};
+use crate::svg::SvgDocument;
+use crate::scene::Scene;
+use crate::display_list::DisplayList;
+use crate::font::{Font, FontError, HorizontalAlign, VerticalAlign};
+use crate::bitmap_font::{self, GLYPH_SIZE};
+use crate::layer::Layer;
+use crate::postprocess::Effect;
+use crate::axis::Axis;
+use crate::metaball::{Metaball, Metaballs};
+use crate::profiler::{Stage, StageTimes};
+
+/// Maximum distance in pixels between a curve and the polyline used to draw it.
+const CURVE_TOLERANCE: f32 = 0.25;
+
+/// Distance in pixels beyond the canvas edges at which lines are clipped.
+const LINE_CLIP_MARGIN: f32 = 2.0;
+
+/// Size in pixels of the cells metaballs are traced on.
+const METABALL_CELL_SIZE: f32 = 3.0;
+
+/// Pixels a shape has to cover before it is filled and stroked on several threads. Smaller
+/// shapes are done before the threads would be started.
+const PARALLEL_SHADING_PIXELS: usize = 128 * 128;
+
+/// Distance in pixels between the lines of `debug_text`.
+const DEBUG_LINE_HEIGHT: usize = 10;
 
 pub struct Canvas {
     pub width: usize,
@@ -12,6 +48,52 @@ pub struct Canvas {
     fill_color: Option<Color>,
     stroke_color: Option<Color>,
     stroke_weight: f32,
+    depth: Option<f32>,
+    depth_queue: Vec<DepthEntry>,
+    transform: Transform,
+    saved_states: Vec<(Transform, Style)>,
+    pick_id: Option<ShapeId>,
+    picks: Vec<PickEntry>,
+    shape_caching: bool,
+    mask_cache: MaskCache,
+    /// How outlines and their strokes are rasterized.
+    precision: Precision,
+    pixel_density: f32,
+    /// Where the pixel buffer starts within the full picture, in buffer pixels, when it holds
+    /// one tile of a larger picture. See `TiledRenderer`.
+    tile_origin: Point,
+    /// Commands drawn since recording started, or None when not recording.
+    recording: Option<Vec<RecordedCommand>>,
+    /// Whether commands are only recorded and not drawn, while recording a display list.
+    record_only: bool,
+    /// Commands drawn since the command log started, in canvas pixels, or None when not logging.
+    command_log: Option<Scene>,
+    /// The font of `text`, or None before one is set.
+    font: Option<Font>,
+    /// The font size of `text` in pixels.
+    text_size: f32,
+    text_align: (HorizontalAlign, VerticalAlign),
+    /// Whether `text` shapes its lines with the font's layout tables.
+    text_shaping: bool,
+    image_filter: ImageFilter,
+    /// The color images are multiplied by, or None to draw them as they are.
+    tint: Option<Color>,
+    /// The image repeated inside filled shapes instead of the fill color, or None.
+    fill_pattern: Option<Pattern>,
+    /// The layers above the canvas, from the bottom up.
+    layers: Vec<Layer>,
+    /// The effects applied to the frame after compositing the layers, in order.
+    post_effects: Vec<Effect>,
+    /// The pixels as drawn while the pixel buffer holds the frame as it is shown, with the
+    /// layers and post effects.
+    drawn_pixels: Option<Vec<u32>>,
+    /// Draws shapes, lines, curves and paths in place of the canvas's own rasterizer, or None.
+    renderer: Option<Box<dyn Renderer>>,
+    /// Whether the renderer has begun drawing and holds pixels not yet written to the buffer.
+    renderer_active: bool,
+    /// Time spent drawing lines, fills and strokes since it was last taken, or None when
+    /// drawing isn't timed.
+    draw_times: Option<StageTimes>,
 }
 
 impl Canvas {
@@ -25,11 +107,280 @@ impl Canvas {
             stroke: true,
             stroke_color: None,
             stroke_weight: 1.0,
+            depth: None,
+            depth_queue: Vec::new(),
+            transform: Transform::identity(),
+            saved_states: Vec::new(),
+            pick_id: None,
+            picks: Vec::new(),
+            shape_caching: true,
+            mask_cache: MaskCache::default(),
+            precision: Precision::Float,
+            pixel_density: 1.0,
+            tile_origin: Point::new(0.0, 0.0),
+            recording: None,
+            record_only: false,
+            command_log: None,
+            font: None,
+            text_size: 12.0,
+            text_align: (HorizontalAlign::Left, VerticalAlign::Baseline),
+            text_shaping: false,
+            image_filter: ImageFilter::Bilinear,
+            tint: None,
+            fill_pattern: None,
+            layers: Vec::new(),
+            post_effects: Vec::new(),
+            drawn_pixels: None,
+            renderer: None,
+            renderer_active: false,
+            draw_times: None,
         }
     }
 
+    /// Creates an offscreen canvas of `width` by `height` logical pixels at the pixel density of
+    /// this one, like Processing's `createGraphics()`. It starts out fully transparent, keeps
+    /// what is drawn on it from frame to frame and isn't shown until drawn onto another canvas
+    /// with `draw_canvas`, with its transparency. Everything else, like the transform and
+    /// font, starts out as on a new canvas.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::color::Color;
+    /// # use simple_sketch::geom::Point;
+    /// # fn draw(canvas: &mut Canvas, mouse: Point) {
+    /// // In setup: a layer for trails that fade out while the main canvas is cleared
+    /// let mut trails = canvas.create_graphics(canvas.width, canvas.height);
+    ///
+    /// // In draw
+    /// trails.set_fill(Some(Color::new(255, 200, 0, 255)));
+    /// trails.circle(mouse, 8.0);
+    /// canvas.background(Color::new(20, 20, 30, 255));
+    /// canvas.draw_canvas(&trails, Point::new(0.0, 0.0));
+    /// # }
+    /// ```
+    pub fn create_graphics(&self, width: usize, height: usize) -> Canvas {
+        Canvas::with_pixel_density(width, height, self.pixel_density)
+    }
+
+    /// Creates a canvas of `width` by `height` logical pixels, backed by `pixel_density` pixels
+    /// in the pixel buffer for every logical pixel along each axis. See `set_pixel_density`.
+    pub fn with_pixel_density(width: usize, height: usize, pixel_density: f32) -> Self {
+        let mut canvas = Canvas::new(width, height);
+        canvas.set_pixel_density(pixel_density);
+        canvas
+    }
+
+    // Creates a canvas of `width` by `height` logical pixels at `pixel_density` whose pixel
+    // buffer only holds the `tile_width` by `tile_height` pixels starting at `tile_origin`.
+    pub(crate) fn tile(width: usize, height: usize, pixel_density: f32, tile_origin: Point, tile_width: usize, tile_height: usize) -> Self {
+        let mut canvas = Canvas::new(0, 0);
+        (canvas.width, canvas.height) = (width, height);
+        canvas.pixel_density = pixel_density;
+        canvas.tile_origin = tile_origin;
+        canvas.pixel_buffer.resize(tile_width, tile_height);
+        canvas
+    }
+
+    // A canvas of `width` by `height` logical pixels without pixels, that records the commands
+    // drawn on it instead of rasterizing them. See `DisplayList::record`.
+    pub(crate) fn recorder(width: usize, height: usize) -> Self {
+        let mut canvas = Canvas::tile(width, height, 1.0, Point::new(0.0, 0.0), 0, 0);
+        canvas.recording = Some(Vec::new());
+        canvas.record_only = true;
+        canvas
+    }
+
+    /// Changes the size of the canvas, in logical pixels. The pixels that fit in the new size
+    /// are kept, new pixels are black.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.sync_renderer();
+        self.width = width;
+        self.height = height;
+        let (buffer_width, buffer_height) = self.buffer_size();
+        self.pixel_buffer.resize(buffer_width, buffer_height);
+        for layer in &mut self.layers {
+            layer.canvas.pixel_density = self.pixel_density;
+            layer.canvas.resize(width, height);
+        }
+    }
+
+    /// Saves the canvas as a PNG file, at the resolution of its pixel buffer. See
+    /// `PixelBuffer::save_png`.
+    pub fn save_png(&self, path: impl AsRef<std::path::Path>) -> Result<(), SketchError> {
+        self.pixel_buffer.save_png(path)
+    }
+
+    /// Saves the canvas in `format`, at the resolution of its pixel buffer. See
+    /// `PixelBuffer::save`.
+    pub fn save(&self, path: impl AsRef<std::path::Path>, format: ImageFormat) -> Result<(), SketchError> {
+        self.pixel_buffer.save(path, format)
+    }
+
+    /// Saves the part of the canvas inside `rect`, in canvas pixels, as a PNG file at the
+    /// resolution of the pixel buffer, ignoring the canvas transform. See `PixelBuffer::crop`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::error::SketchError;
+    /// # use simple_sketch::geom::{Point, Rect};
+    /// # fn draw(canvas: &mut Canvas) -> Result<(), SketchError> {
+    /// // The top right quarter of the canvas
+    /// let quarter = Rect::new(Point::new(canvas.width as f32 / 2.0, 0.0), Point::new(canvas.width as f32, canvas.height as f32 / 2.0));
+    /// canvas.save_region_png(quarter, "detail.png")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn save_region_png(&self, rect: Rect, path: impl AsRef<std::path::Path>) -> Result<(), SketchError> {
+        let scale = self.device_scale();
+        let region = Rect::from_points(scale.transform_point(rect.min), scale.transform_point(rect.max));
+        let region = self.pixel_buffer.crop(region);
+        if region.width == 0 || region.height == 0 {
+            return Err(SketchError::InvalidSize { width: region.width, height: region.height });
+        }
+        region.save_png(path)
+    }
+
+    /// Starts keeping a copy of every command as it is drawn, for vector output with `save_pdf`.
+    /// Commands are kept in the order they reach the pixels, after transforms and depth
+    /// sorting. An opaque background discards the commands before it, since it covers them.
+    /// `draw_shape_transformed`, `draw_shapes` and instanced drawing bypass the commands and
+    /// aren't recorded.
+    pub fn start_recording_commands(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    /// Stops recording commands and returns the recorded ones.
+    pub fn stop_recording_commands(&mut self) -> Vec<RecordedCommand> {
+        self.recording.take().unwrap_or_default()
+    }
+
+    /// The commands recorded so far, empty when not recording.
+    pub fn recorded_commands(&self) -> &[RecordedCommand] {
+        self.recording.as_deref().unwrap_or_default()
+    }
+
+    /// Starts logging every command as it is drawn, in canvas pixels and with stroke weights in
+    /// canvas pixels, so that the drawing can be replayed onto any canvas with `draw_scene`,
+    /// whatever its pixel density, or saved with `Scene::save` for regression tests. Commands
+    /// are logged in the order they reach the pixels, like with `start_recording_commands`, and
+    /// an opaque background discards the commands before it. Images, `draw_shape_transformed`,
+    /// `draw_shapes` and instanced drawing aren't logged.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::error::SketchError;
+    /// # struct Sketch;
+    /// # impl Sketch { fn draw(&mut self, _: &mut Canvas) {} }
+    /// # fn draw(mut canvas: Canvas, mut sketch: Sketch) -> Result<(), SketchError> {
+    /// canvas.start_command_log();
+    /// sketch.draw(&mut canvas);
+    /// let log = canvas.stop_command_log();
+    /// log.save("frames/0001.scene")?;
+    ///
+    /// let mut replay = Canvas::with_pixel_density(canvas.width, canvas.height, 2.0);
+    /// replay.draw_scene(&log);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_command_log(&mut self) {
+        self.command_log = Some(Scene::new());
+    }
+
+    /// Stops logging commands and returns the logged ones as a scene.
+    pub fn stop_command_log(&mut self) -> Scene {
+        self.command_log.take().unwrap_or_default()
+    }
+
+    /// The commands logged so far, or None when not logging.
+    pub fn command_log(&self) -> Option<&Scene> {
+        self.command_log.as_ref()
+    }
+
+    /// Saves the recorded commands as a one page PDF document, for printing. The page is as
+    /// large as the canvas at `dpi` canvas pixels per inch, so a 3000 pixel wide canvas at
+    /// 300 dpi makes a 10 inch wide page. Queued commands from `with_depth` are only
+    /// recorded once `flush` draws them.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::error::SketchError;
+    /// # use simple_sketch::geom::Point;
+    /// # fn draw(canvas: &mut Canvas) -> Result<(), SketchError> {
+    /// canvas.start_recording_commands();
+    /// canvas.circle(Point::new(50.0, 50.0), 80.0);
+    /// canvas.save_pdf("print.pdf", 300.0)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn save_pdf(&self, path: impl AsRef<std::path::Path>, dpi: f32) -> Result<(), SketchError> {
+        let path = path.as_ref();
+        let points_per_pixel = 72.0 / dpi;
+        let page = Page {
+            width: self.width as f32 * points_per_pixel,
+            height: self.height as f32 * points_per_pixel,
+            // Recorded commands are in buffer pixels
+            scale: points_per_pixel / self.pixel_density,
+        };
+        let save = || {
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            let out = std::io::BufWriter::new(std::fs::File::create(path)?);
+            pdf::write_pdf(out, self.recorded_commands(), &page)
+        };
+        save().map_err(|e| std::io::Error::new(e.kind(), format!("could not save {}: {e}", path.display())).into())
+    }
+
+    /// Sets the number of buffer pixels per logical pixel along each axis, like Processing's
+    /// `pixelDensity()`. Drawing calls, stroke weights and picking stay in logical pixels, so a
+    /// density of 2 draws the same picture with twice the resolution, for high-DPI displays.
+    /// `width` and `height` stay the logical size, and the pixel buffer grows instead.
+    pub fn set_pixel_density(&mut self, pixel_density: f32) {
+        self.pixel_density = if pixel_density.is_finite() && pixel_density > 0.0 { pixel_density } else { 1.0 };
+        self.resize(self.width, self.height);
+    }
+
+    pub fn pixel_density(&self) -> f32 {
+        self.pixel_density
+    }
+
+    // The size of the pixel buffer for the logical size and density
+    fn buffer_size(&self) -> (usize, usize) {
+        let scale = |size: usize| (size as f32 * self.pixel_density).round() as usize;
+        (scale(self.width), scale(self.height))
+    }
+
+    // The transform from the coordinates of drawing calls to buffer pixels
+    fn device_transform(&self) -> Transform {
+        self.device_scale() * self.transform
+    }
+
+    // The transform from logical pixels to buffer pixels
+    fn device_scale(&self) -> Transform {
+        Transform::translate(Point::new(0.0, 0.0) - self.tile_origin) * Transform::scale(self.pixel_density, self.pixel_density)
+    }
+
+    // The stroke weight in buffer pixels
+    fn stroke_pixels(&self) -> f32 {
+        self.stroke_weight * self.pixel_density
+    }
+
     pub fn background(&mut self, color: Color) {
-        self.pixel_buffer.clear(color);
+        self.submit(DrawCommand::Background(color));
+    }
+
+    /// Makes every pixel fully transparent, like Processing's `clear()`, for offscreen canvases
+    /// from `create_graphics` that are redrawn from scratch.
+    pub fn clear(&mut self) {
+        self.background(Color::new(0, 0, 0, 0));
     }
 
     pub fn set_fill(&mut self, color: Option<Color>) {
@@ -45,11 +396,518 @@ impl Canvas {
     pub fn set_stroke_weight(&mut self, weight: f32) {
         self.stroke_weight = weight;
     }
+
+    /// Sets an image to repeat inside filled shapes in place of the fill color, or None to fill
+    /// with the color again. Shapes are only filled while there is a fill color, whose alpha
+    /// fades the pattern. The pattern is placed in canvas pixels, ignoring the canvas transform,
+    /// so shapes side by side continue the same wallpaper, and it is sampled with the image
+    /// filter.
+    ///
+    /// Calls queued by `with_depth` are filled with the pattern set when they are flushed.
+    /// Recorded commands, logs and PDF output keep the fill color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::color::Color;
+    /// # use simple_sketch::geom::Point;
+    /// # use simple_sketch::image::{Image, Pattern};
+    /// # fn draw(canvas: &mut Canvas, halftone: &Image, center: Point) {
+    /// canvas.set_fill(Some(Color::new(255, 255, 255, 255)));
+    /// canvas.set_fill_pattern(Some(Pattern::placed(halftone.clone(), Point::new(0.0, 0.0), 2.0, 0.3)));
+    /// canvas.star(center, 5, 60.0, 150.0, 0.0);
+    /// canvas.set_fill_pattern(None);
+    /// # }
+    /// ```
+    pub fn set_fill_pattern(&mut self, pattern: Option<Pattern>) {
+        self.fill_pattern = pattern;
+    }
+
+    pub fn fill_pattern(&self) -> Option<&Pattern> {
+        self.fill_pattern.as_ref()
+    }
+
+    pub fn fill_color(&self) -> Option<Color> {
+        self.fill_color
+    }
+
+    pub fn stroke_color(&self) -> Option<Color> {
+        self.stroke_color
+    }
+
+    pub fn stroke_weight(&self) -> f32 {
+        self.stroke_weight
+    }
+
+    pub fn style(&self) -> Style {
+        Style {
+            fill: self.fill_color,
+            stroke: self.stroke_color,
+            stroke_weight: self.stroke_weight,
+        }
+    }
+
+    pub fn set_style(&mut self, style: Style) {
+        self.set_fill(style.fill);
+        self.set_stroke(style.stroke);
+        self.set_stroke_weight(style.stroke_weight);
+    }
+
+    /// The transform applied to the coordinates of every drawing call.
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+    }
+
+    pub fn reset_transform(&mut self) {
+        self.transform = Transform::identity();
+    }
+
+    /// Moves the origin of subsequent drawing calls by `offset`.
+    pub fn translate(&mut self, offset: Point) {
+        self.transform = self.transform * Transform::translate(offset);
+    }
+
+    /// Rotates subsequent drawing calls clockwise around the current origin by `angle` radians.
+    pub fn rotate(&mut self, angle: f32) {
+        self.transform = self.transform * Transform::rotate(angle);
+    }
+
+    /// Scales subsequent drawing calls around the current origin. Stroke weights are not scaled.
+    pub fn scale(&mut self, sx: f32, sy: f32) {
+        self.transform = self.transform * Transform::scale(sx, sy);
+    }
+
+    /// Saves the current transform and style, to be restored by the matching `pop`.
+    pub fn push(&mut self) {
+        self.saved_states.push((self.transform, self.style()));
+    }
+
+    /// Restores the transform and style saved by the last `push`. Does nothing if there is none.
+    pub fn pop(&mut self) {
+        if let Some((transform, style)) = self.saved_states.pop() {
+            self.transform = transform;
+            self.set_style(style);
+        }
+    }
+
+    /// Runs `draw` with every drawing call assigned the given depth. Instead of being
+    /// rasterized right away, those calls are queued and drawn by `flush` from the lowest
+    /// depth to the highest, so higher depths end up on top. Calls with equal depth keep
+    /// their call order, and calls made outside `with_depth` are drawn immediately.
+    pub fn with_depth<F: FnOnce(&mut Canvas)>(&mut self, depth: f32, draw: F) {
+        let previous = self.depth.replace(depth);
+        draw(self);
+        self.depth = previous;
+    }
+
+    /// Runs `draw` with every drawing call registered for picking under `id`, so that `pick`
+    /// can find it later. Several calls may share an id to be picked as one shape.
+    pub fn with_pick_id<F: FnOnce(&mut Canvas)>(&mut self, id: ShapeId, draw: F) {
+        let previous = self.pick_id.replace(id);
+        draw(self);
+        self.pick_id = previous;
+    }
+
+    /// Runs `draw` with everything it draws masked by an image placed like `image(mask,
+    /// position)`, through the canvas transform: where the mask is white the drawing shows,
+    /// where it is black or transparent the canvas is left as it was, and grays blend the two.
+    /// Nothing shows outside the mask. Calls queued by `with_depth` inside `draw` are drawn
+    /// by `flush` without the mask.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::geom::Point;
+    /// # use simple_sketch::image::Image;
+    /// # fn draw(canvas: &mut Canvas, vignette: &Image, photo: &Image) {
+    /// // Darken the edges of the sketch with a radial gradient that is white in the middle
+    /// canvas.with_mask(&vignette, Point::new(0.0, 0.0), |canvas| {
+    ///     canvas.image(&photo, Point::new(0.0, 0.0));
+    /// });
+    /// # }
+    /// ```
+    pub fn with_mask<F: FnOnce(&mut Canvas)>(&mut self, mask: &Image, position: Point, draw: F) {
+        let (width, height) = (self.pixel_buffer.width, self.pixel_buffer.height);
+        let mut coverage = vec![0.0; width * height];
+        let to_mask = (self.device_transform() * Transform::translate(position)).inverse();
+        if let (Some(to_mask), true) = (to_mask, mask.width > 0 && mask.height > 0) {
+            let texels = (0, 0, mask.width - 1, mask.height - 1);
+            let size = (mask.width as f32, mask.height as f32);
+            for y in 0..height {
+                for x in 0..width {
+                    let point = to_mask.transform_point(Point::new(x as f32 + 0.5, y as f32 + 0.5));
+                    if (0.0..size.0).contains(&point.x) && (0.0..size.1).contains(&point.y) {
+                        coverage[y * width + x] = image::mask_value(mask.sample_bilinear(texels, point.x - 0.5, point.y - 0.5));
+                    }
+                }
+            }
+        }
+        self.draw_masked(&coverage, draw);
+    }
+
+    /// Runs `draw` with everything it draws clipped to `shape`, through the canvas transform,
+    /// with antialiased edges. See `with_mask`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::geom::{Point, Rect};
+    /// # use simple_sketch::image::Image;
+    /// # use simple_sketch::shape::Circle;
+    /// # fn draw(canvas: &mut Canvas, avatar: &Image) {
+    /// // A round avatar
+    /// let center = Point::new(60.0, 60.0);
+    /// canvas.with_mask_shape(&Circle::new(center, 50.0), |canvas| {
+    ///     canvas.image_scaled(&avatar, Rect::new(center - Point::new(50.0, 50.0), center + Point::new(50.0, 50.0)));
+    /// });
+    /// # }
+    /// ```
+    pub fn with_mask_shape<F: FnOnce(&mut Canvas)>(&mut self, shape: &dyn Shape, draw: F) {
+        let shape = Transformed { shape, transform: self.device_transform() };
+        let coverage = image::shape_coverage(&shape, self.pixel_buffer.width, self.pixel_buffer.height, self.precision);
+        self.draw_masked(&coverage, draw);
+    }
+
+    // Runs `draw` and keeps what it drew in proportion to the coverage of every buffer pixel
+    fn draw_masked<F: FnOnce(&mut Canvas)>(&mut self, coverage: &[f32], draw: F) {
+        self.sync_renderer();
+        let size = (self.pixel_buffer.width, self.pixel_buffer.height);
+        let before = self.pixel_buffer.get_buffer().to_vec();
+        draw(self);
+        self.sync_renderer();
+        // A resized canvas can't be matched with the mask
+        if (self.pixel_buffer.width, self.pixel_buffer.height) != size {
+            return;
+        }
+        for ((pixel, &previous), &coverage) in self.pixel_buffer.get_buffer_mut().iter_mut().zip(&before).zip(coverage) {
+            if coverage < 1.0 && *pixel != previous {
+                let blended = Color(previous).lerp(&Color(*pixel), coverage);
+                *pixel = blended.0;
+            }
+        }
+    }
+
+    /// Finds the topmost drawing call registered with `with_pick_id` whose fill or stroke
+    /// covers `point`, in canvas pixels, taking the depth order of `with_depth` into account.
+    /// Returns its id, or None if no registered call was drawn there.
+    pub fn pick(&self, point: Point) -> Option<ShapeId> {
+        // Entries are kept in buffer pixels
+        let point = self.device_scale().transform_point(point);
+        let mut topmost: Option<&PickEntry> = None;
+        for entry in self.picks.iter().filter(|entry| entry.hit(point)) {
+            if topmost.is_none_or(|topmost| entry.is_above(topmost)) {
+                topmost = Some(entry);
+            }
+        }
+        topmost.map(|entry| entry.id)
+    }
+
+    /// Forgets all drawing calls registered for picking. Drawing a background outside of
+    /// `with_depth` does this too, since it covers everything drawn before.
+    pub fn clear_picks(&mut self) {
+        self.picks.clear();
+    }
+
+    /// Rasterizes all calls queued by `with_depth`, sorted by depth.
+    pub fn flush(&mut self) {
+        let mut queue = std::mem::take(&mut self.depth_queue);
+        // Stable sort keeps call order within the same depth
+        queue.sort_by(|a, b| a.depth.total_cmp(&b.depth));
+
+        let style = self.style();
+        for entry in &queue {
+            self.set_style(entry.style);
+            self.execute(&entry.command);
+        }
+        self.set_style(style);
+        self.sync_renderer();
+    }
+
+    /// Sets the renderer that draws shapes, lines, curves and paths from now on, or None for the
+    /// canvas to rasterize them itself again, which is the default. What the renderer draws
+    /// shows up in `pixel_buffer` after `flush`, which ends every frame, or as soon as the
+    /// canvas draws something the renderer doesn't, like an image. Fills with a pattern are
+    /// always drawn by the canvas. See the `renderer` module.
+    pub fn set_renderer(&mut self, renderer: Option<Box<dyn Renderer>>) {
+        self.sync_renderer();
+        self.renderer = renderer;
+    }
+
+    pub fn renderer(&self) -> Option<&dyn Renderer> {
+        self.renderer.as_deref()
+    }
+
+    // Writes what the renderer drew back into the pixel buffer, before the canvas reads or
+    // draws pixels itself.
+    fn sync_renderer(&mut self) {
+        if let (Some(renderer), true) = (&mut self.renderer, self.renderer_active) {
+            renderer.finish(&mut self.pixel_buffer);
+            self.renderer_active = false;
+        }
+    }
+
+    fn submit(&mut self, command: DrawCommand) {
+        let transform = self.device_transform();
+        let command = if transform == Transform::identity() {
+            command
+        } else {
+            command.transformed(&transform)
+        };
+        if let Some(id) = self.pick_id {
+            let style = Style { stroke_weight: self.stroke_pixels(), ..self.style() };
+            self.picks.extend(PickEntry::new(id, style, self.depth, &command));
+        } else if matches!(command, DrawCommand::Background(_)) && self.depth.is_none() {
+            self.picks.clear();
+        }
+        match self.depth {
+            Some(depth) => {
+                let style = self.style();
+                self.depth_queue.push(DepthEntry { depth, style, command });
+            }
+            None => self.execute(&command),
+        }
+    }
+
+    pub(crate) fn execute(&mut self, command: &DrawCommand) {
+        let style = Style { stroke_weight: self.stroke_pixels(), ..self.style() };
+        if let Some(recording) = &mut self.recording {
+            if matches!(command, DrawCommand::Background(color) if color.a() == 255) {
+                recording.clear();
+            }
+            recording.push(RecordedCommand { command: command.clone(), style });
+            if self.record_only {
+                return;
+            }
+        }
+        if self.command_log.is_some() {
+            let to_canvas = self.device_scale().inverse().unwrap_or_else(Transform::identity);
+            let logged = if to_canvas == Transform::identity() { command.clone() } else { command.transformed(&to_canvas) };
+            let style = self.style();
+            if let Some(log) = &mut self.command_log {
+                if matches!(command, DrawCommand::Background(color) if color.a() == 255) {
+                    log.clear();
+                }
+                log.push(logged, style);
+            }
+        }
+        let started = self.draw_times.is_some().then(std::time::Instant::now);
+        if let (Some(renderer), None) = (&mut self.renderer, &self.fill_pattern) {
+            if !self.renderer_active {
+                renderer.begin(&self.pixel_buffer);
+                self.renderer_active = true;
+            }
+            renderer.draw(command, &style);
+        } else {
+            self.rasterize_command(command);
+        }
+        if let (Some(times), Some(started)) = (&mut self.draw_times, started) {
+            times.add(Stage::of_command(command, &style), started.elapsed());
+        }
+    }
+
+    // Draws a command with the canvas's own rasterizer.
+    fn rasterize_command(&mut self, command: &DrawCommand) {
+        self.sync_renderer();
+        match command {
+            DrawCommand::Background(color) => self.pixel_buffer.clear(*color),
+            DrawCommand::Line { start, end } => self.draw_line(*start, *end),
+            DrawCommand::Bezier { start, control1, control2, end } => {
+                let curve = CubicBezier::new(*start, *control1, *control2, *end);
+                let bounds = curve.bounding_box();
+                if self.is_visible((bounds.min, bounds.max)) {
+                    self.polyline(&curve.flatten(CURVE_TOLERANCE));
+                }
+            }
+            DrawCommand::Ellipse { center, width, height } => {
+                let key = ShapeKey::Ellipse { width: width.to_bits(), height: height.to_bits() };
+                self.draw_shape_cached(key, *center, |center| Ellipse {
+                    center,
+                    width: *width,
+                    height: *height,
+                });
+            }
+            DrawCommand::Circle { center, diameter } => {
+                let key = ShapeKey::Circle { diameter: diameter.to_bits() };
+                self.draw_shape_cached(key, *center, |center| Circle::new(center, diameter / 2.0));
+            }
+            DrawCommand::Capsule(capsule) => self.draw_shape_aa(capsule),
+            DrawCommand::Arc(arc) => self.draw_shape_aa(arc),
+            DrawCommand::RoundedRect(rect) => {
+                let key = ShapeKey::RoundedRect {
+                    width: rect.width.to_bits(),
+                    height: rect.height.to_bits(),
+                    radii: rect.radii.map(f32::to_bits),
+                };
+                self.draw_shape_cached(key, rect.top_left, |top_left| RoundedRect { top_left, ..*rect });
+            }
+            DrawCommand::Star(star) => {
+                let key = ShapeKey::Star {
+                    points: star.points,
+                    inner_radius: star.inner_radius.to_bits(),
+                    outer_radius: star.outer_radius.to_bits(),
+                    rotation: star.rotation.to_bits(),
+                };
+                self.draw_shape_cached(key, star.center, |center| Star { center, ..*star });
+            }
+            DrawCommand::RegularPolygon(polygon) => {
+                let key = ShapeKey::RegularPolygon {
+                    sides: polygon.sides,
+                    radius: polygon.radius.to_bits(),
+                    rotation: polygon.rotation.to_bits(),
+                };
+                self.draw_shape_cached(key, polygon.center, |center| RegularPolygon { center, ..*polygon });
+            }
+            DrawCommand::Triangle { a, b, c } => {
+                self.draw_shape_aa(&Triangle { a: *a, b: *b, c: *c });
+            }
+            DrawCommand::Rectangle { top_left, width, height } => {
+                let shape = Rectangle {
+                    top_left: *top_left,
+                    width: *width,
+                    height: *height,
+                };
+                self.draw_shape_aa(&shape);
+            }
+            DrawCommand::Path(path) => {
+                if !path.bounding_box().is_some_and(|bounds| self.is_visible((bounds.min, bounds.max))) {
+                    return;
+                }
+                let shape = Outline {
+                    polylines: path.flatten(CURVE_TOLERANCE),
+                };
+                if !shape.polylines.is_empty() {
+                    self.draw_shape_aa(&shape);
+                }
+            }
+        }
+    }
+
+    /// Draws a round dot in the stroke color, as wide as the stroke weight.
+    pub fn point(&mut self, position: Point) {
+        let Some(color) = self.stroke_color else {
+            return;
+        };
+        let style = self.style();
+        self.set_style(Style { fill: Some(color), stroke: None, ..style });
+        self.ellipse(position, style.stroke_weight, style.stroke_weight);
+        self.set_style(style);
+    }
+
     pub fn line(&mut self, start: Point, end: Point) {
-        if let Some(stroke_color) = &self.stroke_color {
-            //self.pixel_buffer.draw_line(start, end, *stroke_color);
-            self.pixel_buffer.draw_line_aa(start, end, *stroke_color);
+        self.submit(DrawCommand::Line { start, end });
+    }
+
+    /// Draws vertical and horizontal lines `spacing` apart over the whole canvas, starting at
+    /// its top left corner, with `style` and through the canvas transform. The style of the
+    /// canvas is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::color::Color;
+    /// # use simple_sketch::command::Style;
+    /// # fn draw(canvas: &mut Canvas) {
+    /// canvas.grid(20.0, Style { fill: None, stroke: Some(Color::new(32, 32, 32, 255)), stroke_weight: 1.0 });
+    /// # }
+    /// ```
+    pub fn grid(&mut self, spacing: f32, style: Style) {
+        if spacing <= 0.0 || spacing.is_nan() {
+            return;
+        }
+        let (width, height) = (self.width as f32, self.height as f32);
+        let previous = self.style();
+        self.set_style(style);
+        for x in (0..).map(|i| i as f32 * spacing).take_while(|&x| x <= width) {
+            self.line(Point::new(x, 0.0), Point::new(x, height));
+        }
+        for y in (0..).map(|i| i as f32 * spacing).take_while(|&y| y <= height) {
+            self.line(Point::new(0.0, y), Point::new(width, y));
+        }
+        self.set_style(previous);
+    }
+
+    /// Draws an axis with its ticks in the stroke color, and labels its ticks with the current
+    /// font and text size in the fill color. Labels are only drawn once a font is loaded. The
+    /// text alignment is kept.
+    pub fn axis(&mut self, axis: &Axis) {
+        self.line(axis.start, axis.end);
+        let direction = axis.tick_direction();
+        let tick = direction * axis.tick_length;
+        let alignment = self.text_align;
+        // Labels sit past the end of the ticks, facing away from the axis
+        let (horizontal, vertical) = if direction.y.abs() >= direction.x.abs() {
+            (HorizontalAlign::Center, if tick.y >= 0.0 { VerticalAlign::Top } else { VerticalAlign::Bottom })
+        } else {
+            (if tick.x <= 0.0 { HorizontalAlign::Right } else { HorizontalAlign::Left }, VerticalAlign::Center)
+        };
+        self.text_align = (horizontal, vertical);
+        let gap = direction * (axis.tick_length.signum() * self.text_size / 3.0);
+        for value in axis.ticks() {
+            let position = axis.position(value);
+            self.line(position, position + tick);
+            if axis.labels && self.font.is_some() {
+                let label = axis.label(value);
+                self.text(&label, position + tick + gap);
+            }
+        }
+        self.text_align = alignment;
+    }
+
+    fn draw_line(&mut self, start: Point, end: Point) {
+        self.polyline(&[start, end]);
+    }
+
+    pub fn bezier(&mut self, start: Point, control1: Point, control2: Point, end: Point) {
+        self.submit(DrawCommand::Bezier { start, control1, control2, end });
+    }
+
+    // Strokes a polyline with round caps and joins, filling the outline of the stroke around
+    // its visible parts at once, so the joints where its segments overlap aren't blended twice.
+    fn polyline(&mut self, points: &[Point]) {
+        let Some(color) = self.stroke_color else {
+            return;
+        };
+        // Trim the offscreen parts first, with a margin for the line width and antialiased edge
+        let weight = self.stroke_pixels();
+        let margin = LINE_CLIP_MARGIN + weight.max(0.0) / 2.0;
+        let (width, height) = (self.pixel_buffer.width as f32, self.pixel_buffer.height as f32);
+        let bounds = Rect::new(Point::new(-margin, -margin), Point::new(width + margin, height + margin));
+        let lines: Vec<Line> = points.windows(2).filter_map(|segment| Line::new(segment[0], segment[1]).clip_to(&bounds)).collect();
+        let Some(first) = lines.first() else {
+            return;
+        };
+        let (mut top_left, mut bottom_right) = (first.start, first.start);
+        for point in lines.iter().flat_map(|line| [line.start, line.end]) {
+            top_left = Point::new(top_left.x.min(point.x), top_left.y.min(point.y));
+            bottom_right = Point::new(bottom_right.x.max(point.x), bottom_right.y.max(point.y));
+        }
+        let reach = weight.max(0.0) / 2.0 + 1.0;
+        let (x1, y1) = (((top_left.x - reach).floor() as i32).max(0), ((top_left.y - reach).floor() as i32).max(0));
+        let (x2, y2) = (((bottom_right.x + reach).ceil() as i32).min(width as i32 - 1), ((bottom_right.y + reach).ceil() as i32).min(height as i32 - 1));
+        if x1 > x2 || y1 > y2 {
+            return;
         }
+
+        // Segments left whole by clipping continue the same polyline
+        let mut polylines: Vec<Polyline> = Vec::new();
+        for line in &lines {
+            match polylines.last_mut() {
+                Some(polyline) if polyline.points.last() == Some(&line.start) => polyline.points.push(line.end),
+                _ => polylines.push(Polyline { points: vec![line.start, line.end], closed: false }),
+            }
+        }
+        let coverage = FillCoverage::rasterized(&FillOutline::stroke(&polylines, weight), (x1, y1), (x2, y2), self.precision);
+        shade_rows(&mut self.pixel_buffer, (x1, y1), (x2, y2), |px, py, pixel| {
+            raster::blend_premultiplied(pixel, raster::premultiplied(color, coverage.at(px, py)));
+        });
     }
 
 
@@ -57,104 +915,1067 @@ impl Canvas {
 
 
     pub fn ellipse(&mut self, center: Point, width: f32, height: f32) {
-        let shape = Ellipse {
-            center,
-            width,
-            height,
+        self.submit(DrawCommand::Ellipse { center, width, height });
+    }
+
+    /// Draws a circle with the current fill and stroke. Unlike an ellipse of equal width
+    /// and height, its stroke has exactly the same width all around.
+    pub fn circle(&mut self, center: Point, diameter: f32) {
+        self.submit(DrawCommand::Circle { center, diameter });
+    }
+
+    /// Draws circles, each given by its center and diameter, with the current fill and stroke,
+    /// like calling `circle` for each of them. The style and transform are looked up once for
+    /// the whole batch, and circles of the same diameter share their rasterized masks, which
+    /// keeps particle systems with thousands of dots fast. Inside `with_depth` or `with_pick_id`,
+    /// while recording and through transforms that don't keep circles round, the circles are
+    /// drawn one by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::geom::Point;
+    /// # struct Particle { position: Point }
+    /// # struct Sketch { particles: Vec<Particle> }
+    /// # impl Sketch {
+    /// # fn draw(&self, canvas: &mut Canvas) {
+    /// let dots: Vec<(Point, f32)> = self.particles.iter().map(|particle| (particle.position, 4.0)).collect();
+    /// canvas.ellipses(&dots);
+    /// # }
+    /// # }
+    /// ```
+    pub fn ellipses(&mut self, circles: &[(Point, f32)]) {
+        let transform = self.device_transform();
+        let scale = similarity_scale(&transform).filter(|&scale| scale > 0.0);
+        let Some(scale) = scale.filter(|_| !self.queues_commands()) else {
+            for &(center, diameter) in circles {
+                self.circle(center, diameter);
+            }
+            return;
         };
-        self.draw_shape_aa(&shape);
+        for &(center, diameter) in circles {
+            let diameter = diameter * scale;
+            let key = ShapeKey::Circle { diameter: diameter.to_bits() };
+            self.draw_shape_cached(key, transform.transform_point(center), |center| Circle::new(center, diameter / 2.0));
+        }
+    }
+
+    /// Draws the part of a circle between the angles `start` and `end`, in radians clockwise
+    /// from the positive x-axis, closed according to `mode`.
+    pub fn arc(&mut self, center: Point, diameter: f32, start: f32, end: f32, mode: ArcMode) {
+        self.submit(DrawCommand::Arc(Arc { center, radius: diameter / 2.0, start, end, mode }));
+    }
+
+    pub fn triangle(&mut self, a: Point, b: Point, c: Point) {
+        self.submit(DrawCommand::Triangle { a, b, c });
+    }
+
+    /// Draws a capsule, a thick segment with round caps, with the current fill and stroke.
+    pub fn capsule(&mut self, start: Point, end: Point, radius: f32) {
+        self.submit(DrawCommand::Capsule(Capsule { start, end, radius }));
     }
 
     pub fn rectangle(&mut self, top_left: Point, width: f32, height: f32) {
-        let shape = Rectangle {
-            top_left,
-            width,
-            height,
+        self.submit(DrawCommand::Rectangle { top_left, width, height });
+    }
+
+    /// Draws a rectangle with rounded corners. The radii are given clockwise from the
+    /// top-left corner: top-left, top-right, bottom-right and bottom-left.
+    pub fn rounded_rect(&mut self, top_left: Point, width: f32, height: f32, radii: [f32; 4]) {
+        self.submit(DrawCommand::RoundedRect(RoundedRect { top_left, width, height, radii }));
+    }
+
+    /// Draws a star with `points` tips on a circle of `outer_radius` around `center`, and the
+    /// notches between them at `inner_radius`. The first tip points along `rotation` radians
+    /// clockwise from the positive x-axis.
+    pub fn star(&mut self, center: Point, points: usize, inner_radius: f32, outer_radius: f32, rotation: f32) {
+        self.submit(DrawCommand::Star(Star { center, points, inner_radius, outer_radius, rotation }));
+    }
+
+    /// Draws a polygon with `sides` equal sides whose vertices lie on a circle of `radius` around
+    /// `center`. The first vertex lies `rotation` radians clockwise from the positive x-axis.
+    pub fn regular_polygon(&mut self, center: Point, sides: usize, radius: f32, rotation: f32) {
+        self.submit(DrawCommand::RegularPolygon(RegularPolygon { center, sides, radius, rotation }));
+    }
+
+    /// Draws a rotated rectangle with the current fill and stroke.
+    pub fn oriented_rect(&mut self, rect: &OrientedRect) {
+        self.submit(DrawCommand::Path(Path::polygon(&rect.corners())));
+    }
+
+    /// Draws a polygon, such as one made with a `PolygonBuilder`, with the current fill and stroke.
+    /// Open polygons are filled as if closed, but their stroke leaves out the closing edge.
+    pub fn draw_polygon(&mut self, polygon: &Polygon) {
+        self.submit(DrawCommand::Path(polygon.to_path()));
+    }
+
+    pub fn draw_path(&mut self, path: &Path) {
+        self.submit(DrawCommand::Path(path.clone()));
+    }
+
+    /// Draws metaballs with the current fill and stroke, sampled on a grid fine enough to
+    /// look smooth at the canvas's pixel density. Recorded, logged, picked and depth-sorted
+    /// metaballs, and metaballs under transforms that skew or stretch, are drawn as their
+    /// traced outline, which is slower.
+    pub fn draw_metaballs(&mut self, metaballs: &Metaballs) {
+        let transform = self.device_transform();
+        // Rotations and uniform scales keep the balls round
+        let scale = similarity_scale(&transform).filter(|&scale| scale > 0.0);
+        let Some(scale) = scale.filter(|_| !self.queues_commands()) else {
+            let cell_size = METABALL_CELL_SIZE / (self.pixel_density * self.transform.m11.hypot(self.transform.m21)).max(f32::EPSILON);
+            self.submit(DrawCommand::Path(metaballs.to_path(cell_size)));
+            return;
         };
-        self.draw_shape_aa(&shape);
+        let balls = metaballs.balls.iter().map(|ball| Metaball::new(transform.transform_point(ball.center), ball.radius * scale)).collect();
+        let device = Metaballs { balls, threshold: metaballs.threshold };
+        if let Some(grid) = device.grid(METABALL_CELL_SIZE) {
+            self.draw_shape_aa(&grid);
+        }
+    }
+
+    /// A copy of the pixel buffer as an image, at the resolution of the buffer, like
+    /// Processing's `get()`. Draw it back with `image_scaled` to the canvas size.
+    pub fn get_image(&self) -> Image {
+        Image::from(&self.pixel_buffer)
+    }
+
+    /// Draws on the layer called `name`, adding it on top of the other layers if there is no
+    /// layer with that name yet. Layers are offscreen canvases the size of this one, at its
+    /// pixel density, with their own fill, stroke and transform. They keep what is drawn on
+    /// them from frame to frame, and are composited over the canvas, from the bottom up, with
+    /// their opacity and blend mode when the frame is shown, so the canvas itself only holds
+    /// the bottom of the picture. Calls that read the canvas, like `get_image` and `save_png`
+    /// during `draw`, only see the canvas; `composited_image` includes the layers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::color::Color;
+    /// # use simple_sketch::geom::Point;
+    /// # use simple_sketch::layer::BlendMode;
+    /// # fn draw(canvas: &mut Canvas, mouse: Point, particles: &[Point]) {
+    /// // In setup
+    /// canvas.add_layer("trails").blend_mode = BlendMode::Add;
+    /// canvas.add_layer("ui");
+    ///
+    /// // In draw: the trails build up while the background and the UI are redrawn
+    /// canvas.background(Color::new(10, 10, 20, 255));
+    /// canvas.with_layer("trails", |layer| layer.circle(mouse, 6.0));
+    /// canvas.with_layer("ui", |layer| {
+    ///     layer.clear();
+    ///     layer.text(&format!("{} particles", particles.len()), Point::new(10.0, 20.0));
+    /// });
+    /// # }
+    /// ```
+    pub fn with_layer<F: FnOnce(&mut Canvas)>(&mut self, name: &str, draw: F) {
+        draw(&mut self.add_layer(name).canvas);
     }
 
-    fn draw_shape_aa(&mut self, shape: &impl Shape) {
-        if let Some(fill_color) = &self.fill_color {
-            self.fill_shape_aa(shape, *fill_color);
+    /// Adds a transparent layer called `name` on top of the other layers and returns it, to set
+    /// its opacity, blend mode and visibility. Returns the existing layer if there already is
+    /// one with that name. See `with_layer`.
+    pub fn add_layer(&mut self, name: &str) -> &mut Layer {
+        let index = match self.layers.iter().position(|layer| layer.name == name) {
+            Some(index) => index,
+            None => {
+                let canvas = self.create_graphics(self.width, self.height);
+                self.layers.push(Layer::new(name, canvas));
+                self.layers.len() - 1
+            }
+        };
+        &mut self.layers[index]
+    }
 
+    /// The layer called `name`, or None if there is none.
+    pub fn layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut Layer> {
+        self.layers.iter_mut().find(|layer| layer.name == name)
+    }
+
+    /// The layers from the bottom up.
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Removes the layer called `name` and returns it, or None if there is none.
+    pub fn remove_layer(&mut self, name: &str) -> Option<Layer> {
+        let index = self.layers.iter().position(|layer| layer.name == name)?;
+        Some(self.layers.remove(index))
+    }
+
+    /// Moves the layer called `name` to `index` in the stack, where 0 is the bottom layer,
+    /// right above the canvas. Indices past the top move it to the top. Does nothing if there
+    /// is no layer with that name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # fn draw(canvas: &mut Canvas) {
+    /// // Bring the selected layer to the front
+    /// canvas.move_layer("sketch", canvas.layers().len());
+    /// # }
+    /// ```
+    pub fn move_layer(&mut self, name: &str, index: usize) {
+        if let Some(from) = self.layers.iter().position(|layer| layer.name == name) {
+            let layer = self.layers.remove(from);
+            self.layers.insert(index.min(self.layers.len()), layer);
+        }
+    }
+
+    /// Sets the post-processing effects applied to every frame before it is shown, in order,
+    /// after the layers are composited. Like layers, they don't change what is drawn on the
+    /// canvas, so they don't build up on sketches that draw over the previous frame. An empty
+    /// list, the default, turns them off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::postprocess::Effect;
+    /// # fn setup(canvas: &mut Canvas) {
+    /// // Neon lines on black
+    /// canvas.set_post_effects(vec![
+    ///     Effect::Bloom { threshold: 0.6, sigma: 8.0, intensity: 1.5 },
+    ///     Effect::ChromaticAberration { offset: 2.0 },
+    ///     Effect::Vignette { strength: 0.6, radius: 0.5 },
+    /// ]);
+    /// # }
+    /// ```
+    pub fn set_post_effects(&mut self, effects: Vec<Effect>) {
+        self.post_effects = effects;
+    }
+
+    pub fn post_effects(&self) -> &[Effect] {
+        &self.post_effects
+    }
+
+    /// Adds a post-processing effect after the others. See `set_post_effects`.
+    pub fn add_post_effect(&mut self, effect: Effect) {
+        self.post_effects.push(effect);
+    }
+
+    /// A copy of the frame as it is shown: the canvas with its visible layers composited over
+    /// it and the post-processing effects applied, at the resolution of the pixel buffer. The
+    /// same as `get_image` without layers and effects.
+    pub fn composited_image(&self) -> Image {
+        let mut buffer = PixelBuffer::from(self.get_image());
+        self.composite(&mut buffer);
+        Image::from(buffer)
+    }
+
+    fn composite(&self, buffer: &mut PixelBuffer) {
+        for layer in &self.layers {
+            layer.composite(buffer.get_buffer_mut());
         }
-        if let Some(stroke_color) = &self.stroke_color {
-            self.stroke_shape(shape, *stroke_color);
-//            self.stroke_shape_aa(shape, stroke_color.clone());
+        for effect in &self.post_effects {
+            effect.apply(buffer, self.pixel_density);
         }
     }
 
-    fn fill_shape_aa(&mut self, shape: &impl Shape, color: Color) {
-        let (top_left, bottom_right) = shape.bounding_box();
-        let (x1, y1) = (top_left.x.floor() as i32, top_left.y.floor() as i32);
-        let (x2, y2) = (bottom_right.x.ceil() as i32, bottom_right.y.ceil() as i32);
-
-        for px in x1..=x2 {
-            for py in y1..=y2 {
-                let point = Point::new(px as f32, py as f32);
-                let coverage = self.calculate_coverage(shape, point);
-                if coverage > 0.0 {
-                    let aa_color = color.with_alpha((color.a() as f32 * coverage) as u8);
-                    self.pixel_buffer.blend_pixel(px, py, &aa_color);
+    // Turns the pixel buffer into the frame as it is shown, with the layers and post effects,
+    // keeping the pixels as drawn to put back with `restore_drawn_frame` before the next frame
+    // is drawn
+    pub(crate) fn composite_frame(&mut self) {
+        self.sync_renderer();
+        if !self.layers.iter().any(|layer| layer.visible) && self.post_effects.is_empty() {
+            return;
+        }
+        self.drawn_pixels = Some(self.pixel_buffer.get_buffer().to_vec());
+        let mut buffer = std::mem::replace(&mut self.pixel_buffer, PixelBuffer::new(0, 0));
+        self.composite(&mut buffer);
+        self.pixel_buffer = buffer;
+    }
+
+    pub(crate) fn restore_drawn_frame(&mut self) {
+        self.sync_renderer();
+        if let Some(drawn) = self.drawn_pixels.take() {
+            // A resize in between leaves nothing to restore
+            if drawn.len() == self.pixel_buffer.get_buffer().len() {
+                self.pixel_buffer.get_buffer_mut().copy_from_slice(&drawn);
+            }
+        }
+    }
+
+    /// Draws another canvas, such as one from `create_graphics`, with its top left corner at
+    /// `position` and at its logical size, whatever the pixel densities of the two canvases.
+    /// Drawn like an image, through the canvas transform, with the image filter and tint, so
+    /// the transparent parts of `other` show what is below.
+    pub fn draw_canvas(&mut self, other: &Canvas, position: Point) {
+        let size = Point::new(other.width as f32, other.height as f32);
+        self.image_scaled(&other.get_image(), Rect::new(position, position + size));
+    }
+
+    /// Sets how images are sampled when they are drawn scaled or rotated. The default is
+    /// `ImageFilter::Bilinear`; `Nearest` keeps pixel art crisp, and `Bicubic` and `Lanczos3`
+    /// keep photos sharp. Only `Bilinear` doesn't average shrunk images, and it is the fastest
+    /// besides `Nearest`.
+    pub fn set_image_filter(&mut self, filter: ImageFilter) {
+        self.image_filter = filter;
+    }
+
+    pub fn image_filter(&self) -> ImageFilter {
+        self.image_filter
+    }
+
+    /// Sets a color to multiply the pixels of images by as they are drawn, like Processing's
+    /// `tint()`, or None to draw images as they are, like `noTint()`. White with an alpha below
+    /// 255 fades images without changing their colors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::app::Context;
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::color::Color;
+    /// # use simple_sketch::geom::Point;
+    /// # use simple_sketch::image::Image;
+    /// # fn draw(canvas: &mut Canvas, ctx: &Context, photo: &Image) {
+    /// // Fade the photo in over the first second
+    /// let alpha = (ctx.seconds_since_start().min(1.0) * 255.0) as u8;
+    /// canvas.set_tint(Some(Color::new(255, 255, 255, alpha)));
+    /// canvas.image(&photo, Point::new(0.0, 0.0));
+    /// canvas.set_tint(None);
+    /// # }
+    /// ```
+    pub fn set_tint(&mut self, tint: Option<Color>) {
+        self.tint = tint;
+    }
+
+    pub fn tint(&self) -> Option<Color> {
+        self.tint
+    }
+
+    /// Draws an image at its own size with its top left corner at `position`, through the canvas
+    /// transform and multiplied by the tint. Transparent pixels of the image are blended with
+    /// the canvas. The image is drawn immediately, even inside `with_depth`, and isn't recorded
+    /// by `start_recording_commands`.
+    pub fn image(&mut self, image: &Image, position: Point) {
+        let size = Point::new(image.width as f32, image.height as f32);
+        self.image_scaled(image, Rect::new(position, position + size));
+    }
+
+    /// Draws an image stretched to fill `destination`, through the canvas transform. Scaled and
+    /// rotated images are sampled with the image filter, and their edges are antialiased. See
+    /// `image` and `set_image_filter`.
+    pub fn image_scaled(&mut self, image: &Image, destination: Rect) {
+        let source = Rect::new(Point::new(0.0, 0.0), Point::new(image.width as f32, image.height as f32));
+        self.draw_image(image, source, destination);
+    }
+
+    /// Draws the part of an image inside `source`, in image pixels, stretched to fill
+    /// `destination`, for sprite sheets and texture atlases. Pixels outside `source` never
+    /// bleed into the edges, even when the region is scaled. See `image`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::geom::{Point, Rect};
+    /// # use simple_sketch::image::Image;
+    /// # fn draw(canvas: &mut Canvas, sheet: &Image, position: Point) {
+    /// // The third 32x32 frame of a sprite sheet, drawn at twice its size
+    /// let frame = Rect::new(Point::new(64.0, 0.0), Point::new(96.0, 32.0));
+    /// canvas.image_region(&sheet, frame, Rect::new(position, position + Point::new(64.0, 64.0)));
+    /// # }
+    /// ```
+    pub fn image_region(&mut self, image: &Image, source: Rect, destination: Rect) {
+        self.draw_image(image, source, destination);
+    }
+
+    // Draws the `source` rectangle of an image, in image pixels, into `destination`.
+    fn draw_image(&mut self, image: &Image, source: Rect, destination: Rect) {
+        self.sync_renderer();
+        if source.width() <= 0.0 || source.height() <= 0.0 {
+            return;
+        }
+        let to_buffer = self.device_transform()
+            * Transform::translate(destination.min)
+            * Transform::scale(destination.width() / source.width(), destination.height() / source.height())
+            * Transform::translate(Point::new(0.0, 0.0) - source.min);
+        let Some(to_image) = to_buffer.inverse() else {
+            return;
+        };
+
+        // Visit the buffer pixels under the image, with a pixel of margin for the antialiased edges
+        let corners = [source.min, Point::new(source.max.x, source.min.y), source.max, Point::new(source.min.x, source.max.y)]
+            .map(|corner| to_buffer.transform_point(corner));
+        let (width, height) = (self.pixel_buffer.width, self.pixel_buffer.height);
+        let min_x = corners.iter().map(|p| p.x).fold(f32::INFINITY, f32::min).floor().max(0.0) as usize;
+        let min_y = corners.iter().map(|p| p.y).fold(f32::INFINITY, f32::min).floor().max(0.0) as usize;
+        let max_x = (corners.iter().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max).ceil().max(0.0) as usize).min(width);
+        let max_y = (corners.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max).ceil().max(0.0) as usize).min(height);
+
+        // The texels the region covers, clamped to the image, which samples near its edges repeat
+        let first = (source.min.x.floor().max(0.0) as usize, source.min.y.floor().max(0.0) as usize);
+        let end = ((source.max.x.ceil().max(0.0) as usize).min(image.width), (source.max.y.ceil().max(0.0) as usize).min(image.height));
+        if end.0 <= first.0 || end.1 <= first.1 {
+            return;
+        }
+        let texels = (first.0, first.1, end.0 - 1, end.1 - 1);
+        // Buffer pixels per image pixel across the region's vertical and horizontal edges
+        let edge_scale = (1.0 / to_image.m11.hypot(to_image.m12), 1.0 / to_image.m21.hypot(to_image.m22));
+        let footprint = (1.0 / edge_scale.0, 1.0 / edge_scale.1);
+        let filter = self.image_filter;
+        let tint = self.tint_factors();
+
+        let buffer = self.pixel_buffer.get_buffer_mut();
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let point = to_image.transform_point(Point::new(x as f32 + 0.5, y as f32 + 0.5));
+                // Antialias the edges by how far the pixel center is inside them
+                let inside_x = (point.x - source.min.x).min(source.max.x - point.x) * edge_scale.0;
+                let inside_y = (point.y - source.min.y).min(source.max.y - point.y) * edge_scale.1;
+                let coverage = (inside_x + 0.5).clamp(0.0, 1.0) * (inside_y + 0.5).clamp(0.0, 1.0);
+                if coverage <= 0.0 {
+                    continue;
                 }
+                let sample = image.sample(filter, texels, point.x - 0.5, point.y - 0.5, footprint);
+                raster::blend_premultiplied(&mut buffer[y * width + x], [0, 1, 2, 3].map(|channel| sample[channel] * tint[channel] * coverage));
             }
         }
     }
 
-    fn stroke_shape(&mut self, shape: &impl Shape, color: Color) {
-        let (top_left, bottom_right) = shape.bounding_box();
-        let stroke_offset = self.stroke_weight / 2.0;
-        let x1 = (top_left.x - stroke_offset).floor() as i32;
-        let y1 = (top_left.y - stroke_offset).floor() as i32;
-        let x2 = (bottom_right.x + stroke_offset).ceil() as i32;
-        let y2 = (bottom_right.y + stroke_offset).ceil() as i32;
-        
-        for px in x1..=x2 {
-            for py in y1..=y2 {
-                let point = Point::new(px as f32, py as f32);
-                let distance = shape.distance(point);
-                
-                // Check if the pixel is within the stroke width
-                if distance.abs() <= self.stroke_weight / 2.0 {
-                    // For sharper lines, don't use anti-aliasing
-                    self.pixel_buffer.set_pixel(px, py, color);
+    /// Fills a polygon with a texture, mapping each vertex to its texture coordinate from
+    /// `polygon.uvs`, in image pixels, and stretching the image linearly in between, through the
+    /// canvas transform and multiplied by the tint. The polygon is then stroked with the current
+    /// stroke, if any. Polygons without a texture coordinate per vertex are only stroked.
+    ///
+    /// The texture is sampled with the image filter. Its edges aren't antialiased, so that
+    /// polygons sharing edges, like the cells of a warped grid, join without seams. Like
+    /// `image`, the polygon is drawn immediately and isn't recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::geom::Point;
+    /// # use simple_sketch::image::Image;
+    /// # use simple_sketch::shape::Polygon;
+    /// # fn draw(canvas: &mut Canvas, photo: &Image, time: f32) {
+    /// // Map a photo onto a wavy grid
+    /// let cell = Point::new(photo.width as f32 / 8.0, photo.height as f32 / 8.0);
+    /// let warp = |u: f32, v: f32| Point::new(u + (v * 0.05 + time).sin() * 10.0, v + (u * 0.05).cos() * 10.0);
+    /// for row in 0..8 {
+    ///     for column in 0..8 {
+    ///         let (u, v) = (column as f32 * cell.x, row as f32 * cell.y);
+    ///         let uvs = vec![Point::new(u, v), Point::new(u + cell.x, v), Point::new(u + cell.x, v + cell.y), Point::new(u, v + cell.y)];
+    ///         let vertices = uvs.iter().map(|uv| warp(uv.x, uv.y)).collect();
+    ///         canvas.draw_textured_polygon(&Polygon::with_uvs(vertices, uvs).unwrap(), &photo);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn draw_textured_polygon(&mut self, polygon: &Polygon, texture: &Image) {
+        let vertices = &polygon.geometry.vertices;
+        if polygon.uvs.len() == vertices.len() && texture.width > 0 && texture.height > 0 {
+            let transform = self.device_transform();
+            let points: Vec<Point> = vertices.iter().map(|&vertex| transform.transform_point(vertex)).collect();
+            for [a, b, c] in polygon.geometry.triangulate() {
+                self.fill_textured_triangle([points[a], points[b], points[c]], [polygon.uvs[a], polygon.uvs[b], polygon.uvs[c]], texture);
+            }
+        }
+        if self.stroke_color.is_some() {
+            let fill = self.fill_color.take();
+            self.draw_polygon(polygon);
+            self.fill_color = fill;
+        }
+    }
+
+    // Fills the buffer pixels whose centers are inside a triangle, in buffer pixels, with the
+    // texture interpolated between the texture coordinates of its corners.
+    fn fill_textured_triangle(&mut self, mut points: [Point; 3], mut uvs: [Point; 3], texture: &Image) {
+        self.sync_renderer();
+        let cross = |a: Point, b: Point| a.x * b.y - a.y * b.x;
+        let mut area = cross(points[1] - points[0], points[2] - points[0]);
+        if area < 0.0 {
+            points.swap(1, 2);
+            uvs.swap(1, 2);
+            area = -area;
+        }
+        if area <= f32::EPSILON {
+            return;
+        }
+        // The texture coordinate as a linear function of the buffer position
+        let (edge1, edge2) = (points[1] - points[0], points[2] - points[0]);
+        let (uv1, uv2) = (uvs[1] - uvs[0], uvs[2] - uvs[0]);
+        let ds = Point::new(edge2.y, -edge2.x) * (1.0 / area);
+        let dt = Point::new(-edge1.y, edge1.x) * (1.0 / area);
+        let du = Point::new(ds.x * uv1.x + dt.x * uv2.x, ds.y * uv1.x + dt.y * uv2.x);
+        let dv = Point::new(ds.x * uv1.y + dt.x * uv2.y, ds.y * uv1.y + dt.y * uv2.y);
+        let footprint = (du.x.hypot(du.y), dv.x.hypot(dv.y));
+
+        let (width, height) = (self.pixel_buffer.width, self.pixel_buffer.height);
+        let min_x = points.iter().map(|p| p.x).fold(f32::INFINITY, f32::min).floor().max(0.0) as usize;
+        let min_y = points.iter().map(|p| p.y).fold(f32::INFINITY, f32::min).floor().max(0.0) as usize;
+        let max_x = (points.iter().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max).ceil().max(0.0) as usize).min(width);
+        let max_y = (points.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max).ceil().max(0.0) as usize).min(height);
+        let edges = [(points[0], points[1]), (points[1], points[2]), (points[2], points[0])];
+        let texels = (0, 0, texture.width - 1, texture.height - 1);
+        let filter = self.image_filter;
+        let tint = self.tint_factors();
+
+        let buffer = self.pixel_buffer.get_buffer_mut();
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let center = Point::new(x as f32 + 0.5, y as f32 + 0.5);
+                // Centers exactly on an edge belong to one side only, the same for every
+                // triangle sharing it, so neighbors neither overlap nor leave gaps
+                let inside = edges.iter().all(|&(start, end)| {
+                    let side = cross(end - start, center - start);
+                    side > 0.0 || (side == 0.0 && (end.y > start.y || (end.y == start.y && end.x > start.x)))
+                });
+                if !inside {
+                    continue;
                 }
-                // Optional: Add minimal anti-aliasing at the edges
-                else if distance.abs() <= (self.stroke_weight / 2.0) + 1.0 {
-                    let alpha = ((self.stroke_weight / 2.0) + 1.0 - distance.abs()) * 255.0;
-                    let aa_color = color.with_alpha(alpha as u8);
-                    self.pixel_buffer.blend_pixel(px, py, &aa_color);
+                let offset = center - points[0];
+                let (s, t) = (ds.x * offset.x + ds.y * offset.y, dt.x * offset.x + dt.y * offset.y);
+                let uv = uvs[0] + uv1 * s + uv2 * t;
+                let sample = texture.sample(filter, texels, uv.x - 0.5, uv.y - 0.5, footprint);
+                raster::blend_premultiplied(&mut buffer[y * width + x], [0, 1, 2, 3].map(|channel| sample[channel] * tint[channel]));
+            }
+        }
+    }
+
+    // The tint as factors for premultiplied samples, where its alpha scales every channel
+    fn tint_factors(&self) -> [f32; 4] {
+        self.tint.map_or([1.0; 4], |tint| {
+            let alpha = tint.a() as f32 / 255.0;
+            [tint.r() as f32 / 255.0 * alpha, tint.g() as f32 / 255.0 * alpha, tint.b() as f32 / 255.0 * alpha, alpha]
+        })
+    }
+
+    /// Draws the shapes of an SVG file in their own colors and stroke widths, with the
+    /// document's top left corner at the origin of the canvas transform. The current style is
+    /// left as it was.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::geom::Point;
+    /// # use simple_sketch::svg::SvgDocument;
+    /// # fn draw(canvas: &mut Canvas, logo: &SvgDocument) {
+    /// canvas.push();
+    /// canvas.translate(Point::new(100.0, 100.0));
+    /// canvas.scale(0.5, 0.5);
+    /// canvas.draw_svg(&logo);
+    /// canvas.pop();
+    /// # }
+    /// ```
+    pub fn draw_svg(&mut self, document: &SvgDocument) {
+        let style = self.style();
+        for shape in &document.shapes {
+            self.set_style(Style { fill: shape.fill, stroke: shape.stroke, stroke_weight: shape.stroke_weight });
+            self.draw_path(&shape.path);
+        }
+        self.set_style(style);
+    }
+
+    /// Draws every command of a scene with its own style, through the canvas transform. The
+    /// canvas style is left unchanged.
+    pub fn draw_scene(&mut self, scene: &Scene) {
+        self.draw_recorded(&scene.commands);
+    }
+
+    /// Replays a display list with the styles it was recorded with, through the canvas
+    /// transform, so that the same list can be drawn at other positions, sizes and angles.
+    /// The canvas style is left unchanged.
+    pub fn draw_display_list(&mut self, list: &DisplayList) {
+        self.draw_recorded(list.commands());
+    }
+
+    // Draws recorded commands with their own styles, through the canvas transform.
+    fn draw_recorded(&mut self, commands: &[RecordedCommand]) {
+        let style = self.style();
+        for recorded in commands {
+            self.set_style(recorded.style);
+            self.submit(recorded.command.clone());
+        }
+        self.set_style(style);
+    }
+
+    /// Loads a TrueType font file and makes it the font of `text`, like Processing's
+    /// `createFont()` and `textFont()`.
+    pub fn load_font(&mut self, path: impl AsRef<std::path::Path>) -> Result<(), SketchError> {
+        let path = path.as_ref();
+        let font = Font::load(path).map_err(|error| match error {
+            FontError::Io(e) => SketchError::from(std::io::Error::new(e.kind(), format!("could not load {}: {e}", path.display()))),
+            error => error.into(),
+        })?;
+        self.font = Some(font);
+        Ok(())
+    }
+
+    /// Sets the font of `text`.
+    pub fn set_font(&mut self, font: Font) {
+        self.font = Some(font);
+    }
+
+    pub fn font(&self) -> Option<&Font> {
+        self.font.as_ref()
+    }
+
+    /// Sets the font size of `text` in pixels, the height of an em. The default is 12.
+    pub fn set_text_size(&mut self, size: f32) {
+        self.text_size = size;
+    }
+
+    pub fn text_size(&self) -> f32 {
+        self.text_size
+    }
+
+    /// Sets how `text` is placed relative to its position, like Processing's `textAlign()`.
+    /// The default is `Left` and `Baseline`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::font::{HorizontalAlign, VerticalAlign};
+    /// # use simple_sketch::geom::{Point, Rect};
+    /// # fn draw(canvas: &mut Canvas, label: &str, bar: Rect) {
+    /// // A label centered under a bar of the chart
+    /// canvas.set_text_align(HorizontalAlign::Center, VerticalAlign::Top);
+    /// canvas.text(label, Point::new(bar.center().x, bar.max.y + 4.0));
+    /// # }
+    /// ```
+    pub fn set_text_align(&mut self, horizontal: HorizontalAlign, vertical: VerticalAlign) {
+        self.text_align = (horizontal, vertical);
+    }
+
+    pub fn text_align(&self) -> (HorizontalAlign, VerticalAlign) {
+        self.text_align
+    }
+
+    /// Turns shaping text with the font's OpenType layout tables on or off, for scripts like
+    /// Arabic and Devanagari whose letters change shape and combine, text with combining
+    /// marks, and right-to-left text. See `Font::shape`. Off by default, placing glyphs one
+    /// character after another, which is faster and enough for most Latin text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::geom::Point;
+    /// # fn draw(canvas: &mut Canvas) {
+    /// canvas.set_text_shaping(true);
+    /// canvas.text("مرحبا بالعالم", Point::new(20.0, 50.0));
+    /// # }
+    /// ```
+    pub fn set_text_shaping(&mut self, enabled: bool) {
+        self.text_shaping = enabled;
+    }
+
+    pub fn text_shaping(&self) -> bool {
+        self.text_shaping
+    }
+
+    /// The width of text in pixels with the current font and text size, or of its widest line
+    /// for text with newlines. Zero without a font.
+    pub fn text_width(&self, text: &str) -> f32 {
+        match &self.font {
+            Some(font) if self.text_shaping => font.shaped_text_width(text, self.text_size),
+            Some(font) => font.text_width(text, self.text_size),
+            None => 0.0,
+        }
+    }
+
+    /// How far the current font reaches above the baseline at the current text size, in
+    /// pixels. Zero without a font.
+    pub fn text_ascent(&self) -> f32 {
+        self.font.as_ref().map_or(0.0, |font| font.ascender() as f32 * self.text_size / font.units_per_em() as f32)
+    }
+
+    /// How far the current font reaches below the baseline at the current text size, in
+    /// pixels, as a positive distance. Zero without a font.
+    pub fn text_descent(&self) -> f32 {
+        self.font.as_ref().map_or(0.0, |font| -(font.descender() as f32) * self.text_size / font.units_per_em() as f32)
+    }
+
+    /// Draws text with the current font, text size and alignment at `position`, filled with
+    /// the fill color and through the canvas transform. Glyphs are antialiased like other
+    /// paths. Newlines start a new line below. Nothing is drawn before a font is loaded with
+    /// `load_font` or set with `set_font`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::color::Color;
+    /// # use simple_sketch::error::SketchError;
+    /// # use simple_sketch::geom::Point;
+    /// # fn draw(canvas: &mut Canvas) -> Result<(), SketchError> {
+    /// canvas.load_font("assets/DejaVuSans.ttf")?;
+    /// canvas.set_text_size(32.0);
+    /// canvas.set_fill(Some(Color::new(0, 0, 0, 255)));
+    /// canvas.text("hello", Point::new(20.0, 50.0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn text(&mut self, text: &str, position: Point) {
+        let Some(font) = &self.font else {
+            return;
+        };
+        let (horizontal, vertical) = self.text_align;
+        let line_height = font.line_height(self.text_size);
+        let lines = text.split('\n').count() as f32;
+        let (ascent, descent) = (self.text_ascent(), self.text_descent());
+        let baseline = position.y
+            + match vertical {
+                VerticalAlign::Top => ascent,
+                VerticalAlign::Center => (ascent - descent - (lines - 1.0) * line_height) / 2.0,
+                VerticalAlign::Baseline => 0.0,
+                VerticalAlign::Bottom => -descent - (lines - 1.0) * line_height,
+            };
+        let mut path = Path::new();
+        for (index, line) in text.split('\n').enumerate() {
+            let width = self.text_width(line);
+            let x = match horizontal {
+                HorizontalAlign::Left => position.x,
+                HorizontalAlign::Center => position.x - width / 2.0,
+                HorizontalAlign::Right => position.x - width,
+            };
+            let start = Point::new(x, baseline + index as f32 * line_height);
+            let line_path = if self.text_shaping { font.shaped_text_path(line, start, self.text_size) } else { font.text_path(line, start, self.text_size) };
+            path.commands.extend(line_path.commands);
+        }
+        let style = self.style();
+        self.set_style(Style { stroke: None, ..style });
+        self.submit(DrawCommand::Path(path));
+        self.set_style(style);
+    }
+
+    /// Draws text in a built-in 8x8 pixel font with its top left corner at `position`, for
+    /// showing frame rates and variable values without loading a font. Glyphs are 8 canvas
+    /// pixels wide and lines 10 apart. The text is drawn crisp in the fill color, or in white
+    /// without one, ignoring the canvas transform. Characters outside ASCII are drawn as `?`.
+    /// The text is drawn immediately, even inside `with_depth`, and isn't recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::app::Context;
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::geom::Point;
+    /// # fn draw(canvas: &mut Canvas, ctx: &Context, particles: &[Point]) {
+    /// canvas.debug_text(&format!("frame {}\nparticles {}", ctx.frame_count(), particles.len()), Point::new(4.0, 4.0));
+    /// # }
+    /// ```
+    pub fn debug_text(&mut self, text: &str, position: Point) {
+        self.sync_renderer();
+        let color = self.style().fill.unwrap_or(Color::new(255, 255, 255, 255));
+        let scale = self.device_scale();
+        let to_pixel = |point: Point| {
+            let point = scale.transform_point(point);
+            (point.x.round() as i32, point.y.round() as i32)
+        };
+        for (line, text) in text.split('\n').enumerate() {
+            for (column, c) in text.chars().enumerate() {
+                let origin = position + Point::new((column * GLYPH_SIZE) as f32, (line * DEBUG_LINE_HEIGHT) as f32);
+                for (row, bits) in bitmap_font::glyph(c).into_iter().enumerate() {
+                    for bit in (0..GLYPH_SIZE).filter(|bit| bits >> bit & 1 == 1) {
+                        let corner = origin + Point::new(bit as f32, row as f32);
+                        let (left, top) = to_pixel(corner);
+                        let (right, bottom) = to_pixel(corner + Point::new(1.0, 1.0));
+                        for y in top..bottom {
+                            for x in left..right {
+                                self.pixel_buffer.blend_pixel(x, y, &color);
+                            }
+                        }
+                    }
                 }
             }
         }
     }
 
+    /// Draws a software cursor with its hotspot at `position`, in canvas pixels, ignoring the
+    /// canvas transform and style.
+    pub fn draw_cursor(&mut self, cursor: &SoftwareCursor, position: Point) {
+        self.push();
+        self.set_transform(Transform::translate(position));
+        self.set_style(Style { fill: cursor.fill, stroke: cursor.stroke, stroke_weight: cursor.stroke_weight });
+        self.draw_path(&cursor.shape);
+        self.pop();
+    }
 
-    fn calculate_coverage(&self, shape: &impl Shape, point: Point) -> f32 {
-        let samples = [
-            Point::new(0.25, 0.25),
-            Point::new(0.75, 0.25),
-            Point::new(0.25, 0.75),
-            Point::new(0.75, 0.75)
-        ];
-        
-        let count = samples.iter()
-            .filter(|&&sample| shape.contains(point + sample))
-            .count();
-        // Calculate the coverage as the ratio of points inside the shape to total sample points    
-        count as f32 / samples.len() as f32
+    /// Draws a shape through `transform`, on top of the canvas transform, with the current fill
+    /// and stroke. This is how to draw rotated or skewed versions of any `Shape`.
+    /// The shape is drawn immediately, even inside `with_depth`.
+    pub fn draw_shape_transformed(&mut self, shape: &dyn Shape, transform: &Transform) {
+        let shape = Transformed { shape, transform: self.device_transform() * *transform };
+        self.draw_shape_aa(&shape);
     }
-    #[allow(dead_code)]
-    fn calculate_stroke_coverage(&self, distance: f32) -> f32 {
-        let half_stroke = self.stroke_weight / 2.0;
-        if distance.abs() > half_stroke {
-            0.0
-        } else {
-            (half_stroke - distance.abs()) / self.stroke_weight
+
+    /// Draws a collection of shapes, in order, through the canvas transform with the current
+    /// fill and stroke. The shapes can be of different types, as in a scene kept as a `Vec`.
+    /// They are drawn immediately, even inside `with_depth`.
+    pub fn draw_shapes(&mut self, shapes: &[Box<dyn Shape>]) {
+        for shape in shapes {
+            self.draw_shape_transformed(shape.as_ref(), &Transform::identity());
+        }
+    }
+
+    /// Draws a copy of `shape` at each position, moving the shape's origin there, with the
+    /// current fill and stroke and through the canvas transform. The shape is rasterized at most
+    /// once per quarter-pixel offset and then only blended, so stamping thousands of copies of a
+    /// complex shape costs little more than blending their pixels. Positions are rounded to a
+    /// quarter pixel. Copies too big to be worth caching, or filled with a pattern, are drawn
+    /// one by one. The copies are drawn immediately, even inside `with_depth`.
+    pub fn stamp(&mut self, shape: &dyn Shape, positions: &[Point]) {
+        self.sync_renderer();
+        let transform = self.device_transform();
+        // The transform scales and rotates the prototype, and moves the positions
+        let prototype = Transformed { shape, transform: Transform { tx: 0.0, ty: 0.0, ..transform } };
+        let (top_left, bottom_right) = prototype.bounding_box();
+        let place = |anchor: Point| Transformed { shape: &prototype, transform: Transform::translate(anchor) };
+        let stroke = self.stroke_color.map(|color| (color, self.stroke_pixels()));
+        let mut masks = MaskCache::new(self.precision);
+        for &position in positions {
+            let anchor = transform.transform_point(position);
+            if !self.is_visible((top_left + anchor, bottom_right + anchor)) {
+                continue;
+            }
+            let cached = self.fill_pattern.is_none()
+                && masks.draw(&mut self.pixel_buffer, ShapeKey::Prototype, anchor, place, self.fill_color, stroke);
+            if !cached {
+                self.draw_shape_aa(&place(anchor));
+            }
+        }
+    }
+
+    /// Fills one copy of `shape` per instance, each with its own transform and color.
+    /// The shape is rasterized only once, at the resolution needed by the most scaled
+    /// instance, which makes drawing thousands of copies much cheaper than separate calls.
+    /// Instances are drawn immediately, even inside `with_depth`.
+    pub fn draw_instanced(&mut self, shape: &dyn Shape, instances: &[Instance]) {
+        if instances.is_empty() {
+            return;
+        }
+        self.sync_renderer();
+        let instances: &[Instance] = &self.device_instances(instances);
+        let mask = CoverageMask::from_shape(shape, instance::max_scale(instances).max(1.0));
+        let (width, height) = (self.pixel_buffer.width, self.pixel_buffer.height);
+        for (first_row, band) in self.pixel_buffer.bands_mut(height) {
+            for instance in instances {
+                mask.draw_instance(instance, band, first_row, width);
+            }
         }
     }
 
+    /// Like `draw_instanced`, but splits the canvas into horizontal bands drawn on
+    /// separate threads. Worth it for large instance counts or big instances.
+    pub fn draw_instanced_parallel(&mut self, shape: &dyn Shape, instances: &[Instance]) {
+        if instances.is_empty() {
+            return;
+        }
+        self.sync_renderer();
+        let instances: &[Instance] = &self.device_instances(instances);
+        let mask = CoverageMask::from_shape(shape, instance::max_scale(instances).max(1.0));
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let (width, height) = (self.pixel_buffer.width, self.pixel_buffer.height);
+        let rows = height.div_ceil(threads);
 
+        std::thread::scope(|scope| {
+            for (first_row, band) in self.pixel_buffer.bands_mut(rows) {
+                let mask = &mask;
+                scope.spawn(move || {
+                    for instance in instances {
+                        mask.draw_instance(instance, band, first_row, width);
+                    }
+                });
+            }
+        });
+    }
 
-}
\ No newline at end of file
+    // Maps instances from logical pixels to buffer pixels
+    fn device_instances<'a>(&self, instances: &'a [Instance]) -> std::borrow::Cow<'a, [Instance]> {
+        let scale = self.device_scale();
+        if scale == Transform::identity() {
+            return instances.into();
+        }
+        instances.iter().map(|instance| Instance { transform: scale * instance.transform, ..*instance }).collect()
+    }
+
+    /// Turns caching of rasterized shapes on or off. When on, which is the default, ellipses,
+    /// circles, rounded rectangles, stars and regular polygons drawn repeatedly with the same
+    /// size are rasterized once and then copied, with their positions rounded to a quarter pixel.
+    pub fn set_shape_caching(&mut self, enabled: bool) {
+        self.shape_caching = enabled;
+        if !enabled {
+            self.mask_cache = MaskCache::new(self.precision);
+        }
+    }
+
+    /// Turns fixed-point rasterization on or off. When on, the outlines of shapes, their
+    /// strokes, lines and curves are snapped to 1/256 of a pixel and their coverage is added up
+    /// in 24.8 fixed point instead of floats. Their edges then only change when they move by a
+    /// whole step, which keeps the ends of slowly animated lines from wobbling, and the
+    /// rasterizer's inner loops need no floating point. Off by default.
+    pub fn set_fixed_point(&mut self, enabled: bool) {
+        let precision = if enabled { Precision::Fixed } else { Precision::Float };
+        if precision != self.precision {
+            self.precision = precision;
+            self.mask_cache = MaskCache::new(precision);
+        }
+    }
+
+    /// Whether shapes are rasterized in fixed point. See `set_fixed_point`.
+    pub fn fixed_point(&self) -> bool {
+        self.precision == Precision::Fixed
+    }
+
+    /// Turns timing of lines, fills and strokes on or off, for the frame profiler.
+    pub(crate) fn set_draw_timing(&mut self, enabled: bool) {
+        self.draw_times = enabled.then(StageTimes::default);
+    }
+
+    /// The time spent drawing lines, fills and strokes since the last call, and restarts it.
+    pub(crate) fn take_draw_times(&mut self) -> StageTimes {
+        self.draw_times.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    // Draws a shape through the mask cache, or directly if caching is off or the shape is too big.
+    // `place` creates the shape with `anchor` moved to a given point.
+    fn draw_shape_cached<S: Shape>(&mut self, key: ShapeKey, anchor: Point, place: impl Fn(Point) -> S) {
+        if !self.is_visible(place(anchor).bounding_box()) {
+            return;
+        }
+        let stroke = self.stroke_color.map(|color| (color, self.stroke_pixels()));
+        let cached = self.shape_caching
+            && self.fill_pattern.is_none()
+            && self.mask_cache.draw(&mut self.pixel_buffer, key, anchor, &place, self.fill_color, stroke);
+        if !cached {
+            self.draw_shape_aa(&place(anchor));
+        }
+    }
+
+    fn draw_shape_aa(&mut self, shape: &dyn Shape) {
+        self.sync_renderer();
+        if !self.is_visible(shape.bounding_box()) {
+            return;
+        }
+        if self.fill_color.is_some() || self.stroke_color.is_some() {
+            self.paint_shape(shape);
+        }
+    }
+
+    // Whether drawing commands are queued, recorded or handed to a renderer rather than only
+    // drawn, which batched drawing has to go through one command at a time for.
+    fn queues_commands(&self) -> bool {
+        self.depth.is_some() || self.pick_id.is_some() || self.recording.is_some() || self.command_log.is_some() || self.renderer.is_some()
+    }
+
+    // Whether anything drawn within the bounds, with the current stroke around them and an
+    // antialiased edge, lands on the buffer. Offscreen shapes are skipped before any of them is
+    // flattened, rasterized or cached.
+    fn is_visible(&self, (top_left, bottom_right): (Point, Point)) -> bool {
+        let stroke = if self.stroke_color.is_some() { self.stroke_pixels().max(0.0) / 2.0 } else { 0.0 };
+        let margin = LINE_CLIP_MARGIN + stroke;
+        let (width, height) = (self.pixel_buffer.width as f32, self.pixel_buffer.height as f32);
+        bottom_right.x >= -margin && bottom_right.y >= -margin && top_left.x <= width + margin && top_left.y <= height + margin
+    }
+
+    // Fills and strokes a shape in one pass over its pixels. The fill stops where the stroke
+    // starts, within each pixel, and the pixel is blended with both at once, so the background
+    // doesn't show through where their antialiased edges meet.
+    fn paint_shape(&mut self, shape: &dyn Shape) {
+        let (fill, stroke) = (self.fill_color, self.stroke_color);
+        let weight = self.stroke_pixels();
+        let reach = if stroke.is_some() { weight.max(0.0) / 2.0 + 1.0 } else { 0.0 };
+        let (top_left, bottom_right) = shape.bounding_box();
+        let (width, height) = (self.pixel_buffer.width as i32, self.pixel_buffer.height as i32);
+        let (x1, y1) = (((top_left.x - reach).floor() as i32).max(0), ((top_left.y - reach).floor() as i32).max(0));
+        let (x2, y2) = (((bottom_right.x + reach).ceil() as i32).min(width - 1), ((bottom_right.y + reach).ceil() as i32).min(height - 1));
+        if x1 > x2 || y1 > y2 {
+            return;
+        }
+        let coverage = fill.map(|_| FillCoverage::new(shape, (x1, y1), (x2, y2), self.precision));
+        let stroke = stroke.map(|color| (color, StrokeCoverage::new(shape, weight, (x1, y1), (x2, y2), self.precision)));
+
+        // Patterns are sampled in pattern space, with the fill color's alpha as their opacity
+        let pattern = self.fill_pattern.as_ref().filter(|pattern| pattern.image.width > 0 && pattern.image.height > 0);
+        let pattern = match pattern.map(|pattern| (self.device_scale() * pattern.transform).inverse().map(|to_pattern| (pattern, to_pattern))) {
+            Some(None) => return,
+            pattern => pattern.flatten(),
+        };
+        let filter = self.image_filter;
+        let fill_paint = |px: i32, py: i32, coverage: f32| {
+            let Some(color) = fill.filter(|_| coverage > 0.0) else {
+                return [0.0; 4];
+            };
+            match pattern {
+                Some((pattern, to_pattern)) => {
+                    let point = to_pattern.transform_point(Point::new(px as f32 + 0.5, py as f32 + 0.5));
+                    let opacity = color.a() as f32 / 255.0;
+                    pattern.image.sample_wrapped(filter, point.x - 0.5, point.y - 0.5).map(|channel| channel * opacity * coverage)
+                }
+                None => raster::premultiplied(color, coverage),
+            }
+        };
+
+        shade_rows(&mut self.pixel_buffer, (x1, y1), (x2, y2), |px, py, pixel| {
+            let fill_coverage = coverage.as_ref().map_or(0.0, |coverage| coverage.at(px, py));
+            let paint = match &stroke {
+                Some((color, stroke_coverage)) => {
+                    let (fill_coverage, stroke_coverage) = stroke_coverage.beside_fill(px, py, fill_coverage);
+                    raster::side_by_side(fill_paint(px, py, fill_coverage), raster::premultiplied(*color, stroke_coverage))
+                }
+                None => fill_paint(px, py, fill_coverage),
+            };
+            raster::blend_premultiplied(pixel, paint);
+        });
+    }
+}
+
+// The scale of a transform made of rotations, uniform scales and translations, which keep
+// circles round, or None for other transforms.
+fn similarity_scale(transform: &Transform) -> Option<f32> {
+    let tolerance = f32::EPSILON * (transform.m11.abs() + transform.m12.abs());
+    let similar = (transform.m11 - transform.m22).abs() <= tolerance && (transform.m12 + transform.m21).abs() <= tolerance;
+    similar.then(|| transform.m11.hypot(transform.m21))
+}
+
+// Calls `shade` with the position and pixel of every pixel from `(x1, y1)` to `(x2, y2)`
+// that is inside the buffer. Large areas are split into bands of rows, shaded on separate
+// threads.
+fn shade_rows(buffer: &mut PixelBuffer, (x1, y1): (i32, i32), (x2, y2): (i32, i32), shade: impl Fn(i32, i32, &mut u32) + Sync) {
+    let (width, height) = (buffer.width as i32, buffer.height as i32);
+    let (x1, y1, x2, y2) = (x1.max(0), y1.max(0), x2.min(width - 1), y2.min(height - 1));
+    if x1 > x2 || y1 > y2 {
+        return;
+    }
+    let width = width as usize;
+    let rows = &mut buffer.get_buffer_mut()[y1 as usize * width..(y2 as usize + 1) * width];
+    let shade_band = |first_row: usize, band: &mut [u32]| {
+        for (i, row) in band.chunks_mut(width).enumerate() {
+            let y = (first_row + i) as i32;
+            for x in x1..=x2 {
+                shade(x, y, &mut row[x as usize]);
+            }
+        }
+    };
+
+    let row_count = (y2 - y1 + 1) as usize;
+    let threads = if row_count * (x2 - x1 + 1) as usize >= PARALLEL_SHADING_PIXELS {
+        std::thread::available_parallelism().map_or(1, |n| n.get()).min(row_count)
+    } else {
+        1
+    };
+    if threads <= 1 {
+        shade_band(y1 as usize, rows);
+        return;
+    }
+    let band_rows = row_count.div_ceil(threads);
+    std::thread::scope(|scope| {
+        for (i, band) in rows.chunks_mut(band_rows * width).enumerate() {
+            let shade_band = &shade_band;
+            scope.spawn(move || shade_band(y1 as usize + i * band_rows, band));
+        }
+    });
+}
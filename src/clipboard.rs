@@ -0,0 +1,122 @@
+//! Access to the system clipboard through the platform's command line tools.
+//!
+//! minifb has no clipboard support, so text and images go through `pbcopy` and `osascript`
+//! on macOS, PowerShell on Windows, and `wl-copy` or `xclip` elsewhere. A missing tool shows
+//! up as a `SketchError::Clipboard`.
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use crate::error::SketchError;
+
+// Each list holds alternative commands, tried in order until one of them is installed.
+#[cfg(target_os = "macos")]
+mod commands {
+    pub const GET_TEXT: &[&[&str]] = &[&["pbpaste"]];
+    pub const SET_TEXT: &[&[&str]] = &[&["pbcopy"]];
+}
+
+#[cfg(target_os = "windows")]
+mod commands {
+    pub const GET_TEXT: &[&[&str]] = &[&["powershell", "-NoProfile", "-Command", "Get-Clipboard -Raw"]];
+    pub const SET_TEXT: &[&[&str]] = &[&["powershell", "-NoProfile", "-Command", "$input | Set-Clipboard"]];
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod commands {
+    pub const GET_TEXT: &[&[&str]] = &[
+        &["wl-paste", "--no-newline"],
+        &["xclip", "-selection", "clipboard", "-out"],
+        &["xsel", "--clipboard", "--output"],
+    ];
+    pub const SET_TEXT: &[&[&str]] = &[
+        &["wl-copy"],
+        &["xclip", "-selection", "clipboard", "-in"],
+        &["xsel", "--clipboard", "--input"],
+    ];
+    pub const SET_PNG: &[&[&str]] = &[
+        &["wl-copy", "--type", "image/png"],
+        &["xclip", "-selection", "clipboard", "-target", "image/png", "-in"],
+    ];
+}
+
+/// Reads the text on the clipboard.
+pub(crate) fn text() -> Result<String, SketchError> {
+    let output = run(commands::GET_TEXT, None)?;
+    String::from_utf8(output).map_err(|_| SketchError::Clipboard("the clipboard text is not valid UTF-8".to_string()))
+}
+
+/// Puts text on the clipboard.
+pub(crate) fn set_text(text: &str) -> Result<(), SketchError> {
+    run(commands::SET_TEXT, Some(text.as_bytes())).map(|_| ())
+}
+
+/// Puts a PNG image on the clipboard.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub(crate) fn set_png(png: &[u8]) -> Result<(), SketchError> {
+    run(commands::SET_PNG, Some(png)).map(|_| ())
+}
+
+/// Puts a PNG image on the clipboard. The image is passed to the clipboard tool as a
+/// temporary file.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) fn set_png(png: &[u8]) -> Result<(), SketchError> {
+    let path = std::env::temp_dir().join("simple-sketch-clipboard.png");
+    std::fs::write(&path, png)?;
+    let path = path.display().to_string();
+    #[cfg(target_os = "macos")]
+    let script = format!("set the clipboard to (read (POSIX file \"{path}\") as «class PNGf»)");
+    #[cfg(target_os = "macos")]
+    let command: &[&str] = &["osascript", "-e", &script];
+    #[cfg(target_os = "windows")]
+    let script = format!(
+        "Add-Type -AssemblyName System.Windows.Forms, System.Drawing; \
+         [System.Windows.Forms.Clipboard]::SetImage([System.Drawing.Image]::FromFile('{path}'))"
+    );
+    #[cfg(target_os = "windows")]
+    let command: &[&str] = &["powershell", "-NoProfile", "-Sta", "-Command", &script];
+    let result = run(&[command], None).map(|_| ());
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+// Runs the first of `alternatives` that is installed and succeeds, writing `input` to its
+// standard input, and returns its standard output. Trying the next command after a failure
+// skips Wayland tools on X11 sessions and the other way around.
+fn run(alternatives: &[&[&str]], input: Option<&[u8]>) -> Result<Vec<u8>, SketchError> {
+    let mut failure = None;
+    for command in alternatives {
+        let (program, args) = command.split_first().expect("clipboard commands are not empty");
+        // Tools that set the clipboard, like xclip, can leave a process behind to serve it, which
+        // keeps any pipe to its output open, so their output is discarded instead
+        let output = || if input.is_some() { Stdio::null() } else { Stdio::piped() };
+        let child = Command::new(program)
+            .args(args)
+            .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(output())
+            .stderr(output())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(error.into()),
+        };
+
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            // A tool that fails early may close its input, which shows up in its exit status
+            let _ = stdin.write_all(input);
+        }
+        let output = child.wait_with_output()?;
+        if output.status.success() {
+            return Ok(output.stdout);
+        }
+        let message = String::from_utf8_lossy(&output.stderr);
+        failure = Some(SketchError::Clipboard(format!("{program} failed ({}): {}", output.status, message.trim())));
+    }
+
+    if let Some(failure) = failure {
+        return Err(failure);
+    }
+    let programs: Vec<&str> = alternatives.iter().map(|command| command[0]).collect();
+    Err(SketchError::Clipboard(format!("no clipboard tool found, install one of: {}", programs.join(", "))))
+}
@@ -1,6 +1,7 @@
 use std::fmt;
 
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color(pub u32);
 
 pub enum ColorFormat {
@@ -22,6 +23,17 @@ impl Color {
         Color::new(self.r(), self.g(), self.b(), alpha)
     }
 
+    // Linearly interpolates every channel (including alpha) towards another color
+    pub fn lerp(&self, other: &Color, t: f32) -> Color {
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color::new(
+            mix(self.r(), other.r()),
+            mix(self.g(), other.g()),
+            mix(self.b(), other.b()),
+            mix(self.a(), other.a()),
+        )
+    }
+
     // Helper function to convert HSV to RGB
     pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> Color {
         let c = v * s;
@@ -0,0 +1,166 @@
+//! Canvas drawing calls captured as data.
+//!
+//! Every public drawing method of the `Canvas` is expressed as a `DrawCommand`, which is
+//! either rasterized immediately or queued together with the `Style` that was active when
+//! it was issued.
+
+use crate::color::Color;
+use crate::geom::{Point, Rect, Path, Transform};
+use crate::shape::{Arc, Capsule, RegularPolygon, RoundedRect, Star};
+
+/// The drawing state that affects how a command is rasterized.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Style {
+    pub fill: Option<Color>,
+    pub stroke: Option<Color>,
+    pub stroke_weight: f32,
+}
+
+/// A single drawing operation on the canvas.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawCommand {
+    Background(Color),
+    Line { start: Point, end: Point },
+    Bezier { start: Point, control1: Point, control2: Point, end: Point },
+    Ellipse { center: Point, width: f32, height: f32 },
+    Circle { center: Point, diameter: f32 },
+    Triangle { a: Point, b: Point, c: Point },
+    Capsule(Capsule),
+    Arc(Arc),
+    RoundedRect(RoundedRect),
+    Star(Star),
+    RegularPolygon(RegularPolygon),
+    Rectangle { top_left: Point, width: f32, height: f32 },
+    Path(Path),
+}
+
+impl DrawCommand {
+    /// Maps the command's geometry through `transform`. Ellipses and rectangles stay
+    /// axis-aligned shapes under translation and scaling, and become paths otherwise.
+    /// Circles stay circles unless the transform scales differently along different axes,
+    /// and arcs stay arcs unless it does that or mirrors them. Rounded rectangles stay
+    /// rounded rectangles under translation and uniform scaling, and stars, regular
+    /// polygons and capsules keep their shape under the same transforms as circles.
+    pub fn transformed(&self, transform: &Transform) -> DrawCommand {
+        let map = |point: &Point| transform.transform_point(*point);
+        let axis_aligned = transform.m12 == 0.0 && transform.m21 == 0.0;
+        // Rotations and uniform scales map arcs to arcs, and with reflections circles to circles
+        let tolerance = f32::EPSILON * (transform.m11.abs() + transform.m12.abs());
+        let similar = (transform.m11 - transform.m22).abs() <= tolerance && (transform.m12 + transform.m21).abs() <= tolerance;
+        let conformal = similar || ((transform.m11 + transform.m22).abs() <= tolerance && (transform.m12 - transform.m21).abs() <= tolerance);
+
+        match self {
+            DrawCommand::Background(color) => DrawCommand::Background(*color),
+            DrawCommand::Line { start, end } => DrawCommand::Line { start: map(start), end: map(end) },
+            DrawCommand::Bezier { start, control1, control2, end } => DrawCommand::Bezier {
+                start: map(start),
+                control1: map(control1),
+                control2: map(control2),
+                end: map(end),
+            },
+            DrawCommand::Ellipse { center, width, height } if axis_aligned => DrawCommand::Ellipse {
+                center: map(center),
+                width: (width * transform.m11).abs(),
+                height: (height * transform.m22).abs(),
+            },
+            DrawCommand::Ellipse { center, width, height } => {
+                DrawCommand::Path(Path::ellipse(*center, *width, *height).transform(transform))
+            }
+            DrawCommand::Circle { center, diameter } if conformal => DrawCommand::Circle {
+                center: map(center),
+                diameter: diameter * transform.m11.hypot(transform.m21),
+            },
+            DrawCommand::Circle { center, diameter } => {
+                DrawCommand::Path(Path::ellipse(*center, *diameter, *diameter).transform(transform))
+            }
+            DrawCommand::Arc(arc) if similar => {
+                let rotation = transform.m21.atan2(transform.m11);
+                DrawCommand::Arc(Arc {
+                    center: map(&arc.center),
+                    radius: arc.radius * transform.m11.hypot(transform.m21),
+                    start: arc.start + rotation,
+                    end: arc.end + rotation,
+                    mode: arc.mode,
+                })
+            }
+            DrawCommand::Arc(arc) => DrawCommand::Path(arc.to_path().transform(transform)),
+            DrawCommand::RoundedRect(rect) if axis_aligned && transform.m11.abs() == transform.m22.abs() => {
+                let corner = rect.top_left + Point::new(rect.width, rect.height);
+                let bounds = Rect::from_points(map(&rect.top_left), map(&corner));
+                // Mirroring moves the radii to other corners
+                let [top_left, top_right, bottom_right, bottom_left] = rect.radii;
+                let radii = match (transform.m11 < 0.0, transform.m22 < 0.0) {
+                    (false, false) => rect.radii,
+                    (true, false) => [top_right, top_left, bottom_left, bottom_right],
+                    (false, true) => [bottom_left, bottom_right, top_right, top_left],
+                    (true, true) => [bottom_right, bottom_left, top_left, top_right],
+                };
+                DrawCommand::RoundedRect(RoundedRect {
+                    top_left: bounds.min,
+                    width: bounds.width(),
+                    height: bounds.height(),
+                    radii: radii.map(|radius| radius * transform.m11.abs()),
+                })
+            }
+            DrawCommand::RoundedRect(rect) => DrawCommand::Path(rect.to_path().transform(transform)),
+            DrawCommand::Star(star) if conformal => {
+                // Stars are symmetric, so mirroring them only changes their rotation
+                let angle = transform.m21.atan2(transform.m11);
+                let scale = transform.m11.hypot(transform.m21);
+                DrawCommand::Star(Star {
+                    center: map(&star.center),
+                    inner_radius: star.inner_radius * scale,
+                    outer_radius: star.outer_radius * scale,
+                    rotation: if similar { star.rotation + angle } else { angle - star.rotation },
+                    ..*star
+                })
+            }
+            DrawCommand::Star(star) => DrawCommand::Path(Path::polygon(&star.vertices()).transform(transform)),
+            DrawCommand::RegularPolygon(polygon) if conformal => {
+                // Like stars, regular polygons are symmetric
+                let angle = transform.m21.atan2(transform.m11);
+                DrawCommand::RegularPolygon(RegularPolygon {
+                    center: map(&polygon.center),
+                    radius: polygon.radius * transform.m11.hypot(transform.m21),
+                    rotation: if similar { polygon.rotation + angle } else { angle - polygon.rotation },
+                    ..*polygon
+                })
+            }
+            DrawCommand::RegularPolygon(polygon) => DrawCommand::Path(Path::polygon(&polygon.vertices()).transform(transform)),
+            DrawCommand::Triangle { a, b, c } => DrawCommand::Triangle { a: map(a), b: map(b), c: map(c) },
+            DrawCommand::Capsule(capsule) if conformal => DrawCommand::Capsule(Capsule {
+                start: map(&capsule.start),
+                end: map(&capsule.end),
+                radius: capsule.radius * transform.m11.hypot(transform.m21),
+            }),
+            DrawCommand::Capsule(capsule) => DrawCommand::Path(capsule.to_path().transform(transform)),
+            DrawCommand::Rectangle { top_left, width, height } if axis_aligned => {
+                let rect = Rect::from_points(map(top_left), map(&(*top_left + Point::new(*width, *height))));
+                DrawCommand::Rectangle { top_left: rect.min, width: rect.width(), height: rect.height() }
+            }
+            DrawCommand::Rectangle { top_left, width, height } => {
+                DrawCommand::Path(Path::rectangle(*top_left, *width, *height).transform(transform))
+            }
+            DrawCommand::Path(path) => DrawCommand::Path(path.transform(transform)),
+        }
+    }
+}
+
+/// A command waiting to be rasterized in depth order.
+#[derive(Debug, Clone)]
+pub(crate) struct DepthEntry {
+    pub depth: f32,
+    pub style: Style,
+    pub command: DrawCommand,
+}
+
+/// A command as it was drawn, with the style it was drawn with, in pixels of the pixel
+/// buffer. See `Canvas::start_recording_commands`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedCommand {
+    pub command: DrawCommand,
+    pub style: Style,
+}
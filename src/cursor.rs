@@ -0,0 +1,73 @@
+//! Mouse cursors: the window system's standard cursors, and cursors drawn on the canvas.
+//!
+//! minifb can only switch between the system's standard cursors or hide the cursor. Anything
+//! else is a `SoftwareCursor`, a path drawn on top of each frame at the mouse position while
+//! the system cursor is hidden.
+
+use crate::color::Color;
+use crate::geom::{Path, Point};
+
+/// One of the window system's standard cursors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CursorStyle {
+    #[default]
+    Arrow,
+    /// The I-beam shown over text.
+    Text,
+    Crosshair,
+    /// An open hand, for things that can be dragged.
+    OpenHand,
+    /// A closed hand, for things being dragged.
+    ClosedHand,
+    ResizeHorizontal,
+    ResizeVertical,
+    /// Arrows in all four directions, for moving things.
+    Move,
+}
+
+/// A cursor drawn on the canvas, in canvas pixels.
+#[derive(Debug, Clone)]
+pub struct SoftwareCursor {
+    /// The outline of the cursor, with the hotspot at the origin.
+    pub shape: Path,
+    pub fill: Option<Color>,
+    pub stroke: Option<Color>,
+    pub stroke_weight: f32,
+}
+
+impl SoftwareCursor {
+    /// Creates a cursor filled white with a black outline. The hotspot, the point that is
+    /// placed at the mouse position, is the origin of `shape`.
+    pub fn new(shape: Path) -> Self {
+        SoftwareCursor {
+            shape,
+            fill: Some(Color::new(255, 255, 255, 255)),
+            stroke: Some(Color::new(0, 0, 0, 255)),
+            stroke_weight: 1.0,
+        }
+    }
+
+    /// An arrow pointing up and to the left, 16 pixels tall.
+    pub fn arrow() -> Self {
+        SoftwareCursor::new(Path::polygon(&[
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 15.0),
+            Point::new(4.0, 11.5),
+            Point::new(7.0, 17.0),
+            Point::new(9.5, 16.0),
+            Point::new(6.5, 10.5),
+            Point::new(11.0, 10.5),
+        ]))
+    }
+
+    /// A cross of two lines, 21 pixels wide, centered on the hotspot.
+    pub fn crosshair() -> Self {
+        let mut shape = Path::new();
+        shape
+            .move_to(Point::new(-10.0, 0.0))
+            .line_to(Point::new(10.0, 0.0))
+            .move_to(Point::new(0.0, -10.0))
+            .line_to(Point::new(0.0, 10.0));
+        SoftwareCursor { fill: None, stroke_weight: 2.0, ..SoftwareCursor::new(shape) }
+    }
+}
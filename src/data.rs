@@ -0,0 +1,476 @@
+//! Loading CSV and JSON data files for data visualization sketches, without further setup.
+//!
+//! Both formats load into `Value`s: CSV files into a `Table` of rows, JSON files into a
+//! single value. Values can be indexed by key or position, giving `Value::Null` for anything
+//! missing, so chains like `data["cities"][0]["population"].as_f64()` never panic.
+
+use std::fmt;
+use std::fs;
+use std::ops::Index;
+use std::path::Path;
+
+use crate::error::SketchError;
+
+/// Nesting depth of JSON arrays and objects past which documents are rejected.
+const MAX_JSON_DEPTH: usize = 256;
+
+/// A value loaded from a data file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Value {
+    /// JSON `null`, an empty CSV cell, or a missing key or position.
+    #[default]
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// The entries of a JSON object, in the order of the file.
+    Object(Vec<(String, Value)>),
+}
+
+static NULL: Value = Value::Null;
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The number as an `f32`, for passing straight to drawing calls.
+    pub fn as_f32(&self) -> Option<f32> {
+        self.as_f64().map(|value| value as f32)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// The value of `key` in an object, or None for other values and missing keys.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(entries) => entries.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+impl Index<&str> for Value {
+    type Output = Value;
+
+    /// The value of `key` in an object, or `Value::Null`.
+    fn index(&self, key: &str) -> &Value {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+impl Index<usize> for Value {
+    type Output = Value;
+
+    /// The element at `index` in an array, or `Value::Null`.
+    fn index(&self, index: usize) -> &Value {
+        self.as_array().and_then(|values| values.get(index)).unwrap_or(&NULL)
+    }
+}
+
+impl fmt::Display for Value {
+    /// Writes the value as JSON.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Number(value) if value.is_finite() => write!(f, "{value}"),
+            Value::Number(_) => write!(f, "null"),
+            Value::String(value) => write_json_string(f, value),
+            Value::Array(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(entries) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_json_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// The rows of a CSV file, under the column names of its first line.
+///
+/// Cells that read as numbers become `Value::Number`, empty cells `Value::Null` and all
+/// others `Value::String`. Rows with fewer cells than there are columns are filled up with
+/// `Value::Null`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::data::load_csv;
+/// # use simple_sketch::error::SketchError;
+/// # use simple_sketch::geom::Point;
+/// # fn draw(canvas: &mut Canvas) -> Result<(), SketchError> {
+/// let table = load_csv("temperatures.csv")?;
+/// for row in 0..table.len() {
+///     let x = table.get(row, "year").as_f32().unwrap_or(0.0);
+///     let y = table.get(row, "anomaly").as_f32().unwrap_or(0.0);
+///     canvas.circle(Point::new(x - 1880.0, 200.0 - y * 100.0), 4.0);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    /// The number of rows, not counting the line of column names.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// The position of the column named `name`.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|column| column == name)
+    }
+
+    /// The cell of `row` in the column named `column`, or `Value::Null` if there is none.
+    pub fn get(&self, row: usize, column: &str) -> &Value {
+        self.column_index(column).and_then(|column| self.rows.get(row)?.get(column)).unwrap_or(&NULL)
+    }
+
+    /// The cells of the column named `column` from top to bottom, empty if there is none.
+    pub fn column(&self, column: &str) -> Vec<&Value> {
+        match self.column_index(column) {
+            Some(index) => self.rows.iter().map(|row| &row[index]).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The numbers in the column named `column` from top to bottom, skipping other cells. For
+    /// finding the range to map a column onto the canvas.
+    pub fn numbers(&self, column: &str) -> Vec<f64> {
+        self.column(column).into_iter().filter_map(Value::as_f64).collect()
+    }
+}
+
+/// Loads a CSV file with a first line of column names. See `parse_csv`.
+pub fn load_csv(path: impl AsRef<Path>) -> Result<Table, SketchError> {
+    let path = path.as_ref();
+    parse_csv(&read(path)?).map_err(|error| with_path(error, path))
+}
+
+/// Reads CSV text with a first line of column names, fields separated by commas and quoted
+/// with double quotes where they contain commas, quotes or line breaks.
+pub fn parse_csv(text: &str) -> Result<Table, SketchError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = csv_records(text)?.into_iter();
+    let (_, columns) = records.next().ok_or_else(|| SketchError::Data("the file is empty".to_string()))?;
+    let columns: Vec<String> = columns.iter().map(|column| column.trim().to_string()).collect();
+    let mut rows = Vec::new();
+    for (line, record) in records {
+        if record.len() > columns.len() {
+            let reason = format!("line {line}: {} fields but only {} columns", record.len(), columns.len());
+            return Err(SketchError::Data(reason));
+        }
+        let mut row: Vec<Value> = record.into_iter().map(cell_value).collect();
+        row.resize(columns.len(), Value::Null);
+        rows.push(row);
+    }
+    Ok(Table { columns, rows })
+}
+
+/// Loads a JSON file. See `parse_json`.
+pub fn load_json(path: impl AsRef<Path>) -> Result<Value, SketchError> {
+    let path = path.as_ref();
+    parse_json(&read(path)?).map_err(|error| with_path(error, path))
+}
+
+/// Reads a JSON document.
+pub fn parse_json(text: &str) -> Result<Value, SketchError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut parser = JsonParser { text, position: 0 };
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.position < text.len() {
+        return Err(parser.error("unexpected text after the document"));
+    }
+    Ok(value)
+}
+
+fn read(path: &Path) -> Result<String, SketchError> {
+    fs::read_to_string(path).map_err(|e| std::io::Error::new(e.kind(), format!("could not load {}: {e}", path.display())).into())
+}
+
+fn with_path(error: SketchError, path: &Path) -> SketchError {
+    match error {
+        SketchError::Data(reason) => SketchError::Data(format!("{}: {reason}", path.display())),
+        error => error,
+    }
+}
+
+// Splits CSV text into records of fields, each with the line it starts on. Blank lines are
+// skipped.
+fn csv_records(text: &str) -> Result<Vec<(usize, Vec<String>)>, SketchError> {
+    let mut records = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while chars.peek().is_some() {
+        let start = line;
+        let mut record = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        loop {
+            match chars.next() {
+                Some('"') if quoted => {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        quoted = false;
+                    }
+                }
+                Some('"') if field.is_empty() => quoted = true,
+                Some(',') if !quoted => record.push(std::mem::take(&mut field)),
+                Some('\r') if !quoted && chars.peek() == Some(&'\n') => {}
+                Some('\n') if !quoted => {
+                    line += 1;
+                    break;
+                }
+                Some(c) => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+                None if quoted => return Err(SketchError::Data(format!("line {start}: unterminated quoted field"))),
+                None => break,
+            }
+        }
+        if !record.is_empty() || !field.trim().is_empty() {
+            record.push(field);
+            records.push((start, record));
+        }
+    }
+    Ok(records)
+}
+
+fn cell_value(cell: String) -> Value {
+    let trimmed = cell.trim();
+    if trimmed.is_empty() {
+        Value::Null
+    } else if let Some(number) = trimmed.parse::<f64>().ok().filter(|number| number.is_finite()) {
+        Value::Number(number)
+    } else {
+        Value::String(cell)
+    }
+}
+
+fn write_json_string(f: &mut fmt::Formatter<'_>, text: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in text.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+struct JsonParser<'a> {
+    text: &'a str,
+    /// Byte offset of the next character.
+    position: usize,
+}
+
+impl JsonParser<'_> {
+    fn value(&mut self, depth: usize) -> Result<Value, SketchError> {
+        if depth > MAX_JSON_DEPTH {
+            return Err(self.error("too deeply nested"));
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => {
+                self.position += 1;
+                let mut entries = Vec::new();
+                if !self.eat('}') {
+                    loop {
+                        self.skip_whitespace();
+                        if self.peek() != Some('"') {
+                            return Err(self.error("expected a key"));
+                        }
+                        let key = self.string()?;
+                        if !self.eat(':') {
+                            return Err(self.error("expected ':'"));
+                        }
+                        entries.push((key, self.value(depth + 1)?));
+                        if self.eat('}') {
+                            break;
+                        }
+                        if !self.eat(',') {
+                            return Err(self.error("expected ',' or '}'"));
+                        }
+                    }
+                }
+                Ok(Value::Object(entries))
+            }
+            Some('[') => {
+                self.position += 1;
+                let mut values = Vec::new();
+                if !self.eat(']') {
+                    loop {
+                        values.push(self.value(depth + 1)?);
+                        if self.eat(']') {
+                            break;
+                        }
+                        if !self.eat(',') {
+                            return Err(self.error("expected ',' or ']'"));
+                        }
+                    }
+                }
+                Ok(Value::Array(values))
+            }
+            Some('"') => self.string().map(Value::String),
+            Some('t') if self.keyword("true") => Ok(Value::Bool(true)),
+            Some('f') if self.keyword("false") => Ok(Value::Bool(false)),
+            Some('n') if self.keyword("null") => Ok(Value::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let rest = &self.text[self.position..];
+                let length = rest.find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E')).unwrap_or(rest.len());
+                let number = rest[..length].parse().map_err(|_| self.error("invalid number"))?;
+                self.position += length;
+                Ok(Value::Number(number))
+            }
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of the document")),
+        }
+    }
+
+    // Reads a string, starting at its opening quote.
+    fn string(&mut self) -> Result<String, SketchError> {
+        self.position += 1;
+        let mut result = String::new();
+        loop {
+            let c = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+            self.position += c.len_utf8();
+            match c {
+                '"' => return Ok(result),
+                '\\' => {
+                    let escape = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+                    self.position += escape.len_utf8();
+                    result.push(match escape {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => {
+                            let high = self.hex_escape()?;
+                            // Characters outside the basic plane are escaped as surrogate pairs
+                            let code = if (0xD800..0xDC00).contains(&high) && self.text[self.position..].starts_with("\\u") {
+                                self.position += 2;
+                                let low = self.hex_escape()?;
+                                0x10000 + ((high - 0xD800) << 10) + low.wrapping_sub(0xDC00)
+                            } else {
+                                high
+                            };
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    });
+                }
+                c if (c as u32) < 0x20 => return Err(self.error("control character in string")),
+                c => result.push(c),
+            }
+        }
+    }
+
+    fn hex_escape(&mut self) -> Result<u32, SketchError> {
+        let digits = self.text.get(self.position..self.position + 4).ok_or_else(|| self.error("invalid escape"))?;
+        let code = u32::from_str_radix(digits, 16).map_err(|_| self.error("invalid escape"))?;
+        self.position += 4;
+        Ok(code)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.text[self.position..].starts_with(keyword);
+        if found {
+            self.position += keyword.len();
+        }
+        found
+    }
+
+    // Skips whitespace and then `c` if it is next.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        let found = self.peek() == Some(c);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.position..];
+        self.position += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    // An error at the current position, with its line and column.
+    fn error(&self, reason: &str) -> SketchError {
+        let before = &self.text[..self.position.min(self.text.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+        SketchError::Data(format!("line {line}, column {column}: {reason}"))
+    }
+}
@@ -0,0 +1,124 @@
+//! A debug overlay that plots the recent history of animated values as sparklines.
+//!
+//! Register a channel per value, feed it every frame (manually or from a `Timeline`),
+//! and draw the graph on top of the sketch to see how easing and physics parameters behave.
+
+use std::collections::VecDeque;
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::geom::{Point, Rect};
+use crate::timeline::Timeline;
+
+struct Channel {
+    name: String,
+    color: Color,
+    samples: VecDeque<(f32, f32)>,
+}
+
+/// Records values over a sliding time window and draws them as sparklines.
+pub struct DebugGraph {
+    window: f32,
+    channels: Vec<Channel>,
+}
+
+impl DebugGraph {
+    /// Creates a graph showing the last `window` seconds of every channel.
+    pub fn new(window: f32) -> Self {
+        DebugGraph {
+            window,
+            channels: Vec::new(),
+        }
+    }
+
+    /// Registers a channel drawn in `color`. Registering an existing name updates its color.
+    pub fn register(&mut self, name: &str, color: Color) {
+        match self.channels.iter_mut().find(|c| c.name == name) {
+            Some(channel) => channel.color = color,
+            None => self.channels.push(Channel {
+                name: name.to_string(),
+                color,
+                samples: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Records `value` for the channel `name` at `time` seconds.
+    /// Samples for unregistered channels are ignored.
+    pub fn record(&mut self, name: &str, time: f32, value: f32) {
+        let window = self.window;
+        if let Some(channel) = self.channels.iter_mut().find(|c| c.name == name) {
+            channel.samples.push_back((time, value));
+            while channel.samples.front().is_some_and(|&(t, _)| t < time - window) {
+                channel.samples.pop_front();
+            }
+        }
+    }
+
+    /// Records the current value of every registered channel that has a matching float
+    /// track in `timeline`, sampled at the timeline's current time.
+    pub fn record_timeline(&mut self, timeline: &Timeline) {
+        let time = timeline.time();
+        let names: Vec<String> = self.channels.iter().map(|c| c.name.clone()).collect();
+        for name in names {
+            if let Some(value) = timeline.value::<f32>(&name) {
+                self.record(&name, time, value);
+            }
+        }
+    }
+
+    /// Removes all recorded samples, keeping the registered channels.
+    pub fn clear(&mut self) {
+        for channel in &mut self.channels {
+            channel.samples.clear();
+        }
+    }
+
+    /// Draws the graph into `area` on the canvas. Each channel is scaled to its own
+    /// min/max range so values of different magnitudes remain readable.
+    /// The canvas fill, stroke and stroke weight are restored afterwards.
+    pub fn draw(&self, canvas: &mut Canvas, area: Rect) {
+        let fill = canvas.fill_color();
+        let stroke = canvas.stroke_color();
+        let weight = canvas.stroke_weight();
+
+        canvas.set_fill(Some(Color::new(0, 0, 0, 160)));
+        canvas.set_stroke(Some(Color::new(96, 96, 96, 255)));
+        canvas.set_stroke_weight(1.0);
+        canvas.rectangle(area.min, area.width(), area.height());
+
+        let latest = self
+            .channels
+            .iter()
+            .filter_map(|c| c.samples.back().map(|&(t, _)| t))
+            .fold(f32::NEG_INFINITY, f32::max);
+
+        for channel in &self.channels {
+            let (min, max) = channel
+                .samples
+                .iter()
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &(_, v)| (lo.min(v), hi.max(v)));
+            let range = max - min;
+
+            let to_screen = |&(time, value): &(f32, f32)| {
+                let x = area.max.x - (latest - time) / self.window * area.width();
+                let y = if range > 0.0 {
+                    area.max.y - (value - min) / range * area.height()
+                } else {
+                    area.center().y
+                };
+                Point::new(x, y)
+            };
+
+            canvas.set_stroke(Some(channel.color));
+            let points: Vec<Point> = channel.samples.iter().map(to_screen).collect();
+            for segment in points.windows(2) {
+                canvas.line(segment[0], segment[1]);
+            }
+        }
+
+        canvas.set_fill(fill);
+        canvas.set_stroke(stroke);
+        canvas.set_stroke_weight(weight);
+    }
+}
@@ -0,0 +1,105 @@
+//! Display lists: drawing recorded once and replayed many times.
+//!
+//! Drawing that stays the same from frame to frame, like a background generated from
+//! thousands of noise-driven strokes, can be recorded into a `DisplayList` once and then
+//! replayed every frame with `Canvas::draw_display_list`, without running the code that
+//! generated it again. The same list can be replayed under other transforms, through another
+//! `Renderer`, or written to a PDF file or a `Scene`.
+
+use std::path::Path;
+
+use crate::canvas::Canvas;
+use crate::command::RecordedCommand;
+use crate::error::SketchError;
+use crate::geom::Transform;
+use crate::pdf::{self, Page};
+use crate::pixelbuffer::{self, PixelBuffer};
+use crate::renderer::Renderer;
+use crate::scene::Scene;
+
+/// A list of drawing commands, each with the style it was drawn with, captured from a canvas
+/// without being rasterized.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::color::Color;
+/// # use simple_sketch::display_list::DisplayList;
+/// # use simple_sketch::geom::Point;
+/// # fn draw(canvas: &mut Canvas, palette: &[Color], starts: &[Point], controls: &[(Point, Point)], ends: &[Point]) {
+/// let hills = DisplayList::record(800, 600, |canvas| {
+///     for i in 0..2000 {
+///         canvas.set_stroke(Some(palette[i % palette.len()]));
+///         canvas.bezier(starts[i], controls[i].0, controls[i].1, ends[i]);
+///     }
+/// });
+///
+/// // Every frame
+/// canvas.draw_display_list(&hills);
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DisplayList {
+    commands: Vec<RecordedCommand>,
+}
+
+impl DisplayList {
+    /// Records what `draw` draws on a canvas of `width` by `height` pixels, in the coordinates
+    /// of its drawing calls. The canvas has no pixels, so nothing is rasterized. Commands are
+    /// kept in the order they would reach the pixels, after transforms and depth sorting, and
+    /// an opaque background discards the commands before it. Like with
+    /// `Canvas::start_recording_commands`, images, `debug_text`, `draw_shape_transformed`,
+    /// `draw_shapes`, instanced drawing and fills with a pattern aren't recorded.
+    pub fn record(width: usize, height: usize, draw: impl FnOnce(&mut Canvas)) -> Self {
+        let mut canvas = Canvas::recorder(width, height);
+        draw(&mut canvas);
+        canvas.flush();
+        DisplayList { commands: canvas.stop_recording_commands() }
+    }
+
+    /// The recorded commands, in drawing order, with their styles.
+    pub fn commands(&self) -> &[RecordedCommand] {
+        &self.commands
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Draws the list over `pixels` with `renderer`, through `transform`, which maps the
+    /// recorded coordinates to the pixels. Stroke weights aren't scaled by the transform, like
+    /// on a canvas.
+    pub fn replay(&self, renderer: &mut dyn Renderer, transform: &Transform, pixels: &mut PixelBuffer) {
+        renderer.begin(pixels);
+        for recorded in &self.commands {
+            renderer.draw(&recorded.command.transformed(transform), &recorded.style);
+        }
+        renderer.finish(pixels);
+    }
+
+    /// Saves the list as a one page PDF document of `width` by `height` recorded pixels, at
+    /// `dpi` pixels per inch, creating missing directories. See `Canvas::save_pdf`.
+    pub fn save_pdf(&self, path: impl AsRef<Path>, width: f32, height: f32, dpi: f32) -> Result<(), SketchError> {
+        let points_per_pixel = 72.0 / dpi;
+        let page = Page { width: width * points_per_pixel, height: height * points_per_pixel, scale: points_per_pixel };
+        pixelbuffer::save_file(path.as_ref(), |out| pdf::write_pdf(out, &self.commands, &page))
+    }
+}
+
+impl From<Scene> for DisplayList {
+    fn from(scene: Scene) -> Self {
+        DisplayList { commands: scene.commands }
+    }
+}
+
+impl From<DisplayList> for Scene {
+    /// A scene of the list's commands, for saving it with `Scene::save`.
+    fn from(list: DisplayList) -> Self {
+        Scene { commands: list.commands }
+    }
+}
@@ -0,0 +1,91 @@
+//! The error type of running sketches and of reading and writing their files.
+
+use std::fmt;
+
+use crate::font::FontError;
+
+/// An error running a sketch, or reading or writing one of its files.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SketchError {
+    /// The window could not be opened.
+    WindowCreation(String),
+    /// The canvas can't have this size, for example because it is empty.
+    InvalidSize { width: usize, height: usize },
+    /// A frame could not be shown in the window.
+    WindowUpdate(String),
+    /// `ApplicationRunner::run` was called on a headless runner.
+    NoWindow,
+    /// A file could not be read or written.
+    Io(std::io::Error),
+    /// An image file is not valid or uses a format that can't be read.
+    ImageDecode(String),
+    /// The system clipboard could not be read or written.
+    Clipboard(String),
+    /// A video could not be recorded.
+    Video(String),
+    /// Audio could not be captured or played.
+    Audio(String),
+    /// An OSC packet is not valid.
+    Osc(String),
+    /// A serial port could not be opened, configured or written to.
+    Serial(String),
+    /// An SVG file is not valid.
+    Svg(String),
+    /// A scene file is not valid.
+    Scene(String),
+    /// A CSV, JSON or TOML data file is not valid.
+    Data(String),
+    /// A palette file is not valid.
+    Palette(String),
+    /// A script does not parse or failed while running.
+    Script(String),
+    /// A font file could not be read or is not a supported TrueType font.
+    Font(FontError),
+}
+
+impl fmt::Display for SketchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SketchError::WindowCreation(reason) => write!(f, "could not open window: {reason}"),
+            SketchError::InvalidSize { width, height } => write!(f, "invalid canvas size: {width}x{height}"),
+            SketchError::WindowUpdate(reason) => write!(f, "could not update window: {reason}"),
+            SketchError::NoWindow => write!(f, "a headless runner has no window to run in, use run_frames"),
+            SketchError::Io(error) => write!(f, "{error}"),
+            SketchError::ImageDecode(reason) => write!(f, "could not decode image: {reason}"),
+            SketchError::Clipboard(reason) => write!(f, "clipboard error: {reason}"),
+            SketchError::Video(reason) => write!(f, "could not record video: {reason}"),
+            SketchError::Audio(reason) => write!(f, "audio error: {reason}"),
+            SketchError::Osc(reason) => write!(f, "invalid OSC packet: {reason}"),
+            SketchError::Serial(reason) => write!(f, "serial port error: {reason}"),
+            SketchError::Svg(reason) => write!(f, "could not read SVG: {reason}"),
+            SketchError::Scene(reason) => write!(f, "could not read scene: {reason}"),
+            SketchError::Data(reason) => write!(f, "could not read data: {reason}"),
+            SketchError::Palette(reason) => write!(f, "could not read palette: {reason}"),
+            SketchError::Script(reason) => write!(f, "script error: {reason}"),
+            SketchError::Font(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for SketchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SketchError::Io(error) => Some(error),
+            SketchError::Font(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SketchError {
+    fn from(error: std::io::Error) -> Self {
+        SketchError::Io(error)
+    }
+}
+
+impl From<FontError> for SketchError {
+    fn from(error: FontError) -> Self {
+        SketchError::Font(error)
+    }
+}
@@ -0,0 +1,247 @@
+//! Vector fields: a grid of directions over a rectangle, sampled smoothly in between, for
+//! flow-field art where particles or lines follow the field.
+//!
+//! # Examples
+//!
+//! ```
+//! # use simple_sketch::canvas::Canvas;
+//! # use simple_sketch::field::VectorField;
+//! # use simple_sketch::geom::{Point, Rect};
+//! # fn draw(canvas: &mut Canvas) {
+//! let bounds = Rect::new(Point::new(0.0, 0.0), Point::new(800.0, 600.0));
+//! let field = VectorField::from_noise(bounds, 40, 30, 0.005, 7);
+//! // Draw a streamline from every point of a coarse grid
+//! for start in bounds.grid(20, 15).map(|cell| cell.center()) {
+//!     for segment in field.trace(start, 2.0, 100).windows(2) {
+//!         canvas.line(segment[0], segment[1]);
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::f32::consts::TAU;
+
+use crate::color::Color;
+use crate::geom::{Point, Rect};
+use crate::image::Image;
+
+/// A grid of vectors spread evenly over a rectangle, from a vector on its top left to one on
+/// its bottom right corner. Between the grid points the field is interpolated bilinearly, and
+/// outside the rectangle it continues the vectors on its edges.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VectorField {
+    bounds: Rect,
+    columns: usize,
+    rows: usize,
+    /// The vectors row by row, from the top left.
+    vectors: Vec<Point>,
+}
+
+impl VectorField {
+    /// Creates a field of `columns` by `rows` zero vectors over `bounds`. There is at least
+    /// one column and row.
+    pub fn new(bounds: Rect, columns: usize, rows: usize) -> Self {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        VectorField { bounds, columns, rows, vectors: vec![Point::new(0.0, 0.0); columns * rows] }
+    }
+
+    /// Creates a field with the vector `vector` returns for each grid point.
+    pub fn from_fn(bounds: Rect, columns: usize, rows: usize, mut vector: impl FnMut(Point) -> Point) -> Self {
+        let mut field = VectorField::new(bounds, columns, rows);
+        for row in 0..field.rows {
+            for column in 0..field.columns {
+                let point = field.grid_point(column, row);
+                field.vectors[row * field.columns + column] = vector(point);
+            }
+        }
+        field
+    }
+
+    /// Creates a field of unit vectors pointing at the angle in radians `angle` returns for
+    /// each grid point, such as a noise value times a full turn.
+    pub fn from_angles(bounds: Rect, columns: usize, rows: usize, mut angle: impl FnMut(Point) -> f32) -> Self {
+        VectorField::from_fn(bounds, columns, rows, |point| {
+            let angle = angle(point);
+            Point::new(angle.cos(), angle.sin())
+        })
+    }
+
+    /// Creates a field of unit vectors turning smoothly with gradient noise, with `frequency`
+    /// noise features per pixel. Each `seed` gives a different, repeatable field.
+    pub fn from_noise(bounds: Rect, columns: usize, rows: usize, frequency: f32, seed: u32) -> Self {
+        // Noise rarely reaches -1 or 1, so spreading it over two full turns covers all directions
+        VectorField::from_angles(bounds, columns, rows, |point| noise(point.x * frequency, point.y * frequency, seed) * TAU)
+    }
+
+    /// Creates a field over the pixels of `image` whose vectors point from dark to light,
+    /// with the change in brightness per pixel as their length, found with a Sobel filter.
+    /// Rotating them a quarter turn with `map` gives vectors that flow along the edges of
+    /// the image instead.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simple_sketch::error::SketchError;
+    /// # use simple_sketch::field::VectorField;
+    /// # use simple_sketch::image::Image;
+    /// let photo = Image::load("portrait.jpg")?;
+    /// let mut field = VectorField::from_image_gradients(&photo, 80, 100);
+    /// field.map(|vector| vector.perpendicular());
+    /// # Ok::<(), SketchError>(())
+    /// ```
+    pub fn from_image_gradients(image: &Image, columns: usize, rows: usize) -> Self {
+        let bounds = Rect::new(Point::new(0.0, 0.0), Point::new(image.width as f32, image.height as f32));
+        if image.width == 0 || image.height == 0 {
+            return VectorField::new(bounds, columns, rows);
+        }
+        let brightness = |x: isize, y: isize| {
+            let (x, y) = (x.clamp(0, image.width as isize - 1) as usize, y.clamp(0, image.height as isize - 1) as usize);
+            let color = Color(image.pixels()[y * image.width + x]);
+            (color.r() as f32 * 0.2126 + color.g() as f32 * 0.7152 + color.b() as f32 * 0.0722) / 255.0 * color.a() as f32 / 255.0
+        };
+        VectorField::from_fn(bounds, columns, rows, |point| {
+            let (x, y) = (point.x.floor() as isize, point.y.floor() as isize);
+            let at = |dx: isize, dy: isize| brightness(x + dx, y + dy);
+            let gx = (at(1, -1) + 2.0 * at(1, 0) + at(1, 1)) - (at(-1, -1) + 2.0 * at(-1, 0) + at(-1, 1));
+            let gy = (at(-1, 1) + 2.0 * at(0, 1) + at(1, 1)) - (at(-1, -1) + 2.0 * at(0, -1) + at(1, -1));
+            Point::new(gx, gy) * (1.0 / 8.0)
+        })
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The vectors row by row, from the top left.
+    pub fn vectors(&self) -> &[Point] {
+        &self.vectors
+    }
+
+    /// The vector at a grid point, or None outside the grid.
+    pub fn get(&self, column: usize, row: usize) -> Option<Point> {
+        (column < self.columns && row < self.rows).then(|| self.vectors[row * self.columns + column])
+    }
+
+    /// Sets the vector at a grid point. Points outside the grid are ignored.
+    pub fn set(&mut self, column: usize, row: usize, vector: Point) {
+        if column < self.columns && row < self.rows {
+            self.vectors[row * self.columns + column] = vector;
+        }
+    }
+
+    /// Where a grid point lies in the bounds.
+    pub fn grid_point(&self, column: usize, row: usize) -> Point {
+        let fraction = |i: usize, count: usize| if count > 1 { i as f32 / (count - 1) as f32 } else { 0.5 };
+        self.bounds.lerp_point(fraction(column, self.columns), fraction(row, self.rows))
+    }
+
+    /// Replaces every vector with what `f` returns for it, for example to normalize or scale
+    /// the field.
+    pub fn map(&mut self, mut f: impl FnMut(Point) -> Point) {
+        self.vectors.iter_mut().for_each(|vector| *vector = f(*vector));
+    }
+
+    /// The field at `point`, interpolated bilinearly between the four grid points around it.
+    pub fn sample(&self, point: Point) -> Point {
+        // Grid coordinates, clamped to the edges
+        let grid = |value: f32, min: f32, size: f32, count: usize| {
+            if count < 2 || size <= 0.0 {
+                0.0
+            } else {
+                ((value - min) / size * (count - 1) as f32).clamp(0.0, (count - 1) as f32)
+            }
+        };
+        let gx = grid(point.x, self.bounds.min.x, self.bounds.width(), self.columns);
+        let gy = grid(point.y, self.bounds.min.y, self.bounds.height(), self.rows);
+        let (x0, y0) = (gx.floor() as usize, gy.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.columns - 1), (y0 + 1).min(self.rows - 1));
+        let (fx, fy) = (gx - x0 as f32, gy - y0 as f32);
+        let at = |x: usize, y: usize| self.vectors[y * self.columns + x];
+        let top = at(x0, y0).lerp(&at(x1, y0), fx);
+        let bottom = at(x0, y1).lerp(&at(x1, y1), fx);
+        top.lerp(&bottom, fy)
+    }
+
+    /// Moves every point along the field by `step` times the vector there, using the vector
+    /// halfway along the move, which follows curves more closely than the vector at the start.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::field::VectorField;
+    /// # use simple_sketch::geom::Point;
+    /// # struct Sketch { particles: Vec<Point> }
+    /// # impl Sketch {
+    /// # fn update(&mut self, field: &VectorField, dt: f32) {
+    /// // In update
+    /// field.advect(&mut self.particles, 60.0 * dt);
+    /// # }
+    /// # }
+    /// ```
+    pub fn advect(&self, points: &mut [Point], step: f32) {
+        for point in points {
+            *point = self.advance(*point, step);
+        }
+    }
+
+    /// The path of a point moved along the field `steps` times by `step`, like `advect`,
+    /// starting with `start`. The path stops early where the field vanishes.
+    pub fn trace(&self, start: Point, step: f32, steps: usize) -> Vec<Point> {
+        let mut path = Vec::with_capacity(steps + 1);
+        path.push(start);
+        let mut point = start;
+        for _ in 0..steps {
+            let next = self.advance(point, step);
+            if next == point {
+                break;
+            }
+            path.push(next);
+            point = next;
+        }
+        path
+    }
+
+    // Moves a point by `step` times the vector halfway along the move
+    fn advance(&self, point: Point, step: f32) -> Point {
+        let midpoint = point + self.sample(point) * (step / 2.0);
+        point + self.sample(midpoint) * step
+    }
+}
+
+// Smooth 2D gradient noise, roughly in the range [-1, 1]
+fn noise(x: f32, y: f32, seed: u32) -> f32 {
+    let (cell_x, cell_y) = (x.floor(), y.floor());
+    let (tx, ty) = (x - cell_x, y - cell_y);
+    let (i, j) = (cell_x as i32, cell_y as i32);
+    let corner = |di: i32, dj: i32| {
+        let angle = hash(i.wrapping_add(di), j.wrapping_add(dj), seed) * TAU;
+        angle.cos() * (tx - di as f32) + angle.sin() * (ty - dj as f32)
+    };
+    // Quintic fade curve for continuous first and second derivatives
+    let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let (fx, fy) = (fade(tx), fade(ty));
+    let top = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * fx;
+    let bottom = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * fx;
+    // The raw range is about [-0.7, 0.7]
+    (top + (bottom - top) * fy) * std::f32::consts::SQRT_2
+}
+
+// A pseudo-random number in [0, 1) for a lattice point
+fn hash(i: i32, j: i32, seed: u32) -> f32 {
+    let mut h = (i as u32).wrapping_mul(0x9E37_79B9) ^ (j as u32).wrapping_mul(0xC2B2_AE35) ^ seed.wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846C_A68B);
+    h ^= h >> 16;
+    (h >> 8) as f32 / (1 << 24) as f32
+}
@@ -0,0 +1,149 @@
+//! Flocking with Craig Reynolds' boids rules: agents steer apart when crowded (separation),
+//! along with their neighbors (alignment) and towards them (cohesion).
+//!
+//! Agents are the sketch's own structs, which implement `Agent`. Neighbors are found with a
+//! `SpatialHash`, so a flock costs about as much per agent as it has neighbors, instead of
+//! looking at every other agent.
+//!
+//! # Examples
+//!
+//! ```
+//! # use simple_sketch::flock::{Agent, Flock};
+//! # use simple_sketch::geom::Point;
+//! struct Bird {
+//!     position: Point,
+//!     velocity: Point,
+//! }
+//!
+//! impl Agent for Bird {
+//!     fn position(&self) -> Point { self.position }
+//!     fn velocity(&self) -> Point { self.velocity }
+//!     fn set_position(&mut self, position: Point) { self.position = position }
+//!     fn set_velocity(&mut self, velocity: Point) { self.velocity = velocity }
+//! }
+//!
+//! # struct Sketch { flock: Flock, birds: Vec<Bird> }
+//! # impl Sketch {
+//! # fn update(&mut self, dt: f32) {
+//! // In update
+//! self.flock.update(&mut self.birds, dt);
+//! # }
+//! # }
+//! ```
+
+use crate::geom::{Point, SpatialHash};
+
+/// Something that moves with a flock.
+pub trait Agent {
+    fn position(&self) -> Point;
+    fn velocity(&self) -> Point;
+    fn set_position(&mut self, position: Point);
+    fn set_velocity(&mut self, velocity: Point);
+}
+
+/// How a flock behaves. Distances are in pixels, speeds in pixels per second and forces in
+/// pixels per second squared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Flock {
+    /// How far agents see their neighbors for alignment and cohesion.
+    pub neighbor_radius: f32,
+    /// How close neighbors have to be for an agent to steer away from them.
+    pub separation_radius: f32,
+    pub separation: f32,
+    pub alignment: f32,
+    pub cohesion: f32,
+    pub max_speed: f32,
+    /// The most each rule can change an agent's velocity per second.
+    pub max_force: f32,
+}
+
+impl Default for Flock {
+    fn default() -> Self {
+        Flock {
+            neighbor_radius: 50.0,
+            separation_radius: 25.0,
+            separation: 1.5,
+            alignment: 1.0,
+            cohesion: 1.0,
+            max_speed: 120.0,
+            max_force: 180.0,
+        }
+    }
+}
+
+impl Flock {
+    pub fn new() -> Self {
+        Flock::default()
+    }
+
+    /// The acceleration the rules give every agent, in the order of the agents.
+    pub fn steering<A: Agent>(&self, agents: &[A]) -> Vec<Point> {
+        let radius = self.neighbor_radius.max(self.separation_radius).max(f32::EPSILON);
+        let mut grid = SpatialHash::new(radius);
+        for (index, agent) in agents.iter().enumerate() {
+            grid.insert(agent.position(), index);
+        }
+        agents
+            .iter()
+            .enumerate()
+            .map(|(index, agent)| {
+                let (position, velocity) = (agent.position(), agent.velocity());
+                let mut away = Point::new(0.0, 0.0);
+                let (mut heading, mut center, mut neighbors) = (Point::new(0.0, 0.0), Point::new(0.0, 0.0), 0);
+                for (other_position, &other) in grid.query_radius(position, radius) {
+                    if other == index {
+                        continue;
+                    }
+                    let offset = position - other_position;
+                    let distance = offset.length();
+                    if distance < self.separation_radius && distance > 0.0 {
+                        // Closer neighbors push harder
+                        away = away + offset * (1.0 / (distance * distance));
+                    }
+                    if distance <= self.neighbor_radius {
+                        heading = heading + agents[other].velocity();
+                        center = center + other_position;
+                        neighbors += 1;
+                    }
+                }
+                let mut steering = self.steer(away, velocity) * self.separation;
+                if neighbors > 0 {
+                    let count = neighbors as f32;
+                    steering = steering + self.steer(heading * (1.0 / count), velocity) * self.alignment;
+                    steering = steering + self.steer(center * (1.0 / count) - position, velocity) * self.cohesion;
+                }
+                steering
+            })
+            .collect()
+    }
+
+    /// Steers every agent by the rules and moves it `dt` seconds along its velocity, which
+    /// is kept below `max_speed`.
+    pub fn update<A: Agent>(&self, agents: &mut [A], dt: f32) {
+        let steering = self.steering(agents);
+        for (agent, acceleration) in agents.iter_mut().zip(steering) {
+            let velocity = limit(agent.velocity() + acceleration * dt, self.max_speed);
+            agent.set_velocity(velocity);
+            agent.set_position(agent.position() + velocity * dt);
+        }
+    }
+
+    // The change of velocity, limited to `max_force`, that turns `velocity` towards
+    // `direction` at full speed. Nothing for no direction.
+    fn steer(&self, direction: Point, velocity: Point) -> Point {
+        if direction.length() == 0.0 {
+            return Point::new(0.0, 0.0);
+        }
+        limit(direction.normalize() * self.max_speed - velocity, self.max_force)
+    }
+}
+
+fn limit(vector: Point, max: f32) -> Point {
+    let length = vector.length();
+    if length > max {
+        vector * (max / length)
+    } else {
+        vector
+    }
+}
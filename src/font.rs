@@ -0,0 +1,596 @@
+//! TrueType font loading and glyph outlines.
+//!
+//! A small parser for the tables needed to turn text into geometry: character mapping (`cmap`),
+//! horizontal metrics (`hhea`, `hmtx`) and quadratic glyph outlines (`glyf`, `loca`), including
+//! composite glyphs. Text becomes a `Path`, which can be filled, stroked, transformed or
+//! flattened into an `Outline` like any other shape. Fonts with PostScript (CFF) outlines are
+//! not supported. Text can also be shaped with the OpenType layout tables (`GSUB`, `GPOS`,
+//! `GDEF`) for scripts that need it; see `Font::shape`.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::geom::{Path, Point, Transform};
+use crate::shape::Outline;
+use crate::shaping;
+
+pub use crate::shaping::ShapedGlyph;
+
+/// Deepest nesting of composite glyphs that is followed, to stop malformed fonts from recursing forever.
+const MAX_COMPONENT_DEPTH: u32 = 8;
+
+/// An error loading a font.
+#[derive(Debug)]
+pub enum FontError {
+    /// The font file could not be read.
+    Io(std::io::Error),
+    /// The data is not a valid font, or a required table is missing or truncated.
+    Malformed(&'static str),
+    /// The font is valid but uses features this parser does not handle.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::Io(error) => write!(f, "could not read font: {error}"),
+            FontError::Malformed(reason) => write!(f, "malformed font: {reason}"),
+            FontError::Unsupported(reason) => write!(f, "unsupported font: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for FontError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FontError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for FontError {
+    fn from(error: std::io::Error) -> Self {
+        FontError::Io(error)
+    }
+}
+
+/// Where text is drawn horizontally relative to its position. See `Canvas::set_text_align`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HorizontalAlign {
+    /// Lines start at the position.
+    #[default]
+    Left,
+    /// Lines are centered on the position.
+    Center,
+    /// Lines end at the position.
+    Right,
+}
+
+/// Where text is drawn vertically relative to its position. See `Canvas::set_text_align`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerticalAlign {
+    /// The ascent of the first line touches the position.
+    Top,
+    /// The lines are centered on the position, from the ascent of the first line to the
+    /// descent of the last.
+    Center,
+    /// The baseline of the first line goes through the position.
+    #[default]
+    Baseline,
+    /// The descent of the last line touches the position.
+    Bottom,
+}
+
+/// A TrueType font, parsed enough to map characters to glyphs and glyphs to outlines.
+///
+/// Metrics are in font units, with the y-axis pointing up from the baseline. The text
+/// functions scale them to a font size in pixels and flip them onto the screen.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::error::SketchError;
+/// # use simple_sketch::font::Font;
+/// # use simple_sketch::geom::Point;
+/// # fn draw(canvas: &mut Canvas) -> Result<(), SketchError> {
+/// let font = Font::load("assets/DejaVuSans.ttf")?;
+/// let path = font.text_path("Hello", Point::new(20.0, 100.0), 48.0);
+/// canvas.draw_path(&path);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Font {
+    data: Vec<u8>,
+    units_per_em: u16,
+    ascender: i16,
+    descender: i16,
+    line_gap: i16,
+    glyph_count: u16,
+    long_loca: bool,
+    horizontal_metrics: u16,
+    cmap: Range<usize>,
+    hmtx: Range<usize>,
+    loca: Range<usize>,
+    glyf: Range<usize>,
+    /// The OpenType layout tables used by `shape`, empty when the font has none.
+    gdef: Range<usize>,
+    gsub: Range<usize>,
+    gpos: Range<usize>,
+}
+
+impl Font {
+    /// Reads and parses a font file.
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Font, FontError> {
+        Font::from_bytes(std::fs::read(path)?)
+    }
+
+    /// Parses a font from the contents of a `.ttf` file.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Font, FontError> {
+        match read_u32(&data, 0) {
+            Some(0x0001_0000) | Some(0x7472_7565) => {}
+            Some(0x4F54_544F) => return Err(FontError::Unsupported("PostScript (CFF) outlines")),
+            Some(0x7474_6366) => return Err(FontError::Unsupported("font collections")),
+            _ => return Err(FontError::Malformed("not a TrueType font")),
+        }
+
+        let table = |tag: &[u8; 4]| -> Result<Range<usize>, FontError> {
+            let count = read_u16(&data, 4).ok_or(FontError::Malformed("truncated header"))? as usize;
+            (0..count)
+                .map(|i| 12 + i * 16)
+                .find(|&record| data.get(record..record + 4) == Some(&tag[..]))
+                .and_then(|record| {
+                    let offset = read_u32(&data, record + 8)? as usize;
+                    let length = read_u32(&data, record + 12)? as usize;
+                    let end = offset.checked_add(length)?;
+                    (end <= data.len()).then_some(offset..end)
+                })
+                .ok_or(FontError::Malformed("missing or truncated table"))
+        };
+
+        let head = table(b"head")?;
+        let maxp = table(b"maxp")?;
+        let hhea = table(b"hhea")?;
+        let header = |range: &Range<usize>, offset: usize| {
+            read_u16(&data[range.clone()], offset).ok_or(FontError::Malformed("truncated table"))
+        };
+
+        let units_per_em = header(&head, 18)?;
+        if units_per_em == 0 {
+            return Err(FontError::Malformed("zero units per em"));
+        }
+        let long_loca = header(&head, 50)? != 0;
+        let glyph_count = header(&maxp, 4)?;
+        let ascender = header(&hhea, 4)? as i16;
+        let descender = header(&hhea, 6)? as i16;
+        let line_gap = header(&hhea, 8)? as i16;
+        let horizontal_metrics = header(&hhea, 34)?;
+
+        Ok(Font {
+            cmap: table(b"cmap")?,
+            hmtx: table(b"hmtx")?,
+            loca: table(b"loca")?,
+            glyf: table(b"glyf")?,
+            gdef: table(b"GDEF").unwrap_or_default(),
+            gsub: table(b"GSUB").unwrap_or_default(),
+            gpos: table(b"GPOS").unwrap_or_default(),
+            data,
+            units_per_em,
+            ascender,
+            descender,
+            line_gap,
+            glyph_count,
+            long_loca,
+            horizontal_metrics,
+        })
+    }
+
+    /// The number of font units per em, the unit that font sizes are measured in.
+    pub fn units_per_em(&self) -> u16 {
+        self.units_per_em
+    }
+
+    /// The distance from the baseline to the top of the tallest glyphs, in font units.
+    pub fn ascender(&self) -> i16 {
+        self.ascender
+    }
+
+    /// The distance from the baseline to the bottom of the lowest glyphs, in font units. Usually negative.
+    pub fn descender(&self) -> i16 {
+        self.descender
+    }
+
+    /// The extra space recommended between lines, in font units.
+    pub fn line_gap(&self) -> i16 {
+        self.line_gap
+    }
+
+    /// The number of glyphs in the font.
+    pub fn glyph_count(&self) -> u16 {
+        self.glyph_count
+    }
+
+    /// Finds the glyph for a character. Returns None if the font does not cover it.
+    pub fn glyph_index(&self, c: char) -> Option<u16> {
+        let cmap = &self.data[self.cmap.clone()];
+        let count = read_u16(cmap, 2)? as usize;
+        let subtable = |platform: u16, encoding: u16| {
+            (0..count).map(|i| 4 + i * 8).find_map(|record| {
+                let matches = read_u16(cmap, record)? == platform && read_u16(cmap, record + 2)? == encoding;
+                matches.then(|| read_u32(cmap, record + 4)).flatten()
+            })
+        };
+
+        // Prefer full Unicode tables over ones limited to the basic multilingual plane
+        let candidates = [(3, 10), (0, 6), (0, 4), (3, 1), (0, 3), (0, 2), (0, 1), (0, 0)];
+        let glyph = candidates
+            .iter()
+            .filter_map(|&(platform, encoding)| subtable(platform, encoding))
+            .find_map(|offset| cmap_lookup(cmap.get(offset as usize..)?, c as u32))?;
+        (glyph != 0 && glyph < self.glyph_count).then_some(glyph)
+    }
+
+    /// The horizontal distance to move the pen after drawing a glyph, in font units.
+    pub fn advance_width(&self, glyph: u16) -> u16 {
+        let hmtx = &self.data[self.hmtx.clone()];
+        // Glyphs past the last full metric share its advance
+        let index = glyph.min(self.horizontal_metrics.saturating_sub(1)) as usize;
+        read_u16(hmtx, index * 4).unwrap_or(0)
+    }
+
+    /// Returns the outline of a glyph in font units, with the y-axis pointing up.
+    /// Glyphs without an outline, such as the space, give an empty path.
+    pub fn glyph_outline(&self, glyph: u16) -> Path {
+        let mut path = Path::new();
+        self.append_glyph(&mut path, glyph, &Transform::identity(), 0);
+        path
+    }
+
+    /// Lays out a line of text and returns the outlines of its glyphs.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to lay out. Newlines start a new line below.
+    /// * `position` - The start of the first line's baseline, in pixels.
+    /// * `size` - The font size in pixels, the height of an em.
+    ///
+    /// # Returns
+    ///
+    /// A path with one closed subpath per glyph contour, in screen coordinates. Characters
+    /// the font does not cover are drawn as its missing glyph.
+    pub fn text_path(&self, text: &str, position: Point, size: f32) -> Path {
+        let scale = size / self.units_per_em as f32;
+        let line_height = self.line_height(size);
+        let mut path = Path::new();
+        let mut pen = position;
+        for c in text.chars() {
+            if c == '\n' {
+                pen = Point::new(position.x, pen.y + line_height);
+                continue;
+            }
+            let glyph = self.glyph_index(c).unwrap_or(0);
+            // Font units point up, screen coordinates down
+            let transform = Transform::translate(pen) * Transform::scale(scale, -scale);
+            self.append_glyph(&mut path, glyph, &transform, 0);
+            pen.x += self.advance_width(glyph) as f32 * scale;
+        }
+        path
+    }
+
+    /// The width of a line of text at a font size in pixels, the sum of its glyph advances.
+    /// For text with newlines, the width of its widest line.
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        let scale = size / self.units_per_em as f32;
+        text.split('\n')
+            .map(|line| line.chars().map(|c| self.advance_width(self.glyph_index(c).unwrap_or(0)) as f32).sum::<f32>() * scale)
+            .fold(0.0, f32::max)
+    }
+
+    /// The distance between the baselines of consecutive lines at a font size in pixels.
+    pub fn line_height(&self, size: f32) -> f32 {
+        (self.ascender as f32 - self.descender as f32 + self.line_gap as f32) * size / self.units_per_em as f32
+    }
+
+    /// Lays out text like `text_path` and flattens it into an `Outline` shape, which can be
+    /// hit-tested, combined and measured like other shapes.
+    ///
+    /// # Arguments
+    ///
+    /// * `tolerance` - The maximum distance in pixels between the curves and the outline's polylines.
+    pub fn text_outline(&self, text: &str, position: Point, size: f32, tolerance: f32) -> Outline {
+        Outline { polylines: self.text_path(text, position, size).flatten(tolerance) }
+    }
+
+    /// Shapes a line of text with the font's OpenType layout tables: picks the joining forms of
+    /// Arabic letters, forms ligatures, places combining marks on their base glyphs, applies
+    /// kerning and lays out right-to-left runs right to left. See the `shaping` module for what
+    /// is covered. Fonts without layout tables get glyph-by-glyph placement with marks
+    /// overlaid on their base.
+    ///
+    /// # Returns
+    ///
+    /// The glyphs in drawing order from left to right, placed in font units from the start of
+    /// the line's baseline, with the y-axis pointing up. Newlines are ignored.
+    pub fn shape(&self, text: &str) -> Vec<ShapedGlyph> {
+        shaping::shape(self, text)
+    }
+
+    /// Lays out text like `text_path`, with every line shaped by `shape`, for scripts that
+    /// need ligatures, joining forms or mark placement.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::font::Font;
+    /// # use simple_sketch::geom::Point;
+    /// # fn draw(canvas: &mut Canvas, font: &Font) {
+    /// let path = font.shaped_text_path("مرحبا بالعالم", Point::new(20.0, 100.0), 48.0);
+    /// canvas.draw_path(&path);
+    /// # }
+    /// ```
+    pub fn shaped_text_path(&self, text: &str, position: Point, size: f32) -> Path {
+        let scale = size / self.units_per_em as f32;
+        let line_height = self.line_height(size);
+        let mut path = Path::new();
+        for (index, line) in text.split('\n').enumerate() {
+            let baseline = Point::new(position.x, position.y + index as f32 * line_height);
+            for glyph in self.shape(line) {
+                let origin = baseline + Point::new(glyph.x, -glyph.y) * scale;
+                let transform = Transform::translate(origin) * Transform::scale(scale, -scale);
+                self.append_glyph(&mut path, glyph.glyph, &transform, 0);
+            }
+        }
+        path
+    }
+
+    /// The width of a line of text shaped by `shape`, at a font size in pixels. For text with
+    /// newlines, the width of its widest line.
+    pub fn shaped_text_width(&self, text: &str, size: f32) -> f32 {
+        let scale = size / self.units_per_em as f32;
+        text.split('\n')
+            .map(|line| self.shape(line).iter().map(|glyph| glyph.advance).sum::<f32>() * scale)
+            .fold(0.0, f32::max)
+    }
+
+    // The contents of the GDEF, GSUB and GPOS tables, empty for missing tables.
+    pub(crate) fn layout_tables(&self) -> (&[u8], &[u8], &[u8]) {
+        (&self.data[self.gdef.clone()], &self.data[self.gsub.clone()], &self.data[self.gpos.clone()])
+    }
+
+    // The glyph's data in the glyf table, or None for glyphs without an outline.
+    fn glyph_data(&self, glyph: u16) -> Option<&[u8]> {
+        let loca = &self.data[self.loca.clone()];
+        let index = glyph as usize;
+        let (start, end) = if self.long_loca {
+            (read_u32(loca, index * 4)? as usize, read_u32(loca, index * 4 + 4)? as usize)
+        } else {
+            (read_u16(loca, index * 2)? as usize * 2, read_u16(loca, index * 2 + 2)? as usize * 2)
+        };
+        let glyf = &self.data[self.glyf.clone()];
+        (start < end).then(|| glyf.get(start..end)).flatten()
+    }
+
+    // Appends the contours of a glyph mapped through `transform`. Malformed glyphs add nothing.
+    fn append_glyph(&self, path: &mut Path, glyph: u16, transform: &Transform, depth: u32) {
+        let Some(data) = self.glyph_data(glyph) else {
+            return;
+        };
+        let Some(contours) = read_u16(data, 0).map(|count| count as i16) else {
+            return;
+        };
+        let mut glyph_path = Path::new();
+        let parsed = if contours >= 0 {
+            simple_glyph(data, contours as usize, &mut glyph_path)
+        } else if depth < MAX_COMPONENT_DEPTH {
+            self.composite_glyph(data, &mut glyph_path, depth)
+        } else {
+            None
+        };
+        if parsed.is_some() {
+            path.commands.extend(glyph_path.transform(transform).commands);
+        }
+    }
+
+    fn composite_glyph(&self, data: &[u8], path: &mut Path, depth: u32) -> Option<()> {
+        const ARGS_ARE_WORDS: u16 = 0x0001;
+        const ARGS_ARE_OFFSETS: u16 = 0x0002;
+        const HAS_SCALE: u16 = 0x0008;
+        const MORE_COMPONENTS: u16 = 0x0020;
+        const HAS_XY_SCALE: u16 = 0x0040;
+        const HAS_TWO_BY_TWO: u16 = 0x0080;
+
+        let mut offset = 10;
+        loop {
+            let flags = read_u16(data, offset)?;
+            let glyph = read_u16(data, offset + 2)?;
+            offset += 4;
+            let (dx, dy) = if flags & ARGS_ARE_WORDS != 0 {
+                offset += 4;
+                (read_u16(data, offset - 4)? as i16 as f32, read_u16(data, offset - 2)? as i16 as f32)
+            } else {
+                offset += 2;
+                (*data.get(offset - 2)? as i8 as f32, *data.get(offset - 1)? as i8 as f32)
+            };
+            let f2dot14 = |offset: usize| read_u16(data, offset).map(|value| value as i16 as f32 / 16384.0);
+            let (m11, m12, m21, m22) = if flags & HAS_SCALE != 0 {
+                offset += 2;
+                let scale = f2dot14(offset - 2)?;
+                (scale, 0.0, 0.0, scale)
+            } else if flags & HAS_XY_SCALE != 0 {
+                offset += 4;
+                (f2dot14(offset - 4)?, 0.0, 0.0, f2dot14(offset - 2)?)
+            } else if flags & HAS_TWO_BY_TWO != 0 {
+                offset += 8;
+                (f2dot14(offset - 8)?, f2dot14(offset - 4)?, f2dot14(offset - 6)?, f2dot14(offset - 2)?)
+            } else {
+                (1.0, 0.0, 0.0, 1.0)
+            };
+            // Components positioned by matching points are rare, and placed without an offset here
+            let (tx, ty) = if flags & ARGS_ARE_OFFSETS != 0 { (dx, dy) } else { (0.0, 0.0) };
+            let transform = Transform { m11, m12, m21, m22, tx, ty };
+            self.append_glyph(path, glyph, &transform, depth + 1);
+
+            if flags & MORE_COMPONENTS == 0 {
+                return Some(());
+            }
+        }
+    }
+}
+
+// Looks up a character in a cmap subtable of format 4 or 12.
+fn cmap_lookup(subtable: &[u8], c: u32) -> Option<u16> {
+    match read_u16(subtable, 0)? {
+        4 => {
+            let c = u16::try_from(c).ok()?;
+            let segments = read_u16(subtable, 6)? as usize / 2;
+            let end_codes = 14;
+            let start_codes = end_codes + segments * 2 + 2;
+            let deltas = start_codes + segments * 2;
+            let range_offsets = deltas + segments * 2;
+            for i in 0..segments {
+                if c > read_u16(subtable, end_codes + i * 2)? {
+                    continue;
+                }
+                let start = read_u16(subtable, start_codes + i * 2)?;
+                if c < start {
+                    return None;
+                }
+                let delta = read_u16(subtable, deltas + i * 2)?;
+                let range_offset = read_u16(subtable, range_offsets + i * 2)? as usize;
+                if range_offset == 0 {
+                    return Some(c.wrapping_add(delta));
+                }
+                // The offset is relative to its own position in the table
+                let index = range_offsets + i * 2 + range_offset + (c - start) as usize * 2;
+                let glyph = read_u16(subtable, index)?;
+                return (glyph != 0).then(|| glyph.wrapping_add(delta));
+            }
+            None
+        }
+        12 => {
+            let groups = read_u32(subtable, 12)? as usize;
+            (0..groups).map(|i| 16 + i * 12).find_map(|group| {
+                let start = read_u32(subtable, group)?;
+                let end = read_u32(subtable, group + 4)?;
+                if (start..=end).contains(&c) {
+                    u16::try_from(read_u32(subtable, group + 8)? + (c - start)).ok()
+                } else {
+                    None
+                }
+            })
+        }
+        _ => None,
+    }
+}
+
+// Decodes a simple glyph's contours into `path`, as closed quadratic subpaths.
+fn simple_glyph(data: &[u8], contours: usize, path: &mut Path) -> Option<()> {
+    const ON_CURVE: u8 = 0x01;
+    const X_SHORT: u8 = 0x02;
+    const Y_SHORT: u8 = 0x04;
+    const REPEAT: u8 = 0x08;
+    const X_SAME_OR_POSITIVE: u8 = 0x10;
+    const Y_SAME_OR_POSITIVE: u8 = 0x20;
+
+    let end_points: Vec<usize> = (0..contours).map(|i| read_u16(data, 10 + i * 2).map(usize::from)).collect::<Option<_>>()?;
+    let point_count = end_points.last().map_or(0, |last| last + 1);
+    let instructions = read_u16(data, 10 + contours * 2)? as usize;
+    let mut offset = 12 + contours * 2 + instructions;
+
+    let mut flags = Vec::with_capacity(point_count);
+    while flags.len() < point_count {
+        let flag = *data.get(offset)?;
+        offset += 1;
+        let repeats = if flag & REPEAT != 0 {
+            offset += 1;
+            *data.get(offset - 1)? as usize
+        } else {
+            0
+        };
+        flags.extend(std::iter::repeat_n(flag, (repeats + 1).min(point_count - flags.len())));
+    }
+
+    // Coordinates are deltas, stored as all x values followed by all y values
+    let mut read_coordinates = |short: u8, same_or_positive: u8| -> Option<Vec<f32>> {
+        let mut value = 0i32;
+        let mut values = Vec::with_capacity(point_count);
+        for &flag in &flags {
+            if flag & short != 0 {
+                let delta = *data.get(offset)? as i32;
+                offset += 1;
+                value += if flag & same_or_positive != 0 { delta } else { -delta };
+            } else if flag & same_or_positive == 0 {
+                value += read_u16(data, offset)? as i16 as i32;
+                offset += 2;
+            }
+            values.push(value as f32);
+        }
+        Some(values)
+    };
+    let xs = read_coordinates(X_SHORT, X_SAME_OR_POSITIVE)?;
+    let ys = read_coordinates(Y_SHORT, Y_SAME_OR_POSITIVE)?;
+
+    let mut start = 0;
+    for &end in &end_points {
+        if end < start || end >= point_count {
+            return None;
+        }
+        let points: Vec<(Point, bool)> = (start..=end).map(|i| (Point::new(xs[i], ys[i]), flags[i] & ON_CURVE != 0)).collect();
+        append_contour(path, &points);
+        start = end + 1;
+    }
+    Some(())
+}
+
+// Appends one contour of on- and off-curve points. Two off-curve points in a row imply an
+// on-curve point halfway between them.
+fn append_contour(path: &mut Path, points: &[(Point, bool)]) {
+    let n = points.len();
+    if n == 0 {
+        return;
+    }
+    // Start at an on-curve point, or between the first two off-curve points if there is none
+    let first_on = points.iter().position(|&(_, on_curve)| on_curve);
+    let (start, first) = match first_on {
+        Some(i) => (points[i].0, i),
+        None => (points[0].0.lerp(&points[1 % n].0, 0.5), 0),
+    };
+    path.move_to(start);
+
+    let mut control: Option<Point> = None;
+    for step in 1..=n {
+        let (point, on_curve) = points[(first + step) % n];
+        match (on_curve, control) {
+            (true, Some(c)) => {
+                path.quad_to(c, point);
+                control = None;
+            }
+            (true, None) => {
+                path.line_to(point);
+            }
+            (false, Some(c)) => {
+                let middle = c.lerp(&point, 0.5);
+                path.quad_to(c, middle);
+                control = Some(point);
+            }
+            (false, None) => control = Some(point),
+        }
+    }
+    if let Some(c) = control {
+        path.quad_to(c, start);
+    }
+    path.close();
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
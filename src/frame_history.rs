@@ -0,0 +1,175 @@
+//! A ring buffer of the last drawn frames, for going back to a glitch that flashed by.
+//!
+//! With `ApplicationRunner::set_frame_history`, the runner keeps a copy of every frame it
+//! draws, up to a number of seconds back. While the sketch is paused with Space, Left arrow
+//! steps back through them and Right arrow forward, Shift with either jumps a second, and the
+//! screenshot key saves the frame being shown. Resuming the sketch returns to the live frame:
+//!
+//! ```no_run
+//! # use simple_sketch::app::{Application, ApplicationRunner, Context};
+//! # use simple_sketch::canvas::Canvas;
+//! # use simple_sketch::error::SketchError;
+//! # struct Sketch;
+//! # impl Application for Sketch {
+//! #     fn draw(&mut self, _: &mut Canvas, _: &Context) {}
+//! # }
+//! # fn main() -> Result<(), SketchError> {
+//! # let sketch = Sketch;
+//! let mut runner = ApplicationRunner::new(sketch)?;
+//! runner.set_frame_history(Some(5.0));
+//! runner.run()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Frames take four bytes per pixel, so five seconds of an 800 by 600 sketch at 60 frames per
+//! second hold about 550 MB.
+
+use std::collections::VecDeque;
+use std::path::Path;
+
+use crate::error::SketchError;
+use crate::image::Image;
+use crate::pixelbuffer::PixelBuffer;
+
+/// A frame kept by a `FrameHistory`.
+pub struct HistoricFrame {
+    pixels: PixelBuffer,
+    frame_count: u64,
+    time: f32,
+}
+
+impl HistoricFrame {
+    /// The frame count of the frame, as `Context::frame_count` returned while it was drawn.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// The sketch's time in seconds when the frame was drawn, as `Context::seconds_since_start`
+    /// returned.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn pixels(&self) -> &PixelBuffer {
+        &self.pixels
+    }
+
+    /// A copy of the frame's pixels as an image.
+    pub fn to_image(&self) -> Image {
+        Image::from(&self.pixels)
+    }
+
+    /// Saves the frame as an opaque PNG file, the way it looked in the window, creating
+    /// missing directories.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), SketchError> {
+        crate::app::save_png(&self.pixels, path.as_ref()).map_err(SketchError::from)
+    }
+}
+
+/// The last drawn frames, newest first, and the one being looked at.
+pub struct FrameHistory {
+    /// Oldest first.
+    frames: VecDeque<HistoricFrame>,
+    capacity: usize,
+    /// How many frames back from the newest the shown frame is, 0 for the live frame.
+    position: usize,
+}
+
+impl FrameHistory {
+    /// An empty history keeping up to `capacity` frames, at least one.
+    pub fn new(capacity: usize) -> Self {
+        FrameHistory { frames: VecDeque::new(), capacity: capacity.max(1), position: 0 }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Sets how many frames are kept, dropping the oldest ones beyond it.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.frames.len() > self.capacity {
+            self.frames.pop_front();
+        }
+        self.position = self.position.min(self.frames.len().saturating_sub(1));
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The frame drawn `frames_ago` frames before the newest one, so the newest with 0, or
+    /// None if it isn't kept.
+    pub fn get(&self, frames_ago: usize) -> Option<&HistoricFrame> {
+        let index = self.frames.len().checked_sub(frames_ago + 1)?;
+        self.frames.get(index)
+    }
+
+    /// The frames from the oldest to the newest.
+    pub fn iter(&self) -> impl Iterator<Item = &HistoricFrame> {
+        self.frames.iter()
+    }
+
+    /// The frame stepped back to, shown in the window instead of the live frame, or None while
+    /// the live frame is shown.
+    pub fn shown(&self) -> Option<&HistoricFrame> {
+        self.get(self.position).filter(|_| self.position > 0)
+    }
+
+    /// Whether an older frame than the live one is shown.
+    pub fn is_scrubbing(&self) -> bool {
+        self.position > 0
+    }
+
+    /// Moves the shown frame by `frames`, back for negative counts and forward for positive
+    /// ones, stopping at the oldest frame kept and at the live frame.
+    pub fn step(&mut self, frames: isize) {
+        let oldest = self.frames.len().saturating_sub(1);
+        self.position = self.position.saturating_add_signed(-frames).min(oldest);
+    }
+
+    /// Moves the shown frame by `seconds` of the sketch's time, back for negative values and
+    /// forward for positive ones, to the kept frame drawn closest to that time.
+    pub fn jump(&mut self, seconds: f32) {
+        let Some(current) = self.get(self.position) else {
+            return;
+        };
+        let target = current.time + seconds;
+        let distance = |frame: &HistoricFrame| (frame.time - target).abs();
+        let closest = self.frames.iter().enumerate().min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)));
+        if let Some((index, _)) = closest {
+            self.position = self.frames.len() - 1 - index;
+        }
+    }
+
+    /// Shows the live frame again.
+    pub fn go_live(&mut self) {
+        self.position = 0;
+    }
+
+    /// Drops all frames.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.position = 0;
+    }
+
+    // Adds a copy of a drawn frame as the newest one, reusing the buffer of the oldest when
+    // the history is full, and shows the live frame.
+    pub(crate) fn push(&mut self, pixels: &PixelBuffer, frame_count: u64, time: f32) {
+        let reused = (self.frames.len() >= self.capacity).then(|| self.frames.pop_front()).flatten();
+        let pixels = match reused {
+            Some(mut frame) if (frame.pixels.width, frame.pixels.height) == (pixels.width, pixels.height) => {
+                frame.pixels.get_buffer_mut().copy_from_slice(pixels.get_buffer());
+                frame.pixels
+            }
+            _ => PixelBuffer::from_pixels(pixels.width, pixels.height, pixels.get_buffer().to_vec()),
+        };
+        self.frames.push_back(HistoricFrame { pixels, frame_count, time });
+        self.position = 0;
+    }
+}
@@ -0,0 +1,265 @@
+//! A gallery of sketches behind a menu, for shipping many small sketches as one program.
+//!
+//! Every sketch is registered under a name with a function that creates it. The gallery opens
+//! a window listing the names, and launching one closes the menu and runs the sketch in its
+//! own window, with its own `Settings`, until that window is closed or Escape is pressed. The
+//! menu then opens again, with the sketch that just ran selected:
+//!
+//! ```no_run
+//! # use simple_sketch::app::{Application, Context};
+//! # use simple_sketch::canvas::Canvas;
+//! # use simple_sketch::error::SketchError;
+//! # use simple_sketch::gallery::Gallery;
+//! # #[derive(Default)]
+//! # struct Bubbles;
+//! # struct FlowField;
+//! # impl FlowField { fn new(_: usize) -> Self { FlowField } }
+//! # struct Tree { depth: u32 }
+//! # impl Application for Bubbles { fn draw(&mut self, _: &mut Canvas, _: &Context) {} }
+//! # impl Application for FlowField { fn draw(&mut self, _: &mut Canvas, _: &Context) {} }
+//! # impl Application for Tree { fn draw(&mut self, _: &mut Canvas, _: &Context) {} }
+//! fn main() -> Result<(), SketchError> {
+//!     Gallery::new("Workshop")
+//!         .add_default::<Bubbles>("bouncy bubbles")
+//!         .add("flow field", || FlowField::new(4000))
+//!         .add("tree", || Tree { depth: 9 })
+//!         .run()
+//! }
+//! ```
+//!
+//! In the menu, Up and Down or the mouse wheel choose a sketch, Enter or Space launches it, and
+//! so does clicking its name. Escape or closing the menu's window quits the gallery.
+
+use std::time::Instant;
+
+use crate::app::{Application, ApplicationRunner, Context, Settings};
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::error::SketchError;
+use crate::geom::Point;
+use crate::input::{Key, KeyEvent, MouseEvent, WheelEvent};
+
+/// Width in pixels of the menu window.
+const MENU_WIDTH: usize = 480;
+
+/// Height in pixels of a row of the menu.
+const ROW_HEIGHT: f32 = 20.0;
+
+/// Height in pixels of the title above the rows.
+const HEADER_HEIGHT: f32 = 40.0;
+
+/// Height in pixels of the help and error lines below the rows.
+const FOOTER_HEIGHT: f32 = 44.0;
+
+/// Rows shown at once. Longer menus scroll to keep the selected row in view.
+const VISIBLE_ROWS: usize = 24;
+
+/// Distance in pixels between the window's left edge and the text.
+const MARGIN: f32 = 16.0;
+
+const BACKGROUND: Color = Color(0xFF18181C);
+const SELECTED: Color = Color(0xFF3C78B4);
+const TEXT: Color = Color(0xFFFFFFFF);
+const DIMMED: Color = Color(0xFF9090A0);
+const ERROR: Color = Color(0xFFFF6060);
+
+/// Sketches listed in a menu that launches them one at a time.
+pub struct Gallery {
+    title: String,
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    name: String,
+    /// Creates the sketch and runs it in a window until it stops.
+    launch: Box<dyn Fn() -> Result<(), SketchError>>,
+}
+
+impl Gallery {
+    /// An empty gallery whose menu window is titled `title`.
+    pub fn new(title: &str) -> Self {
+        Gallery { title: title.to_string(), entries: Vec::new() }
+    }
+
+    /// Adds a sketch to the end of the menu, listed as `name` and created by `create` every
+    /// time it is launched, so that every launch starts it over.
+    pub fn add<A: Application + 'static>(mut self, name: &str, create: impl Fn() -> A + 'static) -> Self {
+        let launch = move || ApplicationRunner::new(create())?.run();
+        self.entries.push(Entry { name: name.to_string(), launch: Box::new(launch) });
+        self
+    }
+
+    /// Adds a sketch created with `Default`, like `add`.
+    pub fn add_default<A: Application + Default + 'static>(self, name: &str) -> Self {
+        self.add(name, A::default)
+    }
+
+    /// The names of the sketches, in the order of the menu.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Runs the sketch at `index` in the menu in its own window until the window is closed or
+    /// Escape is pressed, without showing the menu.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are `index` sketches or fewer.
+    pub fn launch(&self, index: usize) -> Result<(), SketchError> {
+        (self.entries[index].launch)()
+    }
+
+    /// Shows the menu and runs the sketches launched from it until the menu is closed or
+    /// Escape is pressed in it. A sketch that fails, for example because its window can't be
+    /// opened, returns to the menu, which shows the error until the next launch. Fails if the
+    /// menu's window can't be opened.
+    pub fn run(&self) -> Result<(), SketchError> {
+        let mut menu = Menu {
+            title: self.title.clone(),
+            names: self.names().map(str::to_string).collect(),
+            selected: 0,
+            first_visible: 0,
+            chosen: None,
+            error: None,
+        };
+        loop {
+            let mut runner = ApplicationRunner::new(menu)?;
+            runner.set_playback_keys(false);
+            runner.set_undo_keys(false);
+            runner.set_screenshot_key(None);
+            runner.set_record_key(None);
+            let start = Instant::now();
+            let mut result = Ok(true);
+            while let Ok(true) = result {
+                result = runner.show_frame(start.elapsed());
+                if runner.app().chosen.is_some() {
+                    break;
+                }
+            }
+            menu = runner.finish();
+            result?;
+            let Some(index) = menu.chosen.take() else {
+                return Ok(());
+            };
+            menu.error = self.launch(index).err().map(|error| format!("{}: {error}", self.entries[index].name));
+        }
+    }
+}
+
+// The menu's sketch, listing the names of the gallery's sketches.
+struct Menu {
+    title: String,
+    names: Vec<String>,
+    selected: usize,
+    /// The index of the first row in view.
+    first_visible: usize,
+    /// The index of the sketch to launch once the menu is closed.
+    chosen: Option<usize>,
+    /// The error of the last sketch launched, if it failed.
+    error: Option<String>,
+}
+
+impl Menu {
+    // The index of the sketch whose row is at `position`, if any
+    fn row_at(&self, position: Point) -> Option<usize> {
+        let row = (position.y - HEADER_HEIGHT) / ROW_HEIGHT;
+        let index = self.first_visible + row.floor() as usize;
+        (row >= 0.0 && row.floor() < VISIBLE_ROWS as f32 && index < self.names.len()).then_some(index)
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.names.len().saturating_sub(1));
+        if self.selected < self.first_visible {
+            self.first_visible = self.selected;
+        } else if self.selected >= self.first_visible + VISIBLE_ROWS {
+            self.first_visible = self.selected + 1 - VISIBLE_ROWS;
+        }
+    }
+
+    fn choose(&mut self, index: usize) {
+        if index < self.names.len() {
+            self.selected = index;
+            self.chosen = Some(index);
+        }
+    }
+}
+
+impl Application for Menu {
+    fn settings(&self) -> Settings {
+        let rows = self.names.len().clamp(1, VISIBLE_ROWS) as f32;
+        let height = HEADER_HEIGHT + rows * ROW_HEIGHT + FOOTER_HEIGHT;
+        Settings::new().size(MENU_WIDTH, height as usize).title(&self.title)
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, _ctx: &Context) {
+        canvas.background(BACKGROUND);
+        canvas.set_stroke(None);
+        canvas.set_fill(Some(TEXT));
+        canvas.debug_text(&self.title, Point::new(MARGIN, 16.0));
+
+        let visible = self.names.iter().enumerate().skip(self.first_visible).take(VISIBLE_ROWS);
+        for (row, (index, name)) in visible.enumerate() {
+            let top = HEADER_HEIGHT + row as f32 * ROW_HEIGHT;
+            if index == self.selected {
+                canvas.set_fill(Some(SELECTED));
+                canvas.rectangle(Point::new(0.0, top), canvas.width as f32, ROW_HEIGHT);
+            }
+            canvas.set_fill(Some(if index == self.selected { TEXT } else { DIMMED }));
+            canvas.debug_text(&format!("{:>3}  {name}", index + 1), Point::new(MARGIN, top + (ROW_HEIGHT - 8.0) / 2.0));
+        }
+        if self.names.is_empty() {
+            canvas.set_fill(Some(DIMMED));
+            canvas.debug_text("no sketches", Point::new(MARGIN, HEADER_HEIGHT + 6.0));
+        }
+
+        let footer = canvas.height as f32 - FOOTER_HEIGHT;
+        canvas.set_fill(Some(DIMMED));
+        canvas.debug_text("up/down: choose  enter: launch  esc: quit", Point::new(MARGIN, footer + 10.0));
+        if let Some(error) = &self.error {
+            canvas.set_fill(Some(ERROR));
+            canvas.debug_text(error, Point::new(MARGIN, footer + 26.0));
+        }
+    }
+
+    fn key_pressed(&mut self, event: &KeyEvent) {
+        match event.key {
+            Key::Up => self.select(self.selected.saturating_sub(1)),
+            Key::Down => self.select(self.selected + 1),
+            Key::PageUp => self.select(self.selected.saturating_sub(VISIBLE_ROWS)),
+            Key::PageDown => self.select(self.selected + VISIBLE_ROWS),
+            Key::Home => self.select(0),
+            Key::End => self.select(usize::MAX),
+            Key::Enter | Key::NumPadEnter | Key::Space => self.choose(self.selected),
+            _ => {}
+        }
+    }
+
+    fn mouse_moved(&mut self, event: &MouseEvent) {
+        if let Some(index) = self.row_at(event.position) {
+            self.selected = index;
+        }
+    }
+
+    fn mouse_clicked(&mut self, event: &MouseEvent) {
+        if let Some(index) = self.row_at(event.position) {
+            self.choose(index);
+        }
+    }
+
+    fn mouse_wheel(&mut self, event: &WheelEvent) {
+        // Notches scroll by different amounts on different platforms, move by one row each frame
+        if event.delta.y > 0.0 {
+            self.select(self.selected.saturating_sub(1));
+        } else if event.delta.y < 0.0 {
+            self.select(self.selected + 1);
+        }
+    }
+}
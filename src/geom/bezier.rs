@@ -0,0 +1,491 @@
+//! This module provides quadratic and cubic Bézier curves and associated operations, building upon the `Point` and `Rect` structs.
+
+use crate::geom::{Point, Rect};
+
+/// Number of segments used to build the arc-length lookup table.
+const ARC_LENGTH_SAMPLES: usize = 64;
+
+/// Maximum recursion depth for adaptive flattening.
+const MAX_FLATTEN_DEPTH: u32 = 16;
+
+/// Number of evenly spaced parameter values tried before refining the closest point.
+const CLOSEST_POINT_SAMPLES: usize = 16;
+
+/// Number of Newton iterations used to refine the closest point.
+const CLOSEST_POINT_ITERATIONS: usize = 4;
+
+/// Represents a quadratic Bézier curve defined by a start point, one control point and an end point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuadBezier {
+    pub start: Point,
+    pub control: Point,
+    pub end: Point,
+}
+
+/// Represents a cubic Bézier curve defined by a start point, two control points and an end point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CubicBezier {
+    pub start: Point,
+    pub control1: Point,
+    pub control2: Point,
+    pub end: Point,
+}
+
+impl QuadBezier {
+    /// Creates a new `QuadBezier` from its start, control and end points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, QuadBezier};
+    /// let curve = QuadBezier::new(Point::new(0.0, 0.0), Point::new(1.0, 2.0), Point::new(2.0, 0.0));
+    /// ```
+    pub fn new(start: Point, control: Point, end: Point) -> Self {
+        QuadBezier { start, control, end }
+    }
+
+    /// Evaluates the curve at parameter `t` in the range `[0, 1]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, QuadBezier};
+    /// let curve = QuadBezier::new(Point::new(0.0, 0.0), Point::new(1.0, 2.0), Point::new(2.0, 0.0));
+    /// assert_eq!(curve.point_at(0.5), Point::new(1.0, 1.0));
+    /// ```
+    pub fn point_at(&self, t: f32) -> Point {
+        let mt = 1.0 - t;
+        self.start * (mt * mt) + self.control * (2.0 * mt * t) + self.end * (t * t)
+    }
+
+    /// Calculates the (unnormalized) derivative of the curve at parameter `t`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, QuadBezier};
+    /// let curve = QuadBezier::new(Point::new(0.0, 0.0), Point::new(1.0, 2.0), Point::new(2.0, 0.0));
+    /// assert_eq!(curve.tangent_at(0.5), Point::new(2.0, 0.0));
+    /// ```
+    pub fn tangent_at(&self, t: f32) -> Point {
+        let mt = 1.0 - t;
+        (self.control - self.start) * (2.0 * mt) + (self.end - self.control) * (2.0 * t)
+    }
+
+    /// Splits the curve at parameter `t` into two curves covering `[0, t]` and `[t, 1]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, QuadBezier};
+    /// let curve = QuadBezier::new(Point::new(0.0, 0.0), Point::new(1.0, 2.0), Point::new(2.0, 0.0));
+    /// let (left, right) = curve.split(0.5);
+    /// assert_eq!(left.end, right.start);
+    /// ```
+    pub fn split(&self, t: f32) -> (QuadBezier, QuadBezier) {
+        let a = self.start.lerp(&self.control, t);
+        let b = self.control.lerp(&self.end, t);
+        let mid = a.lerp(&b, t);
+        (
+            QuadBezier::new(self.start, a, mid),
+            QuadBezier::new(mid, b, self.end),
+        )
+    }
+
+    /// Calculates the tight bounding box of the curve, taking its extrema into account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, QuadBezier, Rect};
+    /// let curve = QuadBezier::new(Point::new(0.0, 0.0), Point::new(1.0, 2.0), Point::new(2.0, 0.0));
+    /// assert_eq!(curve.bounding_box(), Rect::new(Point::new(0.0, 0.0), Point::new(2.0, 1.0)));
+    /// ```
+    pub fn bounding_box(&self) -> Rect {
+        let mut rect = Rect::from_points(self.start, self.end);
+        let extrema = [
+            quad_extremum(self.start.x, self.control.x, self.end.x),
+            quad_extremum(self.start.y, self.control.y, self.end.y),
+        ];
+        for t in extrema.into_iter().flatten() {
+            let p = self.point_at(t);
+            rect = rect.union(&Rect::new(p, p));
+        }
+        rect
+    }
+
+    /// Converts the curve into a cubic Bézier describing the same shape.
+    pub fn to_cubic(&self) -> CubicBezier {
+        CubicBezier::new(
+            self.start,
+            self.start + (self.control - self.start) * (2.0 / 3.0),
+            self.end + (self.control - self.end) * (2.0 / 3.0),
+            self.end,
+        )
+    }
+
+    /// Flattens the curve into a polyline whose maximum deviation from the curve is about `tolerance`.
+    /// The returned points include both end points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, QuadBezier};
+    /// let curve = QuadBezier::new(Point::new(0.0, 0.0), Point::new(1.0, 2.0), Point::new(2.0, 0.0));
+    /// let points = curve.flatten(0.1);
+    /// assert_eq!(points.first(), Some(&Point::new(0.0, 0.0)));
+    /// assert_eq!(points.last(), Some(&Point::new(2.0, 0.0)));
+    /// ```
+    pub fn flatten(&self, tolerance: f32) -> Vec<Point> {
+        self.to_cubic().flatten(tolerance)
+    }
+
+    /// Approximates the arc length of the curve.
+    pub fn length(&self) -> f32 {
+        self.to_cubic().length()
+    }
+
+    /// Finds the parameter `t` at which the arc length measured from the start equals `distance`.
+    pub fn t_at_length(&self, distance: f32) -> f32 {
+        self.to_cubic().t_at_length(distance)
+    }
+
+    /// Evaluates the curve at the point lying `distance` along its arc length.
+    pub fn point_at_length(&self, distance: f32) -> Point {
+        self.point_at(self.t_at_length(distance))
+    }
+
+    /// Finds the parameter `t` of the point on the curve closest to `point`.
+    pub fn closest_t(&self, point: &Point) -> f32 {
+        self.to_cubic().closest_t(point)
+    }
+}
+
+impl CubicBezier {
+    /// Creates a new `CubicBezier` from its start, control and end points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CubicBezier, Point};
+    /// let curve = CubicBezier::new(
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(0.0, 1.0),
+    ///     Point::new(1.0, 1.0),
+    ///     Point::new(1.0, 0.0),
+    /// );
+    /// ```
+    pub fn new(start: Point, control1: Point, control2: Point, end: Point) -> Self {
+        CubicBezier { start, control1, control2, end }
+    }
+
+    /// Evaluates the curve at parameter `t` in the range `[0, 1]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CubicBezier, Point};
+    /// let curve = CubicBezier::new(
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(0.0, 1.0),
+    ///     Point::new(1.0, 1.0),
+    ///     Point::new(1.0, 0.0),
+    /// );
+    /// assert_eq!(curve.point_at(0.5), Point::new(0.5, 0.75));
+    /// ```
+    pub fn point_at(&self, t: f32) -> Point {
+        let mt = 1.0 - t;
+        self.start * (mt * mt * mt)
+            + self.control1 * (3.0 * mt * mt * t)
+            + self.control2 * (3.0 * mt * t * t)
+            + self.end * (t * t * t)
+    }
+
+    /// Calculates the (unnormalized) derivative of the curve at parameter `t`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CubicBezier, Point};
+    /// let curve = CubicBezier::new(
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(0.0, 1.0),
+    ///     Point::new(1.0, 1.0),
+    ///     Point::new(1.0, 0.0),
+    /// );
+    /// assert_eq!(curve.tangent_at(0.0), Point::new(0.0, 3.0));
+    /// ```
+    pub fn tangent_at(&self, t: f32) -> Point {
+        let mt = 1.0 - t;
+        (self.control1 - self.start) * (3.0 * mt * mt)
+            + (self.control2 - self.control1) * (6.0 * mt * t)
+            + (self.end - self.control2) * (3.0 * t * t)
+    }
+
+    /// Splits the curve at parameter `t` into two curves covering `[0, t]` and `[t, 1]`
+    /// using de Casteljau's algorithm.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CubicBezier, Point};
+    /// let curve = CubicBezier::new(
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(0.0, 1.0),
+    ///     Point::new(1.0, 1.0),
+    ///     Point::new(1.0, 0.0),
+    /// );
+    /// let (left, right) = curve.split(0.5);
+    /// assert_eq!(left.end, Point::new(0.5, 0.75));
+    /// assert_eq!(right.start, left.end);
+    /// ```
+    pub fn split(&self, t: f32) -> (CubicBezier, CubicBezier) {
+        let ab = self.start.lerp(&self.control1, t);
+        let bc = self.control1.lerp(&self.control2, t);
+        let cd = self.control2.lerp(&self.end, t);
+        let abc = ab.lerp(&bc, t);
+        let bcd = bc.lerp(&cd, t);
+        let mid = abc.lerp(&bcd, t);
+        (
+            CubicBezier::new(self.start, ab, abc, mid),
+            CubicBezier::new(mid, bcd, cd, self.end),
+        )
+    }
+
+    /// Calculates the tight bounding box of the curve, taking its extrema into account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CubicBezier, Point, Rect};
+    /// let curve = CubicBezier::new(
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(0.0, 1.0),
+    ///     Point::new(1.0, 1.0),
+    ///     Point::new(1.0, 0.0),
+    /// );
+    /// assert_eq!(curve.bounding_box(), Rect::new(Point::new(0.0, 0.0), Point::new(1.0, 0.75)));
+    /// ```
+    pub fn bounding_box(&self) -> Rect {
+        let mut rect = Rect::from_points(self.start, self.end);
+        let roots_x = cubic_extrema(self.start.x, self.control1.x, self.control2.x, self.end.x);
+        let roots_y = cubic_extrema(self.start.y, self.control1.y, self.control2.y, self.end.y);
+        for t in roots_x.into_iter().chain(roots_y).flatten() {
+            let p = self.point_at(t);
+            rect = rect.union(&Rect::new(p, p));
+        }
+        rect
+    }
+
+    /// Flattens the curve into a polyline whose maximum deviation from the curve is about `tolerance`.
+    /// Flat sections produce few points while tight bends are subdivided further.
+    /// The returned points include both end points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CubicBezier, Point};
+    /// let curve = CubicBezier::new(
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(0.0, 1.0),
+    ///     Point::new(1.0, 1.0),
+    ///     Point::new(1.0, 0.0),
+    /// );
+    /// let points = curve.flatten(0.01);
+    /// assert_eq!(points.first(), Some(&Point::new(0.0, 0.0)));
+    /// assert_eq!(points.last(), Some(&Point::new(1.0, 0.0)));
+    /// ```
+    pub fn flatten(&self, tolerance: f32) -> Vec<Point> {
+        let mut points = vec![self.start];
+        self.flatten_into(tolerance.max(f32::EPSILON), MAX_FLATTEN_DEPTH, &mut points);
+        points
+    }
+
+    fn flatten_into(&self, tolerance: f32, depth: u32, points: &mut Vec<Point>) {
+        if depth == 0 || self.is_flat(tolerance) {
+            points.push(self.end);
+        } else {
+            let (left, right) = self.split(0.5);
+            left.flatten_into(tolerance, depth - 1, points);
+            right.flatten_into(tolerance, depth - 1, points);
+        }
+    }
+
+    // The curve is flat enough when both control points lie within `tolerance` of the chord.
+    fn is_flat(&self, tolerance: f32) -> bool {
+        let chord = self.end - self.start;
+        let chord_length = chord.length();
+        if chord_length == 0.0 {
+            return self.start.distance(&self.control1) <= tolerance
+                && self.start.distance(&self.control2) <= tolerance;
+        }
+        let d1 = cross(chord, self.control1 - self.start).abs() / chord_length;
+        let d2 = cross(chord, self.control2 - self.start).abs() / chord_length;
+        d1.max(d2) <= tolerance
+    }
+
+    /// Approximates the arc length of the curve.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CubicBezier, Point};
+    /// let curve = CubicBezier::new(
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(1.0, 0.0),
+    ///     Point::new(2.0, 0.0),
+    ///     Point::new(3.0, 0.0),
+    /// );
+    /// assert!((curve.length() - 3.0).abs() < 1e-4);
+    /// ```
+    pub fn length(&self) -> f32 {
+        *self.arc_length_table().last().unwrap()
+    }
+
+    /// Finds the parameter `t` at which the arc length measured from the start equals `distance`.
+    /// Distances outside the curve's length are clamped to its ends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CubicBezier, Point};
+    /// let curve = CubicBezier::new(
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(1.0, 0.0),
+    ///     Point::new(2.0, 0.0),
+    ///     Point::new(3.0, 0.0),
+    /// );
+    /// assert!((curve.t_at_length(1.5) - 0.5).abs() < 1e-4);
+    /// ```
+    pub fn t_at_length(&self, distance: f32) -> f32 {
+        let table = self.arc_length_table();
+        let total = table[ARC_LENGTH_SAMPLES];
+        if distance <= 0.0 || total == 0.0 {
+            return 0.0;
+        }
+        if distance >= total {
+            return 1.0;
+        }
+
+        // Find the table segment containing `distance` and interpolate within it
+        let index = table.partition_point(|&length| length < distance).max(1);
+        let before = table[index - 1];
+        let segment = table[index] - before;
+        let fraction = if segment > 0.0 { (distance - before) / segment } else { 0.0 };
+        (index as f32 - 1.0 + fraction) / ARC_LENGTH_SAMPLES as f32
+    }
+
+    /// Evaluates the curve at the point lying `distance` along its arc length.
+    /// Stepping `distance` uniformly yields evenly spaced points along the curve.
+    pub fn point_at_length(&self, distance: f32) -> Point {
+        self.point_at(self.t_at_length(distance))
+    }
+
+    /// Finds the parameter `t` of the point on the curve closest to `point`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CubicBezier, Point};
+    /// let curve = CubicBezier::new(
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(0.0, 1.0),
+    ///     Point::new(1.0, 1.0),
+    ///     Point::new(1.0, 0.0),
+    /// );
+    /// assert!((curve.closest_t(&Point::new(0.5, 2.0)) - 0.5).abs() < 1e-4);
+    /// ```
+    pub fn closest_t(&self, point: &Point) -> f32 {
+        let distance_squared = |t: f32| {
+            let offset = self.point_at(t) - *point;
+            offset.dot(&offset)
+        };
+
+        // Start from the closest of a few samples so Newton's method converges to the global minimum
+        let mut best = (0..=CLOSEST_POINT_SAMPLES)
+            .map(|i| i as f32 / CLOSEST_POINT_SAMPLES as f32)
+            .min_by(|a, b| distance_squared(*a).total_cmp(&distance_squared(*b)))
+            .unwrap_or(0.0);
+
+        let mut t = best;
+        for _ in 0..CLOSEST_POINT_ITERATIONS {
+            // Find a root of (B(t) - point) . B'(t), the derivative of half the squared distance
+            let offset = self.point_at(t) - *point;
+            let tangent = self.tangent_at(t);
+            let numerator = offset.dot(&tangent);
+            let denominator = tangent.dot(&tangent) + offset.dot(&self.second_derivative_at(t));
+            if denominator == 0.0 {
+                break;
+            }
+            t = (t - numerator / denominator).clamp(0.0, 1.0);
+            if distance_squared(t) < distance_squared(best) {
+                best = t;
+            }
+        }
+        best
+    }
+
+    fn second_derivative_at(&self, t: f32) -> Point {
+        let mt = 1.0 - t;
+        (self.control2 - self.control1 * 2.0 + self.start) * (6.0 * mt)
+            + (self.end - self.control2 * 2.0 + self.control1) * (6.0 * t)
+    }
+
+    // Cumulative arc lengths at evenly spaced parameter values.
+    fn arc_length_table(&self) -> [f32; ARC_LENGTH_SAMPLES + 1] {
+        let mut table = [0.0; ARC_LENGTH_SAMPLES + 1];
+        let mut previous = self.start;
+        for (i, entry) in table.iter_mut().enumerate().skip(1) {
+            let point = self.point_at(i as f32 / ARC_LENGTH_SAMPLES as f32);
+            *entry = previous.distance(&point);
+            previous = point;
+        }
+        for i in 1..table.len() {
+            table[i] += table[i - 1];
+        }
+        table
+    }
+}
+
+// Two-dimensional cross product (z component of the 3D cross product).
+fn cross(a: Point, b: Point) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+// Parameter of the extremum of a 1D quadratic Bézier, if it lies strictly inside (0, 1).
+fn quad_extremum(p0: f32, p1: f32, p2: f32) -> Option<f32> {
+    let denom = p0 - 2.0 * p1 + p2;
+    if denom == 0.0 {
+        return None;
+    }
+    let t = (p0 - p1) / denom;
+    if t > 0.0 && t < 1.0 { Some(t) } else { None }
+}
+
+// Parameters of the extrema of a 1D cubic Bézier that lie strictly inside (0, 1).
+fn cubic_extrema(p0: f32, p1: f32, p2: f32, p3: f32) -> [Option<f32>; 2] {
+    // The derivative is a quadratic a*t^2 + b*t + c
+    let a = 3.0 * (-p0 + 3.0 * p1 - 3.0 * p2 + p3);
+    let b = 6.0 * (p0 - 2.0 * p1 + p2);
+    let c = 3.0 * (p1 - p0);
+    let in_range = |t: f32| if t > 0.0 && t < 1.0 { Some(t) } else { None };
+
+    if a.abs() < 1e-6 {
+        if b == 0.0 {
+            return [None, None];
+        }
+        return [in_range(-c / b), None];
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return [None, None];
+    }
+    let sqrt_d = discriminant.sqrt();
+    [
+        in_range((-b + sqrt_d) / (2.0 * a)),
+        in_range((-b - sqrt_d) / (2.0 * a)),
+    ]
+}
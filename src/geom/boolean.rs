@@ -0,0 +1,325 @@
+//! This module provides boolean operations between polygons using the Greiner–Hormann clipping algorithm.
+//!
+//! Results are returned as lists of polygons. When an operation produces holes, the hole
+//! boundaries are part of the list, so the list describes the resulting area under the
+//! even-odd fill rule.
+
+use crate::geom::{Point, Polygon};
+
+/// Maximum number of perturbation attempts for degenerate inputs.
+const MAX_PERTURBATIONS: u32 = 8;
+
+/// Relative tolerance for detecting intersections at (or near) vertices.
+const DEGENERACY_EPSILON: f32 = 1e-5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    point: Point,
+    next: usize,
+    prev: usize,
+    intersection: bool,
+    entry: bool,
+    neighbor: usize,
+    visited: bool,
+}
+
+struct Crossing {
+    edge_a: usize,
+    edge_b: usize,
+    alpha_a: f32,
+    alpha_b: f32,
+    point: Point,
+}
+
+impl Polygon {
+    /// Calculates the union of this polygon and another.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let a = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(2.0, 0.0), Point::new(2.0, 2.0), Point::new(0.0, 2.0)]);
+    /// let b = Polygon::new(vec![Point::new(1.0, 1.0), Point::new(3.0, 1.0), Point::new(3.0, 3.0), Point::new(1.0, 3.0)]);
+    /// let union = a.union(&b);
+    /// assert_eq!(union.len(), 1);
+    /// assert!((union[0].area() - 7.0).abs() < 1e-3);
+    /// ```
+    pub fn union(&self, other: &Polygon) -> Vec<Polygon> {
+        clip(self, other, Operation::Union)
+    }
+
+    /// Calculates the intersection of this polygon and another.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let a = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(2.0, 0.0), Point::new(2.0, 2.0), Point::new(0.0, 2.0)]);
+    /// let b = Polygon::new(vec![Point::new(1.0, 1.0), Point::new(3.0, 1.0), Point::new(3.0, 3.0), Point::new(1.0, 3.0)]);
+    /// let intersection = a.intersection(&b);
+    /// assert_eq!(intersection.len(), 1);
+    /// assert!((intersection[0].area() - 1.0).abs() < 1e-3);
+    /// ```
+    pub fn intersection(&self, other: &Polygon) -> Vec<Polygon> {
+        clip(self, other, Operation::Intersection)
+    }
+
+    /// Calculates the area of this polygon that is not covered by another.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let a = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(2.0, 0.0), Point::new(2.0, 2.0), Point::new(0.0, 2.0)]);
+    /// let b = Polygon::new(vec![Point::new(1.0, 1.0), Point::new(3.0, 1.0), Point::new(3.0, 3.0), Point::new(1.0, 3.0)]);
+    /// let difference = a.difference(&b);
+    /// assert_eq!(difference.len(), 1);
+    /// assert!((difference[0].area() - 3.0).abs() < 1e-3);
+    /// ```
+    pub fn difference(&self, other: &Polygon) -> Vec<Polygon> {
+        clip(self, other, Operation::Difference)
+    }
+
+    /// Calculates the area covered by exactly one of the two polygons.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let a = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(2.0, 0.0), Point::new(2.0, 2.0), Point::new(0.0, 2.0)]);
+    /// let b = Polygon::new(vec![Point::new(1.0, 1.0), Point::new(3.0, 1.0), Point::new(3.0, 3.0), Point::new(1.0, 3.0)]);
+    /// let xor = a.xor(&b);
+    /// assert_eq!(xor.len(), 2);
+    /// ```
+    pub fn xor(&self, other: &Polygon) -> Vec<Polygon> {
+        let mut result = self.difference(other);
+        result.extend(other.difference(self));
+        result
+    }
+}
+
+fn clip(a: &Polygon, b: &Polygon, operation: Operation) -> Vec<Polygon> {
+    if a.vertices.len() < 3 || b.vertices.len() < 3 {
+        return trivial(a, b, operation, false, false);
+    }
+
+    let scale = match (a.bounding_box(), b.bounding_box()) {
+        (Some(ra), Some(rb)) => {
+            let bounds = ra.union(&rb);
+            bounds.width().max(bounds.height()).max(1.0)
+        }
+        _ => 1.0,
+    };
+
+    // Intersections exactly at vertices or along collinear edges break the entry/exit
+    // classification, so nudge the second polygon by a tiny amount and try again.
+    let mut b = b.clone();
+    for attempt in 0..MAX_PERTURBATIONS {
+        if let Some(crossings) = find_crossings(a, &b) {
+            return if crossings.is_empty() {
+                let a_in_b = b.contains_point(&a.vertices[0]);
+                let b_in_a = a.contains_point(&b.vertices[0]);
+                trivial(a, &b, operation, a_in_b, b_in_a)
+            } else {
+                trace(a, &b, &crossings, operation)
+            };
+        }
+        let amount = scale * DEGENERACY_EPSILON * 10.0 * (attempt + 1) as f32;
+        b = perturb(&b, amount, attempt);
+    }
+    Vec::new()
+}
+
+// Handles operations between polygons whose boundaries do not cross.
+fn trivial(a: &Polygon, b: &Polygon, operation: Operation, a_in_b: bool, b_in_a: bool) -> Vec<Polygon> {
+    let valid = |p: &Polygon| p.vertices.len() >= 3;
+    let keep = |polygons: Vec<&Polygon>| polygons.into_iter().filter(|p| valid(p)).cloned().collect();
+    match operation {
+        Operation::Intersection if a_in_b => keep(vec![a]),
+        Operation::Intersection if b_in_a => keep(vec![b]),
+        Operation::Intersection => Vec::new(),
+        Operation::Union if a_in_b => keep(vec![b]),
+        Operation::Union if b_in_a => keep(vec![a]),
+        Operation::Union => keep(vec![a, b]),
+        Operation::Difference if a_in_b => Vec::new(),
+        // The second polygon becomes a hole
+        Operation::Difference if b_in_a => keep(vec![a, b]),
+        Operation::Difference => keep(vec![a]),
+    }
+}
+
+// Finds all proper crossings between edges of `a` and `b`.
+// Returns None if any intersection is degenerate.
+fn find_crossings(a: &Polygon, b: &Polygon) -> Option<Vec<Crossing>> {
+    let mut crossings = Vec::new();
+    for (edge_a, ea) in a.edges().enumerate() {
+        for (edge_b, eb) in b.edges().enumerate() {
+            let r = ea.end - ea.start;
+            let s = eb.end - eb.start;
+            let denom = r.x * s.y - r.y * s.x;
+            let qp = eb.start - ea.start;
+
+            let scale = r.length() * s.length();
+            if denom.abs() <= DEGENERACY_EPSILON * scale {
+                // Parallel edges only matter if they are collinear and overlap
+                let collinear = (qp.x * r.y - qp.y * r.x).abs() <= DEGENERACY_EPSILON * r.length() * qp.length().max(1.0);
+                if collinear && overlaps(ea.start, ea.end, eb.start, eb.end) {
+                    return None;
+                }
+                continue;
+            }
+
+            let alpha_a = (qp.x * s.y - qp.y * s.x) / denom;
+            let alpha_b = (qp.x * r.y - qp.y * r.x) / denom;
+            let outside = |t: f32| !(-DEGENERACY_EPSILON..=1.0 + DEGENERACY_EPSILON).contains(&t);
+            if outside(alpha_a) || outside(alpha_b) {
+                continue;
+            }
+            let near_end = |t: f32| t <= DEGENERACY_EPSILON || t >= 1.0 - DEGENERACY_EPSILON;
+            if near_end(alpha_a) || near_end(alpha_b) {
+                return None;
+            }
+            crossings.push(Crossing {
+                edge_a,
+                edge_b,
+                alpha_a,
+                alpha_b,
+                point: ea.start + r * alpha_a,
+            });
+        }
+    }
+    Some(crossings)
+}
+
+// Checks whether two collinear segments share more than a single point.
+fn overlaps(a0: Point, a1: Point, b0: Point, b1: Point) -> bool {
+    let axis = a1 - a0;
+    let project = |p: Point| (p - a0).dot(&axis);
+    let (a_min, a_max) = (0.0, axis.dot(&axis));
+    let (b_min, b_max) = {
+        let (p, q) = (project(b0), project(b1));
+        (p.min(q), p.max(q))
+    };
+    b_min < a_max && b_max > a_min
+}
+
+fn perturb(polygon: &Polygon, amount: f32, seed: u32) -> Polygon {
+    // Deterministic, direction-varying offsets using the golden angle. The start angle
+    // avoids the axes so offsets do not slide vertices along axis-aligned edges.
+    let vertices = polygon.vertices.iter().enumerate().map(|(i, &vertex)| {
+        let angle = 0.7 + (i as u32 + seed) as f32 * 2.399_963;
+        vertex + Point::new(angle.cos(), angle.sin()) * amount
+    });
+    Polygon::new(vertices.collect())
+}
+
+// Builds a circular vertex list with the crossings inserted in edge order.
+// Returns the list and, for every crossing, the index of its node.
+fn build_list(vertices: &[Point], crossings: &[Crossing], edge_of: impl Fn(&Crossing) -> (usize, f32)) -> (Vec<Node>, Vec<usize>) {
+    let mut nodes = Vec::with_capacity(vertices.len() + crossings.len());
+    let mut crossing_nodes = vec![0; crossings.len()];
+
+    let mut by_edge: Vec<Vec<(f32, usize)>> = vec![Vec::new(); vertices.len()];
+    for (i, crossing) in crossings.iter().enumerate() {
+        let (edge, alpha) = edge_of(crossing);
+        by_edge[edge].push((alpha, i));
+    }
+
+    let node = |point: Point, intersection: bool| Node {
+        point,
+        next: 0,
+        prev: 0,
+        intersection,
+        entry: false,
+        neighbor: 0,
+        visited: false,
+    };
+
+    for (edge, vertex) in vertices.iter().enumerate() {
+        nodes.push(node(*vertex, false));
+        by_edge[edge].sort_by(|a, b| a.0.total_cmp(&b.0));
+        for &(_, crossing) in &by_edge[edge] {
+            crossing_nodes[crossing] = nodes.len();
+            nodes.push(node(crossings[crossing].point, true));
+        }
+    }
+
+    let n = nodes.len();
+    for (i, node) in nodes.iter_mut().enumerate() {
+        node.next = (i + 1) % n;
+        node.prev = (i + n - 1) % n;
+    }
+    (nodes, crossing_nodes)
+}
+
+// Marks every crossing node as entering or leaving the other polygon, walking from the first vertex.
+fn mark_entries(nodes: &mut [Node], starts_inside: bool, invert: bool) {
+    let mut entry = !starts_inside ^ invert;
+    for node in nodes.iter_mut().filter(|node| node.intersection) {
+        node.entry = entry;
+        entry = !entry;
+    }
+}
+
+fn trace(a: &Polygon, b: &Polygon, crossings: &[Crossing], operation: Operation) -> Vec<Polygon> {
+    let (mut list_a, nodes_a) = build_list(&a.vertices, crossings, |c| (c.edge_a, c.alpha_a));
+    let (mut list_b, nodes_b) = build_list(&b.vertices, crossings, |c| (c.edge_b, c.alpha_b));
+    for (&na, &nb) in nodes_a.iter().zip(&nodes_b) {
+        list_a[na].neighbor = nb;
+        list_b[nb].neighbor = na;
+    }
+
+    let (invert_a, invert_b) = match operation {
+        Operation::Intersection => (false, false),
+        Operation::Union => (true, true),
+        Operation::Difference => (true, false),
+    };
+    mark_entries(&mut list_a, b.contains_point(&a.vertices[0]), invert_a);
+    mark_entries(&mut list_b, a.contains_point(&b.vertices[0]), invert_b);
+
+    let mut lists = [list_a, list_b];
+    let mut result = Vec::new();
+
+    while let Some(start) = lists[0].iter().position(|node| node.intersection && !node.visited) {
+        let mut side = 0;
+        let mut current = start;
+        let mut vertices = vec![lists[0][start].point];
+
+        loop {
+            let neighbor = lists[side][current].neighbor;
+            lists[side][current].visited = true;
+            lists[1 - side][neighbor].visited = true;
+
+            let forward = lists[side][current].entry;
+            loop {
+                let node = &lists[side][current];
+                current = if forward { node.next } else { node.prev };
+                vertices.push(lists[side][current].point);
+                if lists[side][current].intersection {
+                    break;
+                }
+            }
+
+            current = lists[side][current].neighbor;
+            side = 1 - side;
+            if lists[side][current].visited {
+                break;
+            }
+        }
+
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        if vertices.len() >= 3 {
+            result.push(Polygon::new(vertices));
+        }
+    }
+    result
+}
@@ -0,0 +1,112 @@
+//! This module provides a Catmull-Rom (cardinal) spline that interpolates through a sequence of points.
+
+use crate::geom::{Point, CubicBezier};
+
+/// Represents a Catmull-Rom spline passing through all of its control points.
+///
+/// The `tension` controls how tightly the curve bends at each point: 0 gives the classic
+/// Catmull-Rom curve, 1 gives straight line segments between the points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatmullRom {
+    pub points: Vec<Point>,
+    pub tension: f32,
+}
+
+impl CatmullRom {
+    /// Creates a new `CatmullRom` spline through the given points with the given tension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CatmullRom, Point};
+    /// let spline = CatmullRom::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0), Point::new(2.0, 0.0)], 0.0);
+    /// ```
+    pub fn new(points: Vec<Point>, tension: f32) -> Self {
+        CatmullRom { points, tension }
+    }
+
+    /// Returns the number of curve segments, one between each pair of consecutive points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CatmullRom, Point};
+    /// let spline = CatmullRom::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0), Point::new(2.0, 0.0)], 0.0);
+    /// assert_eq!(spline.segment_count(), 2);
+    /// ```
+    pub fn segment_count(&self) -> usize {
+        self.points.len().saturating_sub(1)
+    }
+
+    /// Evaluates the spline at parameter `t` in the range `[0, 1]`, spread evenly over its segments.
+    /// Returns None if the spline has no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CatmullRom, Point};
+    /// let spline = CatmullRom::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0), Point::new(2.0, 0.0)], 0.0);
+    /// assert_eq!(spline.point_at(0.5), Some(Point::new(1.0, 1.0)));
+    /// ```
+    pub fn point_at(&self, t: f32) -> Option<Point> {
+        let segments = self.segment_count();
+        if segments == 0 {
+            return self.points.first().copied();
+        }
+
+        let scaled = t.clamp(0.0, 1.0) * segments as f32;
+        let index = (scaled.floor() as usize).min(segments - 1);
+        Some(self.segment(index).point_at(scaled - index as f32))
+    }
+
+    /// Converts the spline into one cubic Bézier per segment.
+    /// The first and last points are repeated to provide tangents at the ends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CatmullRom, Point};
+    /// let spline = CatmullRom::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0), Point::new(2.0, 0.0)], 0.0);
+    /// let curves = spline.to_beziers();
+    /// assert_eq!(curves.len(), 2);
+    /// assert_eq!(curves[0].end, curves[1].start);
+    /// ```
+    pub fn to_beziers(&self) -> Vec<CubicBezier> {
+        (0..self.segment_count()).map(|i| self.segment(i)).collect()
+    }
+
+    /// Flattens the spline into a polyline whose maximum deviation from the curve is about `tolerance`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{CatmullRom, Point};
+    /// let spline = CatmullRom::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0), Point::new(2.0, 0.0)], 0.0);
+    /// let points = spline.to_polyline(0.1);
+    /// assert_eq!(points.first(), Some(&Point::new(0.0, 0.0)));
+    /// assert_eq!(points.last(), Some(&Point::new(2.0, 0.0)));
+    /// ```
+    pub fn to_polyline(&self, tolerance: f32) -> Vec<Point> {
+        let mut polyline: Vec<Point> = self.points.first().copied().into_iter().collect();
+        for curve in self.to_beziers() {
+            // Skip the first point of each segment since it equals the previous segment's end
+            polyline.extend(curve.flatten(tolerance).into_iter().skip(1));
+        }
+        polyline
+    }
+
+    // Builds the cubic Bézier equivalent of the segment between points `index` and `index + 1`.
+    fn segment(&self, index: usize) -> CubicBezier {
+        let last = self.points.len() - 1;
+        let p0 = self.points[index.saturating_sub(1)];
+        let p1 = self.points[index];
+        let p2 = self.points[index + 1];
+        let p3 = self.points[(index + 2).min(last)];
+
+        let scale = (1.0 - self.tension) / 2.0;
+        let m1 = (p2 - p0) * scale;
+        let m2 = (p3 - p1) * scale;
+
+        CubicBezier::new(p1, p1 + m1 * (1.0 / 3.0), p2 - m2 * (1.0 / 3.0), p2)
+    }
+}
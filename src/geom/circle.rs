@@ -0,0 +1,114 @@
+//! This module provides a circle structure and intersections of lines with circles and ellipses, building upon the `Point`, `Line` and `Rect` structs.
+
+use smallvec::SmallVec;
+
+use crate::geom::{Line, Point, Rect};
+
+/// The points where a line crosses a curve; at most two for circles and ellipses.
+pub type Intersections = SmallVec<[Point; 2]>;
+
+/// Represents a circle in 2D space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Circle {
+    pub center: Point,
+    pub radius: f32,
+}
+
+impl Circle {
+    /// Creates a new `Circle` from its center and radius.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Circle, Point};
+    /// let circle = Circle::new(Point::new(0.0, 0.0), 5.0);
+    /// ```
+    pub fn new(center: Point, radius: f32) -> Self {
+        Circle { center, radius }
+    }
+
+    /// Checks if the circle contains a given point, including its boundary.
+    pub fn contains_point(&self, point: &Point) -> bool {
+        self.center.distance(point) <= self.radius
+    }
+
+    /// Calculates the signed distance from a point to the circle's boundary.
+    /// The distance is negative inside the circle.
+    pub fn signed_distance(&self, point: &Point) -> f32 {
+        self.center.distance(point) - self.radius
+    }
+
+    /// Calculates the bounding box of the circle.
+    pub fn bounding_box(&self) -> Rect {
+        let extent = Point::new(self.radius, self.radius);
+        Rect::new(self.center - extent, self.center + extent)
+    }
+}
+
+impl Line {
+    /// Finds the points where the line segment crosses a circle, ordered from the start of the
+    /// segment to its end. A segment that only touches the circle yields a single point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Circle, Line, Point};
+    /// let line = Line::new(Point::new(-10.0, 0.0), Point::new(10.0, 0.0));
+    /// let circle = Circle::new(Point::new(0.0, 0.0), 5.0);
+    /// assert_eq!(line.intersect_circle(&circle).as_slice(), &[Point::new(-5.0, 0.0), Point::new(5.0, 0.0)]);
+    /// ```
+    pub fn intersect_circle(&self, circle: &Circle) -> Intersections {
+        self.intersect_ellipse(circle.center, circle.radius * 2.0, circle.radius * 2.0)
+    }
+
+    /// Finds the points where the line segment crosses an axis-aligned ellipse with the given
+    /// center, width and height, ordered from the start of the segment to its end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Line, Point};
+    /// let line = Line::new(Point::new(0.0, -10.0), Point::new(0.0, 0.0));
+    /// let points = line.intersect_ellipse(Point::new(0.0, 0.0), 8.0, 4.0);
+    /// assert_eq!(points.as_slice(), &[Point::new(0.0, -2.0)]);
+    /// ```
+    pub fn intersect_ellipse(&self, center: Point, width: f32, height: f32) -> Intersections {
+        let mut points = Intersections::new();
+        let (rx, ry) = (width / 2.0, height / 2.0);
+        if rx <= 0.0 || ry <= 0.0 {
+            return points;
+        }
+
+        // Scale space so the ellipse becomes the unit circle, relative to its center for precision
+        let start = Point::new((self.start.x - center.x) / rx, (self.start.y - center.y) / ry);
+        let d = Point::new((self.end.x - self.start.x) / rx, (self.end.y - self.start.y) / ry);
+
+        // Solve |start + d * t|^2 = 1 for t, written as a t^2 + 2 b t + c = 0
+        let a = d.dot(&d);
+        let b = start.dot(&d);
+        let c = start.dot(&start) - 1.0;
+        if a == 0.0 {
+            return points;
+        }
+        let discriminant = b * b - a * c;
+        if discriminant < 0.0 {
+            return points;
+        }
+
+        let roots = if discriminant == 0.0 {
+            [-b / a, f32::NAN]
+        } else {
+            // Avoids cancellation between b and the root of the discriminant
+            let q = -(b + b.signum() * discriminant.sqrt());
+            let (t1, t2) = (q / a, c / q);
+            [t1.min(t2), t1.max(t2)]
+        };
+        for t in roots {
+            if (0.0..=1.0).contains(&t) {
+                points.push(self.start + (self.end - self.start) * t);
+            }
+        }
+        points
+    }
+}
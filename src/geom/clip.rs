@@ -0,0 +1,146 @@
+//! This module provides clipping of lines against rectangles using the Liang–Barsky algorithm,
+//! and of polygons against convex regions using the Sutherland–Hodgman algorithm.
+//!
+//! The subject polygon may be concave. In that case a clipped result that falls apart into
+//! several pieces is returned as a single polygon whose pieces are joined by zero-width edges
+//! along the clip boundary, which fills correctly but may be visible when stroked.
+
+use crate::geom::{orientation, Line, Point, Polygon, Rect};
+
+impl Line {
+    /// Clips the line segment to a rectangle.
+    /// Returns None if no part of the segment lies inside the rectangle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Line, Point, Rect};
+    /// let line = Line::new(Point::new(-5.0, 2.0), Point::new(15.0, 2.0));
+    /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(10.0, 10.0));
+    /// assert_eq!(line.clip_to(&rect), Some(Line::new(Point::new(0.0, 2.0), Point::new(10.0, 2.0))));
+    ///
+    /// let outside = Line::new(Point::new(-5.0, -2.0), Point::new(15.0, -2.0));
+    /// assert_eq!(outside.clip_to(&rect), None);
+    /// ```
+    pub fn clip_to(&self, rect: &Rect) -> Option<Line> {
+        let d = self.end - self.start;
+        let mut t0: f32 = 0.0;
+        let mut t1: f32 = 1.0;
+
+        // Each boundary is given as p * t <= q, entering the rectangle where p < 0
+        let boundaries = [
+            (-d.x, self.start.x - rect.min.x),
+            (d.x, rect.max.x - self.start.x),
+            (-d.y, self.start.y - rect.min.y),
+            (d.y, rect.max.y - self.start.y),
+        ];
+        for (p, q) in boundaries {
+            if p == 0.0 {
+                // Parallel to this boundary, so either entirely outside or unaffected
+                if q < 0.0 {
+                    return None;
+                }
+            } else {
+                let t = q / p;
+                if p < 0.0 {
+                    t0 = t0.max(t);
+                } else {
+                    t1 = t1.min(t);
+                }
+            }
+        }
+        if t0 > t1 {
+            return None;
+        }
+
+        // Keep unclipped end points exact
+        let start = if t0 > 0.0 { self.start + d * t0 } else { self.start };
+        let end = if t1 < 1.0 { self.start + d * t1 } else { self.end };
+        Some(Line::new(start, end))
+    }
+}
+
+impl Polygon {
+    /// Clips the polygon to a rectangle.
+    /// Returns a polygon without vertices if nothing lies inside the rectangle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon, Rect};
+    /// let square = Polygon::new(vec![
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(4.0, 0.0),
+    ///     Point::new(4.0, 4.0),
+    ///     Point::new(0.0, 4.0),
+    /// ]);
+    /// let panel = Rect::new(Point::new(2.0, 2.0), Point::new(6.0, 6.0));
+    /// assert_eq!(square.clip_to(&panel).area(), 4.0);
+    /// ```
+    pub fn clip_to(&self, rect: &Rect) -> Polygon {
+        let corners = Polygon::new(vec![
+            rect.min,
+            Point::new(rect.max.x, rect.min.y),
+            rect.max,
+            Point::new(rect.min.x, rect.max.y),
+        ]);
+        self.clip_to_convex(&corners)
+    }
+
+    /// Clips the polygon to a convex polygon of either winding direction.
+    /// Returns a polygon without vertices if nothing lies inside the clip region, or if the
+    /// clip region has zero area. The result is unspecified if the clip region is not convex.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let square = Polygon::new(vec![
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(4.0, 0.0),
+    ///     Point::new(4.0, 4.0),
+    ///     Point::new(0.0, 4.0),
+    /// ]);
+    /// let triangle = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(0.0, 4.0)]);
+    /// assert_eq!(square.clip_to_convex(&triangle).area(), 8.0);
+    /// ```
+    pub fn clip_to_convex(&self, clip: &Polygon) -> Polygon {
+        // Inside is where each clip edge turns the same way as the region itself
+        let Some(winding) = clip.winding() else {
+            return Polygon::default();
+        };
+
+        let mut vertices = self.vertices.clone();
+        for edge in clip.edges() {
+            if vertices.is_empty() {
+                break;
+            }
+            let direction = edge.end - edge.start;
+            let side = |p: Point| direction.x * (p.y - edge.start.y) - direction.y * (p.x - edge.start.x);
+            // Points on the edge within tolerance count as inside, so they are not cut off by rounding
+            let inside = |p: Point| orientation(&edge.start, &edge.end, &p).is_none_or(|turn| turn == winding);
+
+            let input = std::mem::take(&mut vertices);
+            let mut previous = input[input.len() - 1];
+            let mut previous_inside = inside(previous);
+            for &current in &input {
+                let current_inside = inside(current);
+                if current_inside != previous_inside {
+                    let (previous_side, current_side) = (side(previous), side(current));
+                    let t = (previous_side / (previous_side - current_side)).clamp(0.0, 1.0);
+                    vertices.push(previous.lerp(&current, t));
+                }
+                if current_inside {
+                    vertices.push(current);
+                }
+                previous = current;
+                previous_inside = current_inside;
+            }
+        }
+
+        if vertices.len() < 3 {
+            vertices.clear();
+        }
+        Polygon::new(vertices)
+    }
+}
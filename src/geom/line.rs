@@ -1,9 +1,10 @@
 //! This module provides a 2D line segment structure and associated operations, building upon the `Point` struct.
 
-use crate::geom::Point;
+use crate::geom::{orientation, Point};
 
 /// Represents a line segment in 2D space defined by its start and end points.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Line {
     pub start: Point,
     pub end: Point,
@@ -15,6 +16,7 @@ impl Line {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Line, Point};
     /// let line = Line::new(Point::new(0.0, 0.0), Point::new(3.0, 4.0));
     /// ```
     pub fn new(start: Point, end: Point) -> Self {
@@ -26,6 +28,7 @@ impl Line {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Line, Point};
     /// let line = Line::new(Point::new(0.0, 0.0), Point::new(3.0, 4.0));
     /// assert_eq!(line.length(), 5.0);
     /// ```
@@ -38,6 +41,7 @@ impl Line {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Line, Point};
     /// let line = Line::new(Point::new(0.0, 0.0), Point::new(4.0, 4.0));
     /// assert_eq!(line.midpoint(), Point::new(2.0, 2.0));
     /// ```
@@ -54,6 +58,7 @@ impl Line {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Line, Point};
     /// let line1 = Line::new(Point::new(0.0, 0.0), Point::new(4.0, 4.0));
     /// assert_eq!(line1.slope(), Some(1.0));
     ///
@@ -74,6 +79,7 @@ impl Line {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Line, Point};
     /// let line1 = Line::new(Point::new(0.0, 0.0), Point::new(4.0, 4.0));
     /// let line2 = Line::new(Point::new(0.0, 4.0), Point::new(4.0, 0.0));
     /// let line3 = Line::new(Point::new(5.0, 5.0), Point::new(6.0, 6.0));
@@ -82,36 +88,18 @@ impl Line {
     /// assert!(!line1.intersects(&line3));
     /// ```
     pub fn intersects(&self, other: &Line) -> bool {
-        // Implementation of line segment intersection
-        // This uses the orientations of triplets of points
-        fn orientation(p: &Point, q: &Point, r: &Point) -> i8 {
-            let val = (q.y - p.y) * (r.x - q.x) - (q.x - p.x) * (r.y - q.y);
-            if val == 0.0 { 0 } else if val > 0.0 { 1 } else { 2 }
-        }
-
-        fn on_segment(p: &Point, q: &Point, r: &Point) -> bool {
-            q.x <= p.x.max(r.x) && q.x >= p.x.min(r.x) &&
-            q.y <= p.y.max(r.y) && q.y >= p.y.min(r.y)
-        }
-
+        // Each segment must have the end points of the other on opposite sides, unless an end
+        // point lies on the other segment
         let o1 = orientation(&self.start, &self.end, &other.start);
         let o2 = orientation(&self.start, &self.end, &other.end);
         let o3 = orientation(&other.start, &other.end, &self.start);
         let o4 = orientation(&other.start, &other.end, &self.end);
 
-        if o1 != o2 && o3 != o4 {
-            true
-        } else if o1 == 0 && on_segment(&self.start, &other.start, &self.end) {
-            true
-        } else if o2 == 0 && on_segment(&self.start, &other.end, &self.end) {
-            true
-        } else if o3 == 0 && on_segment(&other.start, &self.start, &other.end) {
-            true
-        } else if o4 == 0 && on_segment(&other.start, &self.end, &other.end) {
-            true
-        } else {
-            false
-        }
+        (o1 != o2 && o3 != o4)
+            || self.contains_point(&other.start)
+            || self.contains_point(&other.end)
+            || other.contains_point(&self.start)
+            || other.contains_point(&self.end)
     }
 
     /// Calculates the intersection point of this line segment with another line segment.
@@ -120,6 +108,7 @@ impl Line {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Line, Point};
     /// let line1 = Line::new(Point::new(0.0, 0.0), Point::new(4.0, 4.0));
     /// let line2 = Line::new(Point::new(0.0, 4.0), Point::new(4.0, 0.0));
     /// let intersection = line1.intersection_point(&line2).unwrap();
@@ -142,13 +131,13 @@ impl Line {
         let x4 = other.end.x;
         let y4 = other.end.y;
 
-        let denom = (x1 - x2) * (y3 - y4) - (y1 - y2) * (x3 - x4);
-
-        if denom == 0.0 {
+        if self.is_parallel(other) {
             return None; // Lines are parallel
         }
 
-        let t = ((x1 - x3) * (y3 - y4) - (y1 - y3) * (x3 - x4)) / denom;
+        let denom = (x1 - x2) * (y3 - y4) - (y1 - y2) * (x3 - x4);
+        // Touching segments may be classified as intersecting within tolerance, so stay on the segment
+        let t = (((x1 - x3) * (y3 - y4) - (y1 - y3) * (x3 - x4)) / denom).clamp(0.0, 1.0);
 
         Some(Point::new(
             x1 + t * (x2 - x1),
@@ -161,6 +150,7 @@ impl Line {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Line, Point};
     /// let line = Line::new(Point::new(0.0, 0.0), Point::new(4.0, 4.0));
     /// let point = Point::new(0.0, 4.0);
     /// let closest = line.closest_point(&point);
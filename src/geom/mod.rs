@@ -1,12 +1,39 @@
- //! A 2D geometry module providing basic structures and operations for points, lines, and rectangles.
+ //! A 2D geometry module providing basic structures and operations for points, lines, rectangles and curves.
 
 mod point;
 mod line;
 mod rect;
+mod bezier;
+mod catmull_rom;
+mod circle;
+mod transform;
+mod path;
+mod polygon;
+mod boolean;
+mod clip;
+mod offset;
+mod oriented_rect;
+mod spatial_hash;
+mod predicates;
+mod svg_path;
+mod smooth;
+pub mod sdf;
 
 pub use point::Point;
 pub use line::Line;
 pub use rect::Rect;
+pub use bezier::{QuadBezier, CubicBezier};
+pub use catmull_rom::CatmullRom;
+pub use circle::{Circle, Intersections};
+pub use transform::Transform;
+pub use path::{Path, PathCommand, PathSegment, Polyline};
+pub use polygon::{Polygon, Winding};
+pub use offset::Join;
+pub use oriented_rect::OrientedRect;
+pub use spatial_hash::SpatialHash;
+pub use predicates::orientation;
+pub use svg_path::{SvgPathError, SvgPathErrorKind};
+pub use smooth::{smooth_chaikin, smooth_chaikin_closed, resample, resample_count};
 
 // You can add any module-level functions or constants here if needed
 
@@ -18,7 +45,7 @@ pub use rect::Rect;
 /// # Examples
 ///
 /// ```
-/// use geom::distance;
+/// use simple_sketch::geom::distance;
 ///
 /// let dist = distance(0.0, 0.0, 3.0, 4.0);
 /// assert_eq!(dist, 5.0);
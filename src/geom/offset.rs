@@ -0,0 +1,256 @@
+//! This module provides offsetting of polygons and polylines, moving every edge sideways by a fixed distance.
+//!
+//! Offsetting is done per vertex and the result is not cleaned up: insetting a concave shape
+//! by more than its narrowest part produces self-intersecting loops, and sharp inner corners
+//! whose offset lines would meet beyond the adjacent edges fall back to a small notch through
+//! the original vertex. Both still fill correctly under the nonzero rule.
+//!
+//! Strokes offset both sides of a polyline and join them around its ends, which gives their
+//! outline as closed polylines to fill with the nonzero rule.
+
+use std::f32::consts::PI;
+
+use crate::geom::{Point, Polygon, Polyline};
+
+/// Maximum distance between a round join and the polyline used to approximate it.
+const ARC_TOLERANCE: f32 = 0.25;
+
+/// Most segments of a full turn of a round join. Joins with a radius beyond about 850000 are
+/// flattened coarser than `ARC_TOLERANCE` rather than into millions of points.
+const MAX_ARC_STEPS: f32 = 4096.0;
+
+/// Edges shorter than this are merged with their neighbours, since they have no usable direction.
+const MIN_EDGE_LENGTH: f32 = 1e-6;
+
+/// How far along the shorter adjacent edge the offset edges of an inner corner may cross when
+/// offsetting, as a fraction of its length.
+const OFFSET_INNER_REACH: f32 = 1.0;
+
+/// The same for strokes. Keeping every crossing within half of its edges keeps the corners of
+/// a stroke from meeting where it is wider than the curve it follows is tight.
+const STROKE_INNER_REACH: f32 = 0.5;
+
+/// How the offset edges are connected around the outside of a corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Join {
+    /// Extends the edges until they meet. Corners whose miter would be longer than `limit`
+    /// times the offset distance are beveled instead.
+    Miter { limit: f32 },
+    /// Connects the edges with a circular arc around the corner.
+    Round,
+    /// Connects the edge ends with a straight line.
+    Bevel,
+}
+
+impl Default for Join {
+    /// A miter join with the miter limit of SVG and PostScript.
+    fn default() -> Self {
+        Join::Miter { limit: 4.0 }
+    }
+}
+
+impl Polygon {
+    /// Grows the polygon outward by `distance`, or shrinks it for negative distances,
+    /// regardless of its winding direction. Polygons without area are returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Join, Point, Polygon};
+    /// let square = Polygon::new(vec![
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(2.0, 0.0),
+    ///     Point::new(2.0, 2.0),
+    ///     Point::new(0.0, 2.0),
+    /// ]);
+    /// assert_eq!(square.offset(1.0, Join::default()).area(), 16.0);
+    /// assert_eq!(square.offset(-0.5, Join::default()).area(), 1.0);
+    /// ```
+    pub fn offset(&self, distance: f32, join: Join) -> Polygon {
+        // The left side of every edge is the outside for clockwise polygons
+        let side = self.signed_area().signum();
+        if side == 0.0 || side.is_nan() {
+            return self.clone();
+        }
+        Polygon::new(offset_points(&self.vertices, true, distance * side, join, OFFSET_INNER_REACH))
+    }
+}
+
+impl Polyline {
+    /// Moves the polyline sideways by `distance`. Positive distances move it to the left of its
+    /// direction of travel as seen on screen, which is the outside of clockwise closed polylines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Join, Point, Polyline};
+    /// let line = Polyline { points: vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0)], closed: false };
+    /// let shifted = line.offset(1.0, Join::Bevel);
+    /// assert_eq!(shifted.points, vec![Point::new(0.0, -1.0), Point::new(4.0, -1.0)]);
+    /// ```
+    pub fn offset(&self, distance: f32, join: Join) -> Polyline {
+        Polyline {
+            points: offset_points(&self.points, self.closed, distance, join, OFFSET_INNER_REACH),
+            closed: self.closed,
+        }
+    }
+
+    /// Returns the outline of the polyline stroked `width` wide, as closed polylines to be
+    /// filled with the nonzero rule. The ends of open polylines are capped in the style of the
+    /// joins: round for round joins, and cut square through the end points otherwise. A
+    /// polyline that is a single point is a dot with round joins, and nothing otherwise.
+    ///
+    /// Closed polylines give one outline per side of the stroke. Where the stroke is wider than
+    /// the polyline turns tightly, the parts of the outline overlap instead of leaving a hole.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Join, Point, Polyline};
+    /// let line = Polyline { points: vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0)], closed: false };
+    /// let outline = line.stroke(2.0, Join::Bevel);
+    /// assert_eq!(outline[0].points, vec![
+    ///     Point::new(0.0, 1.0),
+    ///     Point::new(0.0, -1.0),
+    ///     Point::new(4.0, -1.0),
+    ///     Point::new(4.0, 1.0),
+    /// ]);
+    /// ```
+    pub fn stroke(&self, width: f32, join: Join) -> Vec<Polyline> {
+        let distance = width / 2.0;
+        let mut points: Vec<Point> = self.points.clone();
+        points.dedup_by(|a, b| a.distance(b) < MIN_EDGE_LENGTH);
+        if self.closed && points.len() > 1 && points[0].distance(&points[points.len() - 1]) < MIN_EDGE_LENGTH {
+            points.pop();
+        }
+        if distance.is_nan() || distance <= 0.0 {
+            return Vec::new();
+        }
+        if points.len() < 2 {
+            return match (points.first(), join) {
+                (Some(&center), Join::Round) => {
+                    let mut dot = Vec::new();
+                    arc_into(&mut dot, center, Point::new(distance, 0.0), 2.0 * PI);
+                    vec![Polyline { points: dot, closed: true }]
+                }
+                _ => Vec::new(),
+            };
+        }
+
+        let sides = if self.closed {
+            let reversed = points.iter().rev().copied().collect();
+            vec![points, reversed]
+        } else {
+            // Going there and back turns around each end like around a corner of a full turn,
+            // which the join caps
+            let back: Vec<Point> = points[1..points.len() - 1].iter().rev().copied().collect();
+            points.extend(back);
+            vec![points]
+        };
+        sides
+            .into_iter()
+            .map(|side| Polyline { points: offset_points(&side, true, distance, join, STROKE_INNER_REACH), closed: true })
+            .collect()
+    }
+}
+
+// Offsets the points to the left of the direction of travel by `distance`. The offset edges of
+// inner corners meet where they cross if that is within `inner_reach` times the length of the
+// shorter edge.
+fn offset_points(points: &[Point], closed: bool, distance: f32, join: Join, inner_reach: f32) -> Vec<Point> {
+    let mut points: Vec<Point> = points.to_vec();
+    points.dedup_by(|a, b| a.distance(b) < MIN_EDGE_LENGTH);
+    if closed && points.len() > 1 && points[0].distance(&points[points.len() - 1]) < MIN_EDGE_LENGTH {
+        points.pop();
+    }
+    if points.len() < 2 || distance == 0.0 {
+        return points;
+    }
+
+    let n = points.len();
+    let edge_count = if closed { n } else { n - 1 };
+    let edges: Vec<Point> = (0..edge_count).map(|i| points[(i + 1) % n] - points[i]).collect();
+
+    let mut result = Vec::with_capacity(n);
+    if closed {
+        for i in 0..n {
+            let incoming = edges[(i + n - 1) % n];
+            join_into(&mut result, points[i], incoming, edges[i], distance, join, inner_reach);
+        }
+    } else {
+        result.push(points[0] + left_normal(edges[0]) * distance);
+        for i in 1..n - 1 {
+            join_into(&mut result, points[i], edges[i - 1], edges[i], distance, join, inner_reach);
+        }
+        result.push(points[n - 1] + left_normal(edges[n - 2]) * distance);
+    }
+    result
+}
+
+// Appends the offset corner at `vertex` between the edges `incoming` and `outgoing`
+fn join_into(out: &mut Vec<Point>, vertex: Point, incoming: Point, outgoing: Point, distance: f32, join: Join, inner_reach: f32) {
+    let (length1, length2) = (incoming.length(), outgoing.length());
+    let (d1, d2) = (incoming * (1.0 / length1), outgoing * (1.0 / length2));
+    let (n1, n2) = (left_normal(incoming), left_normal(outgoing));
+    let p1 = vertex + n1 * distance;
+    let p2 = vertex + n2 * distance;
+
+    let cross = d1.x * d2.y - d1.y * d2.x;
+    let dot = d1.dot(&d2);
+    if cross.abs() < 1e-6 && dot > 0.0 {
+        // Straight continuation
+        out.push(p1);
+        return;
+    }
+
+    // Where the offset edges meet, at distance / cos(half the turn) from the vertex
+    let cos_half_squared = (1.0 + n1.dot(&n2)) / 2.0;
+    let miter = |out: &mut Vec<Point>| {
+        out.push(vertex + (n1 + n2) * (distance / (2.0 * cos_half_squared)));
+    };
+
+    let outer = cross * distance > 0.0 || cross.abs() < 1e-6;
+    if !outer {
+        // The offset edges cross; use the crossing unless it lies beyond the adjacent edges
+        let reach = distance.abs() * (1.0 - cos_half_squared).max(0.0).sqrt() / cos_half_squared.max(f32::EPSILON).sqrt();
+        if reach <= inner_reach * length1.min(length2) {
+            miter(out);
+        } else {
+            out.extend([p1, vertex, p2]);
+        }
+        return;
+    }
+
+    match join {
+        Join::Miter { limit } if cos_half_squared > 0.0 && 1.0 / cos_half_squared.sqrt() <= limit => miter(out),
+        Join::Miter { .. } | Join::Bevel => out.extend([p1, p2]),
+        Join::Round => {
+            // Sweep from the first offset direction to the second around the outside
+            let mut angle = (n1.x * n2.y - n1.y * n2.x).atan2(n1.dot(&n2));
+            if cross.abs() < 1e-6 {
+                angle = PI * distance.signum();
+            }
+            arc_into(out, vertex, n1 * distance, angle);
+        }
+    }
+}
+
+// Appends the points of a circular arc around `center`, from `center + start` and turning by
+// `angle`, within `ARC_TOLERANCE`
+fn arc_into(out: &mut Vec<Point>, center: Point, start: Point, angle: f32) {
+    let radius = start.length();
+    let max_step = if radius > ARC_TOLERANCE { 2.0 * (1.0 - ARC_TOLERANCE / radius).acos() } else { PI / 2.0 };
+    // The step rounds to 0 for huge radii
+    let max_step = max_step.max(2.0 * PI / MAX_ARC_STEPS);
+    let steps = (angle.abs() / max_step).ceil().max(1.0) as u32;
+    for i in 0..=steps {
+        let (sin, cos) = (angle * i as f32 / steps as f32).sin_cos();
+        out.push(center + Point::new(start.x * cos - start.y * sin, start.x * sin + start.y * cos));
+    }
+}
+
+// Unit normal pointing to the left of `direction` as seen on screen, with the y-axis pointing down
+fn left_normal(direction: Point) -> Point {
+    let length = direction.length();
+    Point::new(direction.y / length, -direction.x / length)
+}
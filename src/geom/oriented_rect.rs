@@ -0,0 +1,146 @@
+//! This module provides a rotated rectangle structure, building upon the `Point`, `Rect` and `Polygon` structs.
+
+use crate::geom::{Point, Polygon, Rect};
+
+/// Represents a rectangle rotated around its center.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrientedRect {
+    pub center: Point,
+    /// Half the width and half the height of the rectangle before rotation.
+    pub half_extents: Point,
+    /// Clockwise rotation on screen, in radians.
+    pub rotation: f32,
+}
+
+impl OrientedRect {
+    /// Creates a new `OrientedRect` from its center, half extents and rotation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{OrientedRect, Point};
+    /// let rect = OrientedRect::new(Point::new(5.0, 5.0), Point::new(2.0, 1.0), std::f32::consts::FRAC_PI_4);
+    /// ```
+    pub fn new(center: Point, half_extents: Point, rotation: f32) -> Self {
+        OrientedRect { center, half_extents, rotation }
+    }
+
+    /// Creates an unrotated `OrientedRect` covering the same area as `rect`.
+    pub fn from_rect(rect: &Rect) -> Self {
+        OrientedRect {
+            center: rect.center(),
+            half_extents: Point::new(rect.width() / 2.0, rect.height() / 2.0),
+            rotation: 0.0,
+        }
+    }
+
+    /// Returns the unit vectors along the rectangle's own x- and y-axis.
+    pub fn axes(&self) -> (Point, Point) {
+        let (sin, cos) = self.rotation.sin_cos();
+        (Point::new(cos, sin), Point::new(-sin, cos))
+    }
+
+    /// Returns the four corners, clockwise on screen starting from the corner that is the
+    /// top-left one before rotation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{OrientedRect, Point};
+    /// let rect = OrientedRect::new(Point::new(0.0, 0.0), Point::new(2.0, 1.0), 0.0);
+    /// assert_eq!(rect.corners()[0], Point::new(-2.0, -1.0));
+    /// assert_eq!(rect.corners()[2], Point::new(2.0, 1.0));
+    /// ```
+    pub fn corners(&self) -> [Point; 4] {
+        let (x_axis, y_axis) = self.axes();
+        let x = x_axis * self.half_extents.x;
+        let y = y_axis * self.half_extents.y;
+        [
+            self.center - x - y,
+            self.center + x - y,
+            self.center + x + y,
+            self.center - x + y,
+        ]
+    }
+
+    /// Converts a point into the rectangle's own coordinate system, centered on the rectangle.
+    pub fn to_local(&self, point: &Point) -> Point {
+        let (x_axis, y_axis) = self.axes();
+        let offset = *point - self.center;
+        Point::new(offset.dot(&x_axis), offset.dot(&y_axis))
+    }
+
+    /// Checks if the rectangle contains a given point, including its boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{OrientedRect, Point};
+    /// let rect = OrientedRect::new(Point::new(0.0, 0.0), Point::new(2.0, 0.5), std::f32::consts::FRAC_PI_2);
+    /// assert!(rect.contains_point(&Point::new(0.0, 1.5)));
+    /// assert!(!rect.contains_point(&Point::new(1.5, 0.0)));
+    /// ```
+    pub fn contains_point(&self, point: &Point) -> bool {
+        let local = self.to_local(point);
+        local.x.abs() <= self.half_extents.x && local.y.abs() <= self.half_extents.y
+    }
+
+    /// Calculates the signed distance from a point to the rectangle's boundary.
+    /// The distance is negative inside the rectangle.
+    pub fn signed_distance(&self, point: &Point) -> f32 {
+        let local = self.to_local(point);
+        let dx = local.x.abs() - self.half_extents.x;
+        let dy = local.y.abs() - self.half_extents.y;
+        let outside = Point::new(dx.max(0.0), dy.max(0.0)).length();
+        let inside = dx.max(dy).min(0.0);
+        outside + inside
+    }
+
+    /// Calculates the axis-aligned bounding box of the rotated rectangle.
+    pub fn bounding_box(&self) -> Rect {
+        let (x_axis, y_axis) = self.axes();
+        let extent = Point::new(
+            (x_axis.x * self.half_extents.x).abs() + (y_axis.x * self.half_extents.y).abs(),
+            (x_axis.y * self.half_extents.x).abs() + (y_axis.y * self.half_extents.y).abs(),
+        );
+        Rect::new(self.center - extent, self.center + extent)
+    }
+
+    /// Checks if this rectangle overlaps another, using the separating axis theorem.
+    /// Rectangles that only touch are considered overlapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{OrientedRect, Point};
+    /// let a = OrientedRect::new(Point::new(0.0, 0.0), Point::new(1.0, 1.0), 0.0);
+    /// let b = OrientedRect::new(Point::new(2.3, 0.0), Point::new(1.0, 1.0), std::f32::consts::FRAC_PI_4);
+    /// assert!(a.intersects(&b));
+    ///
+    /// let c = OrientedRect::new(Point::new(3.0, 0.0), Point::new(1.0, 1.0), 0.0);
+    /// assert!(!a.intersects(&c));
+    /// ```
+    pub fn intersects(&self, other: &OrientedRect) -> bool {
+        let (a_x, a_y) = self.axes();
+        let (b_x, b_y) = other.axes();
+        let offset = other.center - self.center;
+
+        [a_x, a_y, b_x, b_y].iter().all(|axis| {
+            // Projected half sizes of both rectangles must together span the projected distance
+            let radius_a = (a_x.dot(axis) * self.half_extents.x).abs() + (a_y.dot(axis) * self.half_extents.y).abs();
+            let radius_b = (b_x.dot(axis) * other.half_extents.x).abs() + (b_y.dot(axis) * other.half_extents.y).abs();
+            offset.dot(axis).abs() <= radius_a + radius_b
+        })
+    }
+
+    /// Checks if this rectangle overlaps an axis-aligned rectangle.
+    pub fn intersects_rect(&self, rect: &Rect) -> bool {
+        self.intersects(&OrientedRect::from_rect(rect))
+    }
+
+    /// Converts the rectangle into a polygon of its corners.
+    pub fn to_polygon(&self) -> Polygon {
+        Polygon::new(self.corners().to_vec())
+    }
+}
@@ -0,0 +1,466 @@
+//! This module provides a general 2D path made of line and Bézier segments, building upon the curve types.
+
+use std::f32::consts::FRAC_PI_2;
+
+use crate::geom::{Point, Line, Rect, QuadBezier, CubicBezier, Transform};
+
+/// Control point distance, relative to the radius, for approximating a quarter ellipse with a cubic Bézier.
+const ELLIPSE_KAPPA: f32 = 0.552_284_8;
+
+/// A single drawing command of a `Path`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PathCommand {
+    /// Starts a new subpath at the given point.
+    MoveTo(Point),
+    /// Draws a straight line to the given point.
+    LineTo(Point),
+    /// Draws a quadratic Bézier with the given control and end points.
+    QuadTo(Point, Point),
+    /// Draws a cubic Bézier with the given control and end points.
+    CubicTo(Point, Point, Point),
+    /// Closes the current subpath with a straight line back to its start.
+    Close,
+}
+
+/// A drawable piece of a path with its start point resolved.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment {
+    Line(Line),
+    Quad(QuadBezier),
+    Cubic(CubicBezier),
+}
+
+impl PathSegment {
+    pub fn start(&self) -> Point {
+        match self {
+            PathSegment::Line(line) => line.start,
+            PathSegment::Quad(curve) => curve.start,
+            PathSegment::Cubic(curve) => curve.start,
+        }
+    }
+
+    pub fn end(&self) -> Point {
+        match self {
+            PathSegment::Line(line) => line.end,
+            PathSegment::Quad(curve) => curve.end,
+            PathSegment::Cubic(curve) => curve.end,
+        }
+    }
+
+    pub fn length(&self) -> f32 {
+        match self {
+            PathSegment::Line(line) => line.length(),
+            PathSegment::Quad(curve) => curve.length(),
+            PathSegment::Cubic(curve) => curve.length(),
+        }
+    }
+
+    /// Evaluates the segment at the point lying `distance` along its arc length.
+    pub fn point_at_length(&self, distance: f32) -> Point {
+        match self {
+            PathSegment::Line(line) => {
+                let length = line.length();
+                let t = if length > 0.0 { (distance / length).clamp(0.0, 1.0) } else { 0.0 };
+                line.start.lerp(&line.end, t)
+            }
+            PathSegment::Quad(curve) => curve.point_at_length(distance),
+            PathSegment::Cubic(curve) => curve.point_at_length(distance),
+        }
+    }
+
+    /// Finds the point on the segment closest to `point`, along with the distance to it
+    /// measured along the segment from its start.
+    pub fn closest_point(&self, point: &Point) -> (Point, f32) {
+        match self {
+            PathSegment::Line(line) => {
+                let closest = line.closest_point(point);
+                (closest, line.start.distance(&closest))
+            }
+            PathSegment::Quad(curve) => {
+                let t = curve.closest_t(point);
+                (curve.point_at(t), curve.split(t).0.length())
+            }
+            PathSegment::Cubic(curve) => {
+                let t = curve.closest_t(point);
+                (curve.point_at(t), curve.split(t).0.length())
+            }
+        }
+    }
+
+    // Appends the flattened segment to `points`, excluding its start point.
+    fn flatten_into(&self, tolerance: f32, points: &mut Vec<Point>) {
+        match self {
+            PathSegment::Line(line) => points.push(line.end),
+            PathSegment::Quad(curve) => points.extend(curve.flatten(tolerance).into_iter().skip(1)),
+            PathSegment::Cubic(curve) => points.extend(curve.flatten(tolerance).into_iter().skip(1)),
+        }
+    }
+}
+
+/// One flattened subpath of a `Path`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polyline {
+    pub points: Vec<Point>,
+    /// Whether the subpath was explicitly closed. Closed polylines do not repeat their first point.
+    pub closed: bool,
+}
+
+/// Represents a path made of one or more subpaths of straight and curved segments.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Path {
+    pub commands: Vec<PathCommand>,
+}
+
+impl Path {
+    /// Creates a new, empty `Path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Path, Point};
+    /// let mut path = Path::new();
+    /// path.move_to(Point::new(0.0, 0.0))
+    ///     .line_to(Point::new(10.0, 0.0))
+    ///     .quad_to(Point::new(10.0, 10.0), Point::new(0.0, 10.0))
+    ///     .close();
+    /// ```
+    pub fn new() -> Self {
+        Path { commands: Vec::new() }
+    }
+
+    /// Creates a closed path tracing the outline of a rectangle clockwise from its top-left corner.
+    pub fn rectangle(top_left: Point, width: f32, height: f32) -> Self {
+        let mut path = Path::new();
+        path.move_to(top_left)
+            .line_to(top_left + Point::new(width, 0.0))
+            .line_to(top_left + Point::new(width, height))
+            .line_to(top_left + Point::new(0.0, height))
+            .close();
+        path
+    }
+
+    /// Creates a closed path through the given points, in order.
+    pub fn polygon(points: &[Point]) -> Self {
+        let mut path = Path::new();
+        if let [first, rest @ ..] = points {
+            path.move_to(*first);
+            for point in rest {
+                path.line_to(*point);
+            }
+            path.close();
+        }
+        path
+    }
+
+    /// Creates a closed path approximating an ellipse with four cubic Bézier curves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Path, Point};
+    /// let circle = Path::ellipse(Point::new(0.0, 0.0), 2.0, 2.0);
+    /// assert!((circle.length() - std::f32::consts::TAU).abs() < 0.01);
+    /// ```
+    pub fn ellipse(center: Point, width: f32, height: f32) -> Self {
+        let (rx, ry) = (width / 2.0, height / 2.0);
+        let (kx, ky) = (rx * ELLIPSE_KAPPA, ry * ELLIPSE_KAPPA);
+        let at = |x: f32, y: f32| center + Point::new(x, y);
+
+        let mut path = Path::new();
+        path.move_to(at(rx, 0.0))
+            .cubic_to(at(rx, ky), at(kx, ry), at(0.0, ry))
+            .cubic_to(at(-kx, ry), at(-rx, ky), at(-rx, 0.0))
+            .cubic_to(at(-rx, -ky), at(-kx, -ry), at(0.0, -ry))
+            .cubic_to(at(kx, -ry), at(rx, -ky), at(rx, 0.0))
+            .close();
+        path
+    }
+
+    pub fn move_to(&mut self, point: Point) -> &mut Self {
+        self.commands.push(PathCommand::MoveTo(point));
+        self
+    }
+
+    pub fn line_to(&mut self, point: Point) -> &mut Self {
+        self.commands.push(PathCommand::LineTo(point));
+        self
+    }
+
+    pub fn quad_to(&mut self, control: Point, end: Point) -> &mut Self {
+        self.commands.push(PathCommand::QuadTo(control, end));
+        self
+    }
+
+    pub fn cubic_to(&mut self, control1: Point, control2: Point, end: Point) -> &mut Self {
+        self.commands.push(PathCommand::CubicTo(control1, control2, end));
+        self
+    }
+
+    /// Adds a circular arc, drawn clockwise on screen for positive `sweep` angles and counterclockwise
+    /// for negative ones. The arc is connected to the end of the path with a straight line, or starts
+    /// a new subpath if the path is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Path, Point};
+    /// let mut path = Path::new();
+    /// path.arc(Point::new(0.0, 0.0), 1.0, 0.0, std::f32::consts::PI);
+    /// assert!((path.length() - std::f32::consts::PI).abs() < 0.01);
+    /// ```
+    pub fn arc(&mut self, center: Point, radius: f32, start: f32, sweep: f32) -> &mut Self {
+        let at = |angle: f32| center + Point::new(angle.cos(), angle.sin()) * radius;
+        if self.is_empty() {
+            self.move_to(at(start));
+        } else {
+            self.line_to(at(start));
+        }
+
+        // Cubics approximate arcs of up to a quarter turn closely
+        let pieces = (sweep.abs() / FRAC_PI_2).ceil().max(1.0) as u32;
+        let step = sweep / pieces as f32;
+        let handle = radius * 4.0 / 3.0 * (step / 4.0).tan();
+        for i in 0..pieces {
+            let (a1, a2) = (start + step * i as f32, start + step * (i + 1) as f32);
+            let tangent = |angle: f32| Point::new(-angle.sin(), angle.cos()) * handle;
+            self.cubic_to(at(a1) + tangent(a1), at(a2) - tangent(a2), at(a2));
+        }
+        self
+    }
+
+    pub fn close(&mut self) -> &mut Self {
+        self.commands.push(PathCommand::Close);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Returns all segments of the path in drawing order, including the closing lines of closed subpaths.
+    /// Drawing commands issued before any `MoveTo` start at the origin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Path, Point};
+    /// let mut path = Path::new();
+    /// path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(3.0, 0.0)).line_to(Point::new(3.0, 4.0)).close();
+    /// assert_eq!(path.segments().len(), 3);
+    /// ```
+    pub fn segments(&self) -> Vec<PathSegment> {
+        let mut segments = Vec::new();
+        let mut subpath_start = Point::new(0.0, 0.0);
+        let mut current = subpath_start;
+
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo(point) => {
+                    subpath_start = point;
+                    current = point;
+                }
+                PathCommand::LineTo(point) => {
+                    segments.push(PathSegment::Line(Line::new(current, point)));
+                    current = point;
+                }
+                PathCommand::QuadTo(control, end) => {
+                    segments.push(PathSegment::Quad(QuadBezier::new(current, control, end)));
+                    current = end;
+                }
+                PathCommand::CubicTo(control1, control2, end) => {
+                    segments.push(PathSegment::Cubic(CubicBezier::new(current, control1, control2, end)));
+                    current = end;
+                }
+                PathCommand::Close => {
+                    if current != subpath_start {
+                        segments.push(PathSegment::Line(Line::new(current, subpath_start)));
+                    }
+                    current = subpath_start;
+                }
+            }
+        }
+        segments
+    }
+
+    /// Calculates the total length of all segments of the path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Path, Point};
+    /// let mut path = Path::new();
+    /// path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(3.0, 0.0)).line_to(Point::new(3.0, 4.0)).close();
+    /// assert_eq!(path.length(), 12.0);
+    /// ```
+    pub fn length(&self) -> f32 {
+        self.segments().iter().map(PathSegment::length).sum()
+    }
+
+    /// Finds the point lying `distance` along the path, measured from its start.
+    /// Distances outside the path's length are clamped to its ends.
+    /// Returns None if the path has no segments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Path, Point};
+    /// let mut path = Path::new();
+    /// path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(3.0, 0.0)).line_to(Point::new(3.0, 4.0));
+    /// assert_eq!(path.point_at_length(5.0), Some(Point::new(3.0, 2.0)));
+    /// ```
+    pub fn point_at_length(&self, distance: f32) -> Option<Point> {
+        let segments = self.segments();
+        let mut remaining = distance.max(0.0);
+        for segment in &segments {
+            let length = segment.length();
+            if remaining <= length {
+                return Some(segment.point_at_length(remaining));
+            }
+            remaining -= length;
+        }
+        segments.last().map(PathSegment::end)
+    }
+
+    /// Finds the point on the path closest to `point`, along with the distance to it measured
+    /// along the path from its start, as used by `point_at_length`.
+    /// Returns None if the path has no segments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Path, Point};
+    /// let mut path = Path::new();
+    /// path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(3.0, 0.0)).line_to(Point::new(3.0, 4.0));
+    /// assert_eq!(path.closest_point(&Point::new(5.0, 2.0)), Some((Point::new(3.0, 2.0), 5.0)));
+    /// ```
+    pub fn closest_point(&self, point: &Point) -> Option<(Point, f32)> {
+        let mut best: Option<(f32, Point, f32)> = None;
+        let mut offset = 0.0;
+        for segment in self.segments() {
+            let (closest, along) = segment.closest_point(point);
+            let distance = point.distance(&closest);
+            if best.is_none_or(|(best_distance, _, _)| distance < best_distance) {
+                best = Some((distance, closest, offset + along));
+            }
+            offset += segment.length();
+        }
+        best.map(|(_, closest, along)| (closest, along))
+    }
+
+    /// Calculates the tight bounding box of the path.
+    /// Returns None if the path has no commands.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Path, Point, Rect};
+    /// let mut path = Path::new();
+    /// path.move_to(Point::new(0.0, 0.0)).quad_to(Point::new(1.0, 2.0), Point::new(2.0, 0.0));
+    /// assert_eq!(path.bounding_box(), Some(Rect::new(Point::new(0.0, 0.0), Point::new(2.0, 1.0))));
+    /// ```
+    pub fn bounding_box(&self) -> Option<Rect> {
+        let mut bounds: Option<Rect> = None;
+        let mut include = |rect: Rect| {
+            bounds = Some(bounds.map_or(rect, |b| b.union(&rect)));
+        };
+
+        for command in &self.commands {
+            if let PathCommand::MoveTo(point) = command {
+                include(Rect::new(*point, *point));
+            }
+        }
+        for segment in self.segments() {
+            match segment {
+                PathSegment::Line(line) => include(Rect::from_points(line.start, line.end)),
+                PathSegment::Quad(curve) => include(curve.bounding_box()),
+                PathSegment::Cubic(curve) => include(curve.bounding_box()),
+            }
+        }
+        bounds
+    }
+
+    /// Flattens every subpath into a polyline whose maximum deviation from the curves is about `tolerance`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Path, Point};
+    /// let mut path = Path::new();
+    /// path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(3.0, 0.0)).line_to(Point::new(3.0, 4.0)).close();
+    /// let polylines = path.flatten(0.1);
+    /// assert_eq!(polylines.len(), 1);
+    /// assert_eq!(polylines[0].points.len(), 3);
+    /// assert!(polylines[0].closed);
+    /// ```
+    pub fn flatten(&self, tolerance: f32) -> Vec<Polyline> {
+        let mut polylines = Vec::new();
+        let mut current = Polyline { points: vec![Point::new(0.0, 0.0)], closed: false };
+        let mut cursor = Point::new(0.0, 0.0);
+
+        let mut finish = |polyline: &mut Polyline, start: Point| {
+            let finished = std::mem::replace(polyline, Polyline { points: vec![start], closed: false });
+            if finished.points.len() > 1 {
+                polylines.push(finished);
+            }
+        };
+
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo(point) => {
+                    finish(&mut current, point);
+                    cursor = point;
+                }
+                PathCommand::LineTo(point) => {
+                    current.points.push(point);
+                    cursor = point;
+                }
+                PathCommand::QuadTo(control, end) => {
+                    PathSegment::Quad(QuadBezier::new(cursor, control, end)).flatten_into(tolerance, &mut current.points);
+                    cursor = end;
+                }
+                PathCommand::CubicTo(control1, control2, end) => {
+                    PathSegment::Cubic(CubicBezier::new(cursor, control1, control2, end)).flatten_into(tolerance, &mut current.points);
+                    cursor = end;
+                }
+                PathCommand::Close => {
+                    let start = current.points[0];
+                    if current.points.len() > 1 && current.points.last() == Some(&start) {
+                        current.points.pop();
+                    }
+                    current.closed = true;
+                    finish(&mut current, start);
+                    cursor = start;
+                }
+            }
+        }
+        finish(&mut current, cursor);
+        polylines
+    }
+
+    /// Creates a new path with every point mapped through `transform`.
+    /// Affine transforms map Bézier curves exactly, so no precision is lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Path, Point, Transform};
+    /// let mut path = Path::new();
+    /// path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(1.0, 0.0));
+    /// let moved = path.transform(&Transform::translate(Point::new(5.0, 5.0)));
+    /// assert_eq!(moved.point_at_length(0.0), Some(Point::new(5.0, 5.0)));
+    /// ```
+    pub fn transform(&self, transform: &Transform) -> Path {
+        let map = |p: Point| transform.transform_point(p);
+        let commands = self.commands.iter().map(|command| match *command {
+            PathCommand::MoveTo(point) => PathCommand::MoveTo(map(point)),
+            PathCommand::LineTo(point) => PathCommand::LineTo(map(point)),
+            PathCommand::QuadTo(control, end) => PathCommand::QuadTo(map(control), map(end)),
+            PathCommand::CubicTo(control1, control2, end) => PathCommand::CubicTo(map(control1), map(control2), map(end)),
+            PathCommand::Close => PathCommand::Close,
+        });
+        Path { commands: commands.collect() }
+    }
+}
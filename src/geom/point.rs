@@ -4,6 +4,7 @@ use std::ops::{Add, Sub, Mul};
 
 /// Represents a point in 2D space with x and y coordinates.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     pub x: f32,
     pub y: f32,
@@ -15,6 +16,7 @@ impl Point {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::Point;
     /// let p = Point::new(3.0, 4.0);
     /// assert_eq!(p.x, 3.0);
     /// assert_eq!(p.y, 4.0);
@@ -28,6 +30,7 @@ impl Point {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::Point;
     /// let p = Point::new(3.0, 4.0);
     /// assert_eq!(p.length(), 5.0);
     /// ```
@@ -41,6 +44,7 @@ impl Point {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::Point;
     /// let p = Point::new(3.0, 4.0);
     /// let normalized = p.normalize();
     /// assert_eq!(normalized.length(), 1.0);
@@ -62,6 +66,7 @@ impl Point {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::Point;
     /// let p = Point::new(-3.0, 4.0);
     /// let abs_p = p.abs();
     /// assert_eq!(abs_p, Point::new(3.0, 4.0));
@@ -78,6 +83,7 @@ impl Point {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::Point;
     /// let p = Point::new(3.0, 4.0);
     /// let perp = p.perpendicular();
     /// assert_eq!(perp, Point::new(-4.0, 3.0));
@@ -94,6 +100,7 @@ impl Point {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::Point;
     /// let p1 = Point::new(0.0, 0.0);
     /// let p2 = Point::new(3.0, 4.0);
     /// assert_eq!(p1.distance(&p2), 5.0);
@@ -107,6 +114,7 @@ impl Point {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::Point;
     /// let p1 = Point::new(1.0, 2.0);
     /// let p2 = Point::new(3.0, 4.0);
     /// assert_eq!(p1.dot(&p2), 11.0);
@@ -114,6 +122,21 @@ impl Point {
     pub fn dot(&self, other: &Point) -> f32 {
         self.x * other.x + self.y * other.y
     }
+
+    /// Linearly interpolates between this point and another point.
+    /// A `t` of 0 returns this point and a `t` of 1 returns `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::Point;
+    /// let p1 = Point::new(0.0, 0.0);
+    /// let p2 = Point::new(4.0, 2.0);
+    /// assert_eq!(p1.lerp(&p2, 0.5), Point::new(2.0, 1.0));
+    /// ```
+    pub fn lerp(&self, other: &Point, t: f32) -> Point {
+        *self + (*other - *self) * t
+    }
 }
 
 impl Add for Point {
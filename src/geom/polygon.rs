@@ -0,0 +1,352 @@
+//! This module provides a simple polygon structure and its analytic properties, building upon the `Point`, `Line` and `Rect` structs.
+
+use crate::geom::{orientation, Point, Line, Rect};
+
+/// The direction in which a polygon's vertices wind, as seen on screen (y-axis pointing down).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Represents a closed polygon defined by its vertices in order.
+/// The last vertex is implicitly connected back to the first.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polygon {
+    pub vertices: Vec<Point>,
+}
+
+impl Polygon {
+    /// Creates a new `Polygon` from its vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let square = Polygon::new(vec![
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(2.0, 0.0),
+    ///     Point::new(2.0, 2.0),
+    ///     Point::new(0.0, 2.0),
+    /// ]);
+    /// ```
+    pub fn new(vertices: Vec<Point>) -> Self {
+        Polygon { vertices }
+    }
+
+    /// Returns the edges of the polygon, including the closing edge from the last vertex to the first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let triangle = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0), Point::new(0.0, 1.0)]);
+    /// assert_eq!(triangle.edges().count(), 3);
+    /// ```
+    pub fn edges(&self) -> impl Iterator<Item = Line> + '_ {
+        let n = self.vertices.len();
+        let count = if n < 2 { 0 } else { n };
+        (0..count).map(move |i| Line::new(self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+
+    /// Calculates the signed area using the shoelace formula.
+    /// The area is positive for clockwise polygons as seen on screen and negative for counterclockwise ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let square = Polygon::new(vec![
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(2.0, 0.0),
+    ///     Point::new(2.0, 2.0),
+    ///     Point::new(0.0, 2.0),
+    /// ]);
+    /// assert_eq!(square.signed_area(), 4.0);
+    /// ```
+    pub fn signed_area(&self) -> f32 {
+        self.edges()
+            .map(|edge| edge.start.x * edge.end.y - edge.end.x * edge.start.y)
+            .sum::<f32>()
+            / 2.0
+    }
+
+    /// Calculates the (unsigned) area of the polygon.
+    pub fn area(&self) -> f32 {
+        self.signed_area().abs()
+    }
+
+    /// Calculates the perimeter of the polygon.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let square = Polygon::new(vec![
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(2.0, 0.0),
+    ///     Point::new(2.0, 2.0),
+    ///     Point::new(0.0, 2.0),
+    /// ]);
+    /// assert_eq!(square.perimeter(), 8.0);
+    /// ```
+    pub fn perimeter(&self) -> f32 {
+        self.edges().map(|edge| edge.length()).sum()
+    }
+
+    /// Calculates the centroid (center of mass) of the polygon's area.
+    /// Returns None if the polygon has zero area.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let square = Polygon::new(vec![
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(2.0, 0.0),
+    ///     Point::new(2.0, 2.0),
+    ///     Point::new(0.0, 2.0),
+    /// ]);
+    /// assert_eq!(square.centroid(), Some(Point::new(1.0, 1.0)));
+    /// ```
+    pub fn centroid(&self) -> Option<Point> {
+        let area = self.signed_area();
+        if area == 0.0 {
+            return None;
+        }
+
+        let (cx, cy) = self.edges().fold((0.0, 0.0), |(cx, cy), edge| {
+            let cross = edge.start.x * edge.end.y - edge.end.x * edge.start.y;
+            (
+                cx + (edge.start.x + edge.end.x) * cross,
+                cy + (edge.start.y + edge.end.y) * cross,
+            )
+        });
+        Some(Point::new(cx / (6.0 * area), cy / (6.0 * area)))
+    }
+
+    /// Determines the winding direction of the vertices.
+    /// Returns None if the polygon has zero area.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon, Winding};
+    /// let triangle = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0), Point::new(0.0, 1.0)]);
+    /// assert_eq!(triangle.winding(), Some(Winding::Clockwise));
+    /// ```
+    pub fn winding(&self) -> Option<Winding> {
+        let area = self.signed_area();
+        if area > 0.0 {
+            Some(Winding::Clockwise)
+        } else if area < 0.0 {
+            Some(Winding::CounterClockwise)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a new polygon with the vertex order reversed, flipping its winding direction.
+    pub fn reversed(&self) -> Polygon {
+        Polygon::new(self.vertices.iter().rev().copied().collect())
+    }
+
+    /// Determines if the polygon is convex, i.e. all its interior angles are at most 180 degrees.
+    /// Collinear vertices are allowed. Polygons with fewer than three vertices are not convex.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let square = Polygon::new(vec![
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(2.0, 0.0),
+    ///     Point::new(2.0, 2.0),
+    ///     Point::new(0.0, 2.0),
+    /// ]);
+    /// assert!(square.is_convex());
+    ///
+    /// let arrow = Polygon::new(vec![
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(2.0, 1.0),
+    ///     Point::new(0.0, 2.0),
+    ///     Point::new(1.0, 1.0),
+    /// ]);
+    /// assert!(!arrow.is_convex());
+    /// ```
+    pub fn is_convex(&self) -> bool {
+        let n = self.vertices.len();
+        if n < 3 {
+            return false;
+        }
+
+        let mut turn = None;
+        for i in 0..n {
+            let a = self.vertices[i];
+            let b = self.vertices[(i + 1) % n];
+            let c = self.vertices[(i + 2) % n];
+            if let Some(current) = orientation(&a, &b, &c) {
+                if turn.is_some_and(|previous| previous != current) {
+                    return false;
+                }
+                turn = Some(current);
+            }
+        }
+        // All vertices collinear means the polygon is degenerate
+        turn.is_some()
+    }
+
+    /// Checks if the polygon contains a given point, using the even-odd rule.
+    /// Points on the boundary count as inside.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let triangle = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(0.0, 4.0)]);
+    /// assert!(triangle.contains_point(&Point::new(1.0, 1.0)));
+    /// assert!(!triangle.contains_point(&Point::new(3.0, 3.0)));
+    /// ```
+    pub fn contains_point(&self, point: &Point) -> bool {
+        if self.edges().any(|edge| edge.contains_point(point)) {
+            return true;
+        }
+
+        let mut inside = false;
+        for edge in self.edges() {
+            let (a, b) = (edge.start, edge.end);
+            // Toggle for every edge crossed by a ray cast towards +x
+            if (a.y > point.y) != (b.y > point.y) {
+                let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if point.x < x {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
+    /// Calculates the shortest distance from a point to the polygon's edges.
+    /// Returns infinity for a polygon without vertices.
+    pub fn distance_to_boundary(&self, point: &Point) -> f32 {
+        match self.vertices.as_slice() {
+            [] => f32::INFINITY,
+            [vertex] => vertex.distance(point),
+            _ => self
+                .edges()
+                .map(|edge| point.distance(&edge.closest_point(point)))
+                .fold(f32::INFINITY, f32::min),
+        }
+    }
+
+    /// Finds the point on the polygon's boundary closest to `point`, along with the distance to it
+    /// measured along the boundary from the first vertex.
+    /// Returns None if the polygon has no vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let square = Polygon::new(vec![
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(2.0, 0.0),
+    ///     Point::new(2.0, 2.0),
+    ///     Point::new(0.0, 2.0),
+    /// ]);
+    /// assert_eq!(square.closest_point(&Point::new(1.0, 3.0)), Some((Point::new(1.0, 2.0), 5.0)));
+    /// ```
+    pub fn closest_point(&self, point: &Point) -> Option<(Point, f32)> {
+        let first = *self.vertices.first()?;
+        let mut best = (first.distance(point), first, 0.0);
+        let mut offset = 0.0;
+        for edge in self.edges() {
+            let closest = edge.closest_point(point);
+            let distance = point.distance(&closest);
+            if distance < best.0 {
+                best = (distance, closest, offset + edge.start.distance(&closest));
+            }
+            offset += edge.length();
+        }
+        Some((best.1, best.2))
+    }
+
+    /// Calculates the bounding box of the polygon.
+    /// Returns None if the polygon has no vertices.
+    pub fn bounding_box(&self) -> Option<Rect> {
+        let first = *self.vertices.first()?;
+        Some(self.vertices.iter().fold(Rect::new(first, first), |rect, &vertex| {
+            rect.union(&Rect::new(vertex, vertex))
+        }))
+    }
+
+    /// Splits the polygon into triangles by ear clipping, returned as indices into `vertices`
+    /// with the winding of the polygon. Works for concave polygons; self-intersecting ones
+    /// get triangles that may overlap. Returns no triangles for polygons with zero area.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Polygon};
+    /// let square = Polygon::new(vec![
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(2.0, 0.0),
+    ///     Point::new(2.0, 2.0),
+    ///     Point::new(0.0, 2.0),
+    /// ]);
+    /// assert_eq!(square.triangulate().len(), 2);
+    /// ```
+    pub fn triangulate(&self) -> Vec<[usize; 3]> {
+        let Some(winding) = self.winding() else {
+            return Vec::new();
+        };
+        let vertices = &self.vertices;
+        let mut remaining: Vec<usize> = (0..vertices.len()).collect();
+        let mut triangles = Vec::with_capacity(vertices.len().saturating_sub(2));
+        while remaining.len() > 3 {
+            let count = remaining.len();
+            let corner = |i: usize| (remaining[(i + count - 1) % count], remaining[i], remaining[(i + 1) % count]);
+            let is_ear = |i: usize| {
+                let (a, b, c) = corner(i);
+                orientation(&vertices[a], &vertices[b], &vertices[c]) == Some(winding)
+                    && !remaining.iter().any(|&p| {
+                        p != a && p != b && p != c && in_triangle(&vertices[p], [&vertices[a], &vertices[b], &vertices[c]], winding)
+                    })
+            };
+            match (0..count).find(|&i| is_ear(i)) {
+                Some(i) => {
+                    let (a, b, c) = corner(i);
+                    triangles.push([a, b, c]);
+                    remaining.remove(i);
+                }
+                None => {
+                    // Collinear vertices add no area and can go. Otherwise the polygon crosses
+                    // itself, and clipping any corner keeps the loop going.
+                    let i = (0..count)
+                        .find(|&i| {
+                            let (a, b, c) = corner(i);
+                            orientation(&vertices[a], &vertices[b], &vertices[c]).is_none()
+                        })
+                        .unwrap_or_else(|| {
+                            let (a, b, c) = corner(0);
+                            triangles.push([a, b, c]);
+                            0
+                        });
+                    remaining.remove(i);
+                }
+            }
+        }
+        if let [a, b, c] = remaining[..] {
+            if orientation(&vertices[a], &vertices[b], &vertices[c]).is_some() {
+                triangles.push([a, b, c]);
+            }
+        }
+        triangles
+    }
+}
+
+// Whether `point` is inside or on the edges of a triangle with the given winding.
+fn in_triangle(point: &Point, [a, b, c]: [&Point; 3], winding: Winding) -> bool {
+    [(a, b), (b, c), (c, a)].iter().all(|(start, end)| orientation(start, end, point).is_none_or(|side| side == winding))
+}
@@ -0,0 +1,72 @@
+//! This module provides orientation and on-segment predicates that tolerate rounding errors, building upon the `Point`, `Line` and `Polygon` structs.
+//!
+//! Comparing a cross product to exactly zero misclassifies points that lie on, or very nearly on,
+//! a line, since its rounding error can have either sign. The predicates here evaluate in double
+//! precision and treat points as collinear when the sine of the angle between them, as seen from
+//! the first point, is below a tolerance.
+
+use crate::geom::{Line, Point, Winding};
+
+/// Relative tolerance below which a cross product counts as zero, measured against the lengths
+/// of the two vectors it is made of, so the sine of the angle between them.
+const COLLINEAR_EPSILON: f64 = 1e-6;
+
+/// Determines which way the path from `a` through `b` turns to reach `c`, as seen on screen
+/// (y-axis pointing down). Returns None if the three points are collinear within tolerance.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::geom::{Point, Winding, orientation};
+/// let (a, b) = (Point::new(0.0, 0.0), Point::new(1.0, 0.0));
+/// assert_eq!(orientation(&a, &b, &Point::new(1.0, 1.0)), Some(Winding::Clockwise));
+/// assert_eq!(orientation(&a, &b, &Point::new(1.0, -1.0)), Some(Winding::CounterClockwise));
+/// assert_eq!(orientation(&a, &b, &Point::new(0.3, 1e-9)), None);
+/// ```
+pub fn orientation(a: &Point, b: &Point, c: &Point) -> Option<Winding> {
+    let (abx, aby) = (b.x as f64 - a.x as f64, b.y as f64 - a.y as f64);
+    let (acx, acy) = (c.x as f64 - a.x as f64, c.y as f64 - a.y as f64);
+    let cross = abx * acy - aby * acx;
+    // Scaled by the lengths rather than the products of the cross product, which are zero
+    // for axis-aligned segments and would make the tolerance vanish
+    let lengths = abx.hypot(aby) * acx.hypot(acy);
+
+    if cross.abs() <= COLLINEAR_EPSILON * lengths {
+        None
+    } else if cross > 0.0 {
+        Some(Winding::Clockwise)
+    } else {
+        Some(Winding::CounterClockwise)
+    }
+}
+
+impl Line {
+    /// Checks if a point lies on the line segment, within tolerance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Line, Point};
+    /// let line = Line::new(Point::new(0.0, 0.0), Point::new(3.0, 3.0));
+    /// assert!(line.contains_point(&Point::new(1.0, 1.0)));
+    /// assert!(!line.contains_point(&Point::new(4.0, 4.0)));
+    /// ```
+    pub fn contains_point(&self, point: &Point) -> bool {
+        if orientation(&self.start, &self.end, point).is_some() {
+            return false;
+        }
+        let direction = self.end - self.start;
+        let length_squared = direction.dot(&direction);
+        if length_squared == 0.0 {
+            return *point == self.start;
+        }
+        let t = ((*point - self.start).dot(&direction) / length_squared) as f64;
+        (-COLLINEAR_EPSILON..=1.0 + COLLINEAR_EPSILON).contains(&t)
+    }
+
+    /// Checks if two line segments point in the same or opposite directions, within tolerance.
+    pub fn is_parallel(&self, other: &Line) -> bool {
+        let origin = Point::new(0.0, 0.0);
+        orientation(&origin, &(self.end - self.start), &(other.end - other.start)).is_none()
+    }
+}
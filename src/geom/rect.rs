@@ -4,6 +4,7 @@ use crate::geom::Point;
 
 /// Represents a rectangle in 2D space defined by its minimum (top-left) and maximum (bottom-right) points.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect {
     pub min: Point,
     pub max: Point,
@@ -15,6 +16,7 @@ impl Rect {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
     /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(5.0, 5.0));
     /// ```
     pub fn new(min: Point, max: Point) -> Self {
@@ -26,6 +28,7 @@ impl Rect {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
     /// let rect = Rect::from_points(Point::new(5.0, 5.0), Point::new(0.0, 0.0));
     /// assert_eq!(rect.min, Point::new(0.0, 0.0));
     /// assert_eq!(rect.max, Point::new(5.0, 5.0));
@@ -42,6 +45,7 @@ impl Rect {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
     /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(5.0, 5.0));
     /// assert_eq!(rect.width(), 5.0);
     /// ```
@@ -54,6 +58,7 @@ impl Rect {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
     /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(5.0, 5.0));
     /// assert_eq!(rect.height(), 5.0);
     /// ```
@@ -66,6 +71,7 @@ impl Rect {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
     /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(5.0, 5.0));
     /// assert_eq!(rect.area(), 25.0);
     /// ```
@@ -78,6 +84,7 @@ impl Rect {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
     /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(5.0, 5.0));
     /// assert_eq!(rect.center(), Point::new(2.5, 2.5));
     /// ```
@@ -93,6 +100,7 @@ impl Rect {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
     /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(5.0, 5.0));
     /// assert!(rect.contains_point(&Point::new(2.5, 2.5)));
     /// assert!(!rect.contains_point(&Point::new(6.0, 6.0)));
@@ -107,6 +115,7 @@ impl Rect {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
     /// let rect1 = Rect::new(Point::new(0.0, 0.0), Point::new(5.0, 5.0));
     /// let rect2 = Rect::new(Point::new(3.0, 3.0), Point::new(8.0, 8.0));
     /// let rect3 = Rect::new(Point::new(6.0, 6.0), Point::new(9.0, 9.0));
@@ -123,6 +132,7 @@ impl Rect {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
     /// let rect1 = Rect::new(Point::new(0.0, 0.0), Point::new(5.0, 5.0));
     /// let rect2 = Rect::new(Point::new(3.0, 3.0), Point::new(8.0, 8.0));
     /// let union = rect1.union(&rect2);
@@ -140,6 +150,7 @@ impl Rect {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
     /// let rect1 = Rect::new(Point::new(0.0, 0.0), Point::new(5.0, 5.0));
     /// let rect2 = Rect::new(Point::new(3.0, 3.0), Point::new(8.0, 8.0));
     /// let intersection = rect1.intersection(&rect2).unwrap();
@@ -161,6 +172,7 @@ impl Rect {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
     /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(5.0, 5.0));
     /// let translated = rect.translate(Point::new(1.0, 1.0));
     /// assert_eq!(translated, Rect::new(Point::new(1.0, 1.0), Point::new(6.0, 6.0)));
@@ -177,6 +189,7 @@ impl Rect {
     /// # Examples
     ///
     /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
     /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(4.0, 4.0));
     /// let scaled = rect.scale(2.0);
     /// assert_eq!(scaled, Rect::new(Point::new(-2.0, -2.0), Point::new(6.0, 6.0)));
@@ -189,4 +202,125 @@ impl Rect {
             max: center + half_size,
         }
     }
-}
\ No newline at end of file
+
+    /// Creates a new rectangle grown by `dx` on the left and right and by `dy` on the top and bottom.
+    /// Negative values shrink the rectangle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
+    /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(4.0, 4.0));
+    /// let inflated = rect.inflate(1.0, 2.0);
+    /// assert_eq!(inflated, Rect::new(Point::new(-1.0, -2.0), Point::new(5.0, 6.0)));
+    /// ```
+    pub fn inflate(&self, dx: f32, dy: f32) -> Rect {
+        let offset = Point::new(dx, dy);
+        Rect {
+            min: self.min - offset,
+            max: self.max + offset,
+        }
+    }
+
+    /// Creates the largest rectangle with the given width to height ratio that fits inside
+    /// this rectangle, centered within it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
+    /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(8.0, 2.0));
+    /// let square = rect.fit_aspect(1.0);
+    /// assert_eq!(square, Rect::new(Point::new(3.0, 0.0), Point::new(5.0, 2.0)));
+    /// ```
+    pub fn fit_aspect(&self, ratio: f32) -> Rect {
+        let (width, height) = if self.width() > self.height() * ratio {
+            (self.height() * ratio, self.height())
+        } else {
+            (self.width(), self.width() / ratio)
+        };
+        let half_size = Point::new(width, height) * 0.5;
+        let center = self.center();
+        Rect {
+            min: center - half_size,
+            max: center + half_size,
+        }
+    }
+
+    /// Splits the rectangle into a left and a right part, at fraction `t` of its width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
+    /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(4.0, 2.0));
+    /// let (left, right) = rect.split_h(0.25);
+    /// assert_eq!(left, Rect::new(Point::new(0.0, 0.0), Point::new(1.0, 2.0)));
+    /// assert_eq!(right, Rect::new(Point::new(1.0, 0.0), Point::new(4.0, 2.0)));
+    /// ```
+    pub fn split_h(&self, t: f32) -> (Rect, Rect) {
+        let x = self.min.x + self.width() * t;
+        (
+            Rect::new(self.min, Point::new(x, self.max.y)),
+            Rect::new(Point::new(x, self.min.y), self.max),
+        )
+    }
+
+    /// Splits the rectangle into a top and a bottom part, at fraction `t` of its height.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
+    /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(4.0, 2.0));
+    /// let (top, bottom) = rect.split_v(0.5);
+    /// assert_eq!(top, Rect::new(Point::new(0.0, 0.0), Point::new(4.0, 1.0)));
+    /// assert_eq!(bottom, Rect::new(Point::new(0.0, 1.0), Point::new(4.0, 2.0)));
+    /// ```
+    pub fn split_v(&self, t: f32) -> (Rect, Rect) {
+        let y = self.min.y + self.height() * t;
+        (
+            Rect::new(self.min, Point::new(self.max.x, y)),
+            Rect::new(Point::new(self.min.x, y), self.max),
+        )
+    }
+
+    /// Divides the rectangle into `cols` by `rows` equal cells, returned row by row from the top-left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
+    /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(4.0, 2.0));
+    /// let cells: Vec<Rect> = rect.grid(2, 2).collect();
+    /// assert_eq!(cells.len(), 4);
+    /// assert_eq!(cells[1], Rect::new(Point::new(2.0, 0.0), Point::new(4.0, 1.0)));
+    /// ```
+    pub fn grid(&self, cols: usize, rows: usize) -> impl Iterator<Item = Rect> {
+        let rect = *self;
+        (0..rows).flat_map(move |row| {
+            (0..cols).map(move |col| {
+                // Computing both edges from fractions keeps neighbouring cells exactly adjacent
+                let min = rect.lerp_point(col as f32 / cols as f32, row as f32 / rows as f32);
+                let max = rect.lerp_point((col + 1) as f32 / cols as f32, (row + 1) as f32 / rows as f32);
+                Rect::new(min, max)
+            })
+        })
+    }
+
+    /// Returns the point at fraction `u` of the width and `v` of the height, measured from the top-left corner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
+    /// let rect = Rect::new(Point::new(0.0, 0.0), Point::new(4.0, 2.0));
+    /// assert_eq!(rect.lerp_point(0.5, 1.0), Point::new(2.0, 2.0));
+    /// ```
+    pub fn lerp_point(&self, u: f32, v: f32) -> Point {
+        Point::new(
+            self.min.x + self.width() * u,
+            self.min.y + self.height() * v,
+        )
+    }
+}
@@ -0,0 +1,227 @@
+//! This module provides signed distance functions for the geometry types and combinators to build
+//! new shapes from them, building upon the `Point`, `Line`, `Rect`, `Circle`, `Polygon` and curve structs.
+//!
+//! Distances are negative inside a shape. The combinators keep the exact distance outside of
+//! unions, but like all SDF boolean operations they only give a bound on it elsewhere, which is
+//! still good enough for rendering and ray marching.
+
+use crate::geom::{Circle, CubicBezier, Line, OrientedRect, Point, Polygon, QuadBezier, Rect};
+
+/// A shape described by its signed distance function.
+///
+/// The combinators are named with an `sdf_` prefix so that they don't shadow the inherent
+/// `union` and `intersection` of `Rect` and `Polygon`, which compute exact areas rather than
+/// distance functions.
+pub trait Sdf {
+    /// Calculates the signed distance from a point to the shape's boundary.
+    /// The distance is negative inside the shape.
+    fn signed_distance(&self, point: &Point) -> f32;
+
+    /// Combines this shape with another, covering the area of both.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Circle, Point};
+    /// # use simple_sketch::geom::sdf::Sdf;
+    /// let a = Circle::new(Point::new(0.0, 0.0), 1.0);
+    /// let b = Circle::new(Point::new(3.0, 0.0), 1.0);
+    /// let both = a.sdf_union(b);
+    /// assert_eq!(Sdf::signed_distance(&both, &Point::new(1.5, 0.0)), 0.5);
+    /// ```
+    fn sdf_union<B: Sdf>(self, other: B) -> Union<Self, B>
+    where
+        Self: Sized,
+    {
+        Union { a: self, b: other }
+    }
+
+    /// Keeps only the area covered by both this shape and another.
+    fn sdf_intersection<B: Sdf>(self, other: B) -> Intersection<Self, B>
+    where
+        Self: Sized,
+    {
+        Intersection { a: self, b: other }
+    }
+
+    /// Removes the area of another shape from this one.
+    fn sdf_subtract<B: Sdf>(self, other: B) -> Subtract<Self, B>
+    where
+        Self: Sized,
+    {
+        Subtract { a: self, b: other }
+    }
+
+    /// Combines this shape with another, blending them together where they are closer than `radius`.
+    fn smooth_union<B: Sdf>(self, other: B, radius: f32) -> SmoothUnion<Self, B>
+    where
+        Self: Sized,
+    {
+        SmoothUnion { a: self, b: other, radius }
+    }
+
+    /// Grows the shape by `radius` in every direction, rounding its corners.
+    /// A rounded line segment becomes a capsule.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Line, Point};
+    /// # use simple_sketch::geom::sdf::Sdf;
+    /// let capsule = Line::new(Point::new(0.0, 0.0), Point::new(4.0, 0.0)).rounded(1.0);
+    /// assert_eq!(capsule.signed_distance(&Point::new(2.0, 3.0)), 2.0);
+    /// ```
+    fn rounded(self, radius: f32) -> Rounded<Self>
+    where
+        Self: Sized,
+    {
+        Rounded { shape: self, radius }
+    }
+}
+
+impl<T: Sdf + ?Sized> Sdf for &T {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        (**self).signed_distance(point)
+    }
+}
+
+impl<T: Sdf + ?Sized> Sdf for Box<T> {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        (**self).signed_distance(point)
+    }
+}
+
+impl Sdf for Circle {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        Circle::signed_distance(self, point)
+    }
+}
+
+impl Sdf for OrientedRect {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        OrientedRect::signed_distance(self, point)
+    }
+}
+
+impl Sdf for Rect {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        let half_size = Point::new(self.width(), self.height()) * 0.5;
+        let offset = (*point - self.center()).abs() - half_size;
+        let outside = Point::new(offset.x.max(0.0), offset.y.max(0.0)).length();
+        let inside = offset.x.max(offset.y).min(0.0);
+        outside + inside
+    }
+}
+
+/// A line segment has no inside, so its distance is never negative. Round it to get a capsule.
+impl Sdf for Line {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        point.distance(&self.closest_point(point))
+    }
+}
+
+/// Uses the even-odd rule to decide which points are inside.
+impl Sdf for Polygon {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        let distance = self.distance_to_boundary(point);
+        if self.contains_point(point) { -distance } else { distance }
+    }
+}
+
+/// An open curve has no inside, so its distance is never negative.
+impl Sdf for QuadBezier {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        point.distance(&self.point_at(self.closest_t(point)))
+    }
+}
+
+/// An open curve has no inside, so its distance is never negative.
+impl Sdf for CubicBezier {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        point.distance(&self.point_at(self.closest_t(point)))
+    }
+}
+
+/// The area covered by either of two shapes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Union<A, B> {
+    pub a: A,
+    pub b: B,
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Union<A, B> {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        self.a.signed_distance(point).min(self.b.signed_distance(point))
+    }
+}
+
+/// The area covered by both of two shapes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intersection<A, B> {
+    pub a: A,
+    pub b: B,
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Intersection<A, B> {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        self.a.signed_distance(point).max(self.b.signed_distance(point))
+    }
+}
+
+/// The area of the first shape that is not covered by the second.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Subtract<A, B> {
+    pub a: A,
+    pub b: B,
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Subtract<A, B> {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        self.a.signed_distance(point).max(-self.b.signed_distance(point))
+    }
+}
+
+/// The area covered by either of two shapes, with a fillet of about `radius` where they meet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmoothUnion<A, B> {
+    pub a: A,
+    pub b: B,
+    pub radius: f32,
+}
+
+impl<A: Sdf, B: Sdf> Sdf for SmoothUnion<A, B> {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        smooth_min(self.a.signed_distance(point), self.b.signed_distance(point), self.radius)
+    }
+}
+
+/// A shape grown by `radius` in every direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rounded<S> {
+    pub shape: S,
+    pub radius: f32,
+}
+
+impl<S: Sdf> Sdf for Rounded<S> {
+    fn signed_distance(&self, point: &Point) -> f32 {
+        self.shape.signed_distance(point) - self.radius
+    }
+}
+
+/// Returns the minimum of `a` and `b`, blended smoothly where they are less than `radius` apart
+/// using a quadratic polynomial.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::geom::sdf::smooth_min;
+/// assert_eq!(smooth_min(0.0, 5.0, 1.0), 0.0);
+/// assert_eq!(smooth_min(1.0, 1.0, 2.0), 0.5);
+/// ```
+pub fn smooth_min(a: f32, b: f32, radius: f32) -> f32 {
+    if radius <= 0.0 {
+        return a.min(b);
+    }
+    let h = (radius - (a - b).abs()).max(0.0) / radius;
+    a.min(b) - h * h * radius * 0.25
+}
@@ -0,0 +1,152 @@
+//! This module provides smoothing and resampling of polylines, building upon the `Point` and `Polyline` structs.
+
+use crate::geom::{Point, Polyline};
+
+/// Rounds off the corners of an open polyline with Chaikin's corner cutting: every pass
+/// replaces each segment with points a quarter and three quarters along it, which converges
+/// to a smooth quadratic B-spline. The ends stay in place. Each pass about doubles the number
+/// of points, so two to four passes are usually enough.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::geom::{Point, smooth_chaikin};
+/// let smooth = smooth_chaikin(&[Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(4.0, 4.0)], 1);
+/// assert_eq!(smooth, vec![Point::new(0.0, 0.0), Point::new(3.0, 0.0), Point::new(4.0, 1.0), Point::new(4.0, 4.0)]);
+/// ```
+pub fn smooth_chaikin(points: &[Point], iterations: usize) -> Vec<Point> {
+    let mut points = points.to_vec();
+    for _ in 0..iterations {
+        if points.len() < 3 {
+            break;
+        }
+        let mut smoothed = Vec::with_capacity(points.len() * 2);
+        smoothed.push(points[0]);
+        let last = points.len() - 2;
+        for (i, pair) in points.windows(2).enumerate() {
+            // The cuts next to the ends are left out to keep them in place
+            if i > 0 {
+                smoothed.push(pair[0].lerp(&pair[1], 0.25));
+            }
+            if i < last {
+                smoothed.push(pair[0].lerp(&pair[1], 0.75));
+            }
+        }
+        smoothed.push(points[points.len() - 1]);
+        points = smoothed;
+    }
+    points
+}
+
+/// Like `smooth_chaikin` for the outline of a closed polyline, whose first point isn't
+/// repeated at the end. All corners are rounded off, including the first.
+pub fn smooth_chaikin_closed(points: &[Point], iterations: usize) -> Vec<Point> {
+    let mut points = points.to_vec();
+    for _ in 0..iterations {
+        if points.len() < 3 {
+            break;
+        }
+        let count = points.len();
+        points = (0..count)
+            .flat_map(|i| {
+                let (a, b) = (points[i], points[(i + 1) % count]);
+                [a.lerp(&b, 0.25), a.lerp(&b, 0.75)]
+            })
+            .collect();
+    }
+    points
+}
+
+/// Places points `spacing` apart along a polyline, measured along its length, from its first
+/// point to its last point. The last gap is shorter unless the length is a multiple of the
+/// spacing. Evenly spaced points make strokes drawn with the mouse at varying speeds, and
+/// flattened curves, behave the same along their whole length.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::geom::{Point, resample};
+/// let points = resample(&[Point::new(0.0, 0.0), Point::new(10.0, 0.0)], 4.0);
+/// assert_eq!(points, vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(8.0, 0.0), Point::new(10.0, 0.0)]);
+/// ```
+pub fn resample(points: &[Point], spacing: f32) -> Vec<Point> {
+    let length = polyline_length(points);
+    if spacing <= 0.0 || length == 0.0 {
+        return points.first().copied().into_iter().collect();
+    }
+    let count = (length / spacing).floor() as usize;
+    let mut distances: Vec<f32> = (0..=count).map(|i| i as f32 * spacing).collect();
+    if length - count as f32 * spacing > length * 1e-6 {
+        distances.push(length);
+    }
+    points_at(points, &distances)
+}
+
+/// Places `count` points evenly along a polyline, measured along its length, including its
+/// first and last point.
+pub fn resample_count(points: &[Point], count: usize) -> Vec<Point> {
+    let length = polyline_length(points);
+    match count {
+        0 => Vec::new(),
+        1 => points.first().copied().into_iter().collect(),
+        _ => points_at(points, &(0..count).map(|i| length * i as f32 / (count - 1) as f32).collect::<Vec<_>>()),
+    }
+}
+
+impl Polyline {
+    /// Rounds off the corners with `smooth_chaikin`, or `smooth_chaikin_closed` for closed
+    /// polylines.
+    pub fn smooth_chaikin(&self, iterations: usize) -> Polyline {
+        let points = if self.closed { smooth_chaikin_closed(&self.points, iterations) } else { smooth_chaikin(&self.points, iterations) };
+        Polyline { points, closed: self.closed }
+    }
+
+    /// Places points about `spacing` apart along the polyline, like `resample`. Closed
+    /// polylines are resampled all the way around, spreading the points evenly.
+    pub fn resample(&self, spacing: f32) -> Polyline {
+        if !self.closed {
+            return Polyline { points: resample(&self.points, spacing), closed: false };
+        }
+        let mut loop_points = self.points.clone();
+        loop_points.extend(self.points.first().copied());
+        let length = polyline_length(&loop_points);
+        if spacing <= 0.0 || length == 0.0 {
+            return self.clone();
+        }
+        let count = ((length / spacing).round() as usize).max(3);
+        let mut points = resample_count(&loop_points, count + 1);
+        // The last point is the first one again
+        points.pop();
+        Polyline { points, closed: true }
+    }
+
+    /// The length of the polyline, including the closing segment of closed polylines.
+    pub fn length(&self) -> f32 {
+        let closing = match (self.closed, self.points.first(), self.points.last()) {
+            (true, Some(first), Some(last)) => first.distance(last),
+            _ => 0.0,
+        };
+        polyline_length(&self.points) + closing
+    }
+}
+
+fn polyline_length(points: &[Point]) -> f32 {
+    points.windows(2).map(|pair| pair[0].distance(&pair[1])).sum()
+}
+
+// The points at increasing distances along a polyline
+fn points_at(points: &[Point], distances: &[f32]) -> Vec<Point> {
+    let mut result = Vec::with_capacity(distances.len());
+    let (mut segment, mut start) = (0, 0.0);
+    for &distance in distances {
+        while segment + 2 < points.len() && start + points[segment].distance(&points[segment + 1]) < distance {
+            start += points[segment].distance(&points[segment + 1]);
+            segment += 1;
+        }
+        let (a, b) = (points[segment], points.get(segment + 1).copied().unwrap_or(points[segment]));
+        let length = a.distance(&b);
+        let t = if length > 0.0 { ((distance - start) / length).clamp(0.0, 1.0) } else { 0.0 };
+        result.push(a.lerp(&b, t));
+    }
+    result
+}
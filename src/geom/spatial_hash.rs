@@ -0,0 +1,107 @@
+//! This module provides a uniform grid for finding points near a location, building upon the `Point` and `Rect` structs.
+
+use std::collections::HashMap;
+
+use crate::geom::{Point, Rect};
+
+/// Stores items at points in fixed-size square cells, so that neighbor queries only have to
+/// look at the cells around the queried area.
+///
+/// Works best when the points are spread fairly evenly and the cell size is close to the
+/// typical query radius, as in particle systems and collision broad-phases.
+#[derive(Debug, Clone)]
+pub struct SpatialHash<T> {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    items: Vec<(Point, T)>,
+}
+
+impl<T> SpatialHash<T> {
+    /// Creates an empty `SpatialHash` with square cells of the given size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, SpatialHash};
+    /// let mut grid = SpatialHash::new(10.0);
+    /// grid.insert(Point::new(5.0, 5.0), "a");
+    /// grid.insert(Point::new(50.0, 5.0), "b");
+    /// let near: Vec<_> = grid.query_radius(Point::new(0.0, 0.0), 10.0).map(|(_, item)| *item).collect();
+    /// assert_eq!(near, vec!["a"]);
+    /// ```
+    pub fn new(cell_size: f32) -> Self {
+        SpatialHash {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::new(),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Removes all items, keeping the allocated cells for reuse.
+    pub fn clear(&mut self) {
+        self.cells.values_mut().for_each(Vec::clear);
+        self.items.clear();
+    }
+
+    /// Adds an item at a point.
+    pub fn insert(&mut self, point: Point, item: T) {
+        let index = self.items.len();
+        self.items.push((point, item));
+        self.cells.entry(self.cell_of(point)).or_default().push(index);
+    }
+
+    /// Iterates over all items in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> + '_ {
+        self.items.iter().map(|(point, item)| (*point, item))
+    }
+
+    /// Iterates over the items within `radius` of `center`, including those exactly at the radius.
+    pub fn query_radius(&self, center: Point, radius: f32) -> impl Iterator<Item = (Point, &T)> + '_ {
+        let area = Rect::new(center - Point::new(radius, radius), center + Point::new(radius, radius));
+        self.query_rect(&area).filter(move |(point, _)| point.distance(&center) <= radius)
+    }
+
+    /// Iterates over the items inside a rectangle, including its boundary.
+    pub fn query_rect(&self, rect: &Rect) -> impl Iterator<Item = (Point, &T)> + '_ {
+        let rect = *rect;
+        let (x1, y1) = self.cell_of(rect.min);
+        let (x2, y2) = self.cell_of(rect.max);
+        let cell_count = (x2 as i64 - x1 as i64 + 1).max(0).saturating_mul((y2 as i64 - y1 as i64 + 1).max(0));
+
+        // Scanning every item is cheaper than visiting more cells than are occupied
+        let candidates: Box<dyn Iterator<Item = usize> + '_> = if cell_count > self.cells.len() as i64 {
+            Box::new(0..self.items.len())
+        } else {
+            Box::new(
+                (y1..=y2)
+                    .flat_map(move |y| (x1..=x2).map(move |x| (x, y)))
+                    .filter_map(|cell| self.cells.get(&cell))
+                    .flatten()
+                    .copied(),
+            )
+        };
+
+        candidates
+            .map(|index| &self.items[index])
+            .filter(move |(point, _)| {
+                point.x >= rect.min.x && point.x <= rect.max.x && point.y >= rect.min.y && point.y <= rect.max.y
+            })
+            .map(|(point, item)| (*point, item))
+    }
+
+    fn cell_of(&self, point: Point) -> (i32, i32) {
+        ((point.x / self.cell_size).floor() as i32, (point.y / self.cell_size).floor() as i32)
+    }
+}
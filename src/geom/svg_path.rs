@@ -0,0 +1,380 @@
+//! This module parses SVG path data into a `Path`, and writes paths and polygons back out as
+//! path data, building upon the `Point`, `Path` and `Polygon` structs.
+//!
+//! The full path grammar of SVG 1.1 is supported: absolute and relative move, line, horizontal,
+//! vertical, cubic, smooth cubic, quadratic, smooth quadratic, elliptical arc and close commands,
+//! with implicitly repeated arguments and compact number syntax such as `M10-5.5.5`.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::fmt::{self, Write as _};
+
+use crate::geom::{Path, PathCommand, Point, Polygon};
+
+/// Describes what went wrong while parsing SVG path data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SvgPathErrorKind {
+    /// A letter that is not a path command.
+    UnknownCommand(char),
+    /// Path data must start with a move command.
+    MissingMoveTo,
+    /// Numbers were found where a command letter was needed, such as after a close command.
+    ExpectedCommand,
+    /// A command ran out of numeric arguments.
+    ExpectedNumber,
+    /// An arc's large-arc or sweep flag was not `0` or `1`.
+    ExpectedFlag,
+}
+
+/// An error in SVG path data, with the byte offset at which it was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvgPathError {
+    /// The byte offset into the path data.
+    pub position: usize,
+    /// What went wrong.
+    pub kind: SvgPathErrorKind,
+}
+
+impl fmt::Display for SvgPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            SvgPathErrorKind::UnknownCommand(c) => write!(f, "unknown path command '{c}'")?,
+            SvgPathErrorKind::MissingMoveTo => write!(f, "path data must start with a move command")?,
+            SvgPathErrorKind::ExpectedCommand => write!(f, "expected a path command")?,
+            SvgPathErrorKind::ExpectedNumber => write!(f, "expected a number")?,
+            SvgPathErrorKind::ExpectedFlag => write!(f, "expected an arc flag of 0 or 1")?,
+        }
+        write!(f, " at offset {}", self.position)
+    }
+}
+
+impl std::error::Error for SvgPathError {}
+
+impl Path {
+    /// Parses SVG path data, the contents of a `<path>` element's `d` attribute.
+    ///
+    /// Elliptical arcs are converted to cubic Bézier curves. Coordinates are used as they are,
+    /// so SVG user units become pixels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Path, SvgPathErrorKind};
+    /// # let mut canvas = simple_sketch::canvas::Canvas::new(100, 100);
+    /// let path = Path::parse_svg("M 10 10 h 20 v 20 h -20 Z").unwrap();
+    /// assert_eq!(path.length(), 80.0);
+    /// canvas.draw_path(&path);
+    ///
+    /// let error = Path::parse_svg("M 10 10 L 20").unwrap_err();
+    /// assert_eq!(error.kind, SvgPathErrorKind::ExpectedNumber);
+    /// ```
+    pub fn parse_svg(data: &str) -> Result<Path, SvgPathError> {
+        let mut parser = Parser { data: data.as_bytes(), position: 0 };
+        let mut path = Path::new();
+        let mut current = Point::new(0.0, 0.0);
+        let mut subpath_start = current;
+        // Control points to reflect for smooth curves, only set right after a curve of the same kind
+        let mut cubic_control: Option<Point> = None;
+        let mut quad_control: Option<Point> = None;
+        let mut repeat: Option<u8> = None;
+
+        parser.skip_separators();
+        while !parser.at_end() {
+            let position = parser.position;
+            let command = match (parser.command()?, repeat) {
+                (Some(command), _) | (None, Some(command)) => command,
+                (None, None) if path.is_empty() => return Err(parser.error(SvgPathErrorKind::MissingMoveTo)),
+                (None, None) => return Err(parser.error(SvgPathErrorKind::ExpectedCommand)),
+            };
+            if path.is_empty() && !matches!(command, b'M' | b'm') {
+                return Err(SvgPathError { position, kind: SvgPathErrorKind::MissingMoveTo });
+            }
+
+            let relative = command.is_ascii_lowercase();
+            let origin = if relative { current } else { Point::new(0.0, 0.0) };
+            let (previous_cubic, previous_quad) = (cubic_control.take(), quad_control.take());
+            repeat = Some(command);
+
+            match command.to_ascii_uppercase() {
+                b'M' => {
+                    current = origin + parser.point()?;
+                    subpath_start = current;
+                    path.move_to(current);
+                    // Further coordinate pairs are implicit line commands
+                    repeat = Some(if relative { b'l' } else { b'L' });
+                }
+                b'L' => {
+                    current = origin + parser.point()?;
+                    path.line_to(current);
+                }
+                b'H' => {
+                    current.x = origin.x + parser.number()?;
+                    path.line_to(current);
+                }
+                b'V' => {
+                    current.y = origin.y + parser.number()?;
+                    path.line_to(current);
+                }
+                b'C' => {
+                    let control1 = origin + parser.point()?;
+                    let control2 = origin + parser.point()?;
+                    current = origin + parser.point()?;
+                    path.cubic_to(control1, control2, current);
+                    cubic_control = Some(control2);
+                }
+                b'S' => {
+                    let control1 = reflect(previous_cubic, current);
+                    let control2 = origin + parser.point()?;
+                    let end = origin + parser.point()?;
+                    path.cubic_to(control1, control2, end);
+                    current = end;
+                    cubic_control = Some(control2);
+                }
+                b'Q' => {
+                    let control = origin + parser.point()?;
+                    current = origin + parser.point()?;
+                    path.quad_to(control, current);
+                    quad_control = Some(control);
+                }
+                b'T' => {
+                    let control = reflect(previous_quad, current);
+                    current = origin + parser.point()?;
+                    path.quad_to(control, current);
+                    quad_control = Some(control);
+                }
+                b'A' => {
+                    let radii = Point::new(parser.number()?.abs(), parser.number()?.abs());
+                    let rotation = parser.number()?.to_radians();
+                    let large_arc = parser.flag()?;
+                    let sweep = parser.flag()?;
+                    let end = origin + parser.point()?;
+                    elliptical_arc(&mut path, current, end, radii, rotation, large_arc, sweep);
+                    current = end;
+                }
+                _ => {
+                    // Close takes no arguments, so it is never repeated implicitly
+                    path.close();
+                    current = subpath_start;
+                    repeat = None;
+                }
+            }
+            parser.skip_separators();
+        }
+        Ok(path)
+    }
+
+    /// Writes the path as SVG path data with absolute commands, for pasting into vector tools
+    /// or the `d` attribute of a `<path>` element. Numbers are written with enough digits to
+    /// parse back to the same path with `parse_svg`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Path, Point};
+    /// let mut path = Path::new();
+    /// path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(10.0, 0.0)).quad_to(Point::new(15.0, 5.0), Point::new(10.0, 10.0)).close();
+    /// assert_eq!(path.to_svg_path_string(), "M 0 0 L 10 0 Q 15 5 10 10 Z");
+    /// ```
+    pub fn to_svg_path_string(&self) -> String {
+        let mut data = String::new();
+        for command in &self.commands {
+            if !data.is_empty() {
+                data.push(' ');
+            }
+            let _ = match command {
+                PathCommand::MoveTo(point) => write!(data, "M {} {}", point.x, point.y),
+                PathCommand::LineTo(point) => write!(data, "L {} {}", point.x, point.y),
+                PathCommand::QuadTo(control, end) => write!(data, "Q {} {} {} {}", control.x, control.y, end.x, end.y),
+                PathCommand::CubicTo(control1, control2, end) => {
+                    write!(data, "C {} {} {} {} {} {}", control1.x, control1.y, control2.x, control2.y, end.x, end.y)
+                }
+                PathCommand::Close => write!(data, "Z"),
+            };
+        }
+        data
+    }
+}
+
+// The smooth curve control point: the previous control point mirrored through the current point,
+// or the current point itself if the previous command was not a curve of the same kind.
+fn reflect(control: Option<Point>, current: Point) -> Point {
+    match control {
+        Some(control) => current * 2.0 - control,
+        None => current,
+    }
+}
+
+impl Polygon {
+    /// Writes the polygon as closed SVG path data. See `Path::to_svg_path_string`.
+    pub fn to_svg_path_string(&self) -> String {
+        Path::polygon(&self.vertices).to_svg_path_string()
+    }
+}
+
+// Appends an SVG elliptical arc from `start` to `end` as cubic Béziers, following the endpoint to
+// center conversion in the SVG implementation notes, including scaling up radii that are too small.
+fn elliptical_arc(path: &mut Path, start: Point, end: Point, radii: Point, rotation: f32, large_arc: bool, sweep: bool) {
+    if start == end {
+        return;
+    }
+    if radii.x == 0.0 || radii.y == 0.0 {
+        path.line_to(end);
+        return;
+    }
+
+    let (sin, cos) = rotation.sin_cos();
+    let half = (start - end) * 0.5;
+    let local = Point::new(cos * half.x + sin * half.y, -sin * half.x + cos * half.y);
+
+    let (mut rx, mut ry) = (radii.x, radii.y);
+    let lambda = (local.x * local.x) / (rx * rx) + (local.y * local.y) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * local.y * local.y - ry * ry * local.x * local.x;
+    let denominator = rx * rx * local.y * local.y + ry * ry * local.x * local.x;
+    let mut factor = (numerator / denominator).max(0.0).sqrt();
+    if large_arc == sweep {
+        factor = -factor;
+    }
+    let local_center = Point::new(factor * rx * local.y / ry, -factor * ry * local.x / rx);
+    let middle = (start + end) * 0.5;
+    let center = Point::new(
+        cos * local_center.x - sin * local_center.y + middle.x,
+        sin * local_center.x + cos * local_center.y + middle.y,
+    );
+
+    let angle = |p: Point| p.y.atan2(p.x);
+    let from = Point::new((local.x - local_center.x) / rx, (local.y - local_center.y) / ry);
+    let to = Point::new((-local.x - local_center.x) / rx, (-local.y - local_center.y) / ry);
+    let start_angle = angle(from);
+    let mut delta = angle(to) - start_angle;
+    if sweep && delta < 0.0 {
+        delta += TAU;
+    } else if !sweep && delta > 0.0 {
+        delta -= TAU;
+    }
+
+    // Map points on the unit circle onto the rotated ellipse
+    let map = |p: Point| {
+        let (x, y) = (p.x * rx, p.y * ry);
+        Point::new(cos * x - sin * y + center.x, sin * x + cos * y + center.y)
+    };
+
+    // Cubics approximate arcs of up to a quarter turn closely
+    let pieces = (delta.abs() / FRAC_PI_2).ceil().max(1.0) as u32;
+    let step = delta / pieces as f32;
+    let handle = 4.0 / 3.0 * (step / 4.0).tan();
+    for i in 0..pieces {
+        let (a1, a2) = (start_angle + step * i as f32, start_angle + step * (i + 1) as f32);
+        let unit = |angle: f32| Point::new(angle.cos(), angle.sin());
+        let tangent = |angle: f32| Point::new(-angle.sin(), angle.cos()) * handle;
+        let last = if i + 1 == pieces { end } else { map(unit(a2)) };
+        path.cubic_to(map(unit(a1) + tangent(a1)), map(unit(a2) - tangent(a2)), last);
+    }
+}
+
+// A cursor over the path data.
+struct Parser<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn at_end(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.position).copied()
+    }
+
+    fn error(&self, kind: SvgPathErrorKind) -> SvgPathError {
+        SvgPathError { position: self.position, kind }
+    }
+
+    // Skips whitespace and at most one comma.
+    fn skip_separators(&mut self) {
+        self.skip_whitespace();
+        if self.peek() == Some(b',') {
+            self.position += 1;
+            self.skip_whitespace();
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    // Consumes a command letter, or returns None if the next token is not a letter.
+    fn command(&mut self) -> Result<Option<u8>, SvgPathError> {
+        match self.peek() {
+            Some(c) if b"MmLlHhVvCcSsQqTtAaZz".contains(&c) => {
+                self.position += 1;
+                Ok(Some(c))
+            }
+            Some(c) if c.is_ascii_alphabetic() && c != b'e' && c != b'E' => {
+                Err(self.error(SvgPathErrorKind::UnknownCommand(c as char)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn number(&mut self) -> Result<f32, SvgPathError> {
+        self.skip_separators();
+        let start = self.position;
+        let digits = |parser: &mut Self| {
+            let from = parser.position;
+            while parser.peek().is_some_and(|c| c.is_ascii_digit()) {
+                parser.position += 1;
+            }
+            parser.position > from
+        };
+
+        if matches!(self.peek(), Some(b'+' | b'-')) {
+            self.position += 1;
+        }
+        let mut has_digits = digits(self);
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            has_digits |= digits(self);
+        }
+        if !has_digits {
+            self.position = start;
+            return Err(self.error(SvgPathErrorKind::ExpectedNumber));
+        }
+        // Only treat an `e` as an exponent if digits follow, so that it can't swallow a command
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            let mantissa_end = self.position;
+            self.position += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.position += 1;
+            }
+            if !digits(self) {
+                self.position = mantissa_end;
+            }
+        }
+
+        let text = std::str::from_utf8(&self.data[start..self.position]).expect("numbers are ASCII");
+        text.parse().map_err(|_| SvgPathError { position: start, kind: SvgPathErrorKind::ExpectedNumber })
+    }
+
+    fn point(&mut self) -> Result<Point, SvgPathError> {
+        Ok(Point::new(self.number()?, self.number()?))
+    }
+
+    // Arc flags are single characters and may be written without separators, as in `a5 5 0 01 10 0`.
+    fn flag(&mut self) -> Result<bool, SvgPathError> {
+        self.skip_separators();
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(self.error(SvgPathErrorKind::ExpectedFlag)),
+        };
+        self.position += 1;
+        Ok(flag)
+    }
+}
@@ -0,0 +1,169 @@
+//! This module provides a 2D affine transform, building upon the `Point` struct.
+
+use std::ops::Mul;
+
+use crate::geom::Point;
+
+/// Represents a 2D affine transform as the matrix
+///
+/// ```text
+/// | m11 m12 tx |
+/// | m21 m22 ty |
+/// |  0   0   1 |
+/// ```
+///
+/// Points are treated as column vectors, so `transform_point` computes `M * p`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transform {
+    pub m11: f32,
+    pub m12: f32,
+    pub m21: f32,
+    pub m22: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl Transform {
+    /// Creates the identity transform, which leaves points unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Transform};
+    /// let t = Transform::identity();
+    /// assert_eq!(t.transform_point(Point::new(3.0, 4.0)), Point::new(3.0, 4.0));
+    /// ```
+    pub fn identity() -> Self {
+        Transform { m11: 1.0, m12: 0.0, m21: 0.0, m22: 1.0, tx: 0.0, ty: 0.0 }
+    }
+
+    /// Creates a transform that translates points by `offset`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Transform};
+    /// let t = Transform::translate(Point::new(1.0, 2.0));
+    /// assert_eq!(t.transform_point(Point::new(3.0, 4.0)), Point::new(4.0, 6.0));
+    /// ```
+    pub fn translate(offset: Point) -> Self {
+        Transform { tx: offset.x, ty: offset.y, ..Transform::identity() }
+    }
+
+    /// Creates a transform that rotates points by `angle` radians around the origin.
+    /// With the y-axis pointing down, positive angles rotate clockwise on screen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Transform};
+    /// let t = Transform::rotate(std::f32::consts::FRAC_PI_2);
+    /// let p = t.transform_point(Point::new(1.0, 0.0));
+    /// assert!((p - Point::new(0.0, 1.0)).length() < 1e-6);
+    /// ```
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Transform { m11: cos, m12: -sin, m21: sin, m22: cos, tx: 0.0, ty: 0.0 }
+    }
+
+    /// Creates a transform that scales points by `sx` and `sy` around the origin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Transform};
+    /// let t = Transform::scale(2.0, 3.0);
+    /// assert_eq!(t.transform_point(Point::new(1.0, 1.0)), Point::new(2.0, 3.0));
+    /// ```
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Transform { m11: sx, m22: sy, ..Transform::identity() }
+    }
+
+    /// Returns a transform that applies `self` first and then `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Transform};
+    /// let t = Transform::scale(2.0, 2.0).then(&Transform::translate(Point::new(1.0, 0.0)));
+    /// assert_eq!(t.transform_point(Point::new(1.0, 1.0)), Point::new(3.0, 2.0));
+    /// ```
+    pub fn then(&self, other: &Transform) -> Transform {
+        *other * *self
+    }
+
+    /// Applies the transform to a point.
+    pub fn transform_point(&self, point: Point) -> Point {
+        Point::new(
+            self.m11 * point.x + self.m12 * point.y + self.tx,
+            self.m21 * point.x + self.m22 * point.y + self.ty,
+        )
+    }
+
+    /// Applies the transform to a direction vector, ignoring the translation.
+    pub fn transform_vector(&self, vector: Point) -> Point {
+        Point::new(
+            self.m11 * vector.x + self.m12 * vector.y,
+            self.m21 * vector.x + self.m22 * vector.y,
+        )
+    }
+
+    /// Calculates the determinant of the linear part of the transform.
+    pub fn determinant(&self) -> f32 {
+        self.m11 * self.m22 - self.m12 * self.m21
+    }
+
+    /// Calculates the inverse transform.
+    /// Returns None if the transform is not invertible (e.g. scaled by zero).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Transform};
+    /// let t = Transform::translate(Point::new(1.0, 2.0));
+    /// let inverse = t.inverse().unwrap();
+    /// assert_eq!(inverse.transform_point(Point::new(1.0, 2.0)), Point::new(0.0, 0.0));
+    /// ```
+    pub fn inverse(&self) -> Option<Transform> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let inv = 1.0 / det;
+        let m11 = self.m22 * inv;
+        let m12 = -self.m12 * inv;
+        let m21 = -self.m21 * inv;
+        let m22 = self.m11 * inv;
+        Some(Transform {
+            m11,
+            m12,
+            m21,
+            m22,
+            tx: -(m11 * self.tx + m12 * self.ty),
+            ty: -(m21 * self.tx + m22 * self.ty),
+        })
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+/// Matrix product: `a * b` applies `b` first and then `a`.
+impl Mul for Transform {
+    type Output = Transform;
+
+    fn mul(self, other: Transform) -> Transform {
+        Transform {
+            m11: self.m11 * other.m11 + self.m12 * other.m21,
+            m12: self.m11 * other.m12 + self.m12 * other.m22,
+            m21: self.m21 * other.m11 + self.m22 * other.m21,
+            m22: self.m21 * other.m12 + self.m22 * other.m22,
+            tx: self.m11 * other.tx + self.m12 * other.ty + self.tx,
+            ty: self.m21 * other.tx + self.m22 * other.ty + self.ty,
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Glitch effects that mangle the pixels of a buffer the way broken codecs and cables do:
+//! pixel sorting, channel shifting, block displacement and scanline corruption. They work on
+//! whole rows of pixels in place, and are cheap enough to run on every frame.
+//!
+//! The random effects take a seed, and mangle the same buffer the same way for the same
+//! seed, so a glitch can hold still for a few frames and then jump by changing the seed.
+//!
+//! # Examples
+//!
+//! ```
+//! # use simple_sketch::glitch;
+//! # use simple_sketch::app::Context;
+//! # use simple_sketch::canvas::Canvas;
+//! # fn draw(canvas: &mut Canvas, ctx: &Context) {
+//! // In draw, after drawing: a new glitch every eight frames
+//! let seed = (ctx.frame_count() / 8) as u32;
+//! glitch::shift_channels(&mut canvas.pixel_buffer, (6, 0), (0, 0), (-6, 0));
+//! glitch::corrupt_scanlines(&mut canvas.pixel_buffer, 0.05, 40, seed);
+//! # }
+//! ```
+
+use std::ops::Range;
+
+use crate::color::Color;
+use crate::pixelbuffer::PixelBuffer;
+
+/// The value of a pixel that `pixel_sort` sorts by, from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// The average of red, green and blue.
+    Brightness,
+    /// The angle of the color on the color wheel, starting from red.
+    Hue,
+    /// How far the color is from gray.
+    Saturation,
+    Red,
+    Green,
+    Blue,
+}
+
+impl SortKey {
+    fn value(self, pixel: u32) -> f32 {
+        let color = Color(pixel);
+        let [r, g, b] = [color.r(), color.g(), color.b()].map(|channel| channel as f32 / 255.0);
+        let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+        match self {
+            SortKey::Brightness => (r + g + b) / 3.0,
+            SortKey::Hue if max == min => 0.0,
+            SortKey::Hue => {
+                let hue = if max == r {
+                    (g - b) / (max - min)
+                } else if max == g {
+                    2.0 + (b - r) / (max - min)
+                } else {
+                    4.0 + (r - g) / (max - min)
+                };
+                (hue / 6.0).rem_euclid(1.0)
+            }
+            SortKey::Saturation if max == 0.0 => 0.0,
+            SortKey::Saturation => (max - min) / max,
+            SortKey::Red => r,
+            SortKey::Green => g,
+            SortKey::Blue => b,
+        }
+    }
+}
+
+/// Which way `pixel_sort` sorts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    /// Along each row, from dark to light left to right for brightness.
+    Horizontal,
+    /// Along each column, from top to bottom.
+    Vertical,
+}
+
+/// Sorts the pixels in every run of neighbors whose key lies in `range`, along rows or
+/// columns, leaving the pixels outside the range in place, like Kim Asendorf's pixel sorting.
+/// A range covering the middle tones sorts them into streaks between the highlights and
+/// shadows.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::glitch;
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::glitch::{SortDirection, SortKey};
+/// # fn draw(canvas: &mut Canvas) {
+/// glitch::pixel_sort(&mut canvas.pixel_buffer, SortKey::Brightness, 0.25..0.8, SortDirection::Vertical);
+/// # }
+/// ```
+pub fn pixel_sort(buffer: &mut PixelBuffer, key: SortKey, range: Range<f32>, direction: SortDirection) {
+    let (width, height) = (buffer.width, buffer.height);
+    let pixels = buffer.get_buffer_mut();
+    let (lines, length) = match direction {
+        SortDirection::Horizontal => (height, width),
+        SortDirection::Vertical => (width, height),
+    };
+    let index = |line: usize, i: usize| match direction {
+        SortDirection::Horizontal => line * width + i,
+        SortDirection::Vertical => i * width + line,
+    };
+    let mut run: Vec<(f32, u32)> = Vec::with_capacity(length);
+    for line in 0..lines {
+        let mut i = 0;
+        while i < length {
+            let value = key.value(pixels[index(line, i)]);
+            if !range.contains(&value) {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            run.clear();
+            while i < length {
+                let pixel = pixels[index(line, i)];
+                let value = key.value(pixel);
+                if !range.contains(&value) {
+                    break;
+                }
+                run.push((value, pixel));
+                i += 1;
+            }
+            run.sort_by(|a, b| a.0.total_cmp(&b.0));
+            for (offset, &(_, pixel)) in run.iter().enumerate() {
+                pixels[index(line, start + offset)] = pixel;
+            }
+        }
+    }
+}
+
+/// Moves the red, green and blue channels by their own offsets in pixels, as `(x, y)`,
+/// wrapping around the edges, so the colors split apart along edges like a misaligned
+/// signal. Alpha stays in place.
+pub fn shift_channels(buffer: &mut PixelBuffer, red: (isize, isize), green: (isize, isize), blue: (isize, isize)) {
+    let (width, height) = (buffer.width as isize, buffer.height as isize);
+    if width == 0 || height == 0 {
+        return;
+    }
+    let source = buffer.get_buffer().to_vec();
+    let at = |x: isize, y: isize, (dx, dy): (isize, isize)| Color(source[((y - dy).rem_euclid(height) * width + (x - dx).rem_euclid(width)) as usize]);
+    for (i, pixel) in buffer.get_buffer_mut().iter_mut().enumerate() {
+        let (x, y) = (i as isize % width, i as isize / width);
+        *pixel = Color::new(at(x, y, red).r(), at(x, y, green).g(), at(x, y, blue).b(), Color(*pixel).a()).0;
+    }
+}
+
+/// Copies `count` rectangular blocks, each up to `max_size` pixels wide and high, to places
+/// up to `max_offset` pixels away, like the blocks of a damaged video frame.
+pub fn displace_blocks(buffer: &mut PixelBuffer, count: usize, max_size: usize, max_offset: usize, seed: u32) {
+    let (width, height) = (buffer.width, buffer.height);
+    if width == 0 || height == 0 || max_size == 0 {
+        return;
+    }
+    let pixels = buffer.get_buffer_mut();
+    let mut row = Vec::with_capacity(max_size);
+    for block in 0..count as u32 {
+        let random = |i: u32| random(block, i, seed);
+        let block_width = 1 + (random(0) * max_size as f32) as usize;
+        let block_height = 1 + (random(1) * max_size as f32) as usize;
+        let x = (random(2) * width as f32) as isize;
+        let y = (random(3) * height as f32) as isize;
+        let dx = ((random(4) * 2.0 - 1.0) * max_offset as f32) as isize;
+        let dy = ((random(5) * 2.0 - 1.0) * max_offset as f32) as isize;
+        // Clip the columns to where both the block and where it goes are in the buffer
+        let left = x.max(-dx).max(0);
+        let right = (x + block_width as isize).min(width as isize - dx).min(width as isize);
+        if right <= left {
+            continue;
+        }
+        let columns = (right - left) as usize;
+        for source_y in y..(y + block_height as isize) {
+            let target_y = source_y + dy;
+            if !(0..height as isize).contains(&source_y) || !(0..height as isize).contains(&target_y) {
+                continue;
+            }
+            let source = source_y as usize * width + left as usize;
+            row.clear();
+            row.extend_from_slice(&pixels[source..source + columns]);
+            let target = target_y as usize * width + (left + dx) as usize;
+            pixels[target..target + columns].copy_from_slice(&row);
+        }
+    }
+}
+
+/// Shifts rows sideways by up to `max_shift` pixels, wrapping around, and repeats some rows
+/// over the next ones, like a bad analog signal. Each row is corrupted with the given
+/// `probability`, from 0 to 1, and corrupted rows come in short bursts.
+pub fn corrupt_scanlines(buffer: &mut PixelBuffer, probability: f32, max_shift: usize, seed: u32) {
+    let (width, height) = (buffer.width, buffer.height);
+    if width == 0 {
+        return;
+    }
+    let pixels = buffer.get_buffer_mut();
+    let mut y = 0;
+    while y < height {
+        if random(y as u32, 0, seed) >= probability {
+            y += 1;
+            continue;
+        }
+        let burst = 1 + (random(y as u32, 1, seed) * 8.0) as usize;
+        let shift = ((random(y as u32, 2, seed) * 2.0 - 1.0) * max_shift as f32) as isize;
+        let repeat = random(y as u32, 3, seed) < 0.3;
+        let first = y;
+        for row in y..(y + burst).min(height) {
+            if repeat && row > first {
+                // Smear the first row of the burst down
+                let (before, after) = pixels.split_at_mut(row * width);
+                after[..width].copy_from_slice(&before[first * width..(first + 1) * width]);
+            } else {
+                pixels[row * width..(row + 1) * width].rotate_right(shift.rem_euclid(width as isize) as usize);
+            }
+        }
+        y += burst;
+    }
+}
+
+// A pseudo-random number in [0, 1) for a pair of integers and a seed
+fn random(a: u32, b: u32, seed: u32) -> f32 {
+    let mut h = a.wrapping_mul(0x9E37_79B9) ^ b.wrapping_mul(0xC2B2_AE35) ^ seed.wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846C_A68B);
+    h ^= h >> 16;
+    (h >> 8) as f32 / (1 << 24) as f32
+}
@@ -0,0 +1,322 @@
+//! A minimal immediate-mode GUI for tweaking a sketch's parameters while it runs.
+//!
+//! The sketch keeps a `Gui` and, every frame, lays out its widgets on a `Panel` in `draw`,
+//! passing each one the name and a mutable reference to the parameter it controls. Widgets are
+//! drawn on the canvas right away with the debug font, and react to the mouse of the context:
+//!
+//! ```
+//! # use simple_sketch::app::Context;
+//! # use simple_sketch::canvas::Canvas;
+//! # use simple_sketch::color::Color;
+//! # use simple_sketch::geom::Point;
+//! # use simple_sketch::gui::Gui;
+//! # struct Sketch { gui: Gui, fill: Color, radius: f32, trails: bool }
+//! # impl Sketch {
+//! fn draw(&mut self, canvas: &mut Canvas, ctx: &Context) {
+//!     canvas.background(Color::new(0, 0, 0, 255));
+//!     canvas.set_fill(Some(self.fill));
+//!     canvas.circle(Point::new(400.0, 300.0), self.radius * 2.0);
+//!
+//!     let mut panel = self.gui.panel(canvas, ctx);
+//!     panel.slider("radius", &mut self.radius, 1.0..=200.0);
+//!     panel.checkbox("trails", &mut self.trails);
+//!     panel.color("fill", &mut self.fill);
+//!     if panel.button("reset") {
+//!         self.radius = 50.0;
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! Draw the panel last, so that it stays on top of the sketch, and check `Gui::wants_mouse`
+//! before reacting to clicks meant for the sketch.
+
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+use crate::app::Context;
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::command::Style;
+use crate::geom::{Point, Transform};
+use crate::input::MouseButton;
+
+/// Height in pixels of a row of the panel.
+const ROW_HEIGHT: f32 = 16.0;
+
+/// Distance in pixels between the rows of the panel.
+const ROW_GAP: f32 = 2.0;
+
+/// Distance in pixels between a row's edge and its text.
+const TEXT_INSET: f32 = 4.0;
+
+/// Height in pixels of a line of the debug font.
+const TEXT_HEIGHT: f32 = 8.0;
+
+/// Distance in pixels the channel sliders of an open color widget are indented by.
+const INDENT: f32 = 12.0;
+
+const BACKGROUND: Color = Color(0xE0202020);
+const HOVERED: Color = Color(0xE0383838);
+const ACCENT: Color = Color(0xFF3C78B4);
+const TEXT: Color = Color(0xFFFFFFFF);
+
+/// The state of a parameter panel that lasts from frame to frame: where it is, which widget
+/// is being dragged and which color widgets are open.
+#[derive(Debug, Clone)]
+pub struct Gui {
+    position: Point,
+    width: f32,
+    visible: bool,
+    /// The name of the widget the held mouse button was pressed on.
+    active: Option<String>,
+    /// The names of the color widgets showing their channel sliders.
+    open: HashSet<String>,
+    /// Whether the left mouse button was held at the last panel.
+    was_pressed: bool,
+    /// Whether the mouse was over a widget at the last panel.
+    hovered: bool,
+}
+
+impl Gui {
+    /// A panel 200 pixels wide with its top left corner at `position`, in canvas pixels.
+    pub fn new(position: Point) -> Self {
+        Gui {
+            position,
+            width: 200.0,
+            visible: true,
+            active: None,
+            open: HashSet::new(),
+            was_pressed: false,
+            hovered: false,
+        }
+    }
+
+    /// Sets the width of the panel in pixels.
+    pub fn set_width(&mut self, width: f32) {
+        self.width = width;
+    }
+
+    /// Shows or hides the panel. Hidden widgets aren't drawn and don't change their parameters.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Whether the mouse was over a widget of the last panel, or is dragging one, so that the
+    /// sketch can ignore clicks on the panel.
+    pub fn wants_mouse(&self) -> bool {
+        self.visible && (self.hovered || self.active.is_some())
+    }
+
+    /// Starts laying out this frame's widgets on `canvas`, from the top of the panel down,
+    /// driven by the mouse of `ctx`.
+    pub fn panel<'a>(&'a mut self, canvas: &'a mut Canvas, ctx: &Context) -> Panel<'a> {
+        let mouse = ctx.mouse();
+        let pressed = mouse.is_pressed(MouseButton::Left);
+        let just_pressed = pressed && !self.was_pressed;
+        self.was_pressed = pressed;
+        if !pressed {
+            self.active = None;
+        }
+        self.hovered = false;
+        let top = self.position.y;
+        Panel { gui: self, canvas, mouse: mouse.position(), just_pressed, top, indent: 0.0 }
+    }
+}
+
+/// The widgets of one frame, laid out from the top of the panel down. Every widget takes one
+/// row, and returns whether it changed its parameter or was clicked.
+pub struct Panel<'a> {
+    gui: &'a mut Gui,
+    canvas: &'a mut Canvas,
+    mouse: Point,
+    /// Whether the left mouse button went down since the last frame.
+    just_pressed: bool,
+    /// The top of the next row.
+    top: f32,
+    indent: f32,
+}
+
+impl Panel<'_> {
+    /// A slider setting `value` within `range`, by pressing or dragging along it.
+    pub fn slider(&mut self, name: &str, value: &mut f32, range: RangeInclusive<f32>) -> bool {
+        let Some(row) = self.row(name) else {
+            return false;
+        };
+        let (min, max) = (*range.start(), *range.end());
+        let mut changed = false;
+        if row.active && max > min {
+            let t = ((self.mouse.x - row.left) / row.width).clamp(0.0, 1.0);
+            let new_value = min + t * (max - min);
+            changed = new_value != *value;
+            *value = new_value;
+        }
+        let t = if max > min { ((*value - min) / (max - min)).clamp(0.0, 1.0) } else { 0.0 };
+        self.draw_row(&row, |canvas| {
+            fill_rect(canvas, Point::new(row.left, row.top), row.width * t, ROW_HEIGHT, ACCENT);
+        });
+        self.draw_text(&row, &format!("{name} {}", format_value(*value)));
+        changed
+    }
+
+    /// A checkbox toggling `value` when clicked.
+    pub fn checkbox(&mut self, name: &str, value: &mut bool) -> bool {
+        let Some(row) = self.row(name) else {
+            return false;
+        };
+        let clicked = row.clicked;
+        if clicked {
+            *value = !*value;
+        }
+        let checked = *value;
+        self.draw_row(&row, |canvas| {
+            let size = ROW_HEIGHT - 2.0 * TEXT_INSET;
+            let corner = Point::new(row.left + row.width - TEXT_INSET - size, row.top + TEXT_INSET);
+            fill_rect(canvas, corner, size, size, if checked { ACCENT } else { TEXT.with_alpha(64) });
+        });
+        self.draw_text(&row, name);
+        clicked
+    }
+
+    /// A button, returning true in the frame it is clicked.
+    pub fn button(&mut self, name: &str) -> bool {
+        let Some(row) = self.row(name) else {
+            return false;
+        };
+        let clicked = row.clicked;
+        self.draw_row(&row, |canvas| {
+            if row.active {
+                fill_rect(canvas, Point::new(row.left, row.top), row.width, ROW_HEIGHT, ACCENT);
+            }
+        });
+        self.draw_text(&row, &format!("[{name}]"));
+        clicked
+    }
+
+    /// A swatch of `color` that opens and closes sliders for its red, green, blue and alpha
+    /// channels when clicked.
+    pub fn color(&mut self, name: &str, color: &mut Color) -> bool {
+        let Some(row) = self.row(name) else {
+            return false;
+        };
+        let open = self.gui.open.contains(name);
+        if row.clicked {
+            if open {
+                self.gui.open.remove(name);
+            } else {
+                self.gui.open.insert(name.to_string());
+            }
+        }
+        let swatch = *color;
+        self.draw_row(&row, |canvas| {
+            let size = ROW_HEIGHT - 2.0 * TEXT_INSET;
+            let corner = Point::new(row.left + row.width - TEXT_INSET - 3.0 * size, row.top + TEXT_INSET);
+            fill_rect(canvas, corner, 3.0 * size, size, swatch);
+        });
+        self.draw_text(&row, name);
+        if !open {
+            return false;
+        }
+
+        let mut channels = [color.r(), color.g(), color.b(), color.a()].map(f32::from);
+        let mut changed = false;
+        self.indent += INDENT;
+        for (channel, label) in channels.iter_mut().zip(["r", "g", "b", "a"]) {
+            changed |= self.channel_slider(&format!("{name}.{label}"), label, channel);
+        }
+        self.indent -= INDENT;
+        if changed {
+            let [r, g, b, a] = channels.map(|channel| channel.round() as u8);
+            *color = Color::new(r, g, b, a);
+        }
+        changed
+    }
+
+    // A slider from 0 to 255 for a channel of a color widget, identified by `id` and labeled
+    // `label`.
+    fn channel_slider(&mut self, id: &str, label: &str, value: &mut f32) -> bool {
+        let before = value.round();
+        let Some(row) = self.row(id) else {
+            return false;
+        };
+        if row.active {
+            *value = ((self.mouse.x - row.left) / row.width).clamp(0.0, 1.0) * 255.0;
+        }
+        let t = *value / 255.0;
+        self.draw_row(&row, |canvas| {
+            fill_rect(canvas, Point::new(row.left, row.top), row.width * t, ROW_HEIGHT, ACCENT);
+        });
+        self.draw_text(&row, &format!("{label} {}", value.round()));
+        value.round() != before
+    }
+
+    // Takes the next row for the widget `id`, and works out how the mouse interacts with it.
+    // None if the panel is hidden.
+    fn row(&mut self, id: &str) -> Option<Row> {
+        if !self.gui.visible {
+            return None;
+        }
+        let left = self.gui.position.x + self.indent;
+        let row = Row { left, top: self.top, width: self.gui.width - self.indent, hovered: false, active: false, clicked: false };
+        self.top += ROW_HEIGHT + ROW_GAP;
+
+        let hovered = (row.left..row.left + row.width).contains(&self.mouse.x) && (row.top..row.top + ROW_HEIGHT).contains(&self.mouse.y);
+        self.gui.hovered |= hovered;
+        let clicked = hovered && self.just_pressed && self.gui.active.is_none();
+        if clicked {
+            self.gui.active = Some(id.to_string());
+        }
+        let active = self.gui.active.as_deref() == Some(id);
+        Some(Row { hovered, active, clicked, ..row })
+    }
+
+    // Draws a row's background, then `draw` over it, in canvas pixels with the canvas style
+    // restored afterwards.
+    fn draw_row(&mut self, row: &Row, draw: impl FnOnce(&mut Canvas)) {
+        self.canvas.push();
+        self.canvas.set_transform(Transform::identity());
+        let background = if row.hovered || row.active { HOVERED } else { BACKGROUND };
+        fill_rect(self.canvas, Point::new(row.left, row.top), row.width, ROW_HEIGHT, background);
+        draw(self.canvas);
+        self.canvas.pop();
+    }
+
+    fn draw_text(&mut self, row: &Row, text: &str) {
+        self.canvas.push();
+        self.canvas.set_style(Style { fill: Some(TEXT), stroke: None, stroke_weight: 1.0 });
+        let position = Point::new(row.left + TEXT_INSET, row.top + (ROW_HEIGHT - TEXT_HEIGHT) / 2.0);
+        self.canvas.debug_text(text, position);
+        self.canvas.pop();
+    }
+}
+
+// Where a widget's row is and how the mouse interacts with it this frame.
+struct Row {
+    left: f32,
+    top: f32,
+    width: f32,
+    hovered: bool,
+    /// Whether the mouse button was pressed on the row and is still held.
+    active: bool,
+    /// Whether the mouse button was pressed on the row this frame.
+    clicked: bool,
+}
+
+// Fills a rectangle without a stroke, in the current transform.
+fn fill_rect(canvas: &mut Canvas, top_left: Point, width: f32, height: f32, color: Color) {
+    canvas.set_style(Style { fill: Some(color), stroke: None, stroke_weight: 1.0 });
+    canvas.rectangle(top_left, width, height);
+}
+
+// Formats a slider value with fewer decimals the larger it is.
+fn format_value(value: f32) -> String {
+    match value.abs() {
+        magnitude if magnitude >= 100.0 => format!("{value:.1}"),
+        magnitude if magnitude >= 1.0 => format!("{value:.2}"),
+        _ => format!("{value:.3}"),
+    }
+}
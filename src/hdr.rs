@@ -0,0 +1,180 @@
+//! Floating point pixel buffers for accumulating many frames or exposures without banding,
+//! saved as OpenEXR or 16 bit PNG files for grading in external tools.
+
+use std::io::Write;
+use std::path::Path;
+
+use crate::error::SketchError;
+use crate::pixelbuffer::{self, PixelBuffer};
+use crate::png::{self, PngColor};
+
+/// A pixel buffer with an RGBA `f32` value per pixel, in which channels can go past 1.0.
+///
+/// Values are the channels of canvas colors divided by 255, so adding a frame of the canvas
+/// adds values from 0.0 to 1.0. No color space conversion is done: EXR files hold the values
+/// as they are, and tools that expect linear light treat them as such.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::error::SketchError;
+/// # use simple_sketch::hdr::HdrBuffer;
+/// # fn draw(_: &mut Canvas, _: i32) {}
+/// # fn render(mut canvas: Canvas) -> Result<(), SketchError> {
+/// // Long exposure: the average of 100 frames
+/// let mut exposure = HdrBuffer::new(canvas.pixel_buffer.width, canvas.pixel_buffer.height);
+/// for frame in 0..100 {
+///     draw(&mut canvas, frame);
+///     exposure.accumulate(&canvas.pixel_buffer, 0.01)?;
+/// }
+/// exposure.save_exr("exposure.exr")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct HdrBuffer {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl HdrBuffer {
+    /// Creates a buffer of `width` by `height` pixels, all zero.
+    pub fn new(width: usize, height: usize) -> Self {
+        HdrBuffer { width, height, pixels: vec![[0.0; 4]; width * height] }
+    }
+
+    /// The RGBA values of all pixels, row by row from the top left.
+    pub fn pixels(&self) -> &[[f32; 4]] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [[f32; 4]] {
+        &mut self.pixels
+    }
+
+    /// The RGBA value of the pixel at `x`, `y`, or None outside the buffer.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<[f32; 4]> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    /// Sets the pixel at `x`, `y` to an RGBA value. Pixels outside the buffer are ignored.
+    pub fn set_pixel(&mut self, x: usize, y: usize, value: [f32; 4]) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = value;
+        }
+    }
+
+    /// Adds an RGBA value to the pixel at `x`, `y`. Pixels outside the buffer are ignored.
+    pub fn add_pixel(&mut self, x: usize, y: usize, value: [f32; 4]) {
+        if x < self.width && y < self.height {
+            let pixel = &mut self.pixels[y * self.width + x];
+            for (channel, value) in pixel.iter_mut().zip(value) {
+                *channel += value;
+            }
+        }
+    }
+
+    /// Adds every pixel of `frame`, a pixel buffer of the same size, multiplied by `weight`.
+    /// Weights that add up to 1.0 over all frames average them.
+    pub fn accumulate(&mut self, frame: &PixelBuffer, weight: f32) -> Result<(), SketchError> {
+        if frame.width != self.width || frame.height != self.height {
+            return Err(SketchError::InvalidSize { width: frame.width, height: frame.height });
+        }
+        let scale = weight / 255.0;
+        for (pixel, &color) in self.pixels.iter_mut().zip(frame.get_buffer()) {
+            let [a, r, g, b] = color.to_be_bytes();
+            for (channel, value) in pixel.iter_mut().zip([r, g, b, a]) {
+                *channel += value as f32 * scale;
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts the buffer to 8 bit colors, after multiplying every channel but alpha by
+    /// `exposure`. Values outside 0.0 to 1.0 are clamped.
+    pub fn to_pixel_buffer(&self, exposure: f32) -> PixelBuffer {
+        let mut result = PixelBuffer::new(self.width, self.height);
+        let byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        for (color, &[r, g, b, a]) in result.get_buffer_mut().iter_mut().zip(&self.pixels) {
+            *color = u32::from_be_bytes([byte(a), byte(r * exposure), byte(g * exposure), byte(b * exposure)]);
+        }
+        result
+    }
+
+    /// Saves the buffer as an uncompressed OpenEXR file with 32 bit float RGBA channels,
+    /// keeping values above 1.0. Creates missing directories.
+    pub fn save_exr(&self, path: impl AsRef<Path>) -> Result<(), SketchError> {
+        let (Ok(width), Ok(height)) = (i32::try_from(self.width), i32::try_from(self.height)) else {
+            return Err(SketchError::InvalidSize { width: self.width, height: self.height });
+        };
+        if width == 0 || height == 0 {
+            return Err(SketchError::InvalidSize { width: self.width, height: self.height });
+        }
+        pixelbuffer::save_file(path.as_ref(), |mut out| {
+            let mut header = Vec::new();
+            // Magic number, then version 2 for single-part scanline files
+            header.extend_from_slice(&[0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0]);
+            // Channels are listed alphabetically and stored in that order
+            let mut channels = Vec::new();
+            for name in [b'A', b'B', b'G', b'R'] {
+                channels.extend_from_slice(&[name, 0]);
+                // FLOAT pixels, not linear, reserved bytes, then x and y sampling
+                channels.extend_from_slice(&2i32.to_le_bytes());
+                channels.extend_from_slice(&[0; 4]);
+                channels.extend_from_slice(&1i32.to_le_bytes());
+                channels.extend_from_slice(&1i32.to_le_bytes());
+            }
+            channels.push(0);
+            let window: Vec<u8> = [0, 0, width - 1, height - 1].iter().flat_map(|value| value.to_le_bytes()).collect();
+            let mut attribute = |name: &str, kind: &str, value: &[u8]| {
+                header.extend_from_slice(name.as_bytes());
+                header.push(0);
+                header.extend_from_slice(kind.as_bytes());
+                header.push(0);
+                header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+                header.extend_from_slice(value);
+            };
+            attribute("channels", "chlist", &channels);
+            // No compression
+            attribute("compression", "compression", &[0]);
+            attribute("dataWindow", "box2i", &window);
+            attribute("displayWindow", "box2i", &window);
+            // Increasing y
+            attribute("lineOrder", "lineOrder", &[0]);
+            attribute("pixelAspectRatio", "float", &1f32.to_le_bytes());
+            attribute("screenWindowCenter", "v2f", &[0; 8]);
+            attribute("screenWindowWidth", "float", &1f32.to_le_bytes());
+            header.push(0);
+            out.write_all(&header)?;
+
+            // Every scanline is its own chunk: its y and size, then each channel's values
+            let line_size = self.width * 4 * 4;
+            let chunk_size = (4 + 4 + line_size) as u64;
+            let first_chunk = (header.len() + self.height * 8) as u64;
+            for y in 0..self.height as u64 {
+                out.write_all(&(first_chunk + y * chunk_size).to_le_bytes())?;
+            }
+            for (y, row) in self.pixels.chunks(self.width).enumerate() {
+                out.write_all(&(y as i32).to_le_bytes())?;
+                out.write_all(&(line_size as i32).to_le_bytes())?;
+                for channel in [3, 2, 1, 0] {
+                    for pixel in row {
+                        out.write_all(&pixel[channel].to_le_bytes())?;
+                    }
+                }
+            }
+            out.flush()
+        })
+    }
+
+    /// Saves the buffer as a PNG file with 16 bit RGBA channels, for tools that don't read
+    /// EXR. Values are clamped to 0.0 to 1.0, but keep 256 times the precision of an 8 bit
+    /// PNG. Creates missing directories.
+    pub fn save_png_16(&self, path: impl AsRef<Path>) -> Result<(), SketchError> {
+        let sample = |value: f32| ((value.clamp(0.0, 1.0) * 65535.0).round() as u16).to_be_bytes();
+        let pixels: Vec<u8> = self.pixels.iter().flat_map(|pixel| pixel.map(sample)).flatten().collect();
+        pixelbuffer::save_file(path.as_ref(), |out| png::write_png(out, self.width, self.height, PngColor::Rgba16, &pixels))
+    }
+}
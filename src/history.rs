@@ -0,0 +1,163 @@
+//! Undo and redo for interactive drawing, such as paint tools.
+//!
+//! A `History` holds a drawing as a `Scene` of styled commands, grouped into edits: everything
+//! pushed until `end_edit`, like the segments of one brush stroke. `undo` takes back the last
+//! edit and `redo` puts it back, until a new edit is made. The sketch's history is at
+//! `Context::history`, which Ctrl+Z and Ctrl+Y step through unless
+//! `ApplicationRunner::set_undo_keys` turns them off:
+//!
+//! ```
+//! # use simple_sketch::app::Context;
+//! # use simple_sketch::canvas::Canvas;
+//! # use simple_sketch::color::Color;
+//! # use simple_sketch::command::{DrawCommand, Style};
+//! # use simple_sketch::input::MouseButton;
+//! # struct Sketch { brush: Style }
+//! # impl Sketch {
+//! fn update(&mut self, ctx: &Context, _dt: f32) {
+//!     let mouse = ctx.mouse();
+//!     if mouse.is_pressed(MouseButton::Left) {
+//!         ctx.history().push(DrawCommand::Line { start: mouse.previous_position(), end: mouse.position() }, self.brush);
+//!     } else {
+//!         ctx.history().end_edit();
+//!     }
+//! }
+//!
+//! fn draw(&mut self, canvas: &mut Canvas, ctx: &Context) {
+//!     canvas.background(Color::new(255, 255, 255, 255));
+//!     canvas.draw_scene(ctx.history().scene());
+//! }
+//! # }
+//! ```
+
+use crate::command::{DrawCommand, RecordedCommand, Style};
+use crate::display_list::DisplayList;
+use crate::scene::Scene;
+
+/// Number of edits that can be undone unless set otherwise.
+const DEFAULT_DEPTH: usize = 100;
+
+/// A drawing made of edits that can be undone and redone.
+#[derive(Debug, Clone)]
+pub struct History {
+    scene: Scene,
+    /// The number of commands of every edit that can be undone, oldest first. The commands
+    /// before them can't be undone anymore.
+    edits: Vec<usize>,
+    /// Whether commands are added to the last edit instead of starting a new one.
+    editing: bool,
+    /// The commands of the undone edits, the last undone last.
+    undone: Vec<Vec<RecordedCommand>>,
+    depth: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new()
+    }
+}
+
+impl History {
+    /// An empty drawing that keeps the last 100 edits.
+    pub fn new() -> Self {
+        History::with_depth(DEFAULT_DEPTH)
+    }
+
+    /// An empty drawing that keeps the last `depth` edits.
+    pub fn with_depth(depth: usize) -> Self {
+        History { scene: Scene::new(), edits: Vec::new(), editing: false, undone: Vec::new(), depth }
+    }
+
+    /// Sets how many edits can be undone. Older edits stay in the drawing but can't be undone
+    /// anymore.
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        self.trim();
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The drawing with every edit that wasn't undone, for `Canvas::draw_scene`.
+    pub fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    /// Adds a command to the drawing, as part of the current edit, which it starts if there is
+    /// none. Drops the edits that could be redone.
+    pub fn push(&mut self, command: DrawCommand, style: Style) {
+        self.extend(vec![RecordedCommand { command, style }]);
+    }
+
+    /// Adds the commands of a display list to the drawing, like `push`.
+    pub fn push_display_list(&mut self, list: &DisplayList) {
+        self.extend(list.commands().to_vec());
+    }
+
+    /// Ends the current edit, so that it is undone as a whole. The next command starts a new
+    /// one. Does nothing if there is no current edit.
+    pub fn end_edit(&mut self) {
+        self.editing = false;
+    }
+
+    /// Takes back the last edit, ending the current one. Returns false if there is none.
+    pub fn undo(&mut self) -> bool {
+        self.end_edit();
+        let Some(count) = self.edits.pop() else {
+            return false;
+        };
+        let start = self.scene.commands.len() - count;
+        self.undone.push(self.scene.commands.split_off(start));
+        true
+    }
+
+    /// Puts back the last undone edit. Returns false if there is none, or a new edit was made
+    /// since.
+    pub fn redo(&mut self) -> bool {
+        self.end_edit();
+        let Some(commands) = self.undone.pop() else {
+            return false;
+        };
+        self.edits.push(commands.len());
+        self.scene.commands.extend(commands);
+        self.trim();
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.edits.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Removes the whole drawing and its history.
+    pub fn clear(&mut self) {
+        self.scene.clear();
+        self.edits.clear();
+        self.undone.clear();
+        self.editing = false;
+    }
+
+    fn extend(&mut self, commands: Vec<RecordedCommand>) {
+        self.undone.clear();
+        match self.edits.last_mut() {
+            Some(count) if self.editing => *count += commands.len(),
+            _ => {
+                self.edits.push(commands.len());
+                self.editing = true;
+            }
+        }
+        self.scene.commands.extend(commands);
+        self.trim();
+    }
+
+    // Forgets the oldest edits beyond the depth, keeping their commands. The current edit
+    // counts too.
+    fn trim(&mut self) {
+        let excess = self.edits.len().saturating_sub(self.depth);
+        self.edits.drain(..excess);
+    }
+}
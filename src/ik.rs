@@ -0,0 +1,185 @@
+//! Inverse kinematics for chains of segments, such as arms reaching for the mouse and
+//! tentacles trailing after it, solved with FABRIK (forward and backward reaching inverse
+//! kinematics).
+
+use std::f32::consts::{PI, TAU};
+
+use crate::geom::Point;
+
+/// A chain of rigid segments joined end to end, from a base to an end effector.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::app::Context;
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::geom::Point;
+/// # use simple_sketch::ik::IkChain;
+/// # use std::f32::consts::PI;
+/// # fn sketch(canvas: &mut Canvas, ctx: &Context) {
+/// // An arm of three segments on the bottom of the window, reaching for the mouse, whose
+/// // elbows bend at most 90 degrees either way
+/// let mut arm = IkChain::new(Point::new(320.0, 360.0), vec![80.0, 60.0, 40.0], -PI / 2.0);
+/// for segment in 1..3 {
+///     arm.set_constraint(segment, Some((-PI / 2.0, PI / 2.0)));
+/// }
+///
+/// // In update
+/// arm.reach(ctx.mouse().position());
+/// // In draw
+/// for joints in arm.joints().windows(2) {
+///     canvas.line(joints[0], joints[1]);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IkChain {
+    /// The joints from the base to the end, one more than there are segments.
+    joints: Vec<Point>,
+    lengths: Vec<f32>,
+    /// The range of angles of each segment relative to the one before, if limited.
+    constraints: Vec<Option<(f32, f32)>>,
+    /// The direction the first segment's angle is measured from, in radians.
+    pub base_angle: f32,
+    /// The most passes `reach` makes to get the end to the target.
+    pub iterations: usize,
+    /// How close to the target the end has to get for `reach` to stop early, in pixels.
+    pub tolerance: f32,
+}
+
+impl IkChain {
+    /// Creates a straight chain of segments with the given lengths, from `base` in the
+    /// direction `angle` in radians.
+    pub fn new(base: Point, lengths: Vec<f32>, angle: f32) -> Self {
+        let direction = Point::new(angle.cos(), angle.sin());
+        let mut joints = vec![base];
+        for length in &lengths {
+            let last = joints[joints.len() - 1];
+            joints.push(last + direction * *length);
+        }
+        let constraints = vec![None; lengths.len()];
+        IkChain { joints, lengths, constraints, base_angle: angle, iterations: 10, tolerance: 0.01 }
+    }
+
+    /// Creates a chain through the given joints, from the base to the end, with segment
+    /// lengths from the distances between them.
+    pub fn from_joints(joints: Vec<Point>) -> Self {
+        let lengths: Vec<f32> = joints.windows(2).map(|pair| pair[0].distance(&pair[1])).collect();
+        let base_angle = match joints.as_slice() {
+            [first, second, ..] => (second.y - first.y).atan2(second.x - first.x),
+            _ => 0.0,
+        };
+        let constraints = vec![None; lengths.len()];
+        IkChain { joints, lengths, constraints, base_angle, iterations: 10, tolerance: 0.01 }
+    }
+
+    /// The joints from the base to the end, for drawing the chain.
+    pub fn joints(&self) -> &[Point] {
+        &self.joints
+    }
+
+    pub fn lengths(&self) -> &[f32] {
+        &self.lengths
+    }
+
+    /// The length of the whole chain, as far as it can reach.
+    pub fn length(&self) -> f32 {
+        self.lengths.iter().sum()
+    }
+
+    pub fn base(&self) -> Point {
+        self.joints.first().copied().unwrap_or(Point::new(0.0, 0.0))
+    }
+
+    /// Moves the whole chain so its base is at `base`.
+    pub fn set_base(&mut self, base: Point) {
+        let offset = base - self.base();
+        self.joints.iter_mut().for_each(|joint| *joint = *joint + offset);
+    }
+
+    /// The end of the last segment.
+    pub fn end(&self) -> Point {
+        self.joints.last().copied().unwrap_or(Point::new(0.0, 0.0))
+    }
+
+    /// The angle of every segment in radians, for drawing images along them.
+    pub fn angles(&self) -> Vec<f32> {
+        self.joints.windows(2).map(|pair| (pair[1].y - pair[0].y).atan2(pair[1].x - pair[0].x)).collect()
+    }
+
+    /// Limits the angle of `segment` relative to the segment before it, or to `base_angle`
+    /// for the first segment, to the range from `min` to `max` radians, each from -π to π.
+    /// Limits given the wrong way round are swapped, and limits that aren't finite are
+    /// dropped like None, which lets it turn freely. Segments outside the chain are ignored.
+    pub fn set_constraint(&mut self, segment: usize, limits: Option<(f32, f32)>) {
+        if let Some(constraint) = self.constraints.get_mut(segment) {
+            *constraint = limits.filter(|(min, max)| min.is_finite() && max.is_finite()).map(|(min, max)| (min.min(max), min.max(max)));
+        }
+    }
+
+    pub fn constraint(&self, segment: usize) -> Option<(f32, f32)> {
+        self.constraints.get(segment).copied().flatten()
+    }
+
+    /// Bends the chain, with its base in place, so its end reaches towards `target`, and
+    /// returns whether it got there. A target out of reach stretches the chain towards it.
+    pub fn reach(&mut self, target: Point) -> bool {
+        if self.lengths.is_empty() {
+            return false;
+        }
+        let base = self.base();
+        for _ in 0..self.iterations.max(1) {
+            // Backward: pull the end onto the target, dragging the joints behind it
+            let last = self.joints.len() - 1;
+            self.joints[last] = target;
+            for i in (0..last).rev() {
+                self.joints[i] = place(self.joints[i + 1], self.joints[i], self.lengths[i]);
+            }
+            // Forward: put the base back and straighten the joints out from it
+            self.joints[0] = base;
+            let mut reference = self.base_angle;
+            for i in 0..last {
+                let offset = self.joints[i + 1] - self.joints[i];
+                let mut angle = if offset.length() > 0.0 { offset.y.atan2(offset.x) } else { reference };
+                if let Some((min, max)) = self.constraints[i] {
+                    angle = reference + wrap(angle - reference).clamp(min, max);
+                }
+                self.joints[i + 1] = self.joints[i] + Point::new(angle.cos(), angle.sin()) * self.lengths[i];
+                reference = angle;
+            }
+            if self.end().distance(&target) <= self.tolerance {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Drags the end of the chain to `target`, with the rest of the chain trailing after it
+    /// and the base following along, like a snake or a tentacle. Constraints are ignored.
+    pub fn follow(&mut self, target: Point) {
+        let Some(last) = self.joints.len().checked_sub(1) else {
+            return;
+        };
+        self.joints[last] = target;
+        for i in (0..last).rev() {
+            self.joints[i] = place(self.joints[i + 1], self.joints[i], self.lengths[i]);
+        }
+    }
+}
+
+// The point `length` away from `anchor` in the direction of `towards`
+fn place(anchor: Point, towards: Point, length: f32) -> Point {
+    let offset = towards - anchor;
+    let distance = offset.length();
+    if distance == 0.0 {
+        // Any direction will do for joints on top of each other
+        return anchor + Point::new(length, 0.0);
+    }
+    anchor + offset * (length / distance)
+}
+
+// Wraps an angle to the range from -π to π
+fn wrap(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
@@ -0,0 +1,498 @@
+//! Bitmap images loaded from PNG and JPEG files or generated pixel by pixel, for drawing on
+//! the canvas with `Canvas::image` and `Canvas::image_scaled`, processing and saving.
+
+use std::f32::consts::PI;
+use std::io;
+use std::path::Path;
+
+use smallvec::SmallVec;
+
+use crate::color::Color;
+use crate::error::SketchError;
+use crate::geom::{Point, Rect, Transform};
+use crate::jpeg;
+use crate::pixelbuffer::{self, ImageFormat, PixelBuffer};
+use crate::png;
+use crate::raster::{FillCoverage, Precision};
+use crate::shape::Shape;
+
+/// How images are sampled when they are resized or drawn at another size. When shrinking
+/// with `Image::resize`, every filter but `Nearest` averages all the pixels that fall into
+/// each new pixel. See `Canvas::set_image_filter` for drawing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageFilter {
+    /// The closest pixel, for crisp pixel art.
+    Nearest,
+    /// Linear interpolation between the closest pixels, smooth but slightly soft.
+    #[default]
+    Bilinear,
+    /// Catmull-Rom cubic interpolation, sharper than bilinear.
+    Bicubic,
+    /// A three-lobed Lanczos window, the sharpest, for photos. Can ring slightly around hard
+    /// edges.
+    Lanczos3,
+}
+
+impl ImageFilter {
+    // How far the filter reaches, in pixels of the larger of the two images.
+    fn radius(self) -> f32 {
+        match self {
+            ImageFilter::Nearest => 0.5,
+            ImageFilter::Bilinear => 1.0,
+            ImageFilter::Bicubic => 2.0,
+            ImageFilter::Lanczos3 => 3.0,
+        }
+    }
+
+    // The weight of a pixel at distance `x` from the sample.
+    fn weight(self, x: f32) -> f32 {
+        let x = x.abs();
+        match self {
+            ImageFilter::Nearest => (x < 0.5) as u8 as f32,
+            ImageFilter::Bilinear => (1.0 - x).max(0.0),
+            ImageFilter::Bicubic => {
+                // Catmull-Rom, the cubic with a = -0.5
+                if x < 1.0 {
+                    (1.5 * x - 2.5) * x * x + 1.0
+                } else if x < 2.0 {
+                    ((-0.5 * x + 2.5) * x - 4.0) * x + 2.0
+                } else {
+                    0.0
+                }
+            }
+            ImageFilter::Lanczos3 => {
+                if x == 0.0 {
+                    1.0
+                } else if x < 3.0 {
+                    3.0 * (PI * x).sin() * (PI * x / 3.0).sin() / (PI * PI * x * x)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    // The pixels from `first` to `last` that contribute to a sample at `center`, with weights
+    // that add up to one. `scale` is the number of source pixels per destination pixel, which
+    // widens the filter when shrinking.
+    fn taps(self, center: f32, scale: f32, first: usize, last: usize) -> SmallVec<[(usize, f32); 8]> {
+        let clamp = |index: f32| (index.max(0.0) as usize).clamp(first, last);
+        if self == ImageFilter::Nearest {
+            return SmallVec::from_elem((clamp((center + 0.5).floor()), 1.0), 1);
+        }
+        let scale = scale.max(1.0);
+        let reach = self.radius() * scale;
+        let mut taps: SmallVec<[(usize, f32); 8]> = SmallVec::new();
+        for index in (center - reach).ceil() as i64..=(center + reach).floor() as i64 {
+            let weight = self.weight((index as f32 - center) / scale);
+            if weight != 0.0 {
+                taps.push((clamp(index as f32), weight));
+            }
+        }
+        let total: f32 = taps.iter().map(|(_, weight)| weight).sum();
+        if total.abs() < f32::EPSILON {
+            return SmallVec::from_elem((clamp(center.round()), 1.0), 1);
+        }
+        for (_, weight) in &mut taps {
+            *weight /= total;
+        }
+        taps
+    }
+}
+
+/// An image in memory, with pixels packed as ARGB like `Color`, row by row from the top left.
+/// Colors aren't premultiplied by their alpha.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<u32>,
+}
+
+impl Image {
+    /// Creates a fully transparent image of `width` by `height` pixels.
+    pub fn new(width: usize, height: usize) -> Self {
+        Image { width, height, pixels: vec![0; width * height] }
+    }
+
+    /// Creates an image from packed ARGB pixels, row by row. Fails unless there are
+    /// `width * height` pixels.
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<u32>) -> Result<Self, SketchError> {
+        if pixels.len() != width * height {
+            return Err(SketchError::InvalidSize { width, height });
+        }
+        Ok(Image { width, height, pixels })
+    }
+
+    /// Loads a PNG or JPEG file. The format is recognized from the contents of the file, not
+    /// its extension.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::error::SketchError;
+    /// # use simple_sketch::geom::Point;
+    /// # use simple_sketch::image::Image;
+    /// # fn draw(canvas: &mut Canvas) -> Result<(), SketchError> {
+    /// let photo = Image::load("assets/photo.jpg")?;
+    /// canvas.image(&photo, Point::new(10.0, 10.0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SketchError> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|e| std::io::Error::new(e.kind(), format!("could not load {}: {e}", path.display())))?;
+        Image::decode(&data).map_err(|error| match error {
+            SketchError::ImageDecode(reason) => SketchError::ImageDecode(format!("{}: {reason}", path.display())),
+            error => error,
+        })
+    }
+
+    /// Decodes the contents of a PNG or JPEG file.
+    pub fn decode(data: &[u8]) -> Result<Self, SketchError> {
+        let decoded = if data.starts_with(b"\x89PNG") {
+            png::read_png(data)
+        } else if data.starts_with(&[0xFF, 0xD8]) {
+            jpeg::read_jpeg(data)
+        } else {
+            Err("unknown image format, only PNG and JPEG files can be read".to_string())
+        };
+        let (width, height, pixels) = decoded.map_err(SketchError::ImageDecode)?;
+        Ok(Image { width, height, pixels })
+    }
+
+    /// The pixels of the image, row by row.
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    /// The pixels of the image, row by row, for processing them in place.
+    pub fn pixels_mut(&mut self) -> &mut [u32] {
+        &mut self.pixels
+    }
+
+    /// The color of the pixel at `x`, `y`, or None outside the image.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<Color> {
+        if x < self.width && y < self.height {
+            Some(Color(self.pixels[y * self.width + x]))
+        } else {
+            None
+        }
+    }
+
+    /// Sets the pixel at `x`, `y` to `color`, without blending. Pixels outside the image are
+    /// ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simple_sketch::color::Color;
+    /// # use simple_sketch::error::SketchError;
+    /// # use simple_sketch::image::Image;
+    /// let mut gradient = Image::new(256, 256);
+    /// for y in 0..256 {
+    ///     for x in 0..256 {
+    ///         gradient.set_pixel(x, y, Color::new(x as u8, y as u8, 128, 255));
+    ///     }
+    /// }
+    /// gradient.save("gradient.png")?;
+    /// # Ok::<(), SketchError>(())
+    /// ```
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color.0;
+        }
+    }
+
+    /// A copy of the image scaled to `width` by `height` pixels, sampled with `filter`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::image::{Image, ImageFilter};
+    /// # fn resize(photo: &Image, pixel_art: &Image) {
+    /// let thumbnail = photo.resize(160, 120, ImageFilter::Lanczos3);
+    /// let sprite = pixel_art.resize(64, 64, ImageFilter::Nearest);
+    /// # }
+    /// ```
+    pub fn resize(&self, width: usize, height: usize, filter: ImageFilter) -> Image {
+        if self.width == 0 || self.height == 0 || width == 0 || height == 0 {
+            return Image::new(width, height);
+        }
+        // Filter the rows and then the columns, in premultiplied colors
+        let premultiplied: Vec<[f32; 4]> = self.pixels.iter().map(|&pixel| premultiply(pixel)).collect();
+        let scale_x = self.width as f32 / width as f32;
+        let columns: Vec<_> = (0..width).map(|x| filter.taps((x as f32 + 0.5) * scale_x - 0.5, scale_x, 0, self.width - 1)).collect();
+        let mut rows_filtered = vec![[0.0; 4]; width * self.height];
+        for (y, row) in rows_filtered.chunks_mut(width).enumerate() {
+            let source = &premultiplied[y * self.width..(y + 1) * self.width];
+            for (pixel, taps) in row.iter_mut().zip(&columns) {
+                *pixel = weighted_sum(taps.iter().map(|&(x, weight)| (source[x], weight)));
+            }
+        }
+        let scale_y = self.height as f32 / height as f32;
+        let mut result = Image::new(width, height);
+        for y in 0..height {
+            let taps = filter.taps((y as f32 + 0.5) * scale_y - 0.5, scale_y, 0, self.height - 1);
+            for x in 0..width {
+                let sample = weighted_sum(taps.iter().map(|&(row, weight)| (rows_filtered[row * width + x], weight)));
+                result.pixels[y * width + x] = unpremultiply(sample);
+            }
+        }
+        result
+    }
+
+    /// Copies the pixels inside `rect` into a new image. The rectangle is grown to whole pixels
+    /// and clipped to the image, so the result is empty when it lies outside.
+    pub fn crop(&self, rect: Rect) -> Image {
+        let (width, height, pixels) = pixelbuffer::crop_pixels(&self.pixels, self.width, self.height, rect);
+        Image { width, height, pixels }
+    }
+
+    /// Multiplies the alpha of every pixel by the brightness of the matching pixel of `mask`,
+    /// like Processing's `mask()`: white keeps pixels, black and transparent pixels of the mask
+    /// hide them and grays fade them. A mask of another size is stretched over the image.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::error::SketchError;
+    /// # use simple_sketch::geom::Point;
+    /// # use simple_sketch::image::Image;
+    /// # fn draw(canvas: &mut Canvas) -> Result<(), SketchError> {
+    /// let mut photo = Image::load("photo.jpg")?;
+    /// photo.mask(&Image::load("vignette.png")?);
+    /// canvas.image(&photo, Point::new(0.0, 0.0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn mask(&mut self, mask: &Image) {
+        if mask.width == 0 || mask.height == 0 {
+            return;
+        }
+        let scale = (mask.width as f32 / self.width as f32, mask.height as f32 / self.height as f32);
+        let texels = (0, 0, mask.width - 1, mask.height - 1);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let sample = mask.sample_bilinear(texels, (x as f32 + 0.5) * scale.0 - 0.5, (y as f32 + 0.5) * scale.1 - 0.5);
+                let pixel = &mut self.pixels[y * self.width + x];
+                *pixel = fade(Color(*pixel), mask_value(sample)).0;
+            }
+        }
+    }
+
+    /// Hides the pixels of the image outside `shape`, given in image pixels, with antialiased
+    /// edges. Masking a square photo with a circle makes a round avatar.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simple_sketch::error::SketchError;
+    /// # use simple_sketch::geom::{Circle, Point};
+    /// # use simple_sketch::image::Image;
+    /// let mut avatar = Image::load("avatar.png")?;
+    /// let radius = avatar.width.min(avatar.height) as f32 / 2.0;
+    /// avatar.mask_shape(&Circle::new(Point::new(radius, radius), radius));
+    /// # Ok::<(), SketchError>(())
+    /// ```
+    pub fn mask_shape(&mut self, shape: &dyn Shape) {
+        let coverage = shape_coverage(shape, self.width, self.height, Precision::Float);
+        for (pixel, coverage) in self.pixels.iter_mut().zip(coverage) {
+            *pixel = fade(Color(*pixel), coverage).0;
+        }
+    }
+
+    /// Saves the image in the format that matches the extension of `path`: PNG, PPM, PAM,
+    /// BMP or TGA. Creates missing directories. See `PixelBuffer::save`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SketchError> {
+        let path = path.as_ref();
+        let Some(format) = ImageFormat::from_path(path) else {
+            let reason = format!("could not save {}: unknown image format, use png, ppm, pam, bmp or tga", path.display());
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, reason).into());
+        };
+        PixelBuffer::from(self.clone()).save(path, format)
+    }
+
+    // Samples the image at `x`, `y` in image pixels with `filter`, like `sample_bilinear`.
+    // `footprint` is the number of image pixels per sample along each axis, which widens the
+    // filter when the image is shrunk. Results stay within the colors of the image.
+    pub(crate) fn sample(&self, filter: ImageFilter, texels: (usize, usize, usize, usize), x: f32, y: f32, footprint: (f32, f32)) -> [f32; 4] {
+        if filter == ImageFilter::Bilinear {
+            return self.sample_bilinear(texels, x, y);
+        }
+        let columns = filter.taps(x, footprint.0, texels.0, texels.2);
+        let rows = filter.taps(y, footprint.1, texels.1, texels.3);
+        let taps = rows.iter().flat_map(|&(row, weight_y)| {
+            columns.iter().map(move |&(column, weight_x)| (premultiply(self.pixels[row * self.width + column]), weight_x * weight_y))
+        });
+        let [r, g, b, a] = weighted_sum(taps);
+        let a = a.clamp(0.0, 255.0);
+        [r.clamp(0.0, a), g.clamp(0.0, a), b.clamp(0.0, a), a]
+    }
+
+    // Samples the image at `x`, `y` in image pixels, where pixel centers are at whole numbers,
+    // and returns premultiplied red, green, blue and alpha. Samples are clamped to `texels`,
+    // the first and last column and row to read from.
+    pub(crate) fn sample_bilinear(&self, texels: (usize, usize, usize, usize), x: f32, y: f32) -> [f32; 4] {
+        let (left, top) = (x.floor(), y.floor());
+        let (fx, fy) = (x - left, y - top);
+        let column = |offset: f32| ((left + offset).max(0.0) as usize).clamp(texels.0, texels.2);
+        let row = |offset: f32| ((top + offset).max(0.0) as usize).clamp(texels.1, texels.3);
+        let mut sample = [0.0; 4];
+        for (py, weight_y) in [(row(0.0), 1.0 - fy), (row(1.0), fy)] {
+            for (px, weight_x) in [(column(0.0), 1.0 - fx), (column(1.0), fx)] {
+                let color = Color(self.pixels[py * self.width + px]);
+                let alpha = color.a() as f32 * weight_x * weight_y;
+                sample[0] += color.r() as f32 * alpha / 255.0;
+                sample[1] += color.g() as f32 * alpha / 255.0;
+                sample[2] += color.b() as f32 * alpha / 255.0;
+                sample[3] += alpha;
+            }
+        }
+        sample
+    }
+
+    // Samples the image like `sample_bilinear`, or the closest pixel with `ImageFilter::Nearest`,
+    // as if it were repeated in every direction.
+    pub(crate) fn sample_wrapped(&self, filter: ImageFilter, x: f32, y: f32) -> [f32; 4] {
+        let wrap = |value: f32, size: usize| (value.rem_euclid(size as f32) as usize).min(size - 1);
+        if filter == ImageFilter::Nearest {
+            return premultiply(self.pixels[wrap(y + 0.5, self.height) * self.width + wrap(x + 0.5, self.width)]);
+        }
+        let (left, top) = (x.floor(), y.floor());
+        let (fx, fy) = (x - left, y - top);
+        let mut sample = [0.0; 4];
+        for (row, weight_y) in [(wrap(top, self.height), 1.0 - fy), (wrap(top + 1.0, self.height), fy)] {
+            for (column, weight_x) in [(wrap(left, self.width), 1.0 - fx), (wrap(left + 1.0, self.width), fx)] {
+                for (total, channel) in sample.iter_mut().zip(premultiply(self.pixels[row * self.width + column])) {
+                    *total += channel * weight_x * weight_y;
+                }
+            }
+        }
+        sample
+    }
+}
+
+/// An image repeated in every direction, for filling shapes with wallpaper and halftone
+/// textures through `Canvas::set_fill_pattern`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::error::SketchError;
+/// # use simple_sketch::geom::Point;
+/// # use simple_sketch::image::{Image, Pattern};
+/// # use std::f32::consts::PI;
+/// # fn draw(canvas: &mut Canvas) -> Result<(), SketchError> {
+/// // Tiles at half size, turned 45 degrees
+/// let pattern = Pattern::placed(Image::load("tile.png")?, Point::new(0.0, 0.0), 0.5, PI / 4.0);
+/// canvas.set_fill_pattern(Some(pattern));
+/// canvas.circle(Point::new(200.0, 200.0), 300.0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub image: Image,
+    /// Maps the pixels of the image onto the canvas, placing, scaling and rotating the tiles.
+    pub transform: Transform,
+}
+
+impl Pattern {
+    /// Creates a pattern with the image's top left corner at the origin of the canvas, at its
+    /// own size.
+    pub fn new(image: Image) -> Self {
+        Pattern { image, transform: Transform::identity() }
+    }
+
+    /// Creates a pattern with a tile's top left corner at `offset`, in canvas pixels, scaled by
+    /// `scale` and rotated clockwise around that corner by `rotation` radians.
+    pub fn placed(image: Image, offset: Point, scale: f32, rotation: f32) -> Self {
+        let transform = Transform::translate(offset) * Transform::rotate(rotation) * Transform::scale(scale, scale);
+        Pattern { image, transform }
+    }
+}
+
+/// Copies the pixels of a pixel buffer, such as the canvas's, into an image.
+impl From<PixelBuffer> for Image {
+    fn from(buffer: PixelBuffer) -> Self {
+        let (width, height) = (buffer.width, buffer.height);
+        Image { width, height, pixels: buffer.into_pixels() }
+    }
+}
+
+impl From<&PixelBuffer> for Image {
+    fn from(buffer: &PixelBuffer) -> Self {
+        Image { width: buffer.width, height: buffer.height, pixels: buffer.get_buffer().to_vec() }
+    }
+}
+
+impl From<Image> for PixelBuffer {
+    fn from(image: Image) -> Self {
+        PixelBuffer::from_pixels(image.width, image.height, image.pixels)
+    }
+}
+
+// Unpacks an ARGB pixel into red, green and blue premultiplied by alpha, and alpha, from 0 to 255.
+fn premultiply(pixel: u32) -> [f32; 4] {
+    let color = Color(pixel);
+    let alpha = color.a() as f32;
+    [color.r() as f32 * alpha / 255.0, color.g() as f32 * alpha / 255.0, color.b() as f32 * alpha / 255.0, alpha]
+}
+
+fn weighted_sum(samples: impl Iterator<Item = ([f32; 4], f32)>) -> [f32; 4] {
+    let mut sum = [0.0; 4];
+    for (sample, weight) in samples {
+        for (total, channel) in sum.iter_mut().zip(sample) {
+            *total += channel * weight;
+        }
+    }
+    sum
+}
+
+// Packs a premultiplied sample back into an ARGB pixel.
+fn unpremultiply([r, g, b, a]: [f32; 4]) -> u32 {
+    let a = a.min(255.0);
+    if a <= 0.0 {
+        return 0;
+    }
+    let channel = |value: f32| (value * 255.0 / a).round().clamp(0.0, 255.0) as u8;
+    Color::new(channel(r), channel(g), channel(b), a.round().clamp(0.0, 255.0) as u8).0
+}
+
+// The mask value of a premultiplied sample of a mask image, from 0.0 to 1.0: its brightness
+// times its alpha.
+pub(crate) fn mask_value([r, g, b, _]: [f32; 4]) -> f32 {
+    ((0.299 * r + 0.587 * g + 0.114 * b) / 255.0).clamp(0.0, 1.0)
+}
+
+// The antialiased fill coverage of `shape` for every pixel of a `width` by `height` grid, from
+// 0.0 to 1.0, row by row, rasterized with `precision`.
+pub(crate) fn shape_coverage(shape: &dyn Shape, width: usize, height: usize, precision: Precision) -> Vec<f32> {
+    let mut coverage = vec![0.0; width * height];
+    let (top_left, bottom_right) = shape.bounding_box();
+    if !(top_left.x.is_finite() && top_left.y.is_finite() && bottom_right.x.is_finite() && bottom_right.y.is_finite()) {
+        return coverage;
+    }
+    let (x1, y1) = (top_left.x.floor().max(0.0) as usize, top_left.y.floor().max(0.0) as usize);
+    let x2 = (bottom_right.x.ceil().max(0.0) as usize).min(width);
+    let y2 = (bottom_right.y.ceil().max(0.0) as usize).min(height);
+    if x1 >= x2 || y1 >= y2 {
+        return coverage;
+    }
+    let fill = FillCoverage::new(shape, (x1 as i32, y1 as i32), (x2 as i32 - 1, y2 as i32 - 1), precision);
+    for y in y1..y2 {
+        for x in x1..x2 {
+            coverage[y * width + x] = fill.at(x as i32, y as i32);
+        }
+    }
+    coverage
+}
+
+// Multiplies the alpha of a color by `amount`.
+fn fade(color: Color, amount: f32) -> Color {
+    color.with_alpha((color.a() as f32 * amount).round() as u8)
+}
@@ -0,0 +1,258 @@
+//! Mouse and keyboard state and events.
+//!
+//! The runner polls the window once per frame and feeds the result to a `Mouse` and a
+//! `Keyboard`, which keep the current state and turn changes since the previous frame into
+//! events, in the spirit of Processing's `mousePressed()`, `mouseClicked()`, `keyPressed()`
+//! and friends.
+
+use crate::geom::Point;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use minifb::Key;
+
+/// A key on the keyboard, with the same variants as minifb's `Key`, which the other targets use.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Key {
+    Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, A, B, C, D, E, F, G, H, I, J,
+    K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10,
+    F11, F12, F13, F14, F15, Down, Left, Right, Up, Apostrophe, Backquote, Backslash, Comma,
+    Equal, LeftBracket, Minus, Period, RightBracket, Semicolon, Slash, Backspace, Delete, End,
+    Enter, Escape, Home, Insert, Menu, PageDown, PageUp, Pause, Space, Tab, NumLock, CapsLock,
+    ScrollLock, LeftShift, RightShift, LeftCtrl, RightCtrl, NumPad0, NumPad1, NumPad2, NumPad3,
+    NumPad4, NumPad5, NumPad6, NumPad7, NumPad8, NumPad9, NumPadDot, NumPadSlash, NumPadAsterisk,
+    NumPadMinus, NumPadPlus, NumPadEnter, LeftAlt, RightAlt, LeftSuper, RightSuper, Unknown,
+    Count,
+}
+
+/// A mouse button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+}
+
+impl MouseButton {
+    pub(crate) const ALL: [MouseButton; 3] = [MouseButton::Left, MouseButton::Middle, MouseButton::Right];
+
+    pub(crate) fn index(self) -> usize {
+        match self {
+            MouseButton::Left => 0,
+            MouseButton::Middle => 1,
+            MouseButton::Right => 2,
+        }
+    }
+}
+
+/// What happened to the mouse, passed to the mouse callbacks of an `Application`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MouseEvent {
+    /// The mouse position in canvas pixels.
+    pub position: Point,
+    /// The mouse position in the previous frame.
+    pub previous: Point,
+    /// The button that was pressed, released or clicked, None for moves.
+    pub button: Option<MouseButton>,
+}
+
+/// The mouse moving with a button held down, passed to `Application::mouse_dragged`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragEvent {
+    /// Where the button was pressed.
+    pub start: Point,
+    /// The mouse position in canvas pixels.
+    pub position: Point,
+    /// The mouse position in the previous frame.
+    pub previous: Point,
+    /// The held button. If several are held, the first of left, middle and right.
+    pub button: MouseButton,
+}
+
+impl DragEvent {
+    /// The distance moved since the previous frame.
+    pub fn delta(&self) -> Point {
+        self.position - self.previous
+    }
+}
+
+/// A turn of the mouse wheel or a scroll on a touchpad, passed to `Application::mouse_wheel`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WheelEvent {
+    /// The mouse position in canvas pixels.
+    pub position: Point,
+    /// The amount scrolled since the previous frame. Scrolling up or left is positive. One notch
+    /// of a typical wheel scrolls by 1 on some platforms and by more on others.
+    pub delta: Point,
+}
+
+/// A change of the mouse state between two frames.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum MouseInput {
+    Pressed(MouseEvent),
+    Released(MouseEvent),
+    Clicked(MouseEvent),
+    Moved(MouseEvent),
+    Dragged(DragEvent),
+    Wheel(WheelEvent),
+}
+
+/// The state of the mouse in the current frame.
+#[derive(Debug, Clone)]
+pub struct Mouse {
+    position: Point,
+    previous: Point,
+    pressed: [bool; 3],
+    /// Where each held button was pressed.
+    press_positions: [Point; 3],
+    /// Whether the mouse moved since each held button was pressed. Releasing a button
+    /// without moving counts as a click.
+    moved_while_pressed: [bool; 3],
+    wheel: Point,
+}
+
+impl Default for Mouse {
+    fn default() -> Self {
+        Mouse {
+            position: Point::new(0.0, 0.0),
+            previous: Point::new(0.0, 0.0),
+            pressed: [false; 3],
+            press_positions: [Point::new(0.0, 0.0); 3],
+            moved_while_pressed: [false; 3],
+            wheel: Point::new(0.0, 0.0),
+        }
+    }
+}
+
+impl Mouse {
+    /// The mouse position in canvas pixels. While the cursor is outside the window, this is
+    /// the last position inside it, like Processing's `mouseX` and `mouseY`.
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    /// The mouse position in the previous frame, like Processing's `pmouseX` and `pmouseY`.
+    pub fn previous_position(&self) -> Point {
+        self.previous
+    }
+
+    /// Checks if a button is held down.
+    pub fn is_pressed(&self, button: MouseButton) -> bool {
+        self.pressed[button.index()]
+    }
+
+    /// Checks if any button is held down, like Processing's `mousePressed` variable.
+    pub fn is_any_pressed(&self) -> bool {
+        self.pressed.contains(&true)
+    }
+
+    /// Where a held button was pressed, or None if it isn't held down.
+    pub fn press_position(&self, button: MouseButton) -> Option<Point> {
+        self.is_pressed(button).then(|| self.press_positions[button.index()])
+    }
+
+    /// The amount scrolled with the wheel since the previous frame, zero if it didn't turn.
+    pub fn wheel_delta(&self) -> Point {
+        self.wheel
+    }
+
+    /// Moves to a new frame's state and returns the events it causes, in the order the
+    /// callbacks are invoked: presses, then moves or drags, then releases and clicks, then the
+    /// wheel.
+    ///
+    /// # Arguments
+    /// * `position` - The mouse position, or None if the cursor is outside the window.
+    /// * `pressed` - Whether each button in `MouseButton::ALL` is held down.
+    /// * `wheel` - The amount scrolled since the previous frame, if any.
+    pub(crate) fn update(&mut self, position: Option<Point>, pressed: [bool; 3], wheel: Option<Point>) -> Vec<MouseInput> {
+        self.previous = self.position;
+        if let Some(position) = position {
+            self.position = position;
+        }
+        let moved = self.position != self.previous;
+        let event = |button| MouseEvent { position: self.position, previous: self.previous, button };
+
+        let mut inputs = Vec::new();
+        for button in MouseButton::ALL {
+            let i = button.index();
+            if pressed[i] && !self.pressed[i] {
+                inputs.push(MouseInput::Pressed(event(Some(button))));
+                self.press_positions[i] = self.position;
+                self.moved_while_pressed[i] = false;
+            }
+        }
+        if moved {
+            match MouseButton::ALL.into_iter().find(|button| pressed[button.index()]) {
+                Some(button) => inputs.push(MouseInput::Dragged(DragEvent {
+                    start: self.press_positions[button.index()],
+                    position: self.position,
+                    previous: self.previous,
+                    button,
+                })),
+                None => inputs.push(MouseInput::Moved(event(None))),
+            }
+        }
+        for button in MouseButton::ALL {
+            let i = button.index();
+            if pressed[i] && moved {
+                self.moved_while_pressed[i] = true;
+            }
+            if !pressed[i] && self.pressed[i] {
+                inputs.push(MouseInput::Released(event(Some(button))));
+                if !self.moved_while_pressed[i] {
+                    inputs.push(MouseInput::Clicked(event(Some(button))));
+                }
+            }
+        }
+
+        self.wheel = wheel.unwrap_or(Point::new(0.0, 0.0));
+        if self.wheel != Point::new(0.0, 0.0) {
+            inputs.push(MouseInput::Wheel(WheelEvent { position: self.position, delta: self.wheel }));
+        }
+
+        self.pressed = pressed;
+        inputs
+    }
+}
+
+/// A key that was pressed or released, passed to the keyboard callbacks of an `Application`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key: Key,
+}
+
+/// A change of the keyboard state between two frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum KeyInput {
+    Pressed(KeyEvent),
+    Released(KeyEvent),
+}
+
+/// The keys held down in the current frame.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Keyboard {
+    down: Vec<Key>,
+}
+
+impl Keyboard {
+    pub fn is_down(&self, key: Key) -> bool {
+        self.down.contains(&key)
+    }
+
+    pub fn down(&self) -> &[Key] {
+        &self.down
+    }
+
+    /// Moves to a new frame's state, given all keys held down, and returns the events it
+    /// causes: releases first, then presses.
+    pub fn update(&mut self, down: Vec<Key>) -> Vec<KeyInput> {
+        let released = self.down.iter().filter(|key| !down.contains(key));
+        let pressed = down.iter().filter(|key| !self.down.contains(key));
+        let inputs = released
+            .map(|&key| KeyInput::Released(KeyEvent { key }))
+            .chain(pressed.map(|&key| KeyInput::Pressed(KeyEvent { key })))
+            .collect();
+        self.down = down;
+        inputs
+    }
+}
@@ -0,0 +1,156 @@
+//! Instanced drawing of one shape under many transforms.
+//!
+//! The shape is rasterized once into a `CoverageMask` in its own coordinate space. Each
+//! instance then only maps its destination pixels back into the mask and blends the sampled
+//! coverage, which avoids repeating the per-pixel `contains` tests for every copy.
+
+use crate::color::Color;
+use crate::geom::{Point, Rect, Transform};
+use crate::pixelbuffer::PixelBuffer;
+use crate::raster::{FillCoverage, Precision};
+use crate::shape::{Shape, Transformed};
+
+/// Largest width or height of a coverage mask, to bound memory for extreme scales.
+const MAX_MASK_SIZE: f32 = 2048.0;
+
+/// One placement of an instanced shape.
+#[derive(Debug, Clone, Copy)]
+pub struct Instance {
+    /// Maps the shape's coordinates onto the canvas.
+    pub transform: Transform,
+    /// The fill color of this copy.
+    pub color: Color,
+}
+
+/// A shape's fill coverage rasterized at a fixed resolution.
+pub struct CoverageMask {
+    width: usize,
+    height: usize,
+    /// Shape-space position of the mask's top-left corner.
+    origin: Point,
+    /// Mask pixels per shape unit.
+    scale: f32,
+    coverage: Vec<f32>,
+}
+
+impl CoverageMask {
+    /// Rasterizes the fill coverage of `shape` at `scale` mask pixels per shape unit.
+    pub fn from_shape(shape: &dyn Shape, scale: f32) -> Self {
+        let (top_left, bottom_right) = shape.bounding_box();
+        let size = bottom_right - top_left;
+        let empty = CoverageMask { width: 0, height: 0, origin: top_left, scale: 1.0, coverage: Vec::new() };
+        if !(size.x.is_finite() && size.y.is_finite()) {
+            return empty;
+        }
+        let scale = scale.min(MAX_MASK_SIZE / size.x.max(size.y).max(1.0)).max(f32::EPSILON);
+        // Shapes so large that even the smallest scale exceeds the size limit aren't drawn
+        if size.x.max(size.y) * scale > MAX_MASK_SIZE {
+            return empty;
+        }
+
+        // One pixel of padding around the shape lets bilinear sampling fade out at the edges
+        let origin = top_left - Point::new(1.0, 1.0) * (1.0 / scale);
+        let width = (size.x * scale).ceil() as usize + 2;
+        let height = (size.y * scale).ceil() as usize + 2;
+
+        // The shape placed so that mask pixels are canvas pixels
+        let to_mask = Transform::translate(Point::new(-origin.x, -origin.y)).then(&Transform::scale(scale, scale));
+        let placed = Transformed { shape, transform: to_mask };
+        let fill = FillCoverage::new(&placed, (0, 0), (width as i32 - 1, height as i32 - 1), Precision::Float);
+        let mut coverage = vec![0.0; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                coverage[y * width + x] = fill.at(x as i32, y as i32);
+            }
+        }
+
+        CoverageMask { width, height, origin, scale, coverage }
+    }
+
+    /// The area covered by the mask in shape coordinates.
+    pub fn bounds(&self) -> Rect {
+        let size = Point::new(self.width as f32, self.height as f32) * (1.0 / self.scale);
+        Rect::new(self.origin, self.origin + size)
+    }
+
+    /// Samples the coverage at a point in shape coordinates with bilinear filtering.
+    pub fn sample(&self, point: Point) -> f32 {
+        let p = (point - self.origin) * self.scale - Point::new(0.5, 0.5);
+        let (x0, y0) = (p.x.floor(), p.y.floor());
+        let (fx, fy) = (p.x - x0, p.y - y0);
+        // Casts saturate for points far outside the mask, where the neighbours must not overflow
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let (x1, y1) = (x0.saturating_add(1), y0.saturating_add(1));
+
+        let at = |x: i64, y: i64| {
+            if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+                0.0
+            } else {
+                self.coverage[y as usize * self.width + x as usize]
+            }
+        };
+
+        let top = at(x0, y0) * (1.0 - fx) + at(x1, y0) * fx;
+        let bottom = at(x0, y1) * (1.0 - fx) + at(x1, y1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    /// Blends one instance of the mask into a band of canvas rows.
+    /// `band` holds the pixels of rows `first_row..` of a buffer `width` pixels wide.
+    pub fn draw_instance(&self, instance: &Instance, band: &mut [u32], first_row: usize, width: usize) {
+        let Some(inverse) = instance.transform.inverse() else {
+            return;
+        };
+        if width == 0 || band.is_empty() {
+            return;
+        }
+        let band_rows = band.len() / width;
+
+        // Destination bounds of the transformed mask, clamped to the band
+        let bounds = self.bounds();
+        let corners = [
+            bounds.min,
+            Point::new(bounds.max.x, bounds.min.y),
+            bounds.max,
+            Point::new(bounds.min.x, bounds.max.y),
+        ]
+        .map(|corner| instance.transform.transform_point(corner));
+        let (min, max) = corners.iter().fold(
+            (Point::new(f32::INFINITY, f32::INFINITY), Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY)),
+            |(min, max), c| (Point::new(min.x.min(c.x), min.y.min(c.y)), Point::new(max.x.max(c.x), max.y.max(c.y))),
+        );
+        if !(min.x.is_finite() && min.y.is_finite() && max.x.is_finite() && max.y.is_finite()) {
+            return;
+        }
+
+        let x1 = (min.x.floor().max(0.0) as usize).min(width);
+        let x2 = (max.x.ceil().max(0.0) as usize).min(width);
+        let y1 = (min.y.floor().max(first_row as f32) as usize).min(first_row + band_rows);
+        let y2 = (max.y.ceil().max(0.0) as usize).min(first_row + band_rows);
+
+        for y in y1..y2 {
+            let row = &mut band[(y - first_row) * width..(y - first_row + 1) * width];
+            for (x, pixel) in row.iter_mut().enumerate().take(x2).skip(x1) {
+                let center = Point::new(x as f32 + 0.5, y as f32 + 0.5);
+                let coverage = self.sample(inverse.transform_point(center));
+                if coverage > 0.0 {
+                    let color = instance.color.with_alpha((instance.color.a() as f32 * coverage) as u8);
+                    *pixel = PixelBuffer::blend(*pixel, &color);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the largest factor by which any of the instances scales the shape.
+pub fn max_scale(instances: &[Instance]) -> f32 {
+    instances
+        .iter()
+        .map(|instance| {
+            let t = instance.transform;
+            let x_axis = Point::new(t.m11, t.m21).length();
+            let y_axis = Point::new(t.m12, t.m22).length();
+            x_axis.max(y_axis)
+        })
+        .fold(0.0, f32::max)
+}
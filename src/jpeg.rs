@@ -0,0 +1,423 @@
+//! A minimal baseline JPEG decoder.
+//!
+//! Reads the JPEG files cameras and most tools write: Huffman coded baseline images in
+//! grayscale or YCbCr, with any chroma subsampling and restart intervals. Progressive and
+//! arithmetic coded images, and CMYK images, are reported as unsupported.
+
+/// The order in which the coefficients of a block are stored.
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20, 13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43,
+    36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59, 52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+/// Decodes a JPEG file into its width, height and pixels, packed as opaque ARGB like `Color`.
+pub(crate) fn read_jpeg(data: &[u8]) -> Result<(usize, usize, Vec<u32>), String> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return Err("not a JPEG file".to_string());
+    }
+    let mut decoder = Decoder {
+        width: 0,
+        height: 0,
+        components: Vec::new(),
+        quantization: [[1; 64]; 4],
+        dc_tables: Default::default(),
+        ac_tables: Default::default(),
+        restart_interval: 0,
+        max_horizontal: 1,
+        max_vertical: 1,
+    };
+    let mut position = 2;
+    loop {
+        // Markers may be padded with any number of 0xFF bytes
+        while data.get(position) == Some(&0xFF) && data.get(position + 1) == Some(&0xFF) {
+            position += 1;
+        }
+        let (Some(0xFF), Some(&marker)) = (data.get(position), data.get(position + 1)) else {
+            return Err("corrupt JPEG marker".to_string());
+        };
+        position += 2;
+        if marker == 0xD9 {
+            break;
+        }
+        if (0xD0..=0xD7).contains(&marker) || marker == 0x01 {
+            continue;
+        }
+        let length = match data.get(position..position + 2) {
+            Some(bytes) => u16::from_be_bytes([bytes[0], bytes[1]]) as usize,
+            None => return Err("truncated JPEG file".to_string()),
+        };
+        let segment = data.get(position + 2..position + length).ok_or("truncated JPEG segment")?;
+        position += length;
+        match marker {
+            0xC0 | 0xC1 => decoder.read_frame(segment)?,
+            0xC2 | 0xC6 | 0xCA | 0xCE => return Err("progressive JPEG files are not supported".to_string()),
+            0xC3 | 0xC5 | 0xC7 | 0xC9 | 0xCB | 0xCD | 0xCF => return Err("this kind of JPEG file is not supported".to_string()),
+            0xC4 => decoder.read_huffman_tables(segment)?,
+            0xDB => decoder.read_quantization_tables(segment)?,
+            0xDD => {
+                let [high, low, ..] = *segment else {
+                    return Err("invalid JPEG restart interval".to_string());
+                };
+                decoder.restart_interval = u16::from_be_bytes([high, low]) as usize;
+            }
+            0xDA => position = decoder.read_scan(segment, data, position)?,
+            _ => {}
+        }
+    }
+    decoder.into_pixels()
+}
+
+struct Decoder {
+    width: usize,
+    height: usize,
+    components: Vec<Component>,
+    quantization: [[u16; 64]; 4],
+    dc_tables: [Option<Huffman>; 4],
+    ac_tables: [Option<Huffman>; 4],
+    restart_interval: usize,
+    /// The largest sampling factors, which set the size of a minimum coded unit.
+    max_horizontal: usize,
+    max_vertical: usize,
+}
+
+struct Component {
+    id: u8,
+    horizontal: usize,
+    vertical: usize,
+    quantization: usize,
+    dc_table: usize,
+    ac_table: usize,
+    /// The samples of the component, `stride` wide and padded to whole units.
+    samples: Vec<u8>,
+    stride: usize,
+    prediction: i32,
+}
+
+impl Decoder {
+    fn read_frame(&mut self, segment: &[u8]) -> Result<(), String> {
+        let [precision, h0, h1, w0, w1, count, ref rest @ ..] = *segment else {
+            return Err("invalid JPEG frame header".to_string());
+        };
+        self.height = u16::from_be_bytes([h0, h1]) as usize;
+        self.width = u16::from_be_bytes([w0, w1]) as usize;
+        if precision != 8 {
+            return Err(format!("{precision} bit JPEG files are not supported"));
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err("JPEG files without a height are not supported".to_string());
+        }
+        if count != 1 && count != 3 {
+            return Err(format!("JPEG files with {count} components are not supported"));
+        }
+        if rest.len() < count as usize * 3 {
+            return Err("invalid JPEG frame header".to_string());
+        }
+        self.components = rest.chunks(3).take(count as usize).map(|bytes| Component {
+            id: bytes[0],
+            horizontal: (bytes[1] >> 4) as usize,
+            vertical: (bytes[1] & 0x0F) as usize,
+            quantization: (bytes[2] & 3) as usize,
+            dc_table: 0,
+            ac_table: 0,
+            samples: Vec::new(),
+            stride: 0,
+            prediction: 0,
+        }).collect();
+        if self.components.iter().any(|component| !(1..=4).contains(&component.horizontal) || !(1..=4).contains(&component.vertical)) {
+            return Err("invalid JPEG sampling factors".to_string());
+        }
+        self.max_horizontal = self.components.iter().map(|component| component.horizontal).max().unwrap_or(1);
+        self.max_vertical = self.components.iter().map(|component| component.vertical).max().unwrap_or(1);
+        let (units_x, units_y) = self.units();
+        for component in &mut self.components {
+            component.stride = units_x * component.horizontal * 8;
+            component.samples = vec![0; component.stride * units_y * component.vertical * 8];
+        }
+        Ok(())
+    }
+
+    // The number of minimum coded units across and down the image.
+    fn units(&self) -> (usize, usize) {
+        (self.width.div_ceil(self.max_horizontal * 8), self.height.div_ceil(self.max_vertical * 8))
+    }
+
+    fn read_huffman_tables(&mut self, mut segment: &[u8]) -> Result<(), String> {
+        while let [class_and_id, ref rest @ ..] = *segment {
+            let counts = rest.get(..16).ok_or("invalid JPEG Huffman table")?;
+            let total: usize = counts.iter().map(|&count| count as usize).sum();
+            let symbols = rest.get(16..16 + total).ok_or("invalid JPEG Huffman table")?;
+            let table = Some(Huffman::new(counts, symbols));
+            match class_and_id >> 4 {
+                0 => self.dc_tables[(class_and_id & 3) as usize] = table,
+                _ => self.ac_tables[(class_and_id & 3) as usize] = table,
+            }
+            segment = &rest[16 + total..];
+        }
+        Ok(())
+    }
+
+    fn read_quantization_tables(&mut self, mut segment: &[u8]) -> Result<(), String> {
+        while let [precision_and_id, ref rest @ ..] = *segment {
+            let wide = precision_and_id >> 4 != 0;
+            let size = if wide { 128 } else { 64 };
+            let values = rest.get(..size).ok_or("invalid JPEG quantization table")?;
+            let table = &mut self.quantization[(precision_and_id & 3) as usize];
+            for (i, entry) in table.iter_mut().enumerate() {
+                *entry = if wide { u16::from_be_bytes([values[i * 2], values[i * 2 + 1]]) } else { values[i] as u16 };
+            }
+            segment = &rest[size..];
+        }
+        Ok(())
+    }
+
+    // Decodes the entropy coded data of a scan starting at `position`, and returns the
+    // position after it.
+    fn read_scan(&mut self, segment: &[u8], data: &[u8], position: usize) -> Result<usize, String> {
+        if self.components.is_empty() {
+            return Err("JPEG scan before the frame header".to_string());
+        }
+        let count = *segment.first().ok_or("invalid JPEG scan header")? as usize;
+        let selectors = segment.get(1..1 + count * 2).ok_or("invalid JPEG scan header")?;
+        let mut scan = Vec::with_capacity(count);
+        for bytes in selectors.chunks(2) {
+            let index = self.components.iter().position(|component| component.id == bytes[0]).ok_or("JPEG scan of an unknown component")?;
+            let component = &mut self.components[index];
+            component.dc_table = (bytes[1] >> 4) as usize & 3;
+            component.ac_table = (bytes[1] & 0x0F) as usize & 3;
+            component.prediction = 0;
+            scan.push(index);
+        }
+
+        let mut bits = BitReader { data, position, buffer: 0, count: 0 };
+        // A scan of one component covers just its blocks, one per unit, not whole units
+        let (units_x, units_y) = if let [index] = scan[..] {
+            let component = &self.components[index];
+            (
+                (self.width * component.horizontal).div_ceil(self.max_horizontal * 8),
+                (self.height * component.vertical).div_ceil(self.max_vertical * 8),
+            )
+        } else {
+            self.units()
+        };
+        let mut block = [0i32; 64];
+        for unit in 0..units_x * units_y {
+            if self.restart_interval > 0 && unit > 0 && unit % self.restart_interval == 0 {
+                bits.restart()?;
+                for &index in &scan {
+                    self.components[index].prediction = 0;
+                }
+            }
+            let (unit_x, unit_y) = (unit % units_x, unit / units_x);
+            for &index in &scan {
+                let (horizontal, vertical) = if scan.len() == 1 { (1, 1) } else { (self.components[index].horizontal, self.components[index].vertical) };
+                for v in 0..vertical {
+                    for h in 0..horizontal {
+                        self.read_block(&mut bits, index, &mut block)?;
+                        let component = &mut self.components[index];
+                        let x = (unit_x * horizontal + h) * 8;
+                        let y = (unit_y * vertical + v) * 8;
+                        let quantization = &self.quantization[component.quantization];
+                        idct(&block, quantization, &mut component.samples[y * component.stride + x..], component.stride);
+                    }
+                }
+            }
+        }
+        Ok(bits.end())
+    }
+
+    // Reads the coefficients of a block in natural order.
+    fn read_block(&mut self, bits: &mut BitReader, index: usize, block: &mut [i32; 64]) -> Result<(), String> {
+        let component = &mut self.components[index];
+        let dc_table = self.dc_tables[component.dc_table].as_ref().ok_or("missing JPEG Huffman table")?;
+        let ac_table = self.ac_tables[component.ac_table].as_ref().ok_or("missing JPEG Huffman table")?;
+        *block = [0; 64];
+        let size = dc_table.decode(bits)?;
+        component.prediction += bits.receive(size)?;
+        block[0] = component.prediction;
+        let mut k = 1;
+        while k < 64 {
+            let symbol = ac_table.decode(bits)?;
+            let (run, size) = ((symbol >> 4) as usize, symbol & 0x0F);
+            if size == 0 {
+                if run != 15 {
+                    break;
+                }
+                k += 16;
+                continue;
+            }
+            k += run;
+            if k > 63 {
+                return Err("corrupt JPEG block".to_string());
+            }
+            block[ZIGZAG[k]] = bits.receive(size)?;
+            k += 1;
+        }
+        Ok(())
+    }
+
+    // Upsamples the chroma, converts to RGB and crops the padding of partial units.
+    fn into_pixels(self) -> Result<(usize, usize, Vec<u32>), String> {
+        if self.components.is_empty() {
+            return Err("JPEG file without a frame".to_string());
+        }
+        let sample = |component: &Component, x: usize, y: usize| {
+            let x = x * component.horizontal / self.max_horizontal;
+            let y = y * component.vertical / self.max_vertical;
+            component.samples[y * component.stride + x]
+        };
+        let mut pixels = Vec::with_capacity(self.width * self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pixel = match &self.components[..] {
+                    [gray] => {
+                        let gray = sample(gray, x, y) as u32;
+                        gray << 16 | gray << 8 | gray
+                    }
+                    [luma, blue, red, ..] => {
+                        let luma = sample(luma, x, y) as f32;
+                        let blue = sample(blue, x, y) as f32 - 128.0;
+                        let red = sample(red, x, y) as f32 - 128.0;
+                        let channel = |value: f32| value.round().clamp(0.0, 255.0) as u32;
+                        channel(luma + 1.402 * red) << 16 | channel(luma - 0.344_136 * blue - 0.714_136 * red) << 8 | channel(luma + 1.772 * blue)
+                    }
+                    _ => unreachable!(),
+                };
+                pixels.push(0xFF00_0000 | pixel);
+            }
+        }
+        Ok((self.width, self.height, pixels))
+    }
+}
+
+// Dequantizes a block and writes its 8x8 samples to `out`, rows `stride` apart.
+fn idct(block: &[i32; 64], quantization: &[u16; 64], out: &mut [u8], stride: usize) {
+    use std::f32::consts::{FRAC_1_SQRT_2, PI};
+    let mut coefficients = [0.0f32; 64];
+    for (k, &zigzag) in ZIGZAG.iter().enumerate() {
+        coefficients[zigzag] = (block[zigzag] * quantization[k] as i32) as f32;
+    }
+    let cosines: [[f32; 8]; 8] = std::array::from_fn(|x| {
+        std::array::from_fn(|u| {
+            let scale = if u == 0 { FRAC_1_SQRT_2 } else { 1.0 };
+            scale * ((2 * x + 1) as f32 * u as f32 * PI / 16.0).cos()
+        })
+    });
+    // Separable: first along the rows, then along the columns
+    let mut rows = [0.0f32; 64];
+    for v in 0..8 {
+        for x in 0..8 {
+            rows[v * 8 + x] = (0..8).map(|u| cosines[x][u] * coefficients[v * 8 + u]).sum();
+        }
+    }
+    for y in 0..8 {
+        for x in 0..8 {
+            let value: f32 = (0..8).map(|v| cosines[y][v] * rows[v * 8 + x]).sum();
+            out[y * stride + x] = (value / 4.0 + 128.0).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    fn bit(&mut self) -> Result<u32, String> {
+        if self.count == 0 {
+            let byte = *self.data.get(self.position).ok_or("truncated JPEG data")?;
+            if byte == 0xFF {
+                match self.data.get(self.position + 1) {
+                    // A stuffed zero byte follows every 0xFF in the data
+                    Some(0) => self.position += 2,
+                    // A marker ends the data early, read zeros until the scan is done
+                    _ => {
+                        self.buffer = 0;
+                        self.count = 8;
+                        return self.bit();
+                    }
+                }
+            } else {
+                self.position += 1;
+            }
+            self.buffer = byte as u32;
+            self.count = 8;
+        }
+        self.count -= 1;
+        Ok((self.buffer >> self.count) & 1)
+    }
+
+    // Reads a `size` bit value, and extends it to its sign.
+    fn receive(&mut self, size: u8) -> Result<i32, String> {
+        if size == 0 {
+            return Ok(0);
+        }
+        if size > 16 {
+            return Err("corrupt JPEG data".to_string());
+        }
+        let mut value = 0i32;
+        for _ in 0..size {
+            value = value << 1 | self.bit()? as i32;
+        }
+        Ok(if value < 1 << (size - 1) { value - (1 << size) + 1 } else { value })
+    }
+
+    // Skips the rest of the byte and the restart marker that follows it.
+    fn restart(&mut self) -> Result<(), String> {
+        self.count = 0;
+        match self.data.get(self.position..self.position + 2) {
+            Some([0xFF, 0xD0..=0xD7]) => {
+                self.position += 2;
+                Ok(())
+            }
+            _ => Err("missing JPEG restart marker".to_string()),
+        }
+    }
+
+    // The position of the marker after the data.
+    fn end(mut self) -> usize {
+        while self.position < self.data.len() && !(self.data[self.position] == 0xFF && self.data.get(self.position + 1).is_some_and(|&next| next != 0)) {
+            self.position += 1;
+        }
+        self.position
+    }
+}
+
+// A canonical Huffman code, decoded one bit at a time.
+struct Huffman {
+    /// For each code length, the largest code of that length, or -1 when there are none.
+    max_codes: [i32; 17],
+    /// For each code length, the index of its first symbol minus its first code.
+    offsets: [i32; 17],
+    symbols: Vec<u8>,
+}
+
+impl Huffman {
+    fn new(counts: &[u8], symbols: &[u8]) -> Self {
+        let (mut max_codes, mut offsets) = ([-1; 17], [0; 17]);
+        let (mut code, mut index) = (0i32, 0i32);
+        for (length, &count) in counts.iter().enumerate() {
+            let count = count as i32;
+            offsets[length + 1] = index - code;
+            if count > 0 {
+                max_codes[length + 1] = code + count - 1;
+            }
+            code = (code + count) << 1;
+            index += count;
+        }
+        Huffman { max_codes, offsets, symbols: symbols.to_vec() }
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u8, String> {
+        let mut code = 0i32;
+        for length in 1..=16 {
+            code = code << 1 | bits.bit()? as i32;
+            if code <= self.max_codes[length] {
+                return self.symbols.get((code + self.offsets[length]) as usize).copied().ok_or("corrupt JPEG data".to_string());
+            }
+        }
+        Err("corrupt JPEG data".to_string())
+    }
+}
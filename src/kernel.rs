@@ -0,0 +1,214 @@
+//! Convolution kernels for filtering pixel buffers: each pixel becomes a weighted sum of the
+//! pixels around it, as in sharpening, blurring, edge detection and embossing. See
+//! `PixelBuffer::convolve`.
+
+use crate::color::Color;
+
+/// How much a kernel's weights may differ from the product of its row and column factors for
+/// it to still count as separable.
+const SEPARABLE_TOLERANCE: f32 = 1e-5;
+
+/// A grid of weights with an odd width and height, centered on the pixel being filtered.
+///
+/// Kernels whose weights are the product of a column and a row of factors, like Gaussian
+/// blurs, are separable: they are applied as a horizontal and a vertical pass, which costs
+/// `width + height` instead of `width * height` multiplications per pixel. This is detected
+/// for any kernel.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::kernel::Kernel;
+/// # fn draw(canvas: &mut Canvas) {
+/// // A diagonal motion blur
+/// let blur = Kernel::new(5, 5, (0..25).map(|i| if i % 6 == 0 { 0.2 } else { 0.0 }).collect()).unwrap();
+/// canvas.pixel_buffer.convolve(&blur);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kernel {
+    width: usize,
+    height: usize,
+    /// The weights row by row, from the top left.
+    weights: Vec<f32>,
+    /// Added to every color channel after weighting, from 0 to 1.
+    bias: f32,
+    /// The column and row factors whose products are the weights, for separable kernels.
+    factors: Option<(Vec<f32>, Vec<f32>)>,
+}
+
+impl Kernel {
+    /// Creates a kernel from its weights, given row by row from the top left. Returns None
+    /// unless the width and height are odd and there are `width * height` weights.
+    pub fn new(width: usize, height: usize, weights: Vec<f32>) -> Option<Self> {
+        if width.is_multiple_of(2) || height.is_multiple_of(2) || weights.len() != width * height {
+            return None;
+        }
+        let factors = factor(width, height, &weights);
+        Some(Kernel { width, height, weights, bias: 0.0, factors })
+    }
+
+    /// Creates a separable kernel from a row of horizontal and a column of vertical factors,
+    /// whose weights are their products. Returns None unless both have an odd length.
+    pub fn separable(horizontal: Vec<f32>, vertical: Vec<f32>) -> Option<Self> {
+        let (width, height) = (horizontal.len(), vertical.len());
+        if width.is_multiple_of(2) || height.is_multiple_of(2) {
+            return None;
+        }
+        let weights = vertical.iter().flat_map(|&v| horizontal.iter().map(move |&h| v * h)).collect();
+        Some(Kernel { width, height, weights, bias: 0.0, factors: Some((vertical, horizontal)) })
+    }
+
+    /// Sets the value added to every color channel after weighting, from 0 to 1, such as 0.5
+    /// to show negative results of edge filters as dark and positive ones as light.
+    pub fn with_bias(mut self, bias: f32) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    /// Scales the weights to add up to 1, so the kernel keeps the brightness of the image.
+    /// Kernels whose weights add up to 0, like edge detection, are left as they are.
+    pub fn normalized(mut self) -> Self {
+        let sum: f32 = self.weights.iter().sum();
+        if sum.abs() > f32::EPSILON {
+            self.weights.iter_mut().for_each(|weight| *weight /= sum);
+            if let Some((_, horizontal)) = &mut self.factors {
+                horizontal.iter_mut().for_each(|factor| *factor /= sum);
+            }
+        }
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    pub fn bias(&self) -> f32 {
+        self.bias
+    }
+
+    /// Whether the kernel is applied as a horizontal and a vertical pass.
+    pub fn is_separable(&self) -> bool {
+        self.factors.is_some()
+    }
+
+    /// Sharpens by subtracting the four neighbors from a weighted center.
+    pub fn sharpen() -> Self {
+        Kernel::new(3, 3, vec![0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0]).expect("the kernel is 3 by 3")
+    }
+
+    /// Finds edges in all directions with a Laplacian, leaving flat areas black.
+    pub fn edge_detect() -> Self {
+        Kernel::new(3, 3, vec![-1.0, -1.0, -1.0, -1.0, 8.0, -1.0, -1.0, -1.0, -1.0]).expect("the kernel is 3 by 3")
+    }
+
+    /// Makes the image look stamped into gray, lit from the top left.
+    pub fn emboss() -> Self {
+        Kernel::new(3, 3, vec![-1.0, -1.0, 0.0, -1.0, 0.0, 1.0, 0.0, 1.0, 1.0]).expect("the kernel is 3 by 3").with_bias(0.5)
+    }
+
+    /// A separable Gaussian blur with a standard deviation of `sigma` pixels, reaching three
+    /// times as far.
+    pub fn gaussian(sigma: f32) -> Self {
+        let sigma = sigma.max(f32::EPSILON);
+        let radius = (sigma * 3.0).ceil() as i32;
+        let factors: Vec<f32> = (-radius..=radius).map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp()).collect();
+        let sum: f32 = factors.iter().sum();
+        let factors: Vec<f32> = factors.iter().map(|factor| factor / sum).collect();
+        Kernel::separable(factors.clone(), factors).expect("the kernel has an odd size")
+    }
+
+    /// A separable blur averaging the pixels up to `radius` away along each axis.
+    pub fn box_blur(radius: usize) -> Self {
+        let size = 2 * radius + 1;
+        let factors = vec![1.0 / size as f32; size];
+        Kernel::separable(factors.clone(), factors).expect("the kernel has an odd size")
+    }
+
+    // Convolves ARGB pixels, repeating the edge pixels past the edges. Colors are weighted
+    // premultiplied by alpha, so transparent pixels don't bleed, and alpha is kept.
+    pub(crate) fn apply(&self, pixels: &[u32], width: usize, height: usize) -> Vec<u32> {
+        if width == 0 || height == 0 {
+            return pixels.to_vec();
+        }
+        let colors: Vec<[f32; 3]> = pixels
+            .iter()
+            .map(|&pixel| {
+                let color = Color(pixel);
+                let alpha = color.a() as f32 / 255.0;
+                [color.r(), color.g(), color.b()].map(|channel| channel as f32 / 255.0 * alpha)
+            })
+            .collect();
+        let at = |x: isize, y: isize| (y.clamp(0, height as isize - 1) as usize) * width + x.clamp(0, width as isize - 1) as usize;
+        let (half_width, half_height) = ((self.width / 2) as isize, (self.height / 2) as isize);
+
+        let filtered: Vec<[f32; 3]> = match &self.factors {
+            Some((vertical, horizontal)) => {
+                let mut rows = vec![[0.0; 3]; colors.len()];
+                for y in 0..height as isize {
+                    for x in 0..width as isize {
+                        rows[at(x, y)] = weighted_sum(horizontal.iter().enumerate().map(|(i, &weight)| (colors[at(x + i as isize - half_width, y)], weight)));
+                    }
+                }
+                let mut columns = vec![[0.0; 3]; colors.len()];
+                for y in 0..height as isize {
+                    for x in 0..width as isize {
+                        columns[at(x, y)] = weighted_sum(vertical.iter().enumerate().map(|(i, &weight)| (rows[at(x, y + i as isize - half_height)], weight)));
+                    }
+                }
+                columns
+            }
+            None => (0..height as isize)
+                .flat_map(|y| (0..width as isize).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    weighted_sum(self.weights.iter().enumerate().map(|(i, &weight)| {
+                        let (dx, dy) = ((i % self.width) as isize - half_width, (i / self.width) as isize - half_height);
+                        (colors[at(x + dx, y + dy)], weight)
+                    }))
+                })
+                .collect(),
+        };
+
+        pixels
+            .iter()
+            .zip(filtered)
+            .map(|(&pixel, color)| {
+                let alpha = Color(pixel).a();
+                if alpha == 0 {
+                    return pixel;
+                }
+                let scale = 255.0 / alpha as f32;
+                let [r, g, b] = color.map(|channel| ((channel * scale + self.bias) * 255.0).round().clamp(0.0, 255.0) as u8);
+                Color::new(r, g, b, alpha).0
+            })
+            .collect()
+    }
+}
+
+fn weighted_sum(samples: impl Iterator<Item = ([f32; 3], f32)>) -> [f32; 3] {
+    samples.fold([0.0; 3], |sum, (color, weight)| [sum[0] + color[0] * weight, sum[1] + color[1] * weight, sum[2] + color[2] * weight])
+}
+
+// Splits weights into a column and a row of factors whose products are the weights, if they
+// are: every row must then be a multiple of the row with the largest weight
+fn factor(width: usize, height: usize, weights: &[f32]) -> Option<(Vec<f32>, Vec<f32>)> {
+    let (pivot, &largest) = weights.iter().enumerate().max_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))?;
+    if largest == 0.0 {
+        return None;
+    }
+    let (column, row) = (pivot % width, pivot / width);
+    let vertical: Vec<f32> = (0..height).map(|y| weights[y * width + column]).collect();
+    let horizontal: Vec<f32> = (0..width).map(|x| weights[row * width + x] / largest).collect();
+    let tolerance = SEPARABLE_TOLERANCE * largest.abs();
+    let separable = (0..height).all(|y| (0..width).all(|x| (vertical[y] * horizontal[x] - weights[y * width + x]).abs() <= tolerance));
+    separable.then_some((vertical, horizontal))
+}
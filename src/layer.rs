@@ -0,0 +1,96 @@
+//! Named layers stacked above a canvas, each an offscreen canvas of its own, composited over
+//! the canvas with an opacity and a blend mode when the frame is shown. See
+//! `Canvas::with_layer`.
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+/// How the colors of a layer combine with the colors below it, as in image editors. The
+/// layer's transparency and opacity then fade the result into what is below.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlendMode {
+    /// The layer covers what is below.
+    #[default]
+    Normal,
+    /// Darkens by multiplying the colors, like stacked slides.
+    Multiply,
+    /// Lightens by multiplying the inverted colors, like overlapping projectors.
+    Screen,
+    /// Adds the colors, for glows and light.
+    Add,
+    /// Multiplies dark and screens light colors below, raising contrast.
+    Overlay,
+    /// Keeps the darker of the two colors in every channel.
+    Darken,
+    /// Keeps the lighter of the two colors in every channel.
+    Lighten,
+    /// The difference between the colors, which turns what is below negative under white.
+    Difference,
+}
+
+impl BlendMode {
+    // Combines a channel of the layer with the channel below, both from 0 to 1
+    fn apply(self, below: f32, layer: f32) -> f32 {
+        match self {
+            BlendMode::Normal => layer,
+            BlendMode::Multiply => below * layer,
+            BlendMode::Screen => below + layer - below * layer,
+            BlendMode::Add => (below + layer).min(1.0),
+            BlendMode::Overlay if below <= 0.5 => 2.0 * below * layer,
+            BlendMode::Overlay => 1.0 - 2.0 * (1.0 - below) * (1.0 - layer),
+            BlendMode::Darken => below.min(layer),
+            BlendMode::Lighten => below.max(layer),
+            BlendMode::Difference => (below - layer).abs(),
+        }
+    }
+}
+
+/// A layer of a canvas: an offscreen canvas the size of the canvas and how it is composited.
+pub struct Layer {
+    pub name: String,
+    /// What is drawn on the layer. It starts out transparent and keeps what is drawn on it
+    /// until it is cleared. It is resized with the canvas, and isn't composited while its
+    /// size differs.
+    pub canvas: Canvas,
+    /// How opaque the whole layer is, from 0 to 1.
+    pub opacity: f32,
+    pub blend_mode: BlendMode,
+    /// Whether the layer is composited. Hidden layers keep what is drawn on them.
+    pub visible: bool,
+}
+
+impl Layer {
+    pub(crate) fn new(name: &str, canvas: Canvas) -> Self {
+        Layer { name: name.to_string(), canvas, opacity: 1.0, blend_mode: BlendMode::Normal, visible: true }
+    }
+
+    // Composites the layer over `pixels`, which are the same size as the layer's buffer
+    pub(crate) fn composite(&self, pixels: &mut [u32]) {
+        let opacity = self.opacity.clamp(0.0, 1.0);
+        // A layer resized on its own no longer lines up with the canvas
+        if !self.visible || opacity <= 0.0 || pixels.len() != self.canvas.pixel_buffer.get_buffer().len() {
+            return;
+        }
+        for (pixel, &source) in pixels.iter_mut().zip(self.canvas.pixel_buffer.get_buffer()) {
+            let source = Color(source);
+            let alpha = source.a() as f32 / 255.0 * opacity;
+            if alpha <= 0.0 {
+                continue;
+            }
+            let below = Color(*pixel);
+            let below_alpha = below.a() as f32 / 255.0;
+            // Where nothing is below, the layer's own color shows
+            let channel = |below: u8, layer: u8| {
+                let (below, layer) = (below as f32 / 255.0, layer as f32 / 255.0);
+                (1.0 - below_alpha) * layer + below_alpha * self.blend_mode.apply(below, layer)
+            };
+            let blended = [channel(below.r(), source.r()), channel(below.g(), source.g()), channel(below.b(), source.b())];
+            let remaining = below_alpha * (1.0 - alpha);
+            let out_alpha = alpha + remaining;
+            let mix = |below: u8, blended: f32| ((blended * alpha + below as f32 / 255.0 * remaining) / out_alpha * 255.0).round().clamp(0.0, 255.0) as u8;
+            let (r, g, b) = (mix(below.r(), blended[0]), mix(below.g(), blended[1]), mix(below.b(), blended[2]));
+            *pixel = Color::new(r, g, b, (out_alpha * 255.0).round() as u8).0;
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! A small CPU-rendered creative coding framework in the spirit of Processing.
+
+pub mod canvas;
+pub mod color;
+pub mod shape;
+pub mod geom;
+pub mod pixelbuffer;
+pub mod easing;
+pub mod timeline;
+pub mod motion;
+pub mod debug_graph;
+pub mod command;
+pub mod instance;
+pub mod pick;
+pub mod font;
+pub mod input;
+pub mod app;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod gallery;
+pub mod backend;
+pub mod terminal;
+pub mod renderer;
+pub mod error;
+pub mod cursor;
+pub mod recorder;
+pub mod image;
+pub mod tiled;
+pub mod svg;
+pub mod scene;
+pub mod display_list;
+pub mod history;
+pub mod gui;
+pub mod params;
+pub mod hdr;
+pub mod data;
+pub mod palette;
+pub mod assets;
+pub mod layer;
+pub mod postprocess;
+pub mod kernel;
+pub mod glitch;
+pub mod math;
+pub mod tween;
+pub mod physics;
+pub mod field;
+pub mod axis;
+pub mod metaball;
+pub mod ik;
+pub mod flock;
+pub mod profiler;
+pub mod frame_history;
+pub mod snapshot;
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod osc;
+#[cfg(feature = "serial")]
+pub mod serial;
+#[cfg(feature = "script")]
+pub mod script;
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub mod web;
+mod mask_cache;
+mod raster;
+mod png;
+mod clipboard;
+mod pdf;
+mod jpeg;
+mod bitmap_font;
+mod shaping;
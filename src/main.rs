@@ -1,14 +1,18 @@
 mod sketch;
-mod canvas;
-mod color;
-mod shape;
-mod geom;
-mod pixelbuffer;
 
+use simple_sketch::error::SketchError;
 use sketch::Sketch;
 
-fn main() -> Result<(), String> {
-    let mut sketch = Sketch::new()?;
-    sketch.run();
-    Ok(())
-}
\ No newline at end of file
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> Result<(), SketchError> {
+    simple_sketch::app::run::<Sketch>()
+}
+
+// In a web page, the sketch draws into `<canvas id="sketch">`
+#[cfg(all(target_arch = "wasm32", feature = "web"))]
+fn main() -> Result<(), SketchError> {
+    simple_sketch::web::run::<Sketch>("sketch")
+}
+
+#[cfg(all(target_arch = "wasm32", not(feature = "web")))]
+compile_error!("sketches need the `web` feature to run on wasm32");
@@ -0,0 +1,234 @@
+//! Caching of rasterized shapes that are drawn many times.
+//!
+//! Shapes like the small ellipses of a particle system are usually drawn at many positions
+//! with the same size. The first time a shape is drawn, its fill or stroke coverage is
+//! rasterized into a `PixelMask` aligned to the pixel grid, and every later draw of the same
+//! shape only blends the stored coverage at its new position. Positions are rounded to a
+//! quarter pixel so that a handful of masks per shape keep sub-pixel motion smooth.
+
+use std::collections::HashMap;
+
+use crate::color::Color;
+use crate::geom::Point;
+use crate::pixelbuffer::PixelBuffer;
+use crate::raster::{self, FillCoverage, Precision, StrokeCoverage};
+use crate::shape::Shape;
+
+/// Number of sub-pixel positions per pixel along each axis that get their own mask.
+const SUBPIXEL_STEPS: f32 = 4.0;
+
+/// Number of masks kept before the cache is emptied, to bound its memory use.
+const MAX_CACHED_MASKS: usize = 1024;
+
+/// Largest area in pixels of a shape's bounding box for it to be cached. Big shapes are
+/// rarely repeated, and drawing them directly costs little compared to their mask's memory.
+const MAX_MASK_AREA: f32 = 128.0 * 128.0;
+
+/// Identifies a shape by everything except its position. Sizes are stored as `f32` bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ShapeKey {
+    Ellipse { width: u32, height: u32 },
+    Circle { diameter: u32 },
+    RoundedRect { width: u32, height: u32, radii: [u32; 4] },
+    Star { points: usize, inner_radius: u32, outer_radius: u32, rotation: u32 },
+    RegularPolygon { sides: usize, radius: u32, rotation: u32 },
+    /// The one shape of a batch, such as `Canvas::stamp`, in a cache of its own.
+    Prototype,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct MaskKey {
+    shape: ShapeKey,
+    /// Sub-pixel position of the shape's anchor, in steps.
+    phase: (u8, u8),
+    layer: MaskLayer,
+}
+
+/// What part of a shape a mask covers. Stroke weights are stored as `f32` bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum MaskLayer {
+    Fill,
+    /// The fill of a shape stroked with a weight, up to the stroke's inner edge.
+    FillInsideStroke(u32),
+    Stroke(u32),
+}
+
+/// Coverage of a shape drawn with its anchor at a fixed sub-pixel position.
+struct PixelMask {
+    /// Offset of the first mask pixel from the pixel containing the anchor.
+    x: i32,
+    y: i32,
+    width: usize,
+    coverage: Vec<f32>,
+}
+
+impl PixelMask {
+    // Rasterizes a fill the same way the canvas does.
+    fn fill(shape: &impl Shape, precision: Precision) -> Self {
+        let (x1, y1, x2, y2) = pixel_bounds(shape, 0.0);
+        let coverage = FillCoverage::new(shape, (x1, y1), (x2, y2), precision);
+        PixelMask::rasterize(shape, 0.0, |point| coverage.at(point.x as i32, point.y as i32))
+    }
+
+    // Rasterizes the fill of a stroked shape the same way the canvas does, stopping where the
+    // stroke starts.
+    fn fill_inside_stroke(shape: &impl Shape, weight: f32, precision: Precision) -> Self {
+        let (x1, y1, x2, y2) = pixel_bounds(shape, 0.0);
+        let coverage = FillCoverage::new(shape, (x1, y1), (x2, y2), precision);
+        let stroke = StrokeCoverage::new(shape, weight, (x1, y1), (x2, y2), precision);
+        PixelMask::rasterize(shape, 0.0, |point| {
+            let (x, y) = (point.x as i32, point.y as i32);
+            stroke.beside_fill(x, y, coverage.at(x, y)).0
+        })
+    }
+
+    // Rasterizes a stroke the same way the canvas does.
+    fn stroke(shape: &impl Shape, weight: f32, precision: Precision) -> Self {
+        let margin = weight.max(0.0) / 2.0 + 1.0;
+        let (x1, y1, x2, y2) = pixel_bounds(shape, margin);
+        let coverage = StrokeCoverage::new(shape, weight, (x1, y1), (x2, y2), precision);
+        PixelMask::rasterize(shape, margin, |point| coverage.beside_fill(point.x as i32, point.y as i32, 0.0).1)
+    }
+
+    fn rasterize(shape: &impl Shape, margin: f32, coverage: impl Fn(Point) -> f32) -> Self {
+        let (x1, y1, x2, y2) = pixel_bounds(shape, margin);
+        let width = (x2 - x1 + 1).max(0) as usize;
+        let height = (y2 - y1 + 1).max(0) as usize;
+
+        let mut values = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                values.push(coverage(Point::new((x1 + x as i32) as f32, (y1 + y as i32) as f32)));
+            }
+        }
+        PixelMask { x: x1, y: y1, width, coverage: values }
+    }
+
+    // The coverage of the mask pixel at `(column, row)`, 0.0 outside the mask
+    fn at(&self, column: i32, row: i32) -> f32 {
+        let height = self.coverage.len().checked_div(self.width).unwrap_or(0);
+        if column < 0 || row < 0 || column as usize >= self.width || row as usize >= height {
+            return 0.0;
+        }
+        self.coverage[row as usize * self.width + column as usize]
+    }
+
+    // The pixels the mask covers when drawn with its anchor in the pixel at `(x, y)`
+    fn bounds(&self, x: i32, y: i32) -> (i32, i32, i32, i32) {
+        let height = self.coverage.len().checked_div(self.width).unwrap_or(0);
+        (x + self.x, y + self.y, x + self.x + self.width as i32 - 1, y + self.y + height as i32 - 1)
+    }
+}
+
+// Blends a fill and a stroke mask drawn with their anchor in the pixel at `(x, y)` over the
+// buffer, blending each pixel once with both, like the canvas does.
+fn blend(buffer: &mut PixelBuffer, x: i32, y: i32, fill: Option<(&PixelMask, Color)>, stroke: Option<(&PixelMask, Color)>) {
+    let Some((x1, y1, x2, y2)) = fill.iter().chain(&stroke).map(|(mask, _)| mask.bounds(x, y)).reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3))) else {
+        return;
+    };
+    let (x1, y1) = (x1.max(0), y1.max(0));
+    let (x2, y2) = (x2.min(buffer.width as i32 - 1), y2.min(buffer.height as i32 - 1));
+    let paint = |layer: Option<(&PixelMask, Color)>, px: i32, py: i32| {
+        layer.map_or([0.0; 4], |(mask, color)| raster::premultiplied(color, mask.at(px - x - mask.x, py - y - mask.y)))
+    };
+    let width = buffer.width;
+    let pixels = buffer.get_buffer_mut();
+    for py in y1..=y2 {
+        for px in x1..=x2 {
+            let color = raster::side_by_side(paint(fill, px, py), paint(stroke, px, py));
+            raster::blend_premultiplied(&mut pixels[py as usize * width + px as usize], color);
+        }
+    }
+}
+
+/// Rasterized fills and strokes of recently drawn shapes.
+#[derive(Default)]
+pub(crate) struct MaskCache {
+    masks: HashMap<MaskKey, PixelMask>,
+    /// How the masks are rasterized.
+    precision: Precision,
+}
+
+impl MaskCache {
+    /// An empty cache whose masks are rasterized with `precision`.
+    pub fn new(precision: Precision) -> Self {
+        MaskCache { masks: HashMap::new(), precision }
+    }
+
+    /// Draws a shape's fill and stroke through the cache.
+    ///
+    /// `place` creates the shape with its anchor at a given point. The cached masks are made
+    /// with the anchor near the origin and blended at `anchor`, rounded to a quarter pixel.
+    /// Returns false without drawing if the shape is too big to be worth caching.
+    pub fn draw<S: Shape>(
+        &mut self,
+        buffer: &mut PixelBuffer,
+        key: ShapeKey,
+        anchor: Point,
+        place: impl Fn(Point) -> S,
+        fill: Option<Color>,
+        stroke: Option<(Color, f32)>,
+    ) -> bool {
+        if !(anchor.x.is_finite() && anchor.y.is_finite()) {
+            return false;
+        }
+        let (x, phase_x) = quantize(anchor.x);
+        let (y, phase_y) = quantize(anchor.y);
+        let shape = place(Point::new(phase_x as f32, phase_y as f32) * (1.0 / SUBPIXEL_STEPS));
+        let (top_left, bottom_right) = shape.bounding_box();
+        let size = bottom_right - top_left;
+        // Masks cover whole pixels around the bounding box, and strokes reach past it by half
+        // their weight, so flat shapes still have an area. Shapes with a negative width or
+        // height have a negative bounding box area
+        let margin = 2.0 + stroke.map_or(0.0, |(_, weight)| weight.max(0.0) + 2.0);
+        let area = (size.x.abs() + margin) * (size.y.abs() + margin);
+        if area.is_nan() || area > MAX_MASK_AREA {
+            return false;
+        }
+
+        if self.masks.len() >= MAX_CACHED_MASKS {
+            self.masks.clear();
+        }
+        let phase = (phase_x, phase_y);
+        let fill_key = MaskKey {
+            shape: key,
+            phase,
+            layer: stroke.map_or(MaskLayer::Fill, |(_, weight)| MaskLayer::FillInsideStroke(weight.to_bits())),
+        };
+        let stroke_key = stroke.map(|(_, weight)| MaskKey { shape: key, phase, layer: MaskLayer::Stroke(weight.to_bits()) });
+        if fill.is_some() {
+            self.masks.entry(fill_key).or_insert_with(|| match stroke {
+                Some((_, weight)) => PixelMask::fill_inside_stroke(&shape, weight, self.precision),
+                None => PixelMask::fill(&shape, self.precision),
+            });
+        }
+        if let (Some(stroke_key), Some((_, weight))) = (stroke_key, stroke) {
+            self.masks.entry(stroke_key).or_insert_with(|| PixelMask::stroke(&shape, weight, self.precision));
+        }
+        let fill = fill.and_then(|color| Some((self.masks.get(&fill_key)?, color)));
+        let stroke = stroke.and_then(|(color, _)| Some((self.masks.get(&stroke_key?)?, color)));
+        blend(buffer, x, y, fill, stroke);
+        true
+    }
+}
+
+// The first and last pixel columns and rows touched by a shape grown by `margin`.
+fn pixel_bounds(shape: &impl Shape, margin: f32) -> (i32, i32, i32, i32) {
+    let (top_left, bottom_right) = shape.bounding_box();
+    let x1 = (top_left.x - margin).floor() as i32;
+    let y1 = (top_left.y - margin).floor() as i32;
+    let x2 = (bottom_right.x + margin).ceil() as i32;
+    let y2 = (bottom_right.y + margin).ceil() as i32;
+    (x1, y1, x2, y2)
+}
+
+// Splits a coordinate into its whole pixel and its rounded sub-pixel step.
+fn quantize(value: f32) -> (i32, u8) {
+    let pixel = value.floor();
+    let step = ((value - pixel) * SUBPIXEL_STEPS).round();
+    if step >= SUBPIXEL_STEPS {
+        (pixel as i32 + 1, 0)
+    } else {
+        (pixel as i32, step as u8)
+    }
+}
@@ -0,0 +1,107 @@
+//! Small numeric helpers from Processing for mapping values between ranges, such as mouse
+//! positions to colors or time to sizes.
+
+/// Maps `value` from the range `start1..stop1` to the range `start2..stop2`, like Processing's
+/// `map()`. Values outside the first range end up outside the second; see `constrain`. Either
+/// range may run backwards.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::math;
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::geom::Point;
+/// # fn draw(canvas: &Canvas, mouse: Point) {
+/// // The further right the mouse, the larger the circle
+/// let size = math::map(mouse.x, 0.0, canvas.width as f32, 10.0, 200.0);
+/// # }
+/// assert_eq!(math::map(5.0, 0.0, 10.0, 100.0, 0.0), 50.0);
+/// ```
+pub fn map(value: f32, start1: f32, stop1: f32, start2: f32, stop2: f32) -> f32 {
+    lerp(start2, stop2, norm(value, start1, stop1))
+}
+
+/// The value `amount` of the way from `start` to `stop`: `start` at 0, `stop` at 1, and
+/// beyond them below 0 and above 1.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::math;
+/// assert_eq!(math::lerp(10.0, 20.0, 0.25), 12.5);
+/// ```
+pub fn lerp(start: f32, stop: f32, amount: f32) -> f32 {
+    start + (stop - start) * amount
+}
+
+/// Limits `value` to the range from `low` to `high`, like Processing's `constrain()`. Unlike
+/// `f32::clamp` it doesn't panic when `low` is above `high`, and returns `high` then.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::math;
+/// assert_eq!(math::constrain(1.5, 0.0, 1.0), 1.0);
+/// ```
+pub fn constrain(value: f32, low: f32, high: f32) -> f32 {
+    value.max(low).min(high)
+}
+
+/// How far `value` is from `start` to `stop`, as 0 at `start` and 1 at `stop`; the inverse of
+/// `lerp`. Returns 0 for an empty range.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::math;
+/// assert_eq!(math::norm(15.0, 10.0, 20.0), 0.5);
+/// ```
+pub fn norm(value: f32, start: f32, stop: f32) -> f32 {
+    if start == stop {
+        return 0.0;
+    }
+    (value - start) / (stop - start)
+}
+
+/// Eases from 0 at `edge0` to 1 at `edge1` along a smooth S-curve, as in GLSL's
+/// `smoothstep()`: 0 before `edge0`, 1 after `edge1`, and with a flat start and end, for
+/// soft edges and fades.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::math;
+/// # use simple_sketch::app::Context;
+/// # fn draw(ctx: &Context) {
+/// // Fade in over the first two seconds
+/// let alpha = math::smoothstep(0.0, 2.0, ctx.seconds_since_start()) * 255.0;
+/// # }
+/// assert_eq!(math::smoothstep(0.0, 1.0, 0.5), 0.5);
+/// ```
+pub fn smoothstep(edge0: f32, edge1: f32, value: f32) -> f32 {
+    if edge0 == edge1 {
+        return if value < edge0 { 0.0 } else { 1.0 };
+    }
+    let t = ((value - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Converts an angle in radians, as used by the canvas, to degrees.
+pub fn degrees(radians: f32) -> f32 {
+    radians.to_degrees()
+}
+
+/// Converts an angle in degrees to radians, as used by the canvas.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::math;
+/// # use simple_sketch::canvas::Canvas;
+/// # fn draw(canvas: &mut Canvas) {
+/// canvas.rotate(math::radians(45.0));
+/// # }
+/// ```
+pub fn radians(degrees: f32) -> f32 {
+    degrees.to_radians()
+}
@@ -0,0 +1,273 @@
+//! Metaballs: blobby shapes that melt together where their balls come close, like drops of
+//! mercury. See `Canvas::draw_metaballs`.
+//!
+//! Each ball adds a smooth bump to a field, and the shapes are where the field reaches the
+//! threshold. The field is only evaluated on a coarse grid and interpolated in between, so
+//! drawing doesn't cost a sum over all balls per pixel. The grid can also be traced into an
+//! outline with marching squares, for exporting or stroking the shapes as paths.
+
+use std::collections::HashMap;
+
+use crate::geom::{Path, Point, Rect};
+use crate::shape::Shape;
+
+/// The field of a lone ball at its radius, where its shape ends.
+const SURFACE: f32 = 0.5625;
+
+/// A source of the field. Alone, it is a disk of `radius`; it reaches twice as far into the
+/// field, so balls closer than that melt together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metaball {
+    pub center: Point,
+    pub radius: f32,
+}
+
+impl Metaball {
+    pub fn new(center: Point, radius: f32) -> Self {
+        Metaball { center, radius }
+    }
+
+    // The contribution of the ball to the field at a point: 1 at its radius, fading smoothly
+    // to 0 at twice its radius
+    fn field(&self, point: Point) -> f32 {
+        let reach = 2.0 * self.radius;
+        if reach <= 0.0 {
+            return 0.0;
+        }
+        let offset = point - self.center;
+        let t = (offset.x * offset.x + offset.y * offset.y) / (reach * reach);
+        if t >= 1.0 {
+            0.0
+        } else {
+            (1.0 - t) * (1.0 - t) / SURFACE
+        }
+    }
+}
+
+/// A set of metaballs and the field value their shapes end at.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::app::Context;
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::geom::Point;
+/// # use simple_sketch::metaball::Metaballs;
+/// # fn draw(canvas: &mut Canvas, ctx: &Context) {
+/// let mut blobs = Metaballs::new();
+/// for i in 0..5 {
+///     let angle = ctx.seconds_since_start() + i as f32;
+///     blobs.add(Point::new(320.0 + 100.0 * angle.cos(), 180.0 + 60.0 * (angle * 1.3).sin()), 30.0);
+/// }
+/// canvas.draw_metaballs(&blobs);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metaballs {
+    pub balls: Vec<Metaball>,
+    /// Where the shapes end. At the default of 1 a lone ball is a disk of its radius; lower
+    /// values make the shapes fatter and melt them together from further apart.
+    pub threshold: f32,
+}
+
+impl Default for Metaballs {
+    fn default() -> Self {
+        Metaballs { balls: Vec::new(), threshold: 1.0 }
+    }
+}
+
+impl Metaballs {
+    pub fn new() -> Self {
+        Metaballs::default()
+    }
+
+    pub fn add(&mut self, center: Point, radius: f32) {
+        self.balls.push(Metaball::new(center, radius));
+    }
+
+    /// The value of the field at `point`, at least `threshold` inside the shapes.
+    pub fn field(&self, point: Point) -> f32 {
+        self.balls.iter().map(|ball| ball.field(point)).sum()
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.field(point) >= self.threshold
+    }
+
+    /// The area the balls reach into the field, which holds the shapes. None without balls.
+    pub fn bounding_box(&self) -> Option<Rect> {
+        self.balls
+            .iter()
+            .filter(|ball| ball.radius > 0.0)
+            .map(|ball| {
+                let reach = Point::new(2.0 * ball.radius, 2.0 * ball.radius);
+                Rect::new(ball.center - reach, ball.center + reach)
+            })
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// Traces the outlines of the shapes on a grid of `cell_size` squares into a path of
+    /// closed contours, with holes wound the other way. Smaller cells follow the field more
+    /// closely and cost more.
+    pub fn to_path(&self, cell_size: f32) -> Path {
+        let mut path = Path::new();
+        let Some(grid) = self.grid(cell_size) else {
+            return path;
+        };
+        let (columns, rows) = (grid.columns, grid.rows);
+        // Where the field crosses the threshold on an edge, by the edge's first corner and
+        // whether it is horizontal
+        let crossing = |(i, j, horizontal): Edge| {
+            let (a, b) = if horizontal { ((i, j), (i + 1, j)) } else { ((i, j), (i, j + 1)) };
+            let (va, vb) = (grid.value(a.0, a.1), grid.value(b.0, b.1));
+            let t = if va == vb { 0.5 } else { ((self.threshold - va) / (vb - va)).clamp(0.0, 1.0) };
+            grid.corner(a.0, a.1).lerp(&grid.corner(b.0, b.1), t)
+        };
+
+        // Every cell links the edge where its contour enters the shape, going clockwise around
+        // the cell, to the edge where it leaves. Neighbors share edges in the opposite
+        // direction, so the links chain into closed contours.
+        let mut next: HashMap<Edge, Edge> = HashMap::new();
+        for j in 0..rows - 1 {
+            for i in 0..columns - 1 {
+                let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                let inside = corners.map(|(x, y)| grid.value(x, y) >= self.threshold);
+                // The edges from each corner to the next, clockwise on the screen
+                let edges = [(i, j, true), (i + 1, j, false), (i, j + 1, true), (i, j, false)];
+                let crossings: Vec<(Edge, bool)> = (0..4).filter(|&k| inside[k] != inside[(k + 1) % 4]).map(|k| (edges[k], !inside[k])).collect();
+                if crossings.is_empty() {
+                    continue;
+                }
+                // Saddles join the inside corners if the center of the cell is inside
+                let center_inside = corners.iter().map(|&(x, y)| grid.value(x, y)).sum::<f32>() / 4.0 >= self.threshold;
+                let count = crossings.len();
+                for k in 0..count {
+                    let (edge, entering) = crossings[k];
+                    if !entering {
+                        continue;
+                    }
+                    let exit = if center_inside && count == 4 { crossings[(k + count - 1) % count].0 } else { crossings[(k + 1) % count].0 };
+                    next.insert(edge, exit);
+                }
+            }
+        }
+
+        while let Some(&start) = next.keys().next() {
+            let mut edge = start;
+            path.move_to(crossing(edge));
+            while let Some(following) = next.remove(&edge) {
+                if following == start {
+                    break;
+                }
+                path.line_to(crossing(following));
+                edge = following;
+            }
+            path.close();
+        }
+        path
+    }
+
+    // The field sampled on a grid of `cell_size` squares over the bounding box, with a cell
+    // of margin so that the border of the grid is outside the shapes
+    pub(crate) fn grid(&self, cell_size: f32) -> Option<FieldGrid> {
+        let bounds = self.bounding_box()?;
+        if cell_size <= 0.0 || self.threshold <= 0.0 {
+            return None;
+        }
+        let origin = bounds.min - Point::new(cell_size, cell_size);
+        let columns = (bounds.width() / cell_size).ceil() as usize + 3;
+        let rows = (bounds.height() / cell_size).ceil() as usize + 3;
+        let mut grid = FieldGrid { origin, cell_size, columns, rows, values: vec![0.0; columns * rows], threshold: self.threshold };
+        // Each ball only adds to the corners within its reach
+        for ball in &self.balls {
+            let reach = 2.0 * ball.radius;
+            if reach <= 0.0 {
+                continue;
+            }
+            let first = |value: f32, start: f32| ((value - reach - start) / cell_size).floor().max(0.0) as usize;
+            let last = |value: f32, start: f32, count: usize| (((value + reach - start) / cell_size).ceil().max(0.0) as usize).min(count - 1);
+            for j in first(ball.center.y, origin.y)..=last(ball.center.y, origin.y, rows) {
+                for i in first(ball.center.x, origin.x)..=last(ball.center.x, origin.x, columns) {
+                    grid.values[j * columns + i] += ball.field(grid.corner(i, j));
+                }
+            }
+        }
+        Some(grid)
+    }
+}
+
+/// The field of metaballs sampled on a grid and interpolated bilinearly in between, as a
+/// shape that costs the same to rasterize however many balls there are.
+pub(crate) struct FieldGrid {
+    origin: Point,
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    values: Vec<f32>,
+    threshold: f32,
+}
+
+impl FieldGrid {
+    fn corner(&self, i: usize, j: usize) -> Point {
+        self.origin + Point::new(i as f32 * self.cell_size, j as f32 * self.cell_size)
+    }
+
+    fn value(&self, i: usize, j: usize) -> f32 {
+        self.values[j * self.columns + i]
+    }
+
+    // The interpolated field and its gradient at a point
+    fn sample(&self, point: Point) -> (f32, Point) {
+        let gx = ((point.x - self.origin.x) / self.cell_size).clamp(0.0, (self.columns - 1) as f32);
+        let gy = ((point.y - self.origin.y) / self.cell_size).clamp(0.0, (self.rows - 1) as f32);
+        let (i, j) = ((gx as usize).min(self.columns - 2), (gy as usize).min(self.rows - 2));
+        let (fx, fy) = (gx - i as f32, gy - j as f32);
+        let (a, b, c, d) = (self.value(i, j), self.value(i + 1, j), self.value(i, j + 1), self.value(i + 1, j + 1));
+        let top = a + (b - a) * fx;
+        let bottom = c + (d - c) * fx;
+        let gradient = Point::new((b - a) + ((d - c) - (b - a)) * fy, bottom - top) * (1.0 / self.cell_size);
+        (top + (bottom - top) * fy, gradient)
+    }
+}
+
+impl Shape for FieldGrid {
+    fn contains(&self, point: Point) -> bool {
+        self.sample(point).0 >= self.threshold
+    }
+
+    fn bounding_box(&self) -> (Point, Point) {
+        (self.origin, self.corner(self.columns - 1, self.rows - 1))
+    }
+
+    // Estimates the distance from how far the field is from the threshold and how steep it is
+    fn distance(&self, point: Point) -> f32 {
+        let (value, gradient) = self.sample(point);
+        let steepness = gradient.length();
+        if steepness <= f32::EPSILON {
+            return if value >= self.threshold { -f32::MAX } else { f32::MAX };
+        }
+        (self.threshold - value) / steepness
+    }
+
+    fn area(&self) -> f32 {
+        let cells = self.values.iter().filter(|&&value| value >= self.threshold).count();
+        cells as f32 * self.cell_size * self.cell_size
+    }
+
+    // Estimates the length of the outline from the number of grid edges it crosses
+    fn perimeter(&self) -> f32 {
+        let inside = |i: usize, j: usize| self.value(i, j) >= self.threshold;
+        let mut crossings = 0;
+        for j in 0..self.rows {
+            for i in 0..self.columns {
+                crossings += usize::from(i + 1 < self.columns && inside(i, j) != inside(i + 1, j));
+                crossings += usize::from(j + 1 < self.rows && inside(i, j) != inside(i, j + 1));
+            }
+        }
+        crossings as f32 * self.cell_size * std::f32::consts::FRAC_PI_4
+    }
+}
+
+type Edge = (usize, usize, bool);
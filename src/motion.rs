@@ -0,0 +1,152 @@
+//! Reusable procedural motion generators.
+//!
+//! Each generator produces a `Point` or `Transform` for the current frame, so sketches can
+//! add camera shake, smooth following or orbiting without re-implementing the maths.
+//! Generators driven by time take the time explicitly, which lets them share a clock with
+//! a `Timeline`.
+
+use crate::geom::{Point, Transform};
+use crate::timeline::Timeline;
+
+/// Smooth, noise-based shake suitable for camera or object jitter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shake {
+    /// Maximum translation in pixels.
+    pub amplitude: f32,
+    /// Maximum rotation in radians.
+    pub max_angle: f32,
+    /// How many noise features pass per second; higher values shake faster.
+    pub frequency: f32,
+    /// Selects a different, repeatable noise pattern.
+    pub seed: u32,
+}
+
+impl Shake {
+    pub fn new(amplitude: f32, max_angle: f32, frequency: f32, seed: u32) -> Self {
+        Shake { amplitude, max_angle, frequency, seed }
+    }
+
+    /// Returns the translation at `time` seconds, scaled by `intensity` (usually 0 to 1).
+    pub fn offset(&self, time: f32, intensity: f32) -> Point {
+        let x = time * self.frequency;
+        Point::new(noise(x, self.seed), noise(x, self.seed.wrapping_add(1))) * (self.amplitude * intensity)
+    }
+
+    /// Returns the rotation in radians at `time` seconds, scaled by `intensity`.
+    pub fn angle(&self, time: f32, intensity: f32) -> f32 {
+        noise(time * self.frequency, self.seed.wrapping_add(2)) * self.max_angle * intensity
+    }
+
+    /// Returns the shake as a transform that rotates around `pivot` and then translates.
+    /// Apply it to the whole scene (e.g. with the screen center as pivot) for camera shake.
+    pub fn transform(&self, time: f32, intensity: f32, pivot: Point) -> Transform {
+        Transform::translate(pivot * -1.0)
+            .then(&Transform::rotate(self.angle(time, intensity)))
+            .then(&Transform::translate(pivot + self.offset(time, intensity)))
+    }
+
+    /// Like `transform`, with the time taken from `timeline` and the intensity read from its
+    /// float track `intensity_track`, so shakes can be keyframed. A missing track means no shake.
+    pub fn transform_from_timeline(&self, timeline: &Timeline, intensity_track: &str, pivot: Point) -> Transform {
+        let intensity = timeline.value::<f32>(intensity_track).unwrap_or(0.0);
+        self.transform(timeline.time(), intensity, pivot)
+    }
+}
+
+/// A damped spring that smoothly follows a moving target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spring {
+    pub position: Point,
+    pub velocity: Point,
+    /// Pull towards the target; higher values follow more tightly.
+    pub stiffness: f32,
+    /// Velocity damping; low values overshoot and wobble, high values settle slowly.
+    pub damping: f32,
+}
+
+impl Spring {
+    pub fn new(position: Point, stiffness: f32, damping: f32) -> Self {
+        Spring { position, velocity: Point::new(0.0, 0.0), stiffness, damping }
+    }
+
+    /// Advances the spring towards `target` by `dt` seconds and returns the new position.
+    pub fn update(&mut self, target: Point, dt: f32) -> Point {
+        // Semi-implicit Euler integration stays stable for typical frame times
+        let acceleration = (target - self.position) * self.stiffness - self.velocity * self.damping;
+        self.velocity = self.velocity + acceleration * dt;
+        self.position = self.position + self.velocity * dt;
+        self.position
+    }
+
+    /// Advances the spring towards the value of the point track `target_track` at the
+    /// timeline's current time. The spring stays put if the track is missing.
+    pub fn update_from_timeline(&mut self, timeline: &Timeline, target_track: &str, dt: f32) -> Point {
+        let target = timeline.value::<Point>(target_track).unwrap_or(self.position);
+        self.update(target, dt)
+    }
+}
+
+/// Circular (or elliptical) motion around a center point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Orbit {
+    pub center: Point,
+    pub radius_x: f32,
+    pub radius_y: f32,
+    /// Angular speed in radians per second; negative values orbit the other way.
+    pub speed: f32,
+    /// Starting angle in radians.
+    pub phase: f32,
+}
+
+impl Orbit {
+    pub fn new(center: Point, radius: f32, speed: f32) -> Self {
+        Orbit { center, radius_x: radius, radius_y: radius, speed, phase: 0.0 }
+    }
+
+    /// Returns the angle in radians at `time` seconds.
+    pub fn angle(&self, time: f32) -> f32 {
+        self.phase + self.speed * time
+    }
+
+    /// Returns the position on the orbit at `time` seconds.
+    pub fn point_at(&self, time: f32) -> Point {
+        let angle = self.angle(time);
+        self.center + Point::new(self.radius_x * angle.cos(), self.radius_y * angle.sin())
+    }
+
+    /// Returns a transform that moves the origin to the orbit position and rotates it to
+    /// face along the direction of travel.
+    pub fn transform(&self, time: f32) -> Transform {
+        let angle = self.angle(time);
+        let heading = angle + self.speed.signum() * std::f32::consts::FRAC_PI_2;
+        Transform::rotate(heading).then(&Transform::translate(self.point_at(time)))
+    }
+}
+
+// Smooth 1D gradient noise in the range [-1, 1]
+fn noise(x: f32, seed: u32) -> f32 {
+    let cell = x.floor();
+    let t = x - cell;
+    let i = cell as i32;
+
+    let g0 = gradient(i, seed);
+    let g1 = gradient(i.wrapping_add(1), seed);
+
+    // Quintic fade curve for continuous first and second derivatives
+    let fade = t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let n0 = g0 * t;
+    let n1 = g1 * (t - 1.0);
+    // The raw range is [-0.5, 0.5]
+    (n0 + (n1 - n0) * fade) * 2.0
+}
+
+// Pseudo-random gradient in [-1, 1] for an integer lattice point
+fn gradient(i: i32, seed: u32) -> f32 {
+    let mut h = (i as u32).wrapping_mul(0x9E37_79B9) ^ seed.wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846C_A68B);
+    h ^= h >> 16;
+    (h as f32 / u32::MAX as f32) * 2.0 - 1.0
+}
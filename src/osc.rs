@@ -0,0 +1,457 @@
+//! Sending and receiving Open Sound Control messages over UDP, for controlling sketches from
+//! TouchOSC, Max or SuperCollider and for coordinating the machines of an installation.
+//!
+//! An `OscReceiver` listens on a port and returns the messages that arrived since it was last
+//! polled, without blocking the frame. An `OscRouter` passes them to the callbacks whose
+//! address patterns they match, along with the sketch state they update:
+//!
+//! ```no_run
+//! # use simple_sketch::error::SketchError;
+//! # use simple_sketch::osc::{OscReceiver, OscRouter};
+//! # struct Params { speed: f32, hue: f32 }
+//! # struct Sketch { receiver: OscReceiver, router: OscRouter<Params>, params: Params }
+//! # impl Sketch {
+//! # fn new() -> Result<Self, SketchError> {
+//! // In the sketch's constructor
+//! let receiver = OscReceiver::bind(9000)?;
+//! let mut router = OscRouter::new();
+//! router.add("/speed", |params: &mut Params, message| params.speed = message.float(0).unwrap_or(1.0));
+//! router.add("/color/*", |params: &mut Params, message| params.hue = message.float(0).unwrap_or(0.0));
+//! # Ok(Sketch { receiver, router, params: Params { speed: 1.0, hue: 0.0 } })
+//! # }
+//! # fn update(&mut self) {
+//!
+//! // In update
+//! for message in self.receiver.poll() {
+//!     self.router.dispatch(&mut self.params, &message);
+//! }
+//! # }
+//! # }
+//! ```
+
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+
+use crate::error::SketchError;
+
+/// The largest datagram a receiver reads. Larger packets are cut off and fail to decode.
+const MAX_PACKET_SIZE: usize = 65_536;
+
+/// Nesting depth of bundles past which packets are rejected.
+const MAX_BUNDLE_DEPTH: usize = 16;
+
+/// An argument of an OSC message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    String(String),
+    Blob(Vec<u8>),
+    Long(i64),
+    Double(f64),
+    Bool(bool),
+    Nil,
+}
+
+impl OscArg {
+    /// The argument as a number, converting any numeric type and booleans, for faders and
+    /// toggles that send ints in one app and floats in another.
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            OscArg::Int(value) => Some(*value as f32),
+            OscArg::Float(value) => Some(*value),
+            OscArg::Long(value) => Some(*value as f32),
+            OscArg::Double(value) => Some(*value as f32),
+            OscArg::Bool(value) => Some(if *value { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+
+    /// The argument as an integer, converting numeric types and booleans; floats are rounded.
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            OscArg::Int(value) => Some(*value),
+            OscArg::Long(value) => i32::try_from(*value).ok(),
+            _ => self.as_f32().map(|value| value.round() as i32),
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            OscArg::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    // The type tag of the argument
+    fn tag(&self) -> u8 {
+        match self {
+            OscArg::Int(_) => b'i',
+            OscArg::Float(_) => b'f',
+            OscArg::String(_) => b's',
+            OscArg::Blob(_) => b'b',
+            OscArg::Long(_) => b'h',
+            OscArg::Double(_) => b'd',
+            OscArg::Bool(true) => b'T',
+            OscArg::Bool(false) => b'F',
+            OscArg::Nil => b'N',
+        }
+    }
+}
+
+impl From<i32> for OscArg {
+    fn from(value: i32) -> Self {
+        OscArg::Int(value)
+    }
+}
+
+impl From<f32> for OscArg {
+    fn from(value: f32) -> Self {
+        OscArg::Float(value)
+    }
+}
+
+impl From<f64> for OscArg {
+    fn from(value: f64) -> Self {
+        OscArg::Double(value)
+    }
+}
+
+impl From<bool> for OscArg {
+    fn from(value: bool) -> Self {
+        OscArg::Bool(value)
+    }
+}
+
+impl From<&str> for OscArg {
+    fn from(value: &str) -> Self {
+        OscArg::String(value.to_string())
+    }
+}
+
+impl From<String> for OscArg {
+    fn from(value: String) -> Self {
+        OscArg::String(value)
+    }
+}
+
+/// An OSC message: an address such as `/mixer/fader/1` and a list of arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+impl OscMessage {
+    /// Creates a message to `address` without arguments.
+    pub fn new(address: &str) -> Self {
+        OscMessage { address: address.to_string(), args: Vec::new() }
+    }
+
+    /// Adds an argument, as in `OscMessage::new("/position").arg(x).arg(y)`.
+    pub fn arg(mut self, value: impl Into<OscArg>) -> Self {
+        self.args.push(value.into());
+        self
+    }
+
+    /// Argument `index` as a number, like `OscArg::as_f32`.
+    pub fn float(&self, index: usize) -> Option<f32> {
+        self.args.get(index).and_then(OscArg::as_f32)
+    }
+
+    /// Argument `index` as an integer, like `OscArg::as_i32`.
+    pub fn int(&self, index: usize) -> Option<i32> {
+        self.args.get(index).and_then(OscArg::as_i32)
+    }
+
+    /// Argument `index` as a string.
+    pub fn string(&self, index: usize) -> Option<&str> {
+        self.args.get(index).and_then(OscArg::as_str)
+    }
+
+    /// Whether the address matches an OSC address pattern, in which `?` matches any
+    /// character, `*` any run of characters, `[a-c]` and `[!a-c]` a character in or out of a
+    /// set, and `{left,right}` any of the alternatives. Wildcards don't match across `/`.
+    pub fn matches(&self, pattern: &str) -> bool {
+        pattern_matches(pattern.as_bytes(), self.address.as_bytes())
+    }
+
+    /// The message in the OSC 1.0 binary format.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_string(&mut bytes, self.address.as_bytes());
+        let mut tags = vec![b','];
+        tags.extend(self.args.iter().map(OscArg::tag));
+        write_string(&mut bytes, &tags);
+        for arg in &self.args {
+            match arg {
+                OscArg::Int(value) => bytes.extend(value.to_be_bytes()),
+                OscArg::Float(value) => bytes.extend(value.to_be_bytes()),
+                OscArg::String(value) => write_string(&mut bytes, value.as_bytes()),
+                OscArg::Blob(value) => {
+                    bytes.extend((value.len() as u32).to_be_bytes());
+                    bytes.extend(value);
+                    pad(&mut bytes);
+                }
+                OscArg::Long(value) => bytes.extend(value.to_be_bytes()),
+                OscArg::Double(value) => bytes.extend(value.to_be_bytes()),
+                OscArg::Bool(_) | OscArg::Nil => {}
+            }
+        }
+        bytes
+    }
+
+    /// Reads the messages in an OSC packet: a single message, or a bundle of them, whose
+    /// nested bundles are flattened in order. Time tags are ignored.
+    pub fn decode(packet: &[u8]) -> Result<Vec<OscMessage>, SketchError> {
+        let mut messages = Vec::new();
+        decode_packet(packet, 0, &mut messages)?;
+        Ok(messages)
+    }
+}
+
+/// Receives OSC messages on a UDP port.
+#[derive(Debug)]
+pub struct OscReceiver {
+    socket: UdpSocket,
+    buffer: Vec<u8>,
+}
+
+impl OscReceiver {
+    /// Listens on `port` on all network interfaces.
+    pub fn bind(port: u16) -> Result<Self, SketchError> {
+        OscReceiver::bind_to(("0.0.0.0", port))
+    }
+
+    /// Listens on a specific address, for example `("127.0.0.1", 9000)` to only receive
+    /// messages from the same machine.
+    pub fn bind_to(address: impl ToSocketAddrs) -> Result<Self, SketchError> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        Ok(OscReceiver { socket, buffer: vec![0; MAX_PACKET_SIZE] })
+    }
+
+    /// The port the receiver listens on, for receivers bound to port 0.
+    pub fn port(&self) -> Result<u16, SketchError> {
+        Ok(self.socket.local_addr()?.port())
+    }
+
+    /// The messages that arrived since the last call, in order, without waiting for more.
+    /// Packets that aren't valid OSC are skipped, so a misbehaving sender can't stop the sketch.
+    pub fn poll(&mut self) -> Vec<OscMessage> {
+        let mut messages = Vec::new();
+        loop {
+            match self.socket.recv(&mut self.buffer) {
+                Ok(size) => {
+                    if let Ok(packet) = OscMessage::decode(&self.buffer[..size]) {
+                        messages.extend(packet);
+                    }
+                }
+                // Windows reports ports that refused earlier sends as receive errors
+                Err(error) if error.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(_) => break,
+            }
+        }
+        messages
+    }
+}
+
+/// Sends OSC messages over UDP to one destination.
+#[derive(Debug)]
+pub struct OscSender {
+    socket: UdpSocket,
+}
+
+impl OscSender {
+    /// Creates a sender to an address such as `("127.0.0.1", 57120)` or `"192.168.1.20:9000"`.
+    pub fn connect(address: impl ToSocketAddrs) -> Result<Self, SketchError> {
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        socket.connect(address)?;
+        Ok(OscSender { socket })
+    }
+
+    pub fn send(&self, message: &OscMessage) -> Result<(), SketchError> {
+        self.socket.send(&message.encode())?;
+        Ok(())
+    }
+
+    /// Sends a message with a single argument, such as a value to show on a controller.
+    pub fn send_value(&self, address: &str, value: impl Into<OscArg>) -> Result<(), SketchError> {
+        self.send(&OscMessage::new(address).arg(value))
+    }
+}
+
+type Route<T> = Box<dyn FnMut(&mut T, &OscMessage) + Send>;
+
+/// Passes OSC messages to callbacks by address pattern, along with the state they update.
+pub struct OscRouter<T> {
+    routes: Vec<(String, Route<T>)>,
+}
+
+impl<T> Default for OscRouter<T> {
+    fn default() -> Self {
+        OscRouter { routes: Vec::new() }
+    }
+}
+
+impl<T> std::fmt::Debug for OscRouter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OscRouter").field("patterns", &self.routes.iter().map(|(pattern, _)| pattern).collect::<Vec<_>>()).finish()
+    }
+}
+
+impl<T> OscRouter<T> {
+    pub fn new() -> Self {
+        OscRouter::default()
+    }
+
+    /// Calls `callback` with every dispatched message whose address matches `pattern`. See
+    /// `OscMessage::matches` for the patterns.
+    pub fn add(&mut self, pattern: &str, callback: impl FnMut(&mut T, &OscMessage) + Send + 'static) {
+        self.routes.push((pattern.to_string(), Box::new(callback)));
+    }
+
+    /// Calls the callbacks of every route the message matches, in the order they were added,
+    /// and returns whether there were any.
+    pub fn dispatch(&mut self, target: &mut T, message: &OscMessage) -> bool {
+        let mut matched = false;
+        for (pattern, callback) in &mut self.routes {
+            if message.matches(pattern) {
+                callback(target, message);
+                matched = true;
+            }
+        }
+        matched
+    }
+}
+
+fn decode_packet(packet: &[u8], depth: usize, messages: &mut Vec<OscMessage>) -> Result<(), SketchError> {
+    if packet.starts_with(b"#bundle\0") {
+        if depth >= MAX_BUNDLE_DEPTH {
+            return Err(invalid("bundles nested too deeply"));
+        }
+        // The name is followed by an 8 byte time tag, then by elements prefixed with their size
+        let mut at = 16;
+        while at < packet.len() {
+            let size = read_u32(packet, &mut at)? as usize;
+            let element = packet.get(at..at.saturating_add(size)).ok_or_else(|| invalid("bundle element past the end"))?;
+            decode_packet(element, depth + 1, messages)?;
+            at += size;
+        }
+        return Ok(());
+    }
+
+    let mut at = 0;
+    let address = read_string(packet, &mut at)?;
+    if !address.starts_with('/') {
+        return Err(invalid("address doesn't start with /"));
+    }
+    // Very old senders leave out the type tags, and with them the arguments
+    let tags = if at < packet.len() { read_string(packet, &mut at)? } else { ",".to_string() };
+    let Some(tags) = tags.strip_prefix(',') else {
+        return Err(invalid("type tags don't start with ,"));
+    };
+    let mut args = Vec::with_capacity(tags.len());
+    for tag in tags.bytes() {
+        let arg = match tag {
+            b'i' => OscArg::Int(read_u32(packet, &mut at)? as i32),
+            b'f' => OscArg::Float(f32::from_bits(read_u32(packet, &mut at)?)),
+            b's' | b'S' => OscArg::String(read_string(packet, &mut at)?),
+            b'b' => {
+                let size = read_u32(packet, &mut at)? as usize;
+                let blob = packet.get(at..at.saturating_add(size)).ok_or_else(|| invalid("blob past the end"))?.to_vec();
+                at += size.next_multiple_of(4);
+                OscArg::Blob(blob)
+            }
+            b'h' => OscArg::Long(read_u64(packet, &mut at)? as i64),
+            b't' => OscArg::Long(read_u64(packet, &mut at)? as i64),
+            b'd' => OscArg::Double(f64::from_bits(read_u64(packet, &mut at)?)),
+            b'c' | b'r' | b'm' => OscArg::Int(read_u32(packet, &mut at)? as i32),
+            b'T' => OscArg::Bool(true),
+            b'F' => OscArg::Bool(false),
+            b'N' | b'I' => OscArg::Nil,
+            other => return Err(invalid(&format!("unsupported type tag {:?}", other as char))),
+        };
+        args.push(arg);
+    }
+    messages.push(OscMessage { address, args });
+    Ok(())
+}
+
+fn invalid(reason: &str) -> SketchError {
+    SketchError::Osc(reason.to_string())
+}
+
+fn read_u32(packet: &[u8], at: &mut usize) -> Result<u32, SketchError> {
+    let bytes = packet.get(*at..*at + 4).ok_or_else(|| invalid("argument past the end"))?;
+    *at += 4;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_u64(packet: &[u8], at: &mut usize) -> Result<u64, SketchError> {
+    Ok((read_u32(packet, at)? as u64) << 32 | read_u32(packet, at)? as u64)
+}
+
+// Reads a string terminated by a zero byte and padded to a multiple of 4 bytes
+fn read_string(packet: &[u8], at: &mut usize) -> Result<String, SketchError> {
+    let rest = packet.get(*at..).unwrap_or_default();
+    let length = rest.iter().position(|&byte| byte == 0).ok_or_else(|| invalid("unterminated string"))?;
+    let string = String::from_utf8_lossy(&rest[..length]).into_owned();
+    *at += (length + 1).next_multiple_of(4);
+    Ok(string)
+}
+
+fn write_string(bytes: &mut Vec<u8>, string: &[u8]) {
+    bytes.extend(string);
+    bytes.push(0);
+    pad(bytes);
+}
+
+fn pad(bytes: &mut Vec<u8>) {
+    bytes.resize(bytes.len().next_multiple_of(4), 0);
+}
+
+fn pattern_matches(pattern: &[u8], address: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => address.is_empty(),
+        Some((b'*', rest)) => {
+            // Try every run of characters up to the next part of the address
+            let limit = address.iter().position(|&byte| byte == b'/').unwrap_or(address.len());
+            (0..=limit).any(|skip| pattern_matches(rest, &address[skip..]))
+        }
+        Some((b'?', rest)) => matches!(address.first(), Some(&byte) if byte != b'/') && pattern_matches(rest, &address[1..]),
+        Some((b'[', rest)) => {
+            let Some(end) = rest.iter().position(|&byte| byte == b']') else {
+                return false;
+            };
+            let Some(&byte) = address.first() else {
+                return false;
+            };
+            let (negated, set) = match rest[..end].split_first() {
+                Some((b'!', set)) => (true, set),
+                _ => (false, &rest[..end]),
+            };
+            let mut found = false;
+            let mut i = 0;
+            while i < set.len() {
+                if i + 2 < set.len() && set[i + 1] == b'-' {
+                    found |= (set[i]..=set[i + 2]).contains(&byte);
+                    i += 3;
+                } else {
+                    found |= set[i] == byte;
+                    i += 1;
+                }
+            }
+            found != negated && pattern_matches(&rest[end + 1..], &address[1..])
+        }
+        Some((b'{', rest)) => {
+            let Some(end) = rest.iter().position(|&byte| byte == b'}') else {
+                return false;
+            };
+            rest[..end].split(|&byte| byte == b',').any(|alternative| {
+                address.starts_with(alternative) && pattern_matches(&rest[end + 1..], &address[alternative.len()..])
+            })
+        }
+        Some((&byte, rest)) => address.first() == Some(&byte) && pattern_matches(rest, &address[1..]),
+    }
+}
@@ -0,0 +1,122 @@
+//! Color palettes loaded from the palette files of pixel art and paint programs.
+
+use std::fs;
+use std::path::Path;
+
+use crate::color::Color;
+use crate::error::SketchError;
+
+/// A list of colors, as loaded from a palette file.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::error::SketchError;
+/// # use simple_sketch::geom::Point;
+/// # use simple_sketch::palette::Palette;
+/// # fn draw(canvas: &mut Canvas, centers: &[Point]) -> Result<(), SketchError> {
+/// let palette = Palette::load("assets/sweetie-16.hex")?;
+/// for (i, center) in centers.iter().enumerate() {
+///     canvas.set_fill(Some(palette.get(i)));
+///     canvas.circle(*center, 20.0);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    pub colors: Vec<Color>,
+}
+
+impl Palette {
+    pub fn new(colors: Vec<Color>) -> Self {
+        Palette { colors }
+    }
+
+    /// Loads a palette file. See `parse`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SketchError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| std::io::Error::new(e.kind(), format!("could not load {}: {e}", path.display())))?;
+        Palette::parse(&text).map_err(|error| match error {
+            SketchError::Palette(reason) => SketchError::Palette(format!("{}: {reason}", path.display())),
+            error => error,
+        })
+    }
+
+    /// Reads a palette from the text of a GIMP `.gpl` file, with a line of red, green and blue
+    /// from 0 to 255 per color, or of a `.hex` file, with one `rrggbb` or `#rrggbb` color per
+    /// line as exported by Lospec. Colors with alpha can be written as `rrggbbaa`. Empty lines
+    /// and lines starting with `#`, `;` or `//` that aren't colors are skipped.
+    pub fn parse(text: &str) -> Result<Self, SketchError> {
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        let mut lines = text.lines().enumerate().map(|(index, line)| (index + 1, line.trim()));
+        let gimp = text.trim_start().starts_with("GIMP Palette");
+        if gimp {
+            lines.next();
+        }
+        let mut colors = Vec::new();
+        for (number, line) in lines {
+            if line.is_empty() {
+                continue;
+            }
+            let color = if gimp {
+                if line.starts_with('#') || line.starts_with("Name:") || line.starts_with("Columns:") {
+                    continue;
+                }
+                parse_gimp(line)
+            } else {
+                if line.starts_with(';') || line.starts_with("//") {
+                    continue;
+                }
+                match parse_hex(line) {
+                    None if line.starts_with('#') => continue,
+                    color => color,
+                }
+            };
+            colors.push(color.ok_or_else(|| SketchError::Palette(format!("line {number}: invalid color '{line}'")))?);
+        }
+        if colors.is_empty() {
+            return Err(SketchError::Palette("no colors".to_string()));
+        }
+        Ok(Palette { colors })
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// The color at `index`, wrapping around past the last color, so any counter can pick
+    /// colors in turn. Black for an empty palette.
+    pub fn get(&self, index: usize) -> Color {
+        if self.colors.is_empty() {
+            return Color::new(0, 0, 0, 255);
+        }
+        self.colors[index % self.colors.len()]
+    }
+}
+
+/// Reads `rrggbb` or `rrggbbaa`, with or without a leading `#`.
+pub(crate) fn parse_hex(line: &str) -> Option<Color> {
+    let hex = line.strip_prefix('#').unwrap_or(line);
+    if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    match hex.len() {
+        6 => Some(Color(0xFF00_0000 | value)),
+        8 => Some(Color(value.rotate_right(8))),
+        _ => None,
+    }
+}
+
+// Reads `r g b` followed by an optional name
+fn parse_gimp(line: &str) -> Option<Color> {
+    let mut channels = line.split_whitespace().map(|channel| channel.parse::<u8>().ok());
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+    Some(Color::new(r, g, b, 255))
+}
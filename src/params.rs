@@ -0,0 +1,328 @@
+//! Named parameters loaded from a TOML or JSON file and reloaded when the file changes, so
+//! that a running sketch or installation can be tuned by editing a text file.
+//!
+//! Parameters are read by name with the type they're used as, and nested tables or objects
+//! are named with dots:
+//!
+//! ```toml
+//! wave_amplitude = 40.0
+//! trails = true
+//! mode = "ribbons"
+//!
+//! [colors]
+//! background = "#101018"
+//! stroke = [255, 200, 0, 128]
+//! ```
+//!
+//! ```
+//! # use simple_sketch::color::Color;
+//! # use simple_sketch::params::Params;
+//! # use std::str::FromStr;
+//! # enum Mode { Lines, Ribbons }
+//! # impl FromStr for Mode {
+//! #     type Err = ();
+//! #     fn from_str(mode: &str) -> Result<Self, ()> {
+//! #         match mode { "lines" => Ok(Mode::Lines), "ribbons" => Ok(Mode::Ribbons), _ => Err(()) }
+//! #     }
+//! # }
+//! # fn read(params: &mut Params) {
+//! let amplitude: f32 = params.get("wave_amplitude").unwrap_or(20.0);
+//! let background: Color = params.get("colors.background").unwrap_or(Color::new(0, 0, 0, 255));
+//! let mode: Mode = params.parse("mode").unwrap_or(Mode::Lines);
+//! # }
+//! ```
+//!
+//! TOML files are read in a subset of TOML: tables, bare and dotted keys, booleans, numbers,
+//! strings and arrays on one line. Files whose name doesn't end in `.toml` are read as JSON.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::color::Color;
+use crate::data::{self, Value};
+use crate::error::SketchError;
+use crate::palette;
+
+/// How long parameters go without checking their file for changes.
+const CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A type parameters can be read as.
+pub trait Param: Sized {
+    /// Converts a loaded value, or returns None if it isn't of this type.
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+impl Param for f32 {
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_f32()
+    }
+}
+
+impl Param for f64 {
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_f64()
+    }
+}
+
+impl Param for i32 {
+    /// Whole numbers only.
+    fn from_value(value: &Value) -> Option<Self> {
+        let whole = |number: &f64| number.fract() == 0.0 && (i32::MIN as f64..=i32::MAX as f64).contains(number);
+        value.as_f64().filter(whole).map(|number| number as i32)
+    }
+}
+
+impl Param for usize {
+    /// Whole numbers of at least zero only.
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_f64().filter(|number| number.fract() == 0.0 && *number >= 0.0).map(|number| number as usize)
+    }
+}
+
+impl Param for bool {
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+impl Param for String {
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_str().map(str::to_string)
+    }
+}
+
+impl Param for Color {
+    /// A string of `#rrggbb` or `#rrggbbaa`, or an array of three or four channels from 0 to
+    /// 255.
+    fn from_value(value: &Value) -> Option<Self> {
+        if let Some(hex) = value.as_str() {
+            return palette::parse_hex(hex);
+        }
+        let channels = value.as_array()?.iter().map(|channel| channel.as_f64().filter(|channel| (0.0..=255.0).contains(channel)).map(|channel| channel.round() as u8));
+        match *channels.collect::<Option<Vec<u8>>>()?.as_slice() {
+            [r, g, b] => Some(Color::new(r, g, b, 255)),
+            [r, g, b, a] => Some(Color::new(r, g, b, a)),
+            _ => None,
+        }
+    }
+}
+
+/// Named values loaded from a file, reloaded when the file changes.
+///
+/// With hot reloading on, which is the default, reading a parameter first reloads the file if
+/// it changed, at most every quarter second. A file that can't be read or parsed, for example
+/// because it is being saved or has a typo, leaves the previous values in place, and the
+/// problem is kept in `error` until the file loads again.
+pub struct Params {
+    path: PathBuf,
+    values: HashMap<String, Value>,
+    hot_reload: bool,
+    /// The modification time of the file the values were loaded from, if the system has one.
+    modified: Option<SystemTime>,
+    checked: Instant,
+    error: Option<SketchError>,
+}
+
+impl Params {
+    /// Loads the parameters in the TOML or JSON file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SketchError> {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified(&path);
+        let values = load_values(&path)?;
+        Ok(Params { path, values, hot_reload: true, modified, checked: Instant::now(), error: None })
+    }
+
+    /// Turns reloading the file when it changes on or off. On by default.
+    pub fn set_hot_reload(&mut self, enabled: bool) {
+        self.hot_reload = enabled;
+    }
+
+    pub fn hot_reload(&self) -> bool {
+        self.hot_reload
+    }
+
+    /// The parameter `name` as a `T`, or None if it is missing or of another type.
+    pub fn get<T: Param>(&mut self, name: &str) -> Option<T> {
+        self.value(name).and_then(T::from_value)
+    }
+
+    /// The string parameter `name` parsed as a `T`, usually an enum that implements `FromStr`,
+    /// or None if it is missing or doesn't parse.
+    pub fn parse<T: FromStr>(&mut self, name: &str) -> Option<T> {
+        self.value(name)?.as_str()?.parse().ok()
+    }
+
+    /// The parameter `name` as it was loaded.
+    pub fn value(&mut self, name: &str) -> Option<&Value> {
+        if self.hot_reload && self.checked.elapsed() >= CHECK_INTERVAL {
+            self.reload_if_changed();
+        }
+        self.values.get(name)
+    }
+
+    /// The names of all parameters, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+
+    /// Reloads the file if it changed since it was loaded, whether hot reloading is on or not.
+    /// Returns whether the values changed.
+    pub fn reload_if_changed(&mut self) -> bool {
+        self.checked = Instant::now();
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return false;
+        }
+        match load_values(&self.path) {
+            Ok(values) => {
+                self.values = values;
+                self.modified = modified;
+                self.error = None;
+                true
+            }
+            Err(error) => {
+                self.error = Some(error);
+                false
+            }
+        }
+    }
+
+    /// Why the file couldn't be reloaded the last time it changed, or None if it was.
+    pub fn error(&self) -> Option<&SketchError> {
+        self.error.as_ref()
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+// Reads the values of a TOML or JSON file, by their dotted names.
+fn load_values(path: &Path) -> Result<HashMap<String, Value>, SketchError> {
+    let text = fs::read_to_string(path).map_err(|e| std::io::Error::new(e.kind(), format!("could not load {}: {e}", path.display())))?;
+    let toml = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+    let parsed = if toml { parse_toml(&text) } else { data::parse_json(&text).map(|document| flatten(document, "")) };
+    parsed.map_err(|error| match error {
+        SketchError::Data(reason) => SketchError::Data(format!("{}: {reason}", path.display())),
+        error => error,
+    })
+}
+
+// Names the values of nested objects by their path of keys, joined by dots.
+fn flatten(value: Value, prefix: &str) -> HashMap<String, Value> {
+    let mut values = HashMap::new();
+    match value {
+        Value::Object(entries) => {
+            for (key, value) in entries {
+                let name = if prefix.is_empty() { key } else { format!("{prefix}.{key}") };
+                values.extend(flatten(value, &name));
+            }
+        }
+        value => {
+            values.insert(prefix.to_string(), value);
+        }
+    }
+    values
+}
+
+// Reads a TOML document in the subset of `Params`.
+fn parse_toml(text: &str) -> Result<HashMap<String, Value>, SketchError> {
+    let mut values = HashMap::new();
+    let mut table = String::new();
+    for (index, line) in text.lines().enumerate() {
+        let error = |reason: &str| SketchError::Data(format!("line {}: {reason}", index + 1));
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let name = header.strip_suffix(']').ok_or_else(|| error("unclosed table header"))?;
+            table = parse_key(name).ok_or_else(|| error("invalid table name"))?;
+            continue;
+        }
+        let (key, value) = line.split_once('=').ok_or_else(|| error("expected `key = value`"))?;
+        let key = parse_key(key).ok_or_else(|| error("invalid key"))?;
+        let name = if table.is_empty() { key } else { format!("{table}.{key}") };
+        let value = parse_toml_value(value.trim()).ok_or_else(|| error("invalid value"))?;
+        if values.insert(name.clone(), value).is_some() {
+            return Err(error(&format!("`{name}` is defined twice")));
+        }
+    }
+    Ok(values)
+}
+
+// Cuts a line at a `#` outside strings.
+fn strip_comment(line: &str) -> &str {
+    let cut = outside_strings(line).find(|&(_, c)| c == '#');
+    cut.map_or(line, |(index, _)| &line[..index])
+}
+
+// The characters of `text` that aren't inside a string, with their byte positions.
+fn outside_strings(text: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let (mut quote, mut escaped) = (None, false);
+    text.char_indices().filter(move |&(_, c)| {
+        let outside = quote.is_none() && c != '"' && c != '\'';
+        match (quote, c) {
+            _ if escaped => escaped = false,
+            (Some('"'), '\\') => escaped = true,
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            _ => {}
+        }
+        outside
+    })
+}
+
+// Reads a bare or dotted key, joining its parts with single dots.
+fn parse_key(key: &str) -> Option<String> {
+    let parts: Vec<&str> = key.split('.').map(str::trim).collect();
+    let bare = |part: &&str| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    parts.iter().all(bare).then(|| parts.join("."))
+}
+
+fn parse_toml_value(text: &str) -> Option<Value> {
+    match text {
+        "true" => return Some(Value::Bool(true)),
+        "false" => return Some(Value::Bool(false)),
+        _ => {}
+    }
+    if let Some(items) = text.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+        let items = split_items(items)?;
+        return items.into_iter().map(parse_toml_value).collect::<Option<Vec<_>>>().map(Value::Array);
+    }
+    if let Some(literal) = text.strip_prefix('\'').and_then(|rest| rest.strip_suffix('\'')) {
+        return Some(Value::String(literal.to_string()));
+    }
+    if text.starts_with('"') {
+        // TOML's basic strings escape like JSON's
+        return data::parse_json(text).ok().filter(|value| value.as_str().is_some());
+    }
+    let number = text.replace('_', "");
+    let number = number.strip_prefix('+').unwrap_or(&number);
+    number.parse::<f64>().ok().filter(|number| number.is_finite()).map(Value::Number)
+}
+
+// Splits the items of a one-line array at the commas outside strings, allowing a trailing
+// comma. Nested arrays aren't supported.
+fn split_items(items: &str) -> Option<Vec<&str>> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    for (index, c) in outside_strings(items) {
+        match c {
+            '[' => return None,
+            ',' => {
+                result.push(items[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = items[start..].trim();
+    if !last.is_empty() {
+        result.push(last);
+    }
+    (!result.contains(&"")).then_some(result)
+}
@@ -0,0 +1,187 @@
+//! A minimal PDF writer for recorded drawing commands.
+//!
+//! Every command becomes a path in the page's content stream, filled with the non-zero
+//! winding rule and stroked with round caps and joins, like the canvas rasterizes it.
+//! Translucent colors use one graphics state per alpha value.
+
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+use crate::color::Color;
+use crate::command::{DrawCommand, RecordedCommand};
+use crate::geom::{Path, PathCommand, Point};
+
+/// The size of a page and how recorded coordinates map onto it.
+pub(crate) struct Page {
+    /// The size of the page in points, 1/72 of an inch.
+    pub width: f32,
+    pub height: f32,
+    /// Points per unit of the recorded coordinates.
+    pub scale: f32,
+}
+
+/// Writes recorded commands as a single page PDF document.
+pub(crate) fn write_pdf(mut out: impl Write, commands: &[RecordedCommand], page: &Page) -> io::Result<()> {
+    let (content, alphas) = content_stream(commands, page);
+
+    let graphics_states: String = alphas.iter().map(|alpha| format!("/GS{alpha} << /ca {0} /CA {0} >> ", number(*alpha as f32 / 255.0))).collect();
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /ExtGState << {graphics_states}>> >> /Contents 4 0 R >>",
+            number(page.width),
+            number(page.height)
+        ),
+        format!("<< /Length {} >>\nstream\n{content}endstream", content.len()),
+        format!("<< /Producer (simple-sketch {}) >>", env!("CARGO_PKG_VERSION")),
+    ];
+
+    let mut document = Vec::new();
+    document.extend_from_slice(b"%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(document.len());
+        write!(document, "{} 0 obj\n{object}\nendobj\n", index + 1)?;
+    }
+    let xref = document.len();
+    write!(document, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1)?;
+    for offset in offsets {
+        writeln!(document, "{offset:010} 00000 n ")?;
+    }
+    write!(document, "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{xref}\n%%EOF\n", objects.len() + 1)?;
+    out.write_all(&document)?;
+    out.flush()
+}
+
+// Builds the page's drawing operators, and returns them with the alpha values they use.
+fn content_stream(commands: &[RecordedCommand], page: &Page) -> (String, BTreeSet<u8>) {
+    let mut content = String::new();
+    let mut alphas = BTreeSet::new();
+    // Flip the y-axis so that recorded coordinates grow downwards from the top left corner
+    content.push_str(&format!("{0} 0 0 -{0} 0 {1} cm 1 J 1 j\n", number(page.scale), number(page.height)));
+
+    for RecordedCommand { command, style } in commands {
+        let (path, fill, stroke) = match command {
+            DrawCommand::Background(color) => {
+                let size = Point::new(page.width, page.height) * (1.0 / page.scale);
+                (Path::rectangle(Point::new(0.0, 0.0), size.x, size.y), Some(*color), None)
+            }
+            // Lines and curves are only stroked
+            DrawCommand::Line { start, end } => {
+                let mut path = Path::new();
+                path.move_to(*start).line_to(*end);
+                (path, None, style.stroke)
+            }
+            DrawCommand::Bezier { start, control1, control2, end } => {
+                let mut path = Path::new();
+                path.move_to(*start).cubic_to(*control1, *control2, *end);
+                (path, None, style.stroke)
+            }
+            DrawCommand::Ellipse { center, width, height } => (Path::ellipse(*center, *width, *height), style.fill, style.stroke),
+            DrawCommand::Circle { center, diameter } => (Path::ellipse(*center, *diameter, *diameter), style.fill, style.stroke),
+            DrawCommand::Triangle { a, b, c } => (Path::polygon(&[*a, *b, *c]), style.fill, style.stroke),
+            DrawCommand::Capsule(capsule) => (capsule.to_path(), style.fill, style.stroke),
+            // Open arcs are filled to their chord, which filling an open path does
+            DrawCommand::Arc(arc) => (arc.to_path(), style.fill, style.stroke),
+            DrawCommand::RoundedRect(rect) => (rect.to_path(), style.fill, style.stroke),
+            DrawCommand::Star(star) => (Path::polygon(&star.vertices()), style.fill, style.stroke),
+            DrawCommand::RegularPolygon(polygon) => (Path::polygon(&polygon.vertices()), style.fill, style.stroke),
+            DrawCommand::Rectangle { top_left, width, height } => (Path::rectangle(*top_left, *width, *height), style.fill, style.stroke),
+            DrawCommand::Path(path) => (path.clone(), style.fill, style.stroke),
+        };
+        let operator = match (fill, stroke) {
+            (Some(_), Some(_)) => "B",
+            (Some(_), None) => "f",
+            (None, Some(_)) => "S",
+            (None, None) => continue,
+        };
+
+        content.push_str("q ");
+        if let Some(color) = fill {
+            content.push_str(&format!("{} rg ", rgb(color)));
+        }
+        if let Some(color) = stroke {
+            // Thin lines are drawn one pixel wide
+            let weight = if matches!(command, DrawCommand::Line { .. }) { style.stroke_weight.max(1.0) } else { style.stroke_weight };
+            content.push_str(&format!("{} RG {} w ", rgb(color), number(weight)));
+        }
+        // A single graphics state sets both alphas, so they have to match
+        let alpha = fill.or(stroke).map_or(255, |color| color.a());
+        if fill.zip(stroke).is_none_or(|(fill, stroke)| fill.a() == stroke.a()) {
+            if alpha < 255 {
+                alphas.insert(alpha);
+                content.push_str(&format!("/GS{alpha} gs "));
+            }
+            push_path(&mut content, &path);
+            content.push_str(operator);
+        } else {
+            // Fill and stroke with different alphas are painted one after the other
+            for (color, operator) in [(fill, "f"), (stroke, "S")] {
+                let alpha = color.map_or(255, |color| color.a());
+                alphas.insert(alpha);
+                content.push_str(&format!("/GS{alpha} gs "));
+                push_path(&mut content, &path);
+                content.push_str(operator);
+                content.push(' ');
+            }
+        }
+        content.push_str(" Q\n");
+    }
+    (content, alphas)
+}
+
+fn push_path(content: &mut String, path: &Path) {
+    let (mut start, mut current) = (Point::new(0.0, 0.0), Point::new(0.0, 0.0));
+    for command in &path.commands {
+        match *command {
+            PathCommand::MoveTo(point) => {
+                content.push_str(&format!("{} {} m ", number(point.x), number(point.y)));
+                (start, current) = (point, point);
+            }
+            PathCommand::LineTo(point) => {
+                content.push_str(&format!("{} {} l ", number(point.x), number(point.y)));
+                current = point;
+            }
+            PathCommand::QuadTo(control, end) => {
+                // PDF has no quadratic curves, raise them to cubics
+                let control1 = current + (control - current) * (2.0 / 3.0);
+                let control2 = end + (control - end) * (2.0 / 3.0);
+                push_cubic(content, control1, control2, end);
+                current = end;
+            }
+            PathCommand::CubicTo(control1, control2, end) => {
+                push_cubic(content, control1, control2, end);
+                current = end;
+            }
+            PathCommand::Close => {
+                content.push_str("h ");
+                current = start;
+            }
+        }
+    }
+}
+
+fn push_cubic(content: &mut String, control1: Point, control2: Point, end: Point) {
+    content.push_str(&format!(
+        "{} {} {} {} {} {} c ",
+        number(control1.x),
+        number(control1.y),
+        number(control2.x),
+        number(control2.y),
+        number(end.x),
+        number(end.y)
+    ));
+}
+
+fn rgb(color: Color) -> String {
+    [color.r(), color.g(), color.b()].map(|channel| number(channel as f32 / 255.0)).join(" ")
+}
+
+// Formats a number with at most three decimals and no trailing zeros, as PDF has no exponents.
+fn number(value: f32) -> String {
+    let value = if value.is_finite() { value } else { 0.0 };
+    let text = format!("{value:.3}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".to_string() } else { text.to_string() }
+}
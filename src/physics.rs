@@ -0,0 +1,352 @@
+//! Lightweight 2D physics with Verlet integration: particles that keep their velocity as the
+//! difference between their current and previous position, held together by distance
+//! constraints and springs, pulled by gravity and kept out of rectangles and circles.
+//!
+//! This is the scaffolding of cloth, rope, ragdoll chains and soft-body blobs. Constraints
+//! are solved by moving particles directly, a few times per step, which stays stable for
+//! large stiffnesses where forces would explode. Steps of a fixed length, as with
+//! `UpdateMode::Fixed`, behave the same at any frame rate.
+//!
+//! # Examples
+//!
+//! ```
+//! # use simple_sketch::canvas::Canvas;
+//! # use simple_sketch::geom::{Point, Rect};
+//! # use simple_sketch::physics::{Collider, World};
+//! # fn sketch(canvas: &mut Canvas, dt: f32) {
+//! // A rope hanging from the top of the window
+//! let mut world = World::new();
+//! world.gravity = Point::new(0.0, 500.0);
+//! world.colliders.push(Collider::Bounds(Rect::new(Point::new(0.0, 0.0), Point::new(800.0, 600.0))));
+//! let rope = world.add_chain((0..20).map(|i| Point::new(400.0 + i as f32 * 10.0, 50.0)));
+//! world.particles[rope.start].pinned = true;
+//!
+//! // In update
+//! world.step(dt);
+//! // In draw
+//! for (a, b) in world.links() {
+//!     canvas.line(a, b);
+//! }
+//! # }
+//! ```
+
+use std::ops::Range;
+
+use crate::geom::{Circle, Point, Rect};
+
+/// A point mass moved by Verlet integration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Particle {
+    pub position: Point,
+    /// The position in the previous step. Moving it changes the velocity.
+    pub previous: Point,
+    /// Heavier particles are moved less by constraints and springs.
+    pub mass: f32,
+    /// How far the particle is kept from colliders.
+    pub radius: f32,
+    /// Pinned particles stay where they are, unless they are moved by hand.
+    pub pinned: bool,
+}
+
+impl Particle {
+    pub fn new(position: Point) -> Self {
+        Particle { position, previous: position, mass: 1.0, radius: 0.0, pinned: false }
+    }
+
+    /// The distance moved in the last step.
+    pub fn displacement(&self) -> Point {
+        self.position - self.previous
+    }
+
+    /// Moves the particle to `position` without giving it any velocity.
+    pub fn teleport(&mut self, position: Point) {
+        self.previous = position;
+        self.position = position;
+    }
+
+    fn inverse_mass(&self) -> f32 {
+        if self.pinned || self.mass <= 0.0 {
+            0.0
+        } else {
+            1.0 / self.mass
+        }
+    }
+}
+
+/// A connection between two particles, given by their indices in `World::particles`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Constraint {
+    /// Keeps the particles `length` apart. A `stiffness` of 1 corrects the whole error in
+    /// every iteration, lower values make the link stretchy.
+    Distance { a: usize, b: usize, length: f32, stiffness: f32 },
+    /// Pulls the particles towards `rest_length` apart with a force proportional to the
+    /// stretch, so they bounce; `damping` slows the bouncing down.
+    Spring { a: usize, b: usize, rest_length: f32, stiffness: f32, damping: f32 },
+}
+
+impl Constraint {
+    /// The indices of the two particles.
+    pub fn particles(&self) -> (usize, usize) {
+        match *self {
+            Constraint::Distance { a, b, .. } | Constraint::Spring { a, b, .. } => (a, b),
+        }
+    }
+}
+
+/// A shape that particles collide with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Collider {
+    /// Keeps particles inside the rectangle, like the walls of the window.
+    Bounds(Rect),
+    /// Keeps particles out of the rectangle.
+    Rect(Rect),
+    /// Keeps particles out of the circle.
+    Circle(Circle),
+}
+
+impl Collider {
+    // Where a particle with `radius` at `position` is pushed to, if it collides
+    fn resolve(&self, position: Point, radius: f32) -> Option<Point> {
+        match self {
+            Collider::Bounds(rect) => {
+                let (min, max) = (rect.min + Point::new(radius, radius), rect.max - Point::new(radius, radius));
+                let clamped = Point::new(position.x.max(min.x).min(max.x), position.y.max(min.y).min(max.y));
+                (clamped != position).then_some(clamped)
+            }
+            Collider::Rect(rect) => {
+                let (min, max) = (rect.min - Point::new(radius, radius), rect.max + Point::new(radius, radius));
+                if position.x <= min.x || position.x >= max.x || position.y <= min.y || position.y >= max.y {
+                    return None;
+                }
+                // Out through the nearest side
+                let exits = [
+                    (position.x - min.x, Point::new(min.x, position.y)),
+                    (max.x - position.x, Point::new(max.x, position.y)),
+                    (position.y - min.y, Point::new(position.x, min.y)),
+                    (max.y - position.y, Point::new(position.x, max.y)),
+                ];
+                exits.into_iter().min_by(|a, b| a.0.total_cmp(&b.0)).map(|(_, exit)| exit)
+            }
+            Collider::Circle(circle) => {
+                let offset = position - circle.center;
+                let distance = offset.length();
+                let reach = circle.radius + radius;
+                if distance >= reach {
+                    return None;
+                }
+                let direction = if distance > 0.0 { offset * (1.0 / distance) } else { Point::new(0.0, -1.0) };
+                Some(circle.center + direction * reach)
+            }
+        }
+    }
+}
+
+/// Particles, the constraints between them and the colliders they bump into.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct World {
+    pub particles: Vec<Particle>,
+    pub constraints: Vec<Constraint>,
+    pub colliders: Vec<Collider>,
+    /// The acceleration of every particle, in pixels per second squared.
+    pub gravity: Point,
+    /// The fraction of their velocity particles lose per step, from 0 to 1, like air
+    /// resistance.
+    pub drag: f32,
+    /// The fraction of their sliding velocity particles lose when they touch a collider,
+    /// from 0 to 1.
+    pub friction: f32,
+    /// How often the constraints and collisions are solved per step. More iterations make
+    /// long chains and cloth stiffer.
+    pub iterations: usize,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        World {
+            particles: Vec::new(),
+            constraints: Vec::new(),
+            colliders: Vec::new(),
+            gravity: Point::new(0.0, 0.0),
+            drag: 0.01,
+            friction: 0.1,
+            iterations: 8,
+        }
+    }
+}
+
+impl World {
+    pub fn new() -> Self {
+        World::default()
+    }
+
+    /// Adds a particle at rest at `position` and returns its index.
+    pub fn add_particle(&mut self, position: Point) -> usize {
+        self.particles.push(Particle::new(position));
+        self.particles.len() - 1
+    }
+
+    /// Keeps the particles `a` and `b` at their current distance, rigidly.
+    pub fn add_distance(&mut self, a: usize, b: usize) {
+        let length = self.particles[a].position.distance(&self.particles[b].position);
+        self.constraints.push(Constraint::Distance { a, b, length, stiffness: 1.0 });
+    }
+
+    /// Connects the particles `a` and `b` with a spring that rests at their current distance.
+    pub fn add_spring(&mut self, a: usize, b: usize, stiffness: f32, damping: f32) {
+        let rest_length = self.particles[a].position.distance(&self.particles[b].position);
+        self.constraints.push(Constraint::Spring { a, b, rest_length, stiffness, damping });
+    }
+
+    /// Adds a particle at every point, each linked to the one before at its current
+    /// distance, like a rope, and returns the range of their indices.
+    pub fn add_chain(&mut self, points: impl IntoIterator<Item = Point>) -> Range<usize> {
+        let start = self.particles.len();
+        for point in points {
+            let index = self.add_particle(point);
+            if index > start {
+                self.add_distance(index - 1, index);
+            }
+        }
+        start..self.particles.len()
+    }
+
+    /// Adds a grid of `columns` by `rows` particles covering `rect`, each linked to its right
+    /// and lower neighbor, like a piece of cloth, and returns the range of their indices,
+    /// row by row from the top left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::{Point, Rect};
+    /// # use simple_sketch::physics::World;
+    /// # let mut world = World::new();
+    /// // Hang the cloth from its top corners
+    /// let cloth = world.add_cloth(Rect::new(Point::new(200.0, 50.0), Point::new(600.0, 350.0)), 30, 20);
+    /// world.particles[cloth.start].pinned = true;
+    /// world.particles[cloth.start + 29].pinned = true;
+    /// ```
+    pub fn add_cloth(&mut self, rect: Rect, columns: usize, rows: usize) -> Range<usize> {
+        let start = self.particles.len();
+        let fraction = |i: usize, count: usize| if count > 1 { i as f32 / (count - 1) as f32 } else { 0.5 };
+        for row in 0..rows {
+            for column in 0..columns {
+                let index = self.add_particle(rect.lerp_point(fraction(column, columns), fraction(row, rows)));
+                if column > 0 {
+                    self.add_distance(index - 1, index);
+                }
+                if row > 0 {
+                    self.add_distance(index - columns, index);
+                }
+            }
+        }
+        start..self.particles.len()
+    }
+
+    /// Adds a ring of `count` particles around `center`, each linked to its neighbors and
+    /// held to the opposite side by springs, like a soft-body blob, and returns the range of
+    /// their indices.
+    pub fn add_blob(&mut self, center: Point, radius: f32, count: usize, stiffness: f32, damping: f32) -> Range<usize> {
+        let start = self.particles.len();
+        for i in 0..count {
+            let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+            self.add_particle(center + Point::new(angle.cos(), angle.sin()) * radius);
+        }
+        for i in 0..count {
+            if count > 1 {
+                self.add_distance(start + i, start + (i + 1) % count);
+            }
+            if i < count / 2 {
+                self.add_spring(start + i, start + i + count / 2, stiffness, damping);
+            }
+        }
+        start..self.particles.len()
+    }
+
+    /// The two ends of every constraint, for drawing them as lines.
+    pub fn links(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        self.constraints.iter().map(|constraint| {
+            let (a, b) = constraint.particles();
+            (self.particles[a].position, self.particles[b].position)
+        })
+    }
+
+    /// Moves the simulation `dt` seconds forward: applies gravity and springs, moves the
+    /// particles, and then solves the constraints and collisions.
+    pub fn step(&mut self, dt: f32) {
+        if dt <= 0.0 {
+            return;
+        }
+        let mut accelerations = vec![self.gravity; self.particles.len()];
+        for constraint in &self.constraints {
+            let Constraint::Spring { a, b, rest_length, stiffness, damping } = *constraint else {
+                continue;
+            };
+            let (Some(pa), Some(pb)) = (self.particles.get(a), self.particles.get(b)) else {
+                continue;
+            };
+            let offset = pb.position - pa.position;
+            let distance = offset.length();
+            if distance == 0.0 {
+                continue;
+            }
+            let direction = offset * (1.0 / distance);
+            let relative_velocity = (pb.displacement() - pa.displacement()) * (1.0 / dt);
+            let force = direction * ((distance - rest_length) * stiffness + relative_velocity.dot(&direction) * damping);
+            accelerations[a] = accelerations[a] + force * pa.inverse_mass();
+            accelerations[b] = accelerations[b] - force * pb.inverse_mass();
+        }
+
+        let keep = 1.0 - self.drag.clamp(0.0, 1.0);
+        for (particle, acceleration) in self.particles.iter_mut().zip(accelerations) {
+            if particle.pinned {
+                particle.previous = particle.position;
+                continue;
+            }
+            let velocity = particle.displacement() * keep;
+            particle.previous = particle.position;
+            particle.position = particle.position + velocity + acceleration * (dt * dt);
+        }
+
+        for _ in 0..self.iterations.max(1) {
+            for constraint in &self.constraints {
+                if let Constraint::Distance { a, b, length, stiffness } = *constraint {
+                    solve_distance(&mut self.particles, a, b, length, stiffness.clamp(0.0, 1.0));
+                }
+            }
+            self.collide();
+        }
+    }
+
+    // Pushes the particles out of the colliders, slowing down their sliding along them
+    fn collide(&mut self) {
+        let keep = 1.0 - self.friction.clamp(0.0, 1.0);
+        for particle in self.particles.iter_mut().filter(|particle| !particle.pinned) {
+            for collider in &self.colliders {
+                if let Some(position) = collider.resolve(particle.position, particle.radius) {
+                    particle.position = position;
+                    particle.previous = particle.position - particle.displacement() * keep;
+                }
+            }
+        }
+    }
+}
+
+// Moves two particles towards `length` apart, each by the share of its inverse mass
+fn solve_distance(particles: &mut [Particle], a: usize, b: usize, length: f32, stiffness: f32) {
+    let (Some(pa), Some(pb)) = (particles.get(a), particles.get(b)) else {
+        return;
+    };
+    let (wa, wb) = (pa.inverse_mass(), pb.inverse_mass());
+    let offset = pb.position - pa.position;
+    let distance = offset.length();
+    if wa + wb == 0.0 || distance == 0.0 {
+        return;
+    }
+    let correction = offset * ((distance - length) / distance * stiffness / (wa + wb));
+    particles[a].position = particles[a].position + correction * wa;
+    particles[b].position = particles[b].position - correction * wb;
+}
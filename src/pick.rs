@@ -0,0 +1,96 @@
+//! Hit-testing of drawn shapes.
+//!
+//! Drawing calls made inside `Canvas::with_pick_id` are remembered together with their style,
+//! in canvas coordinates. `Canvas::pick` then finds the topmost of them under a point, so that
+//! interactive sketches can test against exactly what was drawn instead of duplicating geometry.
+
+use crate::command::{DrawCommand, Style};
+use crate::geom::{CubicBezier, Path, Point, Polyline};
+use crate::shape::{AnyShape, Circle, Ellipse, Outline, Rectangle, Shape, Triangle};
+
+/// Tolerance in pixels for flattening curves into the outlines that are hit-tested.
+const PICK_TOLERANCE: f32 = 0.25;
+
+/// Identifies a drawn shape, or a group of shapes, for picking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShapeId(pub u32);
+
+/// A drawing call registered for picking.
+#[derive(Debug, Clone)]
+pub(crate) struct PickEntry {
+    pub id: ShapeId,
+    pub style: Style,
+    /// The depth the call was queued at, or None if it was drawn immediately.
+    pub depth: Option<f32>,
+    pub shape: AnyShape,
+}
+
+impl PickEntry {
+    /// Creates an entry for a command in canvas coordinates. Returns None for commands without
+    /// geometry, such as backgrounds.
+    pub fn new(id: ShapeId, style: Style, depth: Option<f32>, command: &DrawCommand) -> Option<Self> {
+        let shape = command_shape(command)?;
+        // Lines and curves are never filled
+        let style = match command {
+            DrawCommand::Line { .. } | DrawCommand::Bezier { .. } => Style { fill: None, ..style },
+            _ => style,
+        };
+        Some(PickEntry { id, style, depth, shape })
+    }
+
+    /// Checks if the entry's fill or stroke, as drawn with its style, covers the point.
+    pub fn hit(&self, point: Point) -> bool {
+        (self.style.fill.is_some() && self.shape.contains(point))
+            || (self.style.stroke.is_some() && self.shape.distance(point).abs() <= self.style.stroke_weight / 2.0)
+    }
+
+    /// Compares entries by the order in which they end up on screen: calls drawn immediately
+    /// first, then queued calls by depth. Later calls are drawn over earlier ones.
+    pub fn is_above(&self, other: &PickEntry) -> bool {
+        match (self.depth, other.depth) {
+            (Some(depth), Some(other)) => depth >= other,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (None, None) => true,
+        }
+    }
+}
+
+// The shape a drawing command rasterizes. Lines and curves become open outlines.
+fn command_shape(command: &DrawCommand) -> Option<AnyShape> {
+    let shape = match command {
+        DrawCommand::Background(_) => return None,
+        DrawCommand::Line { start, end } => open_outline(vec![*start, *end]),
+        DrawCommand::Bezier { start, control1, control2, end } => {
+            let curve = CubicBezier::new(*start, *control1, *control2, *end);
+            open_outline(curve.flatten(PICK_TOLERANCE))
+        }
+        DrawCommand::Ellipse { center, width, height } => {
+            AnyShape::Ellipse(Ellipse { center: *center, width: *width, height: *height })
+        }
+        DrawCommand::Circle { center, diameter } => AnyShape::Circle(Circle::new(*center, diameter / 2.0)),
+        DrawCommand::Triangle { a, b, c } => AnyShape::Triangle(Triangle { a: *a, b: *b, c: *c }),
+        DrawCommand::Capsule(capsule) => AnyShape::Capsule(*capsule),
+        DrawCommand::Arc(arc) => AnyShape::Arc(*arc),
+        DrawCommand::RoundedRect(rect) => AnyShape::RoundedRect(*rect),
+        DrawCommand::Star(star) => AnyShape::Star(*star),
+        DrawCommand::RegularPolygon(polygon) => AnyShape::RegularPolygon(*polygon),
+        DrawCommand::Rectangle { top_left, width, height } => {
+            AnyShape::Rectangle(Rectangle { top_left: *top_left, width: *width, height: *height })
+        }
+        DrawCommand::Path(path) => path_outline(path)?,
+    };
+    Some(shape)
+}
+
+fn open_outline(points: Vec<Point>) -> AnyShape {
+    AnyShape::Outline(Outline { polylines: vec![Polyline { points, closed: false }] })
+}
+
+fn path_outline(path: &Path) -> Option<AnyShape> {
+    let polylines = path.flatten(PICK_TOLERANCE);
+    if polylines.is_empty() {
+        return None;
+    }
+    Some(AnyShape::Outline(Outline { polylines }))
+}
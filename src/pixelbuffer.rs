@@ -1,5 +1,85 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
 use crate::color::Color;
-use crate::geom::Point;
+use crate::error::SketchError;
+use crate::geom::{Point, Rect};
+use crate::kernel::Kernel;
+use crate::png::{self, PngColor};
+
+/// The file formats pixel buffers can be saved in, see `PixelBuffer::save`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Compressed RGBA, see `PixelBuffer::save_png`.
+    Png,
+    /// Uncompressed RGB, see `PixelBuffer::save_ppm`.
+    Ppm,
+    /// Uncompressed RGBA, see `PixelBuffer::save_pam`.
+    Pam,
+    /// Uncompressed 32 bit BGRA Windows bitmap, see `PixelBuffer::save_bmp`.
+    Bmp,
+    /// Uncompressed 32 bit BGRA Truevision TGA, see `PixelBuffer::save_tga`.
+    Tga,
+}
+
+impl ImageFormat {
+    /// The format for the extension of `path`, ignoring case, or None for other extensions.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<ImageFormat> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(ImageFormat::Png),
+            "ppm" => Some(ImageFormat::Ppm),
+            "pam" => Some(ImageFormat::Pam),
+            "bmp" => Some(ImageFormat::Bmp),
+            "tga" => Some(ImageFormat::Tga),
+            _ => None,
+        }
+    }
+}
+
+/// How many pixels of a buffer have each value, from 0 to 255, of every channel and of their
+/// luminance. See `PixelBuffer::histogram`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    pub red: [u32; 256],
+    pub green: [u32; 256],
+    pub blue: [u32; 256],
+    pub alpha: [u32; 256],
+    /// Counts of the perceived brightness of the colors, `0.2126 * red + 0.7152 * green +
+    /// 0.0722 * blue` as for sRGB.
+    pub luminance: [u32; 256],
+}
+
+impl Histogram {
+    /// The value below which `fraction`, from 0 to 1, of the counted pixels lie in `counts`,
+    /// such as one of the channels. With fractions like 0.01 and 0.99 this finds the darkest
+    /// and brightest values while ignoring a few stray pixels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::pixelbuffer::Histogram;
+    /// # let canvas = Canvas::new(80, 60);
+    /// // Stretch the luminance to the full range, ignoring the darkest and brightest 1%
+    /// let histogram = canvas.pixel_buffer.histogram();
+    /// let low = Histogram::percentile(&histogram.luminance, 0.01) as f32;
+    /// let high = Histogram::percentile(&histogram.luminance, 0.99) as f32;
+    /// ```
+    pub fn percentile(counts: &[u32; 256], fraction: f32) -> u8 {
+        let total: u64 = counts.iter().map(|&count| count as u64).sum();
+        let target = (total as f64 * fraction.clamp(0.0, 1.0) as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (value, &count) in counts.iter().enumerate() {
+            seen += count as u64;
+            if seen >= target {
+                return value as u8;
+            }
+        }
+        255
+    }
+}
 
 pub struct PixelBuffer {
     pub width: usize,
@@ -16,10 +96,312 @@ impl PixelBuffer {
         }
     }
 
+    /// Changes the size of the buffer. Pixels that are inside both the old and the new size
+    /// keep their color, and new pixels are black.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let mut buffer = vec![0; width * height];
+        let columns = width.min(self.width);
+        for y in 0..height.min(self.height) {
+            buffer[y * width..y * width + columns].copy_from_slice(&self.buffer[y * self.width..y * self.width + columns]);
+        }
+        self.width = width;
+        self.height = height;
+        self.buffer = buffer;
+    }
+
     pub fn get_buffer(&self) -> &[u32] {
         &self.buffer
     }
 
+    pub fn get_buffer_mut(&mut self) -> &mut [u32] {
+        &mut self.buffer
+    }
+
+    /// Saves the buffer as a file in `format`, creating missing directories.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::error::SketchError;
+    /// # use simple_sketch::pixelbuffer::ImageFormat;
+    /// # use std::path::Path;
+    /// # fn save(canvas: &Canvas, path: &Path) -> Result<(), SketchError> {
+    /// canvas.pixel_buffer.save("frame.tga", ImageFormat::Tga)?;
+    /// // The format that matches the file name, PNG if it matches none
+    /// let format = ImageFormat::from_path(&path).unwrap_or(ImageFormat::Png);
+    /// canvas.pixel_buffer.save(&path, format)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn save(&self, path: impl AsRef<Path>, format: ImageFormat) -> Result<(), SketchError> {
+        match format {
+            ImageFormat::Png => self.save_png(path),
+            ImageFormat::Ppm => self.save_ppm(path),
+            ImageFormat::Pam => self.save_pam(path),
+            ImageFormat::Bmp => self.save_bmp(path),
+            ImageFormat::Tga => self.save_tga(path),
+        }
+    }
+
+    /// Saves the buffer as a PNG file with an alpha channel, creating missing directories.
+    /// Pixels keep the alpha of the color they were cleared or set to, so a buffer cleared to
+    /// a transparent background saves as a transparent image, while blended pixels are opaque.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), SketchError> {
+        save_file(path.as_ref(), |out| self.write_png(out, PngColor::Rgba))
+    }
+
+    /// Saves the buffer as a binary PPM file, an uncompressed RGB format without alpha that
+    /// takes no encoder to write and that most image tools read. Creates missing directories.
+    pub fn save_ppm(&self, path: impl AsRef<Path>) -> Result<(), SketchError> {
+        save_file(path.as_ref(), |mut out| {
+            write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+            for &pixel in &self.buffer {
+                let [_, r, g, b] = pixel.to_be_bytes();
+                out.write_all(&[r, g, b])?;
+            }
+            out.flush()
+        })
+    }
+
+    /// Saves the buffer as a PAM file, the variant of PPM with an alpha channel, with the
+    /// alpha of each pixel as `save_png` writes it. Creates missing directories.
+    pub fn save_pam(&self, path: impl AsRef<Path>) -> Result<(), SketchError> {
+        save_file(path.as_ref(), |mut out| {
+            write!(
+                out,
+                "P7\nWIDTH {}\nHEIGHT {}\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n",
+                self.width, self.height
+            )?;
+            for &pixel in &self.buffer {
+                let [a, r, g, b] = pixel.to_be_bytes();
+                out.write_all(&[r, g, b, a])?;
+            }
+            out.flush()
+        })
+    }
+
+    /// Saves the buffer as an uncompressed 32 bit BMP file with an alpha channel, which needs no
+    /// encoder and opens in Windows tools. Alpha is written as `save_png` writes it, though some
+    /// viewers ignore it. Creates missing directories.
+    pub fn save_bmp(&self, path: impl AsRef<Path>) -> Result<(), SketchError> {
+        const FILE_HEADER: u32 = 14;
+        const INFO_HEADER: u32 = 108;
+        let (width, height) = self.checked_file_size(i32::MAX as usize)?;
+        save_file(path.as_ref(), |mut out| {
+            let image_size = width * height * 4;
+            out.write_all(b"BM")?;
+            out.write_all(&(FILE_HEADER + INFO_HEADER + image_size).to_le_bytes())?;
+            out.write_all(&[0; 4])?;
+            out.write_all(&(FILE_HEADER + INFO_HEADER).to_le_bytes())?;
+            // A BITMAPV4HEADER, whose bit masks declare the fourth byte of each pixel as alpha
+            out.write_all(&INFO_HEADER.to_le_bytes())?;
+            out.write_all(&width.to_le_bytes())?;
+            out.write_all(&height.to_le_bytes())?;
+            out.write_all(&1u16.to_le_bytes())?;
+            out.write_all(&32u16.to_le_bytes())?;
+            // BI_BITFIELDS
+            out.write_all(&3u32.to_le_bytes())?;
+            out.write_all(&image_size.to_le_bytes())?;
+            // 72 dpi in pixels per meter
+            out.write_all(&2835u32.to_le_bytes())?;
+            out.write_all(&2835u32.to_le_bytes())?;
+            out.write_all(&[0; 8])?;
+            for mask in [0x00ff0000u32, 0x0000ff00, 0x000000ff, 0xff000000] {
+                out.write_all(&mask.to_le_bytes())?;
+            }
+            // LCS_sRGB, then the unused endpoints and gamma
+            out.write_all(b"BGRs")?;
+            out.write_all(&[0; 48])?;
+            // Rows are stored bottom to top
+            for row in self.buffer.chunks(self.width.max(1)).rev() {
+                for &pixel in row {
+                    out.write_all(&pixel.to_le_bytes())?;
+                }
+            }
+            out.flush()
+        })
+    }
+
+    /// Saves the buffer as an uncompressed 32 bit TGA file with an alpha channel, which needs no
+    /// encoder and is read by most game and video tools. Alpha is written as `save_png` writes
+    /// it. Creates missing directories.
+    pub fn save_tga(&self, path: impl AsRef<Path>) -> Result<(), SketchError> {
+        let (width, height) = self.checked_file_size(u16::MAX as usize)?;
+        save_file(path.as_ref(), |mut out| {
+            // No image ID or color map, uncompressed true color
+            out.write_all(&[0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0])?;
+            out.write_all(&(width as u16).to_le_bytes())?;
+            out.write_all(&(height as u16).to_le_bytes())?;
+            // 32 bits per pixel, 8 of them alpha, rows stored top to bottom
+            out.write_all(&[32, 0x28])?;
+            for &pixel in &self.buffer {
+                out.write_all(&pixel.to_le_bytes())?;
+            }
+            out.flush()
+        })
+    }
+
+    // The size of the buffer for the header of a file format whose sizes are limited to
+    // `limit`, failing for larger buffers instead of writing a broken file.
+    fn checked_file_size(&self, limit: usize) -> Result<(u32, u32), SketchError> {
+        if self.width > limit || self.height > limit || self.width.saturating_mul(self.height).saturating_mul(4) > i32::MAX as usize {
+            return Err(SketchError::InvalidSize { width: self.width, height: self.height });
+        }
+        Ok((self.width as u32, self.height as u32))
+    }
+
+    // Writes the buffer as a PNG, converting the packed ARGB pixels to the channels of `color`.
+    pub(crate) fn write_png(&self, out: impl Write, color: PngColor) -> io::Result<()> {
+        let pixels: Vec<u8> = match color {
+            PngColor::Rgb => self.buffer.iter().flat_map(|&pixel| {
+                let [_, r, g, b] = pixel.to_be_bytes();
+                [r, g, b]
+            }).collect(),
+            PngColor::Rgba => self.buffer.iter().flat_map(|&pixel| {
+                let [a, r, g, b] = pixel.to_be_bytes();
+                [r, g, b, a]
+            }).collect(),
+            // 8 bit channels widen exactly to 16 bits by repeating their byte
+            PngColor::Rgba16 => self.buffer.iter().flat_map(|&pixel| {
+                let [a, r, g, b] = pixel.to_be_bytes();
+                [r, r, g, g, b, b, a, a]
+            }).collect(),
+        };
+        png::write_png(out, self.width, self.height, color, &pixels)
+    }
+
+    /// Shrinks the buffer by `factor` along each axis, averaging each block of `factor` by
+    /// `factor` pixels into one, weighted by alpha. Rows and columns left over at the right
+    /// and bottom edges are dropped. Used to downsample supersampled renders.
+    pub fn downsample(&self, factor: usize) -> PixelBuffer {
+        let factor = factor.max(1);
+        let mut result = PixelBuffer::new(self.width / factor, self.height / factor);
+        let count = (factor * factor) as u64;
+        for y in 0..result.height {
+            for x in 0..result.width {
+                let mut sums = [0u64; 4];
+                for row in y * factor..(y + 1) * factor {
+                    let start = row * self.width + x * factor;
+                    for &pixel in &self.buffer[start..start + factor] {
+                        let [a, r, g, b] = pixel.to_be_bytes().map(u64::from);
+                        sums[0] += a;
+                        sums[1] += r * a;
+                        sums[2] += g * a;
+                        sums[3] += b * a;
+                    }
+                }
+                let channel = |sum: u64| (sum + sums[0] / 2).checked_div(sums[0]).unwrap_or(0) as u8;
+                let alpha = ((sums[0] + count / 2) / count) as u8;
+                result.buffer[y * result.width + x] = u32::from_be_bytes([alpha, channel(sums[1]), channel(sums[2]), channel(sums[3])]);
+            }
+        }
+        result
+    }
+
+    /// Counts the pixels with each value of red, green, blue, alpha and luminance.
+    pub fn histogram(&self) -> Histogram {
+        let mut histogram = Histogram { red: [0; 256], green: [0; 256], blue: [0; 256], alpha: [0; 256], luminance: [0; 256] };
+        for &pixel in &self.buffer {
+            let color = Color(pixel);
+            histogram.red[color.r() as usize] += 1;
+            histogram.green[color.g() as usize] += 1;
+            histogram.blue[color.b() as usize] += 1;
+            histogram.alpha[color.a() as usize] += 1;
+            histogram.luminance[luminance(color) as usize] += 1;
+        }
+        histogram
+    }
+
+    /// The average color of the pixels, with the colors weighted by alpha so transparent pixels
+    /// don't darken it, and the average alpha. Transparent black for an empty buffer.
+    pub fn mean_color(&self) -> Color {
+        let mut sums = [0u64; 4];
+        for &pixel in &self.buffer {
+            let [a, r, g, b] = pixel.to_be_bytes().map(u64::from);
+            sums[0] += a;
+            sums[1] += r * a;
+            sums[2] += g * a;
+            sums[3] += b * a;
+        }
+        let count = self.buffer.len() as u64;
+        let channel = |sum: u64| (sum + sums[0] / 2).checked_div(sums[0]).unwrap_or(0) as u8;
+        let alpha = (sums[0] + count / 2).checked_div(count).unwrap_or(0) as u8;
+        Color::new(channel(sums[1]), channel(sums[2]), channel(sums[3]), alpha)
+    }
+
+    /// The lowest and highest value of every channel among the pixels, as two colors that
+    /// need not appear in the buffer themselves. None for an empty buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # fn reseed(_: &mut Canvas) {}
+    /// # let mut canvas = Canvas::new(80, 60);
+    /// # let canvas = &mut canvas;
+    /// // Stop the feedback loop once it has faded to a flat color
+    /// let (min, max) = canvas.pixel_buffer.min_max().unwrap();
+    /// if max.r() - min.r() < 4 && max.g() - min.g() < 4 && max.b() - min.b() < 4 {
+    ///     reseed(canvas);
+    /// }
+    /// ```
+    pub fn min_max(&self) -> Option<(Color, Color)> {
+        let first = (*self.buffer.first()?).to_be_bytes();
+        let (min, max) = self.buffer.iter().fold((first, first), |(min, max), &pixel| {
+            let bytes = pixel.to_be_bytes();
+            ([0, 1, 2, 3].map(|i| min[i].min(bytes[i])), [0, 1, 2, 3].map(|i| max[i].max(bytes[i])))
+        });
+        Some((Color(u32::from_be_bytes(min)), Color(u32::from_be_bytes(max))))
+    }
+
+    /// Filters the buffer with a convolution kernel: every pixel becomes the weighted sum of
+    /// the pixels around it, with the edge pixels repeated past the edges. Colors are weighted
+    /// premultiplied by alpha and alpha is kept. See `Kernel` for the presets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::kernel::Kernel;
+    /// # let mut canvas = Canvas::new(80, 60);
+    /// canvas.pixel_buffer.convolve(&Kernel::sharpen());
+    /// canvas.pixel_buffer.convolve(&Kernel::gaussian(3.0));
+    /// ```
+    pub fn convolve(&mut self, kernel: &Kernel) {
+        self.buffer = kernel.apply(&self.buffer, self.width, self.height);
+    }
+
+    /// Copies the pixels inside `rect` into a new buffer. The rectangle is grown to whole
+    /// pixels and clipped to the buffer, so the result is empty when it lies outside.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::error::SketchError;
+    /// # use simple_sketch::geom::{Point, Rect};
+    /// # fn save(canvas: &Canvas) -> Result<(), SketchError> {
+    /// let detail = canvas.pixel_buffer.crop(Rect::new(Point::new(400.0, 300.0), Point::new(800.0, 600.0)));
+    /// detail.save_png("detail.png")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn crop(&self, rect: Rect) -> PixelBuffer {
+        let (width, height, buffer) = crop_pixels(&self.buffer, self.width, self.height, rect);
+        PixelBuffer { width, height, buffer }
+    }
+
+    // Creates a buffer from packed ARGB pixels, row by row, that fill `width` by `height`.
+    pub(crate) fn from_pixels(width: usize, height: usize, buffer: Vec<u32>) -> Self {
+        debug_assert_eq!(buffer.len(), width * height);
+        PixelBuffer { width, height, buffer }
+    }
+
+    pub(crate) fn into_pixels(self) -> Vec<u32> {
+        self.buffer
+    }
+
     pub fn clear(&mut self, color: Color) {
         for pixel in self.buffer.iter_mut() {
             *pixel = color.0;
@@ -38,21 +420,48 @@ impl PixelBuffer {
     pub fn blend_pixel(&mut self, x: i32, y: i32, color: &Color) {
         if x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32 {
             let index = y as usize * self.width + x as usize;
-            let background = self.buffer[index];
-    
-            let bg_color = Color(background);
-            let alpha = color.a() as f32 / 255.0;
-            let inv_alpha = 1.0 - alpha;
-    
-            let new_r = (inv_alpha * bg_color.r() as f32 + alpha * color.r() as f32) as u8;
-            let new_g = (inv_alpha * bg_color.g() as f32 + alpha * color.g() as f32) as u8;
-            let new_b = (inv_alpha * bg_color.b() as f32 + alpha * color.b() as f32) as u8;
-            let new_a = 255; // Assuming full opacity for the final color
-    
-            self.buffer[index] = Color::new(new_r, new_g, new_b, new_a).0;
+            self.buffer[index] = Self::blend(self.buffer[index], color);
         }
     }
 
+    // Blends `color` over the packed background pixel and returns the packed result. Over a
+    // transparent background, like on the offscreen canvases of `Canvas::create_graphics`, the
+    // result keeps the transparency left over.
+    pub fn blend(background: u32, color: &Color) -> u32 {
+        let bg_color = Color(background);
+        let alpha = color.a() as f32 / 255.0;
+        let inv_alpha = 1.0 - alpha;
+        if bg_color.a() < 255 {
+            let bg_alpha = bg_color.a() as f32 / 255.0 * inv_alpha;
+            let out_alpha = alpha + bg_alpha;
+            if out_alpha <= 0.0 {
+                return background;
+            }
+            let channel = |color: u8, bg: u8| ((alpha * color as f32 + bg_alpha * bg as f32) / out_alpha) as u8;
+            let (r, g, b) = (channel(color.r(), bg_color.r()), channel(color.g(), bg_color.g()), channel(color.b(), bg_color.b()));
+            return Color::new(r, g, b, (out_alpha * 255.0).round() as u8).0;
+        }
+
+        let new_r = (inv_alpha * bg_color.r() as f32 + alpha * color.r() as f32) as u8;
+        let new_g = (inv_alpha * bg_color.g() as f32 + alpha * color.g() as f32) as u8;
+        let new_b = (inv_alpha * bg_color.b() as f32 + alpha * color.b() as f32) as u8;
+        let new_a = 255;
+
+        Color::new(new_r, new_g, new_b, new_a).0
+    }
+
+    // Splits the buffer into horizontal bands of `rows` rows each, yielding the index of
+    // each band's first row along with its pixels. Bands are disjoint, so they can be
+    // drawn on from different threads.
+    pub fn bands_mut(&mut self, rows: usize) -> impl Iterator<Item = (usize, &mut [u32])> {
+        let rows = rows.max(1);
+        let width = self.width.max(1);
+        self.buffer
+            .chunks_mut(rows * width)
+            .enumerate()
+            .map(move |(i, band)| (i * rows, band))
+    }
+
     pub fn line_dda(&mut self, start: Point, end: Point, color: Color) {
         let dx = end.x as i32 - start.x as i32;
         let dy = end.y as i32 - start.y as i32;
@@ -108,68 +517,15 @@ impl PixelBuffer {
     
 
     // Wu's anti-aliased line drawing algorithm
-    pub fn line_wu(&mut self, mut start: Point, mut end: Point, color: Color) {
-        let steep = (end.y - start.y).abs() > (end.x - start.x).abs();
-        
-        if steep {
-            std::mem::swap(&mut start.x, &mut start.y);
-            std::mem::swap(&mut end.x, &mut end.y);
-        }
-        
-        if start.x > end.x {
-            std::mem::swap(&mut start, &mut end);
-        }
-
-        let dx = end.x - start.x;
-        let dy = end.y - start.y;
-        let gradient = if dx == 0.0 { 1.0 } else { dy / dx };
+    pub fn line_wu(&mut self, start: Point, end: Point, color: Color) {
+        crate::raster::wu_line(start, end, |x, y, alpha| self.plot(x, y, color, alpha));
+    }
 
-        // Handle first endpoint
-        let mut xend = start.x.round();
-        let mut yend = start.y + gradient * (xend - start.x);
-        let mut xgap = 1.0 - (start.x + 0.5).fract();
-        let xpxl1 = xend as i32;
-        let ypxl1 = yend.floor() as i32;
-        
-        if steep {
-            self.plot(ypxl1, xpxl1, color, (1.0 - yend.fract()) * xgap);
-            self.plot(ypxl1 + 1, xpxl1, color, yend.fract() * xgap);
-        } else {
-            self.plot(xpxl1, ypxl1, color, (1.0 - yend.fract()) * xgap);
-            self.plot(xpxl1, ypxl1 + 1, color, yend.fract() * xgap);
-        }
-        
-        let mut intery = yend + gradient;
-
-        // Handle second endpoint
-        xend = end.x.round();
-        yend = end.y + gradient * (xend - end.x);
-        xgap = (end.x + 0.5).fract();
-        let xpxl2 = xend as i32;
-        let ypxl2 = yend.floor() as i32;
-        
-        if steep {
-            self.plot(ypxl2, xpxl2, color, (1.0 - yend.fract()) * xgap);
-            self.plot(ypxl2 + 1, xpxl2, color, yend.fract() * xgap);
-        } else {
-            self.plot(xpxl2, ypxl2, color, (1.0 - yend.fract()) * xgap);
-            self.plot(xpxl2, ypxl2 + 1, color, yend.fract() * xgap);
-        }
-
-        // Main loop
-        if steep {
-            for x in (xpxl1 + 1)..xpxl2 {
-                self.plot(intery.floor() as i32, x, color, 1.0 - intery.fract());
-                self.plot(intery.floor() as i32 + 1, x, color, intery.fract());
-                intery += gradient;
-            }
-        } else {
-            for x in (xpxl1 + 1)..xpxl2 {
-                self.plot(x, intery.floor() as i32, color, 1.0 - intery.fract());
-                self.plot(x, intery.floor() as i32 + 1, color, intery.fract());
-                intery += gradient;
-            }
-        }
+    /// Draws an antialiased line like `line_wu`, with its endpoints snapped to 1/256 of a pixel
+    /// and the line stepped in fixed point. The ends of slowly moving lines then don't wobble
+    /// with the rounding of their coordinates.
+    pub fn line_wu_fixed(&mut self, start: Point, end: Point, color: Color) {
+        crate::raster::wu_line_fixed(start, end, |x, y, alpha| self.plot(x, y, color, alpha));
     }
     fn plot(&mut self, x: i32, y: i32, color: Color, alpha: f32) {
         let aa_color = color.with_alpha((color.a() as f32 * alpha) as u8);
@@ -212,4 +568,33 @@ impl PixelBuffer {
 
 
 
-}
\ No newline at end of file
+}
+
+// Copies the pixels inside `rect` out of the `width` by `height` pixels of `pixels`, after
+// growing it to whole pixels and clipping it, and returns the size and pixels of the copy.
+// The sRGB luminance of a color, from 0 to 255
+fn luminance(color: Color) -> u8 {
+    ((color.r() as u32 * 54 + color.g() as u32 * 183 + color.b() as u32 * 19) >> 8) as u8
+}
+
+pub(crate) fn crop_pixels(pixels: &[u32], width: usize, height: usize, rect: Rect) -> (usize, usize, Vec<u32>) {
+    let clamp = |value: f32, size: usize| (value.max(0.0) as usize).min(size);
+    let (left, top) = (clamp(rect.min.x.floor(), width), clamp(rect.min.y.floor(), height));
+    let (right, bottom) = (clamp(rect.max.x.ceil(), width).max(left), clamp(rect.max.y.ceil(), height).max(top));
+    let mut result = Vec::with_capacity((right - left) * (bottom - top));
+    for y in top..bottom {
+        result.extend_from_slice(&pixels[y * width + left..y * width + right]);
+    }
+    (right - left, bottom - top, result)
+}
+
+// Creates the file at `path` and its missing directories, and writes it with `write`.
+pub(crate) fn save_file(path: &Path, write: impl FnOnce(BufWriter<File>) -> io::Result<()>) -> Result<(), SketchError> {
+    let save = || {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        write(BufWriter::new(File::create(path)?))
+    };
+    save().map_err(|e| io::Error::new(e.kind(), format!("could not save {}: {e}", path.display())).into())
+}
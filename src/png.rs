@@ -0,0 +1,578 @@
+//! A minimal PNG encoder and decoder.
+//!
+//! Pixel rows are stored in uncompressed deflate blocks, which makes the files larger than
+//! those of a full encoder but keeps the encoder small and free of dependencies. The decoder
+//! reads all standard PNG files: every color type and bit depth, palettes, transparency and
+//! interlacing.
+
+use std::io::{self, Write};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// Largest amount of data in a single uncompressed deflate block.
+const MAX_STORED_BLOCK: usize = 65535;
+
+/// The channels of each pixel in a PNG file, 8 bits each, or 16 bits each in big-endian byte
+/// order for `Rgba16`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PngColor {
+    Rgb,
+    Rgba,
+    Rgba16,
+}
+
+impl PngColor {
+    fn bytes_per_pixel(self) -> usize {
+        match self {
+            PngColor::Rgb => 3,
+            PngColor::Rgba => 4,
+            PngColor::Rgba16 => 8,
+        }
+    }
+
+    // The bit depth and color type in the PNG header
+    fn header(self) -> [u8; 2] {
+        match self {
+            PngColor::Rgb => [8, 2],
+            PngColor::Rgba => [8, 6],
+            PngColor::Rgba16 => [16, 6],
+        }
+    }
+}
+
+/// Writes an image as a PNG file.
+///
+/// # Arguments
+/// * `out` - Where to write the file.
+/// * `width`, `height` - The size of the image in pixels.
+/// * `color` - The channels of each pixel.
+/// * `pixels` - The rows of the image from top to bottom, without padding, with the channels
+///   of each pixel in the order of their names in `color`.
+pub(crate) fn write_png(out: impl Write, width: usize, height: usize, color: PngColor, pixels: &[u8]) -> io::Result<()> {
+    if pixels.len() != width * color.bytes_per_pixel() * height {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "pixel data doesn't match the image size"));
+    }
+    let mut writer = PngWriter::new(out, width, height, color)?;
+    writer.write_rows(pixels)?;
+    writer.finish()
+}
+
+/// Writes a PNG file a few rows at a time, for images too large to keep in memory at once.
+/// Each call to `write_rows` becomes its own IDAT chunk.
+pub(crate) struct PngWriter<W: Write> {
+    out: W,
+    row_size: usize,
+    rows_left: usize,
+    /// The checksum of the image data written so far.
+    adler: u32,
+    started: bool,
+}
+
+impl<W: Write> PngWriter<W> {
+    /// Writes the PNG header for an image of `width` by `height` pixels with the channels of
+    /// `color`.
+    pub(crate) fn new(mut out: W, width: usize, height: usize, color: PngColor) -> io::Result<Self> {
+        let (Ok(png_width), Ok(png_height)) = (u32::try_from(width), u32::try_from(height)) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "image too large for PNG"));
+        };
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&png_width.to_be_bytes());
+        header.extend_from_slice(&png_height.to_be_bytes());
+        // No interlacing
+        header.extend_from_slice(&color.header());
+        header.extend_from_slice(&[0, 0, 0]);
+        out.write_all(&SIGNATURE)?;
+        write_chunk(&mut out, b"IHDR", &header)?;
+        Ok(PngWriter { out, row_size: width * color.bytes_per_pixel(), rows_left: height, adler: 1, started: false })
+    }
+
+    /// Writes the next rows of the image, without padding, with the channels of each pixel in
+    /// the order of their names in the writer's color.
+    pub(crate) fn write_rows(&mut self, pixels: &[u8]) -> io::Result<()> {
+        let rows = pixels.len().checked_div(self.row_size).unwrap_or(self.rows_left);
+        if pixels.len() != rows * self.row_size || rows > self.rows_left {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "pixel data doesn't match the image size"));
+        }
+        if rows == 0 {
+            return Ok(());
+        }
+        // Every row starts with its filter type, 0 for none
+        let mut data = Vec::with_capacity((self.row_size + 1) * rows);
+        for row in 0..rows {
+            data.push(0);
+            data.extend_from_slice(&pixels[row * self.row_size..(row + 1) * self.row_size]);
+        }
+        self.rows_left -= rows;
+        self.adler = adler32(self.adler, &data);
+
+        let mut stream = Vec::with_capacity(data.len() + data.len().div_ceil(MAX_STORED_BLOCK) * 5 + 2);
+        self.push_zlib_header(&mut stream);
+        for chunk in data.chunks(MAX_STORED_BLOCK) {
+            let length = chunk.len() as u16;
+            stream.push(0);
+            stream.extend_from_slice(&length.to_le_bytes());
+            stream.extend_from_slice(&(!length).to_le_bytes());
+            stream.extend_from_slice(chunk);
+        }
+        write_chunk(&mut self.out, b"IDAT", &stream)
+    }
+
+    /// Ends the image data and the file. Fails unless all rows were written.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        if self.rows_left > 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "pixel data doesn't match the image size"));
+        }
+        // An empty final block ends the deflate stream
+        let mut stream = Vec::with_capacity(11);
+        self.push_zlib_header(&mut stream);
+        stream.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+        stream.extend_from_slice(&self.adler.to_be_bytes());
+        write_chunk(&mut self.out, b"IDAT", &stream)?;
+        write_chunk(&mut self.out, b"IEND", &[])?;
+        self.out.flush()
+    }
+
+    fn push_zlib_header(&mut self, stream: &mut Vec<u8>) {
+        if !self.started {
+            // Deflate with a 32K window, no preset dictionary and the fastest compression level
+            stream.extend_from_slice(&[0x78, 0x01]);
+            self.started = true;
+        }
+    }
+}
+
+/// Decodes a PNG file into its width, height and pixels, packed as ARGB like `Color`.
+pub(crate) fn read_png(data: &[u8]) -> Result<(usize, usize, Vec<u32>), String> {
+    if !data.starts_with(&SIGNATURE) {
+        return Err("not a PNG file".to_string());
+    }
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut compressed = Vec::new();
+    let mut rest = &data[SIGNATURE.len()..];
+    loop {
+        if rest.len() < 12 {
+            return Err("truncated PNG file".to_string());
+        }
+        let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let kind = &rest[4..8];
+        let Some(chunk) = rest.get(8..8 + length) else {
+            return Err("truncated PNG chunk".to_string());
+        };
+        match kind {
+            b"IHDR" => header = Some(Header::parse(chunk)?),
+            b"PLTE" => palette = chunk,
+            b"tRNS" => transparency = chunk,
+            b"IDAT" => compressed.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ if kind[0] & 0x20 == 0 => return Err(format!("unsupported critical PNG chunk {}", String::from_utf8_lossy(kind))),
+            _ => {}
+        }
+        rest = rest.get(12 + length..).unwrap_or_default();
+    }
+    let header = header.ok_or("PNG file without header")?;
+    let data = inflate_zlib(&compressed)?;
+
+    let mut pixels = vec![0; header.width * header.height];
+    let passes: &[(usize, usize, usize, usize)] = if header.interlaced {
+        &[(0, 0, 8, 8), (4, 0, 8, 8), (0, 4, 4, 8), (2, 0, 4, 4), (0, 2, 2, 4), (1, 0, 2, 2), (0, 1, 1, 2)]
+    } else {
+        &[(0, 0, 1, 1)]
+    };
+    let mut data = data.as_slice();
+    for &(x0, y0, dx, dy) in passes {
+        let width = (header.width + dx - 1 - x0) / dx;
+        let height = (header.height + dy - 1 - y0) / dy;
+        if width == 0 || height == 0 {
+            continue;
+        }
+        let row_size = (width * header.bits_per_pixel()).div_ceil(8);
+        let size = (row_size + 1) * height;
+        let Some(pass) = data.get(..size) else {
+            return Err("PNG image data is too short".to_string());
+        };
+        data = &data[size..];
+        let rows = unfilter(pass, row_size, header.bits_per_pixel().div_ceil(8))?;
+        for (y, row) in rows.chunks(row_size).enumerate() {
+            for x in 0..width {
+                pixels[(y0 + y * dy) * header.width + x0 + x * dx] = header.pixel(row, x, palette, transparency);
+            }
+        }
+    }
+    Ok((header.width, header.height, pixels))
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: usize,
+    color_type: u8,
+    interlaced: bool,
+}
+
+impl Header {
+    fn parse(chunk: &[u8]) -> Result<Self, String> {
+        let [w0, w1, w2, w3, h0, h1, h2, h3, bit_depth, color_type, compression, filter, interlace] = *chunk else {
+            return Err("invalid PNG header".to_string());
+        };
+        let header = Header {
+            width: u32::from_be_bytes([w0, w1, w2, w3]) as usize,
+            height: u32::from_be_bytes([h0, h1, h2, h3]) as usize,
+            bit_depth: bit_depth as usize,
+            color_type,
+            interlaced: interlace == 1,
+        };
+        let valid_depth = match color_type {
+            0 => matches!(bit_depth, 1 | 2 | 4 | 8 | 16),
+            3 => matches!(bit_depth, 1 | 2 | 4 | 8),
+            2 | 4 | 6 => matches!(bit_depth, 8 | 16),
+            _ => false,
+        };
+        if !valid_depth || compression != 0 || filter != 0 || interlace > 1 {
+            return Err(format!("unsupported PNG format: color type {color_type}, bit depth {bit_depth}"));
+        }
+        if header.width == 0 || header.height == 0 || header.width.saturating_mul(header.height) > 1 << 28 {
+            return Err(format!("invalid PNG size {}x{}", header.width, header.height));
+        }
+        Ok(header)
+    }
+
+    fn channels(&self) -> usize {
+        match self.color_type {
+            0 | 3 => 1,
+            4 => 2,
+            2 => 3,
+            _ => 4,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * self.bit_depth
+    }
+
+    // Reads the pixel at `x` in an unfiltered row as ARGB.
+    fn pixel(&self, row: &[u8], x: usize, palette: &[u8], transparency: &[u8]) -> u32 {
+        // Samples at their full precision, and scaled to 8 bits
+        let sample = |index: usize| -> (u16, u8) {
+            match self.bit_depth {
+                16 => {
+                    let value = u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]);
+                    (value, (value >> 8) as u8)
+                }
+                8 => (row[index] as u16, row[index]),
+                bits => {
+                    let bit = index * bits;
+                    let value = (row[bit / 8] >> (8 - bits - bit % 8)) as u16 & ((1 << bits) - 1);
+                    // Gray levels are spread over the full range, palette indices aren't scaled
+                    (value, (value * 255 / ((1 << bits) - 1)) as u8)
+                }
+            }
+        };
+        let channels = self.channels();
+        let key = |channel: usize| transparency.get(channel * 2..channel * 2 + 2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]));
+        let (r, g, b, a) = match self.color_type {
+            0 => {
+                let (value, gray) = sample(x);
+                (gray, gray, gray, if key(0) == Some(value) { 0 } else { 255 })
+            }
+            3 => {
+                let (index, _) = sample(x);
+                let index = index as usize;
+                let rgb = palette.get(index * 3..index * 3 + 3).unwrap_or(&[0, 0, 0]);
+                (rgb[0], rgb[1], rgb[2], transparency.get(index).copied().unwrap_or(255))
+            }
+            4 => (sample(x * 2).1, sample(x * 2).1, sample(x * 2).1, sample(x * 2 + 1).1),
+            2 => {
+                let (r, g, b) = (sample(x * channels), sample(x * channels + 1), sample(x * channels + 2));
+                let transparent = key(0) == Some(r.0) && key(1) == Some(g.0) && key(2) == Some(b.0);
+                (r.1, g.1, b.1, if transparent { 0 } else { 255 })
+            }
+            _ => (sample(x * 4).1, sample(x * 4 + 1).1, sample(x * 4 + 2).1, sample(x * 4 + 3).1),
+        };
+        u32::from_be_bytes([a, r, g, b])
+    }
+}
+
+// Undoes the filter of every row, returning the rows without their filter type bytes.
+fn unfilter(data: &[u8], row_size: usize, bytes_per_pixel: usize) -> Result<Vec<u8>, String> {
+    let mut rows = vec![0; row_size * (data.len() / (row_size + 1))];
+    let mut previous: Option<usize> = None;
+    for (y, line) in data.chunks(row_size + 1).enumerate() {
+        let (filter, line) = (line[0], &line[1..]);
+        let start = y * row_size;
+        for x in 0..row_size {
+            let left = if x >= bytes_per_pixel { rows[start + x - bytes_per_pixel] } else { 0 };
+            let up = previous.map_or(0, |previous| rows[previous + x]);
+            let up_left = match previous {
+                Some(previous) if x >= bytes_per_pixel => rows[previous + x - bytes_per_pixel],
+                _ => 0,
+            };
+            let prediction = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(format!("invalid PNG filter type {filter}")),
+            };
+            rows[start + x] = line[x].wrapping_add(prediction);
+        }
+        previous = Some(start);
+    }
+    Ok(rows)
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let (to_left, to_up, to_up_left) = ((estimate - left as i16).abs(), (estimate - up as i16).abs(), (estimate - up_left as i16).abs());
+    if to_left <= to_up && to_left <= to_up_left {
+        left
+    } else if to_up <= to_up_left {
+        up
+    } else {
+        up_left
+    }
+}
+
+// Decompresses a zlib stream. The checksum isn't verified, PNG chunks have their own.
+fn inflate_zlib(data: &[u8]) -> Result<Vec<u8>, String> {
+    let [method, flags, ..] = *data else {
+        return Err("truncated zlib stream".to_string());
+    };
+    if method & 0x0F != 8 || (u16::from(method) << 8 | u16::from(flags)) % 31 != 0 || flags & 0x20 != 0 {
+        return Err("invalid zlib stream".to_string());
+    }
+    inflate(&data[2..])
+}
+
+// Decompresses a raw deflate stream.
+fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut bits = BitReader { data, position: 0, buffer: 0, count: 0 };
+    let mut out = Vec::new();
+    loop {
+        let last = bits.read(1)? == 1;
+        match bits.read(2)? {
+            0 => {
+                bits.align();
+                let length = bits.read(16)? as usize;
+                let complement = bits.read(16)? as usize;
+                if length != !complement & 0xFFFF {
+                    return Err("corrupt stored deflate block".to_string());
+                }
+                for _ in 0..length {
+                    out.push(bits.read(8)? as u8);
+                }
+            }
+            1 => {
+                let mut lengths = [0; 288 + 32];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..288].fill(8);
+                lengths[288..].fill(5);
+                let literals = Huffman::new(&lengths[..288])?;
+                let distances = Huffman::new(&lengths[288..])?;
+                inflate_block(&mut bits, &mut out, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_tables(&mut bits)?;
+                inflate_block(&mut bits, &mut out, &literals, &distances)?;
+            }
+            _ => return Err("invalid deflate block type".to_string()),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+fn inflate_block(bits: &mut BitReader, out: &mut Vec<u8>, literals: &Huffman, distances: &Huffman) -> Result<(), String> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let index = symbol - 257;
+                if index >= LENGTH_BASE.len() {
+                    return Err("invalid deflate length code".to_string());
+                }
+                let length = LENGTH_BASE[index] as usize + bits.read(LENGTH_EXTRA[index] as u32)? as usize;
+                let index = distances.decode(bits)? as usize;
+                if index >= DISTANCE_BASE.len() {
+                    return Err("invalid deflate distance code".to_string());
+                }
+                let distance = DISTANCE_BASE[index] as usize + bits.read(DISTANCE_EXTRA[index] as u32)? as usize;
+                if distance > out.len() {
+                    return Err("deflate distance too far back".to_string());
+                }
+                // Copies may overlap the bytes they produce, so go byte by byte
+                let start = out.len() - distance;
+                for i in 0..length {
+                    out.push(out[start + i]);
+                }
+            }
+        }
+    }
+}
+
+// Reads the code lengths of a dynamic block and builds its literal and distance codes.
+fn dynamic_tables(bits: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    const ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+    let literal_count = bits.read(5)? as usize + 257;
+    let distance_count = bits.read(5)? as usize + 1;
+    let code_count = bits.read(4)? as usize + 4;
+    let mut code_lengths = [0; 19];
+    for &index in &ORDER[..code_count] {
+        code_lengths[index] = bits.read(3)? as u8;
+    }
+    let codes = Huffman::new(&code_lengths)?;
+
+    let mut lengths = vec![0; literal_count + distance_count];
+    let mut index = 0;
+    while index < lengths.len() {
+        let symbol = codes.decode(bits)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 if index > 0 => (lengths[index - 1], 3 + bits.read(2)?),
+            17 => (0, 3 + bits.read(3)?),
+            18 => (0, 11 + bits.read(7)?),
+            _ => return Err("invalid deflate code lengths".to_string()),
+        };
+        for _ in 0..repeat {
+            *lengths.get_mut(index).ok_or("too many deflate code lengths")? = value;
+            index += 1;
+        }
+    }
+    Ok((Huffman::new(&lengths[..literal_count])?, Huffman::new(&lengths[literal_count..])?))
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    // Reads `count` bits, least significant first.
+    fn read(&mut self, count: u32) -> Result<u32, String> {
+        while self.count < count {
+            let byte = *self.data.get(self.position).ok_or("truncated deflate stream")?;
+            self.position += 1;
+            self.buffer |= (byte as u32) << self.count;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1u64 << count) - 1) as u32;
+        self.buffer >>= count;
+        self.count -= count;
+        Ok(value)
+    }
+
+    // Skips to the next byte boundary.
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+}
+
+// A canonical Huffman code, decoded one bit at a time.
+struct Huffman {
+    /// The number of codes of each length.
+    counts: [u16; 16],
+    /// The symbols ordered by code.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, String> {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..16 {
+            code |= bits.read(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("invalid deflate code".to_string())
+    }
+}
+
+fn write_chunk(out: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let length = u32::try_from(data.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "image too large for PNG"))?;
+    out.write_all(&length.to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32(crc32(0xFFFF_FFFF, kind), data) ^ 0xFFFF_FFFF;
+    out.write_all(&crc.to_be_bytes())
+}
+
+// Continues an Adler-32 checksum over more data. Start with 1.
+fn adler32(adler: u32, data: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+    let (mut a, mut b) = (adler & 0xFFFF, adler >> 16);
+    // Sums of up to 5552 bytes can't overflow before taking the modulus
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MODULUS;
+        b %= MODULUS;
+    }
+    (b << 16) | a
+}
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+// Continues a CRC over more data. Start with all bits set and invert the result.
+fn crc32(crc: u32, data: &[u8]) -> u32 {
+    data.iter().fold(crc, |crc, &byte| CRC_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8))
+}
@@ -0,0 +1,199 @@
+//! Post-processing effects applied to the whole frame before it is shown, like bloom for
+//! neon-style glows or a vignette. See `Canvas::set_post_effects`.
+//!
+//! Effects work on premultiplied colors in floating point, so blurs don't darken the edges of
+//! transparent areas. The Gaussian blur is approximated by three box blurs, which costs the
+//! same for any radius.
+
+use crate::color::Color;
+use crate::pixelbuffer::PixelBuffer;
+
+/// An effect applied to every pixel of the frame. Distances are in logical pixels, and are
+/// scaled by the pixel density of the canvas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Effect {
+    /// Averages every pixel with the pixels up to `radius` away along each axis.
+    BoxBlur { radius: f32 },
+    /// A Gaussian blur with a standard deviation of `sigma`. It reaches about three times as far.
+    GaussianBlur { sigma: f32 },
+    /// Makes the parts brighter than `threshold`, from 0 to 1, glow: they are blurred with a
+    /// standard deviation of `sigma` and added to the frame, multiplied by `intensity`.
+    Bloom { threshold: f32, sigma: f32, intensity: f32 },
+    /// Shifts the red channel away from the center and the blue channel towards it, by up to
+    /// `offset` in the corners, like the color fringes of a cheap lens.
+    ChromaticAberration { offset: f32 },
+    /// Darkens the frame towards its corners. Darkening starts at `radius`, as a fraction of
+    /// the distance from the center to the corners, and reaches `strength`, from 0 to 1, in
+    /// the corners.
+    Vignette { strength: f32, radius: f32 },
+}
+
+impl Effect {
+    /// Applies the effect to a buffer with `scale` buffer pixels per logical pixel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::pixelbuffer::PixelBuffer;
+    /// # use simple_sketch::postprocess::Effect;
+    /// # let canvas = Canvas::new(80, 60);
+    /// // Soften a copy of the frame
+    /// let mut frame: PixelBuffer = canvas.get_image().into();
+    /// Effect::GaussianBlur { sigma: 2.0 }.apply(&mut frame, 1.0);
+    /// ```
+    pub fn apply(&self, buffer: &mut PixelBuffer, scale: f32) {
+        let (width, height) = (buffer.width, buffer.height);
+        if width == 0 || height == 0 {
+            return;
+        }
+        let mut pixels: Vec<[f32; 4]> = buffer.get_buffer().iter().map(|&pixel| premultiply(pixel)).collect();
+        match *self {
+            Effect::BoxBlur { radius } => {
+                let radius = (radius * scale).round().max(0.0) as usize;
+                box_blur(&mut pixels, width, height, radius);
+            }
+            Effect::GaussianBlur { sigma } => gaussian_blur(&mut pixels, width, height, sigma * scale),
+            Effect::Bloom { threshold, sigma, intensity } => {
+                let mut glow: Vec<[f32; 4]> = pixels
+                    .iter()
+                    .map(|&[r, g, b, a]| {
+                        // Only the brightness above the threshold glows, so the glow fades in
+                        let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+                        let factor = if luminance > 0.0 { (luminance - threshold).max(0.0) / luminance } else { 0.0 };
+                        [r * factor, g * factor, b * factor, a * factor]
+                    })
+                    .collect();
+                gaussian_blur(&mut glow, width, height, sigma * scale);
+                for (pixel, glow) in pixels.iter_mut().zip(&glow) {
+                    let alpha = (pixel[3] + glow[3] * intensity).min(1.0);
+                    let add = |channel: usize| (pixel[channel] + glow[channel] * intensity).min(alpha);
+                    *pixel = [add(0), add(1), add(2), alpha];
+                }
+            }
+            Effect::ChromaticAberration { offset } => {
+                let source = pixels.clone();
+                let center = (width as f32 / 2.0, height as f32 / 2.0);
+                let corner = center.0.hypot(center.1);
+                let shift = offset * scale / corner;
+                for y in 0..height {
+                    for x in 0..width {
+                        let (dx, dy) = (x as f32 + 0.5 - center.0, y as f32 + 0.5 - center.1);
+                        let pixel = &mut pixels[y * width + x];
+                        pixel[0] = sample_channel(&source, width, height, 0, x as f32 + dx * shift, y as f32 + dy * shift);
+                        pixel[2] = sample_channel(&source, width, height, 2, x as f32 - dx * shift, y as f32 - dy * shift);
+                        // Shifted channels can't be more opaque than the pixel
+                        pixel[0] = pixel[0].min(pixel[3]);
+                        pixel[2] = pixel[2].min(pixel[3]);
+                    }
+                }
+            }
+            Effect::Vignette { strength, radius } => {
+                let center = (width as f32 / 2.0, height as f32 / 2.0);
+                let corner = center.0.hypot(center.1);
+                for y in 0..height {
+                    for x in 0..width {
+                        let distance = (x as f32 + 0.5 - center.0).hypot(y as f32 + 0.5 - center.1) / corner;
+                        let t = ((distance - radius) / (1.0 - radius).max(f32::EPSILON)).clamp(0.0, 1.0);
+                        let darkness = strength.clamp(0.0, 1.0) * t * t * (3.0 - 2.0 * t);
+                        let pixel = &mut pixels[y * width + x];
+                        for channel in &mut pixel[..3] {
+                            *channel *= 1.0 - darkness;
+                        }
+                    }
+                }
+            }
+        }
+        for (pixel, value) in buffer.get_buffer_mut().iter_mut().zip(&pixels) {
+            *pixel = unpremultiply(*value);
+        }
+    }
+}
+
+fn premultiply(pixel: u32) -> [f32; 4] {
+    let color = Color(pixel);
+    let alpha = color.a() as f32 / 255.0;
+    [color.r() as f32 / 255.0 * alpha, color.g() as f32 / 255.0 * alpha, color.b() as f32 / 255.0 * alpha, alpha]
+}
+
+fn unpremultiply([r, g, b, a]: [f32; 4]) -> u32 {
+    if a <= 0.0 {
+        return 0;
+    }
+    let channel = |value: f32| (value / a * 255.0).round().clamp(0.0, 255.0) as u8;
+    Color::new(channel(r), channel(g), channel(b), (a * 255.0).round().clamp(0.0, 255.0) as u8).0
+}
+
+// Blurs with three box blurs whose sizes together approximate a Gaussian with the standard
+// deviation `sigma`, in buffer pixels
+fn gaussian_blur(pixels: &mut [[f32; 4]], width: usize, height: usize, sigma: f32) {
+    if sigma <= 0.0 {
+        return;
+    }
+    const PASSES: f32 = 3.0;
+    let ideal = (12.0 * sigma * sigma / PASSES + 1.0).sqrt();
+    let mut lower = ideal.floor();
+    if lower % 2.0 == 0.0 {
+        lower -= 1.0;
+    }
+    let lower_passes = ((12.0 * sigma * sigma - PASSES * lower * lower - 4.0 * PASSES * lower - 3.0 * PASSES) / (-4.0 * lower - 4.0)).round();
+    for pass in 0..PASSES as usize {
+        let size = if (pass as f32) < lower_passes { lower } else { lower + 2.0 };
+        box_blur(pixels, width, height, ((size - 1.0) / 2.0) as usize);
+    }
+}
+
+// Averages every pixel with the pixels up to `radius` away, horizontally and then vertically,
+// repeating the edge pixels past the edges
+fn box_blur(pixels: &mut [[f32; 4]], width: usize, height: usize, radius: usize) {
+    if radius == 0 {
+        return;
+    }
+    let mut line = Vec::with_capacity(width.max(height));
+    for y in 0..height {
+        line.clear();
+        line.extend_from_slice(&pixels[y * width..(y + 1) * width]);
+        blur_line(&line, radius, |x, value| pixels[y * width + x] = value);
+    }
+    for x in 0..width {
+        line.clear();
+        line.extend((0..height).map(|y| pixels[y * width + x]));
+        blur_line(&line, radius, |y, value| pixels[y * width + x] = value);
+    }
+}
+
+// Blurs a line of pixels with a running sum, passing every result to `store`
+fn blur_line(line: &[[f32; 4]], radius: usize, mut store: impl FnMut(usize, [f32; 4])) {
+    let length = line.len() as isize;
+    let at = |i: isize| line[i.clamp(0, length - 1) as usize];
+    let radius = radius as isize;
+    let weight = 1.0 / (2 * radius + 1) as f32;
+    let mut sum = [0.0; 4];
+    for i in -radius..=radius {
+        let value = at(i);
+        for channel in 0..4 {
+            sum[channel] += value[channel];
+        }
+    }
+    for i in 0..length {
+        store(i as usize, sum.map(|total| total * weight));
+        let (entering, leaving) = (at(i + radius + 1), at(i - radius));
+        for channel in 0..4 {
+            sum[channel] += entering[channel] - leaving[channel];
+        }
+    }
+}
+
+// Samples one channel at a position in pixels, interpolating bilinearly between pixel centers
+// and repeating the edge pixels past the edges
+fn sample_channel(pixels: &[[f32; 4]], width: usize, height: usize, channel: usize, x: f32, y: f32) -> f32 {
+    let (x, y) = (x.clamp(0.0, (width - 1) as f32), y.clamp(0.0, (height - 1) as f32));
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let value = |x: usize, y: usize| pixels[y * width + x][channel];
+    let top = value(x0, y0) + (value(x1, y0) - value(x0, y0)) * fx;
+    let bottom = value(x0, y1) + (value(x1, y1) - value(x0, y1)) * fx;
+    top + (bottom - top) * fy
+}
@@ -0,0 +1,201 @@
+//! Timing of the stages of every frame, to find out where a sketch spends its time.
+//!
+//! Profiling is turned on with `ApplicationRunner::set_profiling`. The runner then measures
+//! how long each drawn frame spends in `Application::update`, in `Application::draw` and in
+//! showing the frame in the window, and the canvas measures how much of the drawing went into
+//! lines, fills and strokes. The `FrameProfiler` in `Context::profiler` keeps the timings of
+//! the last frames and the rate they were drawn at, and `ApplicationRunner::set_profiler_overlay`
+//! draws its averages over the window with the debug font:
+//!
+//! ```
+//! # use simple_sketch::app::{Application, ApplicationRunner};
+//! # fn profile<A: Application>(runner: &mut ApplicationRunner<A>) {
+//! runner.set_profiling(true);
+//! runner.set_profiler_overlay(true);
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::command::{DrawCommand, Style};
+use crate::geom::{Point, Transform};
+
+/// Number of frames the averages and the frame rate are taken over.
+const ROLLING_FRAMES: usize = 60;
+
+/// Distance in pixels between the overlay's text and its edges.
+const OVERLAY_PADDING: f32 = 4.0;
+
+/// Size in pixels of a character and a line of the debug font.
+const OVERLAY_CHARACTER: (f32, f32) = (8.0, 10.0);
+
+/// A part of a frame whose time is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// All `Application::update` calls of the frame.
+    Update,
+    /// `Application::draw`, including the lines, fills and strokes it draws, and the depth
+    /// queue and layers the canvas composites afterwards.
+    Draw,
+    /// Lines and curves.
+    Lines,
+    /// Shapes without a stroke, and backgrounds.
+    Fills,
+    /// Shapes with a stroke. Their fill is blended in the same pass as their stroke, and is
+    /// counted here too.
+    Strokes,
+    /// Showing the frame in the window. `run_threaded` shows frames on another thread than
+    /// the one drawing them, and doesn't measure it.
+    Present,
+}
+
+impl Stage {
+    /// Every stage, in the order they're listed in the overlay.
+    pub const ALL: [Stage; 6] = [Stage::Update, Stage::Draw, Stage::Lines, Stage::Fills, Stage::Strokes, Stage::Present];
+
+    /// The stage's name in the overlay.
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Update => "update",
+            Stage::Draw => "draw",
+            Stage::Lines => "lines",
+            Stage::Fills => "fills",
+            Stage::Strokes => "strokes",
+            Stage::Present => "present",
+        }
+    }
+
+    /// The stage a drawing command is counted in when drawn with `style`.
+    pub(crate) fn of_command(command: &DrawCommand, style: &Style) -> Stage {
+        match command {
+            DrawCommand::Line { .. } | DrawCommand::Bezier { .. } => Stage::Lines,
+            DrawCommand::Background(_) => Stage::Fills,
+            _ if style.stroke.is_some() => Stage::Strokes,
+            _ => Stage::Fills,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The time spent in each stage of one frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StageTimes([Duration; Stage::ALL.len()]);
+
+impl StageTimes {
+    /// The time spent in `stage`.
+    pub fn get(&self, stage: Stage) -> Duration {
+        self.0[stage.index()]
+    }
+
+    /// Adds `time` to the time spent in `stage`.
+    pub(crate) fn add(&mut self, stage: Stage, time: Duration) {
+        self.0[stage.index()] += time;
+    }
+
+    /// Adds the times spent in every stage of `other`.
+    pub(crate) fn add_all(&mut self, other: &StageTimes) {
+        for stage in Stage::ALL {
+            self.add(stage, other.get(stage));
+        }
+    }
+}
+
+/// The stage timings of the last frames a sketch drew.
+#[derive(Debug, Clone, Default)]
+pub struct FrameProfiler {
+    /// Timings of the last completed frames, the most recent last.
+    frames: VecDeque<StageTimes>,
+    /// Start times of the last frames, the most recent last, for the frame rate.
+    starts: VecDeque<Instant>,
+    /// Timings of the frame being drawn, if one was started.
+    current: Option<StageTimes>,
+}
+
+impl FrameProfiler {
+    /// The timings of the last completed frame, or zero before one was completed.
+    pub fn last(&self) -> StageTimes {
+        self.frames.back().copied().unwrap_or_default()
+    }
+
+    /// The average time spent in `stage` over the last frames.
+    pub fn average(&self, stage: Stage) -> Duration {
+        let total: Duration = self.frames.iter().map(|times| times.get(stage)).sum();
+        total.checked_div(self.frames.len() as u32).unwrap_or_default()
+    }
+
+    /// The number of frames drawn per second over the last frames, or 0.0 before two frames
+    /// were drawn. Paused frames don't count.
+    pub fn fps(&self) -> f32 {
+        let (Some(first), Some(last)) = (self.starts.front(), self.starts.back()) else {
+            return 0.0;
+        };
+        let span = last.duration_since(*first).as_secs_f32();
+        if span > 0.0 {
+            (self.starts.len() - 1) as f32 / span
+        } else {
+            0.0
+        }
+    }
+
+    /// Completes the frame being drawn, if any, and starts timing the next one.
+    pub(crate) fn start_frame(&mut self) {
+        if let Some(times) = self.current.take() {
+            if self.frames.len() == ROLLING_FRAMES {
+                self.frames.pop_front();
+            }
+            self.frames.push_back(times);
+        }
+        if self.starts.len() == ROLLING_FRAMES + 1 {
+            self.starts.pop_front();
+        }
+        self.starts.push_back(Instant::now());
+        self.current = Some(StageTimes::default());
+    }
+
+    /// Adds `time` to the time the frame being drawn spent in `stage`. Does nothing between
+    /// frames.
+    pub(crate) fn add(&mut self, stage: Stage, time: Duration) {
+        if let Some(current) = &mut self.current {
+            current.add(stage, time);
+        }
+    }
+
+    /// Adds the times of every stage of `times` to the frame being drawn.
+    pub(crate) fn add_all(&mut self, times: &StageTimes) {
+        if let Some(current) = &mut self.current {
+            current.add_all(times);
+        }
+    }
+
+    /// Draws the frame rate and the average time of every stage in milliseconds on a dark
+    /// panel with its top left corner at `position`, in canvas pixels, ignoring the canvas
+    /// transform. The canvas style is restored afterwards.
+    pub fn draw(&self, canvas: &mut Canvas, position: Point) {
+        let mut lines = vec![format!("fps     {:6.1}", self.fps())];
+        for stage in Stage::ALL {
+            let indent = if matches!(stage, Stage::Lines | Stage::Fills | Stage::Strokes) { " " } else { "" };
+            let millis = self.average(stage).as_secs_f64() * 1000.0;
+            lines.push(format!("{:8}{millis:6.2} ms", format!("{indent}{}", stage.name())));
+        }
+        let columns = lines.iter().map(String::len).max().unwrap_or(0);
+        let (character_width, line_height) = OVERLAY_CHARACTER;
+
+        canvas.push();
+        canvas.set_transform(Transform::identity());
+        canvas.set_style(Style { fill: Some(Color::new(0, 0, 0, 192)), stroke: None, stroke_weight: 1.0 });
+        canvas.rectangle(
+            position,
+            columns as f32 * character_width + 2.0 * OVERLAY_PADDING,
+            lines.len() as f32 * line_height + 2.0 * OVERLAY_PADDING,
+        );
+        canvas.set_style(Style { fill: Some(Color::new(255, 255, 255, 255)), stroke: None, stroke_weight: 1.0 });
+        canvas.debug_text(&lines.join("\n"), position + Point::new(OVERLAY_PADDING, OVERLAY_PADDING + 1.0));
+        canvas.pop();
+    }
+}
@@ -0,0 +1,524 @@
+//! Antialiased fill coverage of shapes.
+//!
+//! Shapes that describe their edges with `Shape::fill_outline` are rasterized with exact area
+//! coverage: every edge adds the signed area it sweeps to the cells of the rows it crosses, and a
+//! running sum along each row gives the winding of every pixel, weighted by how much of the
+//! pixel is inside. That costs one pass over the edges plus one addition per pixel, however
+//! expensive `contains` is, and shades shallow edges as smoothly as steep ones.
+//!
+//! Other shapes are sampled with `contains` on a 4 by 4 grid, but only close to their boundary
+//! as told by `distance`. Pixels well inside or outside take a single test.
+//!
+//! Outlines can also be rasterized in 24.8 fixed point, snapped to 1/256 of a pixel and added
+//! up in integers, so their coverage doesn't depend on how the floats on the way round.
+//!
+//! Strokes are rasterized the same way, from the outline of the stroke around the boundary told
+//! by `Shape::stroke_boundary`, with round caps and joins. That keeps their width the same for
+//! every shape, and costs little more than the pixels they cover. Shapes without a stroke
+//! boundary are stroked where `distance` is within half the stroke weight.
+//!
+//! Every pixel of a shape is blended once. Its fill covers the pixel beside the stroke, up to
+//! the stroke's inner edge, and the segments of a curve are stroked as one outline, whose parts
+//! cover overlapping pixels together. Blending each part on its own lets the background show
+//! through where antialiased edges meet, and darkens translucent joints.
+
+use crate::color::Color;
+use crate::geom::Point;
+use crate::shape::{FillOutline, FillRule, Shape};
+
+/// How far flattened curves may stray from the true outline, in pixels.
+const FILL_TOLERANCE: f32 = 0.1;
+
+/// Pixels whose center is at least this far from the boundary are sampled only at their center.
+const EDGE_DISTANCE: f32 = 1.0;
+
+/// Samples per side of the grid in each boundary pixel of sampled shapes.
+const SAMPLE_GRID: usize = 4;
+
+/// Subdivisions of a pixel in fixed point, 8 bits.
+const FIXED_ONE: i32 = 256;
+
+/// How the exact rasterizer and lines represent coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Precision {
+    /// As floats.
+    #[default]
+    Float,
+    /// In 24.8 fixed point: coordinates are snapped to 1/256 of a pixel, and coverage is added
+    /// up in integers, which gives the same result wherever a shape is drawn and whatever the
+    /// rounding of the floats on the way.
+    Fixed,
+}
+
+/// The fill coverage of a shape over an area of pixels, from 0.0 to 1.0.
+pub(crate) enum FillCoverage<'a> {
+    /// Coverage rasterized from the shape's outline, for the pixels from `(x, y)` on.
+    Exact { x: i32, y: i32, width: usize, height: usize, values: Vec<f32> },
+    /// Coverage sampled from the shape when asked for.
+    Sampled(&'a dyn Shape),
+}
+
+impl<'a> FillCoverage<'a> {
+    /// Prepares the coverage of `shape` for the pixels from `(x1, y1)` to `(x2, y2)`, inclusive.
+    /// Shapes with an outline are rasterized right away, with `precision`; others are sampled
+    /// pixel by pixel.
+    pub(crate) fn new(shape: &'a dyn Shape, (x1, y1): (i32, i32), (x2, y2): (i32, i32), precision: Precision) -> Self {
+        match shape.fill_outline(FILL_TOLERANCE) {
+            Some(outline) => FillCoverage::rasterized(&outline, (x1, y1), (x2, y2), precision),
+            None => FillCoverage::Sampled(shape),
+        }
+    }
+
+    /// Rasterizes the coverage of an outline for the pixels from `(x1, y1)` to `(x2, y2)`,
+    /// inclusive.
+    pub(crate) fn rasterized(outline: &FillOutline, (x1, y1): (i32, i32), (x2, y2): (i32, i32), precision: Precision) -> Self {
+        let width = (x2 as i64 - x1 as i64 + 1).max(0) as usize;
+        let height = (y2 as i64 - y1 as i64 + 1).max(0) as usize;
+        let values = rasterize(outline, Point::new(x1 as f32, y1 as f32), width, height, precision);
+        FillCoverage::Exact { x: x1, y: y1, width, height, values }
+    }
+
+    /// The coverage of the pixel whose top-left corner is at `(x, y)`. Pixels outside the
+    /// prepared area of an outlined shape are not covered.
+    pub(crate) fn at(&self, x: i32, y: i32) -> f32 {
+        match self {
+            FillCoverage::Exact { x: x1, y: y1, width, height, values } => {
+                let (column, row) = (x as i64 - *x1 as i64, y as i64 - *y1 as i64);
+                if column < 0 || row < 0 || column >= *width as i64 || row >= *height as i64 {
+                    return 0.0;
+                }
+                values[row as usize * width + column as usize]
+            }
+            FillCoverage::Sampled(shape) => sampled_coverage(*shape, Point::new(x as f32, y as f32)),
+        }
+    }
+}
+
+/// The stroke coverage of a shape over an area of pixels, and what it leaves of the fill.
+pub(crate) enum StrokeCoverage<'a> {
+    /// Coverage rasterized from the outline of the stroke.
+    Exact(FillCoverage<'a>),
+    /// Coverage sampled from the shape's distance when asked for.
+    Sampled { shape: &'a dyn Shape, weight: f32 },
+}
+
+impl<'a> StrokeCoverage<'a> {
+    /// Prepares the coverage of a stroke `weight` wide along `shape`, for the pixels from
+    /// `(x1, y1)` to `(x2, y2)`, inclusive. Shapes with a stroke boundary get the outline of
+    /// their stroke rasterized right away, with `precision`; others are sampled pixel by pixel.
+    pub(crate) fn new(shape: &'a dyn Shape, weight: f32, top_left: (i32, i32), bottom_right: (i32, i32), precision: Precision) -> Self {
+        match shape.stroke_boundary(FILL_TOLERANCE) {
+            Some(boundary) => {
+                let outline = FillOutline::stroke(&boundary, weight);
+                StrokeCoverage::Exact(FillCoverage::rasterized(&outline, top_left, bottom_right, precision))
+            }
+            None => StrokeCoverage::Sampled { shape, weight },
+        }
+    }
+
+    /// The coverage of the pixel whose top-left corner is at `(x, y)` by a fill covering
+    /// `fill` of it, up to the inner edge of the stroke, and by the stroke.
+    pub(crate) fn beside_fill(&self, x: i32, y: i32, fill: f32) -> (f32, f32) {
+        match self {
+            StrokeCoverage::Exact(coverage) => {
+                // Within a pixel, the stroke either covers all of the fill or lies within it
+                let stroke = coverage.at(x, y);
+                ((fill - stroke).max(0.0), stroke)
+            }
+            StrokeCoverage::Sampled { shape, weight } => {
+                let distance = shape.distance(Point::new(x as f32 + 0.5, y as f32 + 0.5));
+                (fill.min(inside_stroke_coverage(distance, *weight)), stroke_coverage(distance, *weight))
+            }
+        }
+    }
+}
+
+// The coverage of a pixel whose center is `distance` away from the middle of a stroke `weight`
+// wide: how much of the pixel's width lies within the stroke, measured across it.
+fn stroke_coverage(distance: f32, weight: f32) -> f32 {
+    let (half_weight, distance) = (weight.max(0.0) / 2.0, distance.abs());
+    ((distance + 0.5).min(half_weight) - (distance - 0.5).max(-half_weight)).clamp(0.0, 1.0)
+}
+
+/// `color` premultiplied by its alpha and by `coverage`, with channels from 0 to 255.
+pub(crate) fn premultiplied(color: Color, coverage: f32) -> [f32; 4] {
+    let alpha = color.a() as f32 * coverage;
+    [color.r() as f32 * alpha / 255.0, color.g() as f32 * alpha / 255.0, color.b() as f32 * alpha / 255.0, alpha]
+}
+
+// The coverage of a pixel whose center is `distance` away from a shape's boundary, signed like
+// `Shape::distance`, by the part of the shape within the inner edge of a stroke `weight` wide,
+// which is where the shape's fill shows. The fill and the stroke then cover each pixel side by
+// side rather than one over the other, and together cover it fully wherever the stroked shape
+// does.
+fn inside_stroke_coverage(distance: f32, weight: f32) -> f32 {
+    (0.5 - weight.max(0.0) / 2.0 - distance).clamp(0.0, 1.0)
+}
+
+/// Adds up the premultiplied paints of parts of a shape covering a pixel side by side.
+pub(crate) fn side_by_side(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]]
+}
+
+/// Blends a premultiplied color, with channels from 0 to 255, over a pixel, keeping the
+/// transparency of transparent pixels like `PixelBuffer::blend`.
+pub(crate) fn blend_premultiplied(pixel: &mut u32, [r, g, b, a]: [f32; 4]) {
+    if a <= 0.0 {
+        return;
+    }
+    let background = Color(*pixel);
+    let background_alpha = background.a() as f32 / 255.0 * (1.0 - a / 255.0);
+    let alpha = (a / 255.0 + background_alpha).min(1.0);
+    let channel = |color: f32, background: u8| ((color + background as f32 * background_alpha) / alpha).round().clamp(0.0, 255.0) as u8;
+    let out_alpha = (alpha * 255.0).round() as u8;
+    *pixel = Color::new(channel(r, background.r()), channel(g, background.g()), channel(b, background.b()), out_alpha).0;
+}
+
+/// Calls `plot` with the pixels of an antialiased line one pixel wide from `start` to `end`,
+/// and how much the line covers each, with Wu's algorithm.
+pub(crate) fn wu_line(mut start: Point, mut end: Point, mut plot: impl FnMut(i32, i32, f32)) {
+    let steep = (end.y - start.y).abs() > (end.x - start.x).abs();
+    if steep {
+        std::mem::swap(&mut start.x, &mut start.y);
+        std::mem::swap(&mut end.x, &mut end.y);
+    }
+    if start.x > end.x {
+        std::mem::swap(&mut start, &mut end);
+    }
+    let mut plot = |x: i32, y: i32, coverage: f32| if steep { plot(y, x, coverage) } else { plot(x, y, coverage) };
+
+    let dx = end.x - start.x;
+    let dy = end.y - start.y;
+    let gradient = if dx == 0.0 { 1.0 } else { dy / dx };
+
+    // The endpoints are covered by how far the line reaches into their columns
+    let xend = start.x.round();
+    let yend = start.y + gradient * (xend - start.x);
+    let xgap = 1.0 - (start.x + 0.5).fract();
+    let (xpxl1, ypxl1) = (xend as i32, yend.floor() as i32);
+    plot(xpxl1, ypxl1, (1.0 - yend.fract()) * xgap);
+    plot(xpxl1, ypxl1 + 1, yend.fract() * xgap);
+    let mut intery = yend + gradient;
+
+    let xend = end.x.round();
+    let yend = end.y + gradient * (xend - end.x);
+    let xgap = (end.x + 0.5).fract();
+    let (xpxl2, ypxl2) = (xend as i32, yend.floor() as i32);
+    plot(xpxl2, ypxl2, (1.0 - yend.fract()) * xgap);
+    plot(xpxl2, ypxl2 + 1, yend.fract() * xgap);
+
+    for x in (xpxl1 + 1)..xpxl2 {
+        plot(x, intery.floor() as i32, 1.0 - intery.fract());
+        plot(x, intery.floor() as i32 + 1, intery.fract());
+        intery += gradient;
+    }
+}
+
+/// Like `wu_line`, with the endpoints snapped to 1/256 of a pixel and the line stepped in
+/// fixed point, so the coverage of its ends only changes when they move by a step.
+pub(crate) fn wu_line_fixed(start: Point, end: Point, mut plot: impl FnMut(i32, i32, f32)) {
+    const ONE: i64 = FIXED_ONE as i64;
+    let fixed = |value: f32| (value * ONE as f32).round() as i64;
+    let (mut start, mut end) = ((fixed(start.x), fixed(start.y)), (fixed(end.x), fixed(end.y)));
+    let steep = (end.1 - start.1).abs() > (end.0 - start.0).abs();
+    if steep {
+        start = (start.1, start.0);
+        end = (end.1, end.0);
+    }
+    if start.0 > end.0 {
+        std::mem::swap(&mut start, &mut end);
+    }
+    // Coverage comes in 1/256 squared
+    let mut plot = |x: i64, y: i64, coverage: i64| {
+        let coverage = coverage as f32 / (ONE * ONE) as f32;
+        if steep { plot(y as i32, x as i32, coverage) } else { plot(x as i32, y as i32, coverage) }
+    };
+
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    // In 16.16, like `intery` below
+    let gradient = if dx == 0 { ONE * ONE } else { dy * ONE * ONE / dx };
+
+    // The endpoints are covered by how far the line reaches into their columns
+    let xend = (start.0 + ONE / 2).div_euclid(ONE) * ONE;
+    let yend = start.1 + gradient * (xend - start.0) / (ONE * ONE);
+    let xgap = ONE - (start.0 + ONE / 2).rem_euclid(ONE);
+    let (xpxl1, ypxl1, fraction) = (xend / ONE, yend.div_euclid(ONE), yend.rem_euclid(ONE));
+    plot(xpxl1, ypxl1, (ONE - fraction) * xgap);
+    plot(xpxl1, ypxl1 + 1, fraction * xgap);
+    let mut intery = yend * ONE + gradient;
+
+    let xend = (end.0 + ONE / 2).div_euclid(ONE) * ONE;
+    let yend = end.1 + gradient * (xend - end.0) / (ONE * ONE);
+    let xgap = (end.0 + ONE / 2).rem_euclid(ONE);
+    let (xpxl2, ypxl2, fraction) = (xend / ONE, yend.div_euclid(ONE), yend.rem_euclid(ONE));
+    plot(xpxl2, ypxl2, (ONE - fraction) * xgap);
+    plot(xpxl2, ypxl2 + 1, fraction * xgap);
+
+    for x in (xpxl1 + 1)..xpxl2 {
+        let (y, fraction) = (intery.div_euclid(ONE * ONE), intery.rem_euclid(ONE * ONE) / ONE);
+        plot(x, y, (ONE - fraction) * ONE);
+        plot(x, y + 1, fraction * ONE);
+        intery += gradient;
+    }
+}
+
+// Samples the coverage of the pixel with its top-left corner at `pixel`.
+fn sampled_coverage(shape: &dyn Shape, pixel: Point) -> f32 {
+    let center = pixel + Point::new(0.5, 0.5);
+    if shape.distance(center).abs() >= EDGE_DISTANCE {
+        return if shape.contains(center) { 1.0 } else { 0.0 };
+    }
+    let step = 1.0 / SAMPLE_GRID as f32;
+    let inside = (0..SAMPLE_GRID * SAMPLE_GRID)
+        .filter(|i| {
+            let offset = Point::new((i % SAMPLE_GRID) as f32 + 0.5, (i / SAMPLE_GRID) as f32 + 0.5) * step;
+            shape.contains(pixel + offset)
+        })
+        .count();
+    inside as f32 / (SAMPLE_GRID * SAMPLE_GRID) as f32
+}
+
+// The exact coverage of the `width` by `height` pixels from `origin` on, row by row.
+//
+// The edges are sorted by their top into an edge table. Going down the rows, edges move from
+// the table into the active edges when they reach the row, and leave when they end above it, so
+// each row only looks at the edges crossing it, however many vertices the outline has.
+fn rasterize(outline: &FillOutline, origin: Point, width: usize, height: usize, precision: Precision) -> Vec<f32> {
+    if precision == Precision::Fixed {
+        return rasterize_fixed(outline, origin, width, height);
+    }
+    let right = width as f32;
+    let mut edges = edge_list(outline, origin, right, height as f32, |point| point);
+    edges.sort_by(|a: &Edge, b: &Edge| a.top.y.total_cmp(&b.top.y));
+
+    let mut values = Vec::with_capacity(width * height);
+    let mut cells = vec![0.0; width + 2];
+    let mut active: Vec<Edge> = Vec::new();
+    let mut table = edges.into_iter().peekable();
+    for row in 0..height {
+        let (row_top, row_bottom) = (row as f32, (row + 1) as f32);
+        active.retain(|edge| edge.bottom.y > row_top);
+        while let Some(edge) = table.next_if(|edge| edge.top.y < row_bottom) {
+            if edge.bottom.y > row_top {
+                active.push(edge);
+            }
+        }
+        for edge in &active {
+            edge.accumulate(&mut cells, row_top, right);
+        }
+
+        let mut winding = 0.0;
+        for &cell in &cells[..width] {
+            winding += cell;
+            values.push(fill_value(winding, outline.rule));
+        }
+        cells.fill(0.0);
+    }
+    values
+}
+
+// Like `rasterize`, with the outline snapped to 1/256 of a pixel and its coverage added up in
+// 24.8 fixed point. Every cell keeps the height the edges cross it by, and twice the area they
+// leave to its left, so that each pixel's winding is exact in integers until it is read.
+fn rasterize_fixed(outline: &FillOutline, origin: Point, width: usize, height: usize) -> Vec<f32> {
+    let snap = |point: Point| Point::new((point.x * FIXED_ONE as f32).round(), (point.y * FIXED_ONE as f32).round()) * (1.0 / FIXED_ONE as f32);
+    let mut edges: Vec<FixedEdge> = edge_list(outline, origin, width as f32, height as f32, snap).iter().filter_map(FixedEdge::new).collect();
+    edges.sort_by_key(|edge| edge.top.1);
+
+    let mut values = Vec::with_capacity(width * height);
+    let (mut cover, mut area) = (vec![0; width + 2], vec![0; width + 2]);
+    let mut active: Vec<FixedEdge> = Vec::new();
+    let mut table = edges.into_iter().peekable();
+    for row in 0..height as i32 {
+        let (row_top, row_bottom) = (row * FIXED_ONE, (row + 1) * FIXED_ONE);
+        active.retain(|edge| edge.bottom.1 > row_top);
+        while let Some(edge) = table.next_if(|edge| edge.top.1 < row_bottom) {
+            if edge.bottom.1 > row_top {
+                active.push(edge);
+            }
+        }
+        for edge in &active {
+            edge.accumulate(&mut cover, &mut area, row_top);
+        }
+
+        let mut winding = 0;
+        for (&cell_cover, &cell_area) in cover[..width].iter().zip(&area[..width]) {
+            let value = (winding + cell_cover) * 2 * FIXED_ONE as i64 - cell_area;
+            winding += cell_cover;
+            values.push(fill_value(value as f32 / (2 * FIXED_ONE * FIXED_ONE) as f32, outline.rule));
+        }
+        cover.fill(0);
+        area.fill(0);
+    }
+    values
+}
+
+// The edges of an outline in the grid from `origin` on, `right` pixels wide and `bottom`
+// pixels high, with every point passed through `snap` first.
+fn edge_list(outline: &FillOutline, origin: Point, right: f32, bottom: f32, snap: impl Fn(Point) -> Point) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    for contour in &outline.contours {
+        // Every contour is closed, whether or not its last point repeats the first
+        for (i, &start) in contour.iter().enumerate() {
+            let end = contour[(i + 1) % contour.len()];
+            add_edge(&mut edges, snap(start - origin), snap(end - origin), right, bottom);
+        }
+    }
+    edges
+}
+
+// The coverage of a pixel the outline winds around `winding` times, weighted by how much of
+// the pixel is inside, under `rule`.
+fn fill_value(winding: f32, rule: FillRule) -> f32 {
+    match rule {
+        FillRule::NonZero => f32::abs(winding).min(1.0),
+        FillRule::EvenOdd => {
+            let folded = f32::abs(winding) % 2.0;
+            if folded > 1.0 { 2.0 - folded } else { folded }
+        }
+    }
+}
+
+// Adds an edge in grid coordinates to `edges`. The parts left or right of the grid are moved
+// onto its sides, where they still change the winding of the pixels to their right. The parts
+// above or below it are moved onto its top or bottom, where they're flat and dropped, so that
+// the rows of the edges stay small enough for fixed point.
+fn add_edge(edges: &mut Vec<Edge>, start: Point, end: Point, right: f32, bottom: f32) {
+    if !(start.x.is_finite() && start.y.is_finite() && end.x.is_finite() && end.y.is_finite()) {
+        return;
+    }
+    let mut cuts = [0.0, 1.0, 1.0, 1.0, 1.0, 1.0];
+    let mut count = 1;
+    let sides = [(start.x, end.x, 0.0), (start.x, end.x, right), (start.y, end.y, 0.0), (start.y, end.y, bottom)];
+    for (from, to, side) in sides {
+        let t = (side - from) / (to - from);
+        if t > 0.0 && t < 1.0 {
+            cuts[count] = t;
+            count += 1;
+        }
+    }
+    cuts[..count].sort_by(f32::total_cmp);
+    cuts[count] = 1.0;
+
+    let clamp = |point: Point| Point::new(point.x.clamp(0.0, right), point.y.clamp(0.0, bottom));
+    for pair in cuts[..=count].windows(2) {
+        let (a, b) = (clamp(start.lerp(&end, pair[0])), clamp(start.lerp(&end, pair[1])));
+        if a.y != b.y {
+            // Edges going up take away what edges going down add
+            let (direction, top, bottom) = if a.y < b.y { (1.0, a, b) } else { (-1.0, b, a) };
+            edges.push(Edge { top, bottom, direction, slope: (bottom.x - top.x) / (bottom.y - top.y) });
+        }
+    }
+}
+
+// A straight piece of an outline within the columns of the grid, from its top to its bottom.
+struct Edge {
+    top: Point,
+    bottom: Point,
+    /// 1 for edges going down, -1 for edges going up.
+    direction: f32,
+    /// The change of x per row.
+    slope: f32,
+}
+
+impl Edge {
+    // Adds the signed areas the edge sweeps within the row starting at `row_top` to the cells of
+    // the row, which has two extra cells on the right for edges ending at the right side.
+    fn accumulate(&self, cells: &mut [f32], row_top: f32, right: f32) {
+        let (y1, y2) = (self.top.y.max(row_top), self.bottom.y.min(row_top + 1.0));
+        if y2 <= y1 {
+            return;
+        }
+        let x_at = |y: f32| (self.top.x + self.slope * (y - self.top.y)).clamp(0.0, right);
+        let (x, next_x) = (x_at(y1), x_at(y2));
+        let area = (y2 - y1) * self.direction;
+        let (left, right_x) = if x < next_x { (x, next_x) } else { (next_x, x) };
+        let (left_floor, right_ceil) = (left.floor(), right_x.ceil());
+        let (first, last) = (left_floor as usize, right_ceil as usize);
+
+        if last <= first + 1 {
+            // The edge stays within one column: split its area by where it crosses it
+            let middle = 0.5 * (x + next_x) - left_floor;
+            cells[first] += area * (1.0 - middle);
+            cells[first + 1] += area * middle;
+        } else {
+            // The edge crosses columns: the first and last get triangles, the ones between
+            // equal steps
+            let step = 1.0 / (right_x - left);
+            let left_fraction = left - left_floor;
+            let first_area = 0.5 * step * (1.0 - left_fraction) * (1.0 - left_fraction);
+            let right_fraction = right_x - right_ceil + 1.0;
+            let last_area = 0.5 * step * right_fraction * right_fraction;
+            cells[first] += area * first_area;
+            if last == first + 2 {
+                cells[first + 1] += area * (1.0 - first_area - last_area);
+            } else {
+                let second_area = step * (1.5 - left_fraction);
+                cells[first + 1] += area * (second_area - first_area);
+                for cell in &mut cells[first + 2..last - 1] {
+                    *cell += area * step;
+                }
+                let before_last = second_area + (last - first - 3) as f32 * step;
+                cells[last - 1] += area * (1.0 - before_last - last_area);
+            }
+            cells[last] += area * last_area;
+        }
+    }
+}
+
+// An `Edge` in 24.8 fixed point, as `(x, y)` in 1/256 of a pixel.
+struct FixedEdge {
+    top: (i32, i32),
+    bottom: (i32, i32),
+    /// 1 for edges going down, -1 for edges going up.
+    direction: i32,
+}
+
+impl FixedEdge {
+    // Rounds an edge to fixed point, or None if it ends up flat.
+    fn new(edge: &Edge) -> Option<Self> {
+        let fixed = |point: Point| ((point.x * FIXED_ONE as f32).round() as i32, (point.y * FIXED_ONE as f32).round() as i32);
+        let (top, bottom) = (fixed(edge.top), fixed(edge.bottom));
+        (top.1 < bottom.1).then_some(FixedEdge { top, bottom, direction: edge.direction as i32 })
+    }
+
+    // Adds the heights the edge crosses the cells of the row starting at `row_top` by to
+    // `cover`, and twice the areas it leaves to their left to `area`, cell by cell. The sums are
+    // kept in 64 bits, since thousands of overlapping edges, as in the outlines of wide
+    // strokes, add up past 32.
+    fn accumulate(&self, cover: &mut [i64], area: &mut [i64], row_top: i32) {
+        let (y1, y2) = (self.top.1.max(row_top), self.bottom.1.min(row_top + FIXED_ONE));
+        if y2 <= y1 {
+            return;
+        }
+        let x_at = |y: i32| {
+            let (dx, dy) = (self.bottom.0 as i64 - self.top.0 as i64, self.bottom.1 as i64 - self.top.1 as i64);
+            (self.top.0 as i64 + (y as i64 - self.top.1 as i64) * dx / dy) as i32
+        };
+        // Which end is on the left doesn't change the height or the area
+        let (x, next_x) = (x_at(y1), x_at(y2));
+        let (left, right) = (x.min(next_x), x.max(next_x));
+        let height = y2 - y1;
+        let mut add = |from: i32, to: i32, height: i32| {
+            let cell = from / FIXED_ONE;
+            let start = cell * FIXED_ONE;
+            cover[cell as usize] += (self.direction * height) as i64;
+            area[cell as usize] += (self.direction * height * (from - start + to - start)) as i64;
+        };
+
+        let (first, last) = (left / FIXED_ONE, (right - 1).max(left) / FIXED_ONE);
+        if first == last {
+            add(left, right, height);
+            return;
+        }
+        // Split the height between the cells at the columns the edge crosses
+        let (mut from, mut crossed) = (left, 0);
+        for cell in first..last {
+            let to = (cell + 1) * FIXED_ONE;
+            let reached = (height as i64 * (to - left) as i64 / (right - left) as i64) as i32;
+            add(from, to, reached - crossed);
+            (from, crossed) = (to, reached);
+        }
+        add(from, right, height - crossed);
+    }
+}
@@ -0,0 +1,197 @@
+//! Recording frames as numbered image sequences, for assembling videos in other tools, or
+//! straight to video files through ffmpeg.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+use crate::canvas::Canvas;
+use crate::error::SketchError;
+use crate::png::PngColor;
+
+/// Writes frames as a numbered sequence of opaque PNG files, `frame_000001.png`,
+/// `frame_000002.png` and so on, while recording is on.
+///
+/// Numbers count the recorded frames, not the frames of the sketch, so the sequence has no
+/// gaps when recording is stopped and started again. `ApplicationRunner` has one built in,
+/// see `ApplicationRunner::set_recording_directory`.
+#[derive(Debug, Clone)]
+pub struct FrameRecorder {
+    directory: PathBuf,
+    recording: bool,
+    frames_written: u64,
+}
+
+impl FrameRecorder {
+    /// Creates a recorder that writes to `directory`, which is created when the first frame is
+    /// written. Recording starts switched off.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        FrameRecorder { directory: directory.into(), recording: false, frames_written: 0 }
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Changes the directory later frames are written to. Numbering continues.
+    pub fn set_directory(&mut self, directory: impl Into<PathBuf>) {
+        self.directory = directory.into();
+    }
+
+    pub fn start(&mut self) {
+        self.recording = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.recording = false;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// The number of frames written so far, which is also the number of the last file.
+    pub fn frames_written(&self) -> u64 {
+        self.frames_written
+    }
+
+    /// Writes the canvas as the next frame of the sequence if recording is on, the way it
+    /// looks in the window. Returns the path of the file, or None when not recording.
+    pub fn record(&mut self, canvas: &Canvas) -> Result<Option<PathBuf>, SketchError> {
+        if !self.recording {
+            return Ok(None);
+        }
+        let path = self.directory.join(format!("frame_{:06}.png", self.frames_written + 1));
+        let write = || -> io::Result<()> {
+            fs::create_dir_all(&self.directory)?;
+            canvas.pixel_buffer.write_png(BufWriter::new(File::create(&path)?), PngColor::Rgb)
+        };
+        write().map_err(|e| io::Error::new(e.kind(), format!("could not record frame to {}: {e}", path.display())))?;
+        self.frames_written += 1;
+        Ok(Some(path))
+    }
+}
+
+impl Default for FrameRecorder {
+    /// Writes to `frames` in the working directory.
+    fn default() -> Self {
+        FrameRecorder::new("frames")
+    }
+}
+
+/// Records frames to an MP4 or WebM video by piping them to an `ffmpeg` process, which has to
+/// be installed.
+///
+/// The codec follows the file extension: VP9 for `.webm`, and H.264 for anything else. All
+/// frames must have the size the recording was started with. The video is complete once
+/// `finish` returns, or once the recorder is dropped.
+pub struct VideoRecorder {
+    path: PathBuf,
+    width: usize,
+    height: usize,
+    process: Child,
+    input: Option<ChildStdin>,
+    frames_written: u64,
+    /// The bytes of a frame, reused between frames.
+    frame: Vec<u8>,
+}
+
+impl VideoRecorder {
+    /// Starts ffmpeg to encode frames of `width` by `height` pixels, shown at `frame_rate`
+    /// frames per second, into the video at `path`. An existing file is overwritten.
+    pub fn start(path: impl Into<PathBuf>, width: usize, height: usize, frame_rate: f32) -> Result<Self, SketchError> {
+        let path = path.into();
+        if width == 0 || height == 0 {
+            return Err(SketchError::InvalidSize { width, height });
+        }
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let webm = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("webm"));
+        let codec: &[&str] = if webm {
+            &["-c:v", "libvpx-vp9", "-b:v", "0", "-crf", "30"]
+        } else {
+            &["-c:v", "libx264", "-crf", "18"]
+        };
+        let size = format!("{width}x{height}");
+        let frame_rate = frame_rate.to_string();
+        let process = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pixel_format", "bgra"])
+            .args(["-video_size", &size, "-framerate", &frame_rate, "-i", "-"])
+            .args(codec)
+            // Most players need 4:2:0 chroma, which needs an even width and height
+            .args(["-pix_fmt", "yuv420p", "-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+            .arg(&path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut process = match process {
+            Ok(process) => process,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Err(SketchError::Video("ffmpeg not found, install it to record videos".to_string()));
+            }
+            Err(error) => return Err(error.into()),
+        };
+        let input = process.stdin.take();
+        Ok(VideoRecorder { path, width, height, process, input, frames_written: 0, frame: Vec::new() })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn frames_written(&self) -> u64 {
+        self.frames_written
+    }
+
+    /// Adds the canvas as the next frame of the video, the way it looks in the window.
+    pub fn write_frame(&mut self, canvas: &Canvas) -> Result<(), SketchError> {
+        let buffer = &canvas.pixel_buffer;
+        if (buffer.width, buffer.height) != (self.width, self.height) {
+            return Err(SketchError::Video(format!(
+                "frame size {}x{} differs from the video size {}x{}",
+                buffer.width, buffer.height, self.width, self.height
+            )));
+        }
+        // Packed 0RGB pixels in little endian order are the bytes of ffmpeg's bgra format
+        self.frame.clear();
+        self.frame.extend(buffer.get_buffer().iter().flat_map(|&pixel| (pixel | 0xFF00_0000).to_le_bytes()));
+        let Some(input) = &mut self.input else {
+            return Err(SketchError::Video("the recording was already finished".to_string()));
+        };
+        if let Err(error) = input.write_all(&self.frame) {
+            // ffmpeg exited, its error output says why
+            return Err(self.wait().err().unwrap_or(error.into()));
+        }
+        self.frames_written += 1;
+        Ok(())
+    }
+
+    /// Ends the video and waits for ffmpeg to finish writing it.
+    pub fn finish(mut self) -> Result<(), SketchError> {
+        self.wait()
+    }
+
+    // Closes ffmpeg's input and waits for it to exit
+    fn wait(&mut self) -> Result<(), SketchError> {
+        self.input = None;
+        let mut errors = String::new();
+        if let Some(mut stderr) = self.process.stderr.take() {
+            let _ = stderr.read_to_string(&mut errors);
+        }
+        let status = self.process.wait()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(SketchError::Video(format!("ffmpeg failed ({status}): {}", errors.trim())))
+        }
+    }
+}
+
+impl Drop for VideoRecorder {
+    fn drop(&mut self) {
+        let _ = self.wait();
+    }
+}
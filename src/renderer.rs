@@ -0,0 +1,66 @@
+//! Renderers: what turns a canvas's drawing commands into pixels.
+//!
+//! By default a `Canvas` rasterizes every command itself, on the CPU. With
+//! `Canvas::set_renderer`, the shapes, lines, curves and paths go to a `Renderer` instead,
+//! which an application can implement to draw them some other way, for example on the GPU. The
+//! crate itself only provides `CpuRenderer`, the canvas's own rasterizer. Everything the
+//! renderer doesn't draw, like images, text drawn with `debug_text`, instanced shapes and fills
+//! with a pattern, is still drawn by the canvas, so the canvas hands the picture back and forth
+//! between them: the renderer `begin`s with the pixels drawn so far and `finish`es by writing
+//! its result back, before the canvas draws again and at the end of every frame.
+
+use crate::canvas::Canvas;
+use crate::command::{DrawCommand, Style};
+use crate::pixelbuffer::PixelBuffer;
+
+/// Draws the commands of a canvas somewhere else than the canvas's own rasterizer.
+///
+/// Commands are in buffer pixels: the canvas transform and pixel density are applied already,
+/// and the style's stroke weight is in buffer pixels too.
+pub trait Renderer: Send {
+    /// Starts drawing over `pixels`, the picture as the canvas has drawn it so far.
+    fn begin(&mut self, pixels: &PixelBuffer);
+
+    /// Draws one command with the style it was given.
+    fn draw(&mut self, command: &DrawCommand, style: &Style);
+
+    /// Writes the picture with everything drawn since `begin` into `pixels`, which has the size
+    /// of the buffer passed to `begin`.
+    fn finish(&mut self, pixels: &mut PixelBuffer);
+}
+
+/// The canvas's own rasterizer as a `Renderer`, drawing on a canvas of its own. A reference for
+/// writing other renderers, whose output can be compared with it.
+pub struct CpuRenderer {
+    canvas: Canvas,
+}
+
+impl CpuRenderer {
+    pub fn new() -> Self {
+        CpuRenderer { canvas: Canvas::new(0, 0) }
+    }
+}
+
+impl Default for CpuRenderer {
+    fn default() -> Self {
+        CpuRenderer::new()
+    }
+}
+
+impl Renderer for CpuRenderer {
+    fn begin(&mut self, pixels: &PixelBuffer) {
+        if (pixels.width, pixels.height) != (self.canvas.width, self.canvas.height) {
+            self.canvas = Canvas::new(pixels.width, pixels.height);
+        }
+        self.canvas.pixel_buffer.get_buffer_mut().copy_from_slice(pixels.get_buffer());
+    }
+
+    fn draw(&mut self, command: &DrawCommand, style: &Style) {
+        self.canvas.set_style(*style);
+        self.canvas.execute(command);
+    }
+
+    fn finish(&mut self, pixels: &mut PixelBuffer) {
+        pixels.get_buffer_mut().copy_from_slice(self.canvas.pixel_buffer.get_buffer());
+    }
+}
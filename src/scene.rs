@@ -0,0 +1,265 @@
+//! Scenes: lists of styled drawing commands that can be saved to disk, loaded again and drawn
+//! with `Canvas::draw_scene`, for sketches that keep what was drawn between runs and for
+//! replaying the drawing logged by `Canvas::start_command_log`.
+//!
+//! Scenes are saved as text, one command per line with its style, for example
+//!
+//! ```text
+//! simple-sketch scene 1
+//! background #ffffffff
+//! circle 50 50 80 fill=#ff0000ff stroke=#000000ff weight=2
+//! path "M 10 10 L 90 10 L 50 80 Z" fill=none stroke=#000000ff weight=1
+//! ```
+//!
+//! Numbers are written with enough digits to load back exactly.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path as FilePath;
+
+use crate::color::Color;
+use crate::command::{DrawCommand, RecordedCommand, Style};
+use crate::error::SketchError;
+use crate::geom::{Path, Point};
+use crate::shape::{Arc, ArcMode, Capsule, RegularPolygon, RoundedRect, Star};
+
+/// The first line of scene files.
+const HEADER: &str = "simple-sketch scene 1";
+
+/// A list of drawing commands, each with the style to draw it in, in canvas coordinates.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::command::DrawCommand;
+/// # use simple_sketch::error::SketchError;
+/// # use simple_sketch::geom::Point;
+/// # use simple_sketch::scene::Scene;
+/// # fn draw(canvas: &mut Canvas, mouse: Point) -> Result<(), SketchError> {
+/// let mut scene = Scene::load("drawing.scene").unwrap_or_default();
+/// scene.push(DrawCommand::Circle { center: mouse, diameter: 20.0 }, canvas.style());
+/// scene.save("drawing.scene")?;
+/// canvas.draw_scene(&scene);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scene {
+    pub commands: Vec<RecordedCommand>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Scene::default()
+    }
+
+    /// Adds a command to the end of the scene, drawn on top of the others.
+    pub fn push(&mut self, command: DrawCommand, style: Style) {
+        self.commands.push(RecordedCommand { command, style });
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Saves the scene as a text file, creating missing directories.
+    pub fn save(&self, path: impl AsRef<FilePath>) -> Result<(), SketchError> {
+        let path = path.as_ref();
+        let save = || {
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, self.to_text())
+        };
+        save().map_err(|e| std::io::Error::new(e.kind(), format!("could not save {}: {e}", path.display())).into())
+    }
+
+    /// Loads a scene saved with `save`.
+    pub fn load(path: impl AsRef<FilePath>) -> Result<Self, SketchError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| std::io::Error::new(e.kind(), format!("could not load {}: {e}", path.display())))?;
+        Scene::parse(&text).map_err(|error| match error {
+            SketchError::Scene(reason) => SketchError::Scene(format!("{}: {reason}", path.display())),
+            error => error,
+        })
+    }
+
+    /// The scene in the text format of scene files.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{HEADER}\n");
+        for RecordedCommand { command, style } in &self.commands {
+            write_command(&mut text, command);
+            if !matches!(command, DrawCommand::Background(_)) {
+                let _ = write!(text, " fill={} stroke={} weight={}", paint(style.fill), paint(style.stroke), style.stroke_weight);
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Reads a scene from the text format of scene files. Empty lines and lines starting with
+    /// `#` are skipped.
+    pub fn parse(text: &str) -> Result<Self, SketchError> {
+        let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'));
+        match lines.next() {
+            Some((_, line)) if line.trim() == HEADER => {}
+            _ => return Err(SketchError::Scene("not a scene file".to_string())),
+        }
+        let mut scene = Scene::new();
+        for (index, line) in lines {
+            let command = parse_line(line.trim()).map_err(|reason| SketchError::Scene(format!("line {}: {reason}", index + 1)))?;
+            scene.commands.push(command);
+        }
+        Ok(scene)
+    }
+}
+
+fn write_command(text: &mut String, command: &DrawCommand) {
+    let numbers = |text: &mut String, name: &str, numbers: &[f32]| {
+        text.push_str(name);
+        for number in numbers {
+            let _ = write!(text, " {number}");
+        }
+    };
+    match command {
+        DrawCommand::Background(color) => {
+            let _ = write!(text, "background {}", hex(*color));
+        }
+        DrawCommand::Line { start, end } => numbers(text, "line", &[start.x, start.y, end.x, end.y]),
+        DrawCommand::Bezier { start, control1, control2, end } => {
+            numbers(text, "bezier", &[start.x, start.y, control1.x, control1.y, control2.x, control2.y, end.x, end.y])
+        }
+        DrawCommand::Ellipse { center, width, height } => numbers(text, "ellipse", &[center.x, center.y, *width, *height]),
+        DrawCommand::Circle { center, diameter } => numbers(text, "circle", &[center.x, center.y, *diameter]),
+        DrawCommand::Triangle { a, b, c } => numbers(text, "triangle", &[a.x, a.y, b.x, b.y, c.x, c.y]),
+        DrawCommand::Capsule(capsule) => numbers(text, "capsule", &[capsule.start.x, capsule.start.y, capsule.end.x, capsule.end.y, capsule.radius]),
+        DrawCommand::Arc(arc) => {
+            numbers(text, "arc", &[arc.center.x, arc.center.y, arc.radius, arc.start, arc.end]);
+            text.push_str(match arc.mode {
+                ArcMode::Open => " open",
+                ArcMode::Chord => " chord",
+                ArcMode::Pie => " pie",
+            });
+        }
+        DrawCommand::RoundedRect(rect) => {
+            let [a, b, c, d] = rect.radii;
+            numbers(text, "rounded_rect", &[rect.top_left.x, rect.top_left.y, rect.width, rect.height, a, b, c, d])
+        }
+        DrawCommand::Star(star) => {
+            let _ = write!(text, "star {} {} {} {} {} {}", star.center.x, star.center.y, star.points, star.inner_radius, star.outer_radius, star.rotation);
+        }
+        DrawCommand::RegularPolygon(polygon) => {
+            let _ = write!(text, "regular_polygon {} {} {} {} {}", polygon.center.x, polygon.center.y, polygon.sides, polygon.radius, polygon.rotation);
+        }
+        DrawCommand::Rectangle { top_left, width, height } => numbers(text, "rectangle", &[top_left.x, top_left.y, *width, *height]),
+        DrawCommand::Path(path) => {
+            let _ = write!(text, "path \"{}\"", path.to_svg_path_string());
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Result<RecordedCommand, String> {
+    let (name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    // Path data is quoted, as it contains spaces
+    let (path, rest) = if name == "path" {
+        let data = rest.trim_start().strip_prefix('"').ok_or("expected quoted path data")?;
+        let (data, rest) = data.split_once('"').ok_or("unterminated path data")?;
+        (Some(Path::parse_svg(data).map_err(|error| format!("invalid path data: {error}"))?), rest)
+    } else {
+        (None, rest)
+    };
+
+    let mut arguments = Vec::new();
+    let mut style = Style { fill: None, stroke: None, stroke_weight: 1.0 };
+    for token in rest.split_whitespace() {
+        match token.split_once('=') {
+            Some(("fill", value)) => style.fill = parse_paint(value)?,
+            Some(("stroke", value)) => style.stroke = parse_paint(value)?,
+            Some(("weight", value)) => style.stroke_weight = value.parse().map_err(|_| format!("invalid stroke weight '{value}'"))?,
+            Some((key, _)) => return Err(format!("unknown style '{key}'")),
+            None => arguments.push(token),
+        }
+    }
+
+    let number = |index: usize| -> Result<f32, String> {
+        let argument = arguments.get(index).ok_or_else(|| format!("{name} needs more arguments"))?;
+        argument.parse().map_err(|_| format!("invalid number '{argument}'"))
+    };
+    let count = |index: usize| -> Result<usize, String> {
+        let argument = arguments.get(index).ok_or_else(|| format!("{name} needs more arguments"))?;
+        argument.parse().map_err(|_| format!("invalid count '{argument}'"))
+    };
+    let point = |index: usize| -> Result<Point, String> { Ok(Point::new(number(index)?, number(index + 1)?)) };
+    let (command, expected) = match name {
+        "background" => {
+            let color = arguments.first().and_then(|color| parse_hex(color)).ok_or("background needs a color")?;
+            (DrawCommand::Background(color), 1)
+        }
+        "line" => (DrawCommand::Line { start: point(0)?, end: point(2)? }, 4),
+        "bezier" => (DrawCommand::Bezier { start: point(0)?, control1: point(2)?, control2: point(4)?, end: point(6)? }, 8),
+        "ellipse" => (DrawCommand::Ellipse { center: point(0)?, width: number(2)?, height: number(3)? }, 4),
+        "circle" => (DrawCommand::Circle { center: point(0)?, diameter: number(2)? }, 3),
+        "triangle" => (DrawCommand::Triangle { a: point(0)?, b: point(2)?, c: point(4)? }, 6),
+        "capsule" => (DrawCommand::Capsule(Capsule { start: point(0)?, end: point(2)?, radius: number(4)? }), 5),
+        "arc" => {
+            let mode = match arguments.get(5) {
+                Some(&"open") => ArcMode::Open,
+                Some(&"chord") => ArcMode::Chord,
+                Some(&"pie") => ArcMode::Pie,
+                _ => return Err("arc needs a mode of open, chord or pie".to_string()),
+            };
+            (DrawCommand::Arc(Arc { center: point(0)?, radius: number(2)?, start: number(3)?, end: number(4)?, mode }), 6)
+        }
+        "rounded_rect" => {
+            let radii = [number(4)?, number(5)?, number(6)?, number(7)?];
+            (DrawCommand::RoundedRect(RoundedRect { top_left: point(0)?, width: number(2)?, height: number(3)?, radii }), 8)
+        }
+        "star" => {
+            let star = Star { center: point(0)?, points: count(2)?, inner_radius: number(3)?, outer_radius: number(4)?, rotation: number(5)? };
+            (DrawCommand::Star(star), 6)
+        }
+        "regular_polygon" => {
+            let polygon = RegularPolygon { center: point(0)?, sides: count(2)?, radius: number(3)?, rotation: number(4)? };
+            (DrawCommand::RegularPolygon(polygon), 5)
+        }
+        "rectangle" => (DrawCommand::Rectangle { top_left: point(0)?, width: number(2)?, height: number(3)? }, 4),
+        "path" => (DrawCommand::Path(path.unwrap_or_default()), 0),
+        _ => return Err(format!("unknown command '{name}'")),
+    };
+    if arguments.len() > expected {
+        return Err(format!("too many arguments for {name}"));
+    }
+    Ok(RecordedCommand { command, style })
+}
+
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}{:02x}", color.r(), color.g(), color.b(), color.a())
+}
+
+fn paint(color: Option<Color>) -> String {
+    color.map_or("none".to_string(), hex)
+}
+
+fn parse_paint(text: &str) -> Result<Option<Color>, String> {
+    if text == "none" {
+        return Ok(None);
+    }
+    parse_hex(text).map(Some).ok_or_else(|| format!("invalid color '{text}'"))
+}
+
+// Parses a color written as `#rrggbbaa`.
+fn parse_hex(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#').filter(|hex| hex.len() == 8)?;
+    let [r, g, b, a] = u32::from_str_radix(hex, 16).ok()?.to_be_bytes();
+    Some(Color::new(r, g, b, a))
+}
@@ -0,0 +1,1265 @@
+//! Scripting a sketch's update and draw logic in a small language, loaded from a file and
+//! reloaded whenever it is saved, for live coding without recompiling.
+//!
+//! A script runs its top-level code once when it is loaded, and defines the functions the
+//! sketch calls: `setup()` once before the first frame, `update(dt)` before every frame and
+//! `draw()` to draw it. Any of them can be left out.
+//!
+//! ```text
+//! // Twelve dots circling the center
+//! let angle = 0;
+//!
+//! fn update(dt) {
+//!     angle += dt;
+//! }
+//!
+//! fn draw() {
+//!     background(rgb(16, 16, 24));
+//!     no_stroke();
+//!     for i in 0..12 {
+//!         let a = angle + i * TAU / 12;
+//!         fill(hsv(i * 30, 0.8, 1));
+//!         circle(width / 2 + cos(a) * 150, height / 2 + sin(a) * 150, 30);
+//!     }
+//!     text("frame " + frame, 8, 8);
+//! }
+//! ```
+//!
+//! Values are numbers, booleans, strings, colors and `nil`, the value of functions that don't
+//! return anything. Variables are declared with `let` and live until the end of their block,
+//! or for the life of the script at the top level. Statements are `if`/`else`, `while`, `for`
+//! over a half-open range of numbers, `break`, `continue` and `return`. Operators are those of
+//! Rust without the bitwise ones, and `+` also joins strings with any value.
+//!
+//! The built-in functions are:
+//!
+//! - drawing, only in `draw`: `background(color)`, `fill(color)`, `no_fill()`,
+//!   `stroke(color)`, `no_stroke()`, `stroke_weight(w)`, `line(x1, y1, x2, y2)`,
+//!   `bezier(x1, y1, cx1, cy1, cx2, cy2, x2, y2)`, `circle(x, y, d)`, `ellipse(x, y, w, h)`,
+//!   `rect(x, y, w, h)`, `triangle(x1, y1, x2, y2, x3, y3)`, `text(s, x, y)` with the debug
+//!   font, `push()`, `pop()`, `translate(x, y)`, `rotate(angle)` and `scale(sx, sy)`
+//! - colors: `rgb(r, g, b)`, `rgba(r, g, b, a)`, `hsv(h, s, v)` with `h` in degrees and `s` and
+//!   `v` from 0 to 1, and `with_alpha(color, a)`
+//! - math: `sin`, `cos`, `tan`, `atan2`, `sqrt`, `pow`, `abs`, `floor`, `ceil`, `round`,
+//!   `min`, `max`, `clamp`, `lerp`, `map`, `random(low, high)`, `random_seed(seed)` and
+//!   `print(value)`
+//!
+//! and the variables `width`, `height`, `frame`, `time`, `mouse_x`, `mouse_y`,
+//! `mouse_pressed`, `PI` and `TAU` are set by the sketch.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::app::{Application, Context, Settings};
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::error::SketchError;
+use crate::geom::Point;
+
+/// How long a script goes without checking its file for changes.
+const CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Statements and loop iterations a call into a script may run before it is stopped, so that
+/// an endless loop doesn't freeze the sketch.
+const MAX_STEPS: u64 = 10_000_000;
+
+/// How deep script functions may call each other.
+const MAX_CALL_DEPTH: usize = 256;
+
+/// How deep blocks, parentheses and operators may nest in a script before it is rejected, so
+/// that parsing it can't overflow the stack.
+const MAX_NESTING_DEPTH: usize = 128;
+
+/// How deep blocks and expressions may nest while a script runs, counting those of all the
+/// functions calling each other, so that recursing through deeply nested code can't overflow
+/// the stack either.
+const MAX_RUN_DEPTH: usize = 1024;
+
+/// A value in a script.
+#[derive(Debug, Clone, Default)]
+pub enum ScriptValue {
+    #[default]
+    Nil,
+    Number(f64),
+    Bool(bool),
+    String(String),
+    Color(Color),
+}
+
+impl ScriptValue {
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ScriptValue::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ScriptValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ScriptValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_color(&self) -> Option<Color> {
+        match self {
+            ScriptValue::Color(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            ScriptValue::Nil => "nil",
+            ScriptValue::Number(_) => "number",
+            ScriptValue::Bool(_) => "boolean",
+            ScriptValue::String(_) => "string",
+            ScriptValue::Color(_) => "color",
+        }
+    }
+}
+
+impl fmt::Display for ScriptValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptValue::Nil => write!(f, "nil"),
+            ScriptValue::Number(value) => write!(f, "{value}"),
+            ScriptValue::Bool(value) => write!(f, "{value}"),
+            ScriptValue::String(value) => write!(f, "{value}"),
+            ScriptValue::Color(color) => write!(f, "{color:?}"),
+        }
+    }
+}
+
+impl From<f64> for ScriptValue {
+    fn from(value: f64) -> Self {
+        ScriptValue::Number(value)
+    }
+}
+
+impl From<f32> for ScriptValue {
+    fn from(value: f32) -> Self {
+        ScriptValue::Number(value as f64)
+    }
+}
+
+impl From<bool> for ScriptValue {
+    fn from(value: bool) -> Self {
+        ScriptValue::Bool(value)
+    }
+}
+
+impl From<&str> for ScriptValue {
+    fn from(value: &str) -> Self {
+        ScriptValue::String(value.to_string())
+    }
+}
+
+impl From<String> for ScriptValue {
+    fn from(value: String) -> Self {
+        ScriptValue::String(value)
+    }
+}
+
+impl From<Color> for ScriptValue {
+    fn from(value: Color) -> Self {
+        ScriptValue::Color(value)
+    }
+}
+
+/// A loaded script, with its global variables, reloaded when its file changes.
+///
+/// With hot reloading on, which is the default for scripts loaded from a file, `update` and
+/// `draw` first reload the file if it changed, at most every quarter second. The new version
+/// starts over: its top-level code runs again with fresh global variables, and then the
+/// values set with `set` are set again. A version that doesn't parse or whose top-level code
+/// fails leaves the previous one running, and the problem is kept in `error` until the file
+/// loads again.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simple_sketch::app::Context;
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::error::SketchError;
+/// # use simple_sketch::script::Script;
+/// # fn sketch(canvas: &mut Canvas, ctx: &Context, dt: f32) -> Result<(), SketchError> {
+/// let mut script = Script::load("sketch.script")?;
+/// script.set("speed", 2.0);
+///
+/// // In the sketch
+/// script.update(ctx, dt)?;
+/// script.draw(canvas, ctx)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Script {
+    path: Option<PathBuf>,
+    hot_reload: bool,
+    /// The modification time of the file the script was loaded from, if the system has one.
+    modified: Option<SystemTime>,
+    checked: Instant,
+    program: Program,
+    globals: HashMap<String, ScriptValue>,
+    /// The values set from outside the script, set again after every reload.
+    host_values: HashMap<String, ScriptValue>,
+    random: u64,
+    error: Option<SketchError>,
+}
+
+impl Script {
+    /// Loads the script in the file at `path` and runs its top-level code.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SketchError> {
+        let path = path.as_ref();
+        let modified = modified(path);
+        let mut script = Script::parse(&read(path)?).map_err(|error| with_path(error, path))?;
+        script.path = Some(path.to_path_buf());
+        script.hot_reload = true;
+        script.modified = modified;
+        Ok(script)
+    }
+
+    /// Reads a script from its text and runs its top-level code.
+    pub fn parse(source: &str) -> Result<Self, SketchError> {
+        let mut script = Script {
+            path: None,
+            hot_reload: false,
+            modified: None,
+            checked: Instant::now(),
+            program: Program::default(),
+            globals: HashMap::new(),
+            host_values: HashMap::new(),
+            random: 0x2545_f491_4f6c_dd1d,
+            error: None,
+        };
+        script.start(parse_program(source)?)?;
+        Ok(script)
+    }
+
+    /// Turns reloading the file when it changes on or off.
+    pub fn set_hot_reload(&mut self, enabled: bool) {
+        self.hot_reload = enabled;
+    }
+
+    pub fn hot_reload(&self) -> bool {
+        self.hot_reload
+    }
+
+    /// Reloads the file if it changed since it was loaded, whether hot reloading is on or not.
+    /// Returns whether the new version is running.
+    pub fn reload_if_changed(&mut self) -> bool {
+        self.checked = Instant::now();
+        let Some(path) = self.path.clone() else {
+            return false;
+        };
+        let modified = modified(&path);
+        if modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        let reloaded = read(&path).and_then(|source| parse_program(&source)).and_then(|program| {
+            let previous = (mem::take(&mut self.program), mem::take(&mut self.globals));
+            self.start(program).inspect_err(|_| (self.program, self.globals) = previous)
+        });
+        self.error = reloaded.err().map(|error| with_path(error, &path));
+        self.error.is_none()
+    }
+
+    /// Why the file couldn't be reloaded the last time it changed, or None if it was.
+    pub fn error(&self) -> Option<&SketchError> {
+        self.error.as_ref()
+    }
+
+    /// Sets a global variable of the script, for example to a parameter of the sketch. It
+    /// keeps its value across reloads unless the script assigns it.
+    pub fn set(&mut self, name: &str, value: impl Into<ScriptValue>) {
+        let value = value.into();
+        self.host_values.insert(name.to_string(), value.clone());
+        self.globals.insert(name.to_string(), value);
+    }
+
+    /// The value of a global variable of the script.
+    pub fn get(&self, name: &str) -> Option<&ScriptValue> {
+        self.globals.get(name)
+    }
+
+    /// Whether the script defines the function `name`.
+    pub fn has_function(&self, name: &str) -> bool {
+        self.program.functions.contains_key(name)
+    }
+
+    /// Calls the script function `name` with `args`, and returns what it returns. Drawing
+    /// functions fail outside `draw`.
+    pub fn call(&mut self, name: &str, args: &[ScriptValue]) -> Result<ScriptValue, SketchError> {
+        self.run(None, |machine| machine.call_function(name, args.to_vec()))
+    }
+
+    /// Calls the script's `update(dt)`, if it has one, after reloading it if its file changed.
+    pub fn update(&mut self, ctx: &Context, dt: f32) -> Result<(), SketchError> {
+        self.check_file();
+        self.set_context(ctx);
+        if self.has_function("update") {
+            self.call("update", &[dt.into()])?;
+        }
+        Ok(())
+    }
+
+    /// Calls the script's `draw()`, if it has one, with its drawing functions drawing on
+    /// `canvas`, after reloading it if its file changed.
+    pub fn draw(&mut self, canvas: &mut Canvas, ctx: &Context) -> Result<(), SketchError> {
+        self.check_file();
+        self.set_context(ctx);
+        self.set_size(canvas);
+        if self.has_function("draw") {
+            self.run(Some(canvas), |machine| machine.call_function("draw", Vec::new()))?;
+        }
+        Ok(())
+    }
+
+    // Runs a new version of the script, from fresh globals with the host's values
+    fn start(&mut self, program: Program) -> Result<(), SketchError> {
+        self.program = program;
+        self.globals = HashMap::from([
+            ("PI".to_string(), ScriptValue::Number(std::f64::consts::PI)),
+            ("TAU".to_string(), ScriptValue::Number(std::f64::consts::TAU)),
+        ]);
+        self.globals.extend(self.host_values.clone());
+        let main = Rc::clone(&self.program.main);
+        self.run(None, |machine| machine.statements(&main).map(|_| ScriptValue::Nil))?;
+        Ok(())
+    }
+
+    fn check_file(&mut self) {
+        if self.hot_reload && self.checked.elapsed() >= CHECK_INTERVAL {
+            self.reload_if_changed();
+        }
+    }
+
+    fn set_context(&mut self, ctx: &Context) {
+        let mouse = ctx.mouse();
+        self.globals.insert("frame".to_string(), (ctx.frame_count() as f64).into());
+        self.globals.insert("time".to_string(), ctx.seconds_since_start().into());
+        self.globals.insert("mouse_x".to_string(), mouse.position().x.into());
+        self.globals.insert("mouse_y".to_string(), mouse.position().y.into());
+        self.globals.insert("mouse_pressed".to_string(), mouse.is_any_pressed().into());
+    }
+
+    fn set_size(&mut self, canvas: &Canvas) {
+        self.globals.insert("width".to_string(), (canvas.width as f64).into());
+        self.globals.insert("height".to_string(), (canvas.height as f64).into());
+    }
+
+    fn run(&mut self, canvas: Option<&mut Canvas>, run: impl FnOnce(&mut Machine) -> Result<ScriptValue, Error>) -> Result<ScriptValue, SketchError> {
+        let mut machine = Machine {
+            functions: &self.program.functions,
+            globals: &mut self.globals,
+            scopes: Vec::new(),
+            canvas,
+            steps: 0,
+            depth: 0,
+            nesting: 0,
+            random: &mut self.random,
+        };
+        run(&mut machine).map_err(|error| SketchError::Script(error.to_string()))
+    }
+}
+
+/// A sketch whose setup, update and draw are a script, reloaded when its file is saved. A
+/// script that fails shows its error over the last frame it drew until it is fixed.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simple_sketch::app::{ApplicationRunner, Settings};
+/// # use simple_sketch::error::SketchError;
+/// # use simple_sketch::script::ScriptSketch;
+/// let sketch = ScriptSketch::load("sketch.script", Settings::new().size(800, 600).title("Live"))?;
+/// ApplicationRunner::new(sketch)?.run()?;
+/// # Ok::<(), SketchError>(())
+/// ```
+pub struct ScriptSketch {
+    script: Script,
+    settings: Settings,
+    /// The error of the last call into the script or the last reload, shown over the frame.
+    error: Option<String>,
+}
+
+impl ScriptSketch {
+    /// Loads the script at `path`, to run in a window configured by `settings`.
+    pub fn load(path: impl AsRef<Path>, settings: Settings) -> Result<Self, SketchError> {
+        Ok(ScriptSketch { script: Script::load(path)?, settings, error: None })
+    }
+
+    /// The running script, for example to set its variables.
+    pub fn script(&mut self) -> &mut Script {
+        &mut self.script
+    }
+
+    // Keeps the error of a call into the script, or of the last reload
+    fn report(&mut self, result: Result<(), SketchError>) {
+        let error = result.err().or_else(|| self.script.error().map(|error| SketchError::Script(error.to_string())));
+        self.error = error.map(|error| error.to_string());
+    }
+}
+
+impl Application for ScriptSketch {
+    fn settings(&self) -> Settings {
+        self.settings.clone()
+    }
+
+    fn setup(&mut self, canvas: &mut Canvas) {
+        self.script.set_size(canvas);
+        let result = if self.script.has_function("setup") { self.script.call("setup", &[]).map(|_| ()) } else { Ok(()) };
+        self.report(result);
+    }
+
+    fn update(&mut self, ctx: &Context, dt: f32) {
+        let result = self.script.update(ctx, dt);
+        self.report(result);
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas, ctx: &Context) {
+        if self.error.is_none() {
+            let result = self.script.draw(canvas, ctx);
+            self.report(result);
+        }
+        if let Some(error) = &self.error {
+            canvas.push();
+            canvas.set_fill(Some(Color::new(255, 96, 96, 255)));
+            canvas.debug_text(error, Point::new(8.0, 8.0));
+            canvas.pop();
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn read(path: &Path) -> Result<String, SketchError> {
+    fs::read_to_string(path).map_err(|e| std::io::Error::new(e.kind(), format!("could not load {}: {e}", path.display())).into())
+}
+
+fn with_path(error: SketchError, path: &Path) -> SketchError {
+    match error {
+        SketchError::Script(reason) => SketchError::Script(format!("{}: {reason}", path.display())),
+        error => error,
+    }
+}
+
+// An error while running a script, with the line of the statement it happened in once known
+#[derive(Debug)]
+struct Error {
+    line: Option<usize>,
+    message: String,
+}
+
+impl Error {
+    fn new(message: impl Into<String>) -> Self {
+        Error { line: None, message: message.into() }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+// Tokens
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    String(String),
+    Identifier(String),
+    Let,
+    Fn,
+    If,
+    Else,
+    While,
+    For,
+    In,
+    Return,
+    Break,
+    Continue,
+    True,
+    False,
+    Nil,
+    /// Punctuation and operators, such as `(` or `<=`.
+    Symbol(&'static str),
+}
+
+/// The operators and punctuation, longest first so that `<=` isn't read as `<`.
+const SYMBOLS: [&str; 28] = [
+    "..", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=", "*=", "/=", "(", ")", "{", "}", ",", ";", "=", "<", ">", "+", "-", "*",
+    "/", "%", "!", "[", "]",
+];
+
+// Splits a script into tokens with their line numbers.
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, SketchError> {
+    let error = |line: usize, reason: String| SketchError::Script(format!("line {line}: {reason}"));
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        if c == '\n' {
+            line += 1;
+        }
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if rest.starts_with("//") {
+            rest = rest.find('\n').map_or("", |end| &rest[end..]);
+        } else if c.is_ascii_digit() {
+            let mut end = rest.find(|c: char| !c.is_ascii_digit() && c != '_').unwrap_or(rest.len());
+            // A fraction, but not the `..` of a range
+            if rest[end..].starts_with('.') && rest[end + 1..].starts_with(|c: char| c.is_ascii_digit()) {
+                end += 1 + rest[end + 1..].find(|c: char| !c.is_ascii_digit() && c != '_').unwrap_or(rest.len() - end - 1);
+            }
+            let number = rest[..end].replace('_', "");
+            let value = number.parse().map_err(|_| error(line, format!("invalid number `{number}`")))?;
+            tokens.push((Token::Number(value), line));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(rest.len());
+            let token = match &rest[..end] {
+                "let" => Token::Let,
+                "fn" => Token::Fn,
+                "if" => Token::If,
+                "else" => Token::Else,
+                "while" => Token::While,
+                "for" => Token::For,
+                "in" => Token::In,
+                "return" => Token::Return,
+                "break" => Token::Break,
+                "continue" => Token::Continue,
+                "true" => Token::True,
+                "false" => Token::False,
+                "nil" => Token::Nil,
+                name => Token::Identifier(name.to_string()),
+            };
+            tokens.push((token, line));
+            rest = &rest[end..];
+        } else if c == '"' {
+            let mut text = String::new();
+            let mut chars = rest[1..].char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((index, '"')) => break index + 2,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => text.push('\n'),
+                        Some((_, 't')) => text.push('\t'),
+                        Some((_, escaped @ ('"' | '\\'))) => text.push(escaped),
+                        _ => return Err(error(line, "invalid escape in string".to_string())),
+                    },
+                    Some((_, '\n')) | None => return Err(error(line, "unclosed string".to_string())),
+                    Some((_, c)) => text.push(c),
+                }
+            };
+            tokens.push((Token::String(text), line));
+            rest = &rest[end..];
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            tokens.push((Token::Symbol(symbol), line));
+            rest = &rest[symbol.len()..];
+        } else {
+            return Err(error(line, format!("unexpected character `{c}`")));
+        }
+    }
+    Ok(tokens)
+}
+
+// Syntax
+
+#[derive(Debug)]
+enum Expr {
+    Value(ScriptValue),
+    Variable(String),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug)]
+struct Statement {
+    line: usize,
+    kind: StatementKind,
+}
+
+#[derive(Debug)]
+enum StatementKind {
+    Let(String, Expr),
+    /// An assignment, with the operator of a compound assignment such as `+=`.
+    Assign(String, Option<&'static str>, Expr),
+    Expr(Expr),
+    If(Expr, Vec<Statement>, Vec<Statement>),
+    While(Expr, Vec<Statement>),
+    For(String, Expr, Expr, Vec<Statement>),
+    Return(Option<Expr>),
+    Break,
+    Continue,
+}
+
+#[derive(Debug)]
+struct Function {
+    parameters: Vec<String>,
+    body: Vec<Statement>,
+}
+
+#[derive(Debug, Default)]
+struct Program {
+    functions: HashMap<String, Rc<Function>>,
+    /// The top-level statements.
+    main: Rc<Vec<Statement>>,
+}
+
+fn parse_program(source: &str) -> Result<Program, SketchError> {
+    let mut parser = Parser { tokens: tokenize(source)?, position: 0, depth: 0 };
+    let mut program = Program::default();
+    let mut main = Vec::new();
+    while parser.position < parser.tokens.len() {
+        if parser.accept(&Token::Fn) {
+            let line = parser.line();
+            let name = parser.identifier()?;
+            let function = parser.function()?;
+            if program.functions.insert(name.clone(), Rc::new(function)).is_some() {
+                return Err(SketchError::Script(format!("line {line}: function `{name}` is defined twice")));
+            }
+        } else {
+            main.push(parser.statement()?);
+        }
+    }
+    program.main = Rc::new(main);
+    Ok(program)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    /// How many statements and expressions the one being parsed is nested in.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    // The line of the next token, or of the last one at the end
+    fn line(&self) -> usize {
+        self.tokens.get(self.position).or(self.tokens.last()).map_or(1, |(_, line)| *line)
+    }
+
+    fn error(&self, reason: impl fmt::Display) -> SketchError {
+        SketchError::Script(format!("line {}: {reason}", self.line()))
+    }
+
+    fn accept(&mut self, token: &Token) -> bool {
+        let found = self.peek() == Some(token);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn accept_symbol(&mut self, symbols: &[&'static str]) -> Option<&'static str> {
+        let Some(&Token::Symbol(symbol)) = self.peek() else {
+            return None;
+        };
+        let found = symbols.contains(&symbol);
+        found.then(|| {
+            self.position += 1;
+            symbol
+        })
+    }
+
+    // Parses something nested one level deeper, failing past `MAX_NESTING_DEPTH`
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, SketchError>) -> Result<T, SketchError> {
+        if self.depth == MAX_NESTING_DEPTH {
+            return Err(self.error("too deeply nested"));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn expect(&mut self, symbol: &'static str) -> Result<(), SketchError> {
+        match self.accept_symbol(&[symbol]) {
+            Some(_) => Ok(()),
+            None => Err(self.error(format!("expected `{symbol}`"))),
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, SketchError> {
+        match self.peek() {
+            Some(Token::Identifier(name)) => {
+                let name = name.clone();
+                self.position += 1;
+                Ok(name)
+            }
+            _ => Err(self.error("expected a name")),
+        }
+    }
+
+    fn function(&mut self) -> Result<Function, SketchError> {
+        self.expect("(")?;
+        let mut parameters = Vec::new();
+        while self.accept_symbol(&[")"]).is_none() {
+            if !parameters.is_empty() {
+                self.expect(",")?;
+            }
+            parameters.push(self.identifier()?);
+        }
+        Ok(Function { parameters, body: self.block()? })
+    }
+
+    fn block(&mut self) -> Result<Vec<Statement>, SketchError> {
+        self.expect("{")?;
+        let mut statements = Vec::new();
+        while self.accept_symbol(&["}"]).is_none() {
+            if self.peek().is_none() {
+                return Err(self.error("expected `}`"));
+            }
+            statements.push(self.statement()?);
+        }
+        Ok(statements)
+    }
+
+    fn statement(&mut self) -> Result<Statement, SketchError> {
+        let line = self.line();
+        let kind = self.nested(Self::statement_kind)?;
+        Ok(Statement { line, kind })
+    }
+
+    fn statement_kind(&mut self) -> Result<StatementKind, SketchError> {
+        let kind = match self.peek() {
+            Some(Token::Let) => {
+                self.position += 1;
+                let name = self.identifier()?;
+                self.expect("=")?;
+                let value = self.expression()?;
+                self.expect(";")?;
+                StatementKind::Let(name, value)
+            }
+            Some(Token::If) => {
+                self.position += 1;
+                self.if_statement()?
+            }
+            Some(Token::While) => {
+                self.position += 1;
+                let condition = self.expression()?;
+                StatementKind::While(condition, self.block()?)
+            }
+            Some(Token::For) => {
+                self.position += 1;
+                let name = self.identifier()?;
+                if !self.accept(&Token::In) {
+                    return Err(self.error("expected `in`"));
+                }
+                let start = self.expression()?;
+                self.expect("..")?;
+                let end = self.expression()?;
+                StatementKind::For(name, start, end, self.block()?)
+            }
+            Some(Token::Return) => {
+                self.position += 1;
+                let value = if self.accept_symbol(&[";"]).is_some() {
+                    None
+                } else {
+                    let value = self.expression()?;
+                    self.expect(";")?;
+                    Some(value)
+                };
+                StatementKind::Return(value)
+            }
+            Some(token @ (Token::Break | Token::Continue)) => {
+                let kind = if *token == Token::Break { StatementKind::Break } else { StatementKind::Continue };
+                self.position += 1;
+                self.expect(";")?;
+                kind
+            }
+            Some(Token::Fn) => return Err(self.error("functions can only be defined at the top level")),
+            _ => {
+                let expression = self.expression()?;
+                let kind = match self.accept_symbol(&["=", "+=", "-=", "*=", "/="]) {
+                    Some(operator) => {
+                        let Expr::Variable(name) = expression else {
+                            return Err(self.error("can only assign to a variable"));
+                        };
+                        let operator = match operator {
+                            "+=" => Some("+"),
+                            "-=" => Some("-"),
+                            "*=" => Some("*"),
+                            "/=" => Some("/"),
+                            _ => None,
+                        };
+                        StatementKind::Assign(name, operator, self.expression()?)
+                    }
+                    None => StatementKind::Expr(expression),
+                };
+                self.expect(";")?;
+                kind
+            }
+        };
+        Ok(kind)
+    }
+
+    // The rest of an `if` statement, after `if`
+    fn if_statement(&mut self) -> Result<StatementKind, SketchError> {
+        let condition = self.expression()?;
+        let then = self.block()?;
+        let otherwise = if !self.accept(&Token::Else) {
+            Vec::new()
+        } else if self.accept(&Token::If) {
+            let line = self.line();
+            vec![Statement { line, kind: self.nested(Self::if_statement)? }]
+        } else {
+            self.block()?
+        };
+        Ok(StatementKind::If(condition, then, otherwise))
+    }
+
+    fn expression(&mut self) -> Result<Expr, SketchError> {
+        self.nested(|parser| parser.binary(0))
+    }
+
+    // Parses operators from the loosest binding level `level` up
+    fn binary(&mut self, level: usize) -> Result<Expr, SketchError> {
+        const LEVELS: [&[&str]; 6] = [&["||"], &["&&"], &["==", "!="], &["<", "<=", ">", ">="], &["+", "-"], &["*", "/", "%"]];
+        let Some(operators) = LEVELS.get(level) else {
+            return self.unary();
+        };
+        let mut left = self.binary(level + 1)?;
+        while let Some(operator) = self.accept_symbol(operators) {
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(operator, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, SketchError> {
+        if let Some(operator) = self.accept_symbol(&["-", "!"]) {
+            return Ok(Expr::Unary(operator, Box::new(self.nested(Self::unary)?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, SketchError> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error("expected a value"));
+        };
+        self.position += 1;
+        Ok(match token {
+            Token::Number(value) => Expr::Value(ScriptValue::Number(value)),
+            Token::String(text) => Expr::Value(ScriptValue::String(text)),
+            Token::True => Expr::Value(ScriptValue::Bool(true)),
+            Token::False => Expr::Value(ScriptValue::Bool(false)),
+            Token::Nil => Expr::Value(ScriptValue::Nil),
+            Token::Identifier(name) if self.accept_symbol(&["("]).is_some() => {
+                let mut args = Vec::new();
+                while self.accept_symbol(&[")"]).is_none() {
+                    if !args.is_empty() {
+                        self.expect(",")?;
+                    }
+                    args.push(self.expression()?);
+                }
+                Expr::Call(name, args)
+            }
+            Token::Identifier(name) => Expr::Variable(name),
+            Token::Symbol("(") => {
+                let inner = self.expression()?;
+                self.expect(")")?;
+                inner
+            }
+            _ => {
+                self.position -= 1;
+                return Err(self.error("expected a value"));
+            }
+        })
+    }
+}
+
+// Running
+
+// What a statement makes the statements after it do
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return(ScriptValue),
+}
+
+struct Machine<'a> {
+    functions: &'a HashMap<String, Rc<Function>>,
+    globals: &'a mut HashMap<String, ScriptValue>,
+    /// The variables of the blocks of the running function, innermost last.
+    scopes: Vec<HashMap<String, ScriptValue>>,
+    /// The canvas drawing functions draw on, None outside `draw`.
+    canvas: Option<&'a mut Canvas>,
+    steps: u64,
+    depth: usize,
+    /// How many blocks and expressions the running code is nested in, across calls.
+    nesting: usize,
+    /// The state of `random`, a xorshift generator.
+    random: &'a mut u64,
+}
+
+impl Machine<'_> {
+    fn call_function(&mut self, name: &str, args: Vec<ScriptValue>) -> Result<ScriptValue, Error> {
+        let Some(function) = self.functions.get(name).cloned() else {
+            return self.builtin(name, &args);
+        };
+        if args.len() != function.parameters.len() {
+            return Err(Error::new(format!("`{name}` takes {} arguments but was given {}", function.parameters.len(), args.len())));
+        }
+        if self.depth == MAX_CALL_DEPTH {
+            return Err(Error::new("functions call each other too deeply"));
+        }
+        let scope = function.parameters.iter().cloned().zip(args).collect();
+        let caller = mem::replace(&mut self.scopes, vec![scope]);
+        self.depth += 1;
+        let flow = self.statements(&function.body);
+        self.depth -= 1;
+        self.scopes = caller;
+        match flow? {
+            Flow::Return(value) => Ok(value),
+            Flow::Next => Ok(ScriptValue::Nil),
+            Flow::Break | Flow::Continue => Err(Error::new("`break` or `continue` outside a loop")),
+        }
+    }
+
+    // Runs something nested one level deeper, failing past `MAX_RUN_DEPTH`
+    fn nested<T>(&mut self, run: impl FnOnce(&mut Self) -> Result<T, Error>) -> Result<T, Error> {
+        if self.nesting == MAX_RUN_DEPTH {
+            return Err(Error::new("blocks and expressions nest too deeply across calls"));
+        }
+        self.nesting += 1;
+        let result = run(self);
+        self.nesting -= 1;
+        result
+    }
+
+    // Runs statements in a scope of their own
+    fn block(&mut self, statements: &[Statement]) -> Result<Flow, Error> {
+        self.scopes.push(HashMap::new());
+        let flow = self.nested(|machine| machine.statements(statements));
+        self.scopes.pop();
+        flow
+    }
+
+    fn statements(&mut self, statements: &[Statement]) -> Result<Flow, Error> {
+        for statement in statements {
+            let flow = self.statement(statement).map_err(|mut error| {
+                error.line.get_or_insert(statement.line);
+                error
+            })?;
+            if !matches!(flow, Flow::Next) {
+                return Ok(flow);
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn step(&mut self) -> Result<(), Error> {
+        self.steps += 1;
+        if self.steps > MAX_STEPS {
+            return Err(Error::new("the script ran too long, is there an endless loop?"));
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<Flow, Error> {
+        self.step()?;
+        match &statement.kind {
+            StatementKind::Let(name, value) => {
+                let value = self.eval(value)?;
+                match self.scopes.last_mut() {
+                    Some(scope) => scope.insert(name.clone(), value),
+                    None => self.globals.insert(name.clone(), value),
+                };
+            }
+            StatementKind::Assign(name, operator, value) => {
+                let mut value = self.eval(value)?;
+                if let Some(operator) = operator {
+                    let current = self.variable(name)?.clone();
+                    value = binary(operator, current, value)?;
+                }
+                *self.variable(name)? = value;
+            }
+            StatementKind::Expr(expression) => {
+                self.eval(expression)?;
+            }
+            StatementKind::If(condition, then, otherwise) => {
+                let branch = if self.condition(condition)? { then } else { otherwise };
+                return self.block(branch);
+            }
+            StatementKind::While(condition, body) => {
+                while self.condition(condition)? {
+                    self.step()?;
+                    match self.block(body)? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Next | Flow::Continue => {}
+                    }
+                }
+            }
+            StatementKind::For(name, start, end, body) => {
+                let (start, end) = (self.number(start)?, self.number(end)?);
+                let mut i = start;
+                while i < end {
+                    self.step()?;
+                    self.scopes.push(HashMap::from([(name.clone(), ScriptValue::Number(i))]));
+                    let flow = self.block(body);
+                    self.scopes.pop();
+                    match flow? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Next | Flow::Continue => {}
+                    }
+                    i += 1.0;
+                }
+            }
+            StatementKind::Return(value) => {
+                let value = value.as_ref().map_or(Ok(ScriptValue::Nil), |value| self.eval(value))?;
+                return Ok(Flow::Return(value));
+            }
+            StatementKind::Break => return Ok(Flow::Break),
+            StatementKind::Continue => return Ok(Flow::Continue),
+        }
+        Ok(Flow::Next)
+    }
+
+    fn variable(&mut self, name: &str) -> Result<&mut ScriptValue, Error> {
+        let scope = self.scopes.iter_mut().rev().find(|scope| scope.contains_key(name));
+        scope.map_or_else(|| self.globals.get_mut(name), |scope| scope.get_mut(name)).ok_or_else(|| Error::new(format!("unknown variable `{name}`")))
+    }
+
+    fn condition(&mut self, condition: &Expr) -> Result<bool, Error> {
+        match self.eval(condition)? {
+            ScriptValue::Bool(value) => Ok(value),
+            value => Err(Error::new(format!("expected a boolean condition, found a {}", value.type_name()))),
+        }
+    }
+
+    fn number(&mut self, expression: &Expr) -> Result<f64, Error> {
+        match self.eval(expression)? {
+            ScriptValue::Number(value) => Ok(value),
+            value => Err(Error::new(format!("expected a number, found a {}", value.type_name()))),
+        }
+    }
+
+    fn eval(&mut self, expression: &Expr) -> Result<ScriptValue, Error> {
+        match expression {
+            Expr::Value(value) => Ok(value.clone()),
+            Expr::Variable(name) => self.variable(name).cloned(),
+            _ => self.nested(|machine| machine.eval_nested(expression)),
+        }
+    }
+
+    // Evaluates the expressions made of others
+    fn eval_nested(&mut self, expression: &Expr) -> Result<ScriptValue, Error> {
+        match expression {
+            Expr::Value(value) => Ok(value.clone()),
+            Expr::Variable(name) => self.variable(name).cloned(),
+            Expr::Unary("-", operand) => Ok(ScriptValue::Number(-self.number(operand)?)),
+            Expr::Unary(_, operand) => Ok(ScriptValue::Bool(!self.condition(operand)?)),
+            Expr::Binary("&&", left, right) => Ok(ScriptValue::Bool(self.condition(left)? && self.condition(right)?)),
+            Expr::Binary("||", left, right) => Ok(ScriptValue::Bool(self.condition(left)? || self.condition(right)?)),
+            Expr::Binary(operator, left, right) => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                binary(operator, left, right)
+            }
+            Expr::Call(name, args) => {
+                let args = args.iter().map(|arg| self.eval(arg)).collect::<Result<Vec<_>, _>>()?;
+                self.call_function(name, args)
+            }
+        }
+    }
+
+    fn canvas(&mut self) -> Result<&mut Canvas, Error> {
+        self.canvas.as_deref_mut().ok_or_else(|| Error::new("drawing is only possible in `draw`"))
+    }
+
+    fn builtin(&mut self, name: &str, args: &[ScriptValue]) -> Result<ScriptValue, Error> {
+        let point = |x: f32, y: f32| Point::new(x, y);
+        match name {
+            "background" => self.canvas()?.background(color_arg(name, args)?),
+            "fill" => self.canvas()?.set_fill(Some(color_arg(name, args)?)),
+            "no_fill" => self.canvas()?.set_fill(numbers::<0>(name, args).map(|_| None)?),
+            "stroke" => self.canvas()?.set_stroke(Some(color_arg(name, args)?)),
+            "no_stroke" => self.canvas()?.set_stroke(numbers::<0>(name, args).map(|_| None)?),
+            "stroke_weight" => {
+                let [weight] = numbers(name, args)?;
+                self.canvas()?.set_stroke_weight(weight);
+            }
+            "line" => {
+                let [x1, y1, x2, y2] = numbers(name, args)?;
+                self.canvas()?.line(point(x1, y1), point(x2, y2));
+            }
+            "bezier" => {
+                let [x1, y1, cx1, cy1, cx2, cy2, x2, y2] = numbers(name, args)?;
+                self.canvas()?.bezier(point(x1, y1), point(cx1, cy1), point(cx2, cy2), point(x2, y2));
+            }
+            "circle" => {
+                let [x, y, diameter] = numbers(name, args)?;
+                self.canvas()?.circle(point(x, y), diameter);
+            }
+            "ellipse" => {
+                let [x, y, width, height] = numbers(name, args)?;
+                self.canvas()?.ellipse(point(x, y), width, height);
+            }
+            "rect" => {
+                let [x, y, width, height] = numbers(name, args)?;
+                self.canvas()?.rectangle(point(x, y), width, height);
+            }
+            "triangle" => {
+                let [x1, y1, x2, y2, x3, y3] = numbers(name, args)?;
+                self.canvas()?.triangle(point(x1, y1), point(x2, y2), point(x3, y3));
+            }
+            "text" => {
+                let [ScriptValue::String(text), x, y] = args else {
+                    return Err(Error::new("`text` takes a string and two numbers"));
+                };
+                let [x, y] = numbers(name, &[x.clone(), y.clone()])?;
+                self.canvas()?.debug_text(text, point(x, y));
+            }
+            "push" => self.canvas()?.push(),
+            "pop" => self.canvas()?.pop(),
+            "translate" => {
+                let [x, y] = numbers(name, args)?;
+                self.canvas()?.translate(point(x, y));
+            }
+            "rotate" => {
+                let [angle] = numbers(name, args)?;
+                self.canvas()?.rotate(angle);
+            }
+            "scale" => {
+                let [sx, sy] = numbers(name, args)?;
+                self.canvas()?.scale(sx, sy);
+            }
+            "rgb" => {
+                let [r, g, b] = numbers(name, args)?.map(channel);
+                return Ok(Color::new(r, g, b, 255).into());
+            }
+            "rgba" => {
+                let [r, g, b, a] = numbers(name, args)?.map(channel);
+                return Ok(Color::new(r, g, b, a).into());
+            }
+            "hsv" => {
+                let [h, s, v] = numbers(name, args)?;
+                return Ok(Color::hsv_to_rgb(h.rem_euclid(360.0), s.clamp(0.0, 1.0), v.clamp(0.0, 1.0)).into());
+            }
+            "with_alpha" => {
+                let [ScriptValue::Color(color), ScriptValue::Number(alpha)] = args else {
+                    return Err(Error::new("`with_alpha` takes a color and a number"));
+                };
+                return Ok(color.with_alpha(channel(*alpha as f32)).into());
+            }
+            "random" => {
+                let [low, high] = numbers(name, args)?;
+                // xorshift64
+                *self.random ^= *self.random << 13;
+                *self.random ^= *self.random >> 7;
+                *self.random ^= *self.random << 17;
+                let t = (*self.random >> 11) as f64 / (1u64 << 53) as f64;
+                return Ok((low as f64 + t * (high as f64 - low as f64)).into());
+            }
+            "random_seed" => {
+                let [seed] = numbers(name, args)?;
+                *self.random = (seed as f64).to_bits() | 1;
+            }
+            "print" => println!("{}", args.iter().map(ScriptValue::to_string).collect::<Vec<_>>().join(" ")),
+            _ => return math(name, args),
+        };
+        Ok(ScriptValue::Nil)
+    }
+}
+
+// The math functions
+fn math(name: &str, args: &[ScriptValue]) -> Result<ScriptValue, Error> {
+    let one = |function: fn(f64) -> f64| numbers::<1>(name, args).map(|[x]| function(x as f64));
+    let value = match name {
+        "sin" => one(f64::sin)?,
+        "cos" => one(f64::cos)?,
+        "tan" => one(f64::tan)?,
+        "sqrt" => one(f64::sqrt)?,
+        "abs" => one(f64::abs)?,
+        "floor" => one(f64::floor)?,
+        "ceil" => one(f64::ceil)?,
+        "round" => one(f64::round)?,
+        "atan2" => numbers::<2>(name, args).map(|[y, x]| (y as f64).atan2(x as f64))?,
+        "pow" => numbers::<2>(name, args).map(|[x, y]| (x as f64).powf(y as f64))?,
+        "min" => numbers::<2>(name, args).map(|[a, b]| a.min(b) as f64)?,
+        "max" => numbers::<2>(name, args).map(|[a, b]| a.max(b) as f64)?,
+        "clamp" => numbers::<3>(name, args).map(|[x, low, high]| crate::math::constrain(x, low, high) as f64)?,
+        "lerp" => numbers::<3>(name, args).map(|[a, b, t]| crate::math::lerp(a, b, t) as f64)?,
+        "map" => numbers::<5>(name, args).map(|[x, a, b, c, d]| crate::math::map(x, a, b, c, d) as f64)?,
+        _ => return Err(Error::new(format!("unknown function `{name}`"))),
+    };
+    Ok(ScriptValue::Number(value))
+}
+
+// The arguments of a function that takes `N` numbers
+fn numbers<const N: usize>(name: &str, args: &[ScriptValue]) -> Result<[f32; N], Error> {
+    let wrong = || Error::new(format!("`{name}` takes {N} numbers"));
+    let values: Vec<f32> = args.iter().map(|arg| arg.as_f64().map(|value| value as f32)).collect::<Option<_>>().ok_or_else(wrong)?;
+    values.try_into().map_err(|_| wrong())
+}
+
+fn color_arg(name: &str, args: &[ScriptValue]) -> Result<Color, Error> {
+    match args {
+        [ScriptValue::Color(color)] => Ok(*color),
+        _ => Err(Error::new(format!("`{name}` takes a color"))),
+    }
+}
+
+fn channel(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+fn binary(operator: &str, left: ScriptValue, right: ScriptValue) -> Result<ScriptValue, Error> {
+    use ScriptValue::{Bool, Nil, Number};
+    Ok(match (operator, left, right) {
+        ("+", ScriptValue::String(left), right) => ScriptValue::String(format!("{left}{right}")),
+        ("+", left, ScriptValue::String(right)) => ScriptValue::String(format!("{left}{right}")),
+        ("+", Number(a), Number(b)) => Number(a + b),
+        ("-", Number(a), Number(b)) => Number(a - b),
+        ("*", Number(a), Number(b)) => Number(a * b),
+        ("/", Number(a), Number(b)) => Number(a / b),
+        ("%", Number(a), Number(b)) => Number(a % b),
+        ("<", Number(a), Number(b)) => Bool(a < b),
+        ("<=", Number(a), Number(b)) => Bool(a <= b),
+        (">", Number(a), Number(b)) => Bool(a > b),
+        (">=", Number(a), Number(b)) => Bool(a >= b),
+        ("==" | "!=", left, right) => {
+            let equal = match (&left, &right) {
+                (Number(a), Number(b)) => a == b,
+                (Bool(a), Bool(b)) => a == b,
+                (ScriptValue::String(a), ScriptValue::String(b)) => a == b,
+                (ScriptValue::Color(a), ScriptValue::Color(b)) => a.0 == b.0,
+                (Nil, Nil) => true,
+                _ => false,
+            };
+            Bool(equal == (operator == "=="))
+        }
+        (operator, left, right) => {
+            return Err(Error::new(format!("can't apply `{operator}` to a {} and a {}", left.type_name(), right.type_name())));
+        }
+    })
+}
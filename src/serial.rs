@@ -0,0 +1,260 @@
+//! Reading sensors on Arduino-style boards over a serial port, behind the `serial` feature.
+//!
+//! The port is read on a background thread, which splits the data into frames: lines of text,
+//! such as `512,1023,0` from `Serial.println`, or packets of bytes. `update` and `draw` look at
+//! the latest frame, or take all frames since they last looked, without ever waiting for the
+//! device:
+//!
+//! ```no_run
+//! # use simple_sketch::error::SketchError;
+//! # use simple_sketch::serial::SerialPort;
+//! # struct Sketch { sensors: SerialPort }
+//! # impl Sketch {
+//! # fn new() -> Result<Self, SketchError> {
+//! // In the sketch's constructor
+//! let sensors = SerialPort::open("/dev/ttyACM0", 9600)?;
+//!
+//! # Ok(Sketch { sensors })
+//! # }
+//! # fn draw(&mut self) {
+//! // In draw
+//! let values = self.sensors.values();
+//! let light = values.first().copied().unwrap_or(0.0) / 1023.0;
+//! # }
+//! # }
+//! ```
+//!
+//! The port is configured with `stty` on Linux and macOS and with `mode` on Windows, as 8 data
+//! bits, no parity and 1 stop bit.
+
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+
+use crate::error::SketchError;
+
+/// Most frames kept for `SerialPort::poll`. Older ones are dropped when the sketch doesn't
+/// keep up.
+const MAX_QUEUED_FRAMES: usize = 1024;
+
+/// Longest frame kept while waiting for its end, in bytes, for devices sending garbage.
+const MAX_FRAME_SIZE: usize = 4096;
+
+/// How the data from a device is split into frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Framing {
+    /// Lines of text ending in `\n`, with a `\r` before it removed. The default.
+    #[default]
+    Lines,
+    /// Bytes ending in a delimiter, which is removed, such as 0 for COBS-encoded packets.
+    Delimited(u8),
+    /// Packets of a fixed number of bytes.
+    Fixed(usize),
+}
+
+/// A serial port read on a background thread.
+#[derive(Debug)]
+pub struct SerialPort {
+    shared: Arc<Mutex<Frames>>,
+    /// The open device, for writing to it. None for ports created from a reader.
+    device: Option<File>,
+    /// Tells the reading thread to stop.
+    stop: Arc<AtomicBool>,
+}
+
+// The frames read so far
+#[derive(Debug, Default)]
+struct Frames {
+    latest: Option<Vec<u8>>,
+    queue: VecDeque<Vec<u8>>,
+    /// Why reading stopped, if it did.
+    error: Option<String>,
+}
+
+impl SerialPort {
+    /// Opens a device, such as `/dev/ttyACM0` on Linux, `/dev/cu.usbmodem1101` on macOS or
+    /// `COM3` on Windows, at `baud` bits per second, for lines of text.
+    pub fn open(path: &str, baud: u32) -> Result<Self, SketchError> {
+        SerialPort::open_with(path, baud, Framing::Lines)
+    }
+
+    /// Opens a device for frames of another kind than lines.
+    pub fn open_with(path: &str, baud: u32, framing: Framing) -> Result<Self, SketchError> {
+        let path = configure(path, baud)?;
+        let device = OpenOptions::new().read(true).write(true).open(&path).map_err(|error| SketchError::Serial(format!("could not open {path}: {error}")))?;
+        let reader = device.try_clone()?;
+        let mut port = SerialPort::spawn(reader, framing, true);
+        port.device = Some(device);
+        Ok(port)
+    }
+
+    /// Reads frames from anything else that produces a stream of bytes, such as a TCP
+    /// connection to a serial bridge or a recording of a device's output, until it reads
+    /// nothing.
+    pub fn from_reader(reader: impl Read + Send + 'static, framing: Framing) -> Self {
+        SerialPort::spawn(reader, framing, false)
+    }
+
+    // Starts reading on a background thread. Readers with `timeouts` read nothing when no data
+    // arrived for a while, rather than at the end.
+    fn spawn(mut reader: impl Read + Send + 'static, framing: Framing, timeouts: bool) -> Self {
+        let shared = Arc::new(Mutex::new(Frames::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (frames, stopped) = (Arc::clone(&shared), Arc::clone(&stop));
+        thread::spawn(move || {
+            let mut splitter = Splitter { framing, frame: Vec::new() };
+            let mut bytes = [0; 1024];
+            while !stopped.load(Ordering::Relaxed) {
+                match reader.read(&mut bytes) {
+                    Ok(0) if timeouts => {}
+                    Ok(0) => {
+                        lock(&frames).error = Some("the end of the data was reached".to_string());
+                        break;
+                    }
+                    Ok(read) => {
+                        let complete = splitter.push(&bytes[..read]);
+                        if !complete.is_empty() {
+                            let mut frames = lock(&frames);
+                            for frame in complete {
+                                frames.latest = Some(frame.clone());
+                                frames.queue.push_back(frame);
+                            }
+                            let excess = frames.queue.len().saturating_sub(MAX_QUEUED_FRAMES);
+                            frames.queue.drain(..excess);
+                        }
+                    }
+                    Err(error) if error.kind() == io::ErrorKind::Interrupted || error.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(error) => {
+                        lock(&frames).error = Some(error.to_string());
+                        break;
+                    }
+                }
+            }
+        });
+        SerialPort { shared, device: None, stop }
+    }
+
+    /// The latest complete frame, which stays the same until the next one arrives.
+    pub fn latest(&self) -> Option<Vec<u8>> {
+        self.frames().latest.clone()
+    }
+
+    /// The latest frame as text, for line-framed ports.
+    pub fn latest_line(&self) -> Option<String> {
+        self.frames().latest.as_deref().map(|frame| String::from_utf8_lossy(frame).into_owned())
+    }
+
+    /// The numbers in the latest line, separated by commas, spaces or tabs, like the ones
+    /// `Serial.print` sends. Parts that aren't numbers are skipped.
+    pub fn values(&self) -> Vec<f32> {
+        self.latest_line().map(|line| parse_values(&line)).unwrap_or_default()
+    }
+
+    /// Takes all frames that arrived since the last call, in order, for devices sending
+    /// events rather than readings.
+    pub fn poll(&self) -> Vec<Vec<u8>> {
+        self.frames().queue.drain(..).collect()
+    }
+
+    /// Why the port stopped being read, such as the device being unplugged. None while it is
+    /// being read.
+    pub fn error(&self) -> Option<String> {
+        self.frames().error.clone()
+    }
+
+    /// Sends bytes to the device, for example to switch an LED on the board.
+    pub fn write(&mut self, bytes: &[u8]) -> Result<(), SketchError> {
+        let Some(device) = &mut self.device else {
+            return Err(SketchError::Serial("the port has no device to write to".to_string()));
+        };
+        device.write_all(bytes)?;
+        Ok(())
+    }
+
+    fn frames(&self) -> MutexGuard<'_, Frames> {
+        lock(&self.shared)
+    }
+}
+
+impl Drop for SerialPort {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Splits a line into the numbers in it, the way `SerialPort::values` does.
+pub fn parse_values(line: &str) -> Vec<f32> {
+    line.split(|c: char| c == ',' || c.is_whitespace()).filter_map(|part| part.parse().ok()).collect()
+}
+
+fn lock(frames: &Mutex<Frames>) -> MutexGuard<'_, Frames> {
+    frames.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Collects bytes into frames
+struct Splitter {
+    framing: Framing,
+    frame: Vec<u8>,
+}
+
+impl Splitter {
+    // Adds bytes and returns the frames they complete
+    fn push(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut complete = Vec::new();
+        for &byte in bytes {
+            match self.framing {
+                Framing::Lines | Framing::Delimited(_) => {
+                    let delimiter = if let Framing::Delimited(delimiter) = self.framing { delimiter } else { b'\n' };
+                    if byte == delimiter {
+                        let mut frame = std::mem::take(&mut self.frame);
+                        if self.framing == Framing::Lines && frame.last() == Some(&b'\r') {
+                            frame.pop();
+                        }
+                        complete.push(frame);
+                    } else if self.frame.len() < MAX_FRAME_SIZE {
+                        self.frame.push(byte);
+                    }
+                }
+                Framing::Fixed(size) => {
+                    self.frame.push(byte);
+                    if self.frame.len() >= size.max(1) {
+                        complete.push(std::mem::take(&mut self.frame));
+                    }
+                }
+            }
+        }
+        complete
+    }
+}
+
+// Sets the speed and raw mode of the device, and returns the path to open it at
+#[cfg(not(target_os = "windows"))]
+fn configure(path: &str, baud: u32) -> Result<String, SketchError> {
+    let device_flag = if cfg!(target_os = "macos") { "-f" } else { "-F" };
+    // Reads return after a tenth of a second without data, so the thread can notice it should stop
+    let settings = [baud.to_string(), "raw".into(), "-echo".into(), "cs8".into(), "-parenb".into(), "-cstopb".into(), "min".into(), "0".into(), "time".into(), "1".into()];
+    run_configuration(Command::new("stty").args([device_flag, path]).args(settings))?;
+    Ok(path.to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn configure(path: &str, baud: u32) -> Result<String, SketchError> {
+    let port = path.trim_start_matches(r"\\.\");
+    run_configuration(Command::new("mode").arg(format!("{port}:")).arg(format!("BAUD={baud}")).args(["PARITY=N", "DATA=8", "STOP=1"]))?;
+    // Ports above COM9 can only be opened through the device namespace
+    Ok(format!(r"\\.\{port}"))
+}
+
+fn run_configuration(command: &mut Command) -> Result<(), SketchError> {
+    let output = command.output().map_err(|error| SketchError::Serial(format!("could not configure the port: {error}")))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let errors = String::from_utf8_lossy(&output.stderr);
+        Err(SketchError::Serial(format!("could not configure the port: {}", errors.trim())))
+    }
+}
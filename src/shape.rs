@@ -1,7 +1,22 @@
-use crate::geom::Point;
+use std::f32::consts::{FRAC_1_SQRT_2, FRAC_PI_2, PI, TAU};
+
+use crate::geom::{self, Join, Point, Line, OrientedRect, Path, Polyline, Transform, Winding};
+
+/// Number of refinement steps for the closest point on an ellipse, enough for far below a pixel of error.
+const ELLIPSE_DISTANCE_ITERATIONS: usize = 4;
+
+/// Distance in pixels between the samples used to estimate how a transform stretches distances.
+const TRANSFORMED_GRADIENT_STEP: f32 = 0.25;
+
+/// Number of samples along each side of the bounding box used to estimate the area and perimeter of a `Boolean`.
+const BOOLEAN_SAMPLES: usize = 256;
+
+pub use crate::geom::Circle;
 
 /// Represents a geometric shape in 2D space.
-pub trait Shape {
+///
+/// Shapes are `Sync`, because the canvas samples large shapes from several threads at once.
+pub trait Shape: Sync {
     /// Determines if a point is contained within the shape.
     ///
     /// # Arguments
@@ -10,18 +25,1857 @@ pub trait Shape {
     ///
     /// # Returns
     ///
-    /// `true` if the point is inside or on the boundary of the shape, `false` otherwise.
-    fn contains(&self, point: Point) -> bool;
+    /// `true` if the point is inside or on the boundary of the shape, `false` otherwise.
+    fn contains(&self, point: Point) -> bool;
+
+    /// Calculates the bounding box of the shape.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two `Point`s representing the top-left and bottom-right
+    /// corners of the bounding box that fully encloses the shape.
+    fn bounding_box(&self) -> (Point, Point);
+
+    /// Calculates the signed distance from a point to the shape's boundary, as it is stroked.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to calculate the distance from.
+    ///
+    /// # Returns
+    ///
+    /// The shortest distance from the point to the shape's boundary, negated if the shape
+    /// contains the point. Zero on the boundary itself. Stroking relies on the magnitude
+    /// being the true distance, so approximations should stay within a small fraction of a pixel.
+    fn distance(&self, point: Point) -> f32;
+
+    /// Calculates the area of the shape.
+    ///
+    /// # Returns
+    ///
+    /// The area covered when the shape is filled.
+    fn area(&self) -> f32;
+
+    /// Calculates the perimeter of the shape.
+    ///
+    /// # Returns
+    ///
+    /// The length of the shape's boundary, as it would be stroked.
+    fn perimeter(&self) -> f32;
+
+    /// Describes the filled area of the shape by its edges, so the canvas can compute the
+    /// exact coverage of every pixel instead of sampling `contains`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tolerance` - How far flattened curves may stray from the true boundary.
+    ///
+    /// # Returns
+    ///
+    /// The closed contours around the filled area and the rule deciding what is inside them,
+    /// or None if the shape is only known through `contains`, which is the default.
+    fn fill_outline(&self, _tolerance: f32) -> Option<FillOutline> {
+        None
+    }
+
+    /// Describes the boundary of the shape as it is stroked, so the canvas can fill the outline
+    /// of the stroke instead of measuring `distance` around every pixel near it.
+    ///
+    /// # Arguments
+    ///
+    /// * `tolerance` - How far flattened curves may stray from the true boundary.
+    ///
+    /// # Returns
+    ///
+    /// The boundary flattened to polylines, open where the stroke has ends, or None if the
+    /// shape's stroke is only known through `distance`. By default, the contours of
+    /// `fill_outline` as closed polylines.
+    fn stroke_boundary(&self, tolerance: f32) -> Option<Vec<Polyline>> {
+        let outline = self.fill_outline(tolerance)?;
+        Some(outline.contours.into_iter().map(|points| Polyline { points, closed: true }).collect())
+    }
+}
+
+/// Lets shapes be used by reference wherever a `Shape` is expected.
+impl<S: Shape + ?Sized> Shape for &S {
+    fn contains(&self, point: Point) -> bool {
+        (**self).contains(point)
+    }
+
+    fn bounding_box(&self) -> (Point, Point) {
+        (**self).bounding_box()
+    }
+
+    fn distance(&self, point: Point) -> f32 {
+        (**self).distance(point)
+    }
+
+    fn area(&self) -> f32 {
+        (**self).area()
+    }
+
+    fn perimeter(&self) -> f32 {
+        (**self).perimeter()
+    }
+
+    fn fill_outline(&self, tolerance: f32) -> Option<FillOutline> {
+        (**self).fill_outline(tolerance)
+    }
+
+    fn stroke_boundary(&self, tolerance: f32) -> Option<Vec<Polyline>> {
+        (**self).stroke_boundary(tolerance)
+    }
+}
+
+/// Lets boxed shapes, such as the children of a `ShapeGroup`, be used as shapes.
+impl<S: Shape + ?Sized> Shape for Box<S> {
+    fn contains(&self, point: Point) -> bool {
+        (**self).contains(point)
+    }
+
+    fn bounding_box(&self) -> (Point, Point) {
+        (**self).bounding_box()
+    }
+
+    fn distance(&self, point: Point) -> f32 {
+        (**self).distance(point)
+    }
+
+    fn area(&self) -> f32 {
+        (**self).area()
+    }
+
+    fn perimeter(&self) -> f32 {
+        (**self).perimeter()
+    }
+
+    fn fill_outline(&self, tolerance: f32) -> Option<FillOutline> {
+        (**self).fill_outline(tolerance)
+    }
+
+    fn stroke_boundary(&self, tolerance: f32) -> Option<Vec<Polyline>> {
+        (**self).stroke_boundary(tolerance)
+    }
+}
+
+/// The rule deciding which parts of a shape's contours are inside it where they overlap or nest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    /// Inside wherever the contours wind around a point, in either direction.
+    NonZero,
+    /// Inside wherever a ray from a point crosses the contours an odd number of times, which
+    /// turns nested contours into holes.
+    EvenOdd,
+}
+
+/// The edges of a shape's filled area, flattened to straight lines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillOutline {
+    /// The contours, each closed from its last point back to its first.
+    pub contours: Vec<Vec<Point>>,
+    /// How overlapping and nested contours are filled.
+    pub rule: FillRule,
+}
+
+impl FillOutline {
+    /// Creates an outline filled with the non-zero rule from the polylines of a flattened
+    /// path, treating open ones as closed.
+    pub fn from_polylines(polylines: Vec<Polyline>) -> Self {
+        FillOutline { contours: polylines.into_iter().map(|polyline| polyline.points).collect(), rule: FillRule::NonZero }
+    }
+
+    /// Creates an outline of a single contour filled with the non-zero rule.
+    pub fn polygon(points: Vec<Point>) -> Self {
+        FillOutline { contours: vec![points], rule: FillRule::NonZero }
+    }
+
+    /// Creates the outline of a stroke `weight` wide along polylines, with round caps and
+    /// joins, filled with the non-zero rule. See `Polyline::stroke`.
+    pub fn stroke(polylines: &[Polyline], weight: f32) -> Self {
+        let contours = polylines.iter().flat_map(|polyline| polyline.stroke(weight, Join::Round)).map(|polyline| polyline.points).collect();
+        FillOutline { contours, rule: FillRule::NonZero }
+    }
+}
+
+/// Represents an ellipse in 2D space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ellipse {
+    /// The center point of the ellipse.
+    pub center: Point,
+    /// The width of the ellipse (diameter along the x-axis).
+    pub width: f32,
+    /// The height of the ellipse (diameter along the y-axis).
+    pub height: f32,
+}
+
+/// Represents a rectangle in 2D space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rectangle {
+    /// The top-left corner of the rectangle.
+    pub top_left: Point,
+    /// The width of the rectangle.
+    pub width: f32,
+    /// The height of the rectangle.
+    pub height: f32,
+}
+
+/// Represents a rectangle with rounded corners in 2D space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoundedRect {
+    /// The top-left corner of the rectangle, before rounding.
+    pub top_left: Point,
+    /// The width of the rectangle.
+    pub width: f32,
+    /// The height of the rectangle.
+    pub height: f32,
+    /// The corner radii, clockwise from the top-left corner: top-left, top-right, bottom-right and
+    /// bottom-left. Radii larger than half the shorter side are reduced to it.
+    pub radii: [f32; 4],
+}
+
+impl RoundedRect {
+    /// Returns the corner radii, reduced to fit the rectangle.
+    pub fn clamped_radii(&self) -> [f32; 4] {
+        let max_radius = (self.width.min(self.height) / 2.0).max(0.0);
+        self.radii.map(|radius| radius.clamp(0.0, max_radius))
+    }
+
+    /// Converts the rounded rectangle into a closed path, clockwise from the top edge.
+    pub fn to_path(&self) -> Path {
+        let [top_left, top_right, bottom_right, bottom_left] = self.clamped_radii();
+        let (min, max) = (self.top_left, self.top_left + Point::new(self.width, self.height));
+
+        let mut path = Path::new();
+        path.move_to(Point::new(min.x + top_left, min.y));
+        path.arc(Point::new(max.x - top_right, min.y + top_right), top_right, -FRAC_PI_2, FRAC_PI_2);
+        path.arc(Point::new(max.x - bottom_right, max.y - bottom_right), bottom_right, 0.0, FRAC_PI_2);
+        path.arc(Point::new(min.x + bottom_left, max.y - bottom_left), bottom_left, FRAC_PI_2, FRAC_PI_2);
+        path.arc(Point::new(min.x + top_left, min.y + top_left), top_left, PI, FRAC_PI_2);
+        path.close();
+        path
+    }
+}
+
+/// Represents a star with `points` tips evenly spaced around its center.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Star {
+    /// The center of the star.
+    pub center: Point,
+    /// The number of tips. Stars with fewer than two tips are empty.
+    pub points: usize,
+    /// The distance from the center to the notches between the tips.
+    pub inner_radius: f32,
+    /// The distance from the center to the tips.
+    pub outer_radius: f32,
+    /// The angle of the first tip, in radians clockwise from the positive x-axis.
+    pub rotation: f32,
+}
+
+impl Star {
+    /// Returns the tips and notches of the star, alternating and starting with the first tip.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::Point;
+    /// # use simple_sketch::shape::Star;
+    /// let star = Star { center: Point::new(0.0, 0.0), points: 5, inner_radius: 0.5, outer_radius: 1.0, rotation: 0.0 };
+    /// assert_eq!(star.vertices().len(), 10);
+    /// assert_eq!(star.vertices()[0], Point::new(1.0, 0.0));
+    /// ```
+    pub fn vertices(&self) -> Vec<Point> {
+        let step = PI / self.points as f32;
+        (0..self.points * 2)
+            .map(|i| {
+                let radius = if i % 2 == 0 { self.outer_radius } else { self.inner_radius };
+                let (sin, cos) = (self.rotation + step * i as f32).sin_cos();
+                self.center + Point::new(cos, sin) * radius
+            })
+            .collect()
+    }
+}
+
+/// Represents a polygon with `sides` equal sides and angles, such as a hexagon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegularPolygon {
+    /// The center of the polygon.
+    pub center: Point,
+    /// The number of sides. Polygons with fewer than three sides are empty.
+    pub sides: usize,
+    /// The distance from the center to the vertices.
+    pub radius: f32,
+    /// The angle of the first vertex, in radians clockwise from the positive x-axis.
+    pub rotation: f32,
+}
+
+impl RegularPolygon {
+    /// Returns the distance from the center to the middle of the sides.
+    pub fn inner_radius(&self) -> f32 {
+        self.radius * (PI / self.sides as f32).cos()
+    }
+
+    /// Returns the vertices of the polygon, clockwise on screen starting with the first vertex.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::Point;
+    /// # use simple_sketch::shape::RegularPolygon;
+    /// let hexagon = RegularPolygon { center: Point::new(0.0, 0.0), sides: 6, radius: 1.0, rotation: 0.0 };
+    /// assert_eq!(hexagon.vertices().len(), 6);
+    /// assert_eq!(hexagon.vertices()[0], Point::new(1.0, 0.0));
+    /// ```
+    pub fn vertices(&self) -> Vec<Point> {
+        let step = TAU / self.sides as f32;
+        (0..self.sides)
+            .map(|i| {
+                let (sin, cos) = (self.rotation + step * i as f32).sin_cos();
+                self.center + Point::new(cos, sin) * self.radius
+            })
+            .collect()
+    }
+}
+
+/// Represents a triangle in 2D space. The corners may be given in either winding direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Triangle {
+    /// The first corner of the triangle.
+    pub a: Point,
+    /// The second corner of the triangle.
+    pub b: Point,
+    /// The third corner of the triangle.
+    pub c: Point,
+}
+
+/// Represents a capsule: all points within `radius` of a line segment, like a thick line with round caps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capsule {
+    /// The center of the first cap.
+    pub start: Point,
+    /// The center of the second cap.
+    pub end: Point,
+    /// The radius of the caps, half the capsule's width.
+    pub radius: f32,
+}
+
+impl Capsule {
+    /// Converts the capsule to a closed path, clockwise around its outline.
+    pub fn to_path(&self) -> Path {
+        let direction = self.end - self.start;
+        let angle = direction.y.atan2(direction.x);
+        let mut path = Path::new();
+        path.arc(self.end, self.radius, angle - FRAC_PI_2, PI)
+            .arc(self.start, self.radius, angle + FRAC_PI_2, PI)
+            .close();
+        path
+    }
+
+    // The distance from a point to the capsule's center line.
+    fn axis_distance(&self, point: Point) -> f32 {
+        point.distance(&Line::new(self.start, self.end).closest_point(&point))
+    }
+}
+
+/// How an `Arc` is closed for filling and stroking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArcMode {
+    /// Fills the area between the arc and its chord, but only strokes the curve itself.
+    #[default]
+    Open,
+    /// Fills and strokes the area between the arc and its chord.
+    Chord,
+    /// Fills and strokes the wedge between the arc and the center, like a slice of pie.
+    Pie,
+}
+
+/// Represents a part of a circle between two angles, drawn clockwise on screen from `start` to `end`.
+///
+/// Angles are in radians and measured from the positive x-axis. Arcs spanning a full turn or
+/// more are whole circles, and arcs whose `end` lies before their `start` are empty.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Arc {
+    /// The center of the circle the arc lies on.
+    pub center: Point,
+    /// The radius of the circle the arc lies on.
+    pub radius: f32,
+    /// The angle at which the arc starts.
+    pub start: f32,
+    /// The angle at which the arc ends.
+    pub end: f32,
+    /// How the arc is closed.
+    pub mode: ArcMode,
+}
+
+impl Arc {
+    /// Returns the angle covered by the arc, between zero and a full turn.
+    pub fn sweep(&self) -> f32 {
+        (self.end - self.start).clamp(0.0, TAU)
+    }
+
+    /// Returns the point on the circle at the given angle.
+    pub fn point_at_angle(&self, angle: f32) -> Point {
+        let (sin, cos) = angle.sin_cos();
+        self.center + Point::new(cos, sin) * self.radius
+    }
+
+    /// Converts the arc into a path, closed according to its mode. Open arcs are left open,
+    /// which still fills the area up to their chord.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::geom::Point;
+    /// # use simple_sketch::shape::{Arc, ArcMode};
+    /// use std::f32::consts::PI;
+    ///
+    /// let arc = Arc { center: Point::new(0.0, 0.0), radius: 1.0, start: 0.0, end: PI, mode: ArcMode::Pie };
+    /// assert!((arc.to_path().length() - (PI + 2.0)).abs() < 0.01);
+    /// ```
+    pub fn to_path(&self) -> Path {
+        let mut path = Path::new();
+        if self.mode == ArcMode::Pie && self.sweep() < TAU {
+            path.move_to(self.center);
+        }
+        path.arc(self.center, self.radius, self.start, self.sweep());
+        if self.mode != ArcMode::Open {
+            path.close();
+        }
+        path
+    }
+
+    // Checks if the direction from the center at `angle` lies within the sweep of the arc.
+    fn covers_angle(&self, angle: f32) -> bool {
+        (angle - self.start).rem_euclid(TAU) <= self.sweep()
+    }
+
+    // Distance from a point to the curved part of the arc.
+    fn curve_distance(&self, point: Point) -> f32 {
+        let offset = point - self.center;
+        if self.covers_angle(offset.y.atan2(offset.x)) {
+            (offset.length() - self.radius).abs()
+        } else {
+            let start = self.point_at_angle(self.start);
+            let end = self.point_at_angle(self.start + self.sweep());
+            point.distance(&start).min(point.distance(&end))
+        }
+    }
+}
+
+/// Represents a shape drawn through a transform, such as a rotated ellipse.
+///
+/// Points are mapped back into the shape's own coordinate system for containment and
+/// distance tests. Distances are exact for rotations, translations and uniform scales, and
+/// corrected to first order for other transforms, which keeps strokes close to even.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transformed<S> {
+    /// The shape in its own coordinate system.
+    pub shape: S,
+    /// The transform from the shape's coordinate system to the drawing's.
+    pub transform: Transform,
+}
+
+/// Represents a group of shapes, each placed with its own transform, that behaves as their union.
+///
+/// Build a motif once and then draw or hit-test it as a single shape, or nest it inside other
+/// groups. Strokes follow the outline of the union, skipping the parts of a child's boundary
+/// that are covered by another child.
+///
+/// By default the children can be any shapes. A `ShapeGroup<AnyShape>` is limited to the shapes
+/// of this module, but can be cloned and serialized.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShapeGroup<S = Box<dyn Shape>> {
+    /// The child shapes, each with its transform relative to the group.
+    pub children: Vec<Transformed<S>>,
+}
+
+impl<S> Default for ShapeGroup<S> {
+    fn default() -> Self {
+        ShapeGroup { children: Vec::new() }
+    }
+}
+
+impl<S> ShapeGroup<S> {
+    /// Adds a child to the group, without boxing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `shape` - The shape to add, in its own coordinate system.
+    /// * `transform` - The transform placing the shape within the group.
+    ///
+    /// # Returns
+    ///
+    /// The group itself, so that calls can be chained.
+    pub fn push(&mut self, shape: S, transform: Transform) -> &mut Self {
+        self.children.push(Transformed { shape, transform });
+        self
+    }
+
+    /// Returns true if the group has no children.
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+impl ShapeGroup {
+    /// Creates an empty group.
+    pub fn new() -> Self {
+        ShapeGroup::default()
+    }
+
+    /// Adds a child shape to the group.
+    ///
+    /// # Arguments
+    ///
+    /// * `shape` - The shape to add, in its own coordinate system.
+    /// * `transform` - The transform placing the shape within the group.
+    ///
+    /// # Returns
+    ///
+    /// The group itself, so that calls can be chained.
+    pub fn add(&mut self, shape: impl Shape + 'static, transform: Transform) -> &mut Self {
+        self.push(Box::new(shape), transform)
+    }
+}
+
+/// The ways a `Boolean` can combine two shapes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BooleanOp {
+    /// The area covered by either shape.
+    Union,
+    /// The area covered by both shapes.
+    Intersection,
+    /// The area of the first shape that is not covered by the second.
+    Difference,
+}
+
+/// Represents two shapes combined with a boolean operation, such as a rectangle with a circular hole.
+///
+/// The result is computed per point from the operands' containment and signed distances, so any
+/// shapes can be combined and nested without building new geometry. Strokes follow the outline of
+/// the result, including the edges of holes.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::geom::{Circle, Point};
+/// # use simple_sketch::shape::{Boolean, Rectangle, Shape};
+/// let plate = Rectangle { top_left: Point::new(0.0, 0.0), width: 100.0, height: 60.0 };
+/// let hole = Circle::new(Point::new(50.0, 30.0), 20.0);
+/// let shape = Boolean::difference(plate, hole);
+/// assert!(!shape.contains(Point::new(50.0, 30.0)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Boolean<A, B> {
+    /// The operation combining the shapes.
+    pub op: BooleanOp,
+    /// The first operand.
+    pub a: A,
+    /// The second operand.
+    pub b: B,
+}
+
+impl<A: Shape, B: Shape> Boolean<A, B> {
+    /// Combines two shapes into the area covered by either.
+    pub fn union(a: A, b: B) -> Self {
+        Boolean { op: BooleanOp::Union, a, b }
+    }
+
+    /// Combines two shapes into the area covered by both.
+    pub fn intersection(a: A, b: B) -> Self {
+        Boolean { op: BooleanOp::Intersection, a, b }
+    }
+
+    /// Cuts the second shape out of the first.
+    pub fn difference(a: A, b: B) -> Self {
+        Boolean { op: BooleanOp::Difference, a, b }
+    }
+
+    // Calls `visit` with the signed distance at the center of each cell of a grid over the bounding
+    // box grown by `margin`, returning the area of one cell.
+    fn sample_distances(&self, margin: f32, mut visit: impl FnMut(f32)) -> f32 {
+        let (min, max) = self.bounding_box();
+        let (min, max) = (min - Point::new(margin, margin), max + Point::new(margin, margin));
+        let size = max - min;
+        if !(size.x > 0.0 && size.y > 0.0 && size.x.is_finite() && size.y.is_finite()) {
+            return 0.0;
+        }
+        let cell = Point::new(size.x / BOOLEAN_SAMPLES as f32, size.y / BOOLEAN_SAMPLES as f32);
+        for row in 0..BOOLEAN_SAMPLES {
+            for column in 0..BOOLEAN_SAMPLES {
+                let offset = Point::new((column as f32 + 0.5) * cell.x, (row as f32 + 0.5) * cell.y);
+                visit(self.distance(min + offset));
+            }
+        }
+        cell.x * cell.y
+    }
+}
+
+/// Represents any of the shapes in this module, for storing mixed shapes by value and loading
+/// them at runtime.
+///
+/// With the `serde` feature enabled, it serializes as an object tagged with the shape's
+/// kind in snake case, for example `{"type": "circle", "center": {"x": 0.0, "y": 0.0}, "radius": 5.0}`.
+/// Groups, transforms and booleans nest further shapes the same way.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum AnyShape {
+    Ellipse(Ellipse),
+    Circle(Circle),
+    Rectangle(Rectangle),
+    RoundedRect(RoundedRect),
+    Star(Star),
+    RegularPolygon(RegularPolygon),
+    Triangle(Triangle),
+    Capsule(Capsule),
+    Arc(Arc),
+    Polygon(Polygon),
+    Outline(Outline),
+    OrientedRect(OrientedRect),
+    Transformed(Box<Transformed<AnyShape>>),
+    Group(ShapeGroup<AnyShape>),
+    Boolean(Box<Boolean<AnyShape, AnyShape>>),
+}
+
+impl AnyShape {
+    // The shape as a trait object, to forward the `Shape` methods to.
+    fn as_shape(&self) -> &dyn Shape {
+        match self {
+            AnyShape::Ellipse(shape) => shape,
+            AnyShape::Circle(shape) => shape,
+            AnyShape::Rectangle(shape) => shape,
+            AnyShape::RoundedRect(shape) => shape,
+            AnyShape::Star(shape) => shape,
+            AnyShape::RegularPolygon(shape) => shape,
+            AnyShape::Triangle(shape) => shape,
+            AnyShape::Capsule(shape) => shape,
+            AnyShape::Arc(shape) => shape,
+            AnyShape::Polygon(shape) => shape,
+            AnyShape::Outline(shape) => shape,
+            AnyShape::OrientedRect(shape) => shape,
+            AnyShape::Transformed(shape) => shape.as_ref(),
+            AnyShape::Group(shape) => shape,
+            AnyShape::Boolean(shape) => shape.as_ref(),
+        }
+    }
+}
+
+impl From<Ellipse> for AnyShape {
+    fn from(shape: Ellipse) -> Self {
+        AnyShape::Ellipse(shape)
+    }
+}
+
+impl From<Circle> for AnyShape {
+    fn from(shape: Circle) -> Self {
+        AnyShape::Circle(shape)
+    }
+}
+
+impl From<Rectangle> for AnyShape {
+    fn from(shape: Rectangle) -> Self {
+        AnyShape::Rectangle(shape)
+    }
+}
+
+impl From<RoundedRect> for AnyShape {
+    fn from(shape: RoundedRect) -> Self {
+        AnyShape::RoundedRect(shape)
+    }
+}
+
+impl From<Star> for AnyShape {
+    fn from(shape: Star) -> Self {
+        AnyShape::Star(shape)
+    }
+}
+
+impl From<RegularPolygon> for AnyShape {
+    fn from(shape: RegularPolygon) -> Self {
+        AnyShape::RegularPolygon(shape)
+    }
+}
+
+impl From<Triangle> for AnyShape {
+    fn from(shape: Triangle) -> Self {
+        AnyShape::Triangle(shape)
+    }
+}
+
+impl From<Capsule> for AnyShape {
+    fn from(shape: Capsule) -> Self {
+        AnyShape::Capsule(shape)
+    }
+}
+
+impl From<Arc> for AnyShape {
+    fn from(shape: Arc) -> Self {
+        AnyShape::Arc(shape)
+    }
+}
+
+impl From<Polygon> for AnyShape {
+    fn from(shape: Polygon) -> Self {
+        AnyShape::Polygon(shape)
+    }
+}
+
+impl From<Outline> for AnyShape {
+    fn from(shape: Outline) -> Self {
+        AnyShape::Outline(shape)
+    }
+}
+
+impl From<OrientedRect> for AnyShape {
+    fn from(shape: OrientedRect) -> Self {
+        AnyShape::OrientedRect(shape)
+    }
+}
+
+impl From<Transformed<AnyShape>> for AnyShape {
+    fn from(shape: Transformed<AnyShape>) -> Self {
+        AnyShape::Transformed(Box::new(shape))
+    }
+}
+
+impl From<ShapeGroup<AnyShape>> for AnyShape {
+    fn from(shape: ShapeGroup<AnyShape>) -> Self {
+        AnyShape::Group(shape)
+    }
+}
+
+impl From<Boolean<AnyShape, AnyShape>> for AnyShape {
+    fn from(shape: Boolean<AnyShape, AnyShape>) -> Self {
+        AnyShape::Boolean(Box::new(shape))
+    }
+}
+
+/// Represents a filled area bounded by one or more polylines, such as a flattened `Path`.
+///
+/// Filling always treats every polyline as closed and uses the non-zero winding rule,
+/// while the boundary used for stroking only includes the closing edge of closed polylines.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outline {
+    /// The polylines making up the outline.
+    pub polylines: Vec<Polyline>,
+}
+
+/// Represents a drawable polygon in 2D space.
+///
+/// The geometry itself lives in `geom::Polygon`; this type only adapts it to the `Shape` trait.
+/// An open polygon is still filled as if it were closed, but its stroke leaves out the edge
+/// from the last vertex back to the first.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polygon {
+    /// The polygon geometry.
+    pub geometry: geom::Polygon,
+    /// Whether the boundary includes the closing edge.
+    pub closed: bool,
+    /// The texture coordinates of the vertices, in image pixels, for
+    /// `Canvas::draw_textured_polygon`. Either empty or one per vertex.
+    pub uvs: Vec<Point>,
+}
+
+impl Polygon {
+    /// Creates a closed polygon from its vertices.
+    pub fn new(vertices: Vec<Point>) -> Self {
+        Polygon { geometry: geom::Polygon::new(vertices), closed: true, uvs: Vec::new() }
+    }
+
+    /// Creates a closed polygon from its vertices and their texture coordinates, in image
+    /// pixels. Returns None unless there is one texture coordinate per vertex.
+    pub fn with_uvs(vertices: Vec<Point>, uvs: Vec<Point>) -> Option<Self> {
+        (vertices.len() == uvs.len()).then(|| Polygon { geometry: geom::Polygon::new(vertices), closed: true, uvs })
+    }
+
+    /// Converts the polygon to a path, closed only if the polygon is.
+    pub fn to_path(&self) -> Path {
+        if self.closed {
+            return Path::polygon(&self.geometry.vertices);
+        }
+        let mut path = Path::new();
+        if let [first, rest @ ..] = self.geometry.vertices.as_slice() {
+            path.move_to(*first);
+            for vertex in rest {
+                path.line_to(*vertex);
+            }
+        }
+        path
+    }
+
+    /// Writes the polygon as SVG path data, closed only if the polygon is. See
+    /// `Path::to_svg_path_string`.
+    pub fn to_svg_path_string(&self) -> String {
+        self.to_path().to_svg_path_string()
+    }
+
+    // The edges that make up the stroked boundary.
+    fn boundary(&self) -> impl Iterator<Item = Line> + '_ {
+        let count = if self.closed {
+            self.geometry.edges().count()
+        } else {
+            self.geometry.vertices.len().saturating_sub(1)
+        };
+        self.geometry.edges().take(count)
+    }
+}
+
+/// Builds a `Polygon` one vertex at a time, in the style of Processing's `beginShape()`,
+/// `vertex()` and `endShape()`.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::geom::Point;
+/// # use simple_sketch::shape::PolygonBuilder;
+/// # let mut canvas = Canvas::new(80, 60);
+/// let mut builder = PolygonBuilder::new();
+/// builder.begin_shape();
+/// builder.vertex(Point::new(0.0, 0.0)).vertex(Point::new(4.0, 0.0)).vertex(Point::new(2.0, 3.0));
+/// let triangle = builder.end_shape(true).unwrap();
+/// canvas.draw_polygon(&triangle);
+/// ```
+///
+/// Giving every vertex a texture coordinate with `vertex_uv` makes a polygon for
+/// `Canvas::draw_textured_polygon`, like Processing's `vertex(x, y, u, v)`:
+///
+/// ```
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::geom::Point;
+/// # use simple_sketch::image::Image;
+/// # use simple_sketch::shape::PolygonBuilder;
+/// # fn draw(canvas: &mut Canvas, builder: &mut PolygonBuilder, photo: &Image) {
+/// // A photo on a quad with a sagging bottom edge
+/// builder.begin_shape();
+/// builder.vertex_uv(Point::new(0.0, 0.0), Point::new(0.0, 0.0));
+/// builder.vertex_uv(Point::new(200.0, 0.0), Point::new(photo.width as f32, 0.0));
+/// builder.vertex_uv(Point::new(180.0, 220.0), Point::new(photo.width as f32, photo.height as f32));
+/// builder.vertex_uv(Point::new(20.0, 180.0), Point::new(0.0, photo.height as f32));
+/// canvas.draw_textured_polygon(&builder.end_shape(true).unwrap(), &photo);
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PolygonBuilder {
+    vertices: Vec<Point>,
+    /// The texture coordinates given with `vertex_uv`, one per vertex until a vertex has none.
+    uvs: Vec<Point>,
+    started: bool,
+}
+
+impl PolygonBuilder {
+    /// Creates a builder with no shape started.
+    pub fn new() -> Self {
+        PolygonBuilder::default()
+    }
+
+    /// Starts a new shape, discarding the vertices of any unfinished one.
+    pub fn begin_shape(&mut self) -> &mut Self {
+        self.vertices.clear();
+        self.uvs.clear();
+        self.started = true;
+        self
+    }
+
+    /// Adds a vertex to the current shape. Vertices added outside of `begin_shape` and
+    /// `end_shape` are ignored.
+    pub fn vertex(&mut self, point: Point) -> &mut Self {
+        if self.started {
+            self.vertices.push(point);
+        }
+        self
+    }
+
+    /// Adds a vertex with a texture coordinate, in image pixels. The finished polygon only
+    /// keeps texture coordinates if every vertex has one.
+    pub fn vertex_uv(&mut self, point: Point, uv: Point) -> &mut Self {
+        if self.started {
+            if self.uvs.len() == self.vertices.len() {
+                self.uvs.push(uv);
+            }
+            self.vertices.push(point);
+        }
+        self
+    }
+
+    /// Finishes the current shape.
+    ///
+    /// # Arguments
+    ///
+    /// * `close` - Whether to connect the last vertex back to the first when stroking.
+    ///
+    /// # Returns
+    ///
+    /// The finished polygon, or None if no shape was started or it has too few vertices:
+    /// a closed polygon needs at least three and an open one at least two.
+    pub fn end_shape(&mut self, close: bool) -> Option<Polygon> {
+        if !std::mem::take(&mut self.started) {
+            return None;
+        }
+        let vertices = std::mem::take(&mut self.vertices);
+        let mut uvs = std::mem::take(&mut self.uvs);
+        let required = if close { 3 } else { 2 };
+        if vertices.len() < required {
+            return None;
+        }
+        if uvs.len() != vertices.len() {
+            uvs.clear();
+        }
+        Some(Polygon { geometry: geom::Polygon::new(vertices), closed: close, uvs })
+    }
+}
+
+/// Implements the `Shape` trait for an `Ellipse`.
+///
+/// An `Ellipse` is defined by its center point, width, and height.
+impl Shape for Ellipse {
+    /// Determines if a point is contained within the ellipse.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the point is inside or on the ellipse, `false` otherwise.
+    fn contains(&self, point: Point) -> bool {
+        // Normalize the point relative to the ellipse's center and dimensions
+        let dx = (point.x - self.center.x) / (self.width / 2.0);
+        let dy = (point.y - self.center.y) / (self.height / 2.0);
+        
+        // Check if the normalized point satisfies the ellipse equation
+        dx * dx + dy * dy <= 1.0
+    }
+
+    /// Calculates the bounding box of the ellipse.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two `Point`s representing the top-left and bottom-right
+    /// corners of the bounding box.
+    fn bounding_box(&self) -> (Point, Point) {
+        (
+            Point::new(self.center.x - self.width / 2.0, self.center.y - self.height / 2.0),
+            Point::new(self.center.x + self.width / 2.0, self.center.y + self.height / 2.0),
+        )
+    }
+
+    /// Calculates the shortest distance from a point to the ellipse's boundary.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to calculate the distance from.
+    ///
+    /// # Returns
+    ///
+    /// The shortest distance from the point to the ellipse's boundary.
+    /// Negative if the point is inside the ellipse.
+    fn distance(&self, point: Point) -> f32 {
+        let (a, b) = ((self.width / 2.0).abs(), (self.height / 2.0).abs());
+        let offset = (point - self.center).abs();
+        if a == 0.0 || b == 0.0 {
+            // A flat ellipse is a line segment
+            let extent = Point::new(a, b);
+            return offset.distance(&Line::new(Point::new(0.0, 0.0) - extent, extent).closest_point(&offset));
+        }
+
+        // Find the closest point in the first quadrant by repeatedly approximating the ellipse
+        // near the current guess with its circle of curvature
+        let mut t = Point::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2);
+        for _ in 0..ELLIPSE_DISTANCE_ITERATIONS {
+            let on_ellipse = Point::new(a * t.x, b * t.y);
+            let evolute = Point::new(
+                (a * a - b * b) * t.x.powi(3) / a,
+                (b * b - a * a) * t.y.powi(3) / b,
+            );
+            let radius = (on_ellipse - evolute).length();
+            let towards = offset - evolute;
+            let reach = towards.length();
+            if reach == 0.0 {
+                break;
+            }
+            let target = towards * (radius / reach) + evolute;
+            t = Point::new((target.x / a).clamp(0.0, 1.0), (target.y / b).clamp(0.0, 1.0)).normalize();
+        }
+
+        let distance = offset.distance(&Point::new(a * t.x, b * t.y));
+        if self.contains(point) { -distance } else { distance }
+    }
+
+    /// Calculates the area of the ellipse.
+    ///
+    /// # Returns
+    ///
+    /// The area enclosed by the ellipse.
+    fn area(&self) -> f32 {
+        PI * (self.width / 2.0).abs() * (self.height / 2.0).abs()
+    }
+
+    /// Approximates the circumference of the ellipse using Ramanujan's second approximation.
+    ///
+    /// # Returns
+    ///
+    /// The length of the ellipse's boundary, accurate to a tiny fraction of a pixel for ellipses of any practical size.
+    fn perimeter(&self) -> f32 {
+        let (a, b) = ((self.width / 2.0).abs(), (self.height / 2.0).abs());
+        if a + b == 0.0 {
+            return 0.0;
+        }
+        let h = ((a - b) / (a + b)).powi(2);
+        PI * (a + b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()))
+    }
+
+    /// Describes the filled area of the ellipse by its edges.
+    ///
+    /// # Returns
+    ///
+    /// The ellipse's curve, flattened to within `tolerance`.
+    fn fill_outline(&self, tolerance: f32) -> Option<FillOutline> {
+        Some(FillOutline::from_polylines(Path::ellipse(self.center, self.width, self.height).flatten(tolerance)))
+    }
+}
+
+/// Implements the `Shape` trait for a `Circle`.
+impl Shape for Circle {
+    /// Determines if a point is contained within the circle.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the point is inside or on the circle, `false` otherwise.
+    fn contains(&self, point: Point) -> bool {
+        self.contains_point(&point)
+    }
+
+    /// Calculates the bounding box of the circle.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two `Point`s representing the top-left and bottom-right
+    /// corners of the bounding box.
+    fn bounding_box(&self) -> (Point, Point) {
+        let rect = Circle::bounding_box(self);
+        (rect.min, rect.max)
+    }
+
+    /// Calculates the exact distance from a point to the circle's boundary.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to calculate the distance from.
+    ///
+    /// # Returns
+    ///
+    /// The shortest distance from the point to the circle's boundary.
+    /// Negative if the point is inside the circle.
+    fn distance(&self, point: Point) -> f32 {
+        self.signed_distance(&point)
+    }
+
+    /// Calculates the area of the circle.
+    ///
+    /// # Returns
+    ///
+    /// The area enclosed by the circle.
+    fn area(&self) -> f32 {
+        PI * self.radius * self.radius
+    }
+
+    /// Calculates the circumference of the circle.
+    ///
+    /// # Returns
+    ///
+    /// The length of the circle's boundary.
+    fn perimeter(&self) -> f32 {
+        TAU * self.radius
+    }
+
+    /// Describes the filled area of the circle by its edges.
+    ///
+    /// # Returns
+    ///
+    /// The circle's curve, flattened to within `tolerance`.
+    fn fill_outline(&self, tolerance: f32) -> Option<FillOutline> {
+        let diameter = 2.0 * self.radius;
+        Some(FillOutline::from_polylines(Path::ellipse(self.center, diameter, diameter).flatten(tolerance)))
+    }
+}
+
+/// Implements the `Shape` trait for a `Rectangle`.
+impl Shape for Rectangle {
+    /// Determines if a point is contained within the rectangle.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the point is inside or on the boundary of the rectangle, `false` otherwise.
+    fn contains(&self, point: Point) -> bool {
+        point.x >= self.top_left.x && point.x <= self.top_left.x + self.width &&
+        point.y >= self.top_left.y && point.y <= self.top_left.y + self.height
+    }
+
+    /// Calculates the bounding box of the rectangle.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two `Point`s representing the top-left and bottom-right
+    /// corners of the bounding box. For a rectangle, this is simply its own corners.
+    fn bounding_box(&self) -> (Point, Point) {
+        (
+            self.top_left,
+            Point::new(self.top_left.x + self.width, self.top_left.y + self.height),
+        )
+    }
+
+    /// Calculates the shortest distance from a point to the rectangle's boundary.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to calculate the distance from.
+    ///
+    /// # Returns
+    ///
+    /// The shortest distance from the point to the rectangle's boundary.
+    /// Negative if the point is inside the rectangle.
+    fn distance(&self, point: Point) -> f32 {
+        let half_size = Point::new(self.width / 2.0, self.height / 2.0).abs();
+        let offset = (point - (self.top_left + Point::new(self.width / 2.0, self.height / 2.0))).abs() - half_size;
+
+        // Outside, the distance to the nearest corner or edge; inside, to the nearest edge
+        let outside = Point::new(offset.x.max(0.0), offset.y.max(0.0)).length();
+        let inside = offset.x.max(offset.y).min(0.0);
+        outside + inside
+    }
+
+    /// Calculates the area of the rectangle.
+    ///
+    /// # Returns
+    ///
+    /// The width times the height.
+    fn area(&self) -> f32 {
+        (self.width * self.height).abs()
+    }
+
+    /// Calculates the perimeter of the rectangle.
+    ///
+    /// # Returns
+    ///
+    /// The total length of the rectangle's four sides.
+    fn perimeter(&self) -> f32 {
+        2.0 * (self.width.abs() + self.height.abs())
+    }
+
+    /// Describes the filled area of the rectangle by its edges.
+    ///
+    /// # Returns
+    ///
+    /// The rectangle's four corners.
+    fn fill_outline(&self, _tolerance: f32) -> Option<FillOutline> {
+        let (min, size) = (self.top_left, Point::new(self.width, self.height));
+        Some(FillOutline::polygon(vec![min, Point::new(min.x + size.x, min.y), min + size, Point::new(min.x, min.y + size.y)]))
+    }
+}
+
+/// Implements the `Shape` trait for a `RoundedRect`.
+impl Shape for RoundedRect {
+    /// Determines if a point is contained within the rounded rectangle.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the point is inside or on the boundary of the rounded rectangle, `false` otherwise.
+    fn contains(&self, point: Point) -> bool {
+        self.distance(point) <= 0.0
+    }
+
+    /// Calculates the bounding box of the rounded rectangle.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two `Point`s representing the top-left and bottom-right
+    /// corners of the bounding box, which are those of the rectangle before rounding.
+    fn bounding_box(&self) -> (Point, Point) {
+        (
+            self.top_left,
+            Point::new(self.top_left.x + self.width, self.top_left.y + self.height),
+        )
+    }
+
+    /// Calculates the exact distance from a point to the rounded rectangle's boundary.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to calculate the distance from.
+    ///
+    /// # Returns
+    ///
+    /// The shortest distance from the point to the rounded rectangle's boundary.
+    /// Negative if the point is inside the rounded rectangle.
+    fn distance(&self, point: Point) -> f32 {
+        let half_size = Point::new(self.width / 2.0, self.height / 2.0);
+        let offset = point - (self.top_left + half_size);
+
+        // Only the radius of the corner in the point's quadrant matters
+        let [top_left, top_right, bottom_right, bottom_left] = self.clamped_radii();
+        let radius = match (offset.x >= 0.0, offset.y >= 0.0) {
+            (false, false) => top_left,
+            (true, false) => top_right,
+            (true, true) => bottom_right,
+            (false, true) => bottom_left,
+        };
+
+        // Distance to a rectangle shrunk by the radius, then grown back with round corners
+        let q = offset.abs() - half_size + Point::new(radius, radius);
+        let outside = Point::new(q.x.max(0.0), q.y.max(0.0)).length();
+        let inside = q.x.max(q.y).min(0.0);
+        outside + inside - radius
+    }
+
+    /// Calculates the area of the rounded rectangle.
+    ///
+    /// # Returns
+    ///
+    /// The area of the rectangle, minus what the rounding cuts off its corners.
+    fn area(&self) -> f32 {
+        let corners: f32 = self.clamped_radii().iter().map(|radius| radius * radius * (1.0 - PI / 4.0)).sum();
+        self.width.max(0.0) * self.height.max(0.0) - corners
+    }
+
+    /// Calculates the perimeter of the rounded rectangle.
+    ///
+    /// # Returns
+    ///
+    /// The total length of the straight sides and rounded corners.
+    fn perimeter(&self) -> f32 {
+        let corners: f32 = self.clamped_radii().iter().map(|radius| radius * (2.0 - PI / 2.0)).sum();
+        2.0 * (self.width.max(0.0) + self.height.max(0.0)) - corners
+    }
+
+    /// Describes the filled area of the rounded rectangle by its edges.
+    ///
+    /// # Returns
+    ///
+    /// The outline of `to_path`, with the corners flattened to within `tolerance`.
+    fn fill_outline(&self, tolerance: f32) -> Option<FillOutline> {
+        Some(FillOutline::from_polylines(self.to_path().flatten(tolerance)))
+    }
+}
+
+/// Implements the `Shape` trait for a `Star`.
+impl Shape for Star {
+    /// Determines if a point is contained within the star.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the point is inside or on the boundary of the star, `false` otherwise.
+    fn contains(&self, point: Point) -> bool {
+        self.distance(point) <= 0.0
+    }
+
+    /// Calculates the bounding box of the star's tips and notches.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two `Point`s representing the top-left and bottom-right
+    /// corners of the bounding box.
+    fn bounding_box(&self) -> (Point, Point) {
+        vertex_bounds(&self.vertices())
+    }
+
+    /// Calculates the exact distance from a point to the star's boundary.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to calculate the distance from.
+    ///
+    /// # Returns
+    ///
+    /// The shortest distance from the point to the star's boundary.
+    /// Negative if the point is inside the star.
+    fn distance(&self, point: Point) -> f32 {
+        if self.points < 2 {
+            return f32::INFINITY;
+        }
+        star_distance(point - self.center, self.points, self.inner_radius, self.outer_radius, self.rotation)
+    }
+
+    /// Calculates the area of the star.
+    ///
+    /// # Returns
+    ///
+    /// The area enclosed by the star's outline, or zero for stars with fewer than two tips.
+    fn area(&self) -> f32 {
+        if self.points < 2 {
+            return 0.0;
+        }
+        // Made of two triangles between the center, a tip and its neighbouring notches per tip
+        self.points as f32 * self.outer_radius * self.inner_radius * (PI / self.points as f32).sin()
+    }
+
+    /// Calculates the perimeter of the star.
+    ///
+    /// # Returns
+    ///
+    /// The total length of the star's edges, or zero for stars with fewer than two tips.
+    fn perimeter(&self) -> f32 {
+        match self.vertices().as_slice() {
+            [tip, notch, ..] if self.points >= 2 => 2.0 * self.points as f32 * tip.distance(notch),
+            _ => 0.0,
+        }
+    }
+
+    /// Describes the filled area of the star by its edges.
+    ///
+    /// # Returns
+    ///
+    /// The star's tips and notches.
+    fn fill_outline(&self, _tolerance: f32) -> Option<FillOutline> {
+        Some(FillOutline::polygon(self.vertices()))
+    }
+}
+
+/// Implements the `Shape` trait for a `RegularPolygon`.
+impl Shape for RegularPolygon {
+    /// Determines if a point is contained within the polygon.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the point is inside or on the boundary of the polygon, `false` otherwise.
+    fn contains(&self, point: Point) -> bool {
+        self.distance(point) <= 0.0
+    }
+
+    /// Calculates the bounding box of the polygon's vertices.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two `Point`s representing the top-left and bottom-right
+    /// corners of the bounding box.
+    fn bounding_box(&self) -> (Point, Point) {
+        vertex_bounds(&self.vertices())
+    }
+
+    /// Calculates the exact distance from a point to the polygon's sides.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to calculate the distance from.
+    ///
+    /// # Returns
+    ///
+    /// The shortest distance from the point to the polygon's boundary.
+    /// Negative if the point is inside the polygon.
+    fn distance(&self, point: Point) -> f32 {
+        if self.sides < 3 {
+            return f32::INFINITY;
+        }
+        star_distance(point - self.center, self.sides, self.inner_radius(), self.radius, self.rotation)
+    }
+
+    /// Calculates the area of the polygon.
+    ///
+    /// # Returns
+    ///
+    /// The area enclosed by the polygon, or zero for polygons with fewer than three sides.
+    fn area(&self) -> f32 {
+        if self.sides < 3 {
+            return 0.0;
+        }
+        self.sides as f32 / 2.0 * self.radius * self.radius * (TAU / self.sides as f32).sin()
+    }
+
+    /// Calculates the perimeter of the polygon.
+    ///
+    /// # Returns
+    ///
+    /// The total length of the polygon's sides, or zero for polygons with fewer than three sides.
+    fn perimeter(&self) -> f32 {
+        if self.sides < 3 {
+            return 0.0;
+        }
+        2.0 * self.sides as f32 * self.radius.abs() * (PI / self.sides as f32).sin()
+    }
+
+    /// Describes the filled area of the polygon by its edges.
+    ///
+    /// # Returns
+    ///
+    /// The polygon's vertices.
+    fn fill_outline(&self, _tolerance: f32) -> Option<FillOutline> {
+        Some(FillOutline::polygon(self.vertices()))
+    }
+}
+
+/// Implements the `Shape` trait for a `Triangle`.
+impl Shape for Triangle {
+    /// Determines if a point is contained within the triangle.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the point is inside or on the boundary of the triangle, `false` otherwise.
+    fn contains(&self, point: Point) -> bool {
+        // Inside means no edge turns the opposite way of another when heading to the point
+        let turns = [
+            geom::orientation(&self.a, &self.b, &point),
+            geom::orientation(&self.b, &self.c, &point),
+            geom::orientation(&self.c, &self.a, &point),
+        ];
+        !(turns.contains(&Some(Winding::Clockwise)) && turns.contains(&Some(Winding::CounterClockwise)))
+    }
+
+    /// Calculates the bounding box of the triangle.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two `Point`s representing the top-left and bottom-right
+    /// corners of the bounding box.
+    fn bounding_box(&self) -> (Point, Point) {
+        (
+            Point::new(self.a.x.min(self.b.x).min(self.c.x), self.a.y.min(self.b.y).min(self.c.y)),
+            Point::new(self.a.x.max(self.b.x).max(self.c.x), self.a.y.max(self.b.y).max(self.c.y)),
+        )
+    }
+
+    /// Calculates the exact distance from a point to the triangle's edges.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to calculate the distance from.
+    ///
+    /// # Returns
+    ///
+    /// The shortest distance from the point to the triangle's boundary.
+    /// Negative if the point is inside the triangle.
+    fn distance(&self, point: Point) -> f32 {
+        let distance = [Line::new(self.a, self.b), Line::new(self.b, self.c), Line::new(self.c, self.a)]
+            .iter()
+            .map(|edge| point.distance(&edge.closest_point(&point)))
+            .fold(f32::INFINITY, f32::min);
+        if self.contains(point) { -distance } else { distance }
+    }
+
+    /// Calculates the area of the triangle.
+    ///
+    /// # Returns
+    ///
+    /// The area enclosed by the triangle.
+    fn area(&self) -> f32 {
+        let (ab, ac) = (self.b - self.a, self.c - self.a);
+        (ab.x * ac.y - ab.y * ac.x).abs() / 2.0
+    }
+
+    /// Calculates the perimeter of the triangle.
+    ///
+    /// # Returns
+    ///
+    /// The total length of the triangle's three sides.
+    fn perimeter(&self) -> f32 {
+        self.a.distance(&self.b) + self.b.distance(&self.c) + self.c.distance(&self.a)
+    }
+
+    /// Describes the filled area of the triangle by its edges.
+    ///
+    /// # Returns
+    ///
+    /// The triangle's three corners.
+    fn fill_outline(&self, _tolerance: f32) -> Option<FillOutline> {
+        Some(FillOutline::polygon(vec![self.a, self.b, self.c]))
+    }
+}
+
+/// Implements the `Shape` trait for a `Capsule`.
+impl Shape for Capsule {
+    /// Determines if a point is contained within the capsule.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the point is within `radius` of the segment, `false` otherwise.
+    fn contains(&self, point: Point) -> bool {
+        self.axis_distance(point) <= self.radius
+    }
+
+    /// Calculates the bounding box of the capsule.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two `Point`s representing the top-left and bottom-right
+    /// corners of the bounding box.
+    fn bounding_box(&self) -> (Point, Point) {
+        let (min, max) = vertex_bounds(&[self.start, self.end]);
+        let radius = Point::new(self.radius, self.radius).abs();
+        (min - radius, max + radius)
+    }
+
+    /// Calculates the exact distance from a point to the capsule's outline.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to calculate the distance from.
+    ///
+    /// # Returns
+    ///
+    /// The distance from the point to the segment, less the radius.
+    /// Negative if the point is inside the capsule.
+    fn distance(&self, point: Point) -> f32 {
+        self.axis_distance(point) - self.radius
+    }
+
+    /// Calculates the area of the capsule.
+    ///
+    /// # Returns
+    ///
+    /// The area of the rectangle between the caps plus that of the two half circles.
+    fn area(&self) -> f32 {
+        let radius = self.radius.abs();
+        2.0 * radius * self.start.distance(&self.end) + PI * radius * radius
+    }
+
+    /// Calculates the perimeter of the capsule.
+    ///
+    /// # Returns
+    ///
+    /// The length of the two straight sides plus the circumference of the caps.
+    fn perimeter(&self) -> f32 {
+        2.0 * self.start.distance(&self.end) + TAU * self.radius.abs()
+    }
+
+    /// Describes the filled area of the capsule by its edges.
+    ///
+    /// # Returns
+    ///
+    /// The outline of `to_path`, with the caps flattened to within `tolerance`.
+    fn fill_outline(&self, tolerance: f32) -> Option<FillOutline> {
+        Some(FillOutline::from_polylines(self.to_path().flatten(tolerance)))
+    }
+}
+
+/// Implements the `Shape` trait for an `Arc`.
+impl Shape for Arc {
+    /// Determines if a point is contained within the filled area of the arc.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the point is inside or on the boundary of the wedge for pie arcs, or of the
+    /// area cut off by the chord otherwise, `false` otherwise.
+    fn contains(&self, point: Point) -> bool {
+        let sweep = self.sweep();
+        if sweep == 0.0 || self.center.distance(&point) > self.radius {
+            return false;
+        }
+        if sweep >= TAU {
+            return true;
+        }
+        match self.mode {
+            ArcMode::Pie => {
+                let offset = point - self.center;
+                offset == Point::new(0.0, 0.0) || self.covers_angle(offset.y.atan2(offset.x))
+            }
+            ArcMode::Open | ArcMode::Chord => {
+                // Inside the circle, the arc's side of the chord is the side its midpoint lies on
+                let start = self.point_at_angle(self.start);
+                let end = self.point_at_angle(self.start + sweep);
+                let middle = self.point_at_angle(self.start + sweep / 2.0);
+                let side = geom::orientation(&start, &end, &point);
+                side.is_none() || side == geom::orientation(&start, &end, &middle)
+            }
+        }
+    }
+
+    /// Calculates the bounding box of the arc, including the center for pie arcs.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two `Point`s representing the top-left and bottom-right
+    /// corners of the bounding box.
+    fn bounding_box(&self) -> (Point, Point) {
+        let sweep = self.sweep();
+        let mut points = vec![self.point_at_angle(self.start), self.point_at_angle(self.start + sweep)];
+        // The circle reaches its extremes in x and y at multiples of a quarter turn
+        let first_quarter = (self.start / FRAC_PI_2).ceil() as i32;
+        let last_quarter = ((self.start + sweep) / FRAC_PI_2).floor() as i32;
+        points.extend((first_quarter..=last_quarter.min(first_quarter.saturating_add(3))).map(|quarter| self.point_at_angle(quarter as f32 * FRAC_PI_2)));
+        if self.mode == ArcMode::Pie {
+            points.push(self.center);
+        }
+        vertex_bounds(&points)
+    }
+
+    /// Calculates the exact distance from a point to the stroked outline of the arc.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to calculate the distance from.
+    ///
+    /// # Returns
+    ///
+    /// The shortest distance from the point to the curve, plus the chord or the two radii
+    /// for closed arcs. Negative if the point is inside the filled area, even for open arcs
+    /// whose chord is not stroked.
+    fn distance(&self, point: Point) -> f32 {
+        let sweep = self.sweep();
+        let curve = self.curve_distance(point);
+        let start = self.point_at_angle(self.start);
+        let end = self.point_at_angle(self.start + sweep);
+        let distance = match self.mode {
+            _ if sweep == 0.0 => return f32::INFINITY,
+            _ if sweep >= TAU => curve,
+            ArcMode::Open => curve,
+            ArcMode::Chord => curve.min(point.distance(&Line::new(start, end).closest_point(&point))),
+            ArcMode::Pie => {
+                let radii = [Line::new(self.center, start), Line::new(self.center, end)];
+                radii.iter().map(|radius| point.distance(&radius.closest_point(&point))).fold(curve, f32::min)
+            }
+        };
+        if self.contains(point) { -distance } else { distance }
+    }
+
+    /// Calculates the area filled by the arc.
+    ///
+    /// # Returns
+    ///
+    /// The area of the wedge for pie arcs, and of the part cut off by the chord otherwise.
+    fn area(&self) -> f32 {
+        let sweep = self.sweep();
+        let radius_squared = self.radius * self.radius;
+        match self.mode {
+            ArcMode::Pie => radius_squared * sweep / 2.0,
+            ArcMode::Open | ArcMode::Chord => radius_squared * (sweep - sweep.sin()) / 2.0,
+        }
+    }
+
+    /// Calculates the length of the stroked outline of the arc.
+    ///
+    /// # Returns
+    ///
+    /// The length of the curve, plus the chord or the two radii for closed arcs that are not whole circles.
+    fn perimeter(&self) -> f32 {
+        let sweep = self.sweep();
+        let curve = self.radius * sweep;
+        match self.mode {
+            _ if sweep == 0.0 || sweep >= TAU => curve,
+            ArcMode::Open => curve,
+            ArcMode::Chord => curve + 2.0 * self.radius * (sweep / 2.0).sin(),
+            ArcMode::Pie => curve + 2.0 * self.radius,
+        }
+    }
+
+    /// Describes the filled area of the arc by its edges.
+    ///
+    /// # Returns
+    ///
+    /// The outline of `to_path` flattened to within `tolerance`, closed by the chord for open arcs.
+    fn fill_outline(&self, tolerance: f32) -> Option<FillOutline> {
+        Some(FillOutline::from_polylines(self.to_path().flatten(tolerance)))
+    }
+
+    /// Describes the stroked boundary of the arc.
+    ///
+    /// # Returns
+    ///
+    /// `to_path` flattened to within `tolerance`, left open for open arcs.
+    fn stroke_boundary(&self, tolerance: f32) -> Option<Vec<Polyline>> {
+        Some(self.to_path().flatten(tolerance))
+    }
+}
+
+/// Implements the `Shape` trait for any `Shape` seen through a transform.
+impl<S: Shape> Shape for Transformed<S> {
+    /// Determines if a point is contained within the transformed shape.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the point maps to a point inside the shape, `false` otherwise or if the
+    /// transform collapses the shape.
+    fn contains(&self, point: Point) -> bool {
+        match self.transform.inverse() {
+            Some(inverse) => self.shape.contains(inverse.transform_point(point)),
+            None => false,
+        }
+    }
+
+    /// Calculates the bounding box of the transformed shape's bounding box.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two `Point`s representing the top-left and bottom-right
+    /// corners of the bounding box. It may be larger than needed under rotation.
+    fn bounding_box(&self) -> (Point, Point) {
+        let (min, max) = self.shape.bounding_box();
+        let corners = [min, Point::new(max.x, min.y), max, Point::new(min.x, max.y)];
+        vertex_bounds(&corners.map(|corner| self.transform.transform_point(corner)))
+    }
+
+    /// Calculates the distance from a point to the transformed shape's boundary.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to calculate the distance from.
+    ///
+    /// # Returns
+    ///
+    /// The distance from the point to the boundary, negative inside if the shape's own
+    /// distance is. Infinite if the transform collapses the shape.
+    fn distance(&self, point: Point) -> f32 {
+        let Some(inverse) = self.transform.inverse() else {
+            return f32::INFINITY;
+        };
+        let local = |point: Point| self.shape.distance(inverse.transform_point(point));
+        let distance = local(point);
+
+        let t = &self.transform;
+        let scale = t.determinant().abs().sqrt();
+        if (t.m11 - t.m22).abs() <= f32::EPSILON * scale && (t.m12 + t.m21).abs() <= f32::EPSILON * scale {
+            // Rotations and uniform scales scale every distance equally
+            return distance * scale;
+        }
+
+        // Divide by the gradient of the mapped distance, which is 1 for an exact distance field
+        let step = TRANSFORMED_GRADIENT_STEP;
+        let gradient = Point::new(
+            local(point + Point::new(step, 0.0)) - distance,
+            local(point + Point::new(0.0, step)) - distance,
+        ) * (1.0 / step);
+        let slope = gradient.length();
+        if slope > 0.0 && slope.is_finite() { distance / slope } else { distance * scale }
+    }
+
+    /// Calculates the area of the transformed shape.
+    ///
+    /// # Returns
+    ///
+    /// The shape's own area, scaled by how much the transform scales areas.
+    fn area(&self) -> f32 {
+        self.shape.area() * self.transform.determinant().abs()
+    }
+
+    /// Approximates the perimeter of the transformed shape.
+    ///
+    /// # Returns
+    ///
+    /// The shape's own perimeter, scaled by the average scale of the transform. Exact for
+    /// rotations, translations and uniform scales.
+    fn perimeter(&self) -> f32 {
+        self.shape.perimeter() * self.transform.determinant().abs().sqrt()
+    }
+
+    /// Describes the filled area of the transformed shape by its edges.
+    ///
+    /// # Returns
+    ///
+    /// The shape's own outline mapped through the transform, flattened finely enough to stay
+    /// within `tolerance` however much the transform stretches it, or None if the shape has none.
+    fn fill_outline(&self, tolerance: f32) -> Option<FillOutline> {
+        let t = &self.transform;
+        // The transform stretches no direction by more than the root of its squared entries
+        let stretch = (t.m11 * t.m11 + t.m12 * t.m12 + t.m21 * t.m21 + t.m22 * t.m22).sqrt();
+        let mut outline = self.shape.fill_outline(tolerance / stretch.max(f32::EPSILON))?;
+        for point in outline.contours.iter_mut().flatten() {
+            *point = t.transform_point(*point);
+        }
+        Some(outline)
+    }
+
+    /// Describes the stroked boundary of the transformed shape.
+    ///
+    /// # Returns
+    ///
+    /// The shape's own stroked boundary mapped through the transform and flattened like
+    /// `fill_outline`, or None if the shape has none or the transform collapses it.
+    fn stroke_boundary(&self, tolerance: f32) -> Option<Vec<Polyline>> {
+        let t = &self.transform;
+        t.inverse()?;
+        let stretch = (t.m11 * t.m11 + t.m12 * t.m12 + t.m21 * t.m21 + t.m22 * t.m22).sqrt();
+        let mut polylines = self.shape.stroke_boundary(tolerance / stretch.max(f32::EPSILON))?;
+        for point in polylines.iter_mut().flat_map(|polyline| &mut polyline.points) {
+            *point = t.transform_point(*point);
+        }
+        Some(polylines)
+    }
+}
+
+/// Implements the `Shape` trait for a `ShapeGroup` as the union of its children.
+impl<S: Shape> Shape for ShapeGroup<S> {
+    /// Determines if a point is contained within any of the group's children.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if any child contains the point, `false` otherwise or if the group is empty.
+    fn contains(&self, point: Point) -> bool {
+        self.children.iter().any(|child| child.contains(point))
+    }
 
-    /// Calculates the bounding box of the shape.
+    /// Calculates the bounding box enclosing all of the group's children.
     ///
     /// # Returns
     ///
     /// A tuple of two `Point`s representing the top-left and bottom-right
-    /// corners of the bounding box that fully encloses the shape.
-    fn bounding_box(&self) -> (Point, Point);
+    /// corners of the bounding box. An empty group has an empty box, with its
+    /// top-left corner at positive infinity.
+    fn bounding_box(&self) -> (Point, Point) {
+        let corners: Vec<Point> = self
+            .children
+            .iter()
+            .flat_map(|child| {
+                let (min, max) = child.bounding_box();
+                [min, max]
+            })
+            .collect();
+        vertex_bounds(&corners)
+    }
 
-    /// Calculates the shortest distance from a point to the shape's boundary.
+    /// Calculates the signed distance from a point to the boundary of the union.
     ///
     /// # Arguments
     ///
@@ -29,48 +1883,242 @@ pub trait Shape {
     ///
     /// # Returns
     ///
-    /// The shortest distance from the point to the shape's boundary.
-    /// May be negative if the point is inside the shape, depending on the implementation.
-    fn distance(&self, point: Point) -> f32;
-}
+    /// The smallest of the children's distances, which is exact outside the group. Inside,
+    /// where children overlap, it may underestimate how far the point is from the boundary.
+    /// Infinite for an empty group.
+    fn distance(&self, point: Point) -> f32 {
+        self.children.iter().map(|child| child.distance(point)).fold(f32::INFINITY, f32::min)
+    }
 
-/// Represents an ellipse in 2D space.
-pub struct Ellipse {
-    /// The center point of the ellipse.
-    pub center: Point,
-    /// The width of the ellipse (diameter along the x-axis).
-    pub width: f32,
-    /// The height of the ellipse (diameter along the y-axis).
-    pub height: f32,
+    /// Calculates the area of the group.
+    ///
+    /// # Returns
+    ///
+    /// The sum of the children's areas, which counts overlapping parts more than once.
+    fn area(&self) -> f32 {
+        self.children.iter().map(|child| child.area()).sum()
+    }
+
+    /// Calculates the perimeter of the group.
+    ///
+    /// # Returns
+    ///
+    /// The sum of the children's perimeters, which includes boundaries hidden by overlaps.
+    fn perimeter(&self) -> f32 {
+        self.children.iter().map(|child| child.perimeter()).sum()
+    }
 }
 
-/// Represents a rectangle in 2D space.
-pub struct Rectangle {
-    /// The top-left corner of the rectangle.
-    pub top_left: Point,
-    /// The width of the rectangle.
-    pub width: f32,
-    /// The height of the rectangle.
-    pub height: f32,
+/// Implements the `Shape` trait for a `Boolean` by combining its operands.
+impl<A: Shape, B: Shape> Shape for Boolean<A, B> {
+    /// Determines if a point is contained within the combined shape.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the operands' containment of the point satisfies the operation, `false` otherwise.
+    fn contains(&self, point: Point) -> bool {
+        match self.op {
+            BooleanOp::Union => self.a.contains(point) || self.b.contains(point),
+            BooleanOp::Intersection => self.a.contains(point) && self.b.contains(point),
+            BooleanOp::Difference => self.a.contains(point) && !self.b.contains(point),
+        }
+    }
+
+    /// Calculates the bounding box of the combined shape from the operands' boxes.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two `Point`s representing the top-left and bottom-right corners of the
+    /// bounding box. A difference keeps the first operand's box, and an intersection of
+    /// disjoint boxes is inverted (empty).
+    fn bounding_box(&self) -> (Point, Point) {
+        let (a_min, a_max) = self.a.bounding_box();
+        let (b_min, b_max) = self.b.bounding_box();
+        match self.op {
+            BooleanOp::Union => vertex_bounds(&[a_min, a_max, b_min, b_max]),
+            BooleanOp::Intersection => (
+                Point::new(a_min.x.max(b_min.x), a_min.y.max(b_min.y)),
+                Point::new(a_max.x.min(b_max.x), a_max.y.min(b_max.y)),
+            ),
+            BooleanOp::Difference => (a_min, a_max),
+        }
+    }
+
+    /// Calculates the signed distance from a point to the combined shape's boundary.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to calculate the distance from.
+    ///
+    /// # Returns
+    ///
+    /// The minimum of the operands' distances for a union and the maximum for an intersection,
+    /// with the second operand's distance negated for a difference. Close to the boundary this is
+    /// exact, further away it may underestimate the true distance.
+    fn distance(&self, point: Point) -> f32 {
+        let (a, b) = (self.a.distance(point), self.b.distance(point));
+        match self.op {
+            BooleanOp::Union => a.min(b),
+            BooleanOp::Intersection => a.max(b),
+            BooleanOp::Difference => a.max(-b),
+        }
+    }
+
+    /// Estimates the area of the combined shape by sampling it on a grid over its bounding box.
+    ///
+    /// # Returns
+    ///
+    /// The approximate area covered when the shape is filled.
+    fn area(&self) -> f32 {
+        let mut inside = 0;
+        let cell_area = self.sample_distances(0.0, |distance| inside += (distance <= 0.0) as usize);
+        inside as f32 * cell_area
+    }
+
+    /// Estimates the perimeter of the combined shape from the area of a thin band around its boundary.
+    ///
+    /// # Returns
+    ///
+    /// The approximate length of the combined shape's boundary, including the edges of holes.
+    fn perimeter(&self) -> f32 {
+        let (min, max) = self.bounding_box();
+        let cell = (max.x - min.x).max(max.y - min.y) / BOOLEAN_SAMPLES as f32;
+        // The band must be several samples wide to be measured well
+        let half_width = 4.0 * cell;
+        let mut near = 0;
+        let cell_area = self.sample_distances(half_width, |distance| near += (distance.abs() < half_width) as usize);
+        near as f32 * cell_area / (2.0 * half_width)
+    }
 }
 
+/// Implements the `Shape` trait for `AnyShape` by forwarding to the shape it holds.
+impl Shape for AnyShape {
+    fn contains(&self, point: Point) -> bool {
+        self.as_shape().contains(point)
+    }
 
-pub struct Polygon {
+    fn bounding_box(&self) -> (Point, Point) {
+        self.as_shape().bounding_box()
+    }
+
+    fn distance(&self, point: Point) -> f32 {
+        self.as_shape().distance(point)
+    }
+
+    fn area(&self) -> f32 {
+        self.as_shape().area()
+    }
+
+    fn perimeter(&self) -> f32 {
+        self.as_shape().perimeter()
+    }
+
+    fn fill_outline(&self, tolerance: f32) -> Option<FillOutline> {
+        self.as_shape().fill_outline(tolerance)
+    }
 
+    fn stroke_boundary(&self, tolerance: f32) -> Option<Vec<Polyline>> {
+        self.as_shape().stroke_boundary(tolerance)
+    }
 }
 
-impl Polygon {
-    #[allow(dead_code)]
-    pub fn vertex() {
+/// Implements the `Shape` trait for a `Polygon` by delegating to its geometry.
+impl Shape for Polygon {
+    /// Determines if a point is contained within the polygon, using the even-odd rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the point is inside the polygon, `false` otherwise.
+    fn contains(&self, point: Point) -> bool {
+        self.geometry.contains_point(&point)
+    }
+
+    /// Calculates the bounding box of the polygon.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of two `Point`s representing the top-left and bottom-right
+    /// corners of the bounding box. An empty polygon has an inverted (empty) box.
+    fn bounding_box(&self) -> (Point, Point) {
+        match self.geometry.bounding_box() {
+            Some(rect) => (rect.min, rect.max),
+            None => (
+                Point::new(f32::INFINITY, f32::INFINITY),
+                Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+            ),
+        }
+    }
+
+    /// Calculates the shortest distance from a point to the polygon's edges.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - The point to calculate the distance from.
+    ///
+    /// # Returns
+    ///
+    /// The shortest distance from the point to the polygon's boundary, leaving out the closing
+    /// edge of open polygons. Negative if the point is inside the polygon.
+    fn distance(&self, point: Point) -> f32 {
+        let distance = match self.geometry.vertices.as_slice() {
+            [vertex] => vertex.distance(&point),
+            _ => self
+                .boundary()
+                .map(|edge| point.distance(&edge.closest_point(&point)))
+                .fold(f32::INFINITY, f32::min),
+        };
+        if self.contains(point) { -distance } else { distance }
+    }
+
+    /// Calculates the area of the polygon using the shoelace formula.
+    ///
+    /// # Returns
+    ///
+    /// The area enclosed by the polygon. Self-intersecting polygons count overlapping parts
+    /// with opposite windings against each other.
+    fn area(&self) -> f32 {
+        self.geometry.area()
+    }
+
+    /// Calculates the perimeter of the polygon.
+    ///
+    /// # Returns
+    ///
+    /// The total length of the polygon's edges, including the closing edge only if the polygon is closed.
+    fn perimeter(&self) -> f32 {
+        self.boundary().map(|edge| edge.length()).sum()
+    }
+
+    /// Describes the filled area of the polygon by its edges.
+    ///
+    /// # Returns
+    ///
+    /// The polygon's vertices, filled with the even-odd rule like `contains`.
+    fn fill_outline(&self, _tolerance: f32) -> Option<FillOutline> {
+        Some(FillOutline { contours: vec![self.geometry.vertices.clone()], rule: FillRule::EvenOdd })
+    }
 
+    /// Describes the stroked boundary of the polygon.
+    ///
+    /// # Returns
+    ///
+    /// The polygon's vertices, closed only if the polygon is.
+    fn stroke_boundary(&self, _tolerance: f32) -> Option<Vec<Polyline>> {
+        Some(vec![Polyline { points: self.geometry.vertices.clone(), closed: self.closed }])
     }
 }
 
-/// Implements the `Shape` trait for an `Ellipse`.
-///
-/// An `Ellipse` is defined by its center point, width, and height.
-impl Shape for Ellipse {
-    /// Determines if a point is contained within the ellipse.
+/// Implements the `Shape` trait for an `Outline`.
+impl Shape for Outline {
+    /// Determines if a point is inside the outline using the non-zero winding rule.
     ///
     /// # Arguments
     ///
@@ -78,30 +2126,44 @@ impl Shape for Ellipse {
     ///
     /// # Returns
     ///
-    /// `true` if the point is inside or on the ellipse, `false` otherwise.
+    /// `true` if the winding number of the outline around the point is non-zero, `false` otherwise.
     fn contains(&self, point: Point) -> bool {
-        // Normalize the point relative to the ellipse's center and dimensions
-        let dx = (point.x - self.center.x) / (self.width / 2.0);
-        let dy = (point.y - self.center.y) / (self.height / 2.0);
-        
-        // Check if the normalized point satisfies the ellipse equation
-        dx * dx + dy * dy <= 1.0
+        let mut winding = 0;
+        for polyline in &self.polylines {
+            let points = &polyline.points;
+            for (i, &a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                // Count signed crossings of a ray cast towards +x
+                let side = geom::orientation(&a, &b, &point);
+                if a.y <= point.y {
+                    if b.y > point.y && side == Some(Winding::Clockwise) {
+                        winding += 1;
+                    }
+                } else if b.y <= point.y && side == Some(Winding::CounterClockwise) {
+                    winding -= 1;
+                }
+            }
+        }
+        winding != 0
     }
 
-    /// Calculates the bounding box of the ellipse.
+    /// Calculates the bounding box of all polylines.
     ///
     /// # Returns
     ///
     /// A tuple of two `Point`s representing the top-left and bottom-right
     /// corners of the bounding box.
     fn bounding_box(&self) -> (Point, Point) {
-        (
-            Point::new(self.center.x - self.width / 2.0, self.center.y - self.height / 2.0),
-            Point::new(self.center.x + self.width / 2.0, self.center.y + self.height / 2.0),
-        )
+        let mut min = Point::new(f32::INFINITY, f32::INFINITY);
+        let mut max = Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for point in self.polylines.iter().flat_map(|polyline| &polyline.points) {
+            min = Point::new(min.x.min(point.x), min.y.min(point.y));
+            max = Point::new(max.x.max(point.x), max.y.max(point.y));
+        }
+        (min, max)
     }
 
-    /// Calculates the shortest distance from a point to the ellipse's boundary.
+    /// Calculates the shortest distance from a point to the outline's edges.
     ///
     /// # Arguments
     ///
@@ -109,22 +2171,78 @@ impl Shape for Ellipse {
     ///
     /// # Returns
     ///
-    /// The shortest distance from the point to the ellipse's boundary.
-    /// Negative if the point is inside the ellipse.
+    /// The shortest distance from the point to any edge.
+    /// Negative if the point is inside the outline.
     fn distance(&self, point: Point) -> f32 {
-        // Normalize the point relative to the ellipse's center and dimensions
-        let dx = (point.x - self.center.x) / (self.width / 2.0);
-        let dy = (point.y - self.center.y) / (self.height / 2.0);
-        
-        // Calculate the distance using the ellipse's equation
-        let distance_squared = dx * dx + dy * dy;
-        (distance_squared.sqrt() - 1.0) * (self.width.min(self.height) / 2.0)
+        let mut distance = f32::INFINITY;
+        for polyline in &self.polylines {
+            let points = &polyline.points;
+            let edge_count = if polyline.closed { points.len() } else { points.len().saturating_sub(1) };
+            for i in 0..edge_count {
+                let edge = Line::new(points[i], points[(i + 1) % points.len()]);
+                distance = distance.min(point.distance(&edge.closest_point(&point)));
+            }
+        }
+        if self.contains(point) { -distance } else { distance }
+    }
+
+    /// Calculates the area of the outline using the shoelace formula.
+    ///
+    /// # Returns
+    ///
+    /// The area enclosed by the polylines, with polylines winding in opposite directions
+    /// subtracting from each other, as holes do. Overlapping polylines winding the same
+    /// way are counted more than once.
+    fn area(&self) -> f32 {
+        let signed_area: f32 = self
+            .polylines
+            .iter()
+            .map(|polyline| geom::Polygon::new(polyline.points.clone()).signed_area())
+            .sum();
+        signed_area.abs()
+    }
+
+    /// Calculates the total length of the outline's edges.
+    ///
+    /// # Returns
+    ///
+    /// The length of all polylines, including the closing edge of closed polylines only.
+    fn perimeter(&self) -> f32 {
+        self.polylines
+            .iter()
+            .map(|polyline| {
+                let points = &polyline.points;
+                let closing = match (polyline.closed, points.first(), points.last()) {
+                    (true, Some(first), Some(last)) => last.distance(first),
+                    _ => 0.0,
+                };
+                points.windows(2).map(|pair| pair[0].distance(&pair[1])).sum::<f32>() + closing
+            })
+            .sum()
+    }
+
+    /// Describes the filled area of the outline by its edges.
+    ///
+    /// # Returns
+    ///
+    /// Every polyline as a closed contour, filled with the non-zero rule like `contains`.
+    fn fill_outline(&self, _tolerance: f32) -> Option<FillOutline> {
+        Some(FillOutline::from_polylines(self.polylines.clone()))
+    }
+
+    /// Describes the stroked boundary of the outline.
+    ///
+    /// # Returns
+    ///
+    /// The polylines, with the closing edge only of closed ones.
+    fn stroke_boundary(&self, _tolerance: f32) -> Option<Vec<Polyline>> {
+        Some(self.polylines.clone())
     }
 }
 
-/// Implements the `Shape` trait for a `Rectangle`.
-impl Shape for Rectangle {
-    /// Determines if a point is contained within the rectangle.
+/// Implements the `Shape` trait for an `OrientedRect`.
+impl Shape for OrientedRect {
+    /// Determines if a point is contained within the rotated rectangle.
     ///
     /// # Arguments
     ///
@@ -134,21 +2252,18 @@ impl Shape for Rectangle {
     ///
     /// `true` if the point is inside or on the boundary of the rectangle, `false` otherwise.
     fn contains(&self, point: Point) -> bool {
-        point.x >= self.top_left.x && point.x <= self.top_left.x + self.width &&
-        point.y >= self.top_left.y && point.y <= self.top_left.y + self.height
+        self.contains_point(&point)
     }
 
-    /// Calculates the bounding box of the rectangle.
+    /// Calculates the axis-aligned bounding box of the rotated rectangle.
     ///
     /// # Returns
     ///
     /// A tuple of two `Point`s representing the top-left and bottom-right
-    /// corners of the bounding box. For a rectangle, this is simply its own corners.
+    /// corners of the bounding box.
     fn bounding_box(&self) -> (Point, Point) {
-        (
-            self.top_left,
-            Point::new(self.top_left.x + self.width, self.top_left.y + self.height),
-        )
+        let rect = OrientedRect::bounding_box(self);
+        (rect.min, rect.max)
     }
 
     /// Calculates the shortest distance from a point to the rectangle's boundary.
@@ -160,14 +2275,70 @@ impl Shape for Rectangle {
     /// # Returns
     ///
     /// The shortest distance from the point to the rectangle's boundary.
-    /// Returns 0 if the point is inside the rectangle.
+    /// Negative if the point is inside the rectangle.
     fn distance(&self, point: Point) -> f32 {
-        // Calculate the closest point on the rectangle to the given point
-        let dx = (point.x - self.top_left.x).max(0.0).min(self.width);
-        let dy = (point.y - self.top_left.y).max(0.0).min(self.height);
-        let closest_point = Point::new(self.top_left.x + dx, self.top_left.y + dy);
+        self.signed_distance(&point)
+    }
+
+    /// Calculates the area of the rotated rectangle.
+    ///
+    /// # Returns
+    ///
+    /// The width times the height.
+    fn area(&self) -> f32 {
+        4.0 * (self.half_extents.x * self.half_extents.y).abs()
+    }
+
+    /// Calculates the perimeter of the rotated rectangle.
+    ///
+    /// # Returns
+    ///
+    /// The total length of the rectangle's four sides.
+    fn perimeter(&self) -> f32 {
+        4.0 * (self.half_extents.x.abs() + self.half_extents.y.abs())
+    }
+
+    /// Describes the filled area of the rotated rectangle by its edges.
+    ///
+    /// # Returns
+    ///
+    /// The rectangle's four corners.
+    fn fill_outline(&self, _tolerance: f32) -> Option<FillOutline> {
+        Some(FillOutline::polygon(self.corners().to_vec()))
+    }
+}
+
+// Bounding box of a non-empty list of vertices.
+fn vertex_bounds(vertices: &[Point]) -> (Point, Point) {
+    let mut min = Point::new(f32::INFINITY, f32::INFINITY);
+    let mut max = Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for vertex in vertices {
+        min = Point::new(min.x.min(vertex.x), min.y.min(vertex.y));
+        max = Point::new(max.x.max(vertex.x), max.y.max(vertex.y));
+    }
+    (min, max)
+}
 
-        // Calculate the distance between the given point and the closest point
-        point.distance(&closest_point)
+// Signed distance from `offset`, relative to the center, to the boundary of a star with its first
+// tip at angle `rotation`. A regular polygon is the star whose notches lie on its edges.
+fn star_distance(offset: Point, points: usize, inner_radius: f32, outer_radius: f32, rotation: f32) -> f32 {
+    // Fold the point into the half of a tip's sector between the tip and the following notch,
+    // where the nearest part of the boundary is the single edge between them
+    let half_sector = PI / points as f32;
+    let mut angle = (offset.y.atan2(offset.x) - rotation).rem_euclid(2.0 * half_sector);
+    if angle > half_sector {
+        angle = 2.0 * half_sector - angle;
     }
-}
\ No newline at end of file
+    let (sin, cos) = angle.sin_cos();
+    let local = Point::new(cos, sin) * offset.length();
+
+    let tip = Point::new(outer_radius, 0.0);
+    let notch = Point::new(half_sector.cos(), half_sector.sin()) * inner_radius;
+    let distance = local.distance(&Line::new(tip, notch).closest_point(&local));
+
+    // Inside is the side of the edge that the center lies on
+    let edge = notch - tip;
+    let side = edge.x * (local.y - tip.y) - edge.y * (local.x - tip.x);
+    let center_side = edge.x * -tip.y - edge.y * -tip.x;
+    if side * center_side >= 0.0 { -distance } else { distance }
+}
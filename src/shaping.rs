@@ -0,0 +1,1247 @@
+//! Text shaping: turning a line of text into positioned glyphs with the OpenType layout tables
+//! of a font, for scripts that can't be drawn one character at a time.
+//!
+//! The shaper covers what most text needs without an external shaping engine:
+//!
+//! - a simplified bidirectional algorithm, so that Arabic and Hebrew run right to left while
+//!   numbers and embedded left-to-right words keep their reading order
+//! - the joining forms of Arabic letters and the required ligatures of Arabic fonts
+//! - ligatures, contextual alternates and glyph composition from the `GSUB` table
+//! - kerning and the placement of combining marks on their base glyphs from the `GPOS` table
+//! - moving pre-base vowel signs and the reph of Devanagari, Bengali, Gurmukhi and Gujarati
+//!   syllables into place
+//!
+//! It is not a complete implementation of the OpenType shaping models: cursive attachment,
+//! reverse chaining substitutions and most of the Indic syllable rules are left out, and
+//! features are applied to whole runs rather than to the glyphs of particular syllables.
+
+use crate::font::{read_u16, read_u32, Font};
+
+/// Deepest nesting of contextual lookups that is followed, to stop malformed fonts from
+/// recursing forever.
+const MAX_NESTING: usize = 8;
+
+// Which glyphs a feature applies to. Every glyph has `GLOBAL`; Arabic letters also have the
+// bit of their joining form.
+const GLOBAL: u8 = 1;
+const ISOLATED: u8 = 2;
+const FINAL: u8 = 4;
+const MEDIAL: u8 = 8;
+const INITIAL: u8 = 16;
+
+// Lookup flags
+const IGNORE_BASE_GLYPHS: u16 = 0x0002;
+const IGNORE_LIGATURES: u16 = 0x0004;
+const IGNORE_MARKS: u16 = 0x0008;
+const USE_MARK_FILTERING_SET: u16 = 0x0010;
+
+// GDEF glyph classes
+const BASE_CLASS: u16 = 1;
+const LIGATURE_CLASS: u16 = 2;
+const MARK_CLASS: u16 = 3;
+
+/// A glyph placed by `Font::shape`, in font units with the y-axis pointing up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapedGlyph {
+    pub glyph: u16,
+    /// The byte offset in the text of the first character the glyph was made from.
+    pub cluster: usize,
+    /// Where to draw the glyph, relative to the start of the line's baseline.
+    pub x: f32,
+    pub y: f32,
+    /// How far the glyph moves the pen. Zero for marks placed on other glyphs.
+    pub advance: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Arabic,
+    Hebrew,
+    Devanagari,
+    Bengali,
+    Gurmukhi,
+    Gujarati,
+}
+
+impl Script {
+    // The OpenType script tags to look for, most preferred first
+    fn tags(self) -> &'static [&'static [u8; 4]] {
+        match self {
+            Script::Latin => &[b"latn"],
+            Script::Greek => &[b"grek"],
+            Script::Cyrillic => &[b"cyrl"],
+            Script::Arabic => &[b"arab"],
+            Script::Hebrew => &[b"hebr"],
+            Script::Devanagari => &[b"dev2", b"deva"],
+            Script::Bengali => &[b"bng2", b"beng"],
+            Script::Gurmukhi => &[b"gur2", b"guru"],
+            Script::Gujarati => &[b"gjr2", b"gujr"],
+        }
+    }
+
+    fn features(self) -> &'static [(&'static [u8; 4], u8)] {
+        match self {
+            Script::Arabic => &[
+                (b"ccmp", GLOBAL),
+                (b"isol", ISOLATED),
+                (b"fina", FINAL),
+                (b"medi", MEDIAL),
+                (b"init", INITIAL),
+                (b"rlig", GLOBAL),
+                (b"calt", GLOBAL),
+                (b"liga", GLOBAL),
+                (b"kern", GLOBAL),
+                (b"mark", GLOBAL),
+                (b"mkmk", GLOBAL),
+            ],
+            Script::Devanagari | Script::Bengali | Script::Gurmukhi | Script::Gujarati => &[
+                (b"ccmp", GLOBAL),
+                (b"nukt", GLOBAL),
+                (b"akhn", GLOBAL),
+                (b"rphf", GLOBAL),
+                (b"rkrf", GLOBAL),
+                (b"blwf", GLOBAL),
+                (b"half", GLOBAL),
+                (b"pstf", GLOBAL),
+                (b"vatu", GLOBAL),
+                (b"cjct", GLOBAL),
+                (b"pres", GLOBAL),
+                (b"abvs", GLOBAL),
+                (b"blws", GLOBAL),
+                (b"psts", GLOBAL),
+                (b"haln", GLOBAL),
+                (b"calt", GLOBAL),
+                (b"kern", GLOBAL),
+                (b"dist", GLOBAL),
+                (b"abvm", GLOBAL),
+                (b"blwm", GLOBAL),
+                (b"mark", GLOBAL),
+                (b"mkmk", GLOBAL),
+            ],
+            _ => &[
+                (b"ccmp", GLOBAL),
+                (b"rlig", GLOBAL),
+                (b"liga", GLOBAL),
+                (b"clig", GLOBAL),
+                (b"calt", GLOBAL),
+                (b"kern", GLOBAL),
+                (b"mark", GLOBAL),
+                (b"mkmk", GLOBAL),
+            ],
+        }
+    }
+
+    fn is_indic(self) -> bool {
+        matches!(self, Script::Devanagari | Script::Bengali | Script::Gurmukhi | Script::Gujarati)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GlyphInfo {
+    glyph: u16,
+    cluster: usize,
+    mask: u8,
+    /// Whether the glyph comes from a combining mark, for fonts without a GDEF table.
+    mark: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct GlyphPosition {
+    advance: i32,
+    x_offset: i32,
+    y_offset: i32,
+    /// The glyph this one is placed on, and its offset from that glyph's origin.
+    attached: Option<(usize, i32, i32)>,
+}
+
+/// Shapes a line of text. See `Font::shape`.
+pub(crate) fn shape(font: &Font, text: &str) -> Vec<ShapedGlyph> {
+    let chars: Vec<(usize, char)> = text.char_indices().filter(|&(_, c)| c != '\n').collect();
+    if chars.is_empty() {
+        return Vec::new();
+    }
+    let levels = bidi_levels(&chars);
+    let scripts = scripts(&chars);
+
+    // Runs of characters with the same direction and script, in logical order
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for i in 1..=chars.len() {
+        if i == chars.len() || levels[i] != levels[start] || scripts[i] != scripts[start] {
+            runs.push((start..i, levels[start], scripts[start]));
+            start = i;
+        }
+    }
+
+    // Reverse every sequence of runs at each level or above, from the highest level down
+    let mut order: Vec<usize> = (0..runs.len()).collect();
+    let highest = runs.iter().map(|(_, level, _)| *level).max().unwrap_or(0);
+    for level in (1..=highest).rev() {
+        let mut i = 0;
+        while i < order.len() {
+            if runs[order[i]].1 < level {
+                i += 1;
+                continue;
+            }
+            let end = (i..order.len()).find(|&j| runs[order[j]].1 < level).unwrap_or(order.len());
+            order[i..end].reverse();
+            i = end;
+        }
+    }
+
+    let (gdef, gsub, gpos) = font.layout_tables();
+    let gdef = Gdef::new(gdef);
+    let mut shaped = Vec::with_capacity(chars.len());
+    let mut pen = 0.0;
+    for index in order {
+        let (range, level, script) = runs[index].clone();
+        let rtl = level % 2 == 1;
+        let (infos, positions) = shape_run(font, &gdef, gsub, gpos, &chars[range], script, rtl);
+        pen += place_run(&infos, &positions, rtl, pen, &mut shaped);
+    }
+    shaped
+}
+
+// Substitutes and positions the glyphs of a run, in logical order.
+fn shape_run(font: &Font, gdef: &Gdef, gsub: &[u8], gpos: &[u8], chars: &[(usize, char)], script: Script, rtl: bool) -> (Vec<GlyphInfo>, Vec<GlyphPosition>) {
+    let mut chars = chars.to_vec();
+    if script.is_indic() {
+        reorder_indic(&mut chars, script);
+    }
+    let forms = if script == Script::Arabic { joining_forms(&chars) } else { vec![0; chars.len()] };
+    let mut infos: Vec<GlyphInfo> = chars
+        .iter()
+        .zip(forms)
+        .map(|(&(cluster, c), form)| {
+            let c = if rtl { mirrored(c) } else { c };
+            GlyphInfo { glyph: font.glyph_index(c).unwrap_or(0), cluster, mask: GLOBAL | form, mark: is_mark(c) }
+        })
+        .collect();
+
+    let gsub = Layout { data: gsub, gdef, positioning: false };
+    for (lookup, mask) in gsub.feature_lookups(script) {
+        if let Some(lookup) = gsub.lookup(lookup) {
+            let mut i = 0;
+            while i < infos.len() {
+                let applied = if infos[i].mask & mask != 0 && !gdef.skips(&lookup, &infos[i]) { gsub.substitute(&lookup, &mut infos, i, 0) } else { None };
+                i = applied.unwrap_or(i + 1);
+            }
+        }
+    }
+
+    let mut positions: Vec<GlyphPosition> =
+        infos.iter().map(|info| GlyphPosition { advance: font.advance_width(info.glyph) as i32, ..Default::default() }).collect();
+    let gpos = Layout { data: gpos, gdef, positioning: true };
+    for (lookup, mask) in gpos.feature_lookups(script) {
+        if let Some(lookup) = gpos.lookup(lookup) {
+            let mut i = 0;
+            while i < infos.len() {
+                let applied = if infos[i].mask & mask != 0 && !gdef.skips(&lookup, &infos[i]) { gpos.position(&lookup, &infos, &mut positions, i, 0) } else { None };
+                i = applied.unwrap_or(i + 1);
+            }
+        }
+    }
+
+    // Marks the font doesn't place are drawn where the pen stands after their base, which is
+    // how marks without an advance are designed to be drawn when placed glyph by glyph
+    for i in 0..infos.len() {
+        if gdef.is_mark(&infos[i]) && positions[i].attached.is_none() && positions[i].advance == 0 {
+            if let Some(base) = (0..i).rev().find(|&j| !gdef.is_mark(&infos[j])) {
+                positions[i].attached = Some((base, positions[base].advance, 0));
+            }
+        }
+    }
+    (infos, positions)
+}
+
+// Adds the glyphs of a run to `shaped` from left to right, starting at `pen`, and returns the
+// width of the run.
+fn place_run(infos: &[GlyphInfo], positions: &[GlyphPosition], rtl: bool, pen: f32, shaped: &mut Vec<ShapedGlyph>) -> f32 {
+    let count = infos.len();
+    let mut origins = vec![(0, 0); count];
+    let mut x = 0;
+    let visual: Vec<usize> = if rtl { (0..count).rev().collect() } else { (0..count).collect() };
+    for &i in &visual {
+        let position = positions[i];
+        if position.attached.is_none() {
+            origins[i] = (x + position.x_offset, position.y_offset);
+            x += position.advance;
+        }
+    }
+    // Bases come before their marks in logical order, so they are placed first
+    for (i, position) in positions.iter().enumerate() {
+        if let Some((base, dx, dy)) = position.attached.filter(|&(base, _, _)| base < i) {
+            origins[i] = (origins[base].0 + dx + position.x_offset, origins[base].1 + dy + position.y_offset);
+        }
+    }
+    for &i in &visual {
+        let advance = if positions[i].attached.is_some() { 0 } else { positions[i].advance };
+        shaped.push(ShapedGlyph {
+            glyph: infos[i].glyph,
+            cluster: infos[i].cluster,
+            x: pen + origins[i].0 as f32,
+            y: origins[i].1 as f32,
+            advance: advance as f32,
+        });
+    }
+    x as f32
+}
+
+// The glyph classes of the font's GDEF table, which tell bases, ligatures and marks apart.
+struct Gdef<'a> {
+    glyph_classes: Option<&'a [u8]>,
+    mark_attach_classes: Option<&'a [u8]>,
+    mark_glyph_sets: Option<&'a [u8]>,
+}
+
+impl<'a> Gdef<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        let version = read_u32(data, 0).unwrap_or(0);
+        Gdef {
+            glyph_classes: subtable(data, 4),
+            mark_attach_classes: subtable(data, 10),
+            mark_glyph_sets: if version >= 0x0001_0002 { subtable(data, 12) } else { None },
+        }
+    }
+
+    fn class(&self, glyph: u16) -> u16 {
+        self.glyph_classes.map_or(0, |classes| class_of(classes, glyph))
+    }
+
+    fn is_mark(&self, info: &GlyphInfo) -> bool {
+        match self.glyph_classes {
+            Some(classes) => class_of(classes, info.glyph) == MARK_CLASS,
+            None => info.mark,
+        }
+    }
+
+    // Whether a lookup passes over a glyph, as set by its flags
+    fn skips(&self, lookup: &Lookup, info: &GlyphInfo) -> bool {
+        let flag = lookup.flag;
+        match self.class(info.glyph) {
+            BASE_CLASS => flag & IGNORE_BASE_GLYPHS != 0,
+            LIGATURE_CLASS => flag & IGNORE_LIGATURES != 0,
+            MARK_CLASS => {
+                if flag & IGNORE_MARKS != 0 {
+                    return true;
+                }
+                if flag & USE_MARK_FILTERING_SET != 0 {
+                    let in_set = self
+                        .mark_glyph_sets
+                        .and_then(|sets| {
+                            let offset = read_u32(sets, 4 + lookup.mark_filtering_set as usize * 4)? as usize;
+                            coverage_index(sets.get(offset..)?, info.glyph)
+                        })
+                        .is_some();
+                    return !in_set;
+                }
+                let attach_type = flag >> 8;
+                attach_type != 0 && self.mark_attach_classes.map_or(0, |classes| class_of(classes, info.glyph)) != attach_type
+            }
+            _ => false,
+        }
+    }
+}
+
+struct Lookup<'a> {
+    kind: u16,
+    flag: u16,
+    mark_filtering_set: u16,
+    subtables: Vec<&'a [u8]>,
+}
+
+// A GSUB or GPOS table.
+struct Layout<'a> {
+    data: &'a [u8],
+    gdef: &'a Gdef<'a>,
+    positioning: bool,
+}
+
+impl<'a> Layout<'a> {
+    // The lookups of the features the script uses, in the order they are applied, with the
+    // joining forms each applies to
+    fn feature_lookups(&self, script: Script) -> Vec<(u16, u8)> {
+        let mut lookups: Vec<(u16, u8)> = Vec::new();
+        let Some(scripts) = subtable(self.data, 4) else {
+            return lookups;
+        };
+        let Some(features) = subtable(self.data, 6) else {
+            return lookups;
+        };
+        let script_count = read_u16(scripts, 0).unwrap_or(0) as usize;
+        let find_script = |tag: &[u8; 4]| (0..script_count).map(|i| 2 + i * 6).find(|&record| scripts.get(record..record + 4) == Some(&tag[..]));
+        let record = script.tags().iter().chain([&b"DFLT", &b"latn"]).find_map(|tag| find_script(tag));
+        let Some(script_table) = record.and_then(|record| subtable(scripts, record + 4)) else {
+            return lookups;
+        };
+        // The default language, or the first one if there is none
+        let first_language = if read_u16(script_table, 2).unwrap_or(0) > 0 { subtable(script_table, 8) } else { None };
+        let Some(language) = subtable(script_table, 0).or(first_language) else {
+            return lookups;
+        };
+
+        let required = read_u16(language, 2).unwrap_or(0xFFFF);
+        let feature_count = read_u16(language, 4).unwrap_or(0) as usize;
+        let indices = (0..feature_count).filter_map(|i| read_u16(language, 6 + i * 2));
+        for (index, required) in indices.map(|index| (index, false)).chain((required != 0xFFFF).then_some((required, true))) {
+            let record = 2 + index as usize * 6;
+            let Some(tag) = features.get(record..record + 4) else {
+                continue;
+            };
+            let mask = if required {
+                GLOBAL
+            } else {
+                match script.features().iter().find(|(feature, _)| &feature[..] == tag) {
+                    Some(&(_, mask)) => mask,
+                    None => continue,
+                }
+            };
+            let Some(feature) = subtable(features, record + 4) else {
+                continue;
+            };
+            let count = read_u16(feature, 2).unwrap_or(0) as usize;
+            for lookup in (0..count).filter_map(|i| read_u16(feature, 4 + i * 2)) {
+                match lookups.iter_mut().find(|(index, _)| *index == lookup) {
+                    Some((_, existing)) => *existing |= mask,
+                    None => lookups.push((lookup, mask)),
+                }
+            }
+        }
+        lookups.sort_by_key(|&(index, _)| index);
+        lookups
+    }
+
+    fn lookup(&self, index: u16) -> Option<Lookup<'a>> {
+        let list = subtable(self.data, 8)?;
+        let lookup = subtable(list, 2 + index as usize * 2)?;
+        let mut kind = read_u16(lookup, 0)?;
+        let flag = read_u16(lookup, 2)?;
+        let count = read_u16(lookup, 4)? as usize;
+        let mark_filtering_set = if flag & USE_MARK_FILTERING_SET != 0 { read_u16(lookup, 6 + count * 2)? } else { 0 };
+        let mut subtables: Vec<&[u8]> = (0..count).filter_map(|i| subtable(lookup, 6 + i * 2)).collect();
+        // Extension lookups point on to subtables of another type, with 32-bit offsets
+        let extension = if self.positioning { 9 } else { 7 };
+        if kind == extension {
+            let first = subtables.first()?;
+            kind = read_u16(first, 2)?;
+            subtables = subtables.iter().filter_map(|table| table.get(read_u32(table, 4)? as usize..)).collect();
+        }
+        Some(Lookup { kind, flag, mark_filtering_set, subtables })
+    }
+
+    // The index of the next glyph after `i` that the lookup doesn't skip
+    fn next(&self, lookup: &Lookup, infos: &[GlyphInfo], i: usize) -> Option<usize> {
+        (i + 1..infos.len()).find(|&j| !self.gdef.skips(lookup, &infos[j]))
+    }
+
+    fn previous(&self, lookup: &Lookup, infos: &[GlyphInfo], i: usize) -> Option<usize> {
+        (0..i).rev().find(|&j| !self.gdef.skips(lookup, &infos[j]))
+    }
+
+    // Applies the first subtable of a substitution lookup that matches at `i`. Returns where
+    // to continue, or None if nothing matched.
+    fn substitute(&self, lookup: &Lookup, infos: &mut Vec<GlyphInfo>, i: usize, depth: usize) -> Option<usize> {
+        let glyph = infos[i].glyph;
+        for &table in &lookup.subtables {
+            let format = read_u16(table, 0)?;
+            match lookup.kind {
+                // Single
+                1 => {
+                    let Some(index) = subtable(table, 2).and_then(|coverage| coverage_index(coverage, glyph)) else {
+                        continue;
+                    };
+                    infos[i].glyph = match format {
+                        1 => glyph.wrapping_add(read_u16(table, 4)?),
+                        2 => read_u16(table, 6 + index * 2)?,
+                        _ => continue,
+                    };
+                    return Some(i + 1);
+                }
+                // Multiple and alternate: a sequence of glyphs, or the first alternate
+                2 | 3 => {
+                    let Some(index) = subtable(table, 2).and_then(|coverage| coverage_index(coverage, glyph)) else {
+                        continue;
+                    };
+                    let sequence = subtable(table, 6 + index * 2)?;
+                    let count = read_u16(sequence, 0)? as usize;
+                    let glyphs: Vec<u16> = (0..count).filter_map(|k| read_u16(sequence, 2 + k * 2)).collect();
+                    if lookup.kind == 3 {
+                        infos[i].glyph = *glyphs.first()?;
+                        return Some(i + 1);
+                    }
+                    let info = infos[i];
+                    infos.splice(i..=i, glyphs.iter().map(|&glyph| GlyphInfo { glyph, ..info }));
+                    return Some(i + glyphs.len());
+                }
+                // Ligature
+                4 => {
+                    let Some(index) = subtable(table, 2).and_then(|coverage| coverage_index(coverage, glyph)) else {
+                        continue;
+                    };
+                    let set = subtable(table, 6 + index * 2)?;
+                    for l in 0..read_u16(set, 0)? as usize {
+                        let ligature = subtable(set, 2 + l * 2)?;
+                        let components = read_u16(ligature, 2)? as usize;
+                        let mut matched = Vec::with_capacity(components);
+                        let mut j = i;
+                        for k in 1..components {
+                            match self.next(lookup, infos, j) {
+                                Some(next) if Some(infos[next].glyph) == read_u16(ligature, 4 + (k - 1) * 2) => {
+                                    matched.push(next);
+                                    j = next;
+                                }
+                                _ => break,
+                            }
+                        }
+                        if matched.len() + 1 != components {
+                            continue;
+                        }
+                        infos[i].glyph = read_u16(ligature, 0)?;
+                        infos[i].mark = false;
+                        for &k in matched.iter().rev() {
+                            infos[i].cluster = infos[i].cluster.min(infos[k].cluster);
+                            infos.remove(k);
+                        }
+                        return Some(i + 1);
+                    }
+                }
+                // Contextual and chained contextual
+                5 | 6 => {
+                    let Some((mut matched, records)) = self.match_context(table, lookup.kind == 6, lookup, infos, i) else {
+                        continue;
+                    };
+                    if depth < MAX_NESTING {
+                        for (sequence, nested) in records {
+                            let Some(&at) = matched.get(sequence as usize) else {
+                                continue;
+                            };
+                            let Some(nested) = self.lookup(nested) else {
+                                continue;
+                            };
+                            if at >= infos.len() || self.gdef.skips(&nested, &infos[at]) {
+                                continue;
+                            }
+                            let before = infos.len();
+                            self.substitute(&nested, infos, at, depth + 1);
+                            // Glyphs added or removed shift the matched glyphs after them
+                            let change = infos.len() as isize - before as isize;
+                            for position in matched.iter_mut().filter(|position| **position > at) {
+                                *position = position.saturating_add_signed(change);
+                            }
+                        }
+                    }
+                    return Some(matched.last().map_or(i, |&last| last.max(i)) + 1);
+                }
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    // Applies the first subtable of a positioning lookup that matches at `i`. Returns where to
+    // continue, or None if nothing matched.
+    fn position(&self, lookup: &Lookup, infos: &[GlyphInfo], positions: &mut [GlyphPosition], i: usize, depth: usize) -> Option<usize> {
+        let glyph = infos[i].glyph;
+        for &table in &lookup.subtables {
+            let format = read_u16(table, 0)?;
+            match lookup.kind {
+                // Single adjustment
+                1 => {
+                    let Some(index) = subtable(table, 2).and_then(|coverage| coverage_index(coverage, glyph)) else {
+                        continue;
+                    };
+                    let value_format = read_u16(table, 4)?;
+                    let offset = match format {
+                        1 => 6,
+                        2 => 8 + index * value_size(value_format),
+                        _ => continue,
+                    };
+                    adjust(&mut positions[i], table, offset, value_format);
+                    return Some(i + 1);
+                }
+                // Pair adjustment, such as kerning
+                2 => {
+                    let Some(index) = subtable(table, 2).and_then(|coverage| coverage_index(coverage, glyph)) else {
+                        continue;
+                    };
+                    let Some(j) = self.next(lookup, infos, i) else {
+                        continue;
+                    };
+                    let (format1, format2) = (read_u16(table, 4)?, read_u16(table, 6)?);
+                    let (size1, size2) = (value_size(format1), value_size(format2));
+                    let values = match format {
+                        1 => {
+                            let set = subtable(table, 10 + index * 2)?;
+                            let record_size = 2 + size1 + size2;
+                            (0..read_u16(set, 0)? as usize)
+                                .map(|k| 2 + k * record_size)
+                                .find(|&record| read_u16(set, record) == Some(infos[j].glyph))
+                                .map(|record| (set, record + 2))
+                        }
+                        2 => {
+                            let class1 = subtable(table, 8).map_or(0, |classes| class_of(classes, glyph)) as usize;
+                            let class2 = subtable(table, 10).map_or(0, |classes| class_of(classes, infos[j].glyph)) as usize;
+                            let (count1, count2) = (read_u16(table, 12)? as usize, read_u16(table, 14)? as usize);
+                            (class1 < count1 && class2 < count2).then_some((table, 16 + (class1 * count2 + class2) * (size1 + size2)))
+                        }
+                        _ => None,
+                    };
+                    let Some((data, offset)) = values else {
+                        continue;
+                    };
+                    adjust(&mut positions[i], data, offset, format1);
+                    adjust(&mut positions[j], data, offset + size1, format2);
+                    return Some(if format2 != 0 { j + 1 } else { j });
+                }
+                // Mark to base, mark to ligature and mark to mark attachment
+                4..=6 => {
+                    let Some(mark_index) = subtable(table, 2).and_then(|coverage| coverage_index(coverage, glyph)) else {
+                        continue;
+                    };
+                    let base = if lookup.kind == 6 {
+                        self.previous(lookup, infos, i).filter(|&j| self.gdef.is_mark(&infos[j]))
+                    } else {
+                        (0..i).rev().find(|&j| !self.gdef.is_mark(&infos[j]))
+                    };
+                    let Some(base) = base else {
+                        continue;
+                    };
+                    let Some(base_index) = subtable(table, 4).and_then(|coverage| coverage_index(coverage, infos[base].glyph)) else {
+                        continue;
+                    };
+                    let class_count = read_u16(table, 6)? as usize;
+                    let marks = subtable(table, 8)?;
+                    let class = read_u16(marks, 2 + mark_index * 4)? as usize;
+                    let mark_anchor = anchor(subtable(marks, 2 + mark_index * 4 + 2)?)?;
+                    let bases = subtable(table, 10)?;
+                    let anchors = if lookup.kind == 5 {
+                        // Marks go on the last component of ligatures
+                        let ligature = subtable(bases, 2 + base_index * 2)?;
+                        let components = read_u16(ligature, 0)? as usize;
+                        let record = 2 + (components.checked_sub(1)? * class_count + class) * 2;
+                        subtable(ligature, record)
+                    } else {
+                        subtable(bases, 2 + (base_index * class_count + class) * 2)
+                    };
+                    let Some(base_anchor) = anchors.and_then(anchor) else {
+                        continue;
+                    };
+                    positions[i].attached = Some((base, base_anchor.0 - mark_anchor.0, base_anchor.1 - mark_anchor.1));
+                    positions[i].advance = 0;
+                    return Some(i + 1);
+                }
+                // Contextual and chained contextual
+                7 | 8 => {
+                    let Some((matched, records)) = self.match_context(table, lookup.kind == 8, lookup, infos, i) else {
+                        continue;
+                    };
+                    if depth < MAX_NESTING {
+                        for (sequence, nested) in records {
+                            let (Some(&at), Some(nested)) = (matched.get(sequence as usize), self.lookup(nested)) else {
+                                continue;
+                            };
+                            if !self.gdef.skips(&nested, &infos[at]) {
+                                self.position(&nested, infos, positions, at, depth + 1);
+                            }
+                        }
+                    }
+                    return Some(matched.last().map_or(i + 1, |&last| last + 1));
+                }
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    // Matches a contextual or chained contextual subtable at `i`.
+    fn match_context(&self, table: &[u8], chained: bool, lookup: &Lookup, infos: &[GlyphInfo], i: usize) -> Option<ContextMatch> {
+        let glyph = infos[i].glyph;
+        let format = read_u16(table, 0)?;
+        // Reads a rule: optional backtrack and lookahead sequences around the input sequence,
+        // each a count followed by values, then the lookup records. The first input glyph is
+        // matched by the coverage and not listed.
+        let rule = |rule: &[u8], matches: &dyn Fn(Sequence, usize, u16) -> bool| -> Option<ContextMatch> {
+            let mut offset = 0;
+            let backtrack = if chained {
+                let count = read_u16(rule, 0)? as usize;
+                offset = 2 + count * 2;
+                count
+            } else {
+                0
+            };
+            let input = read_u16(rule, offset)? as usize;
+            let (input_values, mut offset) = if chained { (offset + 2, offset + 2 + input.saturating_sub(1) * 2) } else { (offset + 4, offset + 4 + input.saturating_sub(1) * 2) };
+            let (lookahead, lookahead_values) = if chained {
+                let count = read_u16(rule, offset)? as usize;
+                offset += 2 + count * 2;
+                (count, offset - count * 2)
+            } else {
+                (0, 0)
+            };
+            let record_count = if chained {
+                offset += 2;
+                read_u16(rule, offset - 2)? as usize
+            } else {
+                read_u16(rule, 2)? as usize
+            };
+            let value = |start: usize, k: usize| read_u16(rule, start + k * 2);
+            let matched = self.match_sequences(lookup, infos, i, (backtrack, input, lookahead), &|sequence, k, glyph| {
+                let start = match sequence {
+                    Sequence::Backtrack => 2,
+                    Sequence::Input => input_values,
+                    Sequence::Lookahead => lookahead_values,
+                };
+                // Input values start at the second glyph
+                let k = if sequence == Sequence::Input { k - 1 } else { k };
+                value(start, k).is_some_and(|value| matches(sequence, value as usize, glyph))
+            })?;
+            Some((matched, records(rule, offset, record_count)))
+        };
+
+        match format {
+            // Rules of glyphs, chosen by the first glyph
+            1 => {
+                let index = coverage_index(subtable(table, 2)?, glyph)?;
+                let set = subtable(table, 6 + index * 2)?;
+                (0..read_u16(set, 0)? as usize).find_map(|r| rule(subtable(set, 2 + r * 2)?, &|_, value, glyph| value == glyph as usize))
+            }
+            // Rules of glyph classes, chosen by the class of the first glyph
+            2 => {
+                coverage_index(subtable(table, 2)?, glyph)?;
+                let (backtrack_classes, input_classes, lookahead_classes, sets) = if chained {
+                    (subtable(table, 4), subtable(table, 6), subtable(table, 8), 12)
+                } else {
+                    (None, subtable(table, 4), None, 8)
+                };
+                let class = |classes: Option<&[u8]>, glyph: u16| classes.map_or(0, |classes| class_of(classes, glyph)) as usize;
+                let set = subtable(table, sets + class(input_classes, glyph) * 2)?;
+                (0..read_u16(set, 0)? as usize).find_map(|r| {
+                    rule(subtable(set, 2 + r * 2)?, &|sequence, value, glyph| {
+                        let classes = match sequence {
+                            Sequence::Backtrack => backtrack_classes,
+                            Sequence::Input => input_classes,
+                            Sequence::Lookahead => lookahead_classes,
+                        };
+                        class(classes, glyph) == value
+                    })
+                })
+            }
+            // A single rule of coverages, including the first glyph
+            3 => {
+                let covers = |offset: usize, glyph: u16| subtable(table, offset).and_then(|coverage| coverage_index(coverage, glyph)).is_some();
+                if chained {
+                    let backtrack = read_u16(table, 2)? as usize;
+                    let input_at = 4 + backtrack * 2;
+                    let input = read_u16(table, input_at)? as usize;
+                    let lookahead_at = input_at + 2 + input * 2;
+                    let lookahead = read_u16(table, lookahead_at)? as usize;
+                    let records_at = lookahead_at + 2 + lookahead * 2;
+                    if input == 0 || !covers(input_at + 2, glyph) {
+                        return None;
+                    }
+                    let matched = self.match_sequences(lookup, infos, i, (backtrack, input, lookahead), &|sequence, k, glyph| match sequence {
+                        Sequence::Backtrack => covers(4 + k * 2, glyph),
+                        Sequence::Input => covers(input_at + 2 + k * 2, glyph),
+                        Sequence::Lookahead => covers(lookahead_at + 2 + k * 2, glyph),
+                    })?;
+                    Some((matched, records(table, records_at + 2, read_u16(table, records_at)? as usize)))
+                } else {
+                    let input = read_u16(table, 2)? as usize;
+                    if input == 0 || !covers(6, glyph) {
+                        return None;
+                    }
+                    let matched = self.match_sequences(lookup, infos, i, (0, input, 0), &|_, k, glyph| covers(6 + k * 2, glyph))?;
+                    Some((matched, records(table, 6 + input * 2, read_u16(table, 4)? as usize)))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // Matches the glyphs before `i`, from it on and after the input, skipping the glyphs the
+    // lookup ignores. `matches` is given the sequence, the index within it and the glyph;
+    // the first input glyph, already matched, is not checked.
+    fn match_sequences(
+        &self,
+        lookup: &Lookup,
+        infos: &[GlyphInfo],
+        i: usize,
+        (backtrack, input, lookahead): (usize, usize, usize),
+        matches: &dyn Fn(Sequence, usize, u16) -> bool,
+    ) -> Option<Vec<usize>> {
+        let mut matched = vec![i];
+        let mut j = i;
+        for k in 1..input {
+            j = self.next(lookup, infos, j)?;
+            if !matches(Sequence::Input, k, infos[j].glyph) {
+                return None;
+            }
+            matched.push(j);
+        }
+        let mut after = j;
+        for k in 0..lookahead {
+            after = self.next(lookup, infos, after)?;
+            if !matches(Sequence::Lookahead, k, infos[after].glyph) {
+                return None;
+            }
+        }
+        let mut before = i;
+        for k in 0..backtrack {
+            before = self.previous(lookup, infos, before)?;
+            if !matches(Sequence::Backtrack, k, infos[before].glyph) {
+                return None;
+            }
+        }
+        Some(matched)
+    }
+}
+
+// The positions of the input glyphs a contextual rule matched, and the lookups it applies to
+// them as pairs of the position in the input and the lookup index
+type ContextMatch = (Vec<usize>, Vec<(u16, u16)>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sequence {
+    Backtrack,
+    Input,
+    Lookahead,
+}
+
+// Reads `count` lookup records of a sequence index and a lookup index each.
+fn records(data: &[u8], offset: usize, count: usize) -> Vec<(u16, u16)> {
+    (0..count).filter_map(|k| Some((read_u16(data, offset + k * 4)?, read_u16(data, offset + k * 4 + 2)?))).collect()
+}
+
+// The table at the 16-bit offset stored at `at`, relative to the start of `data`. None for
+// null offsets.
+fn subtable(data: &[u8], at: usize) -> Option<&[u8]> {
+    match read_u16(data, at)? {
+        0 => None,
+        offset => data.get(offset as usize..),
+    }
+}
+
+// The index of a glyph in a coverage table, or None if it isn't covered.
+fn coverage_index(coverage: &[u8], glyph: u16) -> Option<usize> {
+    let count = read_u16(coverage, 2)? as usize;
+    match read_u16(coverage, 0)? {
+        1 => {
+            let (mut low, mut high) = (0, count);
+            while low < high {
+                let middle = (low + high) / 2;
+                match read_u16(coverage, 4 + middle * 2)?.cmp(&glyph) {
+                    std::cmp::Ordering::Less => low = middle + 1,
+                    std::cmp::Ordering::Greater => high = middle,
+                    std::cmp::Ordering::Equal => return Some(middle),
+                }
+            }
+            None
+        }
+        2 => {
+            let (mut low, mut high) = (0, count);
+            while low < high {
+                let middle = (low + high) / 2;
+                let record = 4 + middle * 6;
+                if glyph < read_u16(coverage, record)? {
+                    high = middle;
+                } else if glyph > read_u16(coverage, record + 2)? {
+                    low = middle + 1;
+                } else {
+                    return Some(read_u16(coverage, record + 4)? as usize + (glyph - read_u16(coverage, record)?) as usize);
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+// The class of a glyph in a class definition table, 0 for glyphs it doesn't list.
+fn class_of(classes: &[u8], glyph: u16) -> u16 {
+    let class = || match read_u16(classes, 0)? {
+        1 => {
+            let start = read_u16(classes, 2)?;
+            let index = glyph.checked_sub(start)? as usize;
+            if index >= read_u16(classes, 4)? as usize {
+                return None;
+            }
+            read_u16(classes, 6 + index * 2)
+        }
+        2 => {
+            let count = read_u16(classes, 2)? as usize;
+            let (mut low, mut high) = (0, count);
+            while low < high {
+                let middle = (low + high) / 2;
+                let record = 4 + middle * 6;
+                if glyph < read_u16(classes, record)? {
+                    high = middle;
+                } else if glyph > read_u16(classes, record + 2)? {
+                    low = middle + 1;
+                } else {
+                    return read_u16(classes, record + 4);
+                }
+            }
+            None
+        }
+        _ => None,
+    };
+    class().unwrap_or(0)
+}
+
+// The x and y of an anchor table, in font units.
+fn anchor(table: &[u8]) -> Option<(i32, i32)> {
+    Some((read_u16(table, 2)? as i16 as i32, read_u16(table, 4)? as i16 as i32))
+}
+
+// The size in bytes of a value record with the given format.
+fn value_size(format: u16) -> usize {
+    (format & 0xFF).count_ones() as usize * 2
+}
+
+// Adds a value record to a glyph's position. Device and variation adjustments are skipped.
+fn adjust(position: &mut GlyphPosition, data: &[u8], offset: usize, format: u16) {
+    let mut offset = offset;
+    let mut next = || {
+        let value = read_u16(data, offset).unwrap_or(0) as i16 as i32;
+        offset += 2;
+        value
+    };
+    if format & 0x1 != 0 {
+        position.x_offset += next();
+    }
+    if format & 0x2 != 0 {
+        position.y_offset += next();
+    }
+    if format & 0x4 != 0 {
+        position.advance += next();
+    }
+}
+
+// Bidirectional classes, simplified from the Unicode bidirectional algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Left,
+    Right,
+    Number,
+    Neutral,
+}
+
+fn direction(c: char) -> Direction {
+    if is_right_to_left(c) {
+        Direction::Right
+    } else if c.is_ascii_digit() || ('\u{0660}'..='\u{0669}').contains(&c) || ('\u{06F0}'..='\u{06F9}').contains(&c) {
+        Direction::Number
+    } else if c.is_alphabetic() || is_mark(c) {
+        Direction::Left
+    } else {
+        Direction::Neutral
+    }
+}
+
+// The embedding level of every character: even for left to right, odd for right to left.
+// The paragraph takes the direction of its first strong character. Numbers after
+// right-to-left text, and everything left to right in a right-to-left paragraph, get a
+// higher even level so they keep their order inside it. Neutral characters take the
+// direction of the text around them when it agrees, or else the paragraph's, and marks take
+// the direction of their base.
+fn bidi_levels(chars: &[(usize, char)]) -> Vec<u8> {
+    let mut directions: Vec<Direction> = chars.iter().map(|&(_, c)| direction(c)).collect();
+    for i in 1..chars.len() {
+        if is_mark(chars[i].1) {
+            directions[i] = directions[i - 1];
+        }
+    }
+    let paragraph_rtl = directions.iter().find(|d| matches!(d, Direction::Left | Direction::Right)) == Some(&Direction::Right);
+
+    // Numbers count as right to left around neutrals when they follow right-to-left text
+    let mut resolved = directions.clone();
+    let mut last_strong = if paragraph_rtl { Direction::Right } else { Direction::Left };
+    for direction in &mut resolved {
+        match *direction {
+            Direction::Left | Direction::Right => last_strong = *direction,
+            Direction::Number if last_strong == Direction::Right => *direction = Direction::Right,
+            Direction::Number => *direction = Direction::Left,
+            _ => {}
+        }
+    }
+    let paragraph = if paragraph_rtl { Direction::Right } else { Direction::Left };
+    for i in 0..resolved.len() {
+        if resolved[i] != Direction::Neutral {
+            continue;
+        }
+        let before = resolved[..i].iter().rev().find(|&&d| d != Direction::Neutral).copied().unwrap_or(paragraph);
+        let after = resolved[i + 1..].iter().find(|&&d| d != Direction::Neutral).copied().unwrap_or(paragraph);
+        resolved[i] = if before == after { before } else { paragraph };
+    }
+
+    directions
+        .iter()
+        .zip(&resolved)
+        .map(|(&original, &resolved)| match (original, resolved, paragraph_rtl) {
+            (Direction::Number, Direction::Right, _) | (_, Direction::Left, true) => 2,
+            (_, Direction::Right, _) => 1,
+            _ => 0,
+        })
+        .collect()
+}
+
+fn script_of(c: char) -> Option<Script> {
+    let script = match c as u32 {
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => Script::Greek,
+        0x0400..=0x052F => Script::Cyrillic,
+        0x0590..=0x05FF | 0xFB1D..=0xFB4F => Script::Hebrew,
+        0x0600..=0x06FF | 0x0750..=0x077F | 0x08A0..=0x08FF | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => Script::Arabic,
+        0x0900..=0x097F => Script::Devanagari,
+        0x0980..=0x09FF => Script::Bengali,
+        0x0A00..=0x0A7F => Script::Gurmukhi,
+        0x0A80..=0x0AFF => Script::Gujarati,
+        _ if c.is_alphabetic() => Script::Latin,
+        _ => return None,
+    };
+    // Digits and punctuation of the other blocks are shared with the surrounding text
+    (c.is_alphabetic() || script.is_indic()).then_some(script)
+}
+
+// The script of every character. Characters common to all scripts, like spaces, digits and
+// combining marks, take the script of the text before them, or after them at the start.
+// Indic vowel signs belong to their script, since its syllables are reordered.
+fn scripts(chars: &[(usize, char)]) -> Vec<Script> {
+    let own: Vec<Option<Script>> = chars.iter().map(|&(_, c)| script_of(c).filter(|script| !is_mark(c) || script.is_indic())).collect();
+    let first = own.iter().flatten().next().copied().unwrap_or(Script::Latin);
+    let mut current = first;
+    own.iter()
+        .map(|script| {
+            if let Some(script) = script {
+                current = *script;
+            }
+            current
+        })
+        .collect()
+}
+
+fn is_right_to_left(c: char) -> bool {
+    matches!(c as u32, 0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF | 0x10800..=0x10FFF | 0x1E800..=0x1EFFF)
+        && !matches!(c as u32, 0x0660..=0x0669 | 0x06F0..=0x06F9)
+}
+
+// Whether a character is a combining mark, drawn on the character before it
+fn is_mark(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0300..=0x036F
+            | 0x0483..=0x0489
+            | 0x0591..=0x05BD
+            | 0x05BF
+            | 0x05C1..=0x05C2
+            | 0x05C4..=0x05C5
+            | 0x05C7
+            | 0x0610..=0x061A
+            | 0x064B..=0x065F
+            | 0x0670
+            | 0x06D6..=0x06DC
+            | 0x06DF..=0x06E4
+            | 0x06E7..=0x06E8
+            | 0x06EA..=0x06ED
+            | 0x08D3..=0x08E1
+            | 0x08E3..=0x08FF
+            | 0x0900..=0x0903
+            | 0x093A..=0x093C
+            | 0x093E..=0x094F
+            | 0x0951..=0x0957
+            | 0x0962..=0x0963
+            | 0x0981..=0x0983
+            | 0x09BC
+            | 0x09BE..=0x09CD
+            | 0x09D7
+            | 0x09E2..=0x09E3
+            | 0x0A01..=0x0A03
+            | 0x0A3C..=0x0A51
+            | 0x0A70..=0x0A71
+            | 0x0A75
+            | 0x0A81..=0x0A83
+            | 0x0ABC
+            | 0x0ABE..=0x0ACD
+            | 0x0AE2..=0x0AE3
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x20D0..=0x20FF
+            | 0xFE20..=0xFE2F
+    )
+}
+
+// Brackets face the other way in right-to-left text
+fn mirrored(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        _ => c,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Joining {
+    /// Doesn't join, like hamza.
+    None,
+    /// Joins only to the letter before it, like alef.
+    Right,
+    /// Joins on both sides, like beh.
+    Dual,
+    /// Marks, which letters join across.
+    Transparent,
+}
+
+fn joining(c: char) -> Joining {
+    if is_mark(c) {
+        return Joining::Transparent;
+    }
+    match c as u32 {
+        0x0622..=0x0625 | 0x0627 | 0x0629 | 0x062F..=0x0632 | 0x0648 | 0x0671..=0x0673 | 0x0675..=0x0677 | 0x0688..=0x0699 | 0x06C0
+        | 0x06C3..=0x06CB | 0x06CD | 0x06CF | 0x06D2..=0x06D3 | 0x06D5 | 0x06EE..=0x06EF | 0x0759..=0x075B | 0x076B..=0x076C
+        | 0x0771 | 0x0773..=0x0774 | 0x0778..=0x0779 => Joining::Right,
+        0x0620 | 0x0626 | 0x0628 | 0x062A..=0x062E | 0x0633..=0x063F | 0x0640..=0x0647 | 0x0649..=0x064A | 0x066E..=0x066F
+        | 0x0678..=0x0687 | 0x069A..=0x06BF | 0x06C1..=0x06C2 | 0x06CC | 0x06CE | 0x06D0..=0x06D1 | 0x06FA..=0x06FC | 0x06FF
+        | 0x0750..=0x0758 | 0x075C..=0x076A | 0x076D..=0x0770 | 0x0772 | 0x0775..=0x0777 | 0x077A..=0x077F => Joining::Dual,
+        _ => Joining::None,
+    }
+}
+
+// The joining form of every Arabic letter, as a feature mask. Marks and letters that don't
+// join get none.
+fn joining_forms(chars: &[(usize, char)]) -> Vec<u8> {
+    let types: Vec<Joining> = chars.iter().map(|&(_, c)| joining(c)).collect();
+    let neighbor = |range: &mut dyn Iterator<Item = usize>| range.map(|j| types[j]).find(|&t| t != Joining::Transparent);
+    (0..chars.len())
+        .map(|i| {
+            if !matches!(types[i], Joining::Right | Joining::Dual) {
+                return 0;
+            }
+            let joins_before = neighbor(&mut (0..i).rev()) == Some(Joining::Dual);
+            let joins_after = types[i] == Joining::Dual && matches!(neighbor(&mut (i + 1..chars.len())), Some(Joining::Right | Joining::Dual));
+            match (joins_before, joins_after) {
+                (true, true) => MEDIAL,
+                (true, false) => FINAL,
+                (false, true) => INITIAL,
+                (false, false) => ISOLATED,
+            }
+        })
+        .collect()
+}
+
+// The characters an Indic script's syllables are built from
+struct IndicScript {
+    virama: char,
+    nukta: char,
+    /// The consonant that becomes a reph above the syllable before another consonant, if
+    /// the script has one.
+    ra: Option<char>,
+    consonants: &'static [(char, char)],
+    pre_base_matras: &'static [char],
+}
+
+fn indic_script(script: Script) -> Option<IndicScript> {
+    Some(match script {
+        Script::Devanagari => IndicScript {
+            virama: '\u{094D}',
+            nukta: '\u{093C}',
+            ra: Some('\u{0930}'),
+            consonants: &[('\u{0915}', '\u{0939}'), ('\u{0958}', '\u{095F}'), ('\u{0978}', '\u{097F}')],
+            pre_base_matras: &['\u{093F}', '\u{094E}'],
+        },
+        Script::Bengali => IndicScript {
+            virama: '\u{09CD}',
+            nukta: '\u{09BC}',
+            ra: Some('\u{09B0}'),
+            consonants: &[('\u{0995}', '\u{09B9}'), ('\u{09DC}', '\u{09DF}'), ('\u{09F0}', '\u{09F1}')],
+            pre_base_matras: &['\u{09BF}', '\u{09C7}', '\u{09C8}'],
+        },
+        Script::Gurmukhi => IndicScript {
+            virama: '\u{0A4D}',
+            nukta: '\u{0A3C}',
+            ra: None,
+            consonants: &[('\u{0A15}', '\u{0A39}'), ('\u{0A59}', '\u{0A5E}')],
+            pre_base_matras: &['\u{0A3F}'],
+        },
+        Script::Gujarati => IndicScript {
+            virama: '\u{0ACD}',
+            nukta: '\u{0ABC}',
+            ra: Some('\u{0AB0}'),
+            consonants: &[('\u{0A95}', '\u{0AB9}')],
+            pre_base_matras: &['\u{0ABF}'],
+        },
+        _ => return None,
+    })
+}
+
+// Puts the characters of Indic syllables in the order their glyphs are drawn: vowel signs
+// drawn before the consonants move in front of them, and a reph moves to the end of its
+// syllable, where the font's lookups expect it.
+fn reorder_indic(chars: &mut Vec<(usize, char)>, script: Script) {
+    let Some(indic) = indic_script(script) else {
+        return;
+    };
+    // Bengali vowels written on both sides of the consonant split into their two parts
+    let mut i = 0;
+    while i < chars.len() {
+        let (cluster, c) = chars[i];
+        let parts = match c {
+            '\u{09CB}' => Some(['\u{09C7}', '\u{09BE}']),
+            '\u{09CC}' => Some(['\u{09C7}', '\u{09D7}']),
+            _ => None,
+        };
+        if let Some(parts) = parts {
+            chars.splice(i..=i, parts.map(|part| (cluster, part)));
+        }
+        i += 1;
+    }
+
+    let is_consonant = |c: char| indic.consonants.iter().any(|&(first, last)| (first..=last).contains(&c));
+    let mut i = 0;
+    while i < chars.len() {
+        if !is_consonant(chars[i].1) {
+            i += 1;
+            continue;
+        }
+        // Consonants joined by viramas, each with an optional nukta
+        let start = i;
+        let mut end = i + 1;
+        loop {
+            if chars.get(end).is_some_and(|&(_, c)| c == indic.nukta) {
+                end += 1;
+            }
+            if chars.get(end).is_some_and(|&(_, c)| c == indic.virama) && chars.get(end + 1).is_some_and(|&(_, c)| is_consonant(c)) {
+                end += 2;
+                continue;
+            }
+            break;
+        }
+        let mut consonants_end = end;
+        while chars.get(end).is_some_and(|&(_, c)| is_mark(c)) {
+            end += 1;
+        }
+
+        let has_reph = indic.ra == Some(chars[start].1) && chars[start + 1..consonants_end].first().map(|&(_, c)| c) == Some(indic.virama);
+        let mut signs_end = end;
+        if has_reph {
+            let reph: Vec<(usize, char)> = chars.drain(start..start + 2).collect();
+            chars.splice(end - 2..end - 2, reph);
+            consonants_end -= 2;
+            signs_end -= 2;
+        }
+        if let Some(position) = (consonants_end..signs_end).find(|&k| indic.pre_base_matras.contains(&chars[k].1)) {
+            let matra = chars.remove(position);
+            chars.insert(start, matra);
+        }
+        i = end;
+    }
+}
@@ -1,82 +1,30 @@
-use minifb::{Window, WindowOptions};
-use crate::canvas::Canvas;
-use crate::color::Color;
-use crate::geom::Point;
+use simple_sketch::app::{Application, Context, Settings};
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::command::Style;
+use simple_sketch::geom::Point;
 use std::f32::consts::PI;
 
-//use std::time::Instant;
+/// Speed of the circular motion in radians per second.
+const ANGULAR_SPEED: f32 = 0.15;
 
-pub struct Sketch {
-    window: Window,
-    canvas: Canvas,
-    angle: f32,
-}
-
-impl Sketch {
-    pub fn new() -> Result<Self, String> {
-        let width = 640;
-        let height = 360;
+#[derive(Default)]
+pub struct Sketch;
 
-        let mut window = Window::new(
-            "Simple Sketch",
-            width,
-            height,
-            WindowOptions::default(),
-        ).map_err(|e| e.to_string())?;
-        
-        window.set_target_fps(60);
-
-        Ok(Self {
-            window,
-            canvas: Canvas::new(width, height),
-            angle: 0.0,
-        })
+impl Application for Sketch {
+    fn settings(&self) -> Settings {
+        Settings::new().size(640, 360).title("Simple Sketch")
     }
 
-    pub fn run(&mut self) {
-        while self.window.is_open() && !self.window.is_key_down(minifb::Key::Escape) {
-            self.update();
-            //let start = Instant::now();
-            self.draw();
-            //let duration = start.elapsed();
-            //println!("Time elapsed in draw() is: {:?}", duration);
-
-            self.window
-                .update_with_buffer(self.canvas.pixel_buffer.get_buffer(), self.canvas.width, self.canvas.height)
-                .unwrap();
-        }
-    }
-
-    fn update(&mut self) {
-        self.angle += 0.0025;
-        if self.angle > 2.0 * PI {
-            self.angle -= 2.0 * PI;
-        }
-    }
-
-    fn draw(&mut self) {
-        self.canvas.background(Color::new(0, 0, 0, 255));
+    fn draw(&mut self, canvas: &mut Canvas, ctx: &Context) {
+        let time_angle = (ctx.seconds_since_start() * ANGULAR_SPEED) % (2.0 * PI);
+        canvas.background(Color::new(0, 0, 0, 255));
 
         // Draw grid
-        self.canvas.set_stroke(Some(Color::new(32, 32, 32, 255))); // Light gray color for grid
-        self.canvas.set_stroke_weight(1.0); // Thin lines for grid
-
-        // Vertical lines
-        for x in (0..self.canvas.width).step_by(20) {
-            let start = Point::new(x as f32, 0.0);
-            let end = Point::new(x as f32, self.canvas.height as f32);
-            self.canvas.line(start, end);
-        }
-
-        // Horizontal lines
-        for y in (0..self.canvas.height).step_by(20) {
-            let start = Point::new(0.0, y as f32);
-            let end = Point::new(self.canvas.width as f32, y as f32);
-            self.canvas.line(start, end);
-        }
+        canvas.grid(20.0, Style { fill: None, stroke: Some(Color::new(32, 32, 32, 255)), stroke_weight: 1.0 });
 
         // Drawing 10 rotating and waving circles
-        let center = Point::new(self.canvas.width as f32 / 2.0, self.canvas.height as f32 / 2.0);
+        let center = Point::new(canvas.width as f32 / 2.0, canvas.height as f32 / 2.0);
         let base_radius = 100.0; // Base radius for the circular motion
         let wave_amplitude = 10.0; // Amplitude of the wave motion
         let wave_frequency = 6.0; // Frequency of the wave motion
@@ -84,10 +32,10 @@ impl Sketch {
         let num_ellipses = 100;
 
         for i in 0..num_ellipses {
-            let angle = self.angle + (i as f32 * PI * 2.0 / num_ellipses as f32);
+            let angle = time_angle + (i as f32 * PI * 2.0 / num_ellipses as f32);
 
             // Add a sine wave to the radius, with a phase offset for each circle
-            let wave_offset = self.angle * wave_frequency + (i as f32 * PI / 5.0);
+            let wave_offset = time_angle * wave_frequency + (i as f32 * PI / 5.0);
             let radius = base_radius + wave_amplitude * wave_offset.sin();
 
             let circle_center = center + Point::new(radius * angle.cos(), radius * angle.sin());
@@ -95,18 +43,18 @@ impl Sketch {
             // Set different colors for each circle
             let hue = (i as f32 / num_ellipses as f32) * 360.0;
             let color = Color::hsv_to_rgb(hue, 1.0, 1.0);
-            self.canvas.set_stroke(Some(color));
-            self.canvas.set_fill(Some(color));
+            canvas.set_stroke(Some(color));
+            canvas.set_fill(Some(color));
 
             // Draw the circle
-            self.canvas.set_stroke_weight(1.0);
-            self.canvas.ellipse(circle_center, 5.0, 5.0);
+            canvas.set_stroke_weight(1.0);
+            canvas.ellipse(circle_center, 5.0, 5.0);
 
             // Draw line from center to circle
-            self.canvas.line(center, circle_center);
+            canvas.line(center, circle_center);
         }
 
-        self.canvas.rectangle(Point::new(20.0,20.0), 40.0, 30.0);
+        canvas.rectangle(Point::new(20.0,20.0), 40.0, 30.0);
     }
 
-}
\ No newline at end of file
+}
@@ -0,0 +1,272 @@
+//! Snapshot tests: rendering sketches headlessly and comparing their frames to reference
+//! images, to catch visual regressions when the rasterizer changes.
+//!
+//! A `Snapshot` renders a sketch for a number of frames, with a headless runner whose clock
+//! advances by exactly one frame per frame, and compares the last frame to a PNG file named
+//! after the test in its directory. References that don't exist yet are recorded, and setting
+//! the `UPDATE_GOLDEN` environment variable records all of them again after an intended
+//! change. Small differences, like those of a tweaked antialiasing, can be tolerated per
+//! channel and per number of pixels. In a test:
+//!
+//! ```no_run
+//! # use simple_sketch::app::{Application, Context};
+//! # use simple_sketch::canvas::Canvas;
+//! # use simple_sketch::snapshot::Snapshot;
+//! # struct Bubbles;
+//! # impl Bubbles { fn with_seed(_: u32) -> Self { Bubbles } }
+//! # impl Application for Bubbles {
+//! #     fn draw(&mut self, _: &mut Canvas, _: &Context) {}
+//! # }
+//! Snapshot::new("tests/golden").frames(60).seed(7).tolerance(2).assert_matches("bubbles", Bubbles::with_seed);
+//! ```
+//!
+//! When a frame doesn't match, `assert_matches` panics with the first differing pixels and
+//! writes the frame next to the reference as `<name>.actual.png`, along with
+//! `<name>.diff.png`, which shows the differing pixels in red over a faded copy of the
+//! reference.
+
+use std::fmt::{self, Write};
+use std::path::PathBuf;
+
+use crate::app::{Application, ApplicationRunner};
+use crate::color::Color;
+use crate::error::SketchError;
+use crate::image::Image;
+
+/// Differing pixels listed in the message of a failed `Snapshot::assert_matches`.
+const REPORTED_PIXELS: usize = 10;
+
+/// Renders sketches and compares their last frame to reference images in a directory.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    directory: PathBuf,
+    frames: u64,
+    seed: u32,
+    tolerance: u8,
+    max_differing_pixels: usize,
+    update: bool,
+}
+
+impl Snapshot {
+    /// Compares renders of one frame, with seed 0, exactly to the references in `directory`.
+    /// References are recorded again if the `UPDATE_GOLDEN` environment variable is set.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Snapshot {
+            directory: directory.into(),
+            frames: 1,
+            seed: 0,
+            tolerance: 0,
+            max_differing_pixels: 0,
+            update: std::env::var_os("UPDATE_GOLDEN").is_some(),
+        }
+    }
+
+    /// Sets the number of frames rendered before the last one is compared.
+    pub fn frames(mut self, frames: u64) -> Self {
+        self.frames = frames.max(1);
+        self
+    }
+
+    /// Sets the seed passed to the function that creates the sketch, for the sketch to seed
+    /// its random values with so that it draws the same every time.
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets how much any channel of a pixel may differ from the reference, from 0 to 255, for
+    /// the pixel to count as matching.
+    pub fn tolerance(mut self, tolerance: u8) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets how many pixels may differ beyond the tolerance for a frame to still match.
+    pub fn max_differing_pixels(mut self, count: usize) -> Self {
+        self.max_differing_pixels = count;
+        self
+    }
+
+    /// Sets whether references are recorded again instead of compared, overriding the
+    /// `UPDATE_GOLDEN` environment variable.
+    pub fn update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// The path of the reference image of the snapshot `name`.
+    pub fn reference_path(&self, name: &str) -> PathBuf {
+        self.directory.join(format!("{name}.png"))
+    }
+
+    /// Creates a sketch by calling `create` with the seed, renders it headlessly for the
+    /// number of frames, and returns the last frame.
+    pub fn render<A: Application>(&self, create: impl FnOnce(u32) -> A) -> Result<Image, SketchError> {
+        let mut runner = ApplicationRunner::headless(create(self.seed));
+        runner.run_frames(self.frames, |_, _| {})?;
+        let image = Image::from(&runner.canvas().pixel_buffer);
+        runner.finish();
+        Ok(image)
+    }
+
+    /// Renders a sketch like `render` and compares its last frame to the reference image of
+    /// the snapshot `name`. A missing reference is recorded from the frame, as are all of them
+    /// while updating, and then the comparison finds no differences.
+    pub fn check<A: Application>(&self, name: &str, create: impl FnOnce(u32) -> A) -> Result<Diff, SketchError> {
+        self.check_frame(name, create).map(|(diff, _)| diff)
+    }
+
+    /// Checks a sketch like `check`, and panics with a report of the differences unless its
+    /// frame matches the reference within the tolerances. The frame and an image of the
+    /// differences are then saved next to the reference, and removed by a later run that
+    /// matches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the frame doesn't match, or if it can't be rendered or compared.
+    pub fn assert_matches<A: Application>(&self, name: &str, create: impl FnOnce(u32) -> A) {
+        let reference = self.reference_path(name);
+        let actual_path = self.directory.join(format!("{name}.actual.png"));
+        let diff_path = self.directory.join(format!("{name}.diff.png"));
+        let (diff, actual) = match self.check_frame(name, create) {
+            Ok(result) => result,
+            Err(error) => panic!("snapshot {name}: {error}"),
+        };
+        if diff.passes(self.max_differing_pixels) {
+            // Left over from an earlier failure
+            let _ = std::fs::remove_file(&actual_path);
+            let _ = std::fs::remove_file(&diff_path);
+            return;
+        }
+        let saved = actual.save(&actual_path).and_then(|()| diff.image(&Image::load(&reference)?).save(&diff_path));
+        let saved = match saved {
+            Ok(()) => format!("frame saved to {}, differences to {}", actual_path.display(), diff_path.display()),
+            Err(error) => format!("could not save the frame: {error}"),
+        };
+        panic!("snapshot {name} doesn't match {}:\n{}\n{saved}", reference.display(), diff.report(REPORTED_PIXELS));
+    }
+
+    // Checks a sketch like `check`, and also returns its frame
+    fn check_frame<A: Application>(&self, name: &str, create: impl FnOnce(u32) -> A) -> Result<(Diff, Image), SketchError> {
+        let actual = self.render(create)?;
+        let path = self.reference_path(name);
+        if self.update || !path.exists() {
+            actual.save(&path)?;
+            return Ok((Diff::compare(&actual, &actual, self.tolerance), actual));
+        }
+        Ok((Diff::compare(&Image::load(&path)?, &actual, self.tolerance), actual))
+    }
+}
+
+/// A pixel that differs from the reference beyond the tolerance.
+#[derive(Debug, Clone, Copy)]
+pub struct PixelDiff {
+    pub x: usize,
+    pub y: usize,
+    pub expected: Color,
+    pub actual: Color,
+}
+
+impl PixelDiff {
+    /// The largest difference between a channel of the two colors, alpha included.
+    pub fn difference(&self) -> u8 {
+        let (expected, actual) = (self.expected.0.to_be_bytes(), self.actual.0.to_be_bytes());
+        expected.iter().zip(actual).map(|(expected, actual)| expected.abs_diff(actual)).max().unwrap_or(0)
+    }
+}
+
+/// The differences between a frame and its reference image.
+#[derive(Debug, Clone)]
+pub struct Diff {
+    /// The width and height of the reference.
+    pub expected_size: (usize, usize),
+    /// The width and height of the frame.
+    pub actual_size: (usize, usize),
+    /// The differing pixels, row by row from the top left. Empty if the sizes differ.
+    pub pixels: Vec<PixelDiff>,
+}
+
+impl Diff {
+    /// Compares `actual` to `expected` pixel by pixel, counting pixels as matching when no
+    /// channel differs by more than `tolerance`.
+    pub fn compare(expected: &Image, actual: &Image, tolerance: u8) -> Self {
+        let expected_size = (expected.width, expected.height);
+        let actual_size = (actual.width, actual.height);
+        let mut pixels = Vec::new();
+        if expected_size == actual_size {
+            for (index, (&expected, &actual)) in expected.pixels().iter().zip(actual.pixels()).enumerate() {
+                let pixel = PixelDiff { x: index % actual_size.0, y: index / actual_size.0, expected: Color(expected), actual: Color(actual) };
+                if pixel.difference() > tolerance {
+                    pixels.push(pixel);
+                }
+            }
+        }
+        Diff { expected_size, actual_size, pixels }
+    }
+
+    /// Whether the sizes match and at most `max_differing_pixels` pixels differ.
+    pub fn passes(&self, max_differing_pixels: usize) -> bool {
+        self.expected_size == self.actual_size && self.pixels.len() <= max_differing_pixels
+    }
+
+    /// Whether the frame matches its reference exactly, within the tolerance.
+    pub fn is_empty(&self) -> bool {
+        self.passes(0)
+    }
+
+    /// The largest channel difference of any differing pixel, or 0 if none differs.
+    pub fn max_difference(&self) -> u8 {
+        self.pixels.iter().map(PixelDiff::difference).max().unwrap_or(0)
+    }
+
+    /// A summary of the differences, listing the first `limit` differing pixels.
+    pub fn report(&self, limit: usize) -> String {
+        let mut report = String::new();
+        if self.expected_size != self.actual_size {
+            let ((ew, eh), (aw, ah)) = (self.expected_size, self.actual_size);
+            let _ = write!(report, "size differs: expected {ew}x{eh}, got {aw}x{ah}");
+            return report;
+        }
+        let (width, height) = self.actual_size;
+        let percent = 100.0 * self.pixels.len() as f64 / (width * height).max(1) as f64;
+        let _ = write!(report, "{} of {} pixels differ ({percent:.2}%), by up to {}", self.pixels.len(), width * height, self.max_difference());
+        for pixel in self.pixels.iter().take(limit) {
+            let _ = write!(report, "\n  ({}, {}): expected {:08x}, got {:08x}", pixel.x, pixel.y, pixel.expected.0, pixel.actual.0);
+        }
+        if self.pixels.len() > limit {
+            let _ = write!(report, "\n  and {} more", self.pixels.len() - limit);
+        }
+        report
+    }
+
+    /// An image of the differences for inspecting them: `reference` faded towards white, with
+    /// the differing pixels in red.
+    pub fn image(&self, reference: &Image) -> Image {
+        let mut image = reference.clone();
+        for pixel in image.pixels_mut() {
+            let [_, r, g, b] = pixel.to_be_bytes().map(|channel| 192 + channel / 4);
+            *pixel = Color::new(r, g, b, 255).0;
+        }
+        for diff in &self.pixels {
+            if diff.x < image.width && diff.y < image.height {
+                let width = image.width;
+                image.pixels_mut()[diff.y * width + diff.x] = Color::new(255, 0, 0, 255).0;
+            }
+        }
+        image
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.report(REPORTED_PIXELS))
+    }
+}
+
+/// Hashes pixels with FNV-1a, which is stable across platforms and Rust versions unlike the
+/// standard library's hasher, for storing a frame's fingerprint instead of a reference image.
+pub fn hash(pixels: &[u32]) -> u64 {
+    pixels.iter().flat_map(|pixel| pixel.to_le_bytes()).fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
@@ -0,0 +1,559 @@
+//! Loading SVG files as paths with fill and stroke colors, for drawing with `Canvas::draw_svg`
+//! or using their geometry directly.
+//!
+//! The subset read is what drawing programs such as Illustrator and Inkscape write for plain
+//! vector art: `path`, `rect`, `circle`, `ellipse`, `line`, `polyline` and `polygon` elements,
+//! nested in groups with transforms, styled by presentation attributes, `style` attributes and
+//! class rules in `<style>` elements. Gradients are drawn in the average color of their stops.
+//! Text, images, `use` references, clipping, masks and filters are skipped.
+
+use std::collections::HashMap;
+use std::path::Path as FilePath;
+
+use crate::color::Color;
+use crate::error::SketchError;
+use crate::geom::{Path, Point, Rect, Transform};
+
+/// The shapes of an SVG file, in drawing order, in the pixels of its `width` and `height`.
+#[derive(Debug, Clone)]
+pub struct SvgDocument {
+    /// The size of the document, from the root element's `width` and `height`, or from its
+    /// `viewBox` when they are missing.
+    pub width: f32,
+    pub height: f32,
+    pub shapes: Vec<SvgShape>,
+}
+
+/// A shape of an SVG file with all transforms applied.
+#[derive(Debug, Clone)]
+pub struct SvgShape {
+    /// The `id` attribute of the element, if it had one.
+    pub id: Option<String>,
+    pub path: Path,
+    pub fill: Option<Color>,
+    pub stroke: Option<Color>,
+    /// The stroke width, scaled by the transforms of the shape.
+    pub stroke_weight: f32,
+}
+
+impl SvgDocument {
+    /// Loads an SVG file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use simple_sketch::canvas::Canvas;
+    /// # use simple_sketch::error::SketchError;
+    /// # use simple_sketch::svg::SvgDocument;
+    /// # fn draw(canvas: &mut Canvas) -> Result<(), SketchError> {
+    /// let logo = SvgDocument::load("assets/logo.svg")?;
+    /// canvas.draw_svg(&logo);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load(path: impl AsRef<FilePath>) -> Result<Self, SketchError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| std::io::Error::new(e.kind(), format!("could not load {}: {e}", path.display())))?;
+        SvgDocument::parse(&text).map_err(|error| match error {
+            SketchError::Svg(reason) => SketchError::Svg(format!("{}: {reason}", path.display())),
+            error => error,
+        })
+    }
+
+    /// Reads the contents of an SVG file.
+    pub fn parse(text: &str) -> Result<Self, SketchError> {
+        let nodes = parse_xml(text).map_err(SketchError::Svg)?;
+        let Some(root) = nodes.iter().find(|node| node.name == "svg") else {
+            return Err(SketchError::Svg("no <svg> element".to_string()));
+        };
+
+        let mut reader = Reader { classes: HashMap::new(), gradients: HashMap::new(), shapes: Vec::new() };
+        reader.collect_definitions(&nodes);
+
+        let view_box = root.attribute("viewBox").and_then(|view_box| match numbers(view_box)[..] {
+            [x, y, width, height] if width > 0.0 && height > 0.0 => Some(Rect::new(Point::new(x, y), Point::new(x + width, y + height))),
+            _ => None,
+        });
+        let width = root.attribute("width").and_then(length).or(view_box.map(|view_box| view_box.width())).unwrap_or(300.0);
+        let height = root.attribute("height").and_then(length).or(view_box.map(|view_box| view_box.height())).unwrap_or(150.0);
+        // Fit the view box into the document, keeping its aspect ratio and centering it
+        let transform = match view_box {
+            Some(view_box) => {
+                let scale = (width / view_box.width()).min(height / view_box.height());
+                let offset = Point::new(width - view_box.width() * scale, height - view_box.height() * scale) * 0.5;
+                Transform::translate(offset) * Transform::scale(scale, scale) * Transform::translate(view_box.min * -1.0)
+            }
+            None => Transform::identity(),
+        };
+        reader.read(root, &nodes, &Inherited { transform, ..Inherited::default() })?;
+        Ok(SvgDocument { width, height, shapes: reader.shapes })
+    }
+
+    /// The shape with the given `id` attribute, if there is one.
+    pub fn shape(&self, id: &str) -> Option<&SvgShape> {
+        self.shapes.iter().find(|shape| shape.id.as_deref() == Some(id))
+    }
+
+    /// The bounding box of all shapes, ignoring stroke widths, or None without shapes.
+    pub fn bounding_box(&self) -> Option<Rect> {
+        self.shapes.iter().filter_map(|shape| shape.path.bounding_box()).reduce(|a, b| {
+            Rect::new(Point::new(a.min.x.min(b.min.x), a.min.y.min(b.min.y)), Point::new(a.max.x.max(b.max.x), a.max.y.max(b.max.y)))
+        })
+    }
+}
+
+// The properties a shape inherits from its groups.
+#[derive(Clone)]
+struct Inherited {
+    transform: Transform,
+    fill: Paint,
+    stroke: Paint,
+    stroke_width: f32,
+    fill_opacity: f32,
+    stroke_opacity: f32,
+    /// The product of the `opacity` of the groups, which SVG applies to the group as a whole
+    /// and this applies to each shape instead.
+    opacity: f32,
+}
+
+impl Default for Inherited {
+    fn default() -> Self {
+        Inherited {
+            transform: Transform::identity(),
+            fill: Paint::Color(Color::new(0, 0, 0, 255)),
+            stroke: Paint::None,
+            stroke_width: 1.0,
+            fill_opacity: 1.0,
+            stroke_opacity: 1.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Paint {
+    None,
+    Color(Color),
+}
+
+struct Reader {
+    /// The declarations of each class in `<style>` elements.
+    classes: HashMap<String, Vec<(String, String)>>,
+    /// The average color of each gradient, by id.
+    gradients: HashMap<String, Color>,
+    shapes: Vec<SvgShape>,
+}
+
+impl Reader {
+    fn collect_definitions(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            match node.name.as_str() {
+                "style" => {
+                    for rule in node.text.split('}') {
+                        let Some((selectors, declarations)) = rule.split_once('{') else {
+                            continue;
+                        };
+                        let declarations = declarations_of(declarations);
+                        for selector in selectors.split(',').filter_map(|selector| selector.trim().strip_prefix('.')) {
+                            self.classes.entry(selector.to_string()).or_default().extend(declarations.iter().cloned());
+                        }
+                    }
+                }
+                "linearGradient" | "radialGradient" => {
+                    let stops: Vec<Color> = node
+                        .children
+                        .iter()
+                        .map(|&child| &nodes[child])
+                        .filter(|stop| stop.name == "stop")
+                        .filter_map(|stop| {
+                            let properties = stop.properties(&HashMap::new());
+                            let color = parse_color(properties.get("stop-color").map_or("black", String::as_str))?;
+                            let opacity = properties.get("stop-opacity").and_then(|opacity| number(opacity)).unwrap_or(1.0);
+                            Some(color.with_alpha((color.a() as f32 * opacity.clamp(0.0, 1.0)).round() as u8))
+                        })
+                        .collect();
+                    if let (Some(id), false) = (node.attribute("id"), stops.is_empty()) {
+                        let channel = |channel: fn(&Color) -> u8| (stops.iter().map(|stop| channel(stop) as f32).sum::<f32>() / stops.len() as f32).round() as u8;
+                        self.gradients.insert(id.to_string(), Color::new(channel(Color::r), channel(Color::g), channel(Color::b), channel(Color::a)));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn read(&mut self, node: &Node, nodes: &[Node], inherited: &Inherited) -> Result<(), SketchError> {
+        let properties = node.properties(&self.classes);
+        if properties.get("display").is_some_and(|display| display == "none") {
+            return Ok(());
+        }
+        let mut style = inherited.clone();
+        if let Some(transform) = node.attribute("transform") {
+            style.transform = inherited.transform * parse_transform(transform);
+        }
+        for (name, value) in &properties {
+            let value = value.as_str();
+            match name.as_str() {
+                "fill" => style.fill = self.parse_paint(value).unwrap_or(style.fill),
+                "stroke" => style.stroke = self.parse_paint(value).unwrap_or(style.stroke),
+                "stroke-width" => style.stroke_width = length(value).unwrap_or(style.stroke_width),
+                "fill-opacity" => style.fill_opacity = number(value).unwrap_or(1.0).clamp(0.0, 1.0),
+                "stroke-opacity" => style.stroke_opacity = number(value).unwrap_or(1.0).clamp(0.0, 1.0),
+                "opacity" => style.opacity *= number(value).unwrap_or(1.0).clamp(0.0, 1.0),
+                _ => {}
+            }
+        }
+
+        let path = match node.name.as_str() {
+            "svg" | "g" | "a" => {
+                for &child in &node.children {
+                    self.read(&nodes[child], nodes, &style)?;
+                }
+                return Ok(());
+            }
+            "path" => {
+                let data = node.attribute("d").unwrap_or_default();
+                Path::parse_svg(data).map_err(|error| SketchError::Svg(format!("invalid path data: {error}")))?
+            }
+            "rect" => {
+                let [x, y, width, height] = ["x", "y", "width", "height"].map(|name| node.attribute(name).and_then(length).unwrap_or(0.0));
+                let rx = node.attribute("rx").and_then(length);
+                let ry = node.attribute("ry").and_then(length);
+                let (rx, ry) = match (rx, ry) {
+                    (Some(rx), Some(ry)) => (rx, ry),
+                    (Some(radius), None) | (None, Some(radius)) => (radius, radius),
+                    (None, None) => (0.0, 0.0),
+                };
+                rounded_rect(Point::new(x, y), width, height, rx.min(width / 2.0), ry.min(height / 2.0))
+            }
+            "circle" | "ellipse" => {
+                let center = Point::new(node.attribute("cx").and_then(length).unwrap_or(0.0), node.attribute("cy").and_then(length).unwrap_or(0.0));
+                let (rx, ry) = match node.attribute("r").and_then(length) {
+                    Some(radius) => (radius, radius),
+                    None => (node.attribute("rx").and_then(length).unwrap_or(0.0), node.attribute("ry").and_then(length).unwrap_or(0.0)),
+                };
+                Path::ellipse(center, rx * 2.0, ry * 2.0)
+            }
+            "line" => {
+                let [x1, y1, x2, y2] = ["x1", "y1", "x2", "y2"].map(|name| node.attribute(name).and_then(length).unwrap_or(0.0));
+                let mut path = Path::new();
+                path.move_to(Point::new(x1, y1)).line_to(Point::new(x2, y2));
+                path
+            }
+            "polyline" | "polygon" => {
+                let points: Vec<Point> = numbers(node.attribute("points").unwrap_or_default()).chunks_exact(2).map(|xy| Point::new(xy[0], xy[1])).collect();
+                if node.name == "polygon" {
+                    Path::polygon(&points)
+                } else {
+                    let mut path = Path::new();
+                    for (i, &point) in points.iter().enumerate() {
+                        if i == 0 {
+                            path.move_to(point);
+                        } else {
+                            path.line_to(point);
+                        }
+                    }
+                    path
+                }
+            }
+            // Definitions, text, images and anything else
+            _ => return Ok(()),
+        };
+        if path.is_empty() {
+            return Ok(());
+        }
+
+        let paint = |paint: Paint, opacity: f32| match paint {
+            Paint::None => None,
+            Paint::Color(color) => Some(color.with_alpha((color.a() as f32 * opacity * style.opacity).round() as u8)),
+        };
+        // Lines can't be filled
+        let fill = if node.name == "line" { None } else { paint(style.fill, style.fill_opacity) };
+        let stroke = paint(style.stroke, style.stroke_opacity);
+        if fill.is_none() && stroke.is_none() {
+            return Ok(());
+        }
+        self.shapes.push(SvgShape {
+            id: node.attribute("id").map(str::to_string),
+            path: path.transform(&style.transform),
+            fill,
+            stroke,
+            stroke_weight: style.stroke_width * style.transform.determinant().abs().sqrt(),
+        });
+        Ok(())
+    }
+
+    // Parses a fill or stroke value. None for values that can't be read, which keep the
+    // inherited paint.
+    fn parse_paint(&self, value: &str) -> Option<Paint> {
+        let value = value.trim();
+        if value == "none" {
+            return Some(Paint::None);
+        }
+        if let Some(reference) = value.strip_prefix("url(") {
+            let (id, fallback) = reference.split_once(')').unwrap_or((reference, ""));
+            let id = id.trim().trim_matches(|c| c == '\'' || c == '"').trim_start_matches('#');
+            return match self.gradients.get(id) {
+                Some(&color) => Some(Paint::Color(color)),
+                None => self.parse_paint(fallback).or(Some(Paint::None)),
+            };
+        }
+        parse_color(value).map(Paint::Color)
+    }
+}
+
+// Builds a rectangle with elliptical corners, clockwise from the top left.
+fn rounded_rect(top_left: Point, width: f32, height: f32, rx: f32, ry: f32) -> Path {
+    if rx <= 0.0 || ry <= 0.0 {
+        return Path::rectangle(top_left, width, height);
+    }
+    // Distance of the control points from the corner's end points, for a quarter ellipse
+    const KAPPA: f32 = 0.552_284_8;
+    let (x0, y0, x1, y1) = (top_left.x, top_left.y, top_left.x + width, top_left.y + height);
+    let (kx, ky) = (rx * (1.0 - KAPPA), ry * (1.0 - KAPPA));
+    let mut path = Path::new();
+    path.move_to(Point::new(x0 + rx, y0))
+        .line_to(Point::new(x1 - rx, y0))
+        .cubic_to(Point::new(x1 - kx, y0), Point::new(x1, y0 + ky), Point::new(x1, y0 + ry))
+        .line_to(Point::new(x1, y1 - ry))
+        .cubic_to(Point::new(x1, y1 - ky), Point::new(x1 - kx, y1), Point::new(x1 - rx, y1))
+        .line_to(Point::new(x0 + rx, y1))
+        .cubic_to(Point::new(x0 + kx, y1), Point::new(x0, y1 - ky), Point::new(x0, y1 - ry))
+        .line_to(Point::new(x0, y0 + ry))
+        .cubic_to(Point::new(x0, y0 + ky), Point::new(x0 + kx, y0), Point::new(x0 + rx, y0))
+        .close();
+    path
+}
+
+// Parses a transform list such as `translate(10 20) rotate(45)`.
+fn parse_transform(text: &str) -> Transform {
+    let mut transform = Transform::identity();
+    for function in text.split(')') {
+        let Some((name, arguments)) = function.split_once('(') else {
+            continue;
+        };
+        let arguments = numbers(arguments);
+        let argument = |index: usize, default: f32| arguments.get(index).copied().unwrap_or(default);
+        let next = match name.trim().trim_start_matches(',').trim() {
+            "matrix" if arguments.len() == 6 => Transform {
+                m11: arguments[0],
+                m21: arguments[1],
+                m12: arguments[2],
+                m22: arguments[3],
+                tx: arguments[4],
+                ty: arguments[5],
+            },
+            "translate" => Transform::translate(Point::new(argument(0, 0.0), argument(1, 0.0))),
+            "scale" => Transform::scale(argument(0, 1.0), argument(1, argument(0, 1.0))),
+            "rotate" => {
+                let center = Point::new(argument(1, 0.0), argument(2, 0.0));
+                Transform::translate(center) * Transform::rotate(argument(0, 0.0).to_radians()) * Transform::translate(center * -1.0)
+            }
+            "skewX" => Transform { m12: argument(0, 0.0).to_radians().tan(), ..Transform::identity() },
+            "skewY" => Transform { m21: argument(0, 0.0).to_radians().tan(), ..Transform::identity() },
+            _ => Transform::identity(),
+        };
+        transform = transform * next;
+    }
+    transform
+}
+
+// Parses a color: a hex color, `rgb()` or `rgba()`, or a basic color keyword.
+fn parse_color(text: &str) -> Option<Color> {
+    let text = text.trim().to_ascii_lowercase();
+    if let Some(hex) = text.strip_prefix('#') {
+        let digits: Vec<u8> = hex.chars().map(|c| c.to_digit(16).map(|digit| digit as u8)).collect::<Option<_>>()?;
+        return match digits[..] {
+            [r, g, b] => Some(Color::new(r * 17, g * 17, b * 17, 255)),
+            [r1, r2, g1, g2, b1, b2] => Some(Color::new(r1 << 4 | r2, g1 << 4 | g2, b1 << 4 | b2, 255)),
+            _ => None,
+        };
+    }
+    if let Some(arguments) = text.strip_prefix("rgba(").or(text.strip_prefix("rgb(")) {
+        let channels: Vec<f32> = arguments
+            .trim_end_matches(')')
+            .split([',', ' ', '/'])
+            .filter(|part| !part.is_empty())
+            .map(|part| match part.strip_suffix('%') {
+                Some(percent) => percent.parse::<f32>().map(|percent| percent * 2.55),
+                None => part.parse::<f32>(),
+            })
+            .collect::<Result<_, _>>()
+            .ok()?;
+        let channel = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+        return match channels[..] {
+            [r, g, b] => Some(Color::new(channel(r), channel(g), channel(b), 255)),
+            // The alpha of rgba() is a fraction, unless it's a percentage
+            [r, g, b, a] => Some(Color::new(channel(r), channel(g), channel(b), channel(if a <= 1.0 { a * 255.0 } else { a }))),
+            _ => None,
+        };
+    }
+    let [r, g, b] = match text.as_str() {
+        "black" => [0, 0, 0],
+        "white" => [255, 255, 255],
+        "red" => [255, 0, 0],
+        "lime" => [0, 255, 0],
+        "green" => [0, 128, 0],
+        "blue" => [0, 0, 255],
+        "yellow" => [255, 255, 0],
+        "cyan" | "aqua" => [0, 255, 255],
+        "magenta" | "fuchsia" => [255, 0, 255],
+        "gray" | "grey" => [128, 128, 128],
+        "silver" => [192, 192, 192],
+        "maroon" => [128, 0, 0],
+        "olive" => [128, 128, 0],
+        "navy" => [0, 0, 128],
+        "purple" => [128, 0, 128],
+        "teal" => [0, 128, 128],
+        "orange" => [255, 165, 0],
+        "transparent" => return Some(Color::new(0, 0, 0, 0)),
+        _ => return None,
+    };
+    Some(Color::new(r, g, b, 255))
+}
+
+// Parses a length in pixels, converting absolute units at 96 pixels per inch. Percentages
+// aren't supported.
+fn length(text: &str) -> Option<f32> {
+    let text = text.trim();
+    let split = text.find(|c: char| c.is_ascii_alphabetic() || c == '%').unwrap_or(text.len());
+    let (value, unit) = text.split_at(split);
+    let value: f32 = value.trim().parse().ok()?;
+    let scale = match unit {
+        "" | "px" => 1.0,
+        "pt" => 96.0 / 72.0,
+        "pc" => 16.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        _ => return None,
+    };
+    Some(value * scale)
+}
+
+fn number(text: &str) -> Option<f32> {
+    let text = text.trim();
+    match text.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f32>().ok().map(|percent| percent / 100.0),
+        None => text.parse().ok(),
+    }
+}
+
+// Splits a list of numbers separated by commas and whitespace.
+fn numbers(text: &str) -> Vec<f32> {
+    text.split(|c: char| c == ',' || c.is_whitespace()).filter(|part| !part.is_empty()).filter_map(|part| part.parse().ok()).collect()
+}
+
+// Splits CSS declarations such as `fill: red; stroke: none` into names and values.
+fn declarations_of(text: &str) -> Vec<(String, String)> {
+    text.split(';')
+        .filter_map(|declaration| declaration.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+// An element of an XML document.
+struct Node {
+    name: String,
+    attributes: Vec<(String, String)>,
+    /// Indices of the child elements in the list of nodes.
+    children: Vec<usize>,
+    /// The text directly inside the element.
+    text: String,
+}
+
+impl Node {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(attribute, _)| attribute == name).map(|(_, value)| value.as_str())
+    }
+
+    // The style properties set on the element, from lowest to highest precedence: presentation
+    // attributes, class rules and the `style` attribute.
+    fn properties(&self, classes: &HashMap<String, Vec<(String, String)>>) -> HashMap<String, String> {
+        let mut properties: HashMap<String, String> = self.attributes.iter().filter(|(name, _)| name != "style").cloned().collect();
+        for class in self.attribute("class").unwrap_or_default().split_whitespace() {
+            properties.extend(classes.get(class).into_iter().flatten().cloned());
+        }
+        properties.extend(declarations_of(self.attribute("style").unwrap_or_default()));
+        properties
+    }
+}
+
+// Parses the elements of an XML document into a flat list in document order, each with the
+// indices of its children.
+fn parse_xml(text: &str) -> Result<Vec<Node>, String> {
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        if let Some(&parent) = open.last() {
+            nodes[parent].text.push_str(&rest[..start]);
+        }
+        rest = &rest[start..];
+        // Comments, declarations, processing instructions and character data
+        for (opening, closing) in [("<!--", "-->"), ("<![CDATA[", "]]>"), ("<?", "?>"), ("<!", ">")] {
+            if let Some(inner) = rest.strip_prefix(opening) {
+                let end = inner.find(closing).ok_or("unterminated markup")?;
+                if opening == "<![CDATA[" {
+                    if let Some(&parent) = open.last() {
+                        nodes[parent].text.push_str(&inner[..end]);
+                    }
+                }
+                rest = &inner[end + closing.len()..];
+                break;
+            }
+        }
+        if !rest.starts_with('<') || rest.starts_with("<!") || rest.starts_with("<?") {
+            continue;
+        }
+        if let Some(inner) = rest.strip_prefix("</") {
+            let end = inner.find('>').ok_or("unterminated end tag")?;
+            open.pop().ok_or("unexpected end tag")?;
+            rest = &inner[end + 1..];
+            continue;
+        }
+
+        let mut inner = &rest[1..];
+        let name_end = inner.find(|c: char| c.is_whitespace() || c == '>' || c == '/').ok_or("unterminated tag")?;
+        // Elements may be written with a namespace prefix, as in `svg:path`
+        let name = inner[..name_end].strip_prefix("svg:").unwrap_or(&inner[..name_end]).to_string();
+        inner = &inner[name_end..];
+        let mut attributes = Vec::new();
+        let closed = loop {
+            inner = inner.trim_start();
+            if let Some(after) = inner.strip_prefix("/>") {
+                inner = after;
+                break true;
+            }
+            if let Some(after) = inner.strip_prefix('>') {
+                inner = after;
+                break false;
+            }
+            let equals = inner.find('=').ok_or("invalid attribute")?;
+            let attribute = inner[..equals].trim().to_string();
+            inner = inner[equals + 1..].trim_start();
+            let quote = inner.chars().next().filter(|&c| c == '"' || c == '\'').ok_or("unquoted attribute value")?;
+            let end = inner[1..].find(quote).ok_or("unterminated attribute value")?;
+            attributes.push((attribute, decode_entities(&inner[1..end + 1])));
+            inner = &inner[end + 2..];
+        };
+        rest = inner;
+
+        let index = nodes.len();
+        if let Some(&parent) = open.last() {
+            nodes[parent].children.push(index);
+        }
+        nodes.push(Node { name, attributes, children: Vec::new(), text: String::new() });
+        if !closed {
+            open.push(index);
+        }
+    }
+    Ok(nodes)
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
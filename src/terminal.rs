@@ -0,0 +1,584 @@
+//! A backend that shows sketches in a terminal, for demos over SSH and machines without a
+//! display server:
+//!
+//! ```no_run
+//! # use simple_sketch::app;
+//! # use simple_sketch::app::{Application, Context};
+//! # use simple_sketch::canvas::Canvas;
+//! # use simple_sketch::error::SketchError;
+//! # #[derive(Default)]
+//! # struct MySketch;
+//! # impl Application for MySketch {
+//! #     fn draw(&mut self, _: &mut Canvas, _: &Context) {}
+//! # }
+//! fn main() -> Result<(), SketchError> {
+//!     app::run_in_terminal::<MySketch>()
+//! }
+//! ```
+//!
+//! Every character cell shows two pixels, one above the other, as a `▀` in the color of the top
+//! pixel on a background of the color of the bottom one, with 24-bit ANSI colors. The canvas
+//! keeps the size in the sketch's `Settings` and is scaled to fit the terminal, which most
+//! terminals can show at a few dozen frames per second. Only the rows of characters that
+//! changed are written again.
+//!
+//! Keys, the mouse and its wheel are read from the terminal as far as it reports them. Terminals
+//! only report key presses, never releases, so a key counts as held down for a moment after
+//! each press and for as long as it repeats. Ctrl+C closes the sketch like closing its window.
+//!
+//! The terminal is switched to raw mode with `stty`, so this works on Linux and macOS.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::app::Settings;
+use crate::backend::{Backend, FrameRegion, InputState};
+use crate::cursor::CursorStyle;
+use crate::error::SketchError;
+use crate::geom::Point;
+use crate::input::{Key, MouseButton};
+
+/// How long a key counts as held after a press, a bit longer than the usual interval between
+/// repeats of a held key.
+const KEY_HOLD: Duration = Duration::from_millis(100);
+
+/// How often the size of the terminal is checked.
+const SIZE_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The letter keys, in the order of the alphabet.
+const LETTERS: [Key; 26] = [
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+    Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+];
+
+/// The number keys, from 0 to 9.
+const DIGITS: [Key; 10] = [Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9];
+
+/// A backend drawing frames as colored characters in the terminal the sketch runs in.
+pub struct TerminalBackend {
+    /// The size of the canvas in window pixels, which the terminal reports as its size.
+    size: (usize, usize),
+    /// The terminal's settings before it was switched to raw mode, as printed by `stty -g`.
+    saved_settings: String,
+    received: Arc<Mutex<Received>>,
+    /// Tells the reading thread to stop.
+    stop: Arc<AtomicBool>,
+    /// Where the frame is drawn in the terminal.
+    layout: Layout,
+    /// The terminal's size in characters, as columns and rows.
+    terminal_size: (usize, usize),
+    size_checked: Instant,
+    /// The last presented frame, drawn again when the terminal's size changes.
+    frame: Vec<u32>,
+    frame_size: (usize, usize),
+    frame_time: Duration,
+    next_frame: Instant,
+    /// The escape sequences for the next frame, kept to reuse their allocation.
+    output: String,
+}
+
+// Input read from the terminal since the previous sample
+#[derive(Default)]
+struct Received {
+    /// The keys pressed, when they were last pressed, and whether a sample reported them yet.
+    keys: Vec<(Key, Instant, bool)>,
+    /// The cell under the mouse as its column and pixel row, counting from 0.
+    mouse: Option<(usize, usize)>,
+    mouse_pressed: [bool; 3],
+    wheel: f32,
+    /// Whether Ctrl+C was pressed or the terminal can't be read anymore.
+    closed: bool,
+}
+
+// The area of the terminal a frame is drawn to, in pixels of half a character
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Layout {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl TerminalBackend {
+    /// Switches the terminal to raw mode and clears it, to show a sketch configured by
+    /// `settings`. Fails when the sketch doesn't run in a terminal.
+    pub fn new(settings: &Settings) -> Result<Self, SketchError> {
+        if settings.width == 0 || settings.height == 0 {
+            return Err(SketchError::InvalidSize { width: settings.width, height: settings.height });
+        }
+        let saved_settings = stty(&["-g"])?.trim().to_string();
+        // Reads return after a tenth of a second without input, so the thread can notice it should stop
+        stty(&["raw", "-echo", "min", "0", "time", "1"])?;
+        let tty = File::open("/dev/tty").map_err(|error| SketchError::WindowCreation(format!("could not read the terminal: {error}")))?;
+
+        let received = Arc::new(Mutex::new(Received::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        spawn_reader(tty, Arc::clone(&received), Arc::clone(&stop));
+
+        let now = Instant::now();
+        let mut backend = TerminalBackend {
+            size: (settings.width, settings.height),
+            saved_settings,
+            received,
+            stop,
+            layout: Layout::default(),
+            terminal_size: (0, 0),
+            size_checked: now,
+            frame: Vec::new(),
+            frame_size: (0, 0),
+            frame_time: Duration::from_secs_f32(1.0 / settings.frame_rate.max(1.0)),
+            next_frame: now,
+            output: String::new(),
+        };
+        backend.terminal_size = terminal_size()?;
+        backend.layout = Layout::fit(backend.size, backend.terminal_size);
+        // Switch to the alternate screen, hide the cursor, and report mouse moves, buttons and
+        // the wheel in SGR format
+        backend.write("\x1b[?1049h\x1b[?25l\x1b[2J\x1b[?1003h\x1b[?1006h")?;
+        Ok(backend)
+    }
+
+    // Checks the size of the terminal now and then, and lays the frame out again when it changed
+    fn check_terminal_size(&mut self) -> Result<bool, SketchError> {
+        if self.size_checked.elapsed() < SIZE_CHECK_INTERVAL {
+            return Ok(false);
+        }
+        self.size_checked = Instant::now();
+        let size = terminal_size()?;
+        if size == self.terminal_size {
+            return Ok(false);
+        }
+        self.terminal_size = size;
+        self.layout = Layout::fit(self.size, size);
+        self.write("\x1b[2J")?;
+        Ok(true)
+    }
+
+    // Draws the rows of characters showing the frame's pixel rows from `first_row` to
+    // `last_row`, inclusive
+    fn draw_rows(&mut self, first_row: usize, last_row: usize) -> Result<(), SketchError> {
+        let Layout { x, y, width, height } = self.layout;
+        let (frame_width, frame_height) = self.frame_size;
+        if width == 0 || height == 0 || frame_width == 0 || frame_height == 0 {
+            return Ok(());
+        }
+        // The pixel rows of the layout, and the rows of characters, showing the frame's rows
+        let top = (first_row * height / frame_height).min(height - 1);
+        let bottom = ((last_row + 1) * height).div_ceil(frame_height).clamp(top + 1, height);
+        let (first_cell_row, last_cell_row) = ((y + top) / 2, (y + bottom - 1) / 2);
+
+        let mut output = std::mem::take(&mut self.output);
+        output.clear();
+        for cell_row in first_cell_row..=last_cell_row {
+            output.push_str(&format!("\x1b[{};{}H", cell_row + 1, x + 1));
+            let mut colors = (None, None);
+            for column in 0..width {
+                // Pixel rows of the terminal above or below the frame show the terminal's background
+                let pixel = |terminal_row: usize| (terminal_row >= y && terminal_row < y + height).then(|| self.sample(column, terminal_row - y));
+                let (upper, lower) = (pixel(cell_row * 2), pixel(cell_row * 2 + 1));
+                if upper != colors.0 {
+                    push_color(&mut output, 38, upper);
+                }
+                if lower != colors.1 {
+                    push_color(&mut output, 48, lower);
+                }
+                colors = (upper, lower);
+                output.push('▀');
+            }
+            output.push_str("\x1b[0m");
+        }
+        let written = self.write(&output);
+        self.output = output;
+        written
+    }
+
+    // The average color of the frame's pixels shown by the pixel at `(column, row)` of the layout
+    fn sample(&self, column: usize, row: usize) -> (u8, u8, u8) {
+        let (frame_width, frame_height) = self.frame_size;
+        let span = |position: usize, size: usize, frame_size: usize| {
+            let start = position * frame_size / size;
+            (start, ((position + 1) * frame_size / size).clamp(start + 1, frame_size))
+        };
+        let (x1, x2) = span(column, self.layout.width, frame_width);
+        let (y1, y2) = span(row, self.layout.height, frame_height);
+        let mut sum = [0u32; 3];
+        for y in y1..y2 {
+            for &pixel in &self.frame[y * frame_width + x1..y * frame_width + x2] {
+                sum[0] += (pixel >> 16) & 0xFF;
+                sum[1] += (pixel >> 8) & 0xFF;
+                sum[2] += pixel & 0xFF;
+            }
+        }
+        let count = ((x2 - x1) * (y2 - y1)) as u32;
+        ((sum[0] / count) as u8, (sum[1] / count) as u8, (sum[2] / count) as u8)
+    }
+
+    // Waits until it's time for the next frame, at the sketch's frame rate
+    fn wait_for_frame(&mut self) {
+        let now = Instant::now();
+        if let Some(rest) = self.next_frame.checked_duration_since(now) {
+            thread::sleep(rest);
+        }
+        self.next_frame = (self.next_frame + self.frame_time).max(now);
+    }
+
+    fn write(&self, text: &str) -> Result<(), SketchError> {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(text.as_bytes()).and_then(|()| stdout.flush()).map_err(|error| SketchError::WindowUpdate(error.to_string()))
+    }
+
+    fn received(&self) -> MutexGuard<'_, Received> {
+        self.received.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Backend for TerminalBackend {
+    fn is_open(&self) -> bool {
+        !self.received().closed
+    }
+
+    fn input(&mut self) -> InputState {
+        let (layout, size) = (self.layout, self.size);
+        let mut received = self.received();
+        let now = Instant::now();
+        received.keys.retain(|&(_, pressed, reported)| !reported || now.duration_since(pressed) < KEY_HOLD);
+        let mut keys = Vec::new();
+        for (key, _, reported) in &mut received.keys {
+            *reported = true;
+            if !keys.contains(key) {
+                keys.push(*key);
+            }
+        }
+        let mouse_position = received.mouse.and_then(|(column, row)| layout.window_position(column, row, size));
+        let wheel = std::mem::take(&mut received.wheel);
+        InputState {
+            size,
+            mouse_position,
+            mouse_pressed: received.mouse_pressed,
+            wheel: (wheel != 0.0).then(|| Point::new(0.0, wheel)),
+            keys,
+            dropped_files: Vec::new(),
+        }
+    }
+
+    fn present(&mut self, pixels: &[u32], width: usize, height: usize) -> Result<(), SketchError> {
+        self.check_terminal_size()?;
+        self.frame.clear();
+        self.frame.extend_from_slice(pixels);
+        self.frame_size = (width, height);
+        self.draw_rows(0, height.saturating_sub(1))?;
+        self.wait_for_frame();
+        Ok(())
+    }
+
+    fn present_changed(&mut self, pixels: &[u32], width: usize, height: usize, changed: FrameRegion) -> Result<(), SketchError> {
+        if self.check_terminal_size()? || (width, height) != self.frame_size {
+            return self.present(pixels, width, height);
+        }
+        self.frame.copy_from_slice(pixels);
+        if changed.height > 0 {
+            self.draw_rows(changed.y, changed.y + changed.height - 1)?;
+        }
+        self.wait_for_frame();
+        Ok(())
+    }
+
+    fn poll(&mut self) -> Result<(), SketchError> {
+        if self.check_terminal_size()? {
+            self.draw_rows(0, self.frame_size.1.saturating_sub(1))?;
+        }
+        self.wait_for_frame();
+        Ok(())
+    }
+
+    /// Does nothing: the terminal's cursor stays hidden, while the sketch's software cursor is
+    /// drawn into the frames like in any window.
+    fn set_cursor(&mut self, _visible: bool, _style: CursorStyle) {}
+}
+
+impl Drop for TerminalBackend {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Stop reporting the mouse, show the cursor and go back to the normal screen
+        let _ = self.write("\x1b[0m\x1b[?1006l\x1b[?1003l\x1b[?25h\x1b[?1049l");
+        let _ = stty(&[&self.saved_settings]);
+    }
+}
+
+impl Layout {
+    // The largest area with the aspect ratio of `size` that fits a terminal of `columns` by
+    // `rows` characters, centered in it
+    fn fit((width, height): (usize, usize), (columns, rows): (usize, usize)) -> Self {
+        let scale = f32::min(columns as f32 / width as f32, (rows * 2) as f32 / height as f32);
+        let fitted_width = ((width as f32 * scale) as usize).clamp(1, columns.max(1));
+        let fitted_height = ((height as f32 * scale) as usize).clamp(1, (rows * 2).max(1));
+        Layout { x: (columns - fitted_width.min(columns)) / 2, y: (rows * 2 - fitted_height.min(rows * 2)) / 2, width: fitted_width, height: fitted_height }
+    }
+
+    // The position in window pixels of the center of the pixel at `(column, row)` of the
+    // terminal, or None outside the frame
+    fn window_position(&self, column: usize, row: usize, (width, height): (usize, usize)) -> Option<Point> {
+        let (x, y) = (column.checked_sub(self.x)?, row.checked_sub(self.y)?);
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let scale = Point::new(width as f32 / self.width as f32, height as f32 / self.height as f32);
+        Some(Point::new((x as f32 + 0.5) * scale.x, (y as f32 + 0.5) * scale.y))
+    }
+}
+
+// Sets the foreground (38) or background (48) color, or resets it to the terminal's own color
+fn push_color(output: &mut String, target: u8, color: Option<(u8, u8, u8)>) {
+    match color {
+        Some((r, g, b)) => output.push_str(&format!("\x1b[{target};2;{r};{g};{b}m")),
+        None => output.push_str(&format!("\x1b[{}m", target + 1)),
+    }
+}
+
+// Runs stty on the terminal and returns what it printed
+fn stty(arguments: &[&str]) -> Result<String, SketchError> {
+    let tty = File::open("/dev/tty").map_err(|error| SketchError::WindowCreation(format!("not running in a terminal: {error}")))?;
+    let output = Command::new("stty")
+        .args(arguments)
+        .stdin(tty)
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|error| SketchError::WindowCreation(format!("could not run stty: {error}")))?;
+    if !output.status.success() {
+        let errors = String::from_utf8_lossy(&output.stderr);
+        return Err(SketchError::WindowCreation(format!("could not configure the terminal: {}", errors.trim())));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// The size of the terminal as columns and rows of characters
+fn terminal_size() -> Result<(usize, usize), SketchError> {
+    let size = stty(&["size"])?;
+    let mut numbers = size.split_whitespace().filter_map(|number| number.parse().ok());
+    match (numbers.next(), numbers.next()) {
+        (Some(rows), Some(columns)) => Ok((columns, rows)),
+        _ => Err(SketchError::WindowCreation(format!("could not read the terminal's size from {size:?}"))),
+    }
+}
+
+// Reads the terminal's input on a background thread
+fn spawn_reader(mut tty: File, received: Arc<Mutex<Received>>, stop: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let mut decoder = Decoder::default();
+        let mut bytes = [0; 256];
+        while !stop.load(Ordering::Relaxed) {
+            match tty.read(&mut bytes) {
+                Ok(0) => {}
+                Ok(read) => {
+                    let events = decoder.push(&bytes[..read]);
+                    let mut received = received.lock().unwrap_or_else(PoisonError::into_inner);
+                    for event in events {
+                        received.apply(event);
+                    }
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted || error.kind() == io::ErrorKind::WouldBlock => {}
+                Err(_) => {
+                    received.lock().unwrap_or_else(PoisonError::into_inner).closed = true;
+                    break;
+                }
+            }
+        }
+    });
+}
+
+// Something that happened in the terminal
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Event {
+    /// A key was pressed.
+    Key(Key),
+    /// The mouse is over the cell at `(column, row)`, counting from 0, and a button was pressed
+    /// or released there.
+    Mouse { column: usize, row: usize, button: Option<(MouseButton, bool)> },
+    /// The wheel turned up, for positive amounts, or down.
+    Wheel(f32),
+    /// Ctrl+C was pressed.
+    Interrupt,
+}
+
+impl Received {
+    fn apply(&mut self, event: Event) {
+        match event {
+            Event::Key(key) => {
+                let now = Instant::now();
+                match self.keys.iter_mut().find(|(held, _, _)| *held == key) {
+                    Some(held) => *held = (key, now, false),
+                    None => self.keys.push((key, now, false)),
+                }
+            }
+            Event::Mouse { column, row, button } => {
+                // The pointer is in the middle of the cell, between its two pixels
+                self.mouse = Some((column, row * 2 + 1));
+                if let Some((button, pressed)) = button {
+                    self.mouse_pressed[button.index()] = pressed;
+                }
+            }
+            Event::Wheel(amount) => self.wheel += amount,
+            Event::Interrupt => self.closed = true,
+        }
+    }
+}
+
+// Turns the bytes typed into the terminal into events, keeping escape sequences that were split
+// between reads until they are complete
+#[derive(Default)]
+struct Decoder {
+    pending: Vec<u8>,
+}
+
+impl Decoder {
+    fn push(&mut self, bytes: &[u8]) -> Vec<Event> {
+        self.pending.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        let mut start = 0;
+        while start < self.pending.len() {
+            match decode(&self.pending[start..], &mut events) {
+                Some(length) => start += length,
+                None => break,
+            }
+        }
+        self.pending.drain(..start);
+        events
+    }
+}
+
+// Decodes the event at the start of `bytes`, and returns the number of bytes it took, or None
+// when it needs more bytes
+fn decode(bytes: &[u8], events: &mut Vec<Event>) -> Option<usize> {
+    let key = |key: Key| Event::Key(key);
+    match bytes[0] {
+        0x1B => {
+            // A lone escape is the Escape key, otherwise it starts a sequence
+            let Some(&kind) = bytes.get(1) else {
+                events.push(key(Key::Escape));
+                return Some(1);
+            };
+            match kind {
+                b'[' => decode_sequence(bytes, events),
+                b'O' => {
+                    let &code = bytes.get(2)?;
+                    if let Some(index) = b"PQRS".iter().position(|&c| c == code) {
+                        events.push(key([Key::F1, Key::F2, Key::F3, Key::F4][index]));
+                    }
+                    Some(3)
+                }
+                _ => {
+                    // Alt with another key
+                    events.push(key(Key::LeftAlt));
+                    Some(1)
+                }
+            }
+        }
+        0x03 => {
+            events.push(Event::Interrupt);
+            Some(1)
+        }
+        b'\r' | b'\n' => {
+            events.push(key(Key::Enter));
+            Some(1)
+        }
+        b'\t' => {
+            events.push(key(Key::Tab));
+            Some(1)
+        }
+        0x7F | 0x08 => {
+            events.push(key(Key::Backspace));
+            Some(1)
+        }
+        control @ 0x01..=0x1A => {
+            events.extend([key(Key::LeftCtrl), key(LETTERS[(control - 1) as usize])]);
+            Some(1)
+        }
+        letter @ b'a'..=b'z' => {
+            events.push(key(LETTERS[(letter - b'a') as usize]));
+            Some(1)
+        }
+        letter @ b'A'..=b'Z' => {
+            events.extend([key(Key::LeftShift), key(LETTERS[(letter - b'A') as usize])]);
+            Some(1)
+        }
+        digit @ b'0'..=b'9' => {
+            events.push(key(DIGITS[(digit - b'0') as usize]));
+            Some(1)
+        }
+        symbol => {
+            let symbol_key = match symbol {
+                b' ' => Some(Key::Space),
+                b'\'' => Some(Key::Apostrophe),
+                b'`' => Some(Key::Backquote),
+                b'\\' => Some(Key::Backslash),
+                b',' => Some(Key::Comma),
+                b'=' => Some(Key::Equal),
+                b'[' => Some(Key::LeftBracket),
+                b'-' => Some(Key::Minus),
+                b'.' => Some(Key::Period),
+                b']' => Some(Key::RightBracket),
+                b';' => Some(Key::Semicolon),
+                b'/' => Some(Key::Slash),
+                _ => None,
+            };
+            events.extend(symbol_key.map(key));
+            // Skip the rest of multi-byte characters
+            let length = match symbol {
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                0xF0..=0xF7 => 4,
+                _ => 1,
+            };
+            Some(length.min(bytes.len()))
+        }
+    }
+}
+
+// Decodes a control sequence starting with `ESC [`: arrows, navigation keys and mouse reports
+fn decode_sequence(bytes: &[u8], events: &mut Vec<Event>) -> Option<usize> {
+    // Parameters and intermediate bytes end at the final byte, from `@` to `~`
+    let end = 2 + bytes[2..].iter().position(|byte| (0x40..=0x7E).contains(byte))?;
+    let parameters = std::str::from_utf8(&bytes[2..end]).unwrap_or("");
+    let final_byte = bytes[end];
+    let key = match (parameters, final_byte) {
+        (_, b'A') => Some(Key::Up),
+        (_, b'B') => Some(Key::Down),
+        (_, b'C') => Some(Key::Right),
+        (_, b'D') => Some(Key::Left),
+        (_, b'H') | ("1", b'~') | ("7", b'~') => Some(Key::Home),
+        (_, b'F') | ("4", b'~') | ("8", b'~') => Some(Key::End),
+        ("2", b'~') => Some(Key::Insert),
+        ("3", b'~') => Some(Key::Delete),
+        ("5", b'~') => Some(Key::PageUp),
+        ("6", b'~') => Some(Key::PageDown),
+        _ => None,
+    };
+    if let Some(key) = key {
+        events.push(Event::Key(key));
+    } else if let Some(report) = parameters.strip_prefix('<').filter(|_| final_byte == b'M' || final_byte == b'm') {
+        let mut numbers = report.split(';').map(|number| number.parse::<usize>().ok());
+        if let (Some(Some(code)), Some(Some(column)), Some(Some(row))) = (numbers.next(), numbers.next(), numbers.next()) {
+            let (column, row) = (column.saturating_sub(1), row.saturating_sub(1));
+            if code & 64 != 0 {
+                events.push(Event::Mouse { column, row, button: None });
+                events.push(Event::Wheel(if code & 1 == 0 { 1.0 } else { -1.0 }));
+            } else {
+                let button = match code & 3 {
+                    0 => Some(MouseButton::Left),
+                    1 => Some(MouseButton::Middle),
+                    2 => Some(MouseButton::Right),
+                    _ => None,
+                };
+                // Moves report the buttons held during them, which were already pressed
+                let moved = code & 32 != 0;
+                events.push(Event::Mouse { column, row, button: button.filter(|_| !moved).map(|button| (button, final_byte == b'M')) });
+            }
+        }
+    }
+    Some(end + 1)
+}
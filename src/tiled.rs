@@ -0,0 +1,319 @@
+//! Rendering pictures far larger than a window, such as posters for print, one tile at a time.
+//!
+//! `TiledRenderer` draws a picture from scratch once per tile and streams the tiles out.
+//! `TiledCanvas` keeps a picture of mural scale in memory as tiles that are only allocated
+//! once something is drawn on them, so that drawing can build up over many calls.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::mem;
+use std::path::Path;
+
+use crate::canvas::Canvas;
+use crate::error::SketchError;
+use crate::geom::{Point, Rect};
+use crate::pixelbuffer::{self, PixelBuffer};
+use crate::png::{PngColor, PngWriter};
+
+/// Width and height in pixels of the tiles of a `TiledCanvas` unless set otherwise.
+const DEFAULT_TILE_SIZE: usize = 1024;
+
+/// Renders a drawing at a multiple of its canvas size by drawing it once per tile, either into
+/// a pixel buffer or straight to a PNG file, so that only one row of tiles is ever in memory.
+///
+/// Each tile is a fresh canvas of the logical size whose pixel buffer covers only the tile, so
+/// drawing code works unchanged. It has to draw the same picture every time it is called:
+/// animations need a fixed time, and random values a fixed seed. Drawing that builds up over
+/// many frames can't be rendered in tiles.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simple_sketch::color::Color;
+/// # use simple_sketch::error::SketchError;
+/// # use simple_sketch::geom::Point;
+/// # use simple_sketch::tiled::TiledRenderer;
+/// // A 1000x1000 sketch as a 12000x12000 poster
+/// TiledRenderer::new(1000, 1000).scale(12.0).render_png("poster.png", |canvas| {
+///     canvas.background(Color::new(255, 255, 255, 255));
+///     canvas.circle(Point::new(500.0, 500.0), 800.0);
+/// })?;
+/// # Ok::<(), SketchError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TiledRenderer {
+    width: usize,
+    height: usize,
+    scale: f32,
+    samples: usize,
+    tile_size: usize,
+}
+
+impl TiledRenderer {
+    /// Creates a renderer for drawings on a canvas of `width` by `height` pixels, rendered at
+    /// their own size in tiles of 1024 by 1024 pixels.
+    pub fn new(width: usize, height: usize) -> Self {
+        TiledRenderer { width, height, scale: 1.0, samples: 1, tile_size: DEFAULT_TILE_SIZE }
+    }
+
+    /// Sets the number of output pixels per canvas pixel along each axis. Shapes, stroke
+    /// weights and images are scaled along, like with `Canvas::set_pixel_density`.
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Supersamples every output pixel: tiles are drawn at `samples` times the output
+    /// resolution along each axis and then averaged down, for antialiasing far smoother than
+    /// the canvas's own. Drawing time grows with the square of `samples`.
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Sets the width and height of the tiles in output pixels. Larger tiles draw faster,
+    /// smaller tiles take less memory.
+    pub fn tile_size(mut self, tile_size: usize) -> Self {
+        self.tile_size = tile_size;
+        self
+    }
+
+    /// The size of the rendered picture in pixels.
+    pub fn output_size(&self) -> (usize, usize) {
+        let scale = |size: usize| (size as f32 * self.scale).round() as usize;
+        (scale(self.width), scale(self.height))
+    }
+
+    /// Renders the picture `draw` draws into a pixel buffer of `output_size`. The tiles still
+    /// limit the memory used for drawing, but the whole picture has to fit in memory.
+    pub fn render(&self, mut draw: impl FnMut(&mut Canvas)) -> Result<PixelBuffer, SketchError> {
+        let (width, height) = self.checked_output_size()?;
+        let mut result = PixelBuffer::new(width, height);
+        let mut top = 0;
+        self.render_bands(&mut draw, |band| {
+            result.get_buffer_mut()[top * width..top * width + band.len()].copy_from_slice(band);
+            top += band.len() / width;
+            Ok(())
+        })?;
+        Ok(result)
+    }
+
+    /// Renders the picture `draw` draws to a PNG file with an alpha channel, creating missing
+    /// directories. Only one row of tiles is in memory at a time.
+    pub fn render_png(&self, path: impl AsRef<Path>, mut draw: impl FnMut(&mut Canvas)) -> Result<(), SketchError> {
+        let path = path.as_ref();
+        let (width, height) = self.checked_output_size()?;
+        let context = |e: io::Error| SketchError::from(io::Error::new(e.kind(), format!("could not save {}: {e}", path.display())));
+        let create = || {
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            PngWriter::new(BufWriter::new(File::create(path)?), width, height, PngColor::Rgba)
+        };
+        let mut png = create().map_err(context)?;
+        let mut rows = Vec::new();
+        self.render_bands(&mut draw, |band| {
+            rows.clear();
+            rows.extend(band.iter().flat_map(|&pixel| {
+                let [a, r, g, b] = pixel.to_be_bytes();
+                [r, g, b, a]
+            }));
+            png.write_rows(&rows).map_err(context)
+        })?;
+        png.finish().map_err(context)
+    }
+
+    fn checked_output_size(&self) -> Result<(usize, usize), SketchError> {
+        let (width, height) = self.output_size();
+        if width == 0 || height == 0 || self.tile_size == 0 || self.samples == 0 || !self.scale.is_finite() {
+            return Err(SketchError::InvalidSize { width, height });
+        }
+        Ok((width, height))
+    }
+
+    // Draws the picture one row of tiles at a time and passes the pixels of each row of tiles,
+    // the full width of the output, to `on_band`, from top to bottom.
+    fn render_bands(&self, draw: &mut dyn FnMut(&mut Canvas), mut on_band: impl FnMut(&[u32]) -> Result<(), SketchError>) -> Result<(), SketchError> {
+        let (width, height) = self.checked_output_size()?;
+        let samples = self.samples;
+        let mut band = Vec::new();
+        for top in (0..height).step_by(self.tile_size) {
+            let tile_height = self.tile_size.min(height - top);
+            band.clear();
+            band.resize(width * tile_height, 0);
+            for left in (0..width).step_by(self.tile_size) {
+                let tile_width = self.tile_size.min(width - left);
+                let origin = Point::new((left * samples) as f32, (top * samples) as f32);
+                let density = self.scale * samples as f32;
+                let mut canvas = Canvas::tile(self.width, self.height, density, origin, tile_width * samples, tile_height * samples);
+                draw(&mut canvas);
+                canvas.flush();
+                let tile = if samples == 1 { canvas.pixel_buffer } else { canvas.pixel_buffer.downsample(samples) };
+                for (y, row) in tile.get_buffer().chunks(tile_width).enumerate() {
+                    band[y * width + left..y * width + left + tile_width].copy_from_slice(row);
+                }
+            }
+            on_band(&band)?;
+        }
+        Ok(())
+    }
+}
+
+/// A canvas too large for one pixel buffer, such as a 30000 by 30000 mural, split into square
+/// tiles that are only allocated once something is drawn on them. Pixels of tiles never drawn
+/// on are transparent and take no memory.
+///
+/// Drawing goes through `draw`, which runs a drawing function once for each tile with a canvas
+/// of the full size whose pixel buffer holds only that tile, like `TiledRenderer`. Unlike
+/// there, tiles keep what was drawn on them, so pictures can be built up over many calls. Each
+/// call has to draw the same thing every time it runs in it, so random values need a seed
+/// that is set at the start of the function. `draw_area` only runs it for the tiles of an area,
+/// which is much faster for small additions to a large canvas.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use simple_sketch::color::Color;
+/// # use simple_sketch::error::SketchError;
+/// # use simple_sketch::geom::{Point, Rect};
+/// # use simple_sketch::tiled::TiledCanvas;
+/// # fn render(centers: &[Point], colors: &[Color]) -> Result<(), SketchError> {
+/// let mut mural = TiledCanvas::new(30000, 30000);
+/// for (i, center) in centers.iter().enumerate() {
+///     mural.draw_area(Rect::new(*center - Point::new(60.0, 60.0), *center + Point::new(60.0, 60.0)), |canvas| {
+///         canvas.set_fill(Some(colors[i % colors.len()]));
+///         canvas.circle(*center, 100.0);
+///     });
+/// }
+/// mural.save_png("mural.png")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TiledCanvas {
+    width: usize,
+    height: usize,
+    tile_size: usize,
+    /// The allocated tiles, by column and row.
+    tiles: HashMap<(usize, usize), PixelBuffer>,
+}
+
+impl TiledCanvas {
+    /// Creates a transparent canvas of `width` by `height` pixels with tiles of 1024 by 1024
+    /// pixels, none of them allocated yet.
+    pub fn new(width: usize, height: usize) -> Self {
+        TiledCanvas::with_tile_size(width, height, DEFAULT_TILE_SIZE)
+    }
+
+    /// Creates a transparent canvas with tiles of `tile_size` by `tile_size` pixels. Smaller
+    /// tiles waste less memory around sparse drawings, larger tiles draw faster. A tile size of
+    /// 0 is taken as 1.
+    pub fn with_tile_size(width: usize, height: usize, tile_size: usize) -> Self {
+        TiledCanvas { width, height, tile_size: tile_size.max(1), tiles: HashMap::new() }
+    }
+
+    /// The size of the canvas in pixels.
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// The number of tiles holding pixels. Each takes four bytes per pixel of memory.
+    pub fn allocated_tiles(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Runs `draw` for every tile of the canvas and keeps what it draws. See `TiledCanvas`.
+    pub fn draw(&mut self, draw: impl FnMut(&mut Canvas)) {
+        let all = Rect::new(Point::new(0.0, 0.0), Point::new(self.width as f32, self.height as f32));
+        self.draw_area(all, draw);
+    }
+
+    /// Runs `draw` only for the tiles overlapping `area`, in canvas pixels, and keeps what it
+    /// draws. Anything drawn outside those tiles is lost, so `area` has to cover the whole
+    /// drawing, including strokes and antialiased edges.
+    pub fn draw_area(&mut self, area: Rect, mut draw: impl FnMut(&mut Canvas)) {
+        let (columns, rows) = self.tile_ranges(area);
+        for row in rows {
+            for column in columns.clone() {
+                let (left, top, width, height) = self.tile_bounds(column, row);
+                let mut canvas = Canvas::tile(self.width, self.height, 1.0, Point::new(left as f32, top as f32), 0, 0);
+                let allocated = self.tiles.remove(&(column, row));
+                let was_allocated = allocated.is_some();
+                canvas.pixel_buffer = allocated.unwrap_or_else(|| PixelBuffer::new(width, height));
+                draw(&mut canvas);
+                canvas.flush();
+                let tile = mem::replace(&mut canvas.pixel_buffer, PixelBuffer::new(0, 0));
+                // Tiles the drawing didn't touch stay unallocated
+                if was_allocated || tile.get_buffer().iter().any(|&pixel| pixel != 0) {
+                    self.tiles.insert((column, row), tile);
+                }
+            }
+        }
+    }
+
+    /// The pixels of `area`, in canvas pixels, clipped to the canvas, as one pixel buffer.
+    pub fn region(&self, area: Rect) -> PixelBuffer {
+        let clamp = |value: f32, size: usize| (value.max(0.0) as usize).min(size);
+        let (left, top) = (clamp(area.min.x.floor(), self.width), clamp(area.min.y.floor(), self.height));
+        let (right, bottom) = (clamp(area.max.x.ceil(), self.width).max(left), clamp(area.max.y.ceil(), self.height).max(top));
+        let result_width = right - left;
+        let mut result = PixelBuffer::new(result_width, bottom - top);
+        let (columns, rows) = self.tile_ranges(area);
+        for (&(column, row), tile) in self.tiles.iter().filter(|((column, row), _)| columns.contains(column) && rows.contains(row)) {
+            let (tile_left, tile_top, _, _) = self.tile_bounds(column, row);
+            let (x1, y1) = (left.max(tile_left), top.max(tile_top));
+            let (x2, y2) = (right.min(tile_left + tile.width), bottom.min(tile_top + tile.height));
+            for y in y1..y2 {
+                let source = &tile.get_buffer()[(y - tile_top) * tile.width..][x1 - tile_left..x2 - tile_left];
+                result.get_buffer_mut()[(y - top) * result_width..][x1 - left..x2 - left].copy_from_slice(source);
+            }
+        }
+        result
+    }
+
+    /// Drops every tile, leaving the canvas transparent.
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    /// Saves the canvas as a PNG file with an alpha channel, creating missing directories.
+    /// Only one row of tiles is copied at a time.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), SketchError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(SketchError::InvalidSize { width: self.width, height: self.height });
+        }
+        let (width, height) = (self.width, self.height);
+        pixelbuffer::save_file(path.as_ref(), |out| {
+            let mut png = PngWriter::new(out, width, height, PngColor::Rgba)?;
+            let mut rows = Vec::new();
+            for top in (0..height).step_by(self.tile_size) {
+                let bottom = (top + self.tile_size).min(height);
+                let band = self.region(Rect::new(Point::new(0.0, top as f32), Point::new(width as f32, bottom as f32)));
+                rows.clear();
+                rows.extend(band.get_buffer().iter().flat_map(|&pixel| {
+                    let [a, r, g, b] = pixel.to_be_bytes();
+                    [r, g, b, a]
+                }));
+                png.write_rows(&rows)?;
+            }
+            png.finish()
+        })
+    }
+
+    // The columns and rows of the tiles overlapping `area`.
+    fn tile_ranges(&self, area: Rect) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+        let range = |min: f32, max: f32, size: usize| {
+            let tiles = size.div_ceil(self.tile_size);
+            let first = (min.max(0.0) as usize / self.tile_size).min(tiles);
+            let last = ((max.ceil().max(0.0) as usize).min(size)).div_ceil(self.tile_size);
+            first..last.max(first)
+        };
+        (range(area.min.x, area.max.x, self.width), range(area.min.y, area.max.y, self.height))
+    }
+
+    // The left and top edge and the size of the tile at `column` and `row`.
+    fn tile_bounds(&self, column: usize, row: usize) -> (usize, usize, usize, usize) {
+        let (left, top) = (column * self.tile_size, row * self.tile_size);
+        (left, top, self.tile_size.min(self.width - left), self.tile_size.min(self.height - top))
+    }
+}
@@ -0,0 +1,252 @@
+//! Keyframe animation of named properties.
+//!
+//! A `Timeline` holds one track of keyframes per named property. Each keyframe stores the
+//! easing used for the segment that starts at it, so every segment can have its own feel.
+//! Values are queried at the timeline's current time or at an explicit time.
+
+use std::collections::HashMap;
+
+use crate::color::Color;
+use crate::easing::Easing;
+use crate::geom::Point;
+use crate::tween::{Tween, Tweens};
+
+/// A value that can be animated by a `Timeline` or a `Tween`.
+pub trait Animatable: Copy {
+    /// Interpolates between `self` and `other`, where a `t` of 0 returns `self`.
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+
+    #[doc(hidden)]
+    fn tracks(timeline: &Timeline) -> &HashMap<String, Track<Self>>;
+
+    #[doc(hidden)]
+    fn tracks_mut(timeline: &mut Timeline) -> &mut HashMap<String, Track<Self>>;
+
+    #[doc(hidden)]
+    fn tweens(tweens: &Tweens) -> &HashMap<String, Tween<Self>>;
+
+    #[doc(hidden)]
+    fn tweens_mut(tweens: &mut Tweens) -> &mut HashMap<String, Tween<Self>>;
+}
+
+/// A single keyframe: a value at a point in time, plus the easing towards the next keyframe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe<T> {
+    pub time: f32,
+    pub value: T,
+    pub easing: Easing,
+}
+
+/// A time-sorted sequence of keyframes for one property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track<T> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Animatable> Track<T> {
+    pub fn new() -> Self {
+        Track { keyframes: Vec::new() }
+    }
+
+    /// Inserts a keyframe, keeping the track sorted. A keyframe at an existing time replaces it.
+    pub fn insert(&mut self, keyframe: Keyframe<T>) {
+        match self.keyframes.binary_search_by(|k| k.time.total_cmp(&keyframe.time)) {
+            Ok(index) => self.keyframes[index] = keyframe,
+            Err(index) => self.keyframes.insert(index, keyframe),
+        }
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe<T>] {
+        &self.keyframes
+    }
+
+    /// Returns the time of the last keyframe, or 0 for an empty track.
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    /// Evaluates the track at `time`. Times before the first or after the last keyframe
+    /// hold the first or last value, and NaN holds the first. Returns None for an empty track.
+    pub fn value_at(&self, time: f32) -> Option<T> {
+        let first = self.keyframes.first()?;
+        if time <= first.time || time.is_nan() {
+            return Some(first.value);
+        }
+
+        // Index of the first keyframe after `time`
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        if next == self.keyframes.len() {
+            return self.keyframes.last().map(|k| k.value);
+        }
+
+        let from = &self.keyframes[next - 1];
+        let to = &self.keyframes[next];
+        let progress = (time - from.time) / (to.time - from.time);
+        Some(from.value.interpolate(&to.value, from.easing.apply(progress)))
+    }
+}
+
+impl<T: Animatable> Default for Track<T> {
+    fn default() -> Self {
+        Track::new()
+    }
+}
+
+/// A collection of keyframed properties sharing a clock.
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    time: f32,
+    floats: HashMap<String, Track<f32>>,
+    points: HashMap<String, Track<Point>>,
+    colors: HashMap<String, Track<Color>>,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Timeline::default()
+    }
+
+    /// Returns the current time in seconds.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    /// Advances the current time by `dt` seconds.
+    pub fn advance(&mut self, dt: f32) {
+        self.time += dt;
+    }
+
+    /// Sets the current time to the exact start of `frame` at the given frame rate.
+    /// Exported animations should use this rather than accumulating `advance` calls,
+    /// so that every frame samples the timeline without rounding drift. Frame rates that
+    /// aren't positive seek to 0.
+    pub fn seek_frame(&mut self, frame: u64, fps: f32) {
+        self.time = if fps > 0.0 { (frame as f64 / fps as f64) as f32 } else { 0.0 };
+    }
+
+    /// Returns the time of the last keyframe on any track.
+    pub fn duration(&self) -> f32 {
+        let floats = self.floats.values().map(Track::duration);
+        let points = self.points.values().map(Track::duration);
+        let colors = self.colors.values().map(Track::duration);
+        floats.chain(points).chain(colors).fold(0.0, f32::max)
+    }
+
+    /// Adds a keyframe for the property `name` at `time`. The `easing` shapes the segment
+    /// from this keyframe to the next one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::easing::Easing;
+    /// # use simple_sketch::geom::Point;
+    /// # use simple_sketch::timeline::Timeline;
+    /// let mut timeline = Timeline::new();
+    /// timeline.keyframe("radius", 0.0, 10.0, Easing::QuadInOut);
+    /// timeline.keyframe("radius", 2.0, 50.0, Easing::Linear);
+    /// timeline.keyframe("center", 0.0, Point::new(0.0, 0.0), Easing::Linear);
+    /// ```
+    pub fn keyframe<T: Animatable>(&mut self, name: &str, time: f32, value: T, easing: Easing) {
+        T::tracks_mut(self)
+            .entry(name.to_string())
+            .or_default()
+            .insert(Keyframe { time, value, easing });
+    }
+
+    /// Returns the track for the property `name`, if it has any keyframes of type `T`.
+    pub fn track<T: Animatable>(&self, name: &str) -> Option<&Track<T>> {
+        T::tracks(self).get(name)
+    }
+
+    /// Evaluates the property `name` at the current time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::easing::Easing;
+    /// # use simple_sketch::timeline::Timeline;
+    /// let mut timeline = Timeline::new();
+    /// timeline.keyframe("radius", 0.0, 10.0, Easing::Linear);
+    /// timeline.keyframe("radius", 2.0, 50.0, Easing::Linear);
+    /// timeline.set_time(1.0);
+    /// assert_eq!(timeline.value::<f32>("radius"), Some(30.0));
+    /// ```
+    pub fn value<T: Animatable>(&self, name: &str) -> Option<T> {
+        self.value_at(name, self.time)
+    }
+
+    /// Evaluates the property `name` at an explicit time.
+    pub fn value_at<T: Animatable>(&self, name: &str, time: f32) -> Option<T> {
+        self.track::<T>(name)?.value_at(time)
+    }
+}
+
+impl Animatable for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    fn tracks(timeline: &Timeline) -> &HashMap<String, Track<Self>> {
+        &timeline.floats
+    }
+
+    fn tracks_mut(timeline: &mut Timeline) -> &mut HashMap<String, Track<Self>> {
+        &mut timeline.floats
+    }
+
+    fn tweens(tweens: &Tweens) -> &HashMap<String, Tween<Self>> {
+        &tweens.floats
+    }
+
+    fn tweens_mut(tweens: &mut Tweens) -> &mut HashMap<String, Tween<Self>> {
+        &mut tweens.floats
+    }
+}
+
+impl Animatable for Point {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+
+    fn tracks(timeline: &Timeline) -> &HashMap<String, Track<Self>> {
+        &timeline.points
+    }
+
+    fn tracks_mut(timeline: &mut Timeline) -> &mut HashMap<String, Track<Self>> {
+        &mut timeline.points
+    }
+
+    fn tweens(tweens: &Tweens) -> &HashMap<String, Tween<Self>> {
+        &tweens.points
+    }
+
+    fn tweens_mut(tweens: &mut Tweens) -> &mut HashMap<String, Tween<Self>> {
+        &mut tweens.points
+    }
+}
+
+impl Animatable for Color {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+
+    fn tracks(timeline: &Timeline) -> &HashMap<String, Track<Self>> {
+        &timeline.colors
+    }
+
+    fn tracks_mut(timeline: &mut Timeline) -> &mut HashMap<String, Track<Self>> {
+        &mut timeline.colors
+    }
+
+    fn tweens(tweens: &Tweens) -> &HashMap<String, Tween<Self>> {
+        &tweens.colors
+    }
+
+    fn tweens_mut(tweens: &mut Tweens) -> &mut HashMap<String, Tween<Self>> {
+        &mut tweens.colors
+    }
+}
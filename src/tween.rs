@@ -0,0 +1,397 @@
+//! Tweens: values animated from a start through a sequence of targets over time, with easing,
+//! pauses, repeats and callbacks, for choreographed motion without a state machine.
+//!
+//! A `Tween` is a small script for one value. It can be stepped by hand with `advance`, or
+//! started under a name on the sketch's `Tweens`, which the runner steps every frame with the
+//! sketch's clock, so tweens stand still while the sketch is paused:
+//!
+//! ```
+//! # use simple_sketch::app::Context;
+//! # use simple_sketch::canvas::Canvas;
+//! # use simple_sketch::easing::Easing;
+//! # use simple_sketch::geom::Point;
+//! # use simple_sketch::input::MouseButton;
+//! # struct Sketch { was_pressed: bool }
+//! # impl Sketch {
+//! fn update(&mut self, ctx: &Context, _dt: f32) {
+//!     // Glide from wherever the dot is to every click
+//!     let pressed = ctx.mouse().is_pressed(MouseButton::Left);
+//!     if pressed && !self.was_pressed {
+//!         ctx.tweens().to("dot", ctx.mouse().position(), 0.6, Easing::CubicOut);
+//!     }
+//!     self.was_pressed = pressed;
+//! }
+//!
+//! fn draw(&mut self, canvas: &mut Canvas, ctx: &Context) {
+//!     let dot = ctx.tweens().value("dot").unwrap_or(Point::new(0.0, 0.0));
+//!     canvas.circle(dot, 10.0);
+//! }
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::color::Color;
+use crate::easing::Easing;
+use crate::geom::Point;
+use crate::timeline::Animatable;
+
+type Callback = Arc<dyn Fn() + Send + Sync>;
+
+/// How many times a tween plays its sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Repeat {
+    /// Plays the sequence this many times in total, at least once.
+    Times(u32),
+    /// Plays the sequence until the tween is stopped.
+    Forever,
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Repeat::Times(1)
+    }
+}
+
+#[derive(Clone)]
+enum Step<T> {
+    To { value: T, duration: f32, easing: Easing },
+    Wait(f32),
+    Call(Callback),
+}
+
+impl<T> Step<T> {
+    fn duration(&self) -> f32 {
+        match *self {
+            Step::To { duration, .. } | Step::Wait(duration) => duration,
+            Step::Call(_) => 0.0,
+        }
+    }
+}
+
+/// A value animated from a start value through a sequence of targets, each reached over its
+/// own duration with its own easing.
+///
+/// The sequence can pause with `wait` and call functions with `call` as it plays, and can
+/// start after a `delay` and repeat, playing backwards every other time with `yoyo`.
+///
+/// # Examples
+///
+/// ```
+/// # use simple_sketch::canvas::Canvas;
+/// # use simple_sketch::easing::Easing;
+/// # use simple_sketch::geom::Point;
+/// # use simple_sketch::tween::Tween;
+/// # fn sketch(canvas: &mut Canvas, dt: f32) {
+/// // Drop in after half a second, hold and bounce once
+/// let mut y = Tween::new(-50.0)
+///     .to(200.0, 0.8, Easing::QuadIn)
+///     .wait(1.0)
+///     .to(150.0, 0.2, Easing::QuadOut)
+///     .to(200.0, 0.2, Easing::QuadIn)
+///     .delay(0.5)
+///     .on_complete(|| println!("landed"));
+/// // In update
+/// y.advance(dt);
+/// // In draw
+/// canvas.circle(Point::new(100.0, y.value()), 20.0);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Tween<T> {
+    start: T,
+    steps: Vec<Step<T>>,
+    delay: f32,
+    repeat: Repeat,
+    yoyo: bool,
+    on_complete: Option<Callback>,
+    /// The part of the delay still to wait, in seconds.
+    delay_left: f32,
+    /// How many times the sequence has been played to the end.
+    plays: u32,
+    /// The time into the current play of the sequence, in seconds.
+    play_time: f32,
+    finished: bool,
+}
+
+impl<T: Animatable> Tween<T> {
+    /// Creates a tween that holds `start` until targets are added with `to`.
+    pub fn new(start: T) -> Self {
+        Tween {
+            start,
+            steps: Vec::new(),
+            delay: 0.0,
+            repeat: Repeat::default(),
+            yoyo: false,
+            on_complete: None,
+            delay_left: 0.0,
+            plays: 0,
+            play_time: 0.0,
+            finished: false,
+        }
+    }
+
+    /// Creates a tween from `from` to `to` over `duration` seconds.
+    pub fn between(from: T, to: T, duration: f32, easing: Easing) -> Self {
+        Tween::new(from).to(to, duration, easing)
+    }
+
+    /// Adds a step that moves the value from where the previous step left it to `value` over
+    /// `duration` seconds.
+    pub fn to(mut self, value: T, duration: f32, easing: Easing) -> Self {
+        self.steps.push(Step::To { value, duration: duration.max(0.0), easing });
+        self
+    }
+
+    /// Adds a step that holds the value for `seconds`.
+    pub fn wait(mut self, seconds: f32) -> Self {
+        self.steps.push(Step::Wait(seconds.max(0.0)));
+        self
+    }
+
+    /// Adds a step that calls `callback` when the sequence reaches it, every time it plays.
+    pub fn call(mut self, callback: impl Fn() + Send + Sync + 'static) -> Self {
+        self.steps.push(Step::Call(Arc::new(callback)));
+        self
+    }
+
+    /// Waits `seconds` before playing the sequence the first time.
+    pub fn delay(mut self, seconds: f32) -> Self {
+        self.delay = seconds.max(0.0);
+        self.delay_left = self.delay;
+        self
+    }
+
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Plays the sequence backwards every other time it repeats, so it swings back and forth.
+    pub fn yoyo(mut self, yoyo: bool) -> Self {
+        self.yoyo = yoyo;
+        self
+    }
+
+    /// Calls `callback` once the tween has played the sequence for the last time.
+    pub fn on_complete(mut self, callback: impl Fn() + Send + Sync + 'static) -> Self {
+        self.on_complete = Some(Arc::new(callback));
+        self
+    }
+
+    /// The length of one play of the sequence in seconds, without the delay.
+    pub fn duration(&self) -> f32 {
+        self.steps.iter().map(Step::duration).sum()
+    }
+
+    /// The length of all plays of the sequence including the delay, or None if it repeats
+    /// forever.
+    pub fn total_duration(&self) -> Option<f32> {
+        match self.repeat {
+            Repeat::Times(times) => Some(self.delay + self.duration() * times.max(1) as f32),
+            Repeat::Forever => None,
+        }
+    }
+
+    /// Whether the tween has played the sequence for the last time. It then holds its last
+    /// value.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Starts the tween over, including its delay.
+    pub fn restart(&mut self) {
+        self.delay_left = self.delay;
+        self.plays = 0;
+        self.play_time = 0.0;
+        self.finished = false;
+    }
+
+    /// The value at the current time.
+    pub fn value(&self) -> T {
+        let backwards = self.yoyo && self.plays % 2 == 1;
+        let time = if backwards { self.duration() - self.play_time } else { self.play_time };
+        let mut value = self.start;
+        let mut step_start = 0.0;
+        for step in &self.steps {
+            let Step::To { value: target, duration, easing } = *step else {
+                step_start += step.duration();
+                if time < step_start {
+                    return value;
+                }
+                continue;
+            };
+            if time < step_start + duration {
+                return value.interpolate(&target, easing.apply((time - step_start) / duration));
+            }
+            value = target;
+            step_start += duration;
+        }
+        value
+    }
+
+    /// Moves the tween `dt` seconds forward, calling the callbacks of the steps it passes and
+    /// `on_complete` when it finishes.
+    pub fn advance(&mut self, dt: f32) {
+        if self.finished {
+            return;
+        }
+        let mut dt = dt.max(0.0);
+        if self.delay_left > 0.0 {
+            let waited = dt.min(self.delay_left);
+            self.delay_left -= waited;
+            dt -= waited;
+            if self.delay_left > 0.0 {
+                return;
+            }
+        }
+        let duration = self.duration();
+        loop {
+            let end = self.play_time + dt;
+            if end < duration {
+                self.call_steps(self.play_time, end, false);
+                self.play_time = end;
+                return;
+            }
+            self.call_steps(self.play_time, duration, true);
+            dt = end - duration;
+            self.plays += 1;
+            let last = match self.repeat {
+                Repeat::Times(times) => self.plays >= times.max(1),
+                Repeat::Forever => false,
+            };
+            if last {
+                // Hold the end of the last play, which is the start for backwards plays
+                self.plays -= 1;
+                self.play_time = duration;
+                self.finished = true;
+                if let Some(callback) = &self.on_complete {
+                    callback();
+                }
+                return;
+            }
+            self.play_time = 0.0;
+            // A sequence without length repeats once per step rather than endlessly
+            if duration <= 0.0 {
+                return;
+            }
+        }
+    }
+
+    // Calls the callbacks of the current play whose place in time is from `from` up to `to`,
+    // including `to` at the end of the play
+    fn call_steps(&self, from: f32, to: f32, to_end: bool) {
+        let duration = self.duration();
+        let backwards = self.yoyo && self.plays % 2 == 1;
+        let mut calls = Vec::new();
+        let mut time = 0.0;
+        for step in &self.steps {
+            if let Step::Call(callback) = step {
+                calls.push((if backwards { duration - time } else { time }, callback));
+            }
+            time += step.duration();
+        }
+        if backwards {
+            calls.reverse();
+        }
+        for (time, callback) in calls {
+            if time >= from && (time < to || to_end) {
+                callback();
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Tween<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tween")
+            .field("start", &self.start)
+            .field("steps", &self.steps.len())
+            .field("delay", &self.delay)
+            .field("repeat", &self.repeat)
+            .field("yoyo", &self.yoyo)
+            .field("plays", &self.plays)
+            .field("play_time", &self.play_time)
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+/// Named tweens that are stepped together, like the sketch's tweens in `Context::tweens`.
+/// Finished tweens stay and hold their last value until they are stopped or replaced.
+#[derive(Debug, Clone, Default)]
+pub struct Tweens {
+    pub(crate) floats: HashMap<String, Tween<f32>>,
+    pub(crate) points: HashMap<String, Tween<Point>>,
+    pub(crate) colors: HashMap<String, Tween<Color>>,
+}
+
+impl Tweens {
+    pub fn new() -> Self {
+        Tweens::default()
+    }
+
+    /// Starts `tween` under `name`, replacing any tween of the same type and name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use simple_sketch::app::Context;
+    /// # use simple_sketch::easing::Easing;
+    /// # use simple_sketch::tween::{Repeat, Tween};
+    /// # fn start(ctx: &Context) {
+    /// let pulse = Tween::between(10.0, 30.0, 0.5, Easing::SineInOut).yoyo(true).repeat(Repeat::Forever);
+    /// ctx.tweens().start("radius", pulse);
+    /// # }
+    /// ```
+    pub fn start<T: Animatable>(&mut self, name: &str, tween: Tween<T>) {
+        T::tweens_mut(self).insert(name.to_string(), tween);
+    }
+
+    /// Starts a tween under `name` from its current value to `target`, or jumps to `target`
+    /// if there is no tween of that type and name yet.
+    pub fn to<T: Animatable>(&mut self, name: &str, target: T, duration: f32, easing: Easing) {
+        let start = self.value(name).unwrap_or(target);
+        self.start(name, Tween::between(start, target, duration, easing));
+    }
+
+    pub fn get<T: Animatable>(&self, name: &str) -> Option<&Tween<T>> {
+        T::tweens(self).get(name)
+    }
+
+    pub fn get_mut<T: Animatable>(&mut self, name: &str) -> Option<&mut Tween<T>> {
+        T::tweens_mut(self).get_mut(name)
+    }
+
+    /// The current value of the tween `name`, if there is one of type `T`.
+    pub fn value<T: Animatable>(&self, name: &str) -> Option<T> {
+        self.get::<T>(name).map(Tween::value)
+    }
+
+    /// Whether the tween `name` of type `T` is still playing.
+    pub fn is_playing<T: Animatable>(&self, name: &str) -> bool {
+        self.get::<T>(name).is_some_and(|tween| !tween.is_finished())
+    }
+
+    /// Removes the tween `name` of type `T`, returning it.
+    pub fn stop<T: Animatable>(&mut self, name: &str) -> Option<Tween<T>> {
+        T::tweens_mut(self).remove(name)
+    }
+
+    /// Removes all tweens.
+    pub fn clear(&mut self) {
+        self.floats.clear();
+        self.points.clear();
+        self.colors.clear();
+    }
+
+    /// Moves all tweens `dt` seconds forward.
+    pub fn advance(&mut self, dt: f32) {
+        self.floats.values_mut().for_each(|tween| tween.advance(dt));
+        self.points.values_mut().for_each(|tween| tween.advance(dt));
+        self.colors.values_mut().for_each(|tween| tween.advance(dt));
+    }
+}
@@ -0,0 +1,389 @@
+//! Running sketches in a web page, when compiled to `wasm32-unknown-unknown` with the `web`
+//! feature.
+//!
+//! The sketch draws into an HTML canvas on the page, from the browser's animation frames rather
+//! than a loop of its own, and gets the mouse, wheel and keyboard events of the canvas:
+//!
+//! ```
+//! #[wasm_bindgen(start)]
+//! pub fn start() -> Result<(), JsValue> {
+//!     web::run::<MySketch>("sketch").map_err(|error| JsValue::from_str(&error.to_string()))
+//! }
+//! ```
+//!
+//! with `<canvas id="sketch"></canvas>` on the page, and the crate built with `wasm-pack build
+//! --target web -- --features web`. The canvas element gets the size of the sketch's `Settings`,
+//! and as many pixels as its pixel density asks for.
+//!
+//! Browsers don't give pages the paths of dropped files, and files and processes aren't
+//! available, so screenshots, recordings, audio and serial ports fail with an error.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{Clamped, JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, Event, HtmlCanvasElement, ImageData, KeyboardEvent, MouseEvent, WheelEvent};
+
+use crate::app::{Application, ApplicationRunner, Settings};
+use crate::backend::{Backend, FrameRegion, InputState};
+use crate::cursor::CursorStyle;
+use crate::error::SketchError;
+use crate::geom::Point;
+use crate::input::{Key, MouseButton};
+
+/// Pixels scrolled by the browser for one notch of a typical mouse wheel.
+const PIXELS_PER_NOTCH: f64 = 100.0;
+
+/// Lines scrolled by the browser for one notch of a typical mouse wheel.
+const LINES_PER_NOTCH: f64 = 3.0;
+
+/// Creates a sketch with `Default` and runs it in the HTML canvas with the id `canvas_id`,
+/// until Escape is pressed while the canvas has the focus. Returns once the first frame is
+/// scheduled; the browser draws the others.
+pub fn run<A: Application + Default + 'static>(canvas_id: &str) -> Result<(), SketchError> {
+    run_app(A::default(), canvas_id)
+}
+
+/// Like `run`, with a sketch created in another way than with `Default`.
+pub fn run_app<A: Application + 'static>(app: A, canvas_id: &str) -> Result<(), SketchError> {
+    let settings = app.settings();
+    let backend = WebBackend::new(canvas_id, &settings)?;
+    let mut runner = ApplicationRunner::with_backend(app, backend);
+    let window = web_sys::window().ok_or_else(|| web_error("there is no browser window"))?;
+    let performance = window.performance().ok_or_else(|| web_error("the browser has no performance clock"))?;
+    let start = performance.now();
+    // Animation frames come at the display's refresh rate, skip those that come too early
+    let frame_time = 1000.0 / settings.frame_rate.max(1.0) as f64;
+    let mut last_frame: Option<f64> = None;
+
+    // The callback schedules itself for the next frame, so it owns a reference to itself
+    let callback = Rc::new(RefCell::new(None::<Closure<dyn FnMut(f64)>>));
+    let scheduled = Rc::clone(&callback);
+    *callback.borrow_mut() = Some(Closure::new(move |time: f64| {
+        let due = last_frame.is_none_or(|last| time - last >= frame_time - 1.0);
+        let keep_running = if due {
+            last_frame = Some(time);
+            match runner.show_frame(Duration::from_secs_f64((time - start).max(0.0) / 1000.0)) {
+                Ok(running) => running,
+                Err(error) => {
+                    web_sys::console::error_1(&JsValue::from_str(&error.to_string()));
+                    false
+                }
+            }
+        } else {
+            true
+        };
+        if !keep_running {
+            if let Err(error) = runner.exit() {
+                web_sys::console::error_1(&JsValue::from_str(&error.to_string()));
+            }
+            return;
+        }
+        if let (Some(window), Some(next)) = (web_sys::window(), scheduled.borrow().as_ref()) {
+            let _ = window.request_animation_frame(next.as_ref().unchecked_ref());
+        }
+    }));
+    if let Some(first) = callback.borrow().as_ref() {
+        window.request_animation_frame(first.as_ref().unchecked_ref()).map_err(js_error)?;
+    }
+    Ok(())
+}
+
+/// A backend drawing frames into an HTML canvas and reading its input events.
+pub struct WebBackend {
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    /// The size of the sketch in CSS pixels, which are its window pixels.
+    size: (usize, usize),
+    input: Rc<RefCell<WebInput>>,
+    /// The RGBA bytes of the last region drawn, kept to reuse their allocation.
+    bytes: Vec<u8>,
+    /// Removes the event listeners from the canvas when dropped.
+    _listeners: Vec<Listener>,
+}
+
+// The state of the input devices, updated by the event listeners
+#[derive(Default)]
+struct WebInput {
+    mouse_position: Option<Point>,
+    mouse_pressed: [bool; 3],
+    /// How far the wheel turned since the previous sample, if it did.
+    wheel: Option<Point>,
+    keys: Vec<Key>,
+}
+
+// An event listener added to the canvas
+struct Listener {
+    canvas: HtmlCanvasElement,
+    event: &'static str,
+    closure: Closure<dyn FnMut(Event)>,
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let _ = self.canvas.remove_event_listener_with_callback(self.event, self.closure.as_ref().unchecked_ref());
+    }
+}
+
+impl WebBackend {
+    /// Draws into the HTML canvas with the id `canvas_id`, sized for a sketch configured by
+    /// `settings`. Fails if there is no such canvas on the page.
+    pub fn new(canvas_id: &str, settings: &Settings) -> Result<Self, SketchError> {
+        if settings.width == 0 || settings.height == 0 {
+            return Err(SketchError::InvalidSize { width: settings.width, height: settings.height });
+        }
+        let document = web_sys::window().and_then(|window| window.document()).ok_or_else(|| web_error("there is no document"))?;
+        let canvas = document
+            .get_element_by_id(canvas_id)
+            .and_then(|element| element.dyn_into::<HtmlCanvasElement>().ok())
+            .ok_or_else(|| web_error(&format!("there is no canvas with the id {canvas_id:?}")))?;
+        let context = canvas
+            .get_context("2d")
+            .map_err(js_error)?
+            .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+            .ok_or_else(|| web_error("the canvas can't be drawn on in 2D"))?;
+
+        // The element takes the sketch's size on the page, with as many pixels as its density
+        canvas.set_width((settings.width as f32 * settings.pixel_density).round() as u32);
+        canvas.set_height((settings.height as f32 * settings.pixel_density).round() as u32);
+        let style = canvas.style();
+        style.set_property("width", &format!("{}px", settings.width)).map_err(js_error)?;
+        style.set_property("height", &format!("{}px", settings.height)).map_err(js_error)?;
+        // Canvases with a tab index can have the focus, and so get key events
+        canvas.set_tab_index(0);
+
+        let input = Rc::new(RefCell::new(WebInput::default()));
+        let mut backend = WebBackend { canvas, context, size: (settings.width, settings.height), input, bytes: Vec::new(), _listeners: Vec::new() };
+        backend.listen_to_mouse()?;
+        backend.listen_to_keys()?;
+        Ok(backend)
+    }
+
+    // Follows the mouse and its buttons and wheel over the canvas
+    fn listen_to_mouse(&mut self) -> Result<(), SketchError> {
+        let size = self.size;
+        let canvas = self.canvas.clone();
+        // The position in window pixels, also when the page stretches the canvas
+        let position = move |event: &MouseEvent| {
+            let scale = Point::new(size.0 as f32 / canvas.client_width().max(1) as f32, size.1 as f32 / canvas.client_height().max(1) as f32);
+            Point::new(event.offset_x() as f32 * scale.x, event.offset_y() as f32 * scale.y)
+        };
+        let moved = position.clone();
+        self.listen("mousemove", move |input, event| {
+            if let Some(event) = event.dyn_ref::<MouseEvent>() {
+                input.mouse_position = Some(moved(event));
+            }
+        })?;
+        let canvas = self.canvas.clone();
+        let pressed = position.clone();
+        self.listen("mousedown", move |input, event| {
+            if let Some(event) = event.dyn_ref::<MouseEvent>() {
+                let _ = canvas.focus();
+                input.mouse_position = Some(pressed(event));
+                if let Some(button) = mouse_button(event.button()) {
+                    input.mouse_pressed[button.index()] = true;
+                }
+            }
+        })?;
+        self.listen("mouseup", move |input, event| {
+            if let Some(event) = event.dyn_ref::<MouseEvent>() {
+                input.mouse_position = Some(position(event));
+                if let Some(button) = mouse_button(event.button()) {
+                    input.mouse_pressed[button.index()] = false;
+                }
+            }
+        })?;
+        self.listen("mouseleave", |input, _| {
+            input.mouse_position = None;
+            input.mouse_pressed = [false; 3];
+        })?;
+        self.listen("contextmenu", |_, event| event.prevent_default())?;
+        self.listen("wheel", |input, event| {
+            if let Some(event) = event.dyn_ref::<WheelEvent>() {
+                // The page doesn't scroll while the wheel turns over the sketch
+                event.prevent_default();
+                let notch = match event.delta_mode() {
+                    WheelEvent::DOM_DELTA_PIXEL => PIXELS_PER_NOTCH,
+                    WheelEvent::DOM_DELTA_LINE => LINES_PER_NOTCH,
+                    _ => 1.0,
+                };
+                // Browsers count scrolling down as positive
+                let turned = Point::new((-event.delta_x() / notch) as f32, (-event.delta_y() / notch) as f32);
+                input.wheel = Some(input.wheel.map_or(turned, |wheel| wheel + turned));
+            }
+        })
+    }
+
+    // Follows the keys held down while the canvas has the focus
+    fn listen_to_keys(&mut self) -> Result<(), SketchError> {
+        self.listen("keydown", |input, event| {
+            if let Some(key) = event.dyn_ref::<KeyboardEvent>().and_then(|event| key(&event.code())) {
+                // Keys like Space and the arrows would scroll the page
+                event.prevent_default();
+                if !input.keys.contains(&key) {
+                    input.keys.push(key);
+                }
+            }
+        })?;
+        self.listen("keyup", |input, event| {
+            if let Some(key) = event.dyn_ref::<KeyboardEvent>().and_then(|event| key(&event.code())) {
+                input.keys.retain(|held| *held != key);
+            }
+        })?;
+        // Keys released after the canvas lost the focus never report it
+        self.listen("blur", |input, _| input.keys.clear())
+    }
+
+    fn listen(&mut self, event: &'static str, mut handle: impl FnMut(&mut WebInput, &Event) + 'static) -> Result<(), SketchError> {
+        let input = Rc::clone(&self.input);
+        let closure = Closure::<dyn FnMut(Event)>::new(move |event: Event| handle(&mut input.borrow_mut(), &event));
+        self.canvas.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref()).map_err(js_error)?;
+        self._listeners.push(Listener { canvas: self.canvas.clone(), event, closure });
+        Ok(())
+    }
+
+    // Draws the part of a frame within `region`
+    fn draw(&mut self, pixels: &[u32], width: usize, height: usize, region: FrameRegion) -> Result<(), SketchError> {
+        if (self.canvas.width() as usize, self.canvas.height() as usize) != (width, height) {
+            self.canvas.set_width(width as u32);
+            self.canvas.set_height(height as u32);
+        }
+        if region.width == 0 || region.height == 0 {
+            return Ok(());
+        }
+        self.bytes.clear();
+        for row in pixels.chunks(width).skip(region.y).take(region.height) {
+            for &pixel in &row[region.x..region.x + region.width] {
+                self.bytes.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8, 0xFF]);
+            }
+        }
+        let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&self.bytes), region.width as u32, region.height as u32).map_err(js_error)?;
+        self.context.put_image_data(&image, region.x as f64, region.y as f64).map_err(js_error)
+    }
+}
+
+impl Backend for WebBackend {
+    /// Always true: the sketch runs for as long as the page is open.
+    fn is_open(&self) -> bool {
+        true
+    }
+
+    fn input(&mut self) -> InputState {
+        let mut input = self.input.borrow_mut();
+        InputState {
+            size: self.size,
+            mouse_position: input.mouse_position,
+            mouse_pressed: input.mouse_pressed,
+            wheel: input.wheel.take(),
+            keys: input.keys.clone(),
+            dropped_files: Vec::new(),
+        }
+    }
+
+    fn present(&mut self, pixels: &[u32], width: usize, height: usize) -> Result<(), SketchError> {
+        self.draw(pixels, width, height, FrameRegion { x: 0, y: 0, width, height })
+    }
+
+    fn present_changed(&mut self, pixels: &[u32], width: usize, height: usize, changed: FrameRegion) -> Result<(), SketchError> {
+        self.draw(pixels, width, height, changed)
+    }
+
+    /// Does nothing: the browser handles the page's events between frames.
+    fn poll(&mut self) -> Result<(), SketchError> {
+        Ok(())
+    }
+
+    fn set_cursor(&mut self, visible: bool, style: CursorStyle) {
+        let cursor = match style {
+            _ if !visible => "none",
+            CursorStyle::Arrow => "default",
+            CursorStyle::Text => "text",
+            CursorStyle::Crosshair => "crosshair",
+            CursorStyle::OpenHand => "grab",
+            CursorStyle::ClosedHand => "grabbing",
+            CursorStyle::ResizeHorizontal => "ew-resize",
+            CursorStyle::ResizeVertical => "ns-resize",
+            CursorStyle::Move => "move",
+        };
+        let _ = self.canvas.style().set_property("cursor", cursor);
+    }
+}
+
+// The button of a mouse event, numbered as by the browser
+fn mouse_button(button: i16) -> Option<MouseButton> {
+    match button {
+        0 => Some(MouseButton::Left),
+        1 => Some(MouseButton::Middle),
+        2 => Some(MouseButton::Right),
+        _ => None,
+    }
+}
+
+// The key with the `code` of a keyboard event, which names the key's place on the keyboard
+// whatever the layout
+fn key(code: &str) -> Option<Key> {
+    const LETTERS: [Key; 26] = [
+        Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+        Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    ];
+    const DIGITS: [Key; 10] = [Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9];
+    const FUNCTION_KEYS: [Key; 12] = [Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9, Key::F10, Key::F11, Key::F12];
+    let single = |suffix: &str| (suffix.len() == 1).then(|| suffix.as_bytes()[0]);
+    if let Some(letter) = code.strip_prefix("Key").and_then(single).filter(u8::is_ascii_uppercase) {
+        return Some(LETTERS[(letter - b'A') as usize]);
+    }
+    if let Some(digit) = code.strip_prefix("Digit").and_then(single).filter(u8::is_ascii_digit) {
+        return Some(DIGITS[(digit - b'0') as usize]);
+    }
+    if let Some(number) = code.strip_prefix('F').and_then(|number| number.parse::<usize>().ok()) {
+        return FUNCTION_KEYS.get(number.wrapping_sub(1)).copied();
+    }
+    Some(match code {
+        "ArrowUp" => Key::Up,
+        "ArrowDown" => Key::Down,
+        "ArrowLeft" => Key::Left,
+        "ArrowRight" => Key::Right,
+        "Space" => Key::Space,
+        "Enter" => Key::Enter,
+        "Escape" => Key::Escape,
+        "Backspace" => Key::Backspace,
+        "Tab" => Key::Tab,
+        "Delete" => Key::Delete,
+        "Insert" => Key::Insert,
+        "Home" => Key::Home,
+        "End" => Key::End,
+        "PageUp" => Key::PageUp,
+        "PageDown" => Key::PageDown,
+        "ShiftLeft" => Key::LeftShift,
+        "ShiftRight" => Key::RightShift,
+        "ControlLeft" => Key::LeftCtrl,
+        "ControlRight" => Key::RightCtrl,
+        "AltLeft" => Key::LeftAlt,
+        "AltRight" => Key::RightAlt,
+        "MetaLeft" => Key::LeftSuper,
+        "MetaRight" => Key::RightSuper,
+        "Quote" => Key::Apostrophe,
+        "Backquote" => Key::Backquote,
+        "Backslash" => Key::Backslash,
+        "Comma" => Key::Comma,
+        "Equal" => Key::Equal,
+        "BracketLeft" => Key::LeftBracket,
+        "BracketRight" => Key::RightBracket,
+        "Minus" => Key::Minus,
+        "Period" => Key::Period,
+        "Semicolon" => Key::Semicolon,
+        "Slash" => Key::Slash,
+        _ => return None,
+    })
+}
+
+fn web_error(reason: &str) -> SketchError {
+    SketchError::WindowCreation(reason.to_string())
+}
+
+// Describes an exception thrown by the browser
+fn js_error(error: JsValue) -> SketchError {
+    SketchError::WindowUpdate(error.as_string().unwrap_or_else(|| format!("{error:?}")))
+}
@@ -0,0 +1,39 @@
+//! Geometry predicates near their tolerances, where rounding decides the result.
+
+use simple_sketch::geom::{orientation, Line, Point, Winding};
+
+#[test]
+fn near_collinear_points_on_axis_aligned_segments_are_collinear() {
+    let (a, b) = (Point::new(0.0, 0.0), Point::new(1.0, 0.0));
+    assert_eq!(orientation(&a, &b, &Point::new(0.3, 1e-9)), None);
+    assert_eq!(orientation(&a, &b, &Point::new(0.3, -1e-9)), None);
+    let (a, b) = (Point::new(5.0, 0.0), Point::new(5.0, 100.0));
+    assert_eq!(orientation(&a, &b, &Point::new(5.0 + 1e-6, 50.0)), None);
+    assert!(Line::new(a, b).contains_point(&Point::new(5.0 + 1e-6, 50.0)));
+}
+
+#[test]
+fn near_collinear_points_on_diagonal_segments_are_collinear() {
+    let (a, b) = (Point::new(0.0, 0.0), Point::new(1000.0, 1000.0));
+    assert_eq!(orientation(&a, &b, &Point::new(500.0, 500.0001)), None);
+    assert_eq!(orientation(&a, &b, &Point::new(0.1, 0.1)), None);
+    let (a, b) = (Point::new(0.1, 0.2), Point::new(0.7, 1.4));
+    assert_eq!(orientation(&a, &b, &Point::new(0.4, 0.8)), None);
+    assert!(Line::new(a, b).contains_point(&Point::new(0.4, 0.8)));
+}
+
+#[test]
+fn points_clearly_off_a_segment_have_an_orientation() {
+    let (a, b) = (Point::new(0.0, 0.0), Point::new(1.0, 0.0));
+    assert_eq!(orientation(&a, &b, &Point::new(0.3, 0.01)), Some(Winding::Clockwise));
+    assert_eq!(orientation(&a, &b, &Point::new(0.3, -0.01)), Some(Winding::CounterClockwise));
+    let (a, b) = (Point::new(0.0, 0.0), Point::new(1000.0, 1000.0));
+    assert_eq!(orientation(&a, &b, &Point::new(500.0, 501.0)), Some(Winding::Clockwise));
+}
+
+#[test]
+fn nearly_parallel_segments_are_parallel() {
+    let horizontal = Line::new(Point::new(0.0, 0.0), Point::new(10.0, 0.0));
+    assert!(horizontal.is_parallel(&Line::new(Point::new(0.0, 5.0), Point::new(10.0, 5.0 + 1e-6))));
+    assert!(!horizontal.is_parallel(&Line::new(Point::new(0.0, 5.0), Point::new(10.0, 5.1))));
+}
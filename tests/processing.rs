@@ -0,0 +1,71 @@
+//! Golden renders of the Processing ports in `examples/processing`.
+//!
+//! Every port is rendered headlessly with the scripted mouse, and a hash of the final frame
+//! is compared to the one recorded in `tests/golden/processing.txt`. Ports without a
+//! recorded hash fail like mismatches; set `UPDATE_GOLDEN=1` to record new ports and re-record
+//! all of them after an intended change to the output.
+
+#[path = "../examples/processing/sketches/mod.rs"]
+#[allow(dead_code)]
+mod sketches;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::snapshot::hash;
+
+const FRAMES: u32 = 30;
+const GOLDEN_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/processing.txt");
+
+fn render(port: &sketches::Port) -> u64 {
+    let mut canvas = Canvas::new(sketches::WIDTH, sketches::HEIGHT);
+    let mut example = (port.create)(&mut canvas);
+    for frame in 0..FRAMES {
+        example.draw(&mut canvas, &sketches::scripted_input(frame));
+        canvas.flush();
+    }
+    hash(canvas.pixel_buffer.get_buffer())
+}
+
+fn read_golden() -> BTreeMap<String, u64> {
+    let contents = fs::read_to_string(GOLDEN_PATH).unwrap_or_default();
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (name, hash) = line.split_once(' ')?;
+            Some((name.to_string(), u64::from_str_radix(hash.trim(), 16).ok()?))
+        })
+        .collect()
+}
+
+fn write_golden(golden: &BTreeMap<String, u64>) {
+    let contents: String = golden.iter().map(|(name, hash)| format!("{name} {hash:016x}\n")).collect();
+    fs::create_dir_all(std::path::Path::new(GOLDEN_PATH).parent().unwrap()).unwrap();
+    fs::write(GOLDEN_PATH, contents).unwrap();
+}
+
+#[test]
+fn processing_ports_match_golden_renders() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut golden = read_golden();
+    let mut mismatches = Vec::new();
+
+    for port in sketches::PORTS {
+        let actual = render(port);
+        match golden.get(port.name) {
+            Some(&expected) if expected == actual => {}
+            _ if update => {
+                golden.insert(port.name.to_string(), actual);
+            }
+            Some(&expected) => mismatches.push(format!("{}: expected {expected:016x}, got {actual:016x}", port.name)),
+            None => mismatches.push(format!("{}: no recorded hash, got {actual:016x}", port.name)),
+        }
+    }
+
+    if update {
+        golden.retain(|name, _| sketches::PORTS.iter().any(|port| port.name == name));
+        write_golden(&golden);
+    }
+    assert!(mismatches.is_empty(), "renders differ from {GOLDEN_PATH}, set UPDATE_GOLDEN=1 to re-record:\n{}", mismatches.join("\n"));
+}
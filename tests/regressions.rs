@@ -0,0 +1,146 @@
+//! Regression cases for crashes and hangs found in the rasterizer, most of them by the fuzz
+//! targets in `fuzz/`, and in the rest of the API. Each case only has to finish without
+//! panicking or aborting.
+
+use std::time::{Duration, Instant};
+
+use simple_sketch::canvas::Canvas;
+use simple_sketch::color::Color;
+use simple_sketch::easing::Easing;
+use simple_sketch::geom::{Point, Polyline, Transform};
+use simple_sketch::ik::IkChain;
+use simple_sketch::instance::{CoverageMask, Instance};
+use simple_sketch::shape::{ArcMode, Ellipse, Outline, Shape};
+use simple_sketch::timeline::Timeline;
+
+// Shapes with a negative or zero height used to pass the mask cache's size limit and be
+// rasterized over their whole width, unclipped.
+#[test]
+fn huge_flat_shapes_are_clipped() {
+    let mut canvas = Canvas::new(64, 64);
+    canvas.set_fill(Some(Color::new(255, 0, 0, 255)));
+    let start = Instant::now();
+    canvas.ellipse(Point::new(32.0, 32.0), 1e8, -0.3);
+    canvas.ellipse(Point::new(32.0, 32.0), 1e10, -0.3);
+    canvas.rounded_rect(Point::new(0.0, 0.0), 8.5e9, 0.0, [0.0; 4]);
+    canvas.flush();
+    assert!(start.elapsed() < Duration::from_secs(1), "took {:?}", start.elapsed());
+}
+
+// Fixed point snapped edges far above or below the canvas to the largest integer, and the
+// heights between their ends overflowed, or wrapped and covered the wrong pixels.
+#[test]
+fn huge_shapes_are_clipped_in_fixed_point() {
+    let draw = |fixed_point: bool| {
+        let mut canvas = Canvas::new(100, 100);
+        canvas.set_fixed_point(fixed_point);
+        canvas.set_fill(Some(Color::new(255, 0, 0, 255)));
+        canvas.set_stroke(None);
+        canvas.rectangle(Point::new(10.0, -1.0e7), 20.0, 2.0e7);
+        canvas.set_stroke(Some(Color::new(0, 0, 255, 255)));
+        canvas.rectangle(Point::new(50.0, -1.0e7), 20.0, 2.0e7);
+        canvas.flush();
+        canvas.pixel_buffer.get_buffer().to_vec()
+    };
+    assert_eq!(draw(true), draw(false));
+}
+
+// The outline of a hugely wide stroke overlaps itself thousands of times, and its winding
+// overflowed the 32 bits fixed point added it up in.
+#[test]
+fn hugely_wide_strokes_are_drawn_in_fixed_point() {
+    let mut canvas = Canvas::new(256, 128);
+    canvas.set_fixed_point(true);
+    canvas.set_stroke(Some(Color::new(255, 255, 255, 255)));
+    canvas.set_stroke_weight(4.6e17);
+    canvas.circle(Point::new(0.0, 0.0), -2.7e7);
+    canvas.flush();
+}
+
+// The smallest scale of a coverage mask used to undo its size limit, so instancing a huge
+// shape tried to allocate a mask of its whole size and aborted.
+#[test]
+fn huge_instanced_shapes_are_skipped() {
+    let mut canvas = Canvas::new(64, 64);
+    let instance = Instance { transform: Transform::identity(), color: Color::new(255, 255, 255, 255) };
+    canvas.draw_instanced(&Ellipse { center: Point::new(0.0, 0.0), width: 1e15, height: 1e15 }, &[instance]);
+    canvas.draw_instanced(&Ellipse { center: Point::new(0.0, 0.0), width: 1e30, height: 3.0 }, &[instance]);
+}
+
+// Sampling a mask far outside it saturated the pixel index, and its neighbour overflowed.
+#[test]
+fn coverage_masks_are_empty_far_outside() {
+    let mask = CoverageMask::from_shape(&Ellipse { center: Point::new(0.0, 0.0), width: 10.0, height: 10.0 }, 1.0);
+    assert_eq!(mask.sample(Point::new(1e30, 1e30)), 0.0);
+    assert_eq!(mask.sample(Point::new(0.0, 1e30)), 0.0);
+}
+
+// The quarter turns of an arc starting at a huge angle saturate to the largest integer, and
+// counting three more overflowed.
+#[test]
+fn arcs_at_huge_angles_are_drawn() {
+    let mut canvas = Canvas::new(64, 64);
+    canvas.set_fill(Some(Color::new(255, 255, 255, 255)));
+    canvas.arc(Point::new(32.0, 32.0), 20.0, 1e20, 1e20 + 1.0, ArcMode::Pie);
+    canvas.flush();
+}
+
+// The mask cache only limited the area of the shape, so a small shape with a huge stroke
+// weight was rasterized into a mask as big as its stroke, unclipped.
+#[test]
+fn small_shapes_with_huge_strokes_are_clipped() {
+    let mut canvas = Canvas::new(64, 64);
+    canvas.set_fill(Some(Color::new(255, 0, 0, 255)));
+    canvas.set_stroke(Some(Color::new(0, 0, 255, 255)));
+    canvas.set_stroke_weight(1e5);
+    let start = Instant::now();
+    canvas.circle(Point::new(32.0, 32.0), 10.0);
+    canvas.rounded_rect(Point::new(20.0, 20.0), 10.0, 10.0, [2.0; 4]);
+    canvas.flush();
+    assert!(start.elapsed() < Duration::from_secs(1), "took {:?}", start.elapsed());
+}
+
+// The round caps of a line stroked hugely wide were flattened into billions of points, since
+// the angle of their segments rounded to 0.
+#[test]
+fn hugely_wide_lines_are_drawn_quickly() {
+    let mut canvas = Canvas::new(64, 64);
+    canvas.set_stroke(Some(Color::new(255, 255, 255, 255)));
+    canvas.set_stroke_weight(4.5e17);
+    let start = Instant::now();
+    canvas.line(Point::new(0.0, 0.0), Point::new(0.0, -2.7e7));
+    canvas.flush();
+    assert!(start.elapsed() < Duration::from_secs(1), "took {:?}", start.elapsed());
+}
+
+// Reversed or NaN limits reached `f32::clamp`, which panics on them.
+#[test]
+fn ik_chains_with_reversed_or_nan_constraints_reach() {
+    let mut arm = IkChain::new(Point::new(0.0, 0.0), vec![10.0, 10.0, 10.0], 0.0);
+    arm.set_constraint(1, Some((0.5, -0.5)));
+    arm.set_constraint(2, Some((f32::NAN, 0.5)));
+    assert_eq!(arm.constraint(1), Some((-0.5, 0.5)));
+    assert_eq!(arm.constraint(2), None);
+    arm.reach(Point::new(5.0, 15.0));
+}
+
+// A NaN time found no keyframe before it, and a frame rate of 0 seeked to the NaN time 0/0.
+#[test]
+fn timelines_hold_the_first_value_at_nan_times() {
+    let mut timeline = Timeline::new();
+    timeline.keyframe("x", 0.0, 1.0, Easing::Linear);
+    timeline.keyframe("x", 1.0, 2.0, Easing::Linear);
+    assert_eq!(timeline.value_at::<f32>("x", f32::NAN), Some(1.0));
+    timeline.seek_frame(0, 0.0);
+    assert_eq!(timeline.time(), 0.0);
+    assert_eq!(timeline.value::<f32>("x"), Some(1.0));
+}
+
+// The edges of an open polyline were counted as one less than its points, which underflowed
+// for one without points.
+#[test]
+fn outlines_with_empty_polylines_have_a_distance() {
+    let square = vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(4.0, 4.0), Point::new(0.0, 4.0)];
+    let outline = Outline { polylines: vec![Polyline { points: Vec::new(), closed: false }, Polyline { points: square, closed: true }] };
+    assert_eq!(outline.distance(Point::new(2.0, 6.0)), 2.0);
+}
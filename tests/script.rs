@@ -0,0 +1,51 @@
+//! Limits of the scripting language that keep scripts from crashing the sketch.
+
+#![cfg(feature = "script")]
+
+use simple_sketch::error::SketchError;
+use simple_sketch::script::Script;
+
+fn nesting_error(source: &str) -> String {
+    match Script::parse(source) {
+        Err(SketchError::Script(reason)) => reason,
+        Err(error) => panic!("expected a script error, got {error}"),
+        Ok(_) => panic!("expected the script to be rejected"),
+    }
+}
+
+#[test]
+fn deeply_nested_parentheses_are_rejected() {
+    let source = format!("let x = {}1{};", "(".repeat(5000), ")".repeat(5000));
+    assert!(nesting_error(&source).contains("too deeply nested"));
+}
+
+#[test]
+fn long_chains_of_unary_operators_are_rejected() {
+    let source = format!("let x = {}1;", "-".repeat(20000));
+    assert!(nesting_error(&source).contains("too deeply nested"));
+}
+
+#[test]
+fn deeply_nested_blocks_are_rejected() {
+    let source = format!("{}{}", "if true { ".repeat(5000), "}".repeat(5000));
+    assert!(nesting_error(&source).contains("too deeply nested"));
+    let source = format!("if false {{}}{} else {{}}", " else if false {}".repeat(5000));
+    assert!(nesting_error(&source).contains("too deeply nested"));
+}
+
+#[test]
+fn nesting_within_the_limit_runs() {
+    let source = format!("let x = {}1{};\nlet y = {}1;", "(".repeat(100), ")".repeat(100), "-".repeat(100));
+    let script = Script::parse(&source).unwrap();
+    assert_eq!(script.get("x").and_then(|x| x.as_f64()), Some(1.0));
+    assert_eq!(script.get("y").and_then(|y| y.as_f64()), Some(1.0));
+}
+
+// Sketches run scripts on the main thread, whose stack is larger than a test thread's
+#[test]
+fn recursing_through_deeply_nested_code_is_stopped() {
+    let source = format!("fn f(n) {{ if n > 0 {{ return {}f(n - 1){}; }} return 0; }}\nlet x = f(250);", "1 + (".repeat(100), ")".repeat(100));
+    let main_thread = std::thread::Builder::new().stack_size(8 << 20);
+    let result = main_thread.spawn(move || Script::parse(&source).map(|_| ()).map_err(|error| error.to_string())).unwrap().join().unwrap();
+    assert!(result.unwrap_err().contains("nest too deeply"));
+}